@@ -35,6 +35,52 @@ mod m20260116_000002_create_itps;
 mod m20260117_000001_create_sync_status;
 mod m20260126_000001_add_admin_address_to_itps;
 mod m20260126_000002_create_operations;
+mod m20260127_000001_create_category_change_events;
+mod m20260128_000001_add_deactivated_to_coins;
+mod m20260129_000001_create_job_failures;
+mod m20260130_000001_create_background_tasks;
+mod m20260130_000002_add_progress_to_background_tasks;
+mod m20260131_000001_add_raw_amounts_to_blockchain_events;
+mod m20260131_000002_create_index_deployments;
+mod m20260131_000003_unique_blockchain_event_logs;
+mod m20260201_000001_partition_coins_historical_prices;
+mod m20260201_000002_add_hot_path_indexes;
+mod m20260201_000003_add_content_columns_to_itps;
+mod m20260201_000004_create_price_reconciliation_checks;
+mod m20260201_000005_create_methodology_documents;
+mod m20260201_000006_create_api_keys;
+mod m20260201_000007_create_auth_nonces;
+mod m20260201_000008_create_itp_drift_checks;
+mod m20260201_000009_create_itp_orders;
+mod m20260201_000010_create_solvers;
+mod m20260201_000011_create_labels;
+mod m20260201_000012_add_source_announcement_to_crypto_listings;
+mod m20260201_000013_create_feature_flags;
+mod m20260201_000014_create_coin_logos;
+mod m20260201_000015_create_index_tvl;
+mod m20260201_000016_add_deployed_block_to_rebalances;
+mod m20260201_000017_create_rebalance_approvals;
+mod m20260201_000018_create_chain_transactions;
+mod m20260201_000019_create_wallet_balance_checks;
+mod m20260201_000020_add_index_constraints;
+mod m20260201_000021_add_cash_buffer_to_index_metadata;
+mod m20260201_000022_create_coin_yield_rates;
+mod m20260201_000023_add_leverage_factor_to_index_metadata;
+mod m20260201_000024_create_index_translations;
+mod m20260201_000025_create_rolling_stats;
+mod m20260201_000026_create_snapshot_log;
+mod m20260201_000027_add_anchor_to_snapshot_log;
+mod m20260201_000028_create_reports;
+mod m20260201_000029_add_crypto_listings_query_indexes;
+mod m20260201_000030_create_spread_observations;
+mod m20260201_000031_add_use_observed_spread_to_index_metadata;
+mod m20260201_000032_create_pair_volumes;
+mod m20260201_000033_create_coin_sync_cursors;
+mod m20260201_000034_create_job_runs;
+mod m20260201_000035_add_status_to_index_metadata;
+mod m20260201_000036_create_token_migrations;
+mod m20260201_000037_add_fees_to_rebalances;
+mod m20260201_000038_add_run_after_to_background_tasks;
 
 pub struct Migrator;
 
@@ -77,6 +123,52 @@ impl MigratorTrait for Migrator {
             Box::new(m20260117_000001_create_sync_status::Migration),
             Box::new(m20260126_000001_add_admin_address_to_itps::Migration),
             Box::new(m20260126_000002_create_operations::Migration),
+            Box::new(m20260127_000001_create_category_change_events::Migration),
+            Box::new(m20260128_000001_add_deactivated_to_coins::Migration),
+            Box::new(m20260129_000001_create_job_failures::Migration),
+            Box::new(m20260130_000001_create_background_tasks::Migration),
+            Box::new(m20260130_000002_add_progress_to_background_tasks::Migration),
+            Box::new(m20260131_000001_add_raw_amounts_to_blockchain_events::Migration),
+            Box::new(m20260131_000002_create_index_deployments::Migration),
+            Box::new(m20260131_000003_unique_blockchain_event_logs::Migration),
+            Box::new(m20260201_000001_partition_coins_historical_prices::Migration),
+            Box::new(m20260201_000002_add_hot_path_indexes::Migration),
+            Box::new(m20260201_000003_add_content_columns_to_itps::Migration),
+            Box::new(m20260201_000004_create_price_reconciliation_checks::Migration),
+            Box::new(m20260201_000005_create_methodology_documents::Migration),
+            Box::new(m20260201_000006_create_api_keys::Migration),
+            Box::new(m20260201_000007_create_auth_nonces::Migration),
+            Box::new(m20260201_000008_create_itp_drift_checks::Migration),
+            Box::new(m20260201_000009_create_itp_orders::Migration),
+            Box::new(m20260201_000010_create_solvers::Migration),
+            Box::new(m20260201_000011_create_labels::Migration),
+            Box::new(m20260201_000012_add_source_announcement_to_crypto_listings::Migration),
+            Box::new(m20260201_000013_create_feature_flags::Migration),
+            Box::new(m20260201_000014_create_coin_logos::Migration),
+            Box::new(m20260201_000015_create_index_tvl::Migration),
+            Box::new(m20260201_000016_add_deployed_block_to_rebalances::Migration),
+            Box::new(m20260201_000017_create_rebalance_approvals::Migration),
+            Box::new(m20260201_000018_create_chain_transactions::Migration),
+            Box::new(m20260201_000019_create_wallet_balance_checks::Migration),
+            Box::new(m20260201_000020_add_index_constraints::Migration),
+            Box::new(m20260201_000021_add_cash_buffer_to_index_metadata::Migration),
+            Box::new(m20260201_000022_create_coin_yield_rates::Migration),
+            Box::new(m20260201_000023_add_leverage_factor_to_index_metadata::Migration),
+            Box::new(m20260201_000024_create_index_translations::Migration),
+            Box::new(m20260201_000025_create_rolling_stats::Migration),
+            Box::new(m20260201_000026_create_snapshot_log::Migration),
+            Box::new(m20260201_000027_add_anchor_to_snapshot_log::Migration),
+            Box::new(m20260201_000028_create_reports::Migration),
+            Box::new(m20260201_000029_add_crypto_listings_query_indexes::Migration),
+            Box::new(m20260201_000030_create_spread_observations::Migration),
+            Box::new(m20260201_000031_add_use_observed_spread_to_index_metadata::Migration),
+            Box::new(m20260201_000032_create_pair_volumes::Migration),
+            Box::new(m20260201_000033_create_coin_sync_cursors::Migration),
+            Box::new(m20260201_000034_create_job_runs::Migration),
+            Box::new(m20260201_000035_add_status_to_index_metadata::Migration),
+            Box::new(m20260201_000036_create_token_migrations::Migration),
+            Box::new(m20260201_000037_add_fees_to_rebalances::Migration),
+            Box::new(m20260201_000038_add_run_after_to_background_tasks::Migration),
         ]
     }
 }
@@ -0,0 +1,77 @@
+//! Migration to create the category_change_events table
+//!
+//! Category membership sync appends one row per coin added to or removed from
+//! a category. Consumers (webhooks, notifications) read undelivered rows and
+//! stamp `delivered_at` once processed.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CategoryChangeEvents::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CategoryChangeEvents::Id))
+                    .col(string(CategoryChangeEvents::CategoryId).not_null())
+                    .col(string(CategoryChangeEvents::CoinId).not_null())
+                    .col(string_null(CategoryChangeEvents::Symbol))
+                    .col(string_len(CategoryChangeEvents::ChangeType, 16).not_null())
+                    .col(timestamp(CategoryChangeEvents::EffectiveAt).not_null())
+                    .col(timestamp_null(CategoryChangeEvents::DeliveredAt))
+                    .col(timestamp(CategoryChangeEvents::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for consumers polling undelivered events in order
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_category_change_events_delivered")
+                    .table(CategoryChangeEvents::Table)
+                    .col(CategoryChangeEvents::DeliveredAt)
+                    .col(CategoryChangeEvents::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for per-category change history
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_category_change_events_category")
+                    .table(CategoryChangeEvents::Table)
+                    .col(CategoryChangeEvents::CategoryId)
+                    .col(CategoryChangeEvents::EffectiveAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CategoryChangeEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CategoryChangeEvents {
+    Table,
+    Id,
+    CategoryId,
+    CoinId,
+    Symbol,
+    ChangeType,
+    EffectiveAt,
+    DeliveredAt,
+    CreatedAt,
+}
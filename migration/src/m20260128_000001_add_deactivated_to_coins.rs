@@ -0,0 +1,69 @@
+//! Migration to add soft-deactivation columns to coins
+//!
+//! Coins that disappear from CoinGecko's coin list are never hard-deleted
+//! (prices and listings reference them); they are flagged as deactivated instead.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Coins::Table)
+                    .add_column(
+                        ColumnDef::new(Coins::Deactivated)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(ColumnDef::new(Coins::DeactivatedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coins_deactivated")
+                    .table(Coins::Table)
+                    .col(Coins::Deactivated)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_coins_deactivated")
+                    .table(Coins::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Coins::Table)
+                    .drop_column(Coins::Deactivated)
+                    .drop_column(Coins::DeactivatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Coins {
+    Table,
+    Deactivated,
+    DeactivatedAt,
+}
@@ -0,0 +1,83 @@
+//! Migration to create the job_failures table (dead-letter queue)
+//!
+//! Background jobs record per-item failures (a coin, an index) here instead of
+//! only logging them. A retry worker re-runs pending items with backoff and
+//! admins can requeue or discard them.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobFailures::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JobFailures::Id))
+                    .col(string_len(JobFailures::JobName, 100).not_null())
+                    .col(string_len(JobFailures::EntityType, 32).not_null())
+                    .col(string(JobFailures::EntityId).not_null())
+                    .col(json_binary_null(JobFailures::Payload))
+                    .col(text(JobFailures::Error).not_null())
+                    .col(integer(JobFailures::AttemptCount).default(1))
+                    .col(string_len(JobFailures::Status, 16).not_null())
+                    .col(timestamp_null(JobFailures::NextRetryAt))
+                    .col(timestamp(JobFailures::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(JobFailures::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for the retry worker picking due items
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_job_failures_status_next_retry")
+                    .table(JobFailures::Table)
+                    .col(JobFailures::Status)
+                    .col(JobFailures::NextRetryAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for finding the open failure of a given job item
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_job_failures_job_entity")
+                    .table(JobFailures::Table)
+                    .col(JobFailures::JobName)
+                    .col(JobFailures::EntityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobFailures::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobFailures {
+    Table,
+    Id,
+    JobName,
+    EntityType,
+    EntityId,
+    Payload,
+    Error,
+    AttemptCount,
+    Status,
+    NextRetryAt,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,83 @@
+//! Migration to create the background_tasks table
+//!
+//! Long-running work triggered from request handlers (e.g. the historical
+//! backfill after create_index) is enqueued here instead of being spawned
+//! fire-and-forget, so progress is visible and tasks survive restarts.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BackgroundTasks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(BackgroundTasks::Id))
+                    .col(string_len(BackgroundTasks::TaskType, 64).not_null())
+                    .col(integer_null(BackgroundTasks::IndexId))
+                    .col(json_binary_null(BackgroundTasks::Payload))
+                    .col(string_len(BackgroundTasks::Status, 16).not_null())
+                    .col(text_null(BackgroundTasks::Error))
+                    .col(integer(BackgroundTasks::Attempts).default(0))
+                    .col(timestamp_null(BackgroundTasks::StartedAt))
+                    .col(timestamp_null(BackgroundTasks::FinishedAt))
+                    .col(timestamp(BackgroundTasks::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(BackgroundTasks::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for the worker picking the next pending task
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_background_tasks_status_created")
+                    .table(BackgroundTasks::Table)
+                    .col(BackgroundTasks::Status)
+                    .col(BackgroundTasks::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for the backfill-status lookup
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_background_tasks_index_type")
+                    .table(BackgroundTasks::Table)
+                    .col(BackgroundTasks::IndexId)
+                    .col(BackgroundTasks::TaskType)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BackgroundTasks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BackgroundTasks {
+    Table,
+    Id,
+    TaskType,
+    IndexId,
+    Payload,
+    Status,
+    Error,
+    Attempts,
+    StartedAt,
+    FinishedAt,
+    CreatedAt,
+    UpdatedAt,
+}
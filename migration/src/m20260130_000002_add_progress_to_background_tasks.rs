@@ -0,0 +1,47 @@
+//! Migration to add progress tracking to background_tasks
+//!
+//! Long backfills report how many planned rebalance dates are done and which
+//! date is being processed, so the backfill-status endpoint can show a
+//! percentage and ETA.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BackgroundTasks::Table)
+                    .add_column_if_not_exists(integer_null(BackgroundTasks::ProgressDone))
+                    .add_column_if_not_exists(integer_null(BackgroundTasks::ProgressTotal))
+                    .add_column_if_not_exists(date_null(BackgroundTasks::ProgressDate))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BackgroundTasks::Table)
+                    .drop_column(BackgroundTasks::ProgressDone)
+                    .drop_column(BackgroundTasks::ProgressTotal)
+                    .drop_column(BackgroundTasks::ProgressDate)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BackgroundTasks {
+    Table,
+    ProgressDone,
+    ProgressTotal,
+    ProgressDate,
+}
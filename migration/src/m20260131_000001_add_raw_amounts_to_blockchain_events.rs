@@ -0,0 +1,52 @@
+//! Migration to store raw on-chain amounts on blockchain_events
+//!
+//! `amount` and `quantity` were converted to Decimal at ingestion, which
+//! loses precision (index quantities have 30 decimals) and can't be redone
+//! after a decimals bug. The raw integer values are kept as text since a
+//! uint256 doesn't fit in a Decimal, together with the decimals they were
+//! emitted with. Display quantities are derived from them on read.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BlockchainEvents::Table)
+                    .add_column_if_not_exists(text_null(BlockchainEvents::RawAmount))
+                    .add_column_if_not_exists(integer_null(BlockchainEvents::Decimals))
+                    .add_column_if_not_exists(text_null(BlockchainEvents::RawQuantity))
+                    .add_column_if_not_exists(integer_null(BlockchainEvents::QuantityDecimals))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BlockchainEvents::Table)
+                    .drop_column(BlockchainEvents::RawAmount)
+                    .drop_column(BlockchainEvents::Decimals)
+                    .drop_column(BlockchainEvents::RawQuantity)
+                    .drop_column(BlockchainEvents::QuantityDecimals)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockchainEvents {
+    Table,
+    RawAmount,
+    Decimals,
+    RawQuantity,
+    QuantityDecimals,
+}
@@ -0,0 +1,88 @@
+//! Migration to create the index_deployments table
+//!
+//! An index token can be deployed on several networks, or have more than one
+//! contract on a network (e.g. a bridged copy). Each row maps an index to one
+//! (network, contract_address) pair; blockchain_events queries for the index
+//! cover all of them. Existing indexes are seeded with their
+//! index_metadata.address on "base", the previously hard-coded network.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IndexDeployments::Table)
+                    .if_not_exists()
+                    .col(pk_auto(IndexDeployments::Id))
+                    .col(integer(IndexDeployments::IndexId).not_null())
+                    .col(string_len(IndexDeployments::Network, 32).not_null())
+                    .col(string(IndexDeployments::ContractAddress).not_null())
+                    .col(timestamp(IndexDeployments::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_index_deployments_index_id")
+                            .from(IndexDeployments::Table, IndexDeployments::IndexId)
+                            .to(IndexMetadata::Table, IndexMetadata::IndexId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_index_deployments_unique")
+                    .table(IndexDeployments::Table)
+                    .col(IndexDeployments::IndexId)
+                    .col(IndexDeployments::Network)
+                    .col(IndexDeployments::ContractAddress)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                INSERT INTO index_deployments (index_id, network, contract_address)
+                SELECT index_id, 'base', LOWER(address)
+                FROM index_metadata
+                WHERE address <> ''
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IndexDeployments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexDeployments {
+    Table,
+    Id,
+    IndexId,
+    Network,
+    ContractAddress,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    IndexId,
+}
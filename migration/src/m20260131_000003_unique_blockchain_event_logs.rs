@@ -0,0 +1,90 @@
+//! Migration to identify blockchain_events by (tx_hash, log_index, network)
+//!
+//! tx_hash alone was unique, so a transaction emitting several logs (or the
+//! same hash on two networks) could only store one event and the save
+//! endpoint overwrote it. A log is identified by its transaction, its index
+//! within the transaction and the network; making that the unique key lets
+//! save_blockchain_event upsert idempotently when relayers retry.
+//!
+//! The endpoint now stores tx_hash, network and contract_address lowercased,
+//! so existing rows are lowercased first and the events that then collide
+//! (the same log sent with a different case) are deduplicated, keeping the
+//! oldest row.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Column-level UNIQUE from the original create_blockchain_events migration
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE blockchain_events DROP CONSTRAINT IF EXISTS blockchain_events_tx_hash_key",
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "UPDATE blockchain_events
+             SET tx_hash = LOWER(TRIM(tx_hash)),
+                 network = LOWER(TRIM(network)),
+                 contract_address = LOWER(TRIM(contract_address))",
+        )
+        .await?;
+        db.execute_unprepared(
+            "DELETE FROM blockchain_events e
+             USING blockchain_events kept
+             WHERE kept.tx_hash = e.tx_hash
+               AND kept.log_index = e.log_index
+               AND kept.network = e.network
+               AND kept.id < e.id",
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_blockchain_events_tx_log_network")
+                    .table(BlockchainEvents::Table)
+                    .col(BlockchainEvents::TxHash)
+                    .col(BlockchainEvents::LogIndex)
+                    .col(BlockchainEvents::Network)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_blockchain_events_tx_log_network")
+                    .table(BlockchainEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE blockchain_events ADD CONSTRAINT blockchain_events_tx_hash_key UNIQUE (tx_hash)",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockchainEvents {
+    Table,
+    TxHash,
+    LogIndex,
+    Network,
+}
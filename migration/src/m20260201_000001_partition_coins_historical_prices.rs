@@ -0,0 +1,171 @@
+//! Migration to range-partition coins_historical_prices by year
+//!
+//! Range scans over the full table (top coins by market cap on a date, coin
+//! history between two dates) are the slowest queries behind the category
+//! and history endpoints. Partitioning by year lets Postgres prune to the
+//! partitions a date range touches, and keeps each partition's indexes small.
+//!
+//! The table is rebuilt: existing rows are copied into yearly partitions
+//! (from the oldest year present through next year) and the old table is
+//! dropped. A DEFAULT partition catches anything outside the created years;
+//! the coins price partitions job creates upcoming years ahead of time and
+//! moves stray rows out of it.
+//!
+//! The primary key becomes (id, date) because a partitioned table's unique
+//! constraints must include the partition key. `id` keeps its sequence, so
+//! it stays unique on its own.
+//!
+//! On a large table this copies every row; run it during a maintenance
+//! window.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "ALTER TABLE coins_historical_prices RENAME TO coins_historical_prices_unpartitioned",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE coins_historical_prices (
+                id INTEGER NOT NULL DEFAULT nextval('coins_historical_prices_id_seq'),
+                coin_id VARCHAR NOT NULL,
+                symbol VARCHAR NOT NULL,
+                date DATE NOT NULL,
+                price DECIMAL NOT NULL,
+                market_cap DECIMAL,
+                volume DECIMAL,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            ) PARTITION BY RANGE (date)
+            "#,
+        )
+        .await?;
+
+        // One partition per year from the oldest row through next year
+        db.execute_unprepared(
+            r#"
+            DO $$
+            DECLARE
+                first_year INT;
+                last_year INT := EXTRACT(YEAR FROM CURRENT_DATE)::INT + 1;
+            BEGIN
+                SELECT COALESCE(EXTRACT(YEAR FROM MIN(date))::INT, last_year - 1)
+                INTO first_year
+                FROM coins_historical_prices_unpartitioned;
+
+                FOR y IN first_year..last_year LOOP
+                    EXECUTE format(
+                        'CREATE TABLE coins_historical_prices_y%s PARTITION OF coins_historical_prices
+                         FOR VALUES FROM (%L) TO (%L)',
+                        y, make_date(y, 1, 1), make_date(y + 1, 1, 1)
+                    );
+                END LOOP;
+            END $$
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE TABLE coins_historical_prices_default PARTITION OF coins_historical_prices DEFAULT",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            INSERT INTO coins_historical_prices
+                (id, coin_id, symbol, date, price, market_cap, volume, created_at)
+            SELECT id, coin_id, symbol, date, price, market_cap, volume, created_at
+            FROM coins_historical_prices_unpartitioned
+            "#,
+        )
+        .await?;
+
+        // Hand the id sequence to the new table before dropping the old one
+        db.execute_unprepared(
+            "ALTER SEQUENCE coins_historical_prices_id_seq OWNED BY coins_historical_prices.id",
+        )
+        .await?;
+        db.execute_unprepared("DROP TABLE coins_historical_prices_unpartitioned")
+            .await?;
+
+        // Constraints and indexes are created on the parent (and cascade to
+        // every partition) once the data is in, which is faster than
+        // maintaining them row by row during the copy
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE coins_historical_prices
+                ADD CONSTRAINT coins_historical_prices_pkey PRIMARY KEY (id, date);
+            CREATE UNIQUE INDEX idx_coins_historical_prices_unique
+                ON coins_historical_prices (coin_id, date);
+            CREATE INDEX idx_coins_historical_prices_date_mcap
+                ON coins_historical_prices (date, market_cap);
+            CREATE INDEX idx_coins_historical_prices_symbol_date
+                ON coins_historical_prices (symbol, date);
+            CREATE INDEX idx_coins_historical_prices_coin_date
+                ON coins_historical_prices (coin_id, date DESC);
+            CREATE INDEX idx_coins_historical_prices_distinct_on
+                ON coins_historical_prices (coin_id, date DESC, market_cap DESC NULLS LAST);
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "ALTER TABLE coins_historical_prices RENAME TO coins_historical_prices_partitioned",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TABLE coins_historical_prices (
+                id INTEGER NOT NULL DEFAULT nextval('coins_historical_prices_id_seq') PRIMARY KEY,
+                coin_id VARCHAR NOT NULL,
+                symbol VARCHAR NOT NULL,
+                date DATE NOT NULL,
+                price DECIMAL NOT NULL,
+                market_cap DECIMAL,
+                volume DECIMAL,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT INTO coins_historical_prices
+                (id, coin_id, symbol, date, price, market_cap, volume, created_at)
+            SELECT id, coin_id, symbol, date, price, market_cap, volume, created_at
+            FROM coins_historical_prices_partitioned;
+            ALTER SEQUENCE coins_historical_prices_id_seq OWNED BY coins_historical_prices.id;
+            DROP TABLE coins_historical_prices_partitioned;
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE UNIQUE INDEX idx_coins_historical_prices_unique
+                ON coins_historical_prices (coin_id, date);
+            CREATE INDEX idx_coins_historical_prices_date_mcap
+                ON coins_historical_prices (date, market_cap);
+            CREATE INDEX idx_coins_historical_prices_symbol_date
+                ON coins_historical_prices (symbol, date);
+            CREATE INDEX idx_coins_historical_prices_coin_date
+                ON coins_historical_prices (coin_id, date DESC);
+            CREATE INDEX idx_coins_historical_prices_distinct_on
+                ON coins_historical_prices (coin_id, date DESC, market_cap DESC NULLS LAST);
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
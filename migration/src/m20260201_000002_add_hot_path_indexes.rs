@@ -0,0 +1,105 @@
+//! Migration adding composite indexes for the hot query paths
+//!
+//! - blockchain_events(contract_address, network, event_type): every
+//!   per-index event query (transactions, deposits, minted supply) filters
+//!   on the index's deployments and usually an event type, and was a
+//!   sequential scan.
+//! - coins_historical_prices(date, market_cap DESC): replaces the ascending
+//!   (date, market_cap) index so "top coins by market cap on a date" reads
+//!   the index in order.
+//!
+//! Already covered, so not duplicated here:
+//! - coins_historical_prices(coin_id, date) by idx_coins_historical_prices_unique
+//! - rebalances(index_id, timestamp) by idx_rebalances_unique (btree indexes
+//!   are scanned in either direction, so it serves `timestamp DESC` too)
+//! - daily_prices(index_id, date) by the table's primary key
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_blockchain_events_contract_network_type")
+                    .table(BlockchainEvents::Table)
+                    .col(BlockchainEvents::ContractAddress)
+                    .col(BlockchainEvents::Network)
+                    .col(BlockchainEvents::EventType)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_coins_historical_prices_date_mcap")
+                    .table(CoinsHistoricalPrices::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coins_historical_prices_date_mcap")
+                    .table(CoinsHistoricalPrices::Table)
+                    .col(CoinsHistoricalPrices::Date)
+                    .col((CoinsHistoricalPrices::MarketCap, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_coins_historical_prices_date_mcap")
+                    .table(CoinsHistoricalPrices::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coins_historical_prices_date_mcap")
+                    .table(CoinsHistoricalPrices::Table)
+                    .col(CoinsHistoricalPrices::Date)
+                    .col(CoinsHistoricalPrices::MarketCap)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_blockchain_events_contract_network_type")
+                    .table(BlockchainEvents::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BlockchainEvents {
+    Table,
+    ContractAddress,
+    Network,
+    EventType,
+}
+
+#[derive(DeriveIden)]
+enum CoinsHistoricalPrices {
+    Table,
+    Date,
+    MarketCap,
+}
@@ -0,0 +1,44 @@
+//! Migration to add the content columns of itps
+//!
+//! methodology, description, assets and weights are mapped by the itps
+//! entity but were only ever added by hand on existing databases, so a fresh
+//! database failed every itps query (found by the check_schema binary).
+//! `IF NOT EXISTS` keeps this a no-op where they were already added, and
+//! `down` leaves them in place for the same reason.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Itps::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Itps::Methodology).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(Itps::Description).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(Itps::Assets).json_binary().null())
+                    .add_column_if_not_exists(ColumnDef::new(Itps::Weights).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    /// No-op: on most databases the columns predate this migration, so
+    /// dropping them would delete content `up` never created
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Itps {
+    Table,
+    Methodology,
+    Description,
+    Assets,
+    Weights,
+}
@@ -0,0 +1,83 @@
+//! Migration to create the price_reconciliation_checks table
+//!
+//! The price reconciliation job compares a daily sample of stored
+//! coins_historical_prices closes with an independent source (exchange
+//! klines) and records every comparison here, flagged or not, as evidence of
+//! how far the stored prices can be trusted.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PriceReconciliationChecks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PriceReconciliationChecks::Id))
+                    .col(string(PriceReconciliationChecks::CoinId).not_null())
+                    .col(string(PriceReconciliationChecks::Symbol).not_null())
+                    .col(date(PriceReconciliationChecks::Date).not_null())
+                    .col(string_len(PriceReconciliationChecks::Source, 32).not_null())
+                    .col(ColumnDef::new(PriceReconciliationChecks::StoredPrice).decimal().not_null())
+                    .col(ColumnDef::new(PriceReconciliationChecks::ReferencePrice).decimal().not_null())
+                    .col(integer(PriceReconciliationChecks::DeviationBps).not_null())
+                    .col(boolean(PriceReconciliationChecks::Flagged).not_null())
+                    .col(timestamp(PriceReconciliationChecks::CheckedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // One check per coin, day and source; re-runs update it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_price_reconciliation_checks_unique")
+                    .table(PriceReconciliationChecks::Table)
+                    .col(PriceReconciliationChecks::CoinId)
+                    .col(PriceReconciliationChecks::Date)
+                    .col(PriceReconciliationChecks::Source)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Index for the admin summary over recent days
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_price_reconciliation_checks_date")
+                    .table(PriceReconciliationChecks::Table)
+                    .col(PriceReconciliationChecks::Date)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PriceReconciliationChecks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceReconciliationChecks {
+    Table,
+    Id,
+    CoinId,
+    Symbol,
+    Date,
+    Source,
+    StoredPrice,
+    ReferencePrice,
+    DeviationBps,
+    Flagged,
+    CheckedAt,
+}
@@ -0,0 +1,76 @@
+//! Migration to create the methodology_documents table
+//!
+//! Each row is one immutable version of an index's methodology document.
+//! Versions are numbered per index and take effect on `effective_from`, so
+//! the methodology that applied on any past day can be reconstructed.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MethodologyDocuments::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MethodologyDocuments::Id))
+                    .col(integer(MethodologyDocuments::IndexId).not_null())
+                    .col(integer(MethodologyDocuments::Version).not_null())
+                    .col(string_len(MethodologyDocuments::Format, 16).not_null())
+                    .col(text(MethodologyDocuments::Content).not_null())
+                    .col(date(MethodologyDocuments::EffectiveFrom).not_null())
+                    .col(timestamp(MethodologyDocuments::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_methodology_documents_index_id")
+                            .from(MethodologyDocuments::Table, MethodologyDocuments::IndexId)
+                            .to(IndexMetadata::Table, IndexMetadata::IndexId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_methodology_documents_unique")
+                    .table(MethodologyDocuments::Table)
+                    .col(MethodologyDocuments::IndexId)
+                    .col(MethodologyDocuments::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MethodologyDocuments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MethodologyDocuments {
+    Table,
+    Id,
+    IndexId,
+    Version,
+    Format,
+    Content,
+    EffectiveFrom,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    IndexId,
+}
@@ -0,0 +1,104 @@
+//! Migration to create the api_keys and api_key_usage tables
+//!
+//! api_keys holds the keys issued to external consumers. Only a hash of each
+//! key is stored, with a short prefix to recognize it by. api_key_usage
+//! counts requests per key, UTC day and endpoint (matched route template).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiKeys::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiKeys::Id))
+                    .col(string(ApiKeys::Name).not_null())
+                    .col(string_len(ApiKeys::KeyPrefix, 16).not_null())
+                    .col(string_len(ApiKeys::KeyHash, 64).not_null().unique_key())
+                    .col(string_len(ApiKeys::Tier, 16).not_null())
+                    .col(integer_null(ApiKeys::DailyQuota))
+                    .col(boolean(ApiKeys::Active).default(true))
+                    .col(timestamp(ApiKeys::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp_null(ApiKeys::RevokedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiKeyUsage::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiKeyUsage::Id))
+                    .col(integer(ApiKeyUsage::ApiKeyId).not_null())
+                    .col(date(ApiKeyUsage::Date).not_null())
+                    .col(string(ApiKeyUsage::Endpoint).not_null())
+                    .col(big_integer(ApiKeyUsage::RequestCount).not_null().default(0))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_api_key_usage_api_key_id")
+                            .from(ApiKeyUsage::Table, ApiKeyUsage::ApiKeyId)
+                            .to(ApiKeys::Table, ApiKeys::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One counter per key, day and endpoint; also serves the per-day
+        // quota sum
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_api_key_usage_unique")
+                    .table(ApiKeyUsage::Table)
+                    .col(ApiKeyUsage::ApiKeyId)
+                    .col(ApiKeyUsage::Date)
+                    .col(ApiKeyUsage::Endpoint)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiKeyUsage::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ApiKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiKeys {
+    Table,
+    Id,
+    Name,
+    KeyPrefix,
+    KeyHash,
+    Tier,
+    DailyQuota,
+    Active,
+    CreatedAt,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum ApiKeyUsage {
+    Table,
+    Id,
+    ApiKeyId,
+    Date,
+    Endpoint,
+    RequestCount,
+}
@@ -0,0 +1,41 @@
+//! Migration to create the auth_nonces table
+//!
+//! Sign-In-With-Ethereum nonces handed out by POST /auth/nonce. A nonce is
+//! deleted when a signed message using it is verified, so each one can sign
+//! in once; unused nonces expire after a few minutes.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthNonces::Table)
+                    .if_not_exists()
+                    .col(string_len(AuthNonces::Nonce, 64).primary_key())
+                    .col(timestamp(AuthNonces::ExpiresAt).not_null())
+                    .col(timestamp(AuthNonces::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuthNonces::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuthNonces {
+    Table,
+    Nonce,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,81 @@
+//! Migration to create the itp_drift_checks table
+//!
+//! One row per ITP per day with how far the ITP's market-value weights have
+//! drifted from its target weights (see services::itp_drift).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItpDriftChecks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ItpDriftChecks::Id))
+                    .col(integer(ItpDriftChecks::ItpId).not_null())
+                    .col(date(ItpDriftChecks::Date).not_null())
+                    .col(date(ItpDriftChecks::BaseDate).not_null())
+                    .col(integer(ItpDriftChecks::MaxDriftBps).not_null())
+                    .col(integer(ItpDriftChecks::TotalDriftBps).not_null())
+                    .col(integer(ItpDriftChecks::ThresholdBps).not_null())
+                    .col(boolean(ItpDriftChecks::Flagged).not_null())
+                    .col(json_binary(ItpDriftChecks::Constituents).not_null())
+                    .col(timestamp(ItpDriftChecks::CheckedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_itp_drift_checks_itp_id")
+                            .from(ItpDriftChecks::Table, ItpDriftChecks::ItpId)
+                            .to(Itps::Table, Itps::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_itp_drift_checks_unique")
+                    .table(ItpDriftChecks::Table)
+                    .col(ItpDriftChecks::ItpId)
+                    .col(ItpDriftChecks::Date)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ItpDriftChecks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ItpDriftChecks {
+    Table,
+    Id,
+    ItpId,
+    Date,
+    BaseDate,
+    MaxDriftBps,
+    TotalDriftBps,
+    ThresholdBps,
+    Flagged,
+    Constituents,
+    CheckedAt,
+}
+
+#[derive(DeriveIden)]
+enum Itps {
+    Table,
+    Id,
+}
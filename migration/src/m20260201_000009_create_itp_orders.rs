@@ -0,0 +1,106 @@
+//! Migration to create the itp_orders table
+//!
+//! Mint and redeem orders submitted for an ITP and how far they've got:
+//! pending until the order's on-chain fill event is indexed, filled until
+//! its settlement event is, then settled. Each blockchain event advances at
+//! most one order, hence the unique event ids.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItpOrders::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ItpOrders::Id))
+                    .col(string_len(ItpOrders::OrderId, 36).not_null().unique_key())
+                    .col(integer(ItpOrders::ItpId).not_null())
+                    .col(string_len(ItpOrders::WalletAddress, 42).not_null())
+                    .col(string_len(ItpOrders::Side, 16).not_null())
+                    .col(ColumnDef::new(ItpOrders::Amount).decimal().not_null())
+                    .col(string_len(ItpOrders::Status, 16).not_null())
+                    .col(string_null(ItpOrders::SubmitTxHash))
+                    .col(integer_null(ItpOrders::FillEventId).unique_key())
+                    .col(string_null(ItpOrders::FillTxHash))
+                    .col(timestamp_null(ItpOrders::FilledAt))
+                    .col(integer_null(ItpOrders::SettleEventId).unique_key())
+                    .col(string_null(ItpOrders::SettleTxHash))
+                    .col(timestamp_null(ItpOrders::SettledAt))
+                    .col(timestamp(ItpOrders::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(ItpOrders::UpdatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_itp_orders_itp_id")
+                            .from(ItpOrders::Table, ItpOrders::ItpId)
+                            .to(Itps::Table, Itps::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A wallet's orders on an ITP
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_itp_orders_wallet")
+                    .table(ItpOrders::Table)
+                    .col(ItpOrders::WalletAddress)
+                    .col(ItpOrders::ItpId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Open orders picked up by the indexer
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_itp_orders_status")
+                    .table(ItpOrders::Table)
+                    .col(ItpOrders::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ItpOrders::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ItpOrders {
+    Table,
+    Id,
+    OrderId,
+    ItpId,
+    WalletAddress,
+    Side,
+    Amount,
+    Status,
+    SubmitTxHash,
+    FillEventId,
+    FillTxHash,
+    FilledAt,
+    SettleEventId,
+    SettleTxHash,
+    SettledAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Itps {
+    Table,
+    Id,
+}
@@ -0,0 +1,55 @@
+//! Migration to create the solvers table
+//!
+//! Execution agents (solvers/keepers) that fill ITP orders register here
+//! with the assets and exchanges they support, then send heartbeats so the
+//! UI can show which are live.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Solvers::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Solvers::Id))
+                    .col(string_len(Solvers::Address, 42).not_null().unique_key())
+                    .col(string_len(Solvers::Name, 64).not_null())
+                    .col(json_binary(Solvers::Assets).not_null())
+                    .col(json_binary(Solvers::Exchanges).not_null())
+                    .col(string_null(Solvers::Endpoint))
+                    .col(string_len_null(Solvers::Version, 32))
+                    .col(timestamp(Solvers::RegisteredAt).default(Expr::current_timestamp()))
+                    .col(timestamp(Solvers::UpdatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp_null(Solvers::LastHeartbeatAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Solvers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Solvers {
+    Table,
+    Id,
+    Address,
+    Name,
+    Assets,
+    Exchanges,
+    Endpoint,
+    Version,
+    RegisteredAt,
+    UpdatedAt,
+    LastHeartbeatAt,
+}
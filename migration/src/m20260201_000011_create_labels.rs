@@ -0,0 +1,76 @@
+//! Migration to create the labels table
+//!
+//! Free-form key/value labels on indexes and coins (e.g. theme=ai), so
+//! products can be grouped without a schema change per grouping.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Labels::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Labels::Id))
+                    .col(string_len(Labels::EntityType, 16).not_null())
+                    .col(string_len(Labels::EntityId, 128).not_null())
+                    .col(string_len(Labels::Key, 64).not_null())
+                    .col(string_len(Labels::Value, 128).not_null())
+                    .col(timestamp(Labels::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // One row per distinct label on an entity
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_labels_entity_key_value")
+                    .table(Labels::Table)
+                    .col(Labels::EntityType)
+                    .col(Labels::EntityId)
+                    .col(Labels::Key)
+                    .col(Labels::Value)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Filtering entities by label
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_labels_key_value")
+                    .table(Labels::Table)
+                    .col(Labels::EntityType)
+                    .col(Labels::Key)
+                    .col(Labels::Value)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Labels::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Labels {
+    Table,
+    Id,
+    EntityType,
+    EntityId,
+    Key,
+    Value,
+    CreatedAt,
+}
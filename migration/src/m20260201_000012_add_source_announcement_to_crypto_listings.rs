@@ -0,0 +1,69 @@
+//! Migration to link crypto_listings to their source announcement
+//!
+//! source_announcement_id is the announcement that produced the listing's
+//! most recently applied listing or delisting date. It is cleared if the
+//! announcement is deleted, and stays null for listings whose announcement
+//! could not be matched.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CryptoListings::Table)
+                    .add_column_if_not_exists(ColumnDef::new(CryptoListings::SourceAnnouncementId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_crypto_listings_source_announcement_id")
+                            .from_tbl(CryptoListings::Table)
+                            .from_col(CryptoListings::SourceAnnouncementId)
+                            .to_tbl(Announcements::Table)
+                            .to_col(Announcements::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crypto_listings_source_announcement_id")
+                    .table(CryptoListings::Table)
+                    .col(CryptoListings::SourceAnnouncementId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CryptoListings::Table)
+                    .drop_column(CryptoListings::SourceAnnouncementId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CryptoListings {
+    Table,
+    SourceAnnouncementId,
+}
+
+#[derive(DeriveIden)]
+enum Announcements {
+    Table,
+    Id,
+}
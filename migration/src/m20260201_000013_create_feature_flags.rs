@@ -0,0 +1,40 @@
+//! Migration to create the feature_flags table
+//!
+//! Operator overrides of runtime feature flags (see services::feature_flags).
+//! A flag without a row keeps its default.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlags::Table)
+                    .if_not_exists()
+                    .col(string_len(FeatureFlags::Key, 64).not_null().primary_key())
+                    .col(boolean(FeatureFlags::Enabled).not_null())
+                    .col(timestamp(FeatureFlags::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FeatureFlags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlags {
+    Table,
+    Key,
+    Enabled,
+    UpdatedAt,
+}
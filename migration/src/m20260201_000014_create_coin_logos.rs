@@ -0,0 +1,44 @@
+//! Migration to create the coin_logos table
+//!
+//! Logos served by GET /assets/{coin_id}/logo, fetched once from the coin's
+//! logo_address and kept here so the frontend doesn't hot-link CoinGecko.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CoinLogos::Table)
+                    .if_not_exists()
+                    .col(string(CoinLogos::CoinId).not_null().primary_key())
+                    .col(string(CoinLogos::SourceUrl).not_null())
+                    .col(string_len(CoinLogos::ContentType, 64).not_null())
+                    .col(binary(CoinLogos::Data).not_null())
+                    .col(timestamp(CoinLogos::FetchedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CoinLogos::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CoinLogos {
+    Table,
+    CoinId,
+    SourceUrl,
+    ContentType,
+    Data,
+    FetchedAt,
+}
@@ -0,0 +1,64 @@
+//! Migration to create the index_tvl table
+//!
+//! One row per index and day: the net supply of the index token (mints minus
+//! burns from blockchain_events) at the end of the day, the day's NAV from
+//! daily_prices, and their product, the TVL. Filled by the TVL snapshot job.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IndexTvl::Table)
+                    .if_not_exists()
+                    .col(pk_auto(IndexTvl::Id))
+                    .col(integer(IndexTvl::IndexId).not_null())
+                    .col(date(IndexTvl::Date).not_null())
+                    .col(ColumnDef::new(IndexTvl::NetSupply).decimal().not_null())
+                    .col(ColumnDef::new(IndexTvl::Nav).decimal().not_null())
+                    .col(ColumnDef::new(IndexTvl::TvlUsd).decimal().not_null())
+                    .col(timestamp(IndexTvl::RecordedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // One snapshot per index and day; re-runs update it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_index_tvl_index_date")
+                    .table(IndexTvl::Table)
+                    .col(IndexTvl::IndexId)
+                    .col(IndexTvl::Date)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IndexTvl::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexTvl {
+    Table,
+    Id,
+    IndexId,
+    Date,
+    NetSupply,
+    Nav,
+    TvlUsd,
+    RecordedAt,
+}
@@ -0,0 +1,41 @@
+//! Migration to record the block a rebalance was deployed in
+//!
+//! The rebalance deployer submits each index's latest rebalance as an
+//! on-chain weight update and stores the transaction hash (tx_hash) and the
+//! block it was mined in here.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Rebalances::DeployedBlock).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .drop_column(Rebalances::DeployedBlock)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Rebalances {
+    Table,
+    DeployedBlock,
+}
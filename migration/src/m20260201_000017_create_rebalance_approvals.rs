@@ -0,0 +1,64 @@
+//! Migration to create the rebalance_approvals table
+//!
+//! A rebalance is only deployed on-chain once a second admin credential has
+//! approved it: one row per approval request, pending until approved or
+//! rejected, and void after expires_at.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RebalanceApprovals::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RebalanceApprovals::Id))
+                    .col(integer(RebalanceApprovals::RebalanceId).not_null())
+                    .col(string_len(RebalanceApprovals::Status, 16).not_null())
+                    .col(string_len(RebalanceApprovals::RequestedBy, 64).not_null())
+                    .col(string_len_null(RebalanceApprovals::DecidedBy, 64))
+                    .col(timestamp(RebalanceApprovals::ExpiresAt).not_null())
+                    .col(timestamp(RebalanceApprovals::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp_null(RebalanceApprovals::DecidedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Latest request of a rebalance
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rebalance_approvals_rebalance")
+                    .table(RebalanceApprovals::Table)
+                    .col(RebalanceApprovals::RebalanceId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RebalanceApprovals::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RebalanceApprovals {
+    Table,
+    Id,
+    RebalanceId,
+    Status,
+    RequestedBy,
+    DecidedBy,
+    ExpiresAt,
+    CreatedAt,
+    DecidedAt,
+}
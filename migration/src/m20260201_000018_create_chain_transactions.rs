@@ -0,0 +1,79 @@
+//! Migration to create the chain_transactions table
+//!
+//! One row per on-chain transaction the backend sends (ITP creation, weight
+//! updates), succeeded or reverted, with the gas it paid and its USD cost at
+//! the ETH price of the day, so the deployer wallet's spend can be
+//! reconciled per month.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChainTransactions::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ChainTransactions::Id))
+                    .col(string_len(ChainTransactions::TxHash, 66).not_null().unique_key())
+                    .col(string_len(ChainTransactions::Kind, 32).not_null())
+                    .col(string_len_null(ChainTransactions::Reference, 128))
+                    .col(big_integer(ChainTransactions::ChainId).not_null())
+                    .col(string_len(ChainTransactions::FromAddress, 42).not_null())
+                    .col(string_len_null(ChainTransactions::ToAddress, 42))
+                    .col(big_integer(ChainTransactions::BlockNumber).not_null())
+                    .col(boolean(ChainTransactions::Succeeded).not_null())
+                    .col(big_integer(ChainTransactions::GasUsed).not_null())
+                    .col(ColumnDef::new(ChainTransactions::EffectiveGasPriceWei).decimal().not_null())
+                    .col(ColumnDef::new(ChainTransactions::CostEth).decimal().not_null())
+                    .col(ColumnDef::new(ChainTransactions::EthPriceUsd).decimal().null())
+                    .col(ColumnDef::new(ChainTransactions::CostUsd).decimal().null())
+                    .col(timestamp(ChainTransactions::SentAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Monthly spend reports
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chain_transactions_sent_at")
+                    .table(ChainTransactions::Table)
+                    .col(ChainTransactions::SentAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChainTransactions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChainTransactions {
+    Table,
+    Id,
+    TxHash,
+    Kind,
+    Reference,
+    ChainId,
+    FromAddress,
+    ToAddress,
+    BlockNumber,
+    Succeeded,
+    GasUsed,
+    EffectiveGasPriceWei,
+    CostEth,
+    EthPriceUsd,
+    CostUsd,
+    SentAt,
+}
@@ -0,0 +1,67 @@
+//! Migration to create the wallet_balance_checks table
+//!
+//! One row per network, signer wallet and day with the wallet's latest ETH
+//! balance and whether it was under the alert threshold. Filled by the
+//! wallet balance monitor job; re-runs on the same day update the row.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WalletBalanceChecks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WalletBalanceChecks::Id))
+                    .col(string_len(WalletBalanceChecks::Network, 32).not_null())
+                    .col(string_len(WalletBalanceChecks::Address, 42).not_null())
+                    .col(date(WalletBalanceChecks::Date).not_null())
+                    .col(ColumnDef::new(WalletBalanceChecks::BalanceEth).decimal().not_null())
+                    .col(ColumnDef::new(WalletBalanceChecks::ThresholdEth).decimal().not_null())
+                    .col(boolean(WalletBalanceChecks::Low).not_null())
+                    .col(timestamp(WalletBalanceChecks::CheckedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // One check per network, wallet and day; re-runs update it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_wallet_balance_checks_network_address_date")
+                    .table(WalletBalanceChecks::Table)
+                    .col(WalletBalanceChecks::Network)
+                    .col(WalletBalanceChecks::Address)
+                    .col(WalletBalanceChecks::Date)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WalletBalanceChecks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WalletBalanceChecks {
+    Table,
+    Id,
+    Network,
+    Address,
+    Date,
+    BalanceEth,
+    ThresholdEth,
+    Low,
+    CheckedAt,
+}
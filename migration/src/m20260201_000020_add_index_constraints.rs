@@ -0,0 +1,65 @@
+//! Migration for index composition constraints
+//!
+//! index_metadata.constraints holds an index's sector caps and constituent
+//! count limits (see services::index_constraints); rebalances record which of
+//! them the selection broke and how it was trimmed in constraint_violations.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .add_column_if_not_exists(ColumnDef::new(IndexMetadata::Constraints).json_binary().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Rebalances::ConstraintViolations).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .drop_column(Rebalances::ConstraintViolations)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .drop_column(IndexMetadata::Constraints)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    Constraints,
+}
+
+#[derive(DeriveIden)]
+enum Rebalances {
+    Table,
+    ConstraintViolations,
+}
@@ -0,0 +1,44 @@
+//! Migration for the stablecoin cash buffer of an index
+//!
+//! cash_buffer_pct is the share of the index held in a stablecoin (e.g. 5.0
+//! for 5%) and cash_buffer_symbol which one (USDC when unset); see
+//! services::cash_buffer.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .add_column_if_not_exists(ColumnDef::new(IndexMetadata::CashBufferPct).decimal().null())
+                    .add_column_if_not_exists(ColumnDef::new(IndexMetadata::CashBufferSymbol).string_len(16).null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .drop_column(IndexMetadata::CashBufferPct)
+                    .drop_column(IndexMetadata::CashBufferSymbol)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    CashBufferPct,
+    CashBufferSymbol,
+}
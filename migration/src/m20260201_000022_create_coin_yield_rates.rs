@@ -0,0 +1,73 @@
+//! Migration for staking/dividend yield accrual
+//!
+//! coin_yield_rates holds one annual yield per coin, either set manually or
+//! synced from a staking APY source; index_metadata.accrue_yield turns the
+//! accrual of those yields into an index's NAV on (see services::yield_accrual).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CoinYieldRates::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CoinYieldRates::Id))
+                    .col(string_len(CoinYieldRates::CoinId, 128).not_null().unique_key())
+                    .col(ColumnDef::new(CoinYieldRates::ApyPct).decimal().not_null())
+                    .col(string_len(CoinYieldRates::Source, 32).not_null())
+                    .col(string_len_null(CoinYieldRates::SourceRef, 128))
+                    .col(timestamp(CoinYieldRates::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(IndexMetadata::AccrueYield).boolean().not_null().default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .drop_column(IndexMetadata::AccrueYield)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(CoinYieldRates::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CoinYieldRates {
+    Table,
+    Id,
+    CoinId,
+    ApyPct,
+    Source,
+    SourceRef,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    AccrueYield,
+}
@@ -0,0 +1,40 @@
+//! Migration for leveraged and inverse indexes
+//!
+//! leverage_factor is the daily multiple of the underlying basket's return
+//! the index tracks, e.g. -1 or 2; unset means 1x. See services::leverage.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .add_column_if_not_exists(ColumnDef::new(IndexMetadata::LeverageFactor).decimal().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .drop_column(IndexMetadata::LeverageFactor)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    LeverageFactor,
+}
@@ -0,0 +1,74 @@
+//! Migration to create the index_translations table
+//!
+//! Localized name and description of an index, one row per index and locale
+//! (e.g. "fr", "pt-BR"). GET /indexes and /get-index-config pick the best
+//! one for the request's Accept-Language (see services::index_translations).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IndexTranslations::Table)
+                    .if_not_exists()
+                    .col(pk_auto(IndexTranslations::Id))
+                    .col(integer(IndexTranslations::IndexId).not_null())
+                    .col(string_len(IndexTranslations::Locale, 16).not_null())
+                    .col(string(IndexTranslations::Name).not_null())
+                    .col(text_null(IndexTranslations::Description))
+                    .col(timestamp(IndexTranslations::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(IndexTranslations::UpdatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_index_translations_index_id")
+                            .from(IndexTranslations::Table, IndexTranslations::IndexId)
+                            .to(IndexMetadata::Table, IndexMetadata::IndexId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_index_translations_index_locale")
+                    .table(IndexTranslations::Table)
+                    .col(IndexTranslations::IndexId)
+                    .col(IndexTranslations::Locale)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IndexTranslations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexTranslations {
+    Table,
+    Id,
+    IndexId,
+    Locale,
+    Name,
+    Description,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    IndexId,
+}
@@ -0,0 +1,74 @@
+//! Migration to create the rolling_stats table
+//!
+//! Daily pre-computed return statistics of coins and indexes over rolling
+//! windows (30, 90 and 365 days), one row per subject, window and day, so
+//! analytics endpoints don't recompute them from prices on every request
+//! (see services::rolling_stats).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RollingStats::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RollingStats::Id))
+                    .col(string_len(RollingStats::SubjectType, 16).not_null())
+                    .col(string_len(RollingStats::SubjectId, 128).not_null())
+                    .col(integer(RollingStats::WindowDays).not_null())
+                    .col(date(RollingStats::AsOf).not_null())
+                    .col(integer(RollingStats::Observations).not_null())
+                    .col(ColumnDef::new(RollingStats::TotalReturn).decimal().null())
+                    .col(ColumnDef::new(RollingStats::MeanReturn).decimal().null())
+                    .col(ColumnDef::new(RollingStats::Volatility).decimal().null())
+                    .col(ColumnDef::new(RollingStats::AnnualizedVolatility).decimal().null())
+                    .col(ColumnDef::new(RollingStats::MaxDrawdown).decimal().null())
+                    .col(timestamp(RollingStats::ComputedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rolling_stats_subject_window_as_of")
+                    .table(RollingStats::Table)
+                    .col(RollingStats::SubjectType)
+                    .col(RollingStats::SubjectId)
+                    .col(RollingStats::WindowDays)
+                    .col(RollingStats::AsOf)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RollingStats::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RollingStats {
+    Table,
+    Id,
+    SubjectType,
+    SubjectId,
+    WindowDays,
+    AsOf,
+    Observations,
+    TotalReturn,
+    MeanReturn,
+    Volatility,
+    AnnualizedVolatility,
+    MaxDrawdown,
+    ComputedAt,
+}
@@ -0,0 +1,94 @@
+//! Migration to create the snapshot_log table
+//!
+//! An append-only, hash-chained log of each index's rebalances and daily
+//! NAV records: every entry stores the normalized JSON of a record, its
+//! keccak256, and the keccak256 of the previous entry's hash and that one, so
+//! rewriting a published record (or a log entry) breaks the chain (see
+//! services::snapshot_log).
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SnapshotLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SnapshotLog::Id))
+                    .col(integer(SnapshotLog::IndexId).not_null())
+                    .col(integer(SnapshotLog::Seq).not_null())
+                    .col(string_len(SnapshotLog::Kind, 16).not_null())
+                    .col(string_len(SnapshotLog::RecordKey, 64).not_null())
+                    .col(date(SnapshotLog::RecordDate).not_null())
+                    .col(json_binary(SnapshotLog::Payload).not_null())
+                    .col(string_len(SnapshotLog::PayloadHash, 64).not_null())
+                    .col(string_len_null(SnapshotLog::PrevHash, 64))
+                    .col(string_len(SnapshotLog::Hash, 64).not_null())
+                    .col(timestamp(SnapshotLog::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_snapshot_log_index_seq")
+                    .table(SnapshotLog::Table)
+                    .col(SnapshotLog::IndexId)
+                    .col(SnapshotLog::Seq)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_snapshot_log_index_record")
+                    .table(SnapshotLog::Table)
+                    .col(SnapshotLog::IndexId)
+                    .col(SnapshotLog::RecordKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_snapshot_log_index_date")
+                    .table(SnapshotLog::Table)
+                    .col(SnapshotLog::IndexId)
+                    .col(SnapshotLog::RecordDate)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SnapshotLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SnapshotLog {
+    Table,
+    Id,
+    IndexId,
+    Seq,
+    Kind,
+    RecordKey,
+    RecordDate,
+    Payload,
+    PayloadHash,
+    PrevHash,
+    Hash,
+    CreatedAt,
+}
@@ -0,0 +1,47 @@
+//! Migration for on-chain anchoring of snapshot chain heads
+//!
+//! The snapshot anchor job posts an index's latest snapshot_log hash to the
+//! snapshot registry contract and records the transaction on that entry.
+//! See services::snapshot_anchor.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SnapshotLog::Table)
+                    .add_column_if_not_exists(ColumnDef::new(SnapshotLog::AnchorTxHash).string_len(66).null())
+                    .add_column_if_not_exists(ColumnDef::new(SnapshotLog::AnchorBlock).big_integer().null())
+                    .add_column_if_not_exists(ColumnDef::new(SnapshotLog::AnchoredAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SnapshotLog::Table)
+                    .drop_column(SnapshotLog::AnchorTxHash)
+                    .drop_column(SnapshotLog::AnchorBlock)
+                    .drop_column(SnapshotLog::AnchoredAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SnapshotLog {
+    Table,
+    AnchorTxHash,
+    AnchorBlock,
+    AnchoredAt,
+}
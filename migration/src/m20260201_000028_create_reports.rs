@@ -0,0 +1,70 @@
+//! Migration to create the reports table
+//!
+//! Stores every rendered scheduled report (see services::reports), one row
+//! per template, period and format, with the artifact itself and whether it
+//! was emailed to subscribers.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Reports::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Reports::Id))
+                    .col(string_len(Reports::Template, 32).not_null())
+                    .col(string_len(Reports::Format, 8).not_null())
+                    .col(date(Reports::PeriodStart).not_null())
+                    .col(date(Reports::PeriodEnd).not_null())
+                    .col(string(Reports::Title).not_null())
+                    .col(string_len(Reports::ContentType, 64).not_null())
+                    .col(binary(Reports::Content).not_null())
+                    .col(integer(Reports::Recipients).default(0))
+                    .col(timestamp_null(Reports::DeliveredAt))
+                    .col(timestamp(Reports::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reports_template_period_format")
+                    .table(Reports::Table)
+                    .col(Reports::Template)
+                    .col(Reports::PeriodStart)
+                    .col(Reports::Format)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Reports::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+    Template,
+    Format,
+    PeriodStart,
+    PeriodEnd,
+    Title,
+    ContentType,
+    Content,
+    Recipients,
+    DeliveredAt,
+    CreatedAt,
+}
@@ -0,0 +1,83 @@
+//! Indexes for GET /crypto-listings
+//!
+//! The endpoint filters crypto_listings by exchange, status and symbol (the
+//! latter case-insensitively) and pages through them newest first.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crypto_listings_exchange_status_id")
+                    .table(CryptoListings::Table)
+                    .col(CryptoListings::Exchange)
+                    .col(CryptoListings::Status)
+                    .col((CryptoListings::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_crypto_listings_status_id")
+                    .table(CryptoListings::Table)
+                    .col(CryptoListings::Status)
+                    .col((CryptoListings::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Raw SQL for the expression index symbol filters compare against
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE INDEX IF NOT EXISTS idx_crypto_listings_upper_symbol
+                ON crypto_listings (UPPER(symbol), exchange)
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_crypto_listings_upper_symbol")
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_crypto_listings_status_id")
+                    .table(CryptoListings::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_crypto_listings_exchange_status_id")
+                    .table(CryptoListings::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CryptoListings {
+    Table,
+    Id,
+    Exchange,
+    Status,
+}
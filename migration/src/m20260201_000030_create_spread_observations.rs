@@ -0,0 +1,66 @@
+//! Migration to create the spread_observations table
+//!
+//! A daily sample of the top-of-book spread of each trading pair the indexes
+//! hold (see services::spread_calibration), so the rebalance fee model can
+//! use observed spreads instead of index_metadata.exchange_avg_spread.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SpreadObservations::Table)
+                    .if_not_exists()
+                    .col(pk_auto(SpreadObservations::Id))
+                    .col(string_len(SpreadObservations::Exchange, 16).not_null())
+                    .col(string_len(SpreadObservations::TradingPair, 32).not_null())
+                    .col(string(SpreadObservations::CoinId).not_null())
+                    .col(date(SpreadObservations::Date).not_null())
+                    .col(ColumnDef::new(SpreadObservations::BestBid).decimal().not_null())
+                    .col(ColumnDef::new(SpreadObservations::BestAsk).decimal().not_null())
+                    .col(ColumnDef::new(SpreadObservations::Spread).decimal().not_null())
+                    .col(timestamp(SpreadObservations::ObservedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_spread_observations_pair_date")
+                    .table(SpreadObservations::Table)
+                    .col(SpreadObservations::Exchange)
+                    .col(SpreadObservations::TradingPair)
+                    .col(SpreadObservations::Date)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SpreadObservations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SpreadObservations {
+    Table,
+    Id,
+    Exchange,
+    TradingPair,
+    CoinId,
+    Date,
+    BestBid,
+    BestAsk,
+    Spread,
+    ObservedAt,
+}
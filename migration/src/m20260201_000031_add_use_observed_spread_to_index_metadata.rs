@@ -0,0 +1,43 @@
+//! Migration for observed-spread fees
+//!
+//! With use_observed_spread set, rebalance fees use the trailing average of
+//! the index's sampled spreads instead of exchange_avg_spread. See
+//! services::spread_calibration.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(IndexMetadata::UseObservedSpread).boolean().not_null().default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexMetadata::Table)
+                    .drop_column(IndexMetadata::UseObservedSpread)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexMetadata {
+    Table,
+    UseObservedSpread,
+}
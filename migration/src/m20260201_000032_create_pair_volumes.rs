@@ -0,0 +1,65 @@
+//! Migration to create the pair_volumes table
+//!
+//! A daily snapshot of each exchange pair's own 24h traded volume (see
+//! services::pair_volumes), so selection screens and execution quotes don't
+//! rely on CoinGecko's aggregate volume, which counts venues the indexes
+//! can't trade on.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PairVolumes::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PairVolumes::Id))
+                    .col(string_len(PairVolumes::Exchange, 16).not_null())
+                    .col(string_len(PairVolumes::TradingPair, 32).not_null())
+                    .col(string_len(PairVolumes::QuoteAsset, 8).not_null())
+                    .col(date(PairVolumes::Date).not_null())
+                    .col(ColumnDef::new(PairVolumes::BaseVolume).decimal().not_null())
+                    .col(ColumnDef::new(PairVolumes::QuoteVolume).decimal().not_null())
+                    .col(timestamp(PairVolumes::ObservedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pair_volumes_pair_date")
+                    .table(PairVolumes::Table)
+                    .col(PairVolumes::Exchange)
+                    .col(PairVolumes::TradingPair)
+                    .col(PairVolumes::Date)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PairVolumes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PairVolumes {
+    Table,
+    Id,
+    Exchange,
+    TradingPair,
+    QuoteAsset,
+    Date,
+    BaseVolume,
+    QuoteVolume,
+    ObservedAt,
+}
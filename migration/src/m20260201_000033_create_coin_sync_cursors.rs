@@ -0,0 +1,56 @@
+//! Migration to create the coin_sync_cursors table
+//!
+//! Per-coin progress of the coins historical prices sync (see
+//! services::coin_sync_cursors): the last date synced and when the coin was
+//! last attempted, so a run cut short by its credit budget or CoinGecko rate
+//! limits resumes with the coins it didn't reach.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CoinSyncCursors::Table)
+                    .if_not_exists()
+                    .col(string(CoinSyncCursors::CoinId).not_null().primary_key())
+                    .col(date_null(CoinSyncCursors::SyncedThrough))
+                    .col(timestamp(CoinSyncCursors::LastAttemptAt).not_null())
+                    .col(timestamp_null(CoinSyncCursors::LastSuccessAt))
+                    .col(integer(CoinSyncCursors::ConsecutiveFailures).not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_coin_sync_cursors_last_attempt_at")
+                    .table(CoinSyncCursors::Table)
+                    .col(CoinSyncCursors::LastAttemptAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CoinSyncCursors::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CoinSyncCursors {
+    Table,
+    CoinId,
+    SyncedThrough,
+    LastAttemptAt,
+    LastSuccessAt,
+    ConsecutiveFailures,
+}
@@ -0,0 +1,62 @@
+//! Migration to create the job_runs table
+//!
+//! One row per background job run with its duration and rows processed
+//! (see services::job_runs), the history run regressions are measured
+//! against.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobRuns::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JobRuns::Id))
+                    .col(string_len(JobRuns::JobName, 64).not_null())
+                    .col(timestamp(JobRuns::StartedAt).not_null())
+                    .col(timestamp(JobRuns::FinishedAt).not_null())
+                    .col(big_integer(JobRuns::DurationMs).not_null())
+                    .col(big_integer_null(JobRuns::RowsProcessed))
+                    .col(boolean(JobRuns::Succeeded).not_null())
+                    .col(text_null(JobRuns::Error))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_job_runs_job_name_started_at")
+                    .table(JobRuns::Table)
+                    .col(JobRuns::JobName)
+                    .col(JobRuns::StartedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobRuns::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobRuns {
+    Table,
+    Id,
+    JobName,
+    StartedAt,
+    FinishedAt,
+    DurationMs,
+    RowsProcessed,
+    Succeeded,
+    Error,
+}
@@ -0,0 +1,54 @@
+//! Migration to create the token_migrations table
+//!
+//! One row per coin merged into another (see services::coin_merge), e.g.
+//! after CoinGecko re-ids a coin, with the rows moved per table.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TokenMigrations::Table)
+                    .if_not_exists()
+                    .col(pk_auto(TokenMigrations::Id))
+                    .col(string(TokenMigrations::SourceCoinId).not_null())
+                    .col(string(TokenMigrations::TargetCoinId).not_null())
+                    .col(json_binary(TokenMigrations::RowsMoved).not_null())
+                    .col(timestamp(TokenMigrations::MergedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_token_migrations_source_coin_id")
+                    .table(TokenMigrations::Table)
+                    .col(TokenMigrations::SourceCoinId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TokenMigrations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TokenMigrations {
+    Table,
+    Id,
+    SourceCoinId,
+    TargetCoinId,
+    RowsMoved,
+    MergedAt,
+}
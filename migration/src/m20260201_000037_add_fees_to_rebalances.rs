@@ -0,0 +1,45 @@
+//! Migration to record the fees of a rebalance
+//!
+//! rebalances.portfolio_value is net of the trading fees the rebalance
+//! paid; total_fees keeps their sum and fee_breakdown the traded value and
+//! fee of each position. Both are null for rebalances stored before, and
+//! for manual and inception rebalances, which aren't charged fees.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Rebalances::TotalFees).decimal().null())
+                    .add_column_if_not_exists(ColumnDef::new(Rebalances::FeeBreakdown).json_binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rebalances::Table)
+                    .drop_column(Rebalances::TotalFees)
+                    .drop_column(Rebalances::FeeBreakdown)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Rebalances {
+    Table,
+    TotalFees,
+    FeeBreakdown,
+}
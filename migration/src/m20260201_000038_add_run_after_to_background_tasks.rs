@@ -0,0 +1,41 @@
+//! Migration to let background tasks wait before being picked up again
+//!
+//! A task deferred because its index is locked elsewhere keeps its
+//! created_at, so ordering alone would hand it straight back to the worker
+//! ahead of the tasks queued behind it. run_after holds it back until then.
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BackgroundTasks::Table)
+                    .add_column_if_not_exists(timestamp_null(BackgroundTasks::RunAfter))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BackgroundTasks::Table)
+                    .drop_column(BackgroundTasks::RunAfter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BackgroundTasks {
+    Table,
+    RunAfter,
+}
@@ -0,0 +1,50 @@
+//! Compare SeaORM entities with the live database schema
+//!
+//! Exits non-zero if any entity column is missing from the database, any
+//! table column is missing from its entity, or an entity's table doesn't
+//! exist. With --migrate, pending migrations are applied first, so running
+//! it against an empty database checks that the migrations alone produce
+//! the schema the entities expect.
+//!
+//! Usage: check_schema [--migrate]
+
+use std::env;
+
+use sea_orm::Database;
+use sea_orm_migration::MigratorTrait;
+
+use indexmaker_backend::services::schema_drift;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let migrate = match env::args().nth(1).as_deref() {
+        None => false,
+        Some("--migrate") => true,
+        Some(_) => {
+            eprintln!("Usage: check_schema [--migrate]");
+            std::process::exit(2);
+        }
+    };
+
+    dotenvy::dotenv().ok();
+    let db = Database::connect(env::var("DATABASE_URL")?).await?;
+
+    if migrate {
+        println!("Applying migrations...");
+        migration::Migrator::up(&db, None).await?;
+    }
+
+    let drift = schema_drift::check(&db).await?;
+    let entities = schema_drift::entity_schemas().len();
+
+    if drift.is_empty() {
+        println!("✅ {} entities match the database schema", entities);
+        return Ok(());
+    }
+
+    println!("❌ Schema drift ({} entities checked):", entities);
+    for item in &drift {
+        println!("  - {}", item);
+    }
+    std::process::exit(1);
+}
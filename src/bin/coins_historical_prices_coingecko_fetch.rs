@@ -5,7 +5,6 @@ use sea_orm::{
     ActiveModelTrait, ColumnTrait, Database, DatabaseConnection, EntityTrait, Order, QueryFilter,
     QueryOrder, QuerySelect, Set,
 };
-use serde::Deserialize;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use std::env;
@@ -14,13 +13,6 @@ use tokio::time::{sleep, Duration};
 use indexmaker_backend::entities::{coins, coins_historical_prices, prelude::*};
 use indexmaker_backend::services::coingecko::CoinGeckoService;
 
-#[derive(Debug, Deserialize)]
-struct MarketChartResponse {
-    prices: Vec<[f64; 2]>,
-    market_caps: Vec<[f64; 2]>,
-    total_volumes: Vec<[f64; 2]>,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
@@ -187,27 +179,7 @@ async fn fetch_and_store_prices(
     symbol: &str,
     days: &str,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let url = format!("{}/coins/{}/market_chart", coingecko.base_url(), coin_id);
-
-    let response = coingecko
-        .client()
-        .get(&url)
-        .header("x-cg-pro-api-key", coingecko.api_key())
-        .query(&[
-            ("vs_currency", "usd"),
-            ("days", days),
-            ("interval", "daily"),
-        ])
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("CoinGecko API error {}: {}", status, error_text).into());
-    }
-
-    let data: MarketChartResponse = response.json().await?;
+    let data = coingecko.fetch_daily_market_chart(coin_id, days).await?;
 
     if data.prices.is_empty() {
         return Err("No price data returned".into());
@@ -242,8 +214,8 @@ async fn fetch_and_store_prices(
             symbol: Set(symbol.to_uppercase()),
             date: Set(date),
             price: Set(Decimal::from_f64_retain(price).ok_or("Invalid price")?),
-            market_cap: Set(market_cap.and_then(|mc| Decimal::from_f64_retain(mc))),
-            volume: Set(volume.and_then(|v| Decimal::from_f64_retain(v))),
+            market_cap: Set(market_cap.and_then(Decimal::from_f64_retain)),
+            volume: Set(volume.and_then(Decimal::from_f64_retain)),
             ..Default::default()
         };
 
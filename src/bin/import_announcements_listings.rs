@@ -1,69 +1,225 @@
+//! Import announcements and crypto listings from a pg_dump file
+//!
+//! Reads the COPY blocks of `announcements` and `crypto_listings` and adds
+//! what's missing: new announcements, new listings (one per exchange), and
+//! earlier listing or later delisting dates merged into existing listings.
+//!
+//! With `--dry-run` nothing is written; the counts and changes reported are
+//! what a real run would make, apart from listings linked to announcements
+//! the same run would have imported. `--json-report` prints the summary as
+//! JSON on stdout (progress goes to stderr), or writes it to PATH with
+//! `--json-report=PATH`. `--only` imports a single table.
+//!
+//! Usage: import_announcements_listings <dump.sql> [--dry-run]
+//!            [--json-report[=PATH]] [--only=announcements|listings]
+
 #![allow(unused_assignments)]
 
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use sea_orm::{ActiveModelTrait, ColumnTrait, Database, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, Set};
+use std::sync::atomic::{AtomicBool, Ordering};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Database, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, Set, TryIntoModel};
 use regex::Regex;
 use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
 
 use indexmaker_backend::entities::{announcements, coins, coins_historical_prices, crypto_listings, prelude::*};
+use indexmaker_backend::services::crypto_listings::find_announcement_id;
+
+const USAGE: &str =
+    "Usage: import_announcements_listings <dump.sql> [--dry-run] [--json-report[=PATH]] [--only=announcements|listings]";
+
+/// Set when the JSON report goes to stdout, so progress moves to stderr
+static PROGRESS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Progress and summary output, kept off stdout when it carries the report
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if PROGRESS_TO_STDERR.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Only {
+    Announcements,
+    Listings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JsonReport {
+    Stdout,
+    File(String),
+}
+
+#[derive(Debug)]
+struct Options {
+    file_path: String,
+    dry_run: bool,
+    json_report: Option<JsonReport>,
+    only: Option<Only>,
+}
+
+impl Options {
+    fn from_args(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut file_path = None;
+        let mut dry_run = false;
+        let mut json_report = None;
+        let mut only = None;
+
+        for arg in args {
+            match arg.as_str() {
+                "--dry-run" => dry_run = true,
+                "--json-report" => json_report = Some(JsonReport::Stdout),
+                _ if arg.starts_with("--json-report=") => {
+                    let path = &arg["--json-report=".len()..];
+                    if path.is_empty() {
+                        return Err("--json-report= needs a path".to_string());
+                    }
+                    json_report = Some(JsonReport::File(path.to_string()));
+                }
+                _ if arg.starts_with("--only=") => {
+                    only = Some(match &arg["--only=".len()..] {
+                        "announcements" => Only::Announcements,
+                        "listings" => Only::Listings,
+                        other => return Err(format!("Invalid value for --only: '{}'", other)),
+                    });
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ if file_path.is_none() => file_path = Some(arg),
+                _ => return Err(format!("Unexpected argument {}", arg)),
+            }
+        }
+
+        Ok(Options {
+            file_path: file_path.ok_or("Missing dump file")?,
+            dry_run,
+            json_report,
+            only,
+        })
+    }
+
+    fn includes(&self, table: Only) -> bool {
+        self.only.is_none_or(|only| only == table)
+    }
+}
+
+/// Machine-readable summary of a run
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportReport {
+    file: String,
+    dry_run: bool,
+    only: Option<Only>,
+    announcements_found: usize,
+    listings_found: usize,
+    /// None when not imported (--only)
+    announcements: Option<ImportResult>,
+    listings: Option<ImportResult>,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables
     dotenvy::dotenv().ok();
 
-    // Usage: cargo run --bin import_announcements_listings -- dump.sql
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <dump.sql>", args[0]);
-        std::process::exit(1);
-    }
+    let options = match Options::from_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+    PROGRESS_TO_STDERR.store(options.json_report == Some(JsonReport::Stdout), Ordering::Relaxed);
 
-    let file_path = &args[1];
+    let file_path = &options.file_path;
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
     
     let db = Database::connect(&database_url).await?;
 
-    println!("Parsing {}...", file_path);
+    if options.dry_run {
+        say!("🔍 Dry run: nothing will be written");
+    }
+    say!("Parsing {}...", file_path);
     let (announcements_data, listings_data) = parse_dump_file(file_path)?;
     
-    println!("✓ Found {} announcements", announcements_data.len());
-    println!("✓ Found {} crypto listings", listings_data.len());
-    
+    say!("✓ Found {} announcements", announcements_data.len());
+    say!("✓ Found {} crypto listings", listings_data.len());
+    
+    let mut report = ImportReport {
+        file: file_path.clone(),
+        dry_run: options.dry_run,
+        only: options.only,
+        announcements_found: announcements_data.len(),
+        listings_found: listings_data.len(),
+        announcements: None,
+        listings: None,
+    };
+
     // Import announcements first
-    println!("\n📝 Importing announcements...");
-    let announcements_result = import_announcements(&db, announcements_data).await?;
+    if options.includes(Only::Announcements) {
+        say!("\n📝 Importing announcements...");
+        report.announcements = Some(import_announcements(&db, announcements_data, options.dry_run).await?);
+    }
     
     // Import crypto listings
-    println!("\n💰 Importing crypto listings...");
-    let listings_result = import_crypto_listings(&db, listings_data).await?;
-    
-    // Print summary
-    println!("\n✅ Import complete!");
-    println!("\n📝 Announcements:");
-    println!("   Imported: {}", announcements_result.imported);
-    println!("   Skipped (duplicates): {}", announcements_result.skipped);
-    if announcements_result.errors > 0 {
-        println!("   ⚠️  Errors: {}", announcements_result.errors);
+    if options.includes(Only::Listings) {
+        say!("\n💰 Importing crypto listings...");
+        report.listings = Some(import_crypto_listings(&db, listings_data, options.dry_run).await?);
     }
     
-    println!("\n💰 Crypto Listings:");
-    println!("   Imported: {}", listings_result.imported);
-    println!("   Updated (merged): {}", listings_result.updated);
-    println!("   Skipped (duplicates): {}", listings_result.skipped);
-    println!("   New tokens created: {}", listings_result.tokens_created);
-    println!("   Resolved coin_ids: {}", listings_result.resolved_coin_ids);
-    println!("   Fallback to lowercase: {}", listings_result.fallback_lowercase);
-    if listings_result.errors > 0 {
-        println!("   ⚠️  Errors: {}", listings_result.errors);
+    print_summary(&report);
+
+    match &options.json_report {
+        Some(JsonReport::Stdout) => println!("{}", serde_json::to_string_pretty(&report)?),
+        Some(JsonReport::File(path)) => {
+            std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            say!("\n📄 Report written to {}", path);
+        }
+        None => {}
     }
 
     Ok(())
 }
 
+fn print_summary(report: &ImportReport) {
+    let (verb, imported, updated, created) = if report.dry_run {
+        ("Dry run complete (nothing written)", "Would import", "Would update (merge)", "New tokens to create")
+    } else {
+        ("Import complete!", "Imported", "Updated (merged)", "New tokens created")
+    };
+    say!("\n✅ {}", verb);
+
+    if let Some(result) = &report.announcements {
+        say!("\n📝 Announcements:");
+        say!("   {}: {}", imported, result.imported);
+        say!("   Skipped (duplicates): {}", result.skipped);
+        if !result.errors.is_empty() {
+            say!("   ⚠️  Errors: {}", result.errors.len());
+        }
+    }
+    
+    if let Some(result) = &report.listings {
+        say!("\n💰 Crypto Listings:");
+        say!("   {}: {}", imported, result.imported);
+        say!("   {}: {}", updated, result.updated);
+        say!("   Skipped (duplicates): {}", result.skipped);
+        say!("   {}: {}", created, result.tokens_created);
+        say!("   Resolved coin_ids: {}", result.resolved_coin_ids);
+        say!("   Fallback to lowercase: {}", result.fallback_lowercase);
+        if !result.errors.is_empty() {
+            say!("   ⚠️  Errors: {}", result.errors.len());
+        }
+    }
+}
+
 #[derive(Debug)]
 struct AnnouncementEntry {
     title: String,
@@ -83,14 +239,54 @@ struct CryptoListingEntry {
     delisting_date: Option<String>,              // JSON string
 }
 
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct ImportResult {
     imported: usize,
     updated: usize,
     skipped: usize,
-    errors: usize,
     tokens_created: usize,
     resolved_coin_ids: usize,
     fallback_lowercase: usize,
+    /// Rows inserted, updated or created (or that would be, in a dry run)
+    changes: Vec<Change>,
+    errors: Vec<String>,
+}
+
+/// One row written by the import
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+enum Change {
+    #[serde(rename_all = "camelCase")]
+    InsertAnnouncement {
+        title: String,
+        source: String,
+        announce_date: NaiveDateTime,
+    },
+    CreateToken {
+        symbol: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    InsertListing {
+        coin_id: String,
+        exchange: String,
+        trading_pair: String,
+        status: String,
+    },
+    /// Dates merged into an existing listing
+    #[serde(rename_all = "camelCase")]
+    MergeListing {
+        coin_id: String,
+        exchange: String,
+        trading_pair: String,
+        fields: Vec<&'static str>,
+    },
+}
+
+/// Report an error on stderr and keep it for the summary
+fn record_error(errors: &mut Vec<String>, message: String) {
+    eprintln!("⚠️  {}", message);
+    errors.push(message);
 }
 
 fn parse_dump_file(
@@ -115,7 +311,7 @@ fn parse_dump_file(
         if copy_announcements_regex.is_match(&line) {
             in_announcements = true;
             in_listings = false;
-            println!("✓ Found announcements section");
+            say!("✓ Found announcements section");
             continue;
         }
         
@@ -123,18 +319,18 @@ fn parse_dump_file(
         if copy_listings_regex.is_match(&line) {
             in_listings = true;
             in_announcements = false;
-            println!("✓ Found crypto_listings section");
+            say!("✓ Found crypto_listings section");
             continue;
         }
         
         // Detect end of COPY block
         if line.trim() == "\\." {
             if in_announcements {
-                println!("✓ End of announcements section");
+                say!("✓ End of announcements section");
                 in_announcements = false;
             }
             if in_listings {
-                println!("✓ End of crypto_listings section");
+                say!("✓ End of crypto_listings section");
                 in_listings = false;
             }
             continue;
@@ -221,10 +417,12 @@ fn parse_listing_row(line: &str) -> Option<CryptoListingEntry> {
 async fn import_announcements(
     db: &DatabaseConnection,
     announcements_data: Vec<AnnouncementEntry>,
+    dry_run: bool,
 ) -> Result<ImportResult, Box<dyn std::error::Error>> {
-    let mut imported = 0;
-    let mut skipped = 0;
-    let mut errors = 0;
+    let mut result = ImportResult::default();
+    // Announcements a dry run would have inserted, so repeats in the dump
+    // are skipped as they would be
+    let mut pending: HashSet<(&str, &str, NaiveDateTime)> = HashSet::new();
     
     for (idx, entry) in announcements_data.iter().enumerate() {
         // Check if exists by (title, source, announce_date)
@@ -237,7 +435,10 @@ async fn import_announcements(
         
         match exists {
             Ok(Some(_)) => {
-                skipped += 1;
+                result.skipped += 1;
+            }
+            Ok(None) if dry_run && !pending.insert((&entry.title, &entry.source, entry.announce_date)) => {
+                result.skipped += 1;
             }
             Ok(None) => {
                 // Insert new announcement
@@ -250,42 +451,40 @@ async fn import_announcements(
                     ..Default::default()
                 };
                 
-                match new_announcement.insert(db).await {
-                    Ok(_) => imported += 1,
+                let inserted = if dry_run { Ok(()) } else { new_announcement.insert(db).await.map(|_| ()) };
+                match inserted {
+                    Ok(()) => {
+                        result.imported += 1;
+                        result.changes.push(Change::InsertAnnouncement {
+                            title: entry.title.clone(),
+                            source: entry.source.clone(),
+                            announce_date: entry.announce_date,
+                        });
+                    }
                     Err(e) => {
-                        eprintln!("⚠️  Failed to insert announcement {}: {}", idx + 1, e);
-                        errors += 1;
+                        record_error(&mut result.errors, format!("Failed to insert announcement {}: {}", idx + 1, e));
                     }
                 }
             }
             Err(e) => {
-                eprintln!("⚠️  Database query error at announcement {}: {}", idx + 1, e);
-                errors += 1;
+                record_error(&mut result.errors, format!("Database query error at announcement {}: {}", idx + 1, e));
             }
         }
         
         // Progress update every 1,000 rows
         if (idx + 1) % 1000 == 0 {
-            println!(
+            say!(
                 "   Progress: {}/{} (imported: {}, skipped: {}, errors: {})",
                 idx + 1,
                 announcements_data.len(),
-                imported,
-                skipped,
-                errors
+                result.imported,
+                result.skipped,
+                result.errors.len()
             );
         }
     }
     
-    Ok(ImportResult {
-        imported,
-        updated: 0,
-        skipped,
-        errors,
-        tokens_created: 0,
-        resolved_coin_ids: 0,
-        fallback_lowercase: 0,
-    })
+    Ok(result)
 }
 
 /// Resolve symbol to CoinGecko coin_id using highest market cap
@@ -397,22 +596,23 @@ fn extract_date_for_exchange(json_str: &str, exchange: &str) -> Option<NaiveDate
 async fn import_crypto_listings(
     db: &DatabaseConnection,
     listings_data: Vec<CryptoListingEntry>,
+    dry_run: bool,
 ) -> Result<ImportResult, Box<dyn std::error::Error>> {
-    let mut imported = 0;
-    let mut updated = 0;
-    let mut skipped = 0;
-    let mut errors = 0;
-    let mut tokens_created = 0;
-    let mut resolved_coin_ids = 0;
-    let mut fallback_lowercase = 0;
+    let mut result = ImportResult::default();
+    // Listings as a dry run would have left them, by (coin_id, exchange,
+    // trading_pair), so later rows of the dump merge into them as they would
+    let mut pending: HashMap<(String, String, String), crypto_listings::Model> = HashMap::new();
+    let mut tokens_to_create: HashSet<String> = HashSet::new();
     
     for (idx, entry) in listings_data.iter().enumerate() {
         // Parse trading pair (no exchange info)
         let trading_pair = match parse_trading_pair(&entry.token, &entry.token_name) {
             Some(tp) => tp,
             None => {
-                eprintln!("⚠️  Failed to parse trading pair: {} (token_name: {})", entry.token, entry.token_name);
-                errors += 1;
+                record_error(
+                    &mut result.errors,
+                    format!("Failed to parse trading pair: {} (token_name: {})", entry.token, entry.token_name),
+                );
                 continue;
             }
         };
@@ -449,25 +649,25 @@ async fn import_crypto_listings(
             // ✅ Step 1: Resolve symbol to coin_id
             let coin_id = match resolve_symbol_to_coin_id(db, &symbol).await? {
                 Some(id) => {
-                    resolved_coin_ids += 1;
+                    result.resolved_coin_ids += 1;
                     id
                 }
                 None => {
-                    fallback_lowercase += 1;
+                    result.fallback_lowercase += 1;
                     symbol.to_lowercase()
                 }
             };
             
             // Ensure token exists
-            match ensure_token_exists(db, &symbol).await {
+            match ensure_token_exists(db, &symbol, dry_run).await {
                 Ok(created) => {
-                    if created {
-                        tokens_created += 1;
+                    if created && (!dry_run || tokens_to_create.insert(symbol.clone())) {
+                        result.tokens_created += 1;
+                        result.changes.push(Change::CreateToken { symbol: symbol.clone() });
                     }
                 }
                 Err(e) => {
-                    eprintln!("⚠️  Failed to ensure token exists for {}: {}", symbol, e);
-                    errors += 1;
+                    record_error(&mut result.errors, format!("Failed to ensure token exists for {}: {}", symbol, e));
                     continue;
                 }
             };
@@ -489,13 +689,29 @@ async fn import_crypto_listings(
                 .as_ref()
                 .and_then(|json| extract_date_for_exchange(json, exchange));
             
+            // Link to the announcements the dates came from (imported above)
+            let listing_announcement_id = match listing_announcement_date {
+                Some(date) => find_announcement_id(db, exchange, date, None).await?,
+                None => None,
+            };
+            let delisting_announcement_id = match delisting_announcement_date {
+                Some(date) => find_announcement_id(db, exchange, date, None).await?,
+                None => None,
+            };
+            
             // ✅ Step 2: Check if listing exists (for merging)
-            let exists = CryptoListings::find()
-                .filter(crypto_listings::Column::CoinId.eq(&coin_id))
-                .filter(crypto_listings::Column::Exchange.eq(exchange))
-                .filter(crypto_listings::Column::TradingPair.eq(&trading_pair))
-                .one(db)
-                .await;
+            let key = (coin_id.clone(), exchange.clone(), trading_pair.clone());
+            let exists = match pending.get(&key) {
+                Some(listing) => Ok(Some(listing.clone())),
+                None => {
+                    CryptoListings::find()
+                        .filter(crypto_listings::Column::CoinId.eq(&coin_id))
+                        .filter(crypto_listings::Column::Exchange.eq(exchange))
+                        .filter(crypto_listings::Column::TradingPair.eq(&trading_pair))
+                        .one(db)
+                        .await
+                }
+            };
             
             match exists {
                 Ok(Some(existing_listing)) => {
@@ -506,6 +722,7 @@ async fn import_crypto_listings(
                     let existing_listing_announcement_date = existing_listing.listing_announcement_date;
                     let existing_delisting_date = existing_listing.delisting_date;
                     let existing_delisting_announcement_date = existing_listing.delisting_announcement_date;
+                    let existing_source_announcement_id = existing_listing.source_announcement_id;
                     
                     let mut active: crypto_listings::ActiveModel = existing_listing.into();
                     
@@ -515,6 +732,9 @@ async fn import_crypto_listings(
                     if listing_date.is_some() {
                         if existing_listing_date.is_none() || (listing_date < existing_listing_date) {
                             active.listing_date = Set(listing_date);
+                            if listing_announcement_id.is_some() {
+                                active.source_announcement_id = Set(listing_announcement_id);
+                            }
                             was_updated = true;
                         }
                         
@@ -529,6 +749,9 @@ async fn import_crypto_listings(
                     if delisting_date.is_some() {
                         if existing_delisting_date.is_none() || (delisting_date > existing_delisting_date) {
                             active.delisting_date = Set(delisting_date);
+                            if delisting_announcement_id.is_some() {
+                                active.source_announcement_id = Set(delisting_announcement_id);
+                            }
                             was_updated = true;
                         }
                         
@@ -542,18 +765,43 @@ async fn import_crypto_listings(
                         was_updated = true;
                     }
                     
+                    // Backfill the link on listings imported before it existed
+                    let source_announcement_id = delisting_announcement_id.or(listing_announcement_id);
+                    if existing_source_announcement_id.is_none() && source_announcement_id.is_some() && !active.source_announcement_id.is_set() {
+                        active.source_announcement_id = Set(source_announcement_id);
+                        was_updated = true;
+                    }
+                    
                     if was_updated {
                         active.updated_at = Set(Some(Utc::now().naive_utc()));
+                        let fields = merged_fields(&active);
                         
-                        match active.update(db).await {
-                            Ok(_) => updated += 1,
+                        let merged = if dry_run {
+                            active.try_into_model().map(|listing| {
+                                pending.insert(key, listing);
+                            })
+                        } else {
+                            active.update(db).await.map(|_| ())
+                        };
+                        match merged {
+                            Ok(()) => {
+                                result.updated += 1;
+                                result.changes.push(Change::MergeListing {
+                                    coin_id: coin_id.clone(),
+                                    exchange: exchange.clone(),
+                                    trading_pair: trading_pair.clone(),
+                                    fields,
+                                });
+                            }
                             Err(e) => {
-                                eprintln!("⚠️  Failed to update listing {} for exchange {}: {}", idx + 1, exchange, e);
-                                errors += 1;
+                                record_error(
+                                    &mut result.errors,
+                                    format!("Failed to update listing {} for exchange {}: {}", idx + 1, exchange, e),
+                                );
                             }
                         }
                     } else {
-                        skipped += 1;
+                        result.skipped += 1;
                     }
                 }
                 Ok(None) => {
@@ -575,55 +823,101 @@ async fn import_crypto_listings(
                         delisting_announcement_date: Set(delisting_announcement_date),
                         delisting_date: Set(delisting_date),
                         status: Set(status.to_string()),
+                        source_announcement_id: Set(delisting_announcement_id.or(listing_announcement_id)),
                         ..Default::default()
                     };
                     
-                    match new_listing.insert(db).await {
-                        Ok(_) => imported += 1,
+                    let inserted = if dry_run {
+                        pending.insert(
+                            key,
+                            crypto_listings::Model {
+                                id: 0,
+                                coin_id: coin_id.clone(),
+                                symbol: symbol.clone(),
+                                token_name: symbol.clone(),
+                                exchange: exchange.clone(),
+                                trading_pair: trading_pair.clone(),
+                                listing_announcement_date,
+                                listing_date,
+                                delisting_announcement_date,
+                                delisting_date,
+                                status: status.to_string(),
+                                created_at: None,
+                                updated_at: None,
+                                source_announcement_id: delisting_announcement_id.or(listing_announcement_id),
+                            },
+                        );
+                        Ok(())
+                    } else {
+                        new_listing.insert(db).await.map(|_| ())
+                    };
+                    match inserted {
+                        Ok(()) => {
+                            result.imported += 1;
+                            result.changes.push(Change::InsertListing {
+                                coin_id: coin_id.clone(),
+                                exchange: exchange.clone(),
+                                trading_pair: trading_pair.clone(),
+                                status: status.to_string(),
+                            });
+                        }
                         Err(e) => {
-                            eprintln!("⚠️  Failed to insert listing {} for exchange {}: {}", idx + 1, exchange, e);
-                            errors += 1;
+                            record_error(
+                                &mut result.errors,
+                                format!("Failed to insert listing {} for exchange {}: {}", idx + 1, exchange, e),
+                            );
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("⚠️  Database query error at listing {} for exchange {}: {}", idx + 1, exchange, e);
-                    errors += 1;
+                    record_error(
+                        &mut result.errors,
+                        format!("Database query error at listing {} for exchange {}: {}", idx + 1, exchange, e),
+                    );
                 }
             }
         }
         
         // Progress update every 500 rows
         if (idx + 1) % 500 == 0 {
-            println!(
+            say!(
                 "   Progress: {}/{} (imported: {}, updated: {}, skipped: {}, resolved: {}, fallback: {}, errors: {})",
                 idx + 1,
                 listings_data.len(),
-                imported,
-                updated,
-                skipped,
-                resolved_coin_ids,
-                fallback_lowercase,
-                errors
+                result.imported,
+                result.updated,
+                result.skipped,
+                result.resolved_coin_ids,
+                result.fallback_lowercase,
+                result.errors.len()
             );
         }
     }
     
-    Ok(ImportResult {
-        imported,
-        updated,
-        skipped,
-        errors,
-        tokens_created,
-        resolved_coin_ids,
-        fallback_lowercase,
-    })
+    Ok(result)
 }
 
-/// Ensure token exists in token_metadata, create if not exists
+/// Columns a listing merge sets, for the report
+fn merged_fields(active: &crypto_listings::ActiveModel) -> Vec<&'static str> {
+    [
+        ("listingDate", active.listing_date.is_set()),
+        ("listingAnnouncementDate", active.listing_announcement_date.is_set()),
+        ("delistingDate", active.delisting_date.is_set()),
+        ("delistingAnnouncementDate", active.delisting_announcement_date.is_set()),
+        ("status", active.status.is_set()),
+        ("sourceAnnouncementId", active.source_announcement_id.is_set()),
+    ]
+    .into_iter()
+    .filter_map(|(field, set)| set.then_some(field))
+    .collect()
+}
+
+/// Ensure token exists in token_metadata, create if not exists; in a dry
+/// run, only report whether it would be created
 async fn ensure_token_exists(
     db: &DatabaseConnection,
     symbol: &str,
+    dry_run: bool,
 ) -> Result<bool, Box<dyn std::error::Error>> {
     // Check if exists (case-sensitive)
     let exists = Coins::find()
@@ -634,6 +928,9 @@ async fn ensure_token_exists(
     if exists.is_some() {
         return Ok(false); // Already exists
     }
+    if dry_run {
+        return Ok(true);
+    }
     
     // Create new token
     let new_token = coins::ActiveModel {
@@ -644,4 +941,5 @@ async fn ensure_token_exists(
     
     new_token.insert(db).await?;
     Ok(true) // Created
-}
\ No newline at end of file
+}
+
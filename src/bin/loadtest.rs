@@ -0,0 +1,187 @@
+//! Load test the pricing endpoints of a running backend
+//!
+//! Sends a fixed number of GET requests to each endpoint, `concurrency` at a
+//! time, and prints latency percentiles and the error rate per endpoint. Run
+//! it against a seeded local instance (see the `seed` binary) so results are
+//! comparable between runs; the defaults target the seed index and category.
+//!
+//! Usage: loadtest [--url URL] [--concurrency N] [--requests N]
+//!                 [--index-id ID] [--category ID]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+
+use indexmaker_backend::services::seed::{SEED_CATEGORY_ID, SEED_INDEX_ID};
+
+const USAGE: &str =
+    "Usage: loadtest [--url URL] [--concurrency N] [--requests N] [--index-id ID] [--category ID]";
+
+struct Config {
+    url: String,
+    concurrency: usize,
+    requests: usize,
+    index_id: i32,
+    category: String,
+}
+
+impl Config {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Config {
+            url: "http://localhost:3002".to_string(),
+            concurrency: 16,
+            requests: 500,
+            index_id: SEED_INDEX_ID,
+            category: SEED_CATEGORY_ID.to_string(),
+        };
+
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("Missing value for {}", flag))?;
+            let invalid = |_| format!("Invalid value for {}: '{}'", flag, value);
+            match flag.as_str() {
+                "--url" => config.url = value.trim_end_matches('/').to_string(),
+                "--concurrency" => config.concurrency = value.parse().map_err(invalid)?,
+                "--requests" => config.requests = value.parse().map_err(invalid)?,
+                "--index-id" => config.index_id = value.parse().map_err(invalid)?,
+                "--category" => config.category = value,
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        if config.concurrency == 0 || config.requests == 0 {
+            return Err("--concurrency and --requests must be positive".to_string());
+        }
+        Ok(config)
+    }
+
+    fn endpoints(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("GET /indexes", format!("{}/indexes", self.url)),
+            (
+                "GET /indexes/{id}/last-price",
+                format!("{}/indexes/{}/last-price", self.url, self.index_id),
+            ),
+            (
+                "GET /api/market-cap/top-category",
+                format!("{}/api/market-cap/top-category?category_id={}", self.url, self.category),
+            ),
+        ]
+    }
+}
+
+/// Outcome of one endpoint's run
+struct Report {
+    latencies: Vec<Duration>,
+    errors: usize,
+    elapsed: Duration,
+}
+
+impl Report {
+    /// Nearest-rank percentile of the recorded latencies
+    fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = ((p / 100.0) * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+
+    fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.latencies.len().max(1) as f64 * 100.0
+    }
+
+    fn throughput(&self) -> f64 {
+        self.latencies.len() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Send `requests` GETs to `url` from `concurrency` workers
+async fn run(client: &Client, url: &str, concurrency: usize, requests: usize) -> Report {
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_string();
+            let next = next.clone();
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                while next.fetch_add(1, Ordering::Relaxed) < requests {
+                    let sent = Instant::now();
+                    // Read the whole body so latency includes serialization
+                    let ok = match client.get(&url).send().await {
+                        Ok(response) => {
+                            let success = response.status().is_success();
+                            response.bytes().await.is_ok() && success
+                        }
+                        Err(_) => false,
+                    };
+                    samples.push((sent.elapsed(), ok));
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::with_capacity(requests);
+    let mut errors = 0;
+    for worker in workers {
+        for (latency, ok) in worker.await.unwrap_or_default() {
+            latencies.push(latency);
+            if !ok {
+                errors += 1;
+            }
+        }
+    }
+    latencies.sort();
+
+    Report { latencies, errors, elapsed: started.elapsed() }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .pool_max_idle_per_host(config.concurrency)
+        .build()?;
+
+    println!(
+        "Load testing {} ({} requests per endpoint, concurrency {})\n",
+        config.url, config.requests, config.concurrency
+    );
+    println!(
+        "{:<34} {:>9} {:>9} {:>9} {:>9} {:>8} {:>8}",
+        "endpoint", "p50 ms", "p95 ms", "p99 ms", "max ms", "req/s", "errors"
+    );
+
+    for (name, url) in config.endpoints() {
+        let report = run(&client, &url, config.concurrency, config.requests).await;
+        println!(
+            "{:<34} {:>9.1} {:>9.1} {:>9.1} {:>9.1} {:>8.0} {:>7.1}%",
+            name,
+            ms(report.percentile(50.0)),
+            ms(report.percentile(95.0)),
+            ms(report.percentile(99.0)),
+            ms(report.percentile(100.0)),
+            report.throughput(),
+            report.error_rate(),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Rewrite stored rebalances in the current coins schema version
+//!
+//! One-off upgrade of rebalances.coins entries written before they were
+//! versioned (see `services::rebalance_schema`). Readers upgrade old entries
+//! on the fly, so this is only needed for queries reading the JSON in SQL.
+//! Safe to re-run; rows already current are left alone. With --dry-run,
+//! only reports what would be rewritten.
+//!
+//! Usage: migrate_rebalance_schema [--dry-run]
+
+use std::env;
+
+use sea_orm::Database;
+
+use indexmaker_backend::services::rebalance_schema::{self, CURRENT_VERSION};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let dry_run = match env::args().nth(1).as_deref() {
+        None => false,
+        Some("--dry-run") => true,
+        Some(_) => {
+            eprintln!("Usage: migrate_rebalance_schema [--dry-run]");
+            std::process::exit(2);
+        }
+    };
+
+    dotenvy::dotenv().ok();
+    let db = Database::connect(env::var("DATABASE_URL")?).await?;
+
+    println!("Upgrading rebalances to coins schema version {}...", CURRENT_VERSION);
+    let summary = rebalance_schema::upgrade_stored(&db, dry_run).await?;
+
+    let verb = if dry_run { "would be upgraded" } else { "upgraded" };
+    println!("✅ {} rebalances scanned, {} {}", summary.scanned, summary.upgraded, verb);
+    if summary.failed.is_empty() {
+        return Ok(());
+    }
+
+    println!("❌ {} rebalances can't be upgraded:", summary.failed.len());
+    for (id, error) in &summary.failed {
+        println!("  - rebalance {}: {}", id, error);
+    }
+    std::process::exit(1);
+}
@@ -0,0 +1,49 @@
+//! Populate a local or test database with a small realistic dataset
+//!
+//! Applies pending migrations, then seeds coins with price history, a
+//! category, and one index (SEED) with rebalances, daily prices and
+//! blockchain events. Safe to re-run; see `services::seed`.
+//!
+//! Usage: seed [days]   (default 120 days of history ending today)
+
+use std::env;
+
+use chrono::Utc;
+use sea_orm::Database;
+use sea_orm_migration::MigratorTrait;
+
+use indexmaker_backend::services::seed::{self, SEED_INDEX_ID, SEED_INDEX_SYMBOL};
+
+const DEFAULT_DAYS: i64 = 120;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let days = match env::args().nth(1) {
+        None => DEFAULT_DAYS,
+        Some(arg) => match arg.parse::<i64>() {
+            Ok(days) if days > 0 => days,
+            _ => {
+                eprintln!("Usage: seed [days]");
+                std::process::exit(2);
+            }
+        },
+    };
+
+    dotenvy::dotenv().ok();
+    let db = Database::connect(env::var("DATABASE_URL")?).await?;
+
+    println!("Applying migrations...");
+    migration::Migrator::up(&db, None).await?;
+
+    println!("Seeding {} days of history...", days);
+    let summary = seed::seed(&db, Utc::now().date_naive(), days).await?;
+
+    println!("✅ Seeded index {} ({}):", SEED_INDEX_ID, SEED_INDEX_SYMBOL);
+    println!("  coins:          {}", summary.coins);
+    println!("  coin prices:    {}", summary.prices);
+    println!("  rebalances:     {}", summary.rebalances);
+    println!("  daily prices:   {}", summary.daily_prices);
+    println!("  events:         {}", summary.events);
+
+    Ok(())
+}
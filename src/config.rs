@@ -0,0 +1,186 @@
+//! Server-wide settings read from the environment
+//!
+//! Settings used by a single service stay next to it (e.g. AuthConfig,
+//! ReconciliationConfig); this module holds the ones several endpoints share.
+
+use crate::models::composition::CompositionLimitOverrides;
+
+const ENV_MAX_ASSET_WEIGHT_BPS: &str = "COMPOSITION_MAX_ASSET_WEIGHT_BPS";
+const ENV_MIN_ASSET_WEIGHT_BPS: &str = "COMPOSITION_MIN_ASSET_WEIGHT_BPS";
+const ENV_MAX_ASSETS: &str = "COMPOSITION_MAX_ASSETS";
+
+/// 100% in basis points
+pub const TOTAL_WEIGHT_BPS: u128 = 10_000;
+
+/// Limits on the weights of an ITP or generated composition
+///
+/// Enforced by ITP creation (services::itp_validation) and composition
+/// generation. Defaults impose nothing beyond weights summing to 100%.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompositionLimits {
+    pub max_asset_weight_bps: u128,
+    pub min_asset_weight_bps: u128,
+    /// None for no limit
+    pub max_assets: Option<usize>,
+}
+
+impl Default for CompositionLimits {
+    fn default() -> Self {
+        Self {
+            max_asset_weight_bps: TOTAL_WEIGHT_BPS,
+            min_asset_weight_bps: 0,
+            max_assets: None,
+        }
+    }
+}
+
+impl CompositionLimits {
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(ENV_MAX_ASSET_WEIGHT_BPS).ok().as_deref(),
+            std::env::var(ENV_MIN_ASSET_WEIGHT_BPS).ok().as_deref(),
+            std::env::var(ENV_MAX_ASSETS).ok().as_deref(),
+        )
+    }
+
+    /// Build from raw setting values; invalid values fall back to the defaults
+    fn from_values(max_weight: Option<&str>, min_weight: Option<&str>, max_assets: Option<&str>) -> Self {
+        let defaults = Self::default();
+        let bps = |name: &str, value: Option<&str>, default: u128| match value.map(|v| v.trim().parse::<u128>()) {
+            Some(Ok(v)) if v <= TOTAL_WEIGHT_BPS => v,
+            Some(_) => {
+                tracing::warn!("Invalid {}, using {}", name, default);
+                default
+            }
+            None => default,
+        };
+
+        let mut limits = Self {
+            max_asset_weight_bps: bps(ENV_MAX_ASSET_WEIGHT_BPS, max_weight, defaults.max_asset_weight_bps),
+            min_asset_weight_bps: bps(ENV_MIN_ASSET_WEIGHT_BPS, min_weight, defaults.min_asset_weight_bps),
+            max_assets: match max_assets.map(|v| v.trim().parse::<usize>()) {
+                Some(Ok(v)) if v > 0 => Some(v),
+                Some(_) => {
+                    tracing::warn!("Invalid {}, using no limit", ENV_MAX_ASSETS);
+                    None
+                }
+                None => None,
+            },
+        };
+        if limits.max_asset_weight_bps == 0 || limits.min_asset_weight_bps > limits.max_asset_weight_bps {
+            tracing::warn!(
+                "{} must be positive and at least {}, using defaults",
+                ENV_MAX_ASSET_WEIGHT_BPS,
+                ENV_MIN_ASSET_WEIGHT_BPS
+            );
+            limits.max_asset_weight_bps = defaults.max_asset_weight_bps;
+            limits.min_asset_weight_bps = defaults.min_asset_weight_bps;
+        }
+        limits
+    }
+
+    /// These limits narrowed by a request's own; looser request limits are ignored
+    pub fn narrowed(&self, overrides: Option<&CompositionLimitOverrides>) -> Self {
+        let Some(overrides) = overrides else {
+            return *self;
+        };
+
+        Self {
+            max_asset_weight_bps: overrides
+                .max_asset_weight_bps
+                .map_or(self.max_asset_weight_bps, |max| max.min(self.max_asset_weight_bps)),
+            min_asset_weight_bps: overrides
+                .min_asset_weight_bps
+                .map_or(self.min_asset_weight_bps, |min| min.max(self.min_asset_weight_bps)),
+            max_assets: match (self.max_assets, overrides.max_assets) {
+                (Some(global), Some(requested)) => Some(global.min(requested)),
+                (global, requested) => global.or(requested),
+            },
+        }
+    }
+
+    /// Why these limits can't be met by any composition, if they can't
+    pub fn infeasible(&self) -> Option<String> {
+        if self.min_asset_weight_bps > self.max_asset_weight_bps {
+            return Some(format!(
+                "Minimum asset weight {} bps is above the maximum {} bps",
+                self.min_asset_weight_bps, self.max_asset_weight_bps
+            ));
+        }
+        if self.max_asset_weight_bps == 0 {
+            return Some("Maximum asset weight must be positive".to_string());
+        }
+        if let Some(max_assets) = self
+            .max_assets
+            .filter(|max_assets| (*max_assets as u128) * self.max_asset_weight_bps < TOTAL_WEIGHT_BPS)
+        {
+            return Some(format!(
+                "{} assets capped at {} bps can't reach 100%",
+                max_assets, self.max_asset_weight_bps
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_values() {
+        assert_eq!(CompositionLimits::from_values(None, None, None), CompositionLimits::default());
+
+        let limits = CompositionLimits::from_values(Some("3000"), Some(" 100 "), Some("20"));
+        assert_eq!(limits.max_asset_weight_bps, 3000);
+        assert_eq!(limits.min_asset_weight_bps, 100);
+        assert_eq!(limits.max_assets, Some(20));
+
+        let limits = CompositionLimits::from_values(Some("12000"), Some("x"), Some("0"));
+        assert_eq!(limits, CompositionLimits::default());
+
+        // Min above max falls back to the default pair
+        let limits = CompositionLimits::from_values(Some("1000"), Some("2000"), None);
+        assert_eq!(limits.max_asset_weight_bps, TOTAL_WEIGHT_BPS);
+        assert_eq!(limits.min_asset_weight_bps, 0);
+    }
+
+    #[test]
+    fn test_narrowed_only_tightens() {
+        let global = CompositionLimits { max_asset_weight_bps: 3000, min_asset_weight_bps: 100, max_assets: Some(20) };
+        assert_eq!(global.narrowed(None), global);
+
+        let looser = CompositionLimitOverrides {
+            max_asset_weight_bps: Some(5000),
+            min_asset_weight_bps: Some(0),
+            max_assets: Some(50),
+        };
+        assert_eq!(global.narrowed(Some(&looser)), global);
+
+        let tighter = CompositionLimitOverrides {
+            max_asset_weight_bps: Some(2000),
+            min_asset_weight_bps: Some(500),
+            max_assets: Some(10),
+        };
+        let narrowed = global.narrowed(Some(&tighter));
+        assert_eq!(narrowed.max_asset_weight_bps, 2000);
+        assert_eq!(narrowed.min_asset_weight_bps, 500);
+        assert_eq!(narrowed.max_assets, Some(10));
+
+        let unlimited = CompositionLimits::default();
+        assert_eq!(unlimited.narrowed(Some(&tighter)).max_assets, Some(10));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        assert_eq!(CompositionLimits::default().infeasible(), None);
+
+        let limits = CompositionLimits { max_asset_weight_bps: 3000, min_asset_weight_bps: 0, max_assets: Some(3) };
+        assert!(limits.infeasible().is_some());
+        let limits = CompositionLimits { max_assets: Some(4), ..limits };
+        assert_eq!(limits.infeasible(), None);
+
+        let limits = CompositionLimits { max_asset_weight_bps: 1000, min_asset_weight_bps: 2000, max_assets: None };
+        assert!(limits.infeasible().is_some());
+    }
+}
@@ -0,0 +1,39 @@
+//! SeaORM Entity for api_keys table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "api_keys")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Who the key was issued to
+    pub name: String,
+    /// First characters of the key, to recognize it in listings
+    pub key_prefix: String,
+    /// Hex keccak256 of the full key
+    #[sea_orm(unique)]
+    pub key_hash: String,
+    /// "public", "partner" or "admin"
+    pub tier: String,
+    /// Requests allowed per UTC day; None is unlimited
+    pub daily_quota: Option<i32>,
+    pub active: bool,
+    pub created_at: DateTime,
+    pub revoked_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::api_key_usage::Entity")]
+    ApiKeyUsage,
+}
+
+impl Related<super::api_key_usage::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiKeyUsage.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
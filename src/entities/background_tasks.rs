@@ -0,0 +1,40 @@
+//! SeaORM Entity for background_tasks table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "background_tasks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Kind of work (see services::background_tasks::task_types)
+    pub task_type: String,
+    /// Index the task works on, if any
+    pub index_id: Option<i32>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub payload: Option<Json>,
+    /// pending, running, completed, failed
+    pub status: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub attempts: i32,
+    pub started_at: Option<DateTime>,
+    pub finished_at: Option<DateTime>,
+    pub created_at: DateTime,
+    /// Bumped periodically while running (heartbeat)
+    pub updated_at: DateTime,
+    /// Units of work finished so far (e.g. rebalance dates backfilled)
+    pub progress_done: Option<i32>,
+    /// Units of work planned
+    pub progress_total: Option<i32>,
+    /// Date currently being processed
+    pub progress_date: Option<Date>,
+    /// Not picked up before this time (set when the task is deferred)
+    pub run_after: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
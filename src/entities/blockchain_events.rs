@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    #[sea_orm(unique)]
+    /// Unique together with log_index and network
     pub tx_hash: String,
     pub block_number: i32,
     pub log_index: i32,
@@ -19,6 +19,16 @@ pub struct Model {
     pub amount: Option<Decimal>,
     pub quantity: Option<Decimal>,
     pub timestamp: Option<DateTimeWithTimeZone>,
+    /// Raw uint256 amount as emitted on-chain (decimal string)
+    #[sea_orm(column_type = "Text", nullable)]
+    pub raw_amount: Option<String>,
+    /// Decimals of `raw_amount`
+    pub decimals: Option<i32>,
+    /// Raw uint256 quantity as emitted on-chain (decimal string)
+    #[sea_orm(column_type = "Text", nullable)]
+    pub raw_quantity: Option<String>,
+    /// Decimals of `raw_quantity`
+    pub quantity_decimals: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,28 @@
+//! SeaORM Entity for category_change_events table
+//!
+//! Append-only log of category membership changes produced by
+//! category_membership_sync. `delivered_at` is set by downstream consumers.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "category_change_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub category_id: String,
+    pub coin_id: String,
+    pub symbol: Option<String>,
+    /// "added" or "removed"
+    pub change_type: String,
+    /// Date the membership change took effect (matches added_date/removed_date)
+    pub effective_at: DateTime,
+    pub delivered_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
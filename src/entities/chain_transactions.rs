@@ -0,0 +1,36 @@
+//! SeaORM Entity for chain_transactions table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "chain_transactions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub tx_hash: String,
+    /// What the transaction did (see `services::chain_spend::kinds`)
+    pub kind: String,
+    /// What it was sent for, e.g. "rebalance:42" or "itp:SYMBOL"
+    pub reference: Option<String>,
+    pub chain_id: i64,
+    pub from_address: String,
+    pub to_address: Option<String>,
+    pub block_number: i64,
+    /// Reverted transactions pay for their gas too
+    pub succeeded: bool,
+    pub gas_used: i64,
+    pub effective_gas_price_wei: Decimal,
+    /// gas_used * effective_gas_price_wei, in ETH
+    pub cost_eth: Decimal,
+    /// ETH price of the day the transaction was sent, if known
+    pub eth_price_usd: Option<Decimal>,
+    pub cost_usd: Option<Decimal>,
+    pub sent_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
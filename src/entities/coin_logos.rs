@@ -0,0 +1,22 @@
+//! SeaORM Entity for coin_logos table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "coin_logos")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub coin_id: String,
+    /// coins.logo_address the image was fetched from
+    pub source_url: String,
+    pub content_type: String,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+    pub fetched_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,22 @@
+//! SeaORM Entity for coin_sync_cursors table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "coin_sync_cursors")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub coin_id: String,
+    /// Latest date of the coin's last successful fetch
+    pub synced_through: Option<Date>,
+    pub last_attempt_at: DateTime,
+    pub last_success_at: Option<DateTime>,
+    /// Failed attempts since the last success
+    pub consecutive_failures: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
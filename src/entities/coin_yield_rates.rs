@@ -0,0 +1,25 @@
+//! SeaORM Entity for coin_yield_rates table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "coin_yield_rates")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub coin_id: String,
+    /// Annual yield in percent, e.g. 3.2 for 3.2%
+    pub apy_pct: Decimal,
+    /// manual or defillama
+    pub source: String,
+    /// Source pool the rate was synced from
+    pub source_ref: Option<String>,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
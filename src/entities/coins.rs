@@ -19,6 +19,9 @@ pub struct Model {
     pub updated_at: Option<DateTime>,
     pub active: bool,
     pub logo_address: Option<String>,
+    /// Coin no longer appears in CoinGecko's coin list (soft-deleted)
+    pub deactivated: bool,
+    pub deactivated_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -20,9 +20,25 @@ pub struct Model {
     pub status: String,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
+    pub source_announcement_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::announcements::Entity",
+        from = "Column::SourceAnnouncementId",
+        to = "super::announcements::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Announcements,
+}
+
+impl Related<super::announcements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Announcements.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,19 @@
+//! SeaORM Entity for feature_flags table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "feature_flags")]
+pub struct Model {
+    /// One of services::feature_flags::flags
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key: String,
+    pub enabled: bool,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,37 @@
+//! SeaORM Entity for index_deployments table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "index_deployments")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub index_id: i32,
+    /// Network name as used in blockchain_events.network (e.g. "base")
+    pub network: String,
+    /// Lowercased token contract address
+    pub contract_address: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::index_metadata::Entity",
+        from = "Column::IndexId",
+        to = "super::index_metadata::Column::IndexId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    IndexMetadata,
+}
+
+impl Related<super::index_metadata::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IndexMetadata.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
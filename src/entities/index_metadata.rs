@@ -29,12 +29,29 @@ pub struct Model {
     pub blacklisted_categories: Option<Json>,
     pub top_x: Option<i32>,
     pub skip_backfill: bool,
+    /// Sector caps and constituent limits, see services::index_constraints
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub constraints: Option<Json>,
+    /// Percent of the index held in a stablecoin, see services::cash_buffer
+    pub cash_buffer_pct: Option<Decimal>,
+    pub cash_buffer_symbol: Option<String>,
+    /// Accrue constituents' yields into NAV, see services::yield_accrual
+    pub accrue_yield: bool,
+    /// Daily multiple of the basket's return (e.g. -1, 2), see services::leverage
+    pub leverage_factor: Option<Decimal>,
+    /// Charge rebalance spreads at the observed trailing average rather than
+    /// exchange_avg_spread, see services::spread_calibration
+    pub use_observed_spread: bool,
+    /// active, paused or retired, see services::index_status
+    pub status: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::index_constituents::Entity")]
     IndexConstituents,
+    #[sea_orm(has_many = "super::index_deployments::Entity")]
+    IndexDeployments,
     #[sea_orm(has_many = "super::rebalances::Entity")]
     Rebalances,
 }
@@ -45,6 +62,12 @@ impl Related<super::index_constituents::Entity> for Entity {
     }
 }
 
+impl Related<super::index_deployments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IndexDeployments.def()
+    }
+}
+
 impl Related<super::rebalances::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Rebalances.def()
@@ -0,0 +1,39 @@
+//! SeaORM Entity for index_translations table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "index_translations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub index_id: i32,
+    /// Normalized language tag, e.g. "fr" or "pt-BR"
+    pub locale: String,
+    pub name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::index_metadata::Entity",
+        from = "Column::IndexId",
+        to = "super::index_metadata::Column::IndexId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    IndexMetadata,
+}
+
+impl Related<super::index_metadata::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IndexMetadata.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,25 @@
+//! SeaORM Entity for index_tvl table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "index_tvl")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub index_id: i32,
+    pub date: Date,
+    /// Index tokens minted minus burned by the end of `date`
+    pub net_supply: Decimal,
+    /// daily_prices.price on `date`
+    pub nav: Decimal,
+    /// net_supply * nav
+    pub tvl_usd: Decimal,
+    pub recorded_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+//! SeaORM Entity for itp_drift_checks table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "itp_drift_checks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub itp_id: i32,
+    pub date: Date,
+    /// Day the target weights were set (the ITP's creation date)
+    pub base_date: Date,
+    /// Largest |current - target| weight of a constituent, in basis points
+    pub max_drift_bps: i32,
+    /// Share of the ITP's value a rebalance would move, in basis points
+    pub total_drift_bps: i32,
+    /// Alert threshold in effect for this check
+    pub threshold_bps: i32,
+    /// max_drift_bps reached the threshold: a rebalance is due
+    pub flagged: bool,
+    /// Per-constituent weights and prices (models::itp_drift::ItpDriftConstituent)
+    #[sea_orm(column_type = "JsonBinary")]
+    pub constituents: Json,
+    pub checked_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::itps::Entity",
+        from = "Column::ItpId",
+        to = "super::itps::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Itps,
+}
+
+impl Related<super::itps::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Itps.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,57 @@
+//! SeaORM Entity for itp_orders table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "itp_orders")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Public identifier (UUID) quoted to support
+    #[sea_orm(unique)]
+    pub order_id: String,
+    pub itp_id: i32,
+    /// Lowercase 0x address of the wallet that submitted the order
+    pub wallet_address: String,
+    /// "mint" or "redeem"
+    pub side: String,
+    /// Collateral (USDC) for a mint, ITP tokens for a redeem
+    pub amount: Decimal,
+    /// pending, filled, settled
+    pub status: String,
+    /// Transaction the wallet submitted the order in, if given
+    pub submit_tx_hash: Option<String>,
+    /// blockchain_events row that filled the order
+    #[sea_orm(unique)]
+    pub fill_event_id: Option<i32>,
+    pub fill_tx_hash: Option<String>,
+    pub filled_at: Option<DateTime>,
+    /// blockchain_events row that settled the order
+    #[sea_orm(unique)]
+    pub settle_event_id: Option<i32>,
+    pub settle_tx_hash: Option<String>,
+    pub settled_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::itps::Entity",
+        from = "Column::ItpId",
+        to = "super::itps::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Itps,
+}
+
+impl Related<super::itps::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Itps.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,32 @@
+//! SeaORM Entity for job_failures table (dead-letter queue)
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "job_failures")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Job that produced the failure (see sync_status::jobs)
+    pub job_name: String,
+    /// "coin" or "index"
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Job-specific data needed to replay the item
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub payload: Option<Json>,
+    #[sea_orm(column_type = "Text")]
+    pub error: String,
+    pub attempt_count: i32,
+    /// pending, resolved, discarded, exhausted
+    pub status: String,
+    pub next_retry_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,25 @@
+//! SeaORM Entity for labels table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "labels")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// "index" or "coin" (see services::labels::entity_types)
+    pub entity_type: String,
+    /// index_metadata.index_id or coins.coin_id, as a string
+    pub entity_id: String,
+    /// Lowercase label key, e.g. "theme"
+    pub key: String,
+    /// Lowercase label value, e.g. "ai"
+    pub value: String,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,41 @@
+//! SeaORM Entity for methodology_documents table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "methodology_documents")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub index_id: i32,
+    /// 1-based, increasing per index
+    pub version: i32,
+    /// "markdown" or "text"
+    pub format: String,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    /// First day this version applies to
+    pub effective_from: Date,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::index_metadata::Entity",
+        from = "Column::IndexId",
+        to = "super::index_metadata::Column::IndexId",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    IndexMetadata,
+}
+
+impl Related<super::index_metadata::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IndexMetadata.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
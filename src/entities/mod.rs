@@ -18,5 +18,34 @@ pub mod rebalances;
 pub mod subscriptions;
 pub mod sync_status;
 pub mod operations;
+pub mod category_change_events;
+pub mod job_failures;
+pub mod background_tasks;
+pub mod index_deployments;
+pub mod price_reconciliation_checks;
+pub mod methodology_documents;
+pub mod api_keys;
+pub mod api_key_usage;
+pub mod auth_nonces;
+pub mod itp_drift_checks;
+pub mod itp_orders;
+pub mod solvers;
+pub mod labels;
+pub mod feature_flags;
+pub mod coin_logos;
+pub mod index_tvl;
 
 pub mod prelude;
+pub mod rebalance_approvals;
+pub mod chain_transactions;
+pub mod wallet_balance_checks;
+pub mod coin_yield_rates;
+pub mod index_translations;
+pub mod rolling_stats;
+pub mod snapshot_log;
+pub mod reports;
+pub mod spread_observations;
+pub mod pair_volumes;
+pub mod coin_sync_cursors;
+pub mod job_runs;
+pub mod token_migrations;
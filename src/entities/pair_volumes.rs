@@ -0,0 +1,27 @@
+//! SeaORM Entity for pair_volumes table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "pair_volumes")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub exchange: String,
+    /// e.g. "BTCUSDC"
+    pub trading_pair: String,
+    /// e.g. "USDC"
+    pub quote_asset: String,
+    pub date: Date,
+    /// Base asset traded over the 24h before observed_at
+    pub base_volume: Decimal,
+    /// Quote asset traded over the same window, i.e. USD for stablecoin quotes
+    pub quote_volume: Decimal,
+    pub observed_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
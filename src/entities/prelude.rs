@@ -17,4 +17,33 @@ pub use super::market_cap_rankings::Entity as MarketCapRankings;
 pub use super::rebalances::Entity as Rebalances;
 pub use super::subscriptions::Entity as Subscriptions;
 pub use super::operations::Entity as Operations;
+pub use super::category_change_events::Entity as CategoryChangeEvents;
+pub use super::job_failures::Entity as JobFailures;
+pub use super::background_tasks::Entity as BackgroundTasks;
+pub use super::index_deployments::Entity as IndexDeployments;
+pub use super::price_reconciliation_checks::Entity as PriceReconciliationChecks;
+pub use super::methodology_documents::Entity as MethodologyDocuments;
+pub use super::api_keys::Entity as ApiKeys;
+pub use super::api_key_usage::Entity as ApiKeyUsage;
+pub use super::auth_nonces::Entity as AuthNonces;
+pub use super::itp_drift_checks::Entity as ItpDriftChecks;
+pub use super::itp_orders::Entity as ItpOrders;
+pub use super::solvers::Entity as Solvers;
+pub use super::labels::Entity as Labels;
+pub use super::feature_flags::Entity as FeatureFlags;
+pub use super::coin_logos::Entity as CoinLogos;
+pub use super::index_tvl::Entity as IndexTvl;
 // Note: sync_status is imported directly in services/sync_status.rs
+pub use super::rebalance_approvals::Entity as RebalanceApprovals;
+pub use super::chain_transactions::Entity as ChainTransactions;
+pub use super::wallet_balance_checks::Entity as WalletBalanceChecks;
+pub use super::coin_yield_rates::Entity as CoinYieldRates;
+pub use super::index_translations::Entity as IndexTranslations;
+pub use super::rolling_stats::Entity as RollingStats;
+pub use super::snapshot_log::Entity as SnapshotLog;
+pub use super::reports::Entity as Reports;
+pub use super::spread_observations::Entity as SpreadObservations;
+pub use super::pair_volumes::Entity as PairVolumes;
+pub use super::coin_sync_cursors::Entity as CoinSyncCursors;
+pub use super::job_runs::Entity as JobRuns;
+pub use super::token_migrations::Entity as TokenMigrations;
@@ -0,0 +1,29 @@
+//! SeaORM Entity for price_reconciliation_checks table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "price_reconciliation_checks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub coin_id: String,
+    pub symbol: String,
+    pub date: Date,
+    /// Reference source, e.g. "binance"
+    pub source: String,
+    /// coins_historical_prices.price on `date`
+    pub stored_price: Decimal,
+    /// Source's price at the same instant (00:00 UTC on `date`)
+    pub reference_price: Decimal,
+    /// |stored - reference| / reference, in basis points
+    pub deviation_bps: i32,
+    pub flagged: bool,
+    pub checked_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,27 @@
+//! SeaORM Entity for rebalance_approvals table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "rebalance_approvals")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub rebalance_id: i32,
+    /// pending, approved, rejected
+    pub status: String,
+    /// Admin credential that asked for the approval (see services::rebalance_approvals)
+    pub requested_by: String,
+    /// Admin credential that approved or rejected it
+    pub decided_by: Option<String>,
+    /// Pending: must be decided by then; approved: must be deployed by then
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+    pub decided_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
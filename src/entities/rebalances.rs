@@ -18,7 +18,18 @@ pub struct Model {
     pub deployed: Option<bool>,
     pub deployed_at: Option<DateTime>,
     pub tx_hash: Option<String>,
+    /// Block the weight update transaction was mined in
+    pub deployed_block: Option<i64>,
     pub created_at: Option<DateTime>,
+    /// Composition constraints the selection broke, see services::index_constraints
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub constraint_violations: Option<Json>,
+    /// Trading fees paid, already taken out of portfolio_value; None for
+    /// rebalances without computed fees
+    pub total_fees: Option<Decimal>,
+    /// Traded value and fee per position, see rebalance_math::CoinFee
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub fee_breakdown: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,31 @@
+//! SeaORM Entity for reports table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "reports")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// "weekly_performance" or "monthly_rebalance"
+    pub template: String,
+    /// "html", "pdf" or "csv"
+    pub format: String,
+    pub period_start: Date,
+    /// Last day covered, inclusive
+    pub period_end: Date,
+    pub title: String,
+    pub content_type: String,
+    #[serde(skip)]
+    pub content: Vec<u8>,
+    /// Subscribers the report was emailed to
+    pub recipients: i32,
+    pub delivered_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
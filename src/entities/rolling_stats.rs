@@ -0,0 +1,35 @@
+//! SeaORM Entity for rolling_stats table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "rolling_stats")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// "coin" or "index"
+    pub subject_type: String,
+    /// coin_id, or index_id as text
+    pub subject_id: String,
+    pub window_days: i32,
+    /// Last day of the window
+    pub as_of: Date,
+    /// Daily log returns in the window
+    pub observations: i32,
+    /// Price change from the first to the last price in the window
+    pub total_return: Option<Decimal>,
+    /// Mean daily log return
+    pub mean_return: Option<Decimal>,
+    /// Standard deviation of daily log returns
+    pub volatility: Option<Decimal>,
+    pub annualized_volatility: Option<Decimal>,
+    /// Largest peak-to-trough fall in the window, as a positive fraction
+    pub max_drawdown: Option<Decimal>,
+    pub computed_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
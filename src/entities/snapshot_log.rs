@@ -0,0 +1,38 @@
+//! SeaORM Entity for snapshot_log table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "snapshot_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub index_id: i32,
+    /// Position in the index's chain, from 1
+    pub seq: i32,
+    /// "rebalance" or "daily_price"
+    pub kind: String,
+    /// Identifies the logged record, e.g. "rebalance:1735689600"
+    pub record_key: String,
+    pub record_date: Date,
+    /// The record's normalized JSON
+    #[sea_orm(column_type = "JsonBinary")]
+    pub payload: Json,
+    /// Hex keccak256 of the normalized payload
+    pub payload_hash: String,
+    /// Hash of the previous entry; None for the first
+    pub prev_hash: Option<String>,
+    /// Hex keccak256 of `prev_hash` and `payload_hash` (as bytes)
+    pub hash: String,
+    pub created_at: DateTime,
+    /// Transaction that posted `hash` to the snapshot registry, if any
+    pub anchor_tx_hash: Option<String>,
+    pub anchor_block: Option<i64>,
+    pub anchored_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
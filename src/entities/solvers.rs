@@ -0,0 +1,33 @@
+//! SeaORM Entity for solvers table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "solvers")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Lowercase 0x address of the solver's wallet (its SIWE session)
+    #[sea_orm(unique)]
+    pub address: String,
+    pub name: String,
+    /// Supported asset symbols as a JSON array, e.g. ["BTC", "ETH"]
+    #[sea_orm(column_type = "JsonBinary")]
+    pub assets: Json,
+    /// Supported exchanges as a JSON array, e.g. ["bitget", "binance"]
+    #[sea_orm(column_type = "JsonBinary")]
+    pub exchanges: Json,
+    /// URL the solver can be reached at, if it exposes one
+    pub endpoint: Option<String>,
+    /// Solver software version
+    pub version: Option<String>,
+    pub registered_at: DateTime,
+    pub updated_at: DateTime,
+    pub last_heartbeat_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,26 @@
+//! SeaORM Entity for spread_observations table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "spread_observations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub exchange: String,
+    /// e.g. "BTCUSDC"
+    pub trading_pair: String,
+    pub coin_id: String,
+    pub date: Date,
+    pub best_bid: Decimal,
+    pub best_ask: Decimal,
+    /// (best_ask - best_bid) / mid, the unit of exchange_avg_spread
+    pub spread: Decimal,
+    pub observed_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,23 @@
+//! SeaORM Entity for token_migrations table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "token_migrations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Coin merged away; it no longer exists in coins
+    pub source_coin_id: String,
+    pub target_coin_id: String,
+    /// Rows moved to the target, by table
+    #[sea_orm(column_type = "JsonBinary")]
+    pub rows_moved: Json,
+    pub merged_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,28 @@
+//! SeaORM Entity for wallet_balance_checks table
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "wallet_balance_checks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// arbitrum, base or orbit
+    pub network: String,
+    /// Signer wallet address
+    pub address: String,
+    pub date: Date,
+    /// Balance at the latest check of the day
+    pub balance_eth: Decimal,
+    /// Alert threshold in effect for this check
+    pub threshold_eth: Decimal,
+    /// balance_eth was under the threshold
+    pub low: bool,
+    pub checked_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,1003 @@
+//! Admin endpoints
+//!
+//! Operational endpoints under /admin, protected by the same X-API-Key /
+//! ADMIN_API_KEY check as ITP creation.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::{self, HeaderMap, HeaderValue}, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Datelike;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use tracing::{error, info, warn};
+
+use crate::entities::{index_metadata, prelude::*};
+use crate::models::api_key::{
+    ApiKeyResponse, ApiUsageQuery, ApiUsageReport, CreateApiKeyRequest, CreateApiKeyResponse,
+};
+use crate::models::chain_spend::{ChainSpendQuery, ChainSpendReport};
+use crate::models::coin_merge::{MergeCoinsRequest, MergeCoinsResponse};
+use crate::models::data_freshness::DataFreshnessResponse;
+use crate::models::feature_flag::{FeatureFlagResponse, UpdateFeatureFlagRequest};
+use crate::models::index::{
+    IndexDeploymentsResponse, IndexStatusResponse, UpdateIndexDeploymentsRequest, UpdateIndexStatusRequest,
+};
+use crate::models::index_translation::{IndexTranslationResponse, UpsertIndexTranslationRequest};
+use crate::models::job_failure::{JobFailureResponse, JobFailuresQuery};
+use crate::models::label::{CreateLabelRequest, LabelResponse, LabelsQuery};
+use crate::models::methodology::{CreateMethodologyRequest, MethodologyDocument};
+use crate::models::price_reconciliation::{PriceReconciliationQuery, PriceReconciliationReport};
+use crate::models::portfolio_continuity::{PortfolioContinuityQuery, PortfolioContinuityReport};
+use crate::models::price_retention::PriceRetentionReport;
+use crate::models::rebalance_approval::RebalanceApprovalResponse;
+use crate::models::report::{ReportResponse, ReportsQuery};
+use crate::models::supply_reconciliation::SupplyReconciliationResponse;
+use crate::models::token::ErrorResponse;
+use crate::models::yield_rate::{SetYieldRateRequest, YieldRateResponse};
+use crate::services::coingecko_usage::UsageSnapshot;
+use crate::services::supply_reconciliation::{SupplyReconciliationError, SupplyReconciliationService};
+use crate::services::price_retention::{self, RetentionConfig};
+use crate::services::price_reconciliation;
+use crate::services::api_keys::{self, ApiKeyTier};
+use crate::services::methodology_documents::{self, MethodologyError};
+use crate::services::index_status;
+use crate::services::coin_merge::{self, MergeError};
+use crate::services::portfolio_continuity;
+use crate::services::labels::{self, LabelError};
+use crate::services::index_translations::{self, TranslationError};
+use crate::services::feature_flags::FeatureFlagError;
+use crate::services::rebalance_approvals::{self, ApprovalError};
+use crate::services::yield_accrual::{self, YieldRateError};
+use crate::services::reports::{self, ReportTemplate};
+use crate::services::{chain_spend, data_freshness, index_deployments, job_failures};
+use crate::AppState;
+
+/// Check admin authentication via X-API-Key header
+pub(crate) fn require_admin_key(headers: &HeaderMap) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let admin_key = std::env::var("ADMIN_API_KEY").map_err(|_| {
+        error!("ADMIN_API_KEY not configured");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "Server configuration error".to_string(),
+            }),
+        )
+    })?;
+
+    let provided_key = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if provided_key != admin_key {
+        warn!("Invalid or missing API key on admin endpoint");
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Invalid or missing API key".to_string(),
+            }),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Identify the admin credential of a request: "admin" for the shared
+/// ADMIN_API_KEY, "api_key:<id>" for an admin-tier API key
+pub(crate) async fn require_admin_credential(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    if require_admin_key(headers).is_ok() {
+        return Ok("admin".to_string());
+    }
+
+    let provided_key = headers.get("x-api-key").and_then(|v| v.to_str().ok()).unwrap_or("");
+    let api_key = api_keys::authenticate(&state.db, provided_key)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .filter(|api_key| ApiKeyTier::parse(&api_key.tier) == Some(ApiKeyTier::Admin));
+    match api_key {
+        Some(api_key) => Ok(format!("api_key:{}", api_key.id)),
+        None => {
+            warn!("Invalid or missing API key on admin endpoint");
+            Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse {
+                    error: "Invalid or missing API key".to_string(),
+                }),
+            ))
+        }
+    }
+}
+
+/// GET /admin/data-freshness
+///
+/// Reports, per data domain (coins, historical prices, categories, listings,
+/// daily index prices), the last successful sync, row counts, and the most
+/// stale coin/index.
+pub async fn get_data_freshness(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<DataFreshnessResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let report = data_freshness::build_report(&state.db).await.map_err(db_error)?;
+
+    Ok(Json(report))
+}
+
+/// GET /admin/price-retention
+///
+/// What the coins price retention job would remove with the current
+/// configuration. Always a dry run, regardless of PRICE_RETENTION_DRY_RUN.
+pub async fn get_price_retention_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<PriceRetentionReport>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let config = RetentionConfig { dry_run: true, ..RetentionConfig::from_env() };
+    let report = price_retention::run(&state.db, &config).await.map_err(db_error)?;
+
+    Ok(Json(report))
+}
+
+/// GET /admin/price-reconciliation?days=
+///
+/// Deviation of stored coin prices from the reference source over the last
+/// `days` days (default 30), with the flagged checks.
+pub async fn get_price_reconciliation_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PriceReconciliationQuery>,
+) -> Result<Json<PriceReconciliationReport>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let report = price_reconciliation::report(&state.db, days, chrono::Utc::now().date_naive())
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(report))
+}
+
+/// GET /admin/job-failures?status=&job=&limit=
+///
+/// Lists dead-letter queue items, newest first.
+pub async fn list_job_failures(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<JobFailuresQuery>,
+) -> Result<Json<Vec<JobFailureResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let records = job_failures::list(&state.db, query.status.as_deref(), query.job.as_deref(), limit)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(records.into_iter().map(JobFailureResponse::from).collect()))
+}
+
+/// POST /admin/job-failures/{id}/requeue
+///
+/// Puts a failed item back in the queue for immediate retry, with its
+/// attempt count reset.
+pub async fn requeue_job_failure(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<JobFailureResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let record = job_failures::requeue(&state.db, id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| job_failure_not_found(id))?;
+
+    info!(id = id, job = %record.job_name, entity = %record.entity_id, "Job failure requeued");
+    Ok(Json(record.into()))
+}
+
+/// POST /admin/job-failures/{id}/discard
+///
+/// Drops a failed item from the queue without retrying it.
+pub async fn discard_job_failure(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<JobFailureResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let record = job_failures::discard(&state.db, id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| job_failure_not_found(id))?;
+
+    info!(id = id, job = %record.job_name, entity = %record.entity_id, "Job failure discarded");
+    Ok(Json(record.into()))
+}
+
+/// GET /admin/indexes/{index_id}/supply-reconciliation
+///
+/// Compares the net supply from ingested blockchain events with the index
+/// token's on-chain totalSupply().
+pub async fn get_supply_reconciliation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+) -> Result<Json<SupplyReconciliationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let service = SupplyReconciliationService::from_env(state.db.clone()).map_err(|e| {
+        error!(error = %e, "Supply reconciliation unavailable");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: format!("Supply reconciliation unavailable: {}", e),
+            }),
+        )
+    })?;
+
+    let report = service.reconcile_index(index_id).await.map_err(|e| {
+        let status = match e {
+            SupplyReconciliationError::IndexNotFound(_) => StatusCode::NOT_FOUND,
+            SupplyReconciliationError::ContractCallError(_) => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(ErrorResponse { error: e.to_string() }))
+    })?;
+
+    if report.discrepancy {
+        warn!(index_id = index_id, difference = %report.difference, "Supply discrepancy detected");
+    }
+
+    Ok(Json(report))
+}
+
+/// GET /admin/indexes/{index_id}/deployments
+///
+/// Networks and contracts whose blockchain events count towards the index.
+pub async fn get_index_deployments(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+) -> Result<Json<IndexDeploymentsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let index = find_index(&state, index_id).await?;
+    let deployments = index_deployments::for_index(&state.db, &index)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+
+    Ok(Json(IndexDeploymentsResponse { index_id, deployments }))
+}
+
+/// PUT /admin/indexes/{index_id}/deployments
+///
+/// Replaces the configured deployments of an index. An empty list falls back
+/// to index_metadata.address on the default network.
+pub async fn update_index_deployments(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+    Json(request): Json<UpdateIndexDeploymentsRequest>,
+) -> Result<Json<IndexDeploymentsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    for deployment in &request.deployments {
+        let deployment = index_deployments::normalize(deployment);
+        let valid_address = deployment.contract_address.len() == 42
+            && deployment.contract_address.starts_with("0x")
+            && deployment.contract_address[2..].chars().all(|c| c.is_ascii_hexdigit());
+        if deployment.network.is_empty() || !valid_address {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Invalid deployment '{}' on '{}'",
+                        deployment.contract_address, deployment.network
+                    ),
+                }),
+            ));
+        }
+    }
+
+    let index = find_index(&state, index_id).await?;
+    index_deployments::replace(&state.db, index_id, &request.deployments)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+    let deployments = index_deployments::for_index(&state.db, &index)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+
+    info!(index_id = index_id, count = deployments.len(), "Index deployments updated");
+    Ok(Json(IndexDeploymentsResponse { index_id, deployments }))
+}
+
+/// PUT /admin/indexes/{index_id}/status
+///
+/// Sets an index's lifecycle status (see services::index_status).
+pub async fn update_index_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+    Json(request): Json<UpdateIndexStatusRequest>,
+) -> Result<Json<IndexStatusResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let status = index_status::parse(&request.status).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("status must be one of {}", index_status::ALL.join(", ")),
+            }),
+        )
+    })?;
+
+    let index = find_index(&state, index_id).await?;
+    let previous = index.status.clone();
+    let mut index: index_metadata::ActiveModel = index.into();
+    index.status = Set(status.to_string());
+    index.update(&state.db).await.map_err(|e| db_error(e.into()))?;
+
+    info!(index_id = index_id, from = %previous, to = status, "Index status updated");
+    Ok(Json(IndexStatusResponse {
+        index_id,
+        status: status.to_string(),
+    }))
+}
+
+/// PUT /admin/indexes/{index_id}/translations/{locale}
+///
+/// Creates or replaces the index's name and description in a locale.
+pub async fn upsert_index_translation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((index_id, locale)): Path<(i32, String)>,
+    Json(request): Json<UpsertIndexTranslationRequest>,
+) -> Result<Json<IndexTranslationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let translation = index_translations::upsert(
+        &state.db,
+        index_id,
+        &locale,
+        &request.name,
+        request.description.as_deref(),
+        chrono::Utc::now().naive_utc(),
+    )
+    .await
+    .map_err(translation_error)?;
+
+    info!(index_id = index_id, locale = %translation.locale, "Index translation saved");
+    Ok(Json(translation.into()))
+}
+
+/// DELETE /admin/indexes/{index_id}/translations/{locale}
+pub async fn delete_index_translation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((index_id, locale)): Path<(i32, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let removed = index_translations::remove(&state.db, index_id, &locale)
+        .await
+        .map_err(translation_error)?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Index {} has no '{}' translation", index_id, locale),
+            }),
+        ));
+    }
+
+    info!(index_id = index_id, locale = %locale, "Index translation removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/indexes/{index_id}/methodology
+///
+/// Publishes a new methodology version for an index. Versions are immutable;
+/// the new one takes effect on `effectiveFrom` (default today), which can't
+/// precede the latest version's.
+pub async fn create_methodology_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+    Json(request): Json<CreateMethodologyRequest>,
+) -> Result<(StatusCode, Json<MethodologyDocument>), (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    find_index(&state, index_id).await?;
+
+    let format = request.format.as_deref().unwrap_or("markdown");
+    let effective_from = request.effective_from.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let document = methodology_documents::create(&state.db, index_id, format, &request.content, effective_from)
+        .await
+        .map_err(|e| match e {
+            MethodologyError::Database(e) => db_error(e.into()),
+            e => (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse { error: e.to_string() }),
+            ),
+        })?;
+
+    info!(
+        index_id = index_id,
+        version = document.version,
+        effective_from = %document.effective_from,
+        "Methodology version published"
+    );
+    Ok((StatusCode::CREATED, Json(document.into())))
+}
+
+/// POST /admin/api-keys
+///
+/// Issues an API key. The key is only returned in this response; store it
+/// right away.
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let tier = ApiKeyTier::parse(&request.tier).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid tier '{}', expected public, partner or admin", request.tier),
+            }),
+        )
+    })?;
+    if request.name.trim().is_empty() || request.daily_quota.is_some_and(|quota| quota <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "name must be set and dailyQuota must be positive".to_string(),
+            }),
+        ));
+    }
+
+    let (api_key, key) = api_keys::create(&state.db, request.name.trim(), tier, request.daily_quota)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+
+    info!(id = api_key.id, name = %api_key.name, tier = %api_key.tier, "API key issued");
+    Ok((StatusCode::CREATED, Json(CreateApiKeyResponse { key, api_key: api_key.into() })))
+}
+
+/// GET /admin/api-keys
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ApiKeyResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let keys = api_keys::list(&state.db).await.map_err(|e| db_error(e.into()))?;
+
+    Ok(Json(keys.into_iter().map(ApiKeyResponse::from).collect()))
+}
+
+/// POST /admin/api-keys/{id}/revoke
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let api_key = api_keys::revoke(&state.db, id)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("API key {} not found", id),
+                }),
+            )
+        })?;
+
+    info!(id = id, name = %api_key.name, "API key revoked");
+    Ok(Json(api_key.into()))
+}
+
+/// GET /admin/api-usage?days=&key_id=
+///
+/// Requests per API key over the last `days` days (default 7), today's
+/// count against the quota, and the busiest endpoints.
+pub async fn get_api_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ApiUsageQuery>,
+) -> Result<Json<ApiUsageReport>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let days = query.days.unwrap_or(7).clamp(1, 90);
+    let report = api_keys::usage_report(&state.db, days, query.key_id, chrono::Utc::now().date_naive())
+        .await
+        .map_err(|e| db_error(e.into()))?;
+
+    Ok(Json(report))
+}
+
+/// GET /admin/coingecko-usage
+///
+/// CoinGecko credits used today and since startup by call type, against
+/// the daily budget for on-the-fly fetches.
+pub async fn get_coingecko_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<UsageSnapshot>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+    Ok(Json(state.coingecko.usage().snapshot()))
+}
+
+/// GET /admin/labels?entity_type=&entity_id=
+pub async fn list_labels(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LabelsQuery>,
+) -> Result<Json<Vec<LabelResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let records = labels::list(&state.db, query.entity_type.as_deref(), query.entity_id.as_deref())
+        .await
+        .map_err(|e| db_error(e.into()))?;
+
+    Ok(Json(records.into_iter().map(LabelResponse::from).collect()))
+}
+
+/// POST /admin/labels
+///
+/// Labels an index or coin. Keys and values are stored lowercase; adding a
+/// label the entity already has returns the existing one.
+pub async fn create_label(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateLabelRequest>,
+) -> Result<(StatusCode, Json<LabelResponse>), (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let label = labels::add(&state.db, &request.entity_type, &request.entity_id, &request.key, &request.value)
+        .await
+        .map_err(|e| match e {
+            LabelError::Database(e) => db_error(e.into()),
+            LabelError::EntityNotFound { .. } => (StatusCode::NOT_FOUND, Json(ErrorResponse { error: e.to_string() })),
+            LabelError::Invalid(_) => (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })),
+        })?;
+
+    info!(
+        id = label.id,
+        entity_type = %label.entity_type,
+        entity_id = %label.entity_id,
+        label = %format!("{}:{}", label.key, label.value),
+        "Label added"
+    );
+    Ok((StatusCode::CREATED, Json(label.into())))
+}
+
+/// DELETE /admin/labels/{id}
+pub async fn delete_label(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let label = labels::remove(&state.db, id)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Label {} not found", id),
+                }),
+            )
+        })?;
+
+    info!(id = id, entity_type = %label.entity_type, entity_id = %label.entity_id, "Label removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /admin/feature-flags
+pub async fn list_feature_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FeatureFlagResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let flags = state.feature_flags.list().await;
+    Ok(Json(flags.into_iter().map(FeatureFlagResponse::from).collect()))
+}
+
+/// PUT /admin/feature-flags/{key}
+///
+/// Switches a flag on or off. Takes effect on this instance immediately and
+/// on the others within their refresh interval.
+pub async fn update_feature_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(key): Path<String>,
+    Json(request): Json<UpdateFeatureFlagRequest>,
+) -> Result<Json<FeatureFlagResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let flag = state
+        .feature_flags
+        .set(&state.db, &key, request.enabled)
+        .await
+        .map_err(|e| match e {
+            FeatureFlagError::Database(e) => db_error(e.into()),
+            FeatureFlagError::UnknownFlag(_) => (StatusCode::NOT_FOUND, Json(ErrorResponse { error: e.to_string() })),
+        })?;
+
+    warn!(flag = %key, enabled = request.enabled, "Feature flag changed");
+    Ok(Json(flag.into()))
+}
+
+/// GET /admin/rebalances/{id}/approval
+///
+/// The latest approval record of a rebalance. Accepts ADMIN_API_KEY or an
+/// admin-tier API key.
+pub async fn get_rebalance_approval(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<RebalanceApprovalResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_credential(&state, &headers).await?;
+
+    let approval = rebalance_approvals::latest(&state.db, id)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Rebalance {} has no approval record", id),
+                }),
+            )
+        })?;
+
+    Ok(Json(RebalanceApprovalResponse::new(approval, chrono::Utc::now().naive_utc())))
+}
+
+/// POST /admin/rebalances/{id}/request-approval
+///
+/// First step of deploying a rebalance on-chain: opens a pending approval
+/// that a different admin credential must approve before it expires.
+pub async fn request_rebalance_approval(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<RebalanceApprovalResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let credential = require_admin_credential(&state, &headers).await?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let approval = rebalance_approvals::request(&state.db, id, &credential, now)
+        .await
+        .map_err(approval_error)?;
+
+    info!(rebalance_id = id, requested_by = %credential, expires_at = %approval.expires_at, "Rebalance approval requested");
+    Ok((StatusCode::CREATED, Json(RebalanceApprovalResponse::new(approval, now))))
+}
+
+/// POST /admin/rebalances/{id}/approve
+///
+/// Second step: lets the rebalance deployer submit the rebalance. Must come
+/// from a different credential than the request.
+pub async fn approve_rebalance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<RebalanceApprovalResponse>, (StatusCode, Json<ErrorResponse>)> {
+    decide_rebalance(&state, &headers, id, true).await
+}
+
+/// POST /admin/rebalances/{id}/reject
+pub async fn reject_rebalance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<RebalanceApprovalResponse>, (StatusCode, Json<ErrorResponse>)> {
+    decide_rebalance(&state, &headers, id, false).await
+}
+
+async fn decide_rebalance(
+    state: &AppState,
+    headers: &HeaderMap,
+    id: i32,
+    approve: bool,
+) -> Result<Json<RebalanceApprovalResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let credential = require_admin_credential(state, headers).await?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let approval = rebalance_approvals::decide(&state.db, id, &credential, approve, now)
+        .await
+        .map_err(approval_error)?;
+
+    warn!(rebalance_id = id, decided_by = %credential, status = %approval.status, "Rebalance approval decided");
+    Ok(Json(RebalanceApprovalResponse::new(approval, now)))
+}
+
+/// GET /admin/chain-spend?month=YYYY-MM
+///
+/// Gas the backend's transactions paid in a month (default: the current
+/// one), in ETH and USD, per kind and per transaction.
+pub async fn get_chain_spend(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ChainSpendQuery>,
+) -> Result<Json<ChainSpendReport>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let month = match query.month.as_deref() {
+        Some(month) => chain_spend::parse_month(month).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid month '{}', expected YYYY-MM", month),
+                }),
+            )
+        })?,
+        None => chrono::Utc::now().date_naive().with_day(1).unwrap_or_default(),
+    };
+
+    let report = chain_spend::monthly_report(&state.db, month)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+    Ok(Json(report))
+}
+
+/// GET /admin/yield-rates
+pub async fn list_yield_rates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<YieldRateResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let rates = yield_accrual::list(&state.db)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+    Ok(Json(rates.into_iter().map(YieldRateResponse::from).collect()))
+}
+
+/// PUT /admin/yield-rates/{coin_id}
+///
+/// Sets a coin's yield by hand. A manual rate overrides the DefiLlama sync
+/// until it is deleted.
+pub async fn set_yield_rate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(coin_id): Path<String>,
+    Json(request): Json<SetYieldRateRequest>,
+) -> Result<Json<YieldRateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let rate = yield_accrual::set_manual(&state.db, &coin_id, request.apy_pct, chrono::Utc::now().naive_utc())
+        .await
+        .map_err(|e| match e {
+            YieldRateError::Database(e) => db_error(e.into()),
+            YieldRateError::InvalidRate(_) => (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })),
+        })?;
+
+    info!(coin_id = %coin_id, apy_pct = %rate.apy_pct, "Yield rate set");
+    Ok(Json(rate.into()))
+}
+
+/// DELETE /admin/yield-rates/{coin_id}
+pub async fn delete_yield_rate(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(coin_id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let removed = yield_accrual::remove(&state.db, &coin_id)
+        .await
+        .map_err(|e| db_error(e.into()))?;
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No yield rate for coin {}", coin_id),
+            }),
+        ));
+    }
+
+    info!(coin_id = %coin_id, "Yield rate removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/coins/merge
+///
+/// Merges a duplicate coin (e.g. one CoinGecko re-ided) into another: every
+/// row keyed by its coin_id (prices, listings, constituents, categories,
+/// ...) and its rebalances move to the target and the source is deleted,
+/// all in one transaction.
+pub async fn merge_coins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<MergeCoinsRequest>,
+) -> Result<Json<MergeCoinsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let merged_at = chrono::Utc::now().naive_utc();
+    let summary = coin_merge::merge(&state.db, &request.source_coin_id, &request.target_coin_id, merged_at)
+        .await
+        .map_err(|e| {
+            let status = match e {
+                MergeError::Database(e) => return db_error(e.into()),
+                MergeError::SameCoin => StatusCode::BAD_REQUEST,
+                MergeError::CoinNotFound(_) => StatusCode::NOT_FOUND,
+                MergeError::Conflict { .. } => StatusCode::CONFLICT,
+            };
+            (status, Json(ErrorResponse { error: e.to_string() }))
+        })?;
+
+    info!(
+        source = %request.source_coin_id,
+        target = %request.target_coin_id,
+        rows_moved = ?summary.rows_moved,
+        "Coins merged"
+    );
+    Ok(Json(MergeCoinsResponse {
+        source_coin_id: request.source_coin_id,
+        target_coin_id: request.target_coin_id,
+        rows_moved: summary.rows_moved,
+        rows_dropped: summary.rows_dropped,
+        merged_at,
+    }))
+}
+
+/// GET /admin/indexes/{index_id}/continuity?tolerance_bps=
+///
+/// Compares each rebalance's value before fees with the previous basket
+/// marked at the rebalance date's stored prices, flagging the rebalances
+/// off by more than the tolerance (see services::portfolio_continuity).
+pub async fn get_portfolio_continuity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+    Query(query): Query<PortfolioContinuityQuery>,
+) -> Result<Json<PortfolioContinuityReport>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    find_index(&state, index_id).await?;
+    let tolerance_bps = query.tolerance_bps.unwrap_or(portfolio_continuity::DEFAULT_TOLERANCE_BPS);
+    let report = portfolio_continuity::check_index(&state.db, index_id, tolerance_bps)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(report))
+}
+
+/// GET /admin/reports?template=&limit=
+///
+/// Lists the stored scheduled reports (see services::reports), newest
+/// period first, without their content.
+pub async fn list_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ReportsQuery>,
+) -> Result<Json<Vec<ReportResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let template = match query.template.as_deref() {
+        None => None,
+        Some(t) => Some(ReportTemplate::parse(t).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid template '{}', expected weekly_performance or monthly_rebalance", t),
+                }),
+            )
+        })?),
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let records = reports::list(&state.db, template, limit).await.map_err(|e| db_error(e.into()))?;
+
+    Ok(Json(records.into_iter().map(ReportResponse::from).collect()))
+}
+
+/// GET /admin/reports/{id}/content
+///
+/// Downloads a stored report's artifact.
+pub async fn get_report_content(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let report = Reports::find_by_id(id)
+        .one(&state.db)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Report {} not found", id),
+                }),
+            )
+        })?;
+
+    let disposition = format!("attachment; filename=\"{}\"", reports::filename(&report));
+    let content_type = report.content_type.clone();
+    let mut response = report.content.into_response();
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&content_type) {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+fn translation_error(e: TranslationError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        TranslationError::Database(e) => return db_error(e.into()),
+        TranslationError::Invalid(_) => StatusCode::BAD_REQUEST,
+        TranslationError::IndexNotFound(_) => StatusCode::NOT_FOUND,
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+fn approval_error(e: ApprovalError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        ApprovalError::Database(e) => return db_error(e.into()),
+        ApprovalError::RebalanceNotFound(_) => StatusCode::NOT_FOUND,
+        ApprovalError::SameCredential => StatusCode::FORBIDDEN,
+        ApprovalError::AlreadyDeployed(_) | ApprovalError::AlreadyRequested(_) | ApprovalError::NoPendingRequest(_) => {
+            StatusCode::CONFLICT
+        }
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
+
+async fn find_index(
+    state: &AppState,
+    index_id: i32,
+) -> Result<index_metadata::Model, (StatusCode, Json<ErrorResponse>)> {
+    IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(|e| db_error(e.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Index {} not found", index_id),
+                }),
+            )
+        })
+}
+
+fn job_failure_not_found(id: i32) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: format!("Job failure {} not found", id),
+        }),
+    )
+}
+
+fn db_error(e: Box<dyn std::error::Error + Send + Sync>) -> (StatusCode, Json<ErrorResponse>) {
+    error!(error = %e, "Admin endpoint database error");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: format!("Database error: {}", e),
+        }),
+    )
+}
@@ -0,0 +1,129 @@
+//! Analytics handlers
+//!
+//! POST /api/analytics/correlation serves the correlation matrix and
+//! volatilities of a set of coins' daily returns (see
+//! services::correlation), the inputs of risk-aware weighting strategies.
+//! GET /api/analytics/rolling-stats serves a coin's or an index's
+//! precomputed 30/90/365-day return stats (see services::rolling_stats).
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::models::analytics::{
+    CorrelationRequest, CorrelationResponse, RollingStatsQuery, RollingStatsResponse, WindowStatsResponse,
+};
+use crate::models::market_cap::MAX_BATCH_COINS;
+use crate::models::token::ErrorResponse;
+use crate::services::correlation::{self, DEFAULT_WINDOW, MAX_WINDOW};
+use crate::services::rolling_stats::{self, subjects};
+use crate::AppState;
+
+fn bad_request(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+}
+
+/// POST /api/analytics/correlation
+///
+/// Body: `{"coinIds": [...], "window": 90, "endDate": "2025-06-01"}`.
+/// Coins that aren't in the system are listed in `missing`.
+pub async fn get_correlation(
+    State(state): State<AppState>,
+    Json(request): Json<CorrelationRequest>,
+) -> Result<Json<CorrelationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut seen = HashSet::new();
+    let coin_ids: Vec<String> = request
+        .coin_ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect();
+    if coin_ids.is_empty() || coin_ids.len() > MAX_BATCH_COINS {
+        return Err(bad_request(format!("coinIds must list 1 to {} coins", MAX_BATCH_COINS)));
+    }
+
+    let window = request.window.unwrap_or(DEFAULT_WINDOW);
+    if !(2..=MAX_WINDOW).contains(&window) {
+        return Err(bad_request(format!("window must be between 2 and {} days", MAX_WINDOW)));
+    }
+
+    let today = Utc::now().date_naive();
+    let end_date = request.end_date.unwrap_or(today - Duration::days(1));
+    if end_date > today {
+        return Err(bad_request("endDate cannot be in the future".to_string()));
+    }
+
+    let response = correlation::analyze(&state.db, &coin_ids, end_date, window)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    Ok(Json(response))
+}
+
+/// GET /api/analytics/rolling-stats?subject=coin&id=bitcoin&date=2025-06-01
+///
+/// 404 until the rolling stats job has covered the subject.
+pub async fn get_rolling_stats(
+    State(state): State<AppState>,
+    Query(query): Query<RollingStatsQuery>,
+) -> Result<Json<RollingStatsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if query.subject != subjects::COIN && query.subject != subjects::INDEX {
+        return Err(bad_request(format!(
+            "subject must be '{}' or '{}'",
+            subjects::COIN,
+            subjects::INDEX
+        )));
+    }
+
+    let rows = rolling_stats::latest(&state.db, &query.subject, &query.id, query.date)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+    let Some(as_of) = rows.first().map(|row| row.as_of) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No rolling stats for {} {}", query.subject, query.id),
+            }),
+        ));
+    };
+
+    let windows = rows
+        .into_iter()
+        .map(|row| WindowStatsResponse {
+            window_days: row.window_days,
+            observations: row.observations,
+            total_return: row.total_return.and_then(|v| v.to_f64()),
+            mean_return: row.mean_return.and_then(|v| v.to_f64()),
+            volatility: row.volatility.and_then(|v| v.to_f64()),
+            annualized_volatility: row.annualized_volatility.and_then(|v| v.to_f64()),
+            max_drawdown: row.max_drawdown.and_then(|v| v.to_f64()),
+        })
+        .collect();
+
+    Ok(Json(RollingStatsResponse {
+        subject: query.subject,
+        id: query.id,
+        as_of,
+        windows,
+    }))
+}
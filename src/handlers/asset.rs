@@ -1,20 +1,80 @@
-use axum::extract::Path;
-use axum::{extract::State, http::StatusCode, Json};
+use axum::extract::{Path, Query};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use sea_orm::{ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect};
 use std::collections::{HashMap, HashSet};
 
-use crate::entities::{daily_prices, rebalances, prelude::*};
-use crate::models::asset::{Asset, VaultAsset};
+use crate::entities::{coins, daily_prices, rebalances, prelude::*};
+use crate::models::asset::{Asset, CoinByContractResponse, LogoQuery, VaultAsset};
+use crate::models::label::LabelFilterQuery;
+use crate::models::list_query::ListQuery;
+use crate::handlers::maintenance::{require_coingecko_budget, require_feature};
 use crate::models::token::ErrorResponse;
 use crate::services::category_service::get_coin_category;
+use crate::services::coin_logos::{self, LogoError};
+use crate::services::coingecko::CoinGeckoError;
+use crate::services::feature_flags::flags;
+use crate::services::labels::{self, entity_types};
+use crate::services::list_query::{self, Field};
+use crate::services::rebalance_schema;
 use crate::services::rebalancing::CoinRebalanceInfo;
 use crate::AppState;
 
+/// Filter fields of GET /fetch-all-assets, on the coins table
+const ASSET_FILTER_FIELDS: &[Field<coins::Column>] = &[
+    Field::text("id", coins::Column::CoinId),
+    Field::text("symbol", coins::Column::Symbol),
+    Field::text("name", coins::Column::Name),
+];
+
+/// Sort fields of GET /fetch-all-assets, on the market data
+#[derive(Debug, Clone, Copy)]
+enum AssetSort {
+    Symbol,
+    Name,
+    PriceUsd,
+    MarketCap,
+}
+
+const ASSET_SORT_FIELDS: &[Field<AssetSort>] = &[
+    Field::sort_only("symbol", AssetSort::Symbol),
+    Field::sort_only("name", AssetSort::Name),
+    Field::sort_only("price_usd", AssetSort::PriceUsd),
+    Field::sort_only("market_cap", AssetSort::MarketCap),
+];
+
+fn compare_assets(a: &Asset, b: &Asset, field: AssetSort) -> std::cmp::Ordering {
+    match field {
+        AssetSort::Symbol => a.symbol.cmp(&b.symbol),
+        AssetSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        AssetSort::PriceUsd => a.price_usd.total_cmp(&b.price_usd),
+        AssetSort::MarketCap => a.market_cap.total_cmp(&b.market_cap),
+    }
+}
+
+/// GET /fetch-all-assets?label=&filter=&sort=
+///
+/// Coins held by any index, with market data. `label` keeps only coins with
+/// matching labels (e.g. `theme:ai`; see services::labels). `filter` takes
+/// id, symbol and name, and `sort` symbol, name, price_usd and market_cap
+/// (default: -market_cap; see services::list_query).
 pub async fn fetch_all_assets(
     State(state): State<AppState>,
+    Query(query): Query<LabelFilterQuery>,
+    Query(list): Query<ListQuery>,
 ) -> Result<Json<Vec<Asset>>, (StatusCode, Json<ErrorResponse>)> {
     tracing::info!("Fetching all assets across indexes");
 
+    let label_filters = labels::parse_filters(query.label.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })))?;
+    let bad_request = |e: list_query::ListQueryError| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() }));
+    let filters = list_query::parse_filters(ASSET_FILTER_FIELDS, list.filter.as_deref()).map_err(bad_request)?;
+    let sorts = list_query::parse_sort(ASSET_SORT_FIELDS, list.sort.as_deref()).map_err(bad_request)?;
+
     // Step 1: Get all rebalances and extract unique index IDs
     let all_rebalances = Rebalances::find()
         .all(&state.db)
@@ -71,7 +131,7 @@ pub async fn fetch_all_assets(
         let rebalance = latest_rebalance.unwrap();
 
         // Parse coins from rebalance
-        let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(rebalance.coins.clone())
+        let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins.clone())
             .unwrap_or_default();
 
         // Get latest daily_price for this index
@@ -112,6 +172,43 @@ pub async fn fetch_all_assets(
         }
     }
 
+    if !label_filters.is_empty() {
+        let labelled = labels::matching_entity_ids(&state.db, entity_types::COIN, &label_filters)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: format!("Database error: {}", e),
+                    }),
+                )
+            })?;
+        all_coin_ids.retain(|coin_id| labelled.contains(coin_id));
+    }
+
+    // Filters match the coins table, so coins missing from it don't pass them
+    if !filters.is_empty() && !all_coin_ids.is_empty() {
+        let matching: HashSet<String> = list_query::apply(
+            Coins::find().filter(coins::Column::CoinId.is_in(all_coin_ids.iter().cloned())),
+            filters,
+            Vec::new(),
+        )
+        .all(&state.db)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?
+        .into_iter()
+        .map(|coin| coin.coin_id)
+        .collect();
+        all_coin_ids.retain(|coin_id| matching.contains(coin_id));
+    }
+
     let coin_ids_vec: Vec<String> = all_coin_ids.into_iter().collect();
     tracing::info!(
         "Found {} unique coins across all indexes",
@@ -158,12 +255,23 @@ pub async fn fetch_all_assets(
         })
         .collect();
 
-    // Step 5: Sort by market cap descending
-    assets.sort_by(|a, b| {
-        b.market_cap
-            .partial_cmp(&a.market_cap)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    // Step 5: Sort as requested, by market cap descending by default
+    if sorts.is_empty() {
+        assets.sort_by(|a, b| {
+            b.market_cap
+                .partial_cmp(&a.market_cap)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    } else {
+        assets.sort_by(|a, b| {
+            sorts.iter().fold(std::cmp::Ordering::Equal, |ordering, (field, order)| {
+                ordering.then_with(|| match order {
+                    Order::Desc => compare_assets(b, a, *field),
+                    _ => compare_assets(a, b, *field),
+                })
+            })
+        });
+    }
 
     tracing::info!("Returning {} assets", assets.len());
 
@@ -200,7 +308,7 @@ pub async fn fetch_vault_assets(
     let rebalance = latest_rebalance.unwrap();
 
     // Parse coins from rebalance
-    let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(rebalance.coins.clone())
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins.clone())
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -334,6 +442,179 @@ pub async fn fetch_vault_assets(
     Ok(Json(vault_assets))
 }
 
+/// CoinGecko asset platform id for a chain name or alias
+///
+/// Unknown names that look like platform ids are passed through as-is.
+fn asset_platform_id(chain: &str) -> Option<String> {
+    let chain = chain.trim().to_lowercase();
+    let platform = match chain.as_str() {
+        "eth" | "mainnet" => "ethereum",
+        "arbitrum" | "arb" => "arbitrum-one",
+        "bsc" | "bnb" => "binance-smart-chain",
+        "polygon" | "matic" => "polygon-pos",
+        "optimism" | "op" => "optimistic-ethereum",
+        "avax" => "avalanche",
+        "sol" => "solana",
+        other if !other.is_empty()
+            && other.len() <= 64
+            && other.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') =>
+        {
+            other
+        }
+        _ => return None,
+    };
+    Some(platform.to_string())
+}
+
+/// EVM addresses lowercased; other chains' (base58) addresses kept as given
+fn normalize_contract_address(address: &str) -> Option<String> {
+    let address = address.trim();
+    if let Some(hex) = address.strip_prefix("0x").or_else(|| address.strip_prefix("0X")) {
+        return (hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| format!("0x{}", hex.to_lowercase()));
+    }
+    ((32..=64).contains(&address.len()) && address.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| address.to_string())
+}
+
+/// GET /coins/by-contract/{chain}/{address}
+///
+/// Resolves a token contract to its CoinGecko coin and current market data.
+/// `chain` is a CoinGecko asset platform id ("ethereum", "arbitrum-one", ...)
+/// or a common alias ("eth", "arbitrum", "bsc", "polygon", ...).
+///
+/// # Response
+/// - 200: Coin and market data
+/// - 400: Invalid chain or address
+/// - 404: No coin with this contract
+/// - 502: CoinGecko error
+/// - 503: On-the-fly CoinGecko fetches are switched off
+pub async fn lookup_by_contract(
+    State(state): State<AppState>,
+    Path((chain, address)): Path<(String, String)>,
+) -> Result<Json<CoinByContractResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let platform = asset_platform_id(&chain).ok_or_else(|| bad_request(format!("Invalid chain '{}'", chain)))?;
+    let address = normalize_contract_address(&address)
+        .ok_or_else(|| bad_request(format!("Invalid contract address '{}'", address)))?;
+    require_feature(&state, flags::COINGECKO_ON_THE_FLY).await?;
+    require_coingecko_budget(&state)?;
+
+    let coin_id = state
+        .coingecko
+        .fetch_coin_id_by_contract(&platform, &address)
+        .await
+        .map_err(|e| match e {
+            CoinGeckoError::NotFound => (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("No coin found for contract {} on {}", address, platform),
+                }),
+            ),
+            e => {
+                tracing::error!(platform = %platform, address = %address, error = %e, "Contract lookup failed");
+                (StatusCode::BAD_GATEWAY, Json(ErrorResponse { error: e.to_string() }))
+            }
+        })?;
+
+    // Market data is best-effort; the coin is still resolved without it
+    let market = match state.coingecko.fetch_markets(std::slice::from_ref(&coin_id)).await {
+        Ok(markets) => markets.into_iter().find(|m| m.id == coin_id),
+        Err(e) => {
+            tracing::warn!(coin_id = %coin_id, error = %e, "Failed to fetch market data");
+            None
+        }
+    };
+    let known = match &market {
+        Some(_) => None,
+        None => Coins::find()
+            .filter(coins::Column::CoinId.eq(&coin_id))
+            .one(&state.db)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: format!("Database error: {}", e),
+                    }),
+                )
+            })?,
+    };
+
+    let (symbol, name) = match (&market, known) {
+        (Some(m), _) => (m.symbol.clone(), m.name.clone()),
+        (None, Some(coin)) => (coin.symbol, coin.name),
+        (None, None) => (coin_id.clone(), coin_id.clone()),
+    };
+
+    Ok(Json(CoinByContractResponse {
+        platform,
+        contract_address: address,
+        coin_id,
+        symbol,
+        name,
+        image: market.as_ref().map(|m| m.image.clone()),
+        price_usd: market.as_ref().and_then(|m| m.current_price),
+        market_cap: market.as_ref().and_then(|m| m.market_cap),
+        market_cap_rank: market.as_ref().and_then(|m| m.market_cap_rank),
+        total_volume: market.as_ref().and_then(|m| m.total_volume),
+        circulating_supply: market.as_ref().and_then(|m| m.circulating_supply),
+        total_supply: market.as_ref().and_then(|m| m.total_supply),
+    }))
+}
+
+/// Browsers and CDNs may reuse a logo for a week
+const LOGO_CACHE_CONTROL: &str = "public, max-age=604800, stale-while-revalidate=86400";
+
+/// GET /assets/{coin_id}/logo?size=
+///
+/// The coin's logo, proxied and stored by the backend (see
+/// services::coin_logos) so clients don't hot-link CoinGecko.
+///
+/// # Response
+/// - 200: Image bytes
+/// - 400: Invalid size
+/// - 404: Unknown coin or coin without a logo
+/// - 502: The logo could not be fetched
+pub async fn get_coin_logo(
+    State(state): State<AppState>,
+    Path(coin_id): Path<String>,
+    Query(query): Query<LogoQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(size) = query.size
+        && !(coin_logos::MIN_SIZE..=coin_logos::MAX_SIZE).contains(&size)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("size must be between {} and {}", coin_logos::MIN_SIZE, coin_logos::MAX_SIZE),
+            }),
+        ));
+    }
+
+    let logo = coin_logos::get_logo(&state.db, &coin_id, query.size).await.map_err(|e| {
+        let status = match e {
+            LogoError::CoinNotFound(_) | LogoError::NoLogo(_) => StatusCode::NOT_FOUND,
+            LogoError::Fetch(_) => {
+                tracing::warn!(coin_id = %coin_id, error = %e, "Failed to fetch coin logo");
+                StatusCode::BAD_GATEWAY
+            }
+            LogoError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(ErrorResponse { error: e.to_string() }))
+    })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, logo.content_type),
+            (header::CACHE_CONTROL, LOGO_CACHE_CONTROL.to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        logo.data,
+    )
+        .into_response())
+}
+
 /// Map exchange name to short code
 fn exchange_to_code(exchange: &str) -> &str {
     match exchange.to_lowercase().as_str() {
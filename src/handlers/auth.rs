@@ -0,0 +1,103 @@
+//! Sign-In-With-Ethereum endpoints and wallet session checks
+//!
+//! POST /auth/nonce and POST /auth/verify exchange a signed SIWE message for
+//! a session token (see services::auth). Handlers returning one wallet's
+//! data call `require_wallet` so a session can only read its own address.
+
+use axum::{
+    extract::State,
+    http::{header::{self, HeaderMap}, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use tracing::{error, info, warn};
+
+use crate::models::auth::{NonceResponse, SessionResponse, VerifyRequest};
+use crate::models::token::ErrorResponse;
+use crate::services::auth::{self, AuthConfig, AuthError};
+use crate::AppState;
+
+/// POST /auth/nonce
+pub async fn create_nonce(
+    State(state): State<AppState>,
+) -> Result<Json<NonceResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let nonce = auth::issue_nonce(&state.db, Utc::now().naive_utc())
+        .await
+        .map_err(|e| auth_error(AuthError::Database(e)))?;
+
+    Ok(Json(NonceResponse { nonce: nonce.nonce, expires_at: nonce.expires_at }))
+}
+
+/// POST /auth/verify
+///
+/// Verifies a signed SIWE message and returns a session token for its
+/// address. Each nonce can be used once.
+pub async fn verify(
+    State(state): State<AppState>,
+    Json(request): Json<VerifyRequest>,
+) -> Result<Json<SessionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let session = auth::verify(&state.db, &state.auth, &request.message, &request.signature, Utc::now())
+        .await
+        .map_err(|e| {
+            warn!(error = %e, "SIWE verification failed");
+            auth_error(e)
+        })?;
+
+    info!(address = %session.address, "Wallet signed in");
+    Ok(Json(SessionResponse {
+        token: session.token,
+        address: session.address,
+        expires_at: session.expires_at,
+    }))
+}
+
+/// The wallet address of the request's session
+pub(crate) fn require_session(
+    config: &AuthConfig,
+    headers: &HeaderMap,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| auth_error(AuthError::InvalidToken))?;
+    auth::wallet_from_token(config, token.trim()).map_err(auth_error)
+}
+
+/// Check that the request carries a session for `address`
+pub(crate) fn require_wallet(
+    config: &AuthConfig,
+    headers: &HeaderMap,
+    address: &str,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let wallet = require_session(config, headers)?;
+
+    if !wallet.eq_ignore_ascii_case(address) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Session doesn't belong to this address".to_string(),
+            }),
+        ));
+    }
+
+    Ok(())
+}
+
+fn auth_error(e: AuthError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match &e {
+        AuthError::NotConfigured => {
+            error!("JWT_SECRET or SIWE_DOMAIN not configured");
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        AuthError::Database(e) => {
+            error!(error = %e, "Auth database error");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        AuthError::InvalidMessage(_) => StatusCode::BAD_REQUEST,
+        AuthError::InvalidSignature | AuthError::InvalidNonce | AuthError::Expired | AuthError::InvalidToken => {
+            StatusCode::UNAUTHORIZED
+        }
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
@@ -0,0 +1,53 @@
+//! Backtest handlers
+//!
+//! POST /api/backtest/compare runs a universe and date range under several
+//! methodology parameter sets and returns their stats side by side (see
+//! services::backtest).
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+
+use crate::models::backtest::{BacktestCompareRequest, BacktestCompareResponse};
+use crate::models::token::ErrorResponse;
+use crate::services::backtest;
+use crate::AppState;
+
+/// POST /api/backtest/compare
+///
+/// Body: `{"category": "layer-1", "startDate": "2024-01-01", "endDate":
+/// "2024-12-31", "scenarios": [{"name": "monthly", "topN": 10,
+/// "rebalancePeriodDays": 30, "rankingBuffer": 2, "maxWeightPct": 25}]}`.
+/// Without category or coinIds the universe is every coin with a market cap.
+///
+/// # Response
+/// - 200: One result per scenario, in request order
+/// - 400: Invalid dates or scenario parameters
+/// - 500: Database error
+pub async fn compare_backtests(
+    State(state): State<AppState>,
+    Json(request): Json<BacktestCompareRequest>,
+) -> Result<Json<BacktestCompareResponse>, (StatusCode, Json<ErrorResponse>)> {
+    request
+        .validate()
+        .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
+    if request.end_date > Utc::now().date_naive() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "endDate cannot be in the future".to_string(),
+            }),
+        ));
+    }
+
+    let response = backtest::compare(&state.db, &request).await.map_err(|e| {
+        tracing::error!("Backtest comparison failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    Ok(Json(response))
+}
@@ -1,83 +1,237 @@
+use std::collections::HashMap;
+
 use axum::{extract::State, http::StatusCode, Json};
-use chrono::{FixedOffset, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
+use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Set,
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, QueryFilter, Set, TryInsertResult,
 };
 
 use crate::entities::{blockchain_events, prelude::*};
-use crate::models::blockchain_event::{BlockchainEventResponse, CreateBlockchainEventRequest};
+use crate::models::blockchain_event::{
+    BatchEventResult, BatchSaveBlockchainEventsResponse, BlockchainEventResponse,
+    CreateBlockchainEventRequest, SaveBlockchainEventRequest, SaveBlockchainEventResponse,
+};
 use crate::models::token::ErrorResponse;
+use crate::services::{event_amounts, units};
 use crate::AppState;
 
+/// Largest batch accepted by /save-blockchain-event
+const MAX_BATCH_SIZE: usize = 1000;
+
+/// How far in the future a block timestamp may be (clock skew between relayer and us)
+const MAX_FUTURE_SKEW_SECS: i64 = 300;
+
+/// POST /save-blockchain-event
+///
+/// Accepts one event or an array of events ordered by block number within
+/// each network (for relayers catching up after downtime). Idempotent: an
+/// event is identified by (tx_hash, log_index, network) and re-sent events
+/// refresh the stored row. A single event returns 201 when new and 200 when already stored; a
+/// batch returns 200 with a result per event.
 pub async fn save_blockchain_event(
     State(state): State<AppState>,
-    Json(payload): Json<CreateBlockchainEventRequest>,
-) -> Result<(StatusCode, Json<BlockchainEventResponse>), (StatusCode, Json<ErrorResponse>)> {
-    // Check if event with this tx_hash already exists
-    let existing = BlockchainEvents::find()
-        .filter(blockchain_events::Column::TxHash.eq(&payload.tx_hash))
-        .one(&state.db)
+    Json(payload): Json<SaveBlockchainEventRequest>,
+) -> Result<(StatusCode, Json<SaveBlockchainEventResponse>), (StatusCode, Json<ErrorResponse>)> {
+    match payload {
+        SaveBlockchainEventRequest::Single(event) => {
+            let (created, response) = save_event(&state.db, &event).await?;
+            let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+            Ok((status, Json(SaveBlockchainEventResponse::Single(Box::new(response)))))
+        }
+        SaveBlockchainEventRequest::Batch(events) => {
+            let response = save_batch(&state.db, &events).await?;
+            Ok((StatusCode::OK, Json(SaveBlockchainEventResponse::Batch(response))))
+        }
+    }
+}
+
+async fn save_batch(
+    db: &DatabaseConnection,
+    events: &[CreateBlockchainEventRequest],
+) -> Result<BatchSaveBlockchainEventsResponse, (StatusCode, Json<ErrorResponse>)> {
+    if events.len() > MAX_BATCH_SIZE {
+        return Err(bad_request(format!(
+            "Batch of {} events exceeds the limit of {}",
+            events.len(),
+            MAX_BATCH_SIZE
+        )));
+    }
+    validate_batch_order(events).map_err(bad_request)?;
+
+    let mut response = BatchSaveBlockchainEventsResponse {
+        created: 0,
+        updated: 0,
+        failed: 0,
+        results: Vec::with_capacity(events.len()),
+    };
+
+    // Items are independent: one bad event doesn't reject the rest
+    for (index, event) in events.iter().enumerate() {
+        let (status, stored, error) = match save_event(db, event).await {
+            Ok((true, stored)) => {
+                response.created += 1;
+                ("created", Some(stored), None)
+            }
+            Ok((false, stored)) => {
+                response.updated += 1;
+                ("updated", Some(stored), None)
+            }
+            Err((_, Json(e))) => {
+                response.failed += 1;
+                ("failed", None, Some(e.error))
+            }
+        };
+        response.results.push(BatchEventResult {
+            index,
+            tx_hash: event.tx_hash.clone(),
+            log_index: event.log_index,
+            status: status.to_string(),
+            event: stored,
+            error,
+        });
+    }
+
+    tracing::info!(
+        events = events.len(),
+        created = response.created,
+        updated = response.updated,
+        failed = response.failed,
+        "Saved blockchain event batch"
+    );
+
+    Ok(response)
+}
+
+/// Store one event; returns whether it was new
+async fn save_event(
+    db: &DatabaseConnection,
+    payload: &CreateBlockchainEventRequest,
+) -> Result<(bool, BlockchainEventResponse), (StatusCode, Json<ErrorResponse>)> {
+    let block_time = payload
+        .block_timestamp
+        .map(|ts| validate_block_timestamp(ts, Utc::now().timestamp()))
+        .transpose()
+        .map_err(bad_request)?;
+    let timestamp = block_time.unwrap_or_else(Utc::now).with_timezone(&FixedOffset::east_opt(0).unwrap());
+
+    let amount = resolve_amount(payload.amount, payload.raw_amount.as_deref(), payload.decimals, "amount")?;
+    let quantity = resolve_amount(
+        payload.quantity,
+        payload.raw_quantity.as_deref(),
+        payload.quantity_decimals,
+        "quantity",
+    )?;
+
+    // Normalize the identity columns so re-sent events hit the unique key
+    let tx_hash = payload.tx_hash.trim().to_lowercase();
+    let network = payload.network.trim().to_lowercase();
+    let contract_address = payload.contract_address.trim().to_lowercase();
+
+    let new_event = blockchain_events::ActiveModel {
+        tx_hash: Set(tx_hash.clone()),
+        block_number: Set(payload.block_number),
+        log_index: Set(payload.log_index),
+        event_type: Set(payload.event_type.clone()),
+        contract_address: Set(contract_address.clone()),
+        network: Set(network.clone()),
+        user_address: Set(payload.user_address.clone()),
+        amount: Set(amount.value),
+        quantity: Set(quantity.value),
+        timestamp: Set(Some(timestamp)),
+        raw_amount: Set(amount.raw.clone()),
+        decimals: Set(amount.decimals),
+        raw_quantity: Set(quantity.raw.clone()),
+        quantity_decimals: Set(quantity.decimals),
+        ..Default::default()
+    };
+
+    // Insert, or detect that (tx_hash, log_index, network) is already stored.
+    // Relayers retry, so the same log arriving twice must not add a second row.
+    let inserted = BlockchainEvents::insert(new_event)
+        .on_conflict(
+            OnConflict::columns([
+                blockchain_events::Column::TxHash,
+                blockchain_events::Column::LogIndex,
+                blockchain_events::Column::Network,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
         .await
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
-                    error: format!("Database error: {}", e),
+                    error: format!("Failed to insert blockchain event: {}", e),
                 }),
             )
         })?;
+    let created = matches!(inserted, TryInsertResult::Inserted(_));
 
-    let result = if let Some(existing_event) = existing {
-        // Update existing event
-        let mut active_model = existing_event.into_active_model();
-        
-        active_model.block_number = Set(payload.block_number);
-        active_model.log_index = Set(payload.log_index);
-        active_model.event_type = Set(payload.event_type.clone());
-        active_model.contract_address = Set(payload.contract_address.clone());
-        active_model.network = Set(payload.network.clone());
-        active_model.user_address = Set(payload.user_address.clone());
-        active_model.amount = Set(payload.amount);
-        active_model.quantity = Set(payload.quantity);
-        active_model.timestamp = Set(Some(Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap())));
-
-        active_model.update(&state.db).await.map_err(|e| {
+    let stored = BlockchainEvents::find()
+        .filter(blockchain_events::Column::TxHash.eq(&tx_hash))
+        .filter(blockchain_events::Column::LogIndex.eq(payload.log_index))
+        .filter(blockchain_events::Column::Network.eq(&network))
+        .one(db)
+        .await
+        .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
-                    error: format!("Failed to update blockchain event: {}", e),
+                    error: format!("Database error: {}", e),
                 }),
             )
         })?
+        .ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Blockchain event {}:{} missing after insert", tx_hash, payload.log_index),
+                }),
+            )
+        })?;
+
+    let result = if created {
+        stored
     } else {
-        // Insert new event
-        let new_event = blockchain_events::ActiveModel {
-            tx_hash: Set(payload.tx_hash.clone()),
-            block_number: Set(payload.block_number),
-            log_index: Set(payload.log_index),
-            event_type: Set(payload.event_type.clone()),
-            contract_address: Set(payload.contract_address.clone()),
-            network: Set(payload.network.clone()),
-            user_address: Set(payload.user_address.clone()),
-            amount: Set(payload.amount),
-            quantity: Set(payload.quantity),
-            timestamp: Set(Some(Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()))),
-            ..Default::default()
-        };
+        // Already stored: refresh the payload fields. The original receive
+        // time is kept unless the relayer now sent the block time.
+        let mut active_model = stored.into_active_model();
 
-        new_event.insert(&state.db).await.map_err(|e| {
+        if block_time.is_some() {
+            active_model.timestamp = Set(Some(timestamp));
+        }
+
+        active_model.block_number = Set(payload.block_number);
+        active_model.event_type = Set(payload.event_type.clone());
+        active_model.contract_address = Set(contract_address);
+        active_model.user_address = Set(payload.user_address.clone());
+        active_model.amount = Set(amount.value);
+        active_model.quantity = Set(quantity.value);
+        active_model.raw_amount = Set(amount.raw.clone());
+        active_model.decimals = Set(amount.decimals);
+        active_model.raw_quantity = Set(quantity.raw.clone());
+        active_model.quantity_decimals = Set(quantity.decimals);
+
+        active_model.update(db).await.map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
-                    error: format!("Failed to insert blockchain event: {}", e),
+                    error: format!("Failed to update blockchain event: {}", e),
                 }),
             )
         })?
     };
 
+    let (amount, quantity) = (event_amounts::amount(&result), event_amounts::quantity(&result));
+
     Ok((
-        StatusCode::CREATED,
-        Json(BlockchainEventResponse {
+        created,
+        BlockchainEventResponse {
             id: result.id,
             tx_hash: result.tx_hash,
             block_number: result.block_number,
@@ -86,9 +240,177 @@ pub async fn save_blockchain_event(
             contract_address: result.contract_address,
             network: result.network,
             user_address: result.user_address,
-            amount: result.amount,
-            quantity: result.quantity,
+            amount,
+            quantity,
+            raw_amount: result.raw_amount,
+            decimals: result.decimals,
+            raw_quantity: result.raw_quantity,
+            quantity_decimals: result.quantity_decimals,
+            created,
             timestamp: result.timestamp.map(|dt| dt.naive_utc()),
-        }),
+        },
     ))
-}
\ No newline at end of file
+}
+
+/// Amount columns of an event as they will be stored
+#[derive(Debug)]
+struct ResolvedAmount {
+    value: Option<Decimal>,
+    raw: Option<String>,
+    decimals: Option<i32>,
+}
+
+/// Validate a raw on-chain amount and derive its Decimal value
+///
+/// Without a raw value the pre-converted Decimal is stored as-is.
+fn resolve_amount(
+    value: Option<Decimal>,
+    raw: Option<&str>,
+    decimals: Option<u32>,
+    field: &str,
+) -> Result<ResolvedAmount, (StatusCode, Json<ErrorResponse>)> {
+    let Some(raw) = raw else {
+        return Ok(ResolvedAmount { value, raw: None, decimals: None });
+    };
+
+    let decimals = decimals.ok_or_else(|| bad_request(format!("raw {} requires its decimals", field)))?;
+    if decimals > units::MAX_DECIMALS {
+        return Err(bad_request(format!(
+            "{} decimals must be at most {}, got {}",
+            field,
+            units::MAX_DECIMALS,
+            decimals
+        )));
+    }
+    let raw = units::parse_raw(raw).map_err(|e| bad_request(e.to_string()))?;
+    let value = units::from_base_units(raw, decimals).map_err(|e| bad_request(e.to_string()))?;
+
+    Ok(ResolvedAmount {
+        value: Some(value),
+        raw: Some(raw.to_string()),
+        decimals: Some(decimals as i32),
+    })
+}
+
+/// Block timestamps must be positive and not meaningfully in the future
+fn validate_block_timestamp(timestamp: i64, now: i64) -> Result<DateTime<Utc>, String> {
+    if timestamp > now + MAX_FUTURE_SKEW_SECS {
+        return Err(format!("Block timestamp {} is in the future", timestamp));
+    }
+    DateTime::from_timestamp(timestamp, 0)
+        .filter(|_| timestamp > 0)
+        .ok_or_else(|| format!("Invalid block timestamp {}", timestamp))
+}
+
+/// Batches must be ordered by (block number, log index) within each network,
+/// with block timestamps that never go backwards; events of different
+/// networks may be interleaved
+fn validate_batch_order(events: &[CreateBlockchainEventRequest]) -> Result<(), String> {
+    let mut last_by_network: HashMap<String, &CreateBlockchainEventRequest> = HashMap::new();
+    for (i, next) in events.iter().enumerate() {
+        let network = next.network.trim().to_lowercase();
+        if let Some(prev) = last_by_network.get(&network) {
+            if (next.block_number, next.log_index) < (prev.block_number, prev.log_index) {
+                return Err(format!(
+                    "Events must be ordered by block number per network: event {} (block {}, log {}) comes after block {}, log {} on {}",
+                    i,
+                    next.block_number,
+                    next.log_index,
+                    prev.block_number,
+                    prev.log_index,
+                    network
+                ));
+            }
+            if let (Some(prev_ts), Some(next_ts)) = (prev.block_timestamp, next.block_timestamp)
+                && next_ts < prev_ts
+            {
+                return Err(format!(
+                    "Block timestamps go backwards at event {} ({} < {}) on {}",
+                    i, next_ts, prev_ts, network
+                ));
+            }
+        }
+        last_by_network.insert(network, next);
+    }
+    Ok(())
+}
+
+fn bad_request(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(block_number: i32, log_index: i32, block_timestamp: Option<i64>) -> CreateBlockchainEventRequest {
+        CreateBlockchainEventRequest {
+            tx_hash: format!("0x{}{}", block_number, log_index),
+            block_number,
+            log_index,
+            event_type: "mint".to_string(),
+            contract_address: "0xindex".to_string(),
+            network: "base".to_string(),
+            user_address: None,
+            amount: None,
+            quantity: None,
+            raw_amount: None,
+            decimals: None,
+            raw_quantity: None,
+            quantity_decimals: None,
+            block_timestamp,
+        }
+    }
+
+    #[test]
+    fn test_batch_ordered_by_block() {
+        let events = vec![event(10, 0, Some(100)), event(10, 3, Some(100)), event(12, 1, Some(124))];
+        assert!(validate_batch_order(&events).is_ok());
+        assert!(validate_batch_order(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_batch_rejects_out_of_order_blocks() {
+        assert!(validate_batch_order(&[event(12, 0, None), event(10, 0, None)]).is_err());
+        assert!(validate_batch_order(&[event(10, 3, None), event(10, 1, None)]).is_err());
+    }
+
+    #[test]
+    fn test_batch_ordered_per_network() {
+        let on = |network: &str, block_number, log_index, block_timestamp| CreateBlockchainEventRequest {
+            network: network.to_string(),
+            ..event(block_number, log_index, Some(block_timestamp))
+        };
+        let events = vec![on("base", 500, 0, 200), on("ethereum", 20, 1, 100), on("Base", 501, 0, 202), on("ethereum", 21, 0, 112)];
+        assert!(validate_batch_order(&events).is_ok());
+        assert!(validate_batch_order(&[on("base", 500, 0, 200), on("ethereum", 20, 1, 100), on("base", 499, 0, 198)]).is_err());
+    }
+
+    #[test]
+    fn test_batch_rejects_backwards_timestamps() {
+        assert!(validate_batch_order(&[event(10, 0, Some(200)), event(11, 0, Some(100))]).is_err());
+    }
+
+    #[test]
+    fn test_validate_block_timestamp() {
+        let now = 1_700_000_000;
+        assert_eq!(
+            validate_block_timestamp(now - 86400, now).unwrap().timestamp(),
+            now - 86400
+        );
+        assert!(validate_block_timestamp(now + 60, now).is_ok());
+        assert!(validate_block_timestamp(now + 3600, now).is_err());
+        assert!(validate_block_timestamp(0, now).is_err());
+    }
+
+    #[test]
+    fn test_resolve_amount_rejects_too_many_decimals() {
+        let (status, Json(body)) = resolve_amount(None, Some("1"), Some(4_000_000_000), "amount").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.error.contains("at most 77"));
+
+        let resolved = resolve_amount(None, Some("1"), Some(units::MAX_DECIMALS), "amount").unwrap();
+        assert_eq!(resolved.value, Some(Decimal::ZERO));
+        assert_eq!(resolved.decimals, Some(77));
+    }
+}
@@ -0,0 +1,229 @@
+//! Cache-Control headers per endpoint
+//!
+//! A middleware layer on the router picks a caching policy from the matched
+//! route, so CDNs and browsers can reuse responses instead of refetching:
+//! history that can no longer change is cached for a year, daily data for a
+//! few minutes, live prices for seconds, and admin, write and per-user
+//! responses not at all. Error responses are never cached, and a header set
+//! by the handler itself is left alone. Routes translated from
+//! Accept-Language get `Vary: Accept-Language` so a shared cache keeps one
+//! copy per language.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{NaiveDate, Utc};
+
+/// How long a response may be reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Admin, writes, per-user data and in-progress status
+    NoStore,
+    /// Live prices and listings that move within seconds
+    Live,
+    /// Data that changes at most a few times a day (daily prices, rankings)
+    Recent,
+    /// Slow-moving reference data (asset and category lists)
+    Reference,
+    /// Requests fully in the past, whose answer no longer changes
+    Historical,
+}
+
+impl CachePolicy {
+    pub fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(match self {
+            CachePolicy::NoStore => "no-store",
+            CachePolicy::Live => "public, max-age=10",
+            CachePolicy::Recent => "public, max-age=300, stale-while-revalidate=60",
+            CachePolicy::Reference => "public, max-age=3600",
+            CachePolicy::Historical => "public, max-age=31536000, immutable",
+        })
+    }
+}
+
+/// Policy for a request to `route` (the matched route template)
+pub fn policy_for(method: &Method, route: &str, query: Option<&str>, today: NaiveDate) -> CachePolicy {
+    if method != Method::GET && method != Method::HEAD {
+        return CachePolicy::NoStore;
+    }
+
+    // Answers keyed on a date before today are final
+    let dated = |param: &str| {
+        query_date(query, param)
+            .map(|date| if date < today { CachePolicy::Historical } else { CachePolicy::Recent })
+            .unwrap_or(CachePolicy::Recent)
+    };
+
+    match route {
+        r if r.starts_with("/admin/") => CachePolicy::NoStore,
+
+        "/indexes/{index_id}/price-at-date" | "/api/market-cap/top-category" | "/api/market-cap/top" => {
+            dated("date")
+        }
+        "/api/market-cap/history" => dated("end_date"),
+
+        "/indexes/{index_id}/last-price"
+        | "/api/market-cap/live-category"
+        | "/indexes"
+        | "/api/itp/list"
+        | "/api/keeper-charts/all"
+        | "/api/solvers"
+        | "/stats"
+        | "/api/itp/{address}/execution-quote"
+        | "/api/keeper-charts/{keeper_address}/latest"
+        | "/current-index-weight/{index_id}" => CachePolicy::Live,
+
+        "/fetch-index-historical-data/{index_id}"
+        | "/download-daily-price-data/{index_id}"
+        | "/fetch-coin-historical-data/{coin_id}"
+        | "/indexes/{index_id}/transactions"
+        | "/indexes/leaderboard"
+        | "/index-categories"
+        | "/indexes/{index_id}/sparkline"
+        | "/indexes/{index_id}/tvl-history"
+        | "/api/itp/{id}/history"
+        | "/api/itp/{index_id}/rebalances"
+        | "/api/itp/{address}/drift"
+        | "/coins/by-contract/{chain}/{address}"
+        | "/api/listings"
+        | "/crypto-listings"
+        | "/feeds/announcements.xml"
+        | "/feeds/indexes/{index_id}/events.xml"
+        | "/api/analytics/rolling-stats"
+        | "/indexes/{index_id}/snapshot-proof/{date}"
+        | "/exchanges/{exchange}/pairs"
+        | "/api/keeper-charts/{keeper_address}/history"
+        | "/categories/{category_id}/members"
+        | "/get-index-config/{index_id}"
+        | "/indexes/{index_id}/methodology" => CachePolicy::Recent,
+
+        "/fetch-all-assets"
+        | "/fetch-vault-assets/{index_id}"
+        | "/coingecko-categories"
+        | "/api/categories/with-counts"
+        | "/api/exchange/tradeable-pairs"
+        | "/api/exchange/all-tradeable-assets"
+        | "/api/coins/symbol-mapping"
+        | "/indexes/{index_id}/methodology/{version}" => CachePolicy::Reference,
+
+        // Status polling, websockets, per-user data and anything unlisted
+        _ => CachePolicy::NoStore,
+    }
+}
+
+/// Routes whose body depends on Accept-Language (index translations)
+pub fn varies_by_language(route: &str) -> bool {
+    matches!(route, "/indexes" | "/get-index-config/{index_id}")
+}
+
+/// A `YYYY-MM-DD` query parameter
+fn query_date(query: Option<&str>, param: &str) -> Option<NaiveDate> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == param)
+        .and_then(|(_, value)| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
+
+/// Middleware setting Cache-Control on every response
+pub async fn cache_control(request: Request, next: Next) -> Response {
+    let (policy, vary_language) = match request.extensions().get::<MatchedPath>() {
+        Some(route) => (
+            policy_for(request.method(), route.as_str(), request.uri().query(), Utc::now().date_naive()),
+            varies_by_language(route.as_str()),
+        ),
+        None => (CachePolicy::NoStore, false),
+    };
+
+    let mut response = next.run(request).await;
+
+    let policy = if response.status().is_success() { policy } else { CachePolicy::NoStore };
+    response
+        .headers_mut()
+        .entry(header::CACHE_CONTROL)
+        .or_insert_with(|| policy.header_value());
+    if vary_language {
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept-language"));
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    fn get_policy(route: &str, query: Option<&str>) -> CachePolicy {
+        policy_for(&Method::GET, route, query, today())
+    }
+
+    #[test]
+    fn test_policy_by_route() {
+        assert_eq!(get_policy("/indexes/{index_id}/last-price", None), CachePolicy::Live);
+        assert_eq!(get_policy("/fetch-index-historical-data/{index_id}", None), CachePolicy::Recent);
+        assert_eq!(get_policy("/fetch-all-assets", None), CachePolicy::Reference);
+        assert_eq!(get_policy("/admin/data-freshness", None), CachePolicy::NoStore);
+        assert_eq!(get_policy("/api/itp/status/{nonce}", None), CachePolicy::NoStore);
+        assert_eq!(policy_for(&Method::POST, "/indexes", None, today()), CachePolicy::NoStore);
+    }
+
+    #[test]
+    fn test_past_dates_are_historical() {
+        let route = "/indexes/{index_id}/price-at-date";
+        assert_eq!(get_policy(route, Some("date=2024-05-31")), CachePolicy::Historical);
+        assert_eq!(get_policy(route, Some("date=2025-06-01")), CachePolicy::Recent);
+        assert_eq!(get_policy(route, Some("date=bogus")), CachePolicy::Recent);
+
+        let history = "/api/market-cap/history";
+        assert_eq!(
+            get_policy(history, Some("coin_id=bitcoin&start_date=2024-01-01&end_date=2024-12-31")),
+            CachePolicy::Historical
+        );
+        // Open-ended ranges run up to today
+        assert_eq!(get_policy(history, Some("coin_id=bitcoin&start_date=2024-01-01")), CachePolicy::Recent);
+    }
+
+    #[tokio::test]
+    async fn test_layer_sets_header_from_matched_route() {
+        let app = Router::new()
+            .route("/indexes/{index_id}/last-price", get(|| async { "ok" }))
+            .route("/admin/data-freshness", get(|| async { "ok" }))
+            .route("/fetch-all-assets", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route("/indexes", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(cache_control));
+
+        let cache_header = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+                response.headers()[header::CACHE_CONTROL].to_str().unwrap().to_string()
+            }
+        };
+
+        assert_eq!(cache_header("/indexes/7/last-price").await, "public, max-age=10");
+        assert_eq!(cache_header("/admin/data-freshness").await, "no-store");
+        assert_eq!(cache_header("/fetch-all-assets").await, "no-store");
+
+        // Translated per language
+        let vary = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+                response.headers().get(header::VARY).map(|v| v.to_str().unwrap().to_string())
+            }
+        };
+        assert_eq!(vary("/indexes").await.as_deref(), Some("accept-language"));
+        assert_eq!(vary("/indexes/7/last-price").await, None);
+    }
+}
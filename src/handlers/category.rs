@@ -1,9 +1,20 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
 use sea_orm::{ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder};
 use std::collections::{HashMap, HashSet};
 
 use crate::entities::{category_membership, coingecko_categories, prelude::*};
-use crate::models::category::{CategoriesListResponse, CategoriesWithCountResponse, CategoryResponse, CategoryWithCountResponse};
+use crate::models::category::{
+    CategoriesListResponse, CategoriesWithCountResponse, CategoryMemberResponse,
+    CategoryMembersQuery, CategoryMembersResponse, CategoryResponse, CategoryWithCountResponse,
+};
+use crate::handlers::maintenance::require_feature;
+use crate::services::category_service;
+use crate::services::feature_flags::flags;
 use crate::models::token::ErrorResponse;
 use crate::AppState;
 
@@ -50,6 +61,8 @@ pub async fn get_coingecko_categories(
 pub async fn get_categories_with_counts(
     State(state): State<AppState>,
 ) -> Result<Json<CategoriesWithCountResponse>, (StatusCode, Json<ErrorResponse>)> {
+    require_feature(&state, flags::LIVE_EXCHANGE_CHECKS).await?;
+
     // Step 1: Get all tradeable symbols from exchange API
     let tradeable_tokens = state.exchange_api.get_all_tradeable_symbols().await
         .map_err(|e| {
@@ -143,3 +156,77 @@ pub async fn get_categories_with_counts(
 
     Ok(Json(response))
 }
+
+
+/// GET /categories/{category_id}/members?date=YYYY-MM-DD
+///
+/// Returns the coins that belonged to a category as of the given date
+/// (defaults to today), using the same point-in-time rules as index selection.
+pub async fn get_category_members(
+    State(state): State<AppState>,
+    Path(category_id): Path<String>,
+    Query(query): Query<CategoryMembersQuery>,
+) -> Result<Json<CategoryMembersResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let date = if let Some(ref date_str) = query.date {
+        NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("Invalid date format: '{}'. Expected YYYY-MM-DD", date_str),
+                }),
+            )
+        })?
+    } else {
+        Utc::now().date_naive()
+    };
+
+    let category = CoingeckoCategories::find()
+        .filter(coingecko_categories::Column::CategoryId.eq(&category_id))
+        .one(&state.db)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    if category.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Category '{}' not found", category_id),
+            }),
+        ));
+    }
+
+    let memberships = category_service::get_category_members_at(&state.db, &category_id, date)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    let members: Vec<CategoryMemberResponse> = memberships
+        .into_iter()
+        .map(|m| CategoryMemberResponse {
+            coin_id: m.coin_id,
+            symbol: m.symbol,
+            added_date: m.added_date.format("%Y-%m-%d").to_string(),
+            removed_date: m.removed_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        })
+        .collect();
+
+    Ok(Json(CategoryMembersResponse {
+        category_id,
+        date: date.format("%Y-%m-%d").to_string(),
+        count: members.len(),
+        members,
+    }))
+}
@@ -0,0 +1,45 @@
+//! Composition generation handler
+//!
+//! POST /api/compositions/generate weights a list of assets for a new ITP
+//! (see services::composition). The result's asset_ids and weights are what
+//! POST /api/itp/create expects.
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+
+use crate::config::CompositionLimits;
+use crate::models::composition::{GenerateCompositionRequest, GeneratedComposition};
+use crate::models::token::ErrorResponse;
+use crate::services::composition::{self, CompositionError};
+use crate::AppState;
+
+/// POST /api/compositions/generate
+pub async fn generate_composition(
+    State(state): State<AppState>,
+    Json(request): Json<GenerateCompositionRequest>,
+) -> Result<Json<GeneratedComposition>, (StatusCode, Json<ErrorResponse>)> {
+    let today = Utc::now().date_naive();
+    let composition = composition::generate(
+        &state.db,
+        &state.asset_registry,
+        &request,
+        &CompositionLimits::from_env(),
+        today,
+    )
+    .await
+    .map_err(composition_error)?;
+
+    Ok(Json(composition))
+}
+
+fn composition_error(e: CompositionError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        CompositionError::Database(_) => {
+            tracing::error!("Failed to generate composition: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        CompositionError::MissingMarketCaps(_) | CompositionError::LimitsUnmet(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
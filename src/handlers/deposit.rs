@@ -1,36 +1,50 @@
+use alloy::primitives::U256;
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
-use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect};
 use std::collections::HashMap;
 
 use crate::entities::{blockchain_events, daily_prices, prelude::*};
+use crate::handlers::admin::require_admin_credential;
+use crate::handlers::auth::require_wallet;
 use crate::models::deposit::{
     DepositTransactionAll, DepositTransactionResponse, DepositTransactionSingle,
 };
 use crate::models::token::ErrorResponse;
+use crate::services::{event_amounts, index_deployments};
+use crate::services::units::{self, UnitsError};
 use crate::AppState;
 
 const USDC_DECIMALS: u32 = 6;
 const INDEX_DECIMALS: u32 = 30;
-const NETWORK: &str = "base";
 
+/// GET /get-deposit-transaction-data/{index_id}/{address}
+///
+/// Deposits into one index (or all, with index_id -1). A specific address
+/// returns that wallet's deposits and requires its SIWE session; "0x0000"
+/// returns the index totals, broken down per holder only for admin
+/// credentials (X-API-Key).
 pub async fn get_deposit_transaction_data(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path((index_id, address)): Path<(i32, String)>,
 ) -> Result<Json<DepositTransactionResponse>, (StatusCode, Json<ErrorResponse>)> {
     let address_filter = if address.is_empty() || address == "0x0000" {
         None
     } else {
+        require_wallet(&state.auth, &headers, &address)?;
         Some(address.to_lowercase())
     };
 
     // ------- SINGLE-INDEX MODE -------
     if index_id != -1 {
-        let result = get_single_index_deposits(&state, index_id, address_filter).await?;
+        let per_holder = address_filter.is_none()
+            && headers.contains_key("x-api-key")
+            && require_admin_credential(&state, &headers).await.is_ok();
+        let result = get_single_index_deposits(&state, index_id, address_filter, per_holder).await?;
         return Ok(Json(DepositTransactionResponse::Single(result)));
     }
 
@@ -43,6 +57,7 @@ async fn get_single_index_deposits(
     state: &AppState,
     index_id: i32,
     address_filter: Option<String>,
+    per_holder: bool,
 ) -> Result<Vec<DepositTransactionSingle>, (StatusCode, Json<ErrorResponse>)> {
     // Get index metadata from database
     let index_data = IndexMetadata::find_by_id(index_id)
@@ -65,15 +80,23 @@ async fn get_single_index_deposits(
             )
         })?;
 
-    let index_address = index_data.address.to_lowercase();
+    let deployments = index_deployments::for_index(&state.db, &index_data)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
 
     // Get latest price
     let index_price = get_latest_price(state, index_id).await?;
 
-    // Get all mint events for this index
+    // Get all mint events for this index, on every network it is deployed on
     let all_rows = BlockchainEvents::find()
-        .filter(blockchain_events::Column::ContractAddress.eq(&index_address))
-        .filter(blockchain_events::Column::Network.eq(NETWORK))
+        .filter(index_deployments::events_condition(&deployments))
         .filter(blockchain_events::Column::EventType.eq("mint"))
         .all(&state.db)
         .await
@@ -87,12 +110,12 @@ async fn get_single_index_deposits(
         })?;
 
     // Calculate totals
-    let mut total_amount_base = Decimal::ZERO;
-    let mut total_qty_base = Decimal::ZERO;
+    let mut total_amount_base = U256::ZERO;
+    let mut total_qty_base = U256::ZERO;
 
     for ev in &all_rows {
-        total_amount_base += to_bigint_units(ev.amount.unwrap_or(Decimal::ZERO), USDC_DECIMALS);
-        total_qty_base += to_bigint_units(ev.quantity.unwrap_or(Decimal::ZERO), INDEX_DECIMALS);
+        total_amount_base += base_units(event_amounts::amount_base_units(ev, USDC_DECIMALS))?;
+        total_qty_base += base_units(event_amounts::quantity_base_units(ev, INDEX_DECIMALS))?;
     }
 
     // Filter by address if provided
@@ -110,7 +133,7 @@ async fn get_single_index_deposits(
         all_rows
     };
 
-    // Group by user
+    // Group by user for the per-holder breakdown, otherwise one totals row
     let mut grouped: HashMap<String, GroupedDeposit> = HashMap::new();
 
     for event in rows {
@@ -119,17 +142,17 @@ async fn get_single_index_deposits(
             .as_ref()
             .map(|s| s.to_lowercase())
             .unwrap_or_default();
-        let key = if address_filter.is_some() {
-            index_id.to_string()
-        } else {
+        let key = if per_holder {
             user.clone()
+        } else {
+            index_id.to_string()
         };
 
-        let amount_base = to_bigint_units(event.amount.unwrap_or(Decimal::ZERO), USDC_DECIMALS);
-        let qty_base = to_bigint_units(event.quantity.unwrap_or(Decimal::ZERO), INDEX_DECIMALS);
+        let amount_base = base_units(event_amounts::amount_base_units(&event, USDC_DECIMALS))?;
+        let qty_base = base_units(event_amounts::quantity_base_units(&event, INDEX_DECIMALS))?;
 
-        let supply = format_units(amount_base, USDC_DECIMALS);
-        let quantity = format_units(qty_base, INDEX_DECIMALS);
+        let supply = units::to_f64(amount_base, USDC_DECIMALS);
+        let quantity = units::to_f64(qty_base, INDEX_DECIMALS);
 
         grouped
             .entry(key)
@@ -140,11 +163,7 @@ async fn get_single_index_deposits(
                 g.quantity += quantity;
             })
             .or_insert_with(|| GroupedDeposit {
-                user: if address_filter.is_some() {
-                    None
-                } else {
-                    Some(user)
-                },
+                user: if per_holder { Some(user) } else { None },
                 deposit_count: 1,
                 supply,
                 supply_value_usd: supply,
@@ -152,8 +171,8 @@ async fn get_single_index_deposits(
             });
     }
 
-    let total_supply_number = format_units(total_amount_base, USDC_DECIMALS);
-    let total_quantity_number = format_units(total_qty_base, INDEX_DECIMALS);
+    let total_supply_number = units::to_f64(total_amount_base, USDC_DECIMALS);
+    let total_quantity_number = units::to_f64(total_qty_base, INDEX_DECIMALS);
 
     let result: Vec<DepositTransactionSingle> = grouped
         .into_values()
@@ -205,6 +224,26 @@ async fn get_all_indexes_deposits(
         )
     })?;
 
+    let deployments = index_deployments::for_indexes(&state.db, &all_indexes)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    // Events from any deployment of an index are grouped under the index address
+    let mut owner_of: HashMap<(String, String), String> = HashMap::new();
+    for index in &all_indexes {
+        for d in deployments.get(&index.index_id).into_iter().flatten() {
+            owner_of.insert((d.network.clone(), d.contract_address.clone()), index.address.to_lowercase());
+        }
+    }
+    let all_deployments: Vec<_> = deployments.into_values().flatten().collect();
+
     let by_addr: HashMap<String, _> = all_indexes
         .into_iter()
         .map(|idx| (idx.address.to_lowercase(), idx))
@@ -212,7 +251,7 @@ async fn get_all_indexes_deposits(
 
     // Get all mint events
     let all_rows = BlockchainEvents::find()
-        .filter(blockchain_events::Column::Network.eq(NETWORK))
+        .filter(index_deployments::events_condition(&all_deployments))
         .filter(blockchain_events::Column::EventType.eq("mint"))
         .all(&state.db)
         .await
@@ -244,19 +283,19 @@ async fn get_all_indexes_deposits(
     // Calculate overall totals per contract
     let mut totals_by_contract: HashMap<String, ContractTotals> = HashMap::new();
     for ev in &all_rows {
-        let contract = ev.contract_address.to_lowercase();
-        let entry = totals_by_contract.entry(contract).or_default();
-        entry.amount_base += to_bigint_units(ev.amount.unwrap_or(Decimal::ZERO), USDC_DECIMALS);
-        entry.qty_base += to_bigint_units(ev.quantity.unwrap_or(Decimal::ZERO), INDEX_DECIMALS);
+        let Some(contract) = owner_of.get(&event_key(ev)) else { continue };
+        let entry = totals_by_contract.entry(contract.clone()).or_default();
+        entry.amount_base += base_units(event_amounts::amount_base_units(ev, USDC_DECIMALS))?;
+        entry.qty_base += base_units(event_amounts::quantity_base_units(ev, INDEX_DECIMALS))?;
     }
 
     // Group filtered rows by contract
     let mut by_contract_filtered: HashMap<String, FilteredContractGroup> = HashMap::new();
     for ev in filtered_rows {
-        let contract = ev.contract_address.to_lowercase();
-        let entry = by_contract_filtered.entry(contract).or_default();
-        entry.amount_base += to_bigint_units(ev.amount.unwrap_or(Decimal::ZERO), USDC_DECIMALS);
-        entry.qty_base += to_bigint_units(ev.quantity.unwrap_or(Decimal::ZERO), INDEX_DECIMALS);
+        let Some(contract) = owner_of.get(&event_key(&ev)) else { continue };
+        let entry = by_contract_filtered.entry(contract.clone()).or_default();
+        entry.amount_base += base_units(event_amounts::amount_base_units(&ev, USDC_DECIMALS))?;
+        entry.qty_base += base_units(event_amounts::quantity_base_units(&ev, INDEX_DECIMALS))?;
         entry.count += 1;
     }
 
@@ -274,11 +313,11 @@ async fn get_all_indexes_deposits(
         if let Some(meta) = by_addr.get(&contract_addr) {
             let overall_totals = totals_by_contract.get(&contract_addr).cloned().unwrap_or_default();
 
-            let total_supply = format_units(overall_totals.amount_base, USDC_DECIMALS);
-            let total_quantity = format_units(overall_totals.qty_base, INDEX_DECIMALS);
+            let total_supply = units::to_f64(overall_totals.amount_base, USDC_DECIMALS);
+            let total_quantity = units::to_f64(overall_totals.qty_base, INDEX_DECIMALS);
 
-            let group_supply = format_units(group.amount_base, USDC_DECIMALS);
-            let group_quantity = format_units(group.qty_base, INDEX_DECIMALS);
+            let group_supply = units::to_f64(group.amount_base, USDC_DECIMALS);
+            let group_quantity = units::to_f64(group.qty_base, INDEX_DECIMALS);
 
             let share = if total_supply > 0.0 {
                 (group_supply / total_supply) * 100.0
@@ -338,27 +377,33 @@ async fn get_latest_price(
     Ok(price_row.and_then(|row| row.price.to_string().parse::<f64>().ok()))
 }
 
-fn to_bigint_units(value: Decimal, decimals: u32) -> Decimal {
-    let multiplier = Decimal::from(10_u64.pow(decimals));
-    value * multiplier
+/// (network, contract) of an event, as stored in index_deployments
+fn event_key(event: &blockchain_events::Model) -> (String, String) {
+    (event.network.to_lowercase(), event.contract_address.to_lowercase())
 }
 
-fn format_units(value: Decimal, decimals: u32) -> f64 {
-    let divisor = Decimal::from(10_u64.pow(decimals));
-    let result = value / divisor;
-    result.to_string().parse::<f64>().unwrap_or(0.0)
+/// Map a unit conversion failure on a stored event to a response error
+fn base_units(result: Result<U256, UnitsError>) -> Result<U256, (StatusCode, Json<ErrorResponse>)> {
+    result.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Invalid event amount: {}", e),
+            }),
+        )
+    })
 }
 
 #[derive(Default, Clone)]
 struct ContractTotals {
-    amount_base: Decimal,
-    qty_base: Decimal,
+    amount_base: U256,
+    qty_base: U256,
 }
 
 #[derive(Default)]
 struct FilteredContractGroup {
-    amount_base: Decimal,
-    qty_base: Decimal,
+    amount_base: U256,
+    qty_base: U256,
     count: i32,
 }
 
@@ -0,0 +1,64 @@
+//! Server-sent events
+//!
+//! GET /events/indexes/{index_id} streams an index's backfill progress,
+//! rebalance creation and deployment, and ITP status changes (see
+//! services::index_events) so the admin UI can follow them without polling.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use sea_orm::EntityTrait;
+
+use crate::entities::prelude::*;
+use crate::models::index_event::IndexEvent;
+use crate::models::token::ErrorResponse;
+use crate::services::index_events::IndexEventWatcher;
+use crate::AppState;
+
+/// How often the watched tables are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// GET /events/indexes/{index_id}
+pub async fn index_events(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, (StatusCode, Json<ErrorResponse>)> {
+    let index = IndexMetadata::find_by_id(index_id).one(&state.db).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+    if index.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Index {} not found", index_id),
+            }),
+        ));
+    }
+
+    let watcher = IndexEventWatcher::new(index_id);
+    let events = stream::unfold((state.db, watcher, true), move |(db, mut watcher, first)| async move {
+        if !first {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        let events = watcher.poll(&db).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to poll events of index {}: {}", index_id, e);
+            Vec::new()
+        });
+        Some((stream::iter(events), (db, watcher, false)))
+    })
+    .flatten()
+    .map(|event: IndexEvent| Event::default().event(event.name()).json_data(&event));
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
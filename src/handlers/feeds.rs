@@ -0,0 +1,330 @@
+//! Atom feeds
+//!
+//! GET /feeds/announcements.xml serves the scraped exchange announcements and
+//! GET /feeds/indexes/{index_id}/events.xml an index's composition changes,
+//! one entry per rebalance with the constituents it added and removed, so
+//! feed readers (Slack, newsletter tooling) can poll them as is.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+use crate::entities::{announcements, prelude::*, rebalances};
+use crate::models::list_query::ListQuery;
+use crate::models::token::ErrorResponse;
+use crate::services::list_query::{self, Field};
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::AppState;
+
+/// Entries per feed
+const FEED_LIMIT: u64 = 50;
+
+/// Announcement contents are cut to this many characters
+const SUMMARY_LEN: usize = 500;
+
+/// Authority of the entries' tag: URIs (RFC 4151)
+const TAG_AUTHORITY: &str = "indexmaker.global,2025";
+
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+/// Filter and sort fields of the announcements feed
+const ANNOUNCEMENT_FIELDS: &[Field<announcements::Column>] = &[
+    Field::text("source", announcements::Column::Source),
+    Field::text("type", announcements::Column::AnnouncementType),
+    Field::sort_only("date", announcements::Column::AnnounceDate),
+    Field::sort_only("title", announcements::Column::Title),
+];
+
+type FeedError = (StatusCode, Json<ErrorResponse>);
+
+fn db_error(e: sea_orm::DbErr) -> FeedError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: format!("Database error: {}", e),
+        }),
+    )
+}
+
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub updated: DateTime<Utc>,
+    pub link: Option<String>,
+    pub summary: String,
+}
+
+/// Escape text for an XML element or attribute
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Atom document for a feed; `updated` is its newest entry's, or now if empty
+pub fn render_atom(id: &str, title: &str, entries: &[FeedEntry]) -> String {
+    let updated = entries.iter().map(|e| e.updated).max().unwrap_or_else(Utc::now);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape(id)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    xml.push_str("  <author><name>IndexMaker</name></author>\n");
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+        xml.push_str(&format!("    <updated>{}</updated>\n", entry.updated.to_rfc3339()));
+        if let Some(link) = &entry.link {
+            xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(link)));
+        }
+        xml.push_str(&format!("    <summary>{}</summary>\n", escape(&entry.summary)));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn atom_response(xml: String) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, HeaderValue::from_static(ATOM_CONTENT_TYPE))], xml)
+}
+
+fn tag_uri(specific: &str) -> String {
+    format!("tag:{}:{}", TAG_AUTHORITY, specific)
+}
+
+fn utc(datetime: NaiveDateTime) -> DateTime<Utc> {
+    datetime.and_utc()
+}
+
+fn summarize(content: &str) -> String {
+    let content = content.trim();
+    match content.char_indices().nth(SUMMARY_LEN) {
+        Some((end, _)) => format!("{}…", &content[..end]),
+        None => content.to_string(),
+    }
+}
+
+fn announcement_entry(announcement: announcements::Model) -> FeedEntry {
+    FeedEntry {
+        id: tag_uri(&format!("announcements/{}", announcement.id)),
+        title: format!("[{}] {}", announcement.source, announcement.title),
+        updated: utc(announcement.announce_date),
+        link: announcement.url,
+        summary: summarize(&announcement.content),
+    }
+}
+
+/// Symbols added and removed going from `previous` to `current`, in
+/// `current`'s (resp. `previous`'s) order
+pub fn composition_changes(
+    previous: &[CoinRebalanceInfo],
+    current: &[CoinRebalanceInfo],
+) -> (Vec<String>, Vec<String>) {
+    let before: HashSet<&str> = previous.iter().map(|c| c.coin_id.as_str()).collect();
+    let after: HashSet<&str> = current.iter().map(|c| c.coin_id.as_str()).collect();
+
+    let added = current
+        .iter()
+        .filter(|c| !before.contains(c.coin_id.as_str()))
+        .map(|c| c.symbol.clone())
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|c| !after.contains(c.coin_id.as_str()))
+        .map(|c| c.symbol.clone())
+        .collect();
+    (added, removed)
+}
+
+fn rebalance_entry(
+    symbol: &str,
+    rebalance: &rebalances::Model,
+    previous: Option<&[CoinRebalanceInfo]>,
+    coins: &[CoinRebalanceInfo],
+) -> FeedEntry {
+    let mut changes = Vec::new();
+    let title = match previous {
+        None => format!("{} launched with {} constituents", symbol, coins.len()),
+        Some(previous) => {
+            let (added, removed) = composition_changes(previous, coins);
+            if !added.is_empty() {
+                changes.push(format!("Added: {}.", added.join(", ")));
+            }
+            if !removed.is_empty() {
+                changes.push(format!("Removed: {}.", removed.join(", ")));
+            }
+            match (added.len(), removed.len()) {
+                (0, 0) => format!("{} reweighted", symbol),
+                (a, r) => format!("{} rebalanced: {} added, {} removed", symbol, a, r),
+            }
+        }
+    };
+
+    let weights: Vec<String> = coins.iter().map(|c| format!("{} {}", c.symbol, c.weight)).collect();
+    changes.push(format!("Weights: {}.", weights.join(", ")));
+    if rebalance.deployed == Some(true) {
+        changes.push("Deployed on-chain.".to_string());
+    }
+
+    FeedEntry {
+        id: tag_uri(&format!("indexes/{}/rebalances/{}", rebalance.index_id, rebalance.id)),
+        title,
+        updated: DateTime::from_timestamp(rebalance.timestamp, 0).unwrap_or_default(),
+        link: None,
+        summary: format!("{} ({} rebalance) {}", symbol, rebalance.rebalance_type, changes.join(" ")),
+    }
+}
+
+/// GET /feeds/announcements.xml?filter=&sort=
+///
+/// The newest exchange announcements, linking to the original post.
+/// `filter` takes source and type (e.g. `source:binance`), and `sort` also
+/// date and title, ahead of the newest-first default (see
+/// services::list_query).
+pub async fn announcements_feed(
+    State(state): State<AppState>,
+    Query(list): Query<ListQuery>,
+) -> Result<impl IntoResponse, FeedError> {
+    let select = list_query::apply_query(Announcements::find(), ANNOUNCEMENT_FIELDS, &list)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })))?;
+    let entries: Vec<FeedEntry> = select
+        .order_by_desc(announcements::Column::AnnounceDate)
+        .order_by_desc(announcements::Column::Id)
+        .limit(FEED_LIMIT)
+        .all(&state.db)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(announcement_entry)
+        .collect();
+
+    Ok(atom_response(render_atom(
+        &tag_uri("announcements"),
+        "IndexMaker exchange announcements",
+        &entries,
+    )))
+}
+
+/// GET /feeds/indexes/{index_id}/events.xml
+///
+/// The index's newest rebalances, each with its composition changes.
+pub async fn index_events_feed(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+) -> Result<impl IntoResponse, FeedError> {
+    let index = IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Index {} not found", index_id),
+                }),
+            )
+        })?;
+
+    // One extra, to diff the oldest entry against
+    let mut history = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(index_id))
+        .order_by_desc(rebalances::Column::Timestamp)
+        .order_by_desc(rebalances::Column::Id)
+        .limit(FEED_LIMIT + 1)
+        .all(&state.db)
+        .await
+        .map_err(db_error)?;
+    let has_earlier = history.len() as u64 > FEED_LIMIT;
+    history.reverse();
+
+    let compositions: Vec<Vec<CoinRebalanceInfo>> = history
+        .iter()
+        .map(|rebalance| {
+            rebalance_schema::decode(rebalance.coins.clone()).unwrap_or_else(|e| {
+                tracing::warn!(rebalance_id = rebalance.id, "Unreadable rebalance coins: {}", e);
+                Vec::new()
+            })
+        })
+        .collect();
+
+    let mut entries: Vec<FeedEntry> = history
+        .iter()
+        .enumerate()
+        .skip(usize::from(has_earlier))
+        .map(|(i, rebalance)| {
+            let previous = i.checked_sub(1).map(|p| compositions[p].as_slice());
+            rebalance_entry(&index.symbol, rebalance, previous, &compositions[i])
+        })
+        .collect();
+    entries.reverse();
+
+    Ok(atom_response(render_atom(
+        &tag_uri(&format!("indexes/{}/events", index_id)),
+        &format!("{} ({}) composition changes", index.name, index.symbol),
+        &entries,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn coin(coin_id: &str, symbol: &str) -> CoinRebalanceInfo {
+        CoinRebalanceInfo {
+            coin_id: coin_id.to_string(),
+            symbol: symbol.to_string(),
+            quantity: "1".to_string(),
+            weight: "0.5".to_string(),
+            price: dec!(1),
+            exchange: "binance".to_string(),
+            trading_pair: "usdt".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("A & B <b>\"x\"</b> 'y'"), "A &amp; B &lt;b&gt;&quot;x&quot;&lt;/b&gt; &apos;y&apos;");
+        assert_eq!(escape("bell\u{7}\nline"), "bell\nline");
+    }
+
+    #[test]
+    fn test_composition_changes() {
+        let previous = [coin("bitcoin", "BTC"), coin("ethereum", "ETH"), coin("ripple", "XRP")];
+        let current = [coin("ethereum", "ETH"), coin("solana", "SOL"), coin("bitcoin", "BTC")];
+        assert_eq!(
+            composition_changes(&previous, &current),
+            (vec!["SOL".to_string()], vec!["XRP".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_summarize_truncates_on_char_boundary() {
+        let long = "é".repeat(SUMMARY_LEN + 10);
+        let summary = summarize(&long);
+        assert_eq!(summary.chars().count(), SUMMARY_LEN + 1);
+        assert!(summary.ends_with('…'));
+        assert_eq!(summarize("  short  "), "short");
+    }
+}
@@ -0,0 +1,405 @@
+//! GraphQL API (`graphql` feature)
+//!
+//! Read-only queries over indexes, their rebalances, constituents and daily
+//! prices, and ITPs, so a page can fetch what it shows in one request.
+//! Nested fields go through per-request dataloaders, so listing N indexes
+//! with their rebalances costs one query per field rather than N.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock};
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use axum::response::Html;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order, QueryFilter, QueryOrder};
+
+use crate::entities::{daily_prices, index_metadata, itps, prelude::*, rebalances};
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::AppState;
+
+/// Deepest nesting a query may have
+const MAX_DEPTH: usize = 8;
+
+/// Highest complexity (roughly, fields resolved) a query may have
+const MAX_COMPLEXITY: usize = 1000;
+
+pub type IndexMakerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: LazyLock<IndexMakerSchema> = LazyLock::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// POST /graphql
+pub async fn graphql(State(state): State<AppState>, request: GraphQLRequest) -> GraphQLResponse {
+    let loader = DataLoader::new(DbLoader { db: state.db.clone() }, tokio::spawn);
+    SCHEMA
+        .execute(request.into_inner().data(state.db).data(loader))
+        .await
+        .into()
+}
+
+/// GET /graphql: the GraphiQL explorer
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// All indexes, optionally of one category
+    async fn indexes(&self, ctx: &Context<'_>, category: Option<String>) -> async_graphql::Result<Vec<Index>> {
+        let mut query = IndexMetadata::find().order_by(index_metadata::Column::IndexId, Order::Asc);
+        if let Some(category) = category {
+            query = query.filter(index_metadata::Column::Category.eq(category));
+        }
+        Ok(query.all(db(ctx)).await?.into_iter().map(Index).collect())
+    }
+
+    async fn index(&self, ctx: &Context<'_>, index_id: i32) -> async_graphql::Result<Option<Index>> {
+        Ok(loader(ctx).load_one(IndexKey(index_id)).await?.map(Index))
+    }
+
+    /// All ITPs, optionally in one state (0=initiated, 1=active, 2=paused,
+    /// 3=deprecated)
+    async fn itps(&self, ctx: &Context<'_>, state: Option<i16>) -> async_graphql::Result<Vec<Itp>> {
+        let mut query = Itps::find().order_by(itps::Column::Id, Order::Asc);
+        if let Some(state) = state {
+            query = query.filter(itps::Column::State.eq(state));
+        }
+        Ok(query.all(db(ctx)).await?.into_iter().map(Itp).collect())
+    }
+
+    async fn itp(&self, ctx: &Context<'_>, id: i32) -> async_graphql::Result<Option<Itp>> {
+        Ok(Itps::find_by_id(id).one(db(ctx)).await?.map(Itp))
+    }
+}
+
+pub struct Index(index_metadata::Model);
+
+#[Object]
+impl Index {
+    async fn index_id(&self) -> i32 {
+        self.0.index_id
+    }
+
+    async fn symbol(&self) -> &str {
+        &self.0.symbol
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn address(&self) -> &str {
+        &self.0.address
+    }
+
+    async fn category(&self) -> Option<&str> {
+        self.0.category.as_deref()
+    }
+
+    async fn asset_class(&self) -> Option<&str> {
+        self.0.asset_class.as_deref()
+    }
+
+    async fn initial_date(&self) -> Option<NaiveDate> {
+        self.0.initial_date
+    }
+
+    async fn initial_price(&self) -> Option<Decimal> {
+        self.0.initial_price
+    }
+
+    async fn rebalance_period(&self) -> Option<i32> {
+        self.0.rebalance_period
+    }
+
+    /// Rebalances, oldest first
+    async fn rebalances(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Rebalance>> {
+        let rebalances = loader(ctx).load_one(RebalancesOf(self.0.index_id)).await?.unwrap_or_default();
+        Ok(rebalances.into_iter().map(Rebalance).collect())
+    }
+
+    /// Constituents of the latest rebalance
+    async fn constituents(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Constituent>> {
+        let rebalances = loader(ctx).load_one(RebalancesOf(self.0.index_id)).await?.unwrap_or_default();
+        match rebalances.last() {
+            Some(latest) => constituents(latest),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Daily prices between `from` and `to` (inclusive), oldest first
+    async fn prices(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> async_graphql::Result<Vec<DailyPrice>> {
+        let key = PricesOf { index_id: self.0.index_id, from, to };
+        Ok(loader(ctx).load_one(key).await?.unwrap_or_default())
+    }
+
+    /// Latest daily price
+    async fn last_price(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<DailyPrice>> {
+        let prices = loader(ctx)
+            .load_one(PricesOf { index_id: self.0.index_id, from: None, to: None })
+            .await?
+            .unwrap_or_default();
+        Ok(prices.last().cloned())
+    }
+
+    /// ITPs tracking the index
+    async fn itps(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Itp>> {
+        let itps = loader(ctx).load_one(ItpsOf(self.0.index_id)).await?.unwrap_or_default();
+        Ok(itps.into_iter().map(Itp).collect())
+    }
+}
+
+pub struct Rebalance(rebalances::Model);
+
+#[Object]
+impl Rebalance {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    /// Unix timestamp the rebalance took effect at
+    async fn timestamp(&self) -> i64 {
+        self.0.timestamp
+    }
+
+    async fn date(&self) -> Option<NaiveDate> {
+        pricing_time::rebalance_date(self.0.timestamp)
+    }
+
+    async fn rebalance_type(&self) -> &str {
+        &self.0.rebalance_type
+    }
+
+    async fn portfolio_value(&self) -> Decimal {
+        self.0.portfolio_value
+    }
+
+    async fn total_weight(&self) -> Decimal {
+        self.0.total_weight
+    }
+
+    async fn deployed(&self) -> bool {
+        self.0.deployed.unwrap_or(false)
+    }
+
+    async fn tx_hash(&self) -> Option<&str> {
+        self.0.tx_hash.as_deref()
+    }
+
+    async fn constituents(&self) -> async_graphql::Result<Vec<Constituent>> {
+        constituents(&self.0)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Constituent {
+    coin_id: String,
+    symbol: String,
+    weight: String,
+    quantity: String,
+    /// Price at the rebalance
+    price: Option<Decimal>,
+    exchange: String,
+    trading_pair: String,
+}
+
+#[derive(Clone, SimpleObject)]
+pub struct DailyPrice {
+    date: NaiveDate,
+    price: Decimal,
+}
+
+pub struct Itp(itps::Model);
+
+#[Object]
+impl Itp {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn orbit_address(&self) -> &str {
+        &self.0.orbit_address
+    }
+
+    async fn arbitrum_address(&self) -> Option<&str> {
+        self.0.arbitrum_address.as_deref()
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn symbol(&self) -> &str {
+        &self.0.symbol
+    }
+
+    /// 0=initiated, 1=active, 2=paused, 3=deprecated
+    async fn state(&self) -> i16 {
+        self.0.state
+    }
+
+    /// Current price in smallest USDC units (6 decimals)
+    async fn current_price(&self) -> Option<Decimal> {
+        self.0.current_price
+    }
+
+    /// Total supply in smallest token units (18 decimals)
+    async fn total_supply(&self) -> Option<Decimal> {
+        self.0.total_supply
+    }
+
+    async fn index(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Index>> {
+        let Some(index_id) = self.0.index_id.and_then(|id| i32::try_from(id).ok()) else {
+            return Ok(None);
+        };
+        Ok(loader(ctx).load_one(IndexKey(index_id)).await?.map(Index))
+    }
+}
+
+fn db<'a>(ctx: &Context<'a>) -> &'a DatabaseConnection {
+    ctx.data_unchecked::<DatabaseConnection>()
+}
+
+fn loader<'a>(ctx: &Context<'a>) -> &'a DataLoader<DbLoader> {
+    ctx.data_unchecked::<DataLoader<DbLoader>>()
+}
+
+fn constituents(rebalance: &rebalances::Model) -> async_graphql::Result<Vec<Constituent>> {
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins.clone())
+        .map_err(|e| format!("Failed to parse rebalance {}: {}", rebalance.id, e))?;
+    Ok(coins
+        .into_iter()
+        .map(|coin| Constituent {
+            price: Some(coin.price),
+            coin_id: coin.coin_id,
+            symbol: coin.symbol,
+            weight: coin.weight,
+            quantity: coin.quantity,
+            exchange: coin.exchange,
+            trading_pair: coin.trading_pair,
+        })
+        .collect())
+}
+
+/// Batches the lookups of one request into one query per kind
+struct DbLoader {
+    db: DatabaseConnection,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct IndexKey(i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct RebalancesOf(i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct ItpsOf(i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PricesOf {
+    index_id: i32,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl Loader<IndexKey> for DbLoader {
+    type Value = index_metadata::Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[IndexKey]) -> Result<HashMap<IndexKey, Self::Value>, Self::Error> {
+        let indexes = IndexMetadata::find()
+            .filter(index_metadata::Column::IndexId.is_in(keys.iter().map(|key| key.0)))
+            .all(&self.db)
+            .await?;
+        Ok(indexes.into_iter().map(|index| (IndexKey(index.index_id), index)).collect())
+    }
+}
+
+impl Loader<RebalancesOf> for DbLoader {
+    type Value = Vec<rebalances::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[RebalancesOf]) -> Result<HashMap<RebalancesOf, Self::Value>, Self::Error> {
+        let rebalances = Rebalances::find()
+            .filter(rebalances::Column::IndexId.is_in(keys.iter().map(|key| key.0)))
+            .order_by(rebalances::Column::Timestamp, Order::Asc)
+            .all(&self.db)
+            .await?;
+        let mut by_index: HashMap<RebalancesOf, Self::Value> = HashMap::new();
+        for rebalance in rebalances {
+            by_index.entry(RebalancesOf(rebalance.index_id)).or_default().push(rebalance);
+        }
+        Ok(by_index)
+    }
+}
+
+impl Loader<ItpsOf> for DbLoader {
+    type Value = Vec<itps::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[ItpsOf]) -> Result<HashMap<ItpsOf, Self::Value>, Self::Error> {
+        let itps = Itps::find()
+            .filter(itps::Column::IndexId.is_in(keys.iter().map(|key| i64::from(key.0))))
+            .order_by(itps::Column::Id, Order::Asc)
+            .all(&self.db)
+            .await?;
+        let mut by_index: HashMap<ItpsOf, Self::Value> = HashMap::new();
+        for itp in itps {
+            if let Some(index_id) = itp.index_id.and_then(|id| i32::try_from(id).ok()) {
+                by_index.entry(ItpsOf(index_id)).or_default().push(itp);
+            }
+        }
+        Ok(by_index)
+    }
+}
+
+impl Loader<PricesOf> for DbLoader {
+    type Value = Vec<DailyPrice>;
+    type Error = Arc<DbErr>;
+
+    /// One query over the union of the keys' ranges, split per key
+    async fn load(&self, keys: &[PricesOf]) -> Result<HashMap<PricesOf, Self::Value>, Self::Error> {
+        let index_ids: HashSet<String> = keys.iter().map(|key| key.index_id.to_string()).collect();
+        let mut query = DailyPrices::find().filter(daily_prices::Column::IndexId.is_in(index_ids));
+        if let Some(from) = keys.iter().map(|key| key.from).min().flatten() {
+            query = query.filter(daily_prices::Column::Date.gte(from));
+        }
+        if let Some(to) = keys.iter().map(|key| key.to).collect::<Option<Vec<_>>>().and_then(|to| to.into_iter().max()) {
+            query = query.filter(daily_prices::Column::Date.lte(to));
+        }
+        let prices = query.order_by(daily_prices::Column::Date, Order::Asc).all(&self.db).await?;
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let index_id = key.index_id.to_string();
+                let in_range = prices
+                    .iter()
+                    .filter(|price| price.index_id == index_id)
+                    .filter(|price| key.from.is_none_or(|from| price.date >= from))
+                    .filter(|price| key.to.is_none_or(|to| price.date <= to))
+                    .map(|price| DailyPrice { date: price.date, price: price.price })
+                    .collect();
+                (*key, in_range)
+            })
+            .collect())
+    }
+}
@@ -1,3 +1,64 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use tracing::error;
+
+use crate::models::health::{ReadinessResponse, WalletBalanceStatus};
+use crate::services::wallet_balances;
+use crate::AppState;
+
 pub async fn hello_indexmaker() -> &'static str {
     "Hello from IndexMaker Backend! 🚀"
-}
\ No newline at end of file
+}
+
+/// GET /readyz
+///
+/// 503 when the database doesn't answer. Also reports the signer wallet's
+/// latest balance per network; a low balance is flagged but leaves the
+/// backend ready, since only transactions need gas.
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    if let Err(e) = state.db.ping().await {
+        error!(error = %e, "Readiness check: database unavailable");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "unavailable".to_string(),
+                database: false,
+                low_wallet_balance: false,
+                wallets: Vec::new(),
+            }),
+        );
+    }
+
+    let wallets: Vec<WalletBalanceStatus> = match wallet_balances::latest(&state.db, chrono::Utc::now().date_naive()).await {
+        Ok(checks) => checks.into_iter().map(Into::into).collect(),
+        Err(e) => {
+            error!(error = %e, "Readiness check: failed to load wallet balances");
+            Vec::new()
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(ReadinessResponse {
+            status: "ready".to_string(),
+            database: true,
+            low_wallet_balance: wallets.iter().any(|w| w.low),
+            wallets,
+        }),
+    )
+}
+
+/// GET /metrics
+///
+/// Prometheus text format: CoinGecko credit usage (see
+/// services::coingecko_usage).
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.coingecko.usage().render_metrics(),
+    )
+}
@@ -14,6 +14,7 @@ use crate::models::historical::{
     IndexHistoricalDataQuery, IndexHistoricalDataResponse,
 };
 use crate::models::token::ErrorResponse;
+use crate::services::pricing_time;
 use crate::AppState;
 
 pub async fn fetch_coin_historical_data(
@@ -92,10 +93,7 @@ pub async fn fetch_coin_historical_data(
             prev_price = Some(price);
 
             // Convert to DateTime for response
-            let date_time = current_date
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_utc();
+            let date_time = pricing_time::cutoff_utc(current_date);
 
             historical_data.push(HistoricalEntry {
                 name: coin_name.clone(),
@@ -517,7 +515,7 @@ async fn get_chart_data_from_daily_prices(
             // First entry: value = base_value (10000)
             chart_data.push(ChartDataEntry {
                 name: index_name.to_string(),
-                date: price_row.date.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                date: pricing_time::cutoff_utc(price_row.date),
                 price,
                 value: base_value,
             });
@@ -536,7 +534,7 @@ async fn get_chart_data_from_daily_prices(
 
             chart_data.push(ChartDataEntry {
                 name: index_name.to_string(),
-                date: price_row.date.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                date: pricing_time::cutoff_utc(price_row.date),
                 price,
                 value: base_value,
             });
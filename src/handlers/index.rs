@@ -1,11 +1,14 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 
+use alloy::primitives::U256;
 use axum::extract::{Path, Query, State};
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::Json;
 use chrono::{Datelike, Duration, NaiveDate, Utc};
-use rust_decimal::Decimal;
+use futures_util::stream::{self, StreamExt};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
 use rust_decimal_macros::dec;
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Order,
@@ -17,15 +20,39 @@ use crate::entities::{
     prelude::*, rebalances,
 };
 use crate::models::index::{
-    CollateralToken, ConstituentPriceInfo, ConstituentWeight, CreateIndexManualRequest,
+    BackfillStatusResponse, CollateralToken, ConstituentPriceInfo, ConstituentWeight, CreateIndexManualRequest,
     CreateIndexManualResponse, CreateIndexRequest, CreateIndexResponse, CurrentIndexWeightResponse,
-    IndexConfigResponse, IndexLastPriceResponse, IndexListEntry, IndexListResponse,
-    IndexPriceAtDateRequest, IndexPriceAtDateResponse, ManualRebalanceRequest,
-    ManualRebalanceResponse, Performance, Ratings, RemoveIndexRequest, RemoveIndexResponse,
+    IndexConfigResponse, IndexDeployment, IndexLastPriceResponse, IndexListEntry, IndexListResponse,
+    IndexPriceAtDateRequest, IndexPriceAtDateResponse, InceptionReport, ManualRebalanceRequest,
+    ManualRebalanceResponse, Performance, PriceSource, Ratings, RemoveIndexRequest, RemoveIndexResponse,
+    SeedInceptionRequest, SparklineQuery, SparklineResponse,
 };
+use crate::handlers::admin::require_admin_key;
+use crate::models::index_translation::IndexTranslationResponse;
+use crate::models::label::LabelFilterQuery;
+use crate::models::list_query::ListQuery;
+use crate::models::methodology::MethodologyVersionRef;
 use crate::models::token::ErrorResponse;
+use crate::services::background_tasks;
+use crate::services::cash_buffer;
 use crate::services::coingecko::CoinGeckoService;
-use crate::services::rebalancing::{CoinRebalanceInfo, RebalancingService};
+use crate::services::event_amounts;
+use crate::services::feature_flags::flags;
+use crate::services::inception::{self, InceptionError, InceptionSeed};
+use crate::services::index_deployments;
+use crate::services::index_translations;
+use crate::services::labels::{self, entity_types};
+use crate::services::leverage;
+use crate::services::list_query::{self, Field};
+use crate::services::methodology_documents;
+use crate::services::price_fallback::{self, PriceFallback};
+use crate::services::price_utils;
+use crate::services::pricing_time;
+use crate::services::realtime_prices::{PriceData, RealTimePriceService};
+use crate::services::sparkline;
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::services::units;
 use crate::AppState;
 
 static DEFAULT_CURATOR: LazyLock<String> = LazyLock::new(|| {
@@ -40,14 +67,41 @@ static DEFAULT_MANAGEMENT_FEE: LazyLock<i32> = LazyLock::new(|| {
         .unwrap_or(2)
 });
 
+/// Decimal places of index prices and constituent values in responses
+const PRICE_RESPONSE_DP: u32 = 8;
+
+/// Sparkline window and resolution, by default and at most
+const DEFAULT_SPARKLINE_DAYS: i64 = 30;
+const MAX_SPARKLINE_DAYS: i64 = 3650;
+const DEFAULT_SPARKLINE_POINTS: usize = 60;
+const MAX_SPARKLINE_POINTS: usize = 500;
+
+/// Constituent prices fetched at once while assembling an index price
+const ON_THE_FLY_CONCURRENCY: usize = 8;
+
+/// Rounding for response values: banker's rounding so repeated rounding
+/// doesn't drift in one direction. Intermediate math is unrounded.
+fn round_for_response(value: Decimal) -> Decimal {
+    value.round_dp_with_strategy(PRICE_RESPONSE_DP, RoundingStrategy::MidpointNearestEven)
+}
+
 /// Shared calculation logic for index price
+///
+/// All arithmetic is done in `Decimal`; only the final index price and
+/// constituent values are rounded (see `round_for_response`). Prices missing
+/// from the database are taken from `live` exchange tickers, unless it is
+/// None, then fetched from `coingecko`, unless it is None. Prices that can't
+/// be had fall back as per services::price_fallback; the returned flag is set
+/// when any constituent fell back, each recorded in its
+/// `ConstituentPriceInfo` along with where its price came from.
 async fn calculate_index_price_internal(
     db: &DatabaseConnection,
-    coingecko: &CoinGeckoService,
+    coingecko: Option<&CoinGeckoService>,
+    live: Option<&RealTimePriceService>,
     index_id: i32,
     target_date: NaiveDate,
 ) -> Result<
-    (i64, f64, Vec<ConstituentPriceInfo>),
+    (i64, Decimal, Vec<ConstituentPriceInfo>, bool),
     (StatusCode, Json<ErrorResponse>),
 > {
     // Get index metadata
@@ -95,7 +149,7 @@ async fn calculate_index_price_internal(
     }
 
     // Get last rebalance before or on target date (this is T0)
-    let target_timestamp = target_date.and_hms_opt(23, 59, 59).unwrap().and_utc().timestamp();
+    let target_timestamp = pricing_time::day_end_timestamp(target_date);
 
     let last_rebalance = Rebalances::find()
         .filter(rebalances::Column::IndexId.eq(index_id))
@@ -123,12 +177,10 @@ async fn calculate_index_price_internal(
 
     // T0 timestamp and date (rebalance date)
     let t0_timestamp = last_rebalance.timestamp;
-    let t0_date = chrono::DateTime::from_timestamp(t0_timestamp, 0)
-        .unwrap()
-        .date_naive();
+    let t0_date = pricing_time::rebalance_date(t0_timestamp).unwrap_or_default();
 
     // Index price at T0 (from rebalance)
-    let index_price_t0: f64 = last_rebalance.portfolio_value.to_string().parse().unwrap_or(0.0);
+    let index_price_t0 = last_rebalance.portfolio_value;
 
     tracing::debug!(
         "Calculating price for index {} on {} (last rebalance: {}, base price: {})",
@@ -139,7 +191,7 @@ async fn calculate_index_price_internal(
     );
 
     // Parse constituents from rebalance (these have Price_T0 stored)
-    let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(last_rebalance.coins)
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(last_rebalance.coins)
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -149,21 +201,44 @@ async fn calculate_index_price_internal(
             )
         })?;
 
-    // Calculate price change contribution for each constituent
-    let mut constituent_prices = Vec::new();
-    let mut total_price_change = 0.0;
+    let internal_error = |error: String| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error }));
+
+    let coin_ids: Vec<String> = coins.iter().map(|coin| coin.coin_id.clone()).collect();
+    let mut live_prices = HashMap::new();
+    if let Some(live) = live {
+        for coin in &coins {
+            if let Some(data) = live.get_price_data(&coin.symbol).await {
+                live_prices.insert(coin.coin_id.clone(), data);
+            }
+        }
+    }
+    let mut prices_t1 = get_or_fetch_prices(db, coingecko, &live_prices, &coin_ids, target_date)
+        .await
+        .map_err(|e| internal_error(format!("Database error: {}", e)))?;
 
+    // Quantity, weight, price at T0 (stored in rebalance) and T1 (target
+    // date) of each constituent, None when excluded (see
+    // services::price_fallback)
+    let mut priced = Vec::new();
     for coin in coins {
-        // Price at T0 (stored in rebalance)
+        let parse = |field: &str, value: &str| {
+            value.parse::<Decimal>().map_err(|e| {
+                internal_error(format!(
+                    "Invalid {} '{}' for {} in rebalance {}: {}",
+                    field, value, coin.coin_id, last_rebalance.id, e
+                ))
+            })
+        };
+        let quantity = parse("quantity", &coin.quantity)?;
+        let weight = parse("weight", &coin.weight)?;
         let price_t0 = coin.price;
 
-        // Quantity (from rebalance)
-        let quantity: f64 = coin.quantity.parse().unwrap_or(0.0);
-        let weight: f64 = coin.weight.parse().unwrap_or(0.0);
-
-        // Get price at T1 (target date)
-        let price_t1 = match get_or_fetch_price(db, coingecko, &coin.coin_id, target_date).await {
-            Ok(p) => p,
+        let price_t1 = match prices_t1.remove(&coin.coin_id).unwrap_or(Ok(None)) {
+            Ok(Some(price)) => price,
+            Ok(None) => {
+                tracing::warn!("No price for {} ({}) on {}, excluding it", coin.symbol, coin.coin_id, target_date);
+                SourcedPrice::excluded()
+            }
             Err(e) => {
                 tracing::error!(
                     "Failed to get price for {} ({}) on {}: {}",
@@ -172,17 +247,38 @@ async fn calculate_index_price_internal(
                     target_date,
                     e
                 );
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: format!(
-                            "Failed to get price for {} on {}: {}",
-                            coin.symbol, target_date, e
-                        ),
-                    }),
-                ));
+                return Err(internal_error(format!(
+                    "Failed to get price for {} on {}: {}",
+                    coin.symbol, target_date, e
+                )));
             }
         };
+        priced.push((coin, quantity, weight, price_t0, price_t1));
+    }
+
+    // Excluded constituents move with the others
+    let values: Vec<(Decimal, Option<Decimal>)> = priced
+        .iter()
+        .map(|(_, quantity, _, price_t0, price_t1)| {
+            (quantity * price_t0, price_t1.price.map(|price| quantity * price))
+        })
+        .collect();
+    let stand_in_ratio = price_fallback::stand_in_ratio(&values).map_err(|e| {
+        internal_error(format!("Failed to price index {} on {}: {}", index_id, target_date, e))
+    })?;
+
+    // Calculate price change contribution for each constituent
+    let mut constituent_prices = Vec::new();
+    let mut total_price_change = Decimal::ZERO;
+    // Weight × quantity and T0 price of each constituent and basket value at
+    // T1, for leveraged indexes
+    let mut basket_units = Vec::new();
+    let mut basket_t1 = Decimal::ZERO;
+    let mut stale = false;
+
+    for (coin, quantity, weight, price_t0, sourced) in priced {
+        stale |= sourced.fallback.is_some();
+        let price_t1 = sourced.price.unwrap_or_else(|| price_t0 * stand_in_ratio.unwrap_or(Decimal::ONE));
 
         // Calculate price change contribution
         // Formula: Quantity × (Price_T1 - Price_T0)
@@ -192,15 +288,8 @@ async fn calculate_index_price_internal(
 
         // Value at T1
         let value_t1 = weight * quantity * price_t1;
-
-        constituent_prices.push(ConstituentPriceInfo {
-            coin_id: coin.coin_id,
-            symbol: coin.symbol.clone(),
-            quantity: coin.quantity,
-            weight: coin.weight,
-            price: price_t1,
-            value: value_t1,
-        });
+        basket_units.push((coin.coin_id.clone(), weight * quantity, price_t0));
+        basket_t1 += value_t1;
 
         tracing::debug!(
             "{}: Qty={}, Price T0={}, Price T1={}, Change={}, Contribution={}",
@@ -211,10 +300,31 @@ async fn calculate_index_price_internal(
             price_change,
             contribution
         );
+
+        constituent_prices.push(ConstituentPriceInfo {
+            coin_id: coin.coin_id,
+            symbol: coin.symbol,
+            quantity: coin.quantity,
+            weight: coin.weight,
+            price: price_t1,
+            value: round_for_response(value_t1),
+            fallback: sourced.fallback,
+            price_source: sourced.source,
+            price_timestamp: sourced.timestamp,
+        });
     }
 
-    // Final index price = Index Price at T0 + Total Price Change
-    let index_price_t1 = index_price_t0 + total_price_change;
+    // Final index price = Index Price at T0 + Total Price Change, or the NAV
+    // of a leveraged index
+    let index_price_t1 = match leverage::for_index(&index) {
+        None => round_for_response(index_price_t0 + total_price_change),
+        Some(factor) => {
+            let (nav, stale_basket) =
+                leveraged_price_at_date(db, coingecko, index_id, target_date, factor, &basket_units, basket_t1).await?;
+            stale |= stale_basket;
+            round_for_response(nav)
+        }
+    };
 
     tracing::debug!(
         "Index {} price on {}: Base={}, Change={}, Final={}",
@@ -225,7 +335,57 @@ async fn calculate_index_price_internal(
         index_price_t1
     );
 
-    Ok((t0_timestamp, index_price_t1, constituent_prices))
+    Ok((t0_timestamp, index_price_t1, constituent_prices, stale))
+}
+
+/// NAV of a leveraged index on `target_date` (see services::leverage): its
+/// daily price if stored, else the previous day's carried over by the
+/// basket's return; flagged when a price of the day before's fell back (see
+/// services::price_fallback)
+async fn leveraged_price_at_date(
+    db: &DatabaseConnection,
+    coingecko: Option<&CoinGeckoService>,
+    index_id: i32,
+    target_date: NaiveDate,
+    factor: Decimal,
+    basket_units: &[(String, Decimal, Decimal)],
+    basket_t1: Decimal,
+) -> Result<(Decimal, bool), (StatusCode, Json<ErrorResponse>)> {
+    let internal_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: e }));
+
+    let stored = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+        .filter(daily_prices::Column::Date.eq(target_date))
+        .one(db)
+        .await
+        .map_err(|e| internal_error(format!("Database error: {}", e)))?;
+    if let Some(stored) = stored {
+        return Ok((stored.price, false));
+    }
+
+    let day_before = target_date - chrono::Duration::days(1);
+    let mut values = Vec::new();
+    let mut stale = false;
+    let coin_ids: Vec<String> = basket_units.iter().map(|(coin_id, _, _)| coin_id.clone()).collect();
+    let mut prices = get_or_fetch_prices(db, coingecko, &HashMap::new(), &coin_ids, day_before)
+        .await
+        .map_err(|e| internal_error(format!("Database error: {}", e)))?;
+    for (coin_id, units, rebalance_price) in basket_units {
+        let price = prices
+            .remove(coin_id)
+            .unwrap_or(Ok(None))
+            .map_err(|e| internal_error(format!("Failed to get price for {} on {}: {}", coin_id, day_before, e)))?;
+        let price = price.unwrap_or_else(SourcedPrice::excluded);
+        stale |= price.fallback.is_some();
+        values.push((units * rebalance_price, price.price.map(|price| units * price)));
+    }
+    let basket_t0 = price_fallback::renormalized_value(&values)
+        .map_err(|e| internal_error(format!("Failed to price index {} on {}: {}", index_id, day_before, e)))?;
+
+    let nav = leverage::nav_on(db, index_id, target_date, factor, basket_t0, basket_t1)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok((nav, stale))
 }
 
 /// GET /indexes/{index_id}/price-at-date?date=YYYY-MM-DD
@@ -256,14 +416,16 @@ pub async fn get_index_price_at_date(
     }
 
     // Calculate price using shared logic
-    let (_timestamp, price, constituents) =
-        calculate_index_price_internal(&state.db, &state.coingecko, index_id, target_date).await?;
+    let (_timestamp, price, constituents, stale) =
+        calculate_index_price_internal(&state.db, on_the_fly_coingecko(&state).await, None, index_id, target_date)
+            .await?;
 
     Ok(Json(IndexPriceAtDateResponse {
         index_id,
         date: target_date.to_string(),
         price,
         constituents,
+        stale,
     }))
 }
 
@@ -275,9 +437,16 @@ pub async fn get_index_last_price(
     // Use today's date
     let today = Utc::now().date_naive();
 
-    // Calculate price using shared logic
-    let (timestamp, last_price, constituents) =
-        calculate_index_price_internal(&state.db, &state.coingecko, index_id, today).await?;
+    // Calculate price using shared logic, with live exchange prices standing
+    // in for today's not yet stored
+    let (timestamp, last_price, constituents, stale) = calculate_index_price_internal(
+        &state.db,
+        on_the_fly_coingecko(&state).await,
+        Some(&state.realtime_prices),
+        index_id,
+        today,
+    )
+    .await?;
 
     Ok(Json(IndexLastPriceResponse {
         index_id,
@@ -286,19 +455,204 @@ pub async fn get_index_last_price(
         last_bid: None,  // Not implemented yet
         last_ask: None,  // Not implemented yet
         constituents,
+        stale,
     }))
 }
 
+/// GET /indexes/{index_id}/sparkline?days=30&points=60
+///
+/// Daily prices of the last `days` (1 to `MAX_SPARKLINE_DAYS`) downsampled to
+/// at most `points` (3 to `MAX_SPARKLINE_POINTS`), for list-view charts (see
+/// services::sparkline).
+///
+/// # Response
+/// - 200: Sparkline points
+/// - 400: `days` or `points` out of range
+/// - 404: Unknown index
+pub async fn get_index_sparkline(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+    Query(query): Query<SparklineQuery>,
+) -> Result<Json<SparklineResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let days = query.days.unwrap_or(DEFAULT_SPARKLINE_DAYS);
+    if !(1..=MAX_SPARKLINE_DAYS).contains(&days) {
+        return Err(bad_request(format!("days must be between 1 and {}", MAX_SPARKLINE_DAYS)));
+    }
+    let points = query.points.unwrap_or(DEFAULT_SPARKLINE_POINTS);
+    if !(3..=MAX_SPARKLINE_POINTS).contains(&points) {
+        return Err(bad_request(format!("points must be between 3 and {}", MAX_SPARKLINE_POINTS)));
+    }
+
+    let db_error = |e: sea_orm::DbErr| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    };
+    if IndexMetadata::find_by_id(index_id).one(&state.db).await.map_err(db_error)?.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Index {} not found", index_id),
+            }),
+        ));
+    }
+
+    let today = pricing_time::pricing_date(Utc::now());
+    let points = sparkline::index_sparkline(&state.db, index_id, today, days, points)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(SparklineResponse { index_id, days, points }))
+}
+
+/// Status of the most recent historical backfill task for an index
+pub async fn get_backfill_status(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+) -> Result<Json<BackfillStatusResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let task = background_tasks::latest_for_index(
+        &state.db,
+        index_id,
+        background_tasks::task_types::INDEX_BACKFILL,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load backfill status for index {}: {}", index_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "Failed to load backfill status".to_string(),
+            }),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No backfill task found for index {}", index_id),
+            }),
+        )
+    })?;
+
+    Ok(Json(background_tasks::backfill_status(index_id, task, Utc::now().naive_utc())))
+}
+
+/// CoinGecko for on-the-fly price fetches, unless operators switched them off
+/// or today's credit budget is spent
+async fn on_the_fly_coingecko(state: &AppState) -> Option<&CoinGeckoService> {
+    if state.coingecko.budget_exhausted() {
+        tracing::debug!("CoinGecko credit budget exhausted, skipping on-the-fly fetches");
+        return None;
+    }
+    state
+        .feature_flags
+        .is_enabled(flags::COINGECKO_ON_THE_FLY)
+        .await
+        .then_some(&state.coingecko)
+}
+
+/// A constituent's price on a day, with where it came from
+struct SourcedPrice {
+    /// None when excluded
+    price: Option<Decimal>,
+    source: Option<PriceSource>,
+    /// Unix timestamp the price is for
+    timestamp: Option<i64>,
+    fallback: Option<PriceFallback>,
+}
+
+impl SourcedPrice {
+    /// The price of `date` from `source`
+    fn on(price: Decimal, source: PriceSource, date: NaiveDate) -> Self {
+        Self {
+            price: Some(price),
+            source: Some(source),
+            timestamp: Some(pricing_time::cutoff_timestamp(date)),
+            fallback: None,
+        }
+    }
+
+    fn cash() -> Self {
+        Self { price: Some(Decimal::ONE), source: None, timestamp: None, fallback: None }
+    }
+
+    fn excluded() -> Self {
+        Self { price: None, source: None, timestamp: None, fallback: Some(PriceFallback::Excluded) }
+    }
+}
+
+type PriceResult = Result<Option<SourcedPrice>, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Prices of several coins on one date, as `get_or_fetch_price` would return
+/// them one by one, `live` exchange prices by coin ID standing in for
+/// missing stored ones
+///
+/// Stored prices are loaded with one query. Each missing coin is then looked
+/// up once, `ON_THE_FLY_CONCURRENCY` at a time; requests for the same coin
+/// and day running concurrently elsewhere share one CoinGecko call (see
+/// `CoinGeckoService::get_price_on_date`).
+async fn get_or_fetch_prices(
+    db: &DatabaseConnection,
+    coingecko: Option<&CoinGeckoService>,
+    live: &HashMap<String, PriceData>,
+    coin_ids: &[String],
+    date: NaiveDate,
+) -> Result<HashMap<String, PriceResult>, sea_orm::DbErr> {
+    use crate::entities::{coins_historical_prices, prelude::*};
+
+    let mut prices: HashMap<String, PriceResult> = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.is_in(coin_ids.iter().cloned()))
+        .filter(coins_historical_prices::Column::Date.eq(date))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|record| (record.coin_id, Ok(Some(SourcedPrice::on(record.price, PriceSource::DbCache, date)))))
+        .collect();
+
+    let mut missing: Vec<String> =
+        coin_ids.iter().filter(|coin_id| !prices.contains_key(*coin_id)).cloned().collect();
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        tracing::debug!("{} of {} constituent prices missing on {}", missing.len(), coin_ids.len(), date);
+    }
+
+    let fetched: Vec<(String, PriceResult)> = stream::iter(missing)
+        .map(|coin_id| async move {
+            let price = get_or_fetch_price(db, coingecko, live.get(&coin_id), &coin_id, date).await;
+            (coin_id, price)
+        })
+        .buffer_unordered(ON_THE_FLY_CONCURRENCY)
+        .collect()
+        .await;
+    prices.extend(fetched);
+
+    Ok(prices)
+}
+
 /// Get price for a coin on a specific date, fetching from CoinGecko if not in database
+///
+/// A `live` exchange price, when given, is used before CoinGecko. When it
+/// can't be fetched (or CoinGecko is None), a recent stored price is carried
+/// forward; None when there is none either, for the caller to exclude the
+/// coin (see services::price_fallback).
 async fn get_or_fetch_price(
     db: &DatabaseConnection,
-    coingecko: &CoinGeckoService,
+    coingecko: Option<&CoinGeckoService>,
+    live: Option<&PriceData>,
     coin_id: &str,
     date: NaiveDate,
-) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+) -> PriceResult {
     use crate::entities::{coins_historical_prices, prelude::*};
     use sea_orm::ActiveModelTrait;
-    use rust_decimal::Decimal;
+
+    if cash_buffer::is_cash(coin_id) {
+        return Ok(Some(SourcedPrice::cash()));
+    }
 
     // Try to get from database first
     let existing = CoinsHistoricalPrices::find()
@@ -308,82 +662,54 @@ async fn get_or_fetch_price(
         .await?;
 
     if let Some(record) = existing {
-        // Convert Decimal to f64
-        let price = record.price.to_string().parse::<f64>().unwrap_or(0.0);
-        tracing::debug!("Found price for {} on {} in database: {}", coin_id, date, price);
-        return Ok(price);
+        tracing::debug!("Found price for {} on {} in database: {}", coin_id, date, record.price);
+        return Ok(Some(SourcedPrice::on(record.price, PriceSource::DbCache, date)));
     }
 
-    // Not in database, fetch from CoinGecko
-    tracing::info!("Fetching price for {} on {} from CoinGecko (on-the-fly)", coin_id, date);
-
-    // Calculate days from target date to now
-    let today = Utc::now().date_naive();
-    let days_ago = (today - date).num_days() as u32;
-
-    if days_ago == 0 {
-        // For today, we still need to fetch (use days=1 to get latest)
-        let prices = coingecko
-            .get_token_market_chart(coin_id, "usd", 1)
-            .await?;
-
-        if prices.is_empty() {
-            return Err(format!("No price data returned from CoinGecko for {}", coin_id).into());
-        }
-
-        // Use the latest price
-        let price = prices.last().unwrap().1;
-
-        // Convert f64 to Decimal for storage
-        let price_decimal = Decimal::from_f64_retain(price)
-            .ok_or("Failed to convert price to Decimal")?;
-
-        // Store in database
-        let new_record = coins_historical_prices::ActiveModel {
-            coin_id: Set(coin_id.to_string()),
-            symbol: Set(String::new()),
-            date: Set(date),
-            price: Set(price_decimal),
-            market_cap: Set(None),
-            volume: Set(None),
-            ..Default::default()
-        };
-
-        match new_record.insert(db).await {
-            Ok(_) => tracing::info!("Stored price for {} on {}: {}", coin_id, date, price),
-            Err(e) => tracing::warn!("Failed to store price for {}: {}", coin_id, e),
-        }
-
-        return Ok(price);
-    }
-
-    // Fetch from CoinGecko
-    let prices = coingecko
-        .get_token_market_chart(coin_id, "usd", days_ago + 1)
-        .await?;
-
-    if prices.is_empty() {
-        return Err(format!("No price data returned from CoinGecko for {}", coin_id).into());
+    if let Some(data) = live
+        && let Some(price) = price_utils::decimal_from_f64(data.price)
+    {
+        tracing::debug!("Using {} price for {} on {}: {}", data.exchange, coin_id, date, price);
+        return Ok(Some(SourcedPrice {
+            price: Some(price),
+            source: Some(PriceSource::ExchangeLive),
+            timestamp: Some(data.fetched_at),
+            fallback: None,
+        }));
     }
 
-    // Find the price closest to our target date
-    let target_timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() * 1000;
-
-    let mut closest_price = None;
-    let mut min_diff = i64::MAX;
-
-    for (timestamp_ms, price) in &prices {
-        let diff = (timestamp_ms - target_timestamp).abs();
-        if diff < min_diff {
-            min_diff = diff;
-            closest_price = Some(*price);
+    // Not in database, fetch from CoinGecko
+    let fetched = match coingecko {
+        Some(coingecko) => {
+            tracing::info!("Fetching price for {} on {} from CoinGecko (on-the-fly)", coin_id, date);
+            match coingecko.get_price_on_date(coin_id, date).await {
+                Ok(Some(price)) => Some(price),
+                Ok(None) => {
+                    tracing::warn!("No price data returned from CoinGecko for {} on {}", coin_id, date);
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to fetch price for {} on {} from CoinGecko: {}", coin_id, date, e);
+                    None
+                }
+            }
         }
-    }
-
-    let price = closest_price.ok_or("No suitable price found in CoinGecko data")?;
+        None => None,
+    };
+    let Some(price) = fetched else {
+        let carried = price_fallback::carried_forward_price(db, coin_id, date).await?;
+        return Ok(carried.map(|(price, fallback)| {
+            let mut sourced = SourcedPrice::on(price, PriceSource::CarriedForward, date);
+            if let PriceFallback::CarriedForward { from } = fallback {
+                tracing::info!("Carrying forward price for {} from {} to {}", coin_id, from, date);
+                sourced.timestamp = Some(pricing_time::cutoff_timestamp(from));
+            }
+            sourced.fallback = Some(fallback);
+            sourced
+        }));
+    };
 
-    // Convert f64 to Decimal for storage
-    let price_decimal = Decimal::from_f64_retain(price)
+    let price_decimal = price_utils::decimal_from_f64(price)
         .ok_or("Failed to convert price to Decimal")?;
 
     // Store in database for future use
@@ -407,17 +733,38 @@ async fn get_or_fetch_price(
         }
     }
 
-    Ok(price)
+    Ok(Some(SourcedPrice::on(price_decimal, PriceSource::CoingeckoLive, date)))
 }
 
-
+/// Filter and sort fields of GET /indexes
+const INDEX_LIST_FIELDS: &[Field<index_metadata::Column>] = &[
+    Field::integer("id", index_metadata::Column::IndexId),
+    Field::text("symbol", index_metadata::Column::Symbol),
+    Field::text("name", index_metadata::Column::Name),
+    Field::text("category", index_metadata::Column::Category),
+    Field::text("asset_class", index_metadata::Column::AssetClass),
+    Field::sort_only("initial_date", index_metadata::Column::InitialDate),
+];
+
+/// GET /indexes?label=&filter=&sort=
+///
+/// `label` keeps only indexes with matching labels (e.g. `theme:ai`; see
+/// services::labels). `filter` and `sort` take id, symbol, name, category
+/// and asset_class, and sort also initial_date (see services::list_query).
 pub async fn get_index_list(
     State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LabelFilterQuery>,
+    Query(list): Query<ListQuery>,
 ) -> Result<Json<IndexListResponse>, (StatusCode, Json<ErrorResponse>)> {
     const INDEX_DECIMALS: u32 = 30;
-    const NETWORK: &str = "base";
+    let label_filters = labels::parse_filters(query.label.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })))?;
+    let select = list_query::apply_query(IndexMetadata::find(), INDEX_LIST_FIELDS, &list)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })))?;
+
     // Fetch all indexes from database
-    let indexes = IndexMetadata::find().all(&state.db).await.map_err(|e| {
+    let mut indexes = select.all(&state.db).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -426,6 +773,42 @@ pub async fn get_index_list(
         )
     })?;
 
+    // Keep only indexes with the requested labels
+    if !label_filters.is_empty() {
+        let labelled = labels::matching_entity_ids(&state.db, entity_types::INDEX, &label_filters)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: format!("Database error while fetching labels: {}", e),
+                    }),
+                )
+            })?;
+        indexes.retain(|index| labelled.contains(&index.index_id.to_string()));
+    }
+
+    let mut deployments = index_deployments::for_indexes(&state.db, &indexes).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error while fetching index deployments: {}", e),
+            }),
+        )
+    })?;
+
+    let index_ids: Vec<i32> = indexes.iter().map(|index| index.index_id).collect();
+    let mut translations = index_translations::for_indexes(&state.db, &index_ids, &accept_language(&headers))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error while fetching index translations: {}", e),
+                }),
+            )
+        })?;
+
     let mut index_list = Vec::new();
 
     for index in indexes {
@@ -433,12 +816,11 @@ pub async fn get_index_list(
         // Get collateral from last rebalance
         let collateral = get_collateral_from_last_rebalance(&state.db, index.index_id).await?;
 
-        // Calculate total minted quantity from blockchain events
-        let index_address = index.address.to_lowercase();
+        // Calculate total minted quantity from blockchain events on all networks
+        let index_deployments = deployments.remove(&index.index_id).unwrap_or_default();
         let total_minted_quantity = calculate_total_minted_quantity(
             &state,
-            &index_address,
-            NETWORK,
+            &index_deployments,
             INDEX_DECIMALS,
         )
         .await?;
@@ -459,15 +841,12 @@ pub async fn get_index_list(
                 )
             })?;
 
-        let latest_price = latest_price_row
-            .as_ref()
-            .and_then(|row| row.price.to_string().parse::<f64>().ok());
+        let latest_price = latest_price_row.as_ref().map(|row| round_for_response(row.price));
 
         // USD value of supply = total minted qty * latest index price
-        let total_supply_usd = if let Some(price) = latest_price {
-            total_minted_quantity * price
-        } else {
-            0.0
+        let total_supply_usd = match latest_price {
+            Some(price) => total_minted_quantity * price_to_f64(price, index.index_id)?,
+            None => 0.0,
         };
 
         // Calculate performance metrics
@@ -481,12 +860,14 @@ pub async fn get_index_list(
         let ytd_return = (ytd_return * 100.0).floor() / 100.0;
 
         // Get inception date
-        let inception_date = get_inception_date_for_index(&state, index.index_id).await?;
+        let inception_date = get_inception_date_for_index(&state, &index).await?;
+
+        let translation = translations.remove(&index.index_id);
 
         // Map database model to API response model
         index_list.push(IndexListEntry {
             index_id: index.index_id,
-            name: index.name,
+            name: translation.as_ref().map_or(index.name, |t| t.name.clone()),
             address: index.address,
             ticker: index.symbol,
             curator: DEFAULT_CURATOR.clone(),
@@ -511,6 +892,8 @@ pub async fn get_index_list(
                 ten_year_return,
             }),
             index_price: latest_price,
+            description: translation.as_ref().and_then(|t| t.description.clone()),
+            locale: translation.map(|t| t.locale),
         });
     }
 
@@ -548,7 +931,7 @@ async fn get_collateral_from_last_rebalance(
     };
 
     // Parse coins from rebalance JSON
-    let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(rebalance.coins)
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins)
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -602,14 +985,16 @@ async fn get_collateral_from_last_rebalance(
 
 async fn calculate_total_minted_quantity(
     state: &AppState,
-    index_address: &str,
-    network: &str,
+    deployments: &[IndexDeployment],
     index_decimal: u32
 ) -> Result<f64, (StatusCode, Json<ErrorResponse>)> {
+    if deployments.is_empty() {
+        return Ok(0.0);
+    }
+
     // Query all mint events for this index
     let mint_events = BlockchainEvents::find()
-        .filter(blockchain_events::Column::ContractAddress.eq(index_address))
-        .filter(blockchain_events::Column::Network.eq(network))
+        .filter(index_deployments::events_condition(deployments))
         .filter(blockchain_events::Column::EventType.eq("mint"))
         .all(&state.db)
         .await
@@ -623,35 +1008,21 @@ async fn calculate_total_minted_quantity(
         })?;
 
     // Sum up quantities in base units (with INDEX_DECIMALS precision)
-    let mut total_qty_base = Decimal::ZERO;
+    let mut total_qty_base = U256::ZERO;
 
     for event in mint_events {
-        if let Some(quantity) = event.quantity {
-            // Convert quantity to base units (multiply by 10^INDEX_DECIMALS)
-            let qty_decimal = to_bigint_units(quantity, index_decimal);
-            total_qty_base += qty_decimal;
-        }
+        total_qty_base += event_amounts::quantity_base_units(&event, index_decimal).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Invalid mint quantity in event {}: {}", event.tx_hash, e),
+                }),
+            )
+        })?;
     }
 
     // Convert back to decimal representation (divide by 10^INDEX_DECIMALS)
-    let total_minted_quantity = from_bigint_units(total_qty_base, index_decimal);
-
-    Ok(total_minted_quantity)
-}
-
-// Convert decimal to base units (like toBigIntUnits in TypeScript)
-fn to_bigint_units(value: Decimal, decimals: u32) -> Decimal {
-    let multiplier = Decimal::from(10_u64.pow(decimals));
-    value * multiplier
-}
-
-// Convert base units to decimal (like ethers.formatUnits)
-fn from_bigint_units(value: Decimal, decimals: u32) -> f64 {
-    let divisor = Decimal::from(10_u64.pow(decimals));
-    let result = value / divisor;
-    
-    // Convert Decimal to f64
-    result.to_string().parse::<f64>().unwrap_or(0.0)
+    Ok(units::to_f64(total_qty_base, index_decimal))
 }
 
 // Helper function to calculate YTD return
@@ -659,22 +1030,16 @@ async fn calculate_ytd_return(
     state: &AppState,
     index_id: i32,
 ) -> Result<f64, (StatusCode, Json<ErrorResponse>)> {
-    let now = Utc::now();
-    
-    // Previous day at midnight UTC
-    let previous_day = (now - Duration::days(1))
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
-    
-    // January 1st of current year at midnight UTC
-    let jan1 = NaiveDate::from_ymd_opt(now.year(), 1, 1)
-        .unwrap()
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
+    let today = pricing_time::pricing_date(Utc::now());
+
+    // Previous day's close
+    let previous_day = today - Duration::days(1);
+
+    // January 1st of current year
+    let jan1 = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap();
 
-    let latest_price = get_price_for_date(state, index_id, previous_day.date()).await?;
-    let jan1_price = get_price_for_date(state, index_id, jan1.date()).await?;
+    let latest_price = get_price_for_date(state, index_id, previous_day).await?;
+    let jan1_price = get_price_for_date(state, index_id, jan1).await?;
 
     if jan1_price.is_none() || jan1_price.unwrap() == 0.0 {
         return Ok(0.0);
@@ -692,21 +1057,13 @@ async fn calculate_period_return(
     index_id: i32,
     days: i64,
 ) -> Result<f64, (StatusCode, Json<ErrorResponse>)> {
-    let now = Utc::now();
-    
-    // End date: 2 days ago at midnight UTC
-    let end_date = (now - Duration::days(2))
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .date();
-    
-    // Start date: days ago at midnight UTC
-    let start_date = (now - Duration::days(days))
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .date();
+    let today = pricing_time::pricing_date(Utc::now());
+
+    // End date: close of 2 days ago
+    let end_date = today - Duration::days(2);
+
+    // Start date: close of `days` ago
+    let start_date = today - Duration::days(days);
 
     let end_price = get_price_for_date(state, index_id, end_date).await?;
     let start_price = get_price_for_date(state, index_id, start_date).await?;
@@ -743,19 +1100,27 @@ async fn get_price_for_date(
             )
         })?;
 
-    Ok(price_row.and_then(|row| row.price.to_string().parse::<f64>().ok()))
+    price_row.map(|row| price_to_f64(row.price, index_id)).transpose()
+}
+
+/// An index price as f64 for return and supply math
+fn price_to_f64(price: Decimal, index_id: i32) -> Result<f64, (StatusCode, Json<ErrorResponse>)> {
+    price.to_f64().ok_or_else(|| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to convert price {} of index {}", price, index_id),
+            }),
+        )
+    })
 }
 
 // Helper function to get inception date for an index
 async fn get_inception_date_for_index(
     state: &AppState,
-    index_id: i32,
+    index: &index_metadata::Model,
 ) -> Result<Option<String>, (StatusCode, Json<ErrorResponse>)> {
-    let earliest_price = DailyPrices::find()
-        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
-        .order_by(daily_prices::Column::Date, Order::Asc)
-        .limit(1)
-        .one(&state.db)
+    let inception_date = inception::inception_date(&state.db, index)
         .await
         .map_err(|e| {
             (
@@ -766,7 +1131,7 @@ async fn get_inception_date_for_index(
             )
         })?;
 
-    Ok(earliest_price.map(|row| row.date.to_string()))
+    Ok(inception_date.map(|date| date.to_string()))
 }
 
 // Add new create_index handler
@@ -888,6 +1253,75 @@ pub async fn create_index(
         }
     }
 
+    // Validate composition constraints; capped categories must exist like blacklisted ones
+    let constraints = payload.constraints.clone().filter(|c| !c.is_empty());
+    if let Some(ref constraints) = constraints {
+        if let Err(err) = constraints.validate() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse { error: err }),
+            ));
+        }
+
+        for category_id in constraints.max_category_weight.keys() {
+            let category_exists = CoingeckoCategories::find()
+                .filter(coingecko_categories::Column::CategoryId.eq(category_id))
+                .one(&state.db)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ErrorResponse {
+                            error: format!("Database error: {}", e),
+                        }),
+                    )
+                })?;
+
+            if category_exists.is_none() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!(
+                            "Invalid constrained category: '{}'. Use /coingecko-categories to see valid categories",
+                            category_id
+                        ),
+                    }),
+                ));
+            }
+        }
+    }
+
+    // Validate the cash buffer; a symbol without a percentage holds no cash
+    let cash_buffer_pct = payload.cash_buffer_pct.filter(|pct| !pct.is_zero());
+    if let Some(pct) = cash_buffer_pct
+        && let Err(err) = cash_buffer::validate_pct(pct)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: err }),
+        ));
+    }
+    let cash_buffer_symbol = cash_buffer_pct.map(|_| {
+        payload
+            .cash_buffer_symbol
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(cash_buffer::DEFAULT_SYMBOL)
+            .to_uppercase()
+    });
+
+    // 1x is an ordinary index
+    let leverage_factor = payload.leverage_factor.filter(|factor| *factor != Decimal::ONE);
+    if let Some(factor) = leverage_factor
+        && let Err(err) = leverage::validate_factor(factor)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: err }),
+        ));
+    }
+
     // Look up token IDs from symbols
     // Serialize exchanges_allowed to JSON
     let exchanges_json = serde_json::to_value(&payload.exchanges_allowed).map_err(|e| {
@@ -917,6 +1351,18 @@ pub async fn create_index(
         None
     };
 
+    let constraints_json = match constraints {
+        Some(ref constraints) => Some(serde_json::to_value(constraints).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to serialize constraints: {}", e),
+                }),
+            )
+        })?),
+        None => None,
+    };
+
     // Insert new index
     let new_index = index_metadata::ActiveModel {
         index_id: Set(payload.index_id),
@@ -936,6 +1382,12 @@ pub async fn create_index(
         weight_threshold: Set(payload.weight_threshold),
         blacklisted_categories: Set(blacklisted_categories_json),
         top_x: Set(payload.top_x.map(|t| t as i32)),
+        constraints: Set(constraints_json),
+        cash_buffer_pct: Set(cash_buffer_pct),
+        cash_buffer_symbol: Set(cash_buffer_symbol),
+        accrue_yield: Set(payload.accrue_yield),
+        leverage_factor: Set(leverage_factor),
+        use_observed_spread: Set(payload.use_observed_spread),
         ..Default::default()
     };
 
@@ -950,91 +1402,28 @@ pub async fn create_index(
 
     let index_id = result.index_id;
 
-    // Generate correlation ID for tracking this backfill operation
-    // Using timestamp + index_id for simple unique identification without uuid dependency
-    let backfill_id = format!("bf-{}-{}", index_id, Utc::now().timestamp_millis());
-
-    // Spawn background task for backfilling with structured logging
-    let db_clone = state.db.clone();
-    let coingecko_clone = state.coingecko.clone();
-    let backfill_id_clone = backfill_id.clone();
-    tokio::spawn(async move {
-        tracing::info!(
+    // Queue the backfill; the background tasks worker runs it and clients
+    // can follow it via /indexes/{index_id}/backfill-status
+    match background_tasks::enqueue(
+        &state.db,
+        background_tasks::task_types::INDEX_BACKFILL,
+        Some(index_id),
+        None,
+    )
+    .await
+    {
+        Ok(task) => tracing::info!(
             index_id = index_id,
-            backfill_id = %backfill_id_clone,
-            status = "started",
-            "Backfill job started for index"
-        );
-
-        // Step 1: Backfill rebalances
-        let rebalancing_service = RebalancingService::new(db_clone.clone(), coingecko_clone.clone(), None);
-
-        match rebalancing_service.backfill_historical_rebalances(index_id).await {
-            Ok(_) => {
-                tracing::info!(
-                    index_id = index_id,
-                    backfill_id = %backfill_id_clone,
-                    stage = "rebalances",
-                    status = "completed",
-                    "Rebalances backfill complete"
-                );
-
-                // Step 2: Backfill daily prices (only after rebalances are done)
-                tracing::info!(
-                    index_id = index_id,
-                    backfill_id = %backfill_id_clone,
-                    stage = "daily_prices",
-                    status = "started",
-                    "Starting daily prices backfill"
-                );
-
-                match crate::services::daily_prices::backfill_daily_prices(
-                    &db_clone,
-                    &coingecko_clone,
-                    index_id
-                ).await {
-                    Ok(_) => tracing::info!(
-                        index_id = index_id,
-                        backfill_id = %backfill_id_clone,
-                        stage = "daily_prices",
-                        status = "completed",
-                        "Daily prices backfill complete"
-                    ),
-                    Err(e) => tracing::error!(
-                        index_id = index_id,
-                        backfill_id = %backfill_id_clone,
-                        stage = "daily_prices",
-                        status = "failed",
-                        error = %e,
-                        "Failed to backfill daily prices"
-                    ),
-                }
-
-                tracing::info!(
-                    index_id = index_id,
-                    backfill_id = %backfill_id_clone,
-                    status = "completed",
-                    "Backfill job completed successfully"
-                );
-            }
-            Err(e) => {
-                tracing::error!(
-                    index_id = index_id,
-                    backfill_id = %backfill_id_clone,
-                    stage = "rebalances",
-                    status = "failed",
-                    error = %e,
-                    "Backfill job failed at rebalances stage"
-                );
-            }
-        }
-    });
-
-    tracing::info!(
-        index_id = index_id,
-        backfill_id = %backfill_id,
-        "Index created, backfill job spawned. Monitor logs with backfill_id for progress."
-    );
+            task_id = task.id,
+            "Index created, backfill task queued"
+        ),
+        // The index row exists already; rebalance_sync picks up incomplete backfills
+        Err(e) => tracing::error!(
+            index_id = index_id,
+            error = %e,
+            "Index created but failed to queue backfill task"
+        ),
+    }
 
     // Parse blacklisted_categories from result for response
     let blacklisted_categories_response = result.blacklisted_categories
@@ -1062,6 +1451,12 @@ pub async fn create_index(
             weight_strategy: result.weight_strategy,
             weight_threshold: result.weight_threshold.map(|d| d.to_string()),
             blacklisted_categories: blacklisted_categories_response,
+            constraints,
+            cash_buffer_pct: result.cash_buffer_pct.map(|d| d.to_string()),
+            cash_buffer_symbol: result.cash_buffer_symbol,
+            accrue_yield: result.accrue_yield,
+            leverage_factor: result.leverage_factor.map(|d| d.to_string()),
+            use_observed_spread: result.use_observed_spread,
         }),
     ))
 }
@@ -1075,7 +1470,7 @@ pub async fn create_index(
 /// - Having full control over historical composition without automatic calculations
 ///
 /// # Key Differences from Regular /create-index
-/// - Regular endpoint: Spawns `tokio::spawn()` background task for backfill
+/// - Regular endpoint: Queues a background task for backfill
 /// - Manual endpoint: NO background tasks → user must manually add rebalances
 ///
 /// # Next Steps Workflow
@@ -1187,6 +1582,7 @@ pub async fn create_manual_index(
 
 pub async fn get_index_config(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(index_id): Path<i32>,
 ) -> Result<Json<IndexConfigResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Get index metadata from database
@@ -1276,11 +1672,34 @@ pub async fn get_index_config(
             )
         })?;
 
+    let methodology = methodology_documents::effective_on(&state.db, index_id, Utc::now().date_naive())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    let translation = index_translations::for_indexes(&state.db, &[index_id], &accept_language(&headers))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?
+        .remove(&index_id);
+
     // Build response
     let response = IndexConfigResponse {
         index_id: index.index_id,
         symbol: index.symbol,
-        name: index.name,
+        name: translation.as_ref().map_or(index.name, |t| t.name.clone()),
         address: index.address,
         initial_date,
         initial_price: initial_price.to_string(),
@@ -1288,11 +1707,53 @@ pub async fn get_index_config(
         exchange_trading_fees: exchange_trading_fees.to_string(),
         exchange_avg_spread: exchange_avg_spread.to_string(),
         rebalance_period,
+        pricing_cutoff: pricing_time::cutoff_label(),
+        methodology: methodology.as_ref().map(MethodologyVersionRef::from),
+        description: translation.as_ref().and_then(|t| t.description.clone()),
+        locale: translation.map(|t| t.locale),
     };
 
     Ok(Json(response))
 }
 
+/// GET /indexes/{index_id}/translations
+pub async fn get_index_translations(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+) -> Result<Json<Vec<IndexTranslationResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let db_error = |e: sea_orm::DbErr| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    };
+
+    IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Index {} not found", index_id),
+                }),
+            )
+        })?;
+
+    let translations = index_translations::list(&state.db, index_id).await.map_err(db_error)?;
+    Ok(Json(translations.into_iter().map(IndexTranslationResponse::from).collect()))
+}
+
+/// Locales of the request's Accept-Language, most preferred first
+fn accept_language(headers: &HeaderMap) -> Vec<String> {
+    index_translations::parse_accept_language(
+        headers.get(header::ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok()),
+    )
+}
+
 pub async fn remove_index(
     State(state): State<AppState>,
     Json(payload): Json<RemoveIndexRequest>,
@@ -1442,7 +1903,7 @@ pub async fn get_current_index_weight(
 
     // Parse constituents from last rebalance
     let coins: Vec<crate::services::rebalancing::CoinRebalanceInfo> =
-        serde_json::from_value(last_rebalance.coins.clone())
+        rebalance_schema::decode(last_rebalance.coins.clone())
             .map_err(|e| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -1452,51 +1913,54 @@ pub async fn get_current_index_weight(
                 )
             })?;
 
-    // Calculate total weight for percentage calculations
-    let total_weight: f64 = last_rebalance.total_weight
-        .to_string()
-        .parse()
-        .unwrap_or(0.0);
-
-    // Build constituent weights with percentage
+    // Build constituent weights with percentage of the rebalance's total weight
+    let total_weight = last_rebalance.total_weight;
     let mut constituents = Vec::new();
 
     for coin in coins {
-        let weight: f64 = coin.weight.parse().unwrap_or(0.0);
-        let quantity: f64 = coin.quantity.parse().unwrap_or(0.0);
-        let price = coin.price;
-        let value = weight * quantity * price;
+        let parse = |field: &str, value: &str| {
+            value.parse::<Decimal>().map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: format!(
+                            "Invalid {} '{}' for {} in rebalance {}: {}",
+                            field, value, coin.coin_id, last_rebalance.id, e
+                        ),
+                    }),
+                )
+            })
+        };
+        let weight = parse("weight", &coin.weight)?;
+        let quantity = parse("quantity", &coin.quantity)?;
+        let value = weight * quantity * coin.price;
 
         // Calculate weight percentage (weight / total_weight * 100)
-        let weight_percentage = if total_weight > 0.0 {
-            (weight / total_weight) * 100.0
+        let weight_percentage = if total_weight > Decimal::ZERO {
+            weight / total_weight * Decimal::ONE_HUNDRED
         } else {
-            0.0
+            Decimal::ZERO
         };
 
         constituents.push(ConstituentWeight {
             coin_id: coin.coin_id,
             symbol: coin.symbol,
             weight: coin.weight,
-            weight_percentage,
+            weight_percentage: round_for_response(weight_percentage),
             quantity: coin.quantity,
-            price,
-            value,
+            price: coin.price,
+            value: round_for_response(value),
             exchange: coin.exchange,
             trading_pair: coin.trading_pair,
         });
     }
 
     // Sort by weight percentage descending (largest holdings first)
-    constituents.sort_by(|a, b| {
-        b.weight_percentage
-            .partial_cmp(&a.weight_percentage)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    constituents.sort_by_key(|c| std::cmp::Reverse(c.weight_percentage));
 
     // Format rebalance date
-    let rebalance_date = chrono::DateTime::from_timestamp(last_rebalance.timestamp, 0)
-        .map(|dt| dt.format("%Y-%m-%d").to_string())
+    let rebalance_date = pricing_time::rebalance_date(last_rebalance.timestamp)
+        .map(|date| date.format("%Y-%m-%d").to_string())
         .unwrap_or_else(|| "Unknown".to_string());
 
     Ok(Json(CurrentIndexWeightResponse {
@@ -1579,18 +2043,14 @@ pub async fn add_manual_rebalance(
         }
     }
 
-    // Convert date to timestamp (end of day: 23:59:59)
-    let timestamp = payload
-        .date
-        .and_hms_opt(23, 59, 59)
-        .unwrap()
-        .and_utc()
-        .timestamp();
+    // Stamped at the date's cutoff, like computed rebalances
+    let timestamp = pricing_time::rebalance_timestamp(payload.date);
 
-    // Check for duplicate rebalance on same date (AC-3)
+    // Check for duplicate rebalance on same date (AC-3), including ones
+    // stamped later in the day before the pricing-time policy
     let existing = Rebalances::find()
         .filter(rebalances::Column::IndexId.eq(index_id))
-        .filter(rebalances::Column::Timestamp.eq(timestamp))
+        .filter(rebalances::Column::Timestamp.between(timestamp, pricing_time::day_end_timestamp(payload.date)))
         .one(&state.db)
         .await
         .map_err(|e| {
@@ -1642,7 +2102,30 @@ pub async fn add_manual_rebalance(
     }
 
     // Build constituents JSON from coins array (AC-6)
-    let constituents_json = serde_json::to_value(&payload.coins).map_err(|e| {
+    let constituents: Vec<CoinRebalanceInfo> = payload
+        .coins
+        .iter()
+        .map(|coin| {
+            let price = price_utils::decimal_from_f64(coin.price).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid price {} for {}", coin.price, coin.coin_id),
+                    }),
+                )
+            });
+            price.map(|price| CoinRebalanceInfo {
+                coin_id: coin.coin_id.clone(),
+                symbol: coin.symbol.clone(),
+                quantity: coin.quantity.clone(),
+                weight: coin.weight.clone(),
+                price,
+                exchange: coin.exchange.clone(),
+                trading_pair: coin.trading_pair.clone(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let constituents_json = rebalance_schema::encode(&constituents).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -1699,3 +2182,50 @@ pub async fn add_manual_rebalance(
         }),
     ))
 }
+
+/// Handler for writing or checking an index's inception
+/// POST /indexes/:index_id/seed-inception
+///
+/// With `coins`, writes the inception rebalance and daily price from the
+/// request (201); otherwise checks the existing ones (200, see `valid` and
+/// `issues`). Requires the admin API key.
+pub async fn seed_inception(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(index_id): Path<i32>,
+    Json(payload): Json<SeedInceptionRequest>,
+) -> Result<(StatusCode, Json<InceptionReport>), (StatusCode, Json<ErrorResponse>)> {
+    require_admin_key(&headers)?;
+
+    let to_response = |e: InceptionError| {
+        let status = match e {
+            InceptionError::IndexNotFound(_) => StatusCode::NOT_FOUND,
+            InceptionError::Invalid(_) => StatusCode::BAD_REQUEST,
+            InceptionError::Conflict(_) => StatusCode::CONFLICT,
+            InceptionError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(ErrorResponse { error: e.to_string() }))
+    };
+
+    let Some(coins) = payload.coins else {
+        let report = inception::check(&state.db, index_id).await.map_err(to_response)?;
+        return Ok((StatusCode::OK, Json(report)));
+    };
+
+    let index = IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(|e| to_response(e.into()))?
+        .ok_or_else(|| to_response(InceptionError::IndexNotFound(index_id)))?;
+    let date = payload.date.or(index.initial_date).ok_or_else(|| {
+        to_response(InceptionError::Invalid("date is required: the index has no initial date".to_string()))
+    })?;
+    let price = payload.price.or(index.initial_price).ok_or_else(|| {
+        to_response(InceptionError::Invalid("price is required: the index has no initial price".to_string()))
+    })?;
+
+    let report = inception::seed(&state.db, index_id, InceptionSeed { date, price, coins }, Utc::now().naive_utc())
+        .await
+        .map_err(to_response)?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
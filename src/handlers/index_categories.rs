@@ -0,0 +1,33 @@
+//! Index directory handler
+//!
+//! GET /index-categories groups indexes by category and asset class for the
+//! product directory page (see services::index_categories).
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{Duration, Utc};
+
+use crate::models::index_category::IndexCategoriesResponse;
+use crate::models::token::ErrorResponse;
+use crate::services::index_categories;
+use crate::services::pricing_time;
+use crate::AppState;
+
+/// GET /index-categories
+///
+/// Index count, total TVL and average YTD return (to the previous day's
+/// close, as in GET /indexes) per category and asset class.
+pub async fn get_index_categories(
+    State(state): State<AppState>,
+) -> Result<Json<IndexCategoriesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let as_of = pricing_time::pricing_date(Utc::now()) - Duration::days(1);
+    let response = index_categories::index_categories(&state.db, as_of).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    Ok(Json(response))
+}
@@ -1,7 +1,8 @@
 //! ITP (Index Token Product) creation handler
 //!
 //! POST /api/itp/create endpoint for creating live ITPs via BridgeProxy on Arbitrum.
-//! Admin-only endpoint protected by API key authentication.
+//! Admin-only endpoint protected by API key authentication. Requested assets
+//! are checked by services::itp_validation before anything is sent on-chain.
 
 use axum::{
     extract::State,
@@ -17,9 +18,15 @@ use std::time::Instant;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
+use crate::config::CompositionLimits;
 use crate::entities::itps;
-use crate::models::itp::{CreateItpRequest, CreateItpResponse, CreateItpSyncResponse, ItpErrorResponse};
+use crate::models::itp::{
+    CreateItpRequest, CreateItpResponse, CreateItpSimulationResponse, CreateItpSyncResponse, ItpErrorResponse,
+};
+use crate::services::feature_flags::flags;
 use crate::services::itp_creation::{ItpCreationError, ItpCreationService};
+use crate::services::itp_validation::{self, ItpValidationConfig};
+use crate::services::signers;
 use crate::AppState;
 
 /// Default estimated completion time in seconds
@@ -139,6 +146,19 @@ lazy_static::lazy_static! {
 ///   "status": "completed"
 /// }
 /// ```
+///
+/// # Response (dry run, dry_run=true)
+///
+/// ```json
+/// {
+///   "block_number": 123456,
+///   "gas_estimate": 412000,
+///   "status": "simulated"
+/// }
+/// ```
+///
+/// Every request is simulated against the latest block first; one that would
+/// revert fails with 422 and the decoded revert reason, without sending.
 pub async fn create_itp(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -156,6 +176,18 @@ pub async fn create_itp(
     // Check admin authentication (returns API key for rate limiting)
     let api_key = check_admin_auth(&headers)?;
 
+    if !state.feature_flags.is_enabled(flags::ITP_CREATION).await {
+        warn!(correlation_id = %correlation_id, "ITP creation is disabled");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ItpErrorResponse {
+                error: "ITP creation is temporarily disabled, try again later".to_string(),
+                code: Some("ITP_CREATION_DISABLED".to_string()),
+                violations: Vec::new(),
+            }),
+        ));
+    }
+
     // Check per-API-key rate limit (AC #5.5)
     {
         let mut limiter = RATE_LIMITER.lock().await;
@@ -166,6 +198,7 @@ pub async fn create_itp(
                 Json(ItpErrorResponse {
                     error: "Rate limit exceeded. Max 10 creations per minute per API key.".to_string(),
                     code: Some("RATE_LIMIT_EXCEEDED".to_string()),
+                    violations: Vec::new(),
                 }),
             ));
         }
@@ -193,12 +226,17 @@ pub async fn create_itp(
         weights: payload.weights.clone(),
         asset_composition: payload.asset_composition.clone(),
         sync: payload.sync,
+        dry_run: payload.dry_run,
         admin_address: payload.admin_address.clone(),
+        limits: payload.limits.clone(),
     };
 
     // Validate sanitized request
     validate_create_itp_request(&sanitized_payload)?;
 
+    // Check assets against the registry, exchanges and ITP validation config
+    validate_itp_assets(&state, &sanitized_payload, &correlation_id).await?;
+
     // Get configuration from environment
     let rpc_url = std::env::var("ARB_RPC_URL").map_err(|_| {
         error!(correlation_id = %correlation_id, "ARB_RPC_URL not configured");
@@ -207,22 +245,22 @@ pub async fn create_itp(
             Json(ItpErrorResponse {
                 error: "Server configuration error".to_string(),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         )
     })?;
 
-    let private_key = std::env::var("ARBITRUM_PRIVATE_KEY")
-        .or_else(|_| std::env::var("DEPLOY_PRIVATE_KEY"))
-        .map_err(|_| {
-            error!(correlation_id = %correlation_id, "ARBITRUM_PRIVATE_KEY not configured");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ItpErrorResponse {
-                    error: "Server configuration error".to_string(),
-                    code: Some("CONFIG_ERROR".to_string()),
-                }),
-            )
-        })?;
+    let wallet = signers::wallet_from_env().await.map_err(|e| {
+        error!(correlation_id = %correlation_id, error = %e, "Transaction signer not configured");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ItpErrorResponse {
+                error: "Server configuration error".to_string(),
+                code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
+            }),
+        )
+    })?;
 
     let bridge_proxy_address = std::env::var("BRIDGE_PROXY_ADDRESS").map_err(|_| {
         error!(correlation_id = %correlation_id, "BRIDGE_PROXY_ADDRESS not configured");
@@ -231,12 +269,13 @@ pub async fn create_itp(
             Json(ItpErrorResponse {
                 error: "Server configuration error".to_string(),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         )
     })?;
 
     // Initialize service
-    let service = ItpCreationService::new(&rpc_url, &private_key, &bridge_proxy_address)
+    let service = ItpCreationService::with_wallet(&rpc_url, wallet, &bridge_proxy_address)
         .await
         .map_err(|e| {
             error!(
@@ -245,12 +284,47 @@ pub async fn create_itp(
                 "Failed to initialize ITP creation service"
             );
             map_creation_error(e)
-        })?;
+        })?
+        .with_spend_recording(state.db.clone());
 
     // Extract asset IDs and weights (default to empty vectors if not provided)
     let asset_ids = payload.asset_ids.clone().unwrap_or_default();
     let weights = payload.weights.clone().unwrap_or_default();
 
+    // Dry run: report what sending would do without sending
+    if payload.dry_run {
+        let simulation = service
+            .simulate_create_itp(
+                &sanitized_name,
+                &sanitized_symbol,
+                &sanitized_description,
+                &sanitized_methodology,
+                payload.initial_price,
+                payload.max_order_size,
+                &asset_ids,
+                &weights,
+            )
+            .await
+            .map_err(|e| {
+                warn!(correlation_id = %correlation_id, error = %e, "ITP creation simulation failed");
+                map_creation_error(e.into())
+            })?;
+
+        info!(
+            correlation_id = %correlation_id,
+            block_number = simulation.block_number,
+            gas_estimate = simulation.gas_estimate,
+            "ITP creation simulated (dry run)"
+        );
+
+        let response = CreateItpSimulationResponse {
+            block_number: simulation.block_number,
+            gas_estimate: simulation.gas_estimate,
+            status: "simulated".to_string(),
+        };
+        return Ok(Json(serde_json::to_value(response).unwrap()));
+    }
+
     // Execute creation based on sync mode (using sanitized inputs)
     if payload.sync {
         // Sync mode: wait for completion
@@ -414,22 +488,22 @@ pub async fn get_itp_status(
             Json(ItpErrorResponse {
                 error: "Server configuration error".to_string(),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         )
     })?;
 
-    let private_key = std::env::var("ARBITRUM_PRIVATE_KEY")
-        .or_else(|_| std::env::var("DEPLOY_PRIVATE_KEY"))
-        .map_err(|_| {
-            error!("ARBITRUM_PRIVATE_KEY not configured");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ItpErrorResponse {
-                    error: "Server configuration error".to_string(),
-                    code: Some("CONFIG_ERROR".to_string()),
-                }),
-            )
-        })?;
+    let wallet = signers::wallet_from_env().await.map_err(|e| {
+        error!(error = %e, "Transaction signer not configured");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ItpErrorResponse {
+                error: "Server configuration error".to_string(),
+                code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
+            }),
+        )
+    })?;
 
     let bridge_proxy_address = std::env::var("BRIDGE_PROXY_ADDRESS").map_err(|_| {
         error!("BRIDGE_PROXY_ADDRESS not configured");
@@ -438,12 +512,13 @@ pub async fn get_itp_status(
             Json(ItpErrorResponse {
                 error: "Server configuration error".to_string(),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         )
     })?;
 
     // Initialize service
-    let service = ItpCreationService::new(&rpc_url, &private_key, &bridge_proxy_address)
+    let service = ItpCreationService::with_wallet(&rpc_url, wallet, &bridge_proxy_address)
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to initialize ITP creation service");
@@ -485,6 +560,7 @@ fn check_admin_auth(headers: &HeaderMap) -> Result<String, (StatusCode, Json<Itp
             Json(ItpErrorResponse {
                 error: "Server configuration error".to_string(),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         )
     })?;
@@ -501,6 +577,7 @@ fn check_admin_auth(headers: &HeaderMap) -> Result<String, (StatusCode, Json<Itp
             Json(ItpErrorResponse {
                 error: "Invalid or missing API key".to_string(),
                 code: Some("UNAUTHORIZED".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -519,6 +596,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: "Token name cannot be empty".to_string(),
                 code: Some("INVALID_NAME".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -529,6 +607,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: format!("Token name cannot exceed {} characters", MAX_NAME_LENGTH),
                 code: Some("INVALID_NAME".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -540,6 +619,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: "Token name must be alphanumeric (spaces allowed)".to_string(),
                 code: Some("INVALID_NAME".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -551,6 +631,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: "Token symbol cannot be empty".to_string(),
                 code: Some("INVALID_SYMBOL".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -561,6 +642,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: format!("Token symbol cannot exceed {} characters", MAX_SYMBOL_LENGTH),
                 code: Some("INVALID_SYMBOL".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -572,6 +654,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: "Token symbol must be uppercase alphanumeric".to_string(),
                 code: Some("INVALID_SYMBOL".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -583,6 +666,7 @@ fn validate_create_itp_request(
             Json(ItpErrorResponse {
                 error: "Initial price must be greater than 0".to_string(),
                 code: Some("INVALID_PRICE".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -596,6 +680,7 @@ fn validate_create_itp_request(
                     MAX_INITIAL_PRICE
                 ),
                 code: Some("INVALID_PRICE".to_string()),
+                violations: Vec::new(),
             }),
         ));
     }
@@ -609,6 +694,7 @@ fn validate_create_itp_request(
                     Json(ItpErrorResponse {
                         error: "Weights count must match asset_ids count".to_string(),
                         code: Some("INVALID_WEIGHTS".to_string()),
+                        violations: Vec::new(),
                     }),
                 ));
             }
@@ -621,6 +707,7 @@ fn validate_create_itp_request(
                     Json(ItpErrorResponse {
                         error: format!("Weights must sum to 10000 (100%), got {}", sum),
                         code: Some("INVALID_WEIGHTS".to_string()),
+                        violations: Vec::new(),
                     }),
                 ));
             }
@@ -630,6 +717,7 @@ fn validate_create_itp_request(
                 Json(ItpErrorResponse {
                     error: "Weights required when asset_ids provided".to_string(),
                     code: Some("INVALID_WEIGHTS".to_string()),
+                    violations: Vec::new(),
                 }),
             ));
         }
@@ -638,6 +726,87 @@ fn validate_create_itp_request(
     Ok(())
 }
 
+/// Check the requested assets; all violations are returned at once
+async fn validate_itp_assets(
+    state: &AppState,
+    req: &CreateItpRequest,
+    correlation_id: &str,
+) -> Result<(), (StatusCode, Json<ItpErrorResponse>)> {
+    let (Some(asset_ids), Some(weights)) = (&req.asset_ids, &req.weights) else {
+        return Ok(());
+    };
+
+    let limits = CompositionLimits::from_env().narrowed(req.limits.as_ref());
+    if let Some(reason) = limits.infeasible() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ItpErrorResponse {
+                error: reason,
+                code: Some("INVALID_LIMITS".to_string()),
+                violations: Vec::new(),
+            }),
+        ));
+    }
+    if let Some(max_assets) = limits.max_assets.filter(|max_assets| asset_ids.len() > *max_assets) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ItpErrorResponse {
+                error: format!("At most {} assets allowed, got {}", max_assets, asset_ids.len()),
+                code: Some("TOO_MANY_ASSETS".to_string()),
+                violations: Vec::new(),
+            }),
+        ));
+    }
+
+    // Tradeability can't be checked while live exchange checks are off
+    if !state.feature_flags.is_enabled(flags::LIVE_EXCHANGE_CHECKS).await {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ItpErrorResponse {
+                error: "Live exchange checks are temporarily disabled, try again later".to_string(),
+                code: Some("EXCHANGE_UNAVAILABLE".to_string()),
+                violations: Vec::new(),
+            }),
+        ));
+    }
+
+    let config = ItpValidationConfig::from_env();
+    let violations = itp_validation::validate_assets(
+        &state.asset_registry,
+        &state.exchange_api,
+        &config,
+        &limits,
+        asset_ids,
+        weights,
+    )
+        .await
+        .map_err(|e| {
+            error!(correlation_id = %correlation_id, error = %e, "Failed to check asset tradeability");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ItpErrorResponse {
+                    error: "Could not check asset tradeability, try again later".to_string(),
+                    code: Some("EXCHANGE_UNAVAILABLE".to_string()),
+                    violations: Vec::new(),
+                }),
+            )
+        })?;
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    warn!(correlation_id = %correlation_id, violations = violations.len(), "ITP creation request has invalid assets");
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(ItpErrorResponse {
+            error: format!("{} asset violation(s)", violations.len()),
+            code: Some("INVALID_ASSETS".to_string()),
+            violations,
+        }),
+    ))
+}
+
 /// Map ItpCreationError to HTTP response
 fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorResponse>) {
     match err {
@@ -646,6 +815,7 @@ fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorRespon
             Json(ItpErrorResponse {
                 error: format!("Bridge connection error: {}", msg),
                 code: Some("PROVIDER_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         ),
         ItpCreationError::TransactionError(msg) => {
@@ -661,6 +831,7 @@ fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorRespon
                 Json(ItpErrorResponse {
                     error: error_msg,
                     code: Some(code),
+                    violations: Vec::new(),
                 }),
             )
         }
@@ -669,13 +840,33 @@ fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorRespon
             Json(ItpErrorResponse {
                 error: format!("Gas estimation failed: {}", msg),
                 code: Some("GAS_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         ),
+        ItpCreationError::SimulationReverted(reason) => {
+            // The contract rejected the request; nothing was sent
+            let (error_msg, code) = match parse_revert_reason(&reason) {
+                (_, code) if code == "CONTRACT_REVERT" => {
+                    (format!("Transaction would revert: {}", reason), "SIMULATION_REVERTED".to_string())
+                }
+                parsed => parsed,
+            };
+
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ItpErrorResponse {
+                    error: error_msg,
+                    code: Some(code),
+                    violations: Vec::new(),
+                }),
+            )
+        }
         ItpCreationError::EventParsingError(msg) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ItpErrorResponse {
                 error: format!("Event parsing failed: {}", msg),
                 code: Some("EVENT_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         ),
         ItpCreationError::Timeout(msg) => (
@@ -683,6 +874,7 @@ fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorRespon
             Json(ItpErrorResponse {
                 error: format!("Operation timed out: {}", msg),
                 code: Some("TIMEOUT".to_string()),
+                violations: Vec::new(),
             }),
         ),
         ItpCreationError::InvalidConfig(msg) => (
@@ -690,6 +882,7 @@ fn map_creation_error(err: ItpCreationError) -> (StatusCode, Json<ItpErrorRespon
             Json(ItpErrorResponse {
                 error: format!("Configuration error: {}", msg),
                 code: Some("CONFIG_ERROR".to_string()),
+                violations: Vec::new(),
             }),
         ),
     }
@@ -732,7 +925,9 @@ mod tests {
             weights: None,
             asset_composition: None,
             sync: false,
+            dry_run: false,
             admin_address: None, // Story 2-3 AC#6: Optional issuer address
+            limits: None,
         }
     }
 
@@ -828,6 +1023,17 @@ mod tests {
         assert_eq!(code, "UNAUTHORIZED_WALLET");
     }
 
+    #[test]
+    fn test_map_creation_error_simulation_reverted() {
+        let (status, Json(body)) = map_creation_error(ItpCreationError::SimulationReverted("InvalidTokenSymbol()".to_string()));
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body.code.as_deref(), Some("INVALID_SYMBOL"));
+
+        let (_, Json(body)) = map_creation_error(ItpCreationError::SimulationReverted("revert: paused".to_string()));
+        assert_eq!(body.code.as_deref(), Some("SIMULATION_REVERTED"));
+        assert_eq!(body.error, "Transaction would revert: revert: paused");
+    }
+
     #[test]
     fn test_map_creation_error_timeout() {
         let err = ItpCreationError::Timeout("test".to_string());
@@ -0,0 +1,64 @@
+//! ITP composition drift handler
+//!
+//! GET /api/itp/{address}/drift returns the latest drift check recorded by
+//! the ITP drift monitor job (see services::itp_drift).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::error;
+
+use crate::models::itp::ItpErrorResponse;
+use crate::models::itp_drift::ItpDriftResponse;
+use crate::services::{itp_drift, itp_listing};
+use crate::AppState;
+
+/// GET /api/itp/{address}/drift
+///
+/// `address` is the ITP's Orbit or Arbitrum address.
+///
+/// # Response
+/// - 200: Latest drift check; `rebalance_due` is set when drift reached the threshold
+/// - 404: ITP not found, or not checked yet
+/// - 500: Database error
+pub async fn get_itp_drift(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<ItpDriftResponse>, (StatusCode, Json<ItpErrorResponse>)> {
+    let itp = itp_listing::find_by_address(&state.db, &address)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| not_found("ITP not found", "ITP_NOT_FOUND"))?;
+
+    let drift = itp_drift::latest_check(&state.db, &itp)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| not_found("No drift check recorded for this ITP yet", "DRIFT_NOT_CHECKED"))?;
+
+    Ok(Json(drift))
+}
+
+fn not_found(message: &str, code: &str) -> (StatusCode, Json<ItpErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ItpErrorResponse {
+            error: message.to_string(),
+            code: Some(code.to_string()),
+            violations: Vec::new(),
+        }),
+    )
+}
+
+fn database_error(e: sea_orm::DbErr) -> (StatusCode, Json<ItpErrorResponse>) {
+    error!(error = %e, "Database error fetching ITP drift");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ItpErrorResponse {
+            error: "Database error".to_string(),
+            code: Some("DATABASE_ERROR".to_string()),
+            violations: Vec::new(),
+        }),
+    )
+}
@@ -0,0 +1,90 @@
+//! ITP execution quote handler
+//!
+//! GET /api/itp/{address}/execution-quote?notional= estimates the slippage
+//! and fees of minting `notional` USD of an ITP from live order books (see
+//! services::itp_execution_quote).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::error;
+
+use crate::models::itp::ItpErrorResponse;
+use crate::models::itp_execution_quote::{ExecutionQuoteQuery, ExecutionQuoteResponse};
+use crate::services::feature_flags::flags;
+use crate::services::itp_execution_quote::{self, ExecutionQuoteError};
+use crate::services::itp_listing;
+use crate::AppState;
+
+/// GET /api/itp/{address}/execution-quote?notional=
+///
+/// `address` is the ITP's Orbit or Arbitrum address and `notional` the mint
+/// size in USD.
+///
+/// # Response
+/// - 200: Per-leg and total execution cost; `fully_fillable` is false when a
+///   leg couldn't be quoted or the fetched depth is too thin
+/// - 400: Missing or invalid notional (INVALID_NOTIONAL)
+/// - 404: ITP not found (ITP_NOT_FOUND)
+/// - 422: ITP has no target weights (WEIGHTS_UNAVAILABLE)
+/// - 502: Exchange pairs could not be loaded (EXCHANGE_ERROR)
+/// - 503: Live exchange checks are switched off (FEATURE_DISABLED)
+/// - 500: Database error
+pub async fn get_execution_quote(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<ExecutionQuoteQuery>,
+) -> Result<Json<ExecutionQuoteResponse>, (StatusCode, Json<ItpErrorResponse>)> {
+    let notional = query.notional.ok_or_else(|| {
+        error_response(StatusCode::BAD_REQUEST, "notional is required".to_string(), "INVALID_NOTIONAL")
+    })?;
+
+    let itp = itp_listing::find_by_address(&state.db, &address)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Database error fetching ITP for execution quote");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string(), "DATABASE_ERROR")
+        })?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "ITP not found".to_string(), "ITP_NOT_FOUND"))?;
+
+    if !state.feature_flags.is_enabled(flags::LIVE_EXCHANGE_CHECKS).await {
+        return Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Live exchange checks are temporarily disabled, try again later".to_string(),
+            "FEATURE_DISABLED",
+        ));
+    }
+
+    let quote = itp_execution_quote::quote(&state.db, &state.exchange_api, &itp, notional)
+        .await
+        .map_err(|e| {
+            let (status, code) = match &e {
+                ExecutionQuoteError::InvalidNotional => (StatusCode::BAD_REQUEST, "INVALID_NOTIONAL"),
+                ExecutionQuoteError::MissingWeights => (StatusCode::UNPROCESSABLE_ENTITY, "WEIGHTS_UNAVAILABLE"),
+                ExecutionQuoteError::Exchange(_) => {
+                    error!(error = %e, itp = %itp.orbit_address, "Failed to quote ITP execution");
+                    (StatusCode::BAD_GATEWAY, "EXCHANGE_ERROR")
+                }
+                ExecutionQuoteError::Database(_) => {
+                    error!(error = %e, itp = %itp.orbit_address, "Database error quoting ITP execution");
+                    (StatusCode::INTERNAL_SERVER_ERROR, "DATABASE_ERROR")
+                }
+            };
+            error_response(status, e.to_string(), code)
+        })?;
+
+    Ok(Json(quote))
+}
+
+fn error_response(status: StatusCode, message: String, code: &str) -> (StatusCode, Json<ItpErrorResponse>) {
+    (
+        status,
+        Json(ItpErrorResponse {
+            error: message,
+            code: Some(code.to_string()),
+            violations: Vec::new(),
+        }),
+    )
+}
@@ -126,6 +126,7 @@ mod tests {
             active: Some(true),
             user_holdings: None,
             search: Some("DEFI".to_string()),
+            admin_address: None,
         };
         assert!(query.validate().is_ok());
     }
@@ -138,6 +139,7 @@ mod tests {
             active: None,
             user_holdings: None,
             search: None,
+            admin_address: None,
         };
         assert!(query.validate().is_err());
     }
@@ -150,6 +152,7 @@ mod tests {
             active: None,
             user_holdings: None,
             search: None,
+            admin_address: None,
         };
         assert!(query.validate().is_err());
     }
@@ -162,6 +165,7 @@ mod tests {
             active: None,
             user_holdings: None,
             search: None,
+            admin_address: None,
         };
         assert!(query.validate().is_err());
     }
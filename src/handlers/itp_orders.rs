@@ -0,0 +1,137 @@
+//! ITP order handlers
+//!
+//! Wallets record mint/redeem orders and follow them from pending to filled
+//! to settled as the order indexer matches their on-chain events (see
+//! services::itp_orders).
+//!
+//! - POST /api/itp/{address}/orders - record an order of the signed-in wallet
+//! - GET /api/itp/{address}/orders - the signed-in wallet's orders on the ITP
+//! - GET /api/itp/orders/{order_id} - one order of the signed-in wallet, or
+//!   any order with an admin credential (support lookups by id)
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use tracing::error;
+
+use crate::entities::itps;
+use crate::handlers::admin::require_admin_credential;
+use crate::handlers::auth::require_session;
+use crate::models::itp::ItpErrorResponse;
+use crate::models::itp_order::{CreateItpOrderRequest, ItpOrderResponse};
+use crate::services::itp_listing;
+use crate::services::itp_orders::{self, ItpOrderError};
+use crate::AppState;
+
+type HandlerError = (StatusCode, Json<ItpErrorResponse>);
+
+/// POST /api/itp/{address}/orders
+///
+/// # Response
+/// - 201: Order recorded as pending
+/// - 400: Invalid side, amount or tx_hash
+/// - 401: No valid session
+/// - 404: ITP not found
+pub async fn create_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+    Json(request): Json<CreateItpOrderRequest>,
+) -> Result<(StatusCode, Json<ItpOrderResponse>), HandlerError> {
+    let wallet = session_wallet(&state, &headers)?;
+    let itp = find_itp(&state, &address).await?;
+
+    let order = itp_orders::create(&state.db, &itp, &wallet, &request).await.map_err(|e| {
+        let (status, code) = match &e {
+            ItpOrderError::InvalidSide(_) => (StatusCode::BAD_REQUEST, "INVALID_SIDE"),
+            ItpOrderError::InvalidAmount => (StatusCode::BAD_REQUEST, "INVALID_AMOUNT"),
+            ItpOrderError::InvalidTxHash(_) => (StatusCode::BAD_REQUEST, "INVALID_TX_HASH"),
+            ItpOrderError::Database(e) => return database_error(e),
+        };
+        error_response(status, &e.to_string(), code)
+    })?;
+
+    Ok((StatusCode::CREATED, Json(order)))
+}
+
+/// GET /api/itp/{address}/orders
+pub async fn list_orders(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<ItpOrderResponse>>, HandlerError> {
+    let wallet = session_wallet(&state, &headers)?;
+    let itp = find_itp(&state, &address).await?;
+
+    let orders = itp_orders::list_for_wallet(&state.db, &itp, &wallet)
+        .await
+        .map_err(|e| database_error(&e))?;
+
+    Ok(Json(orders))
+}
+
+/// GET /api/itp/orders/{order_id}
+///
+/// # Response
+/// - 200: The order
+/// - 401: Neither a valid session nor an admin credential
+/// - 404: No such order, or it belongs to another wallet
+pub async fn get_order(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(order_id): Path<String>,
+) -> Result<Json<ItpOrderResponse>, HandlerError> {
+    // None for admins, who may look up any order
+    let wallet = match session_wallet(&state, &headers) {
+        Ok(wallet) => Some(wallet),
+        Err(unauthorized) => {
+            require_admin_credential(&state, &headers).await.map_err(|_| unauthorized)?;
+            None
+        }
+    };
+
+    itp_orders::find(&state.db, &order_id)
+        .await
+        .map_err(|e| database_error(&e))?
+        .filter(|order| wallet.as_ref().is_none_or(|wallet| order.wallet_address.eq_ignore_ascii_case(wallet)))
+        .map(Json)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Order not found", "ORDER_NOT_FOUND"))
+}
+
+fn session_wallet(state: &AppState, headers: &HeaderMap) -> Result<String, HandlerError> {
+    require_session(&state.auth, headers).map_err(|(status, Json(e))| {
+        (
+            status,
+            Json(ItpErrorResponse {
+                error: e.error,
+                code: None,
+                violations: Vec::new(),
+            }),
+        )
+    })
+}
+
+async fn find_itp(state: &AppState, address: &str) -> Result<itps::Model, HandlerError> {
+    itp_listing::find_by_address(&state.db, address)
+        .await
+        .map_err(|e| database_error(&e))?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "ITP not found", "ITP_NOT_FOUND"))
+}
+
+fn error_response(status: StatusCode, message: &str, code: &str) -> HandlerError {
+    (
+        status,
+        Json(ItpErrorResponse {
+            error: message.to_string(),
+            code: Some(code.to_string()),
+            violations: Vec::new(),
+        }),
+    )
+}
+
+fn database_error(e: &sea_orm::DbErr) -> HandlerError {
+    error!(error = %e, "Database error handling ITP order");
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error", "DATABASE_ERROR")
+}
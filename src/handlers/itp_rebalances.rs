@@ -10,12 +10,14 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use rust_decimal::Decimal;
 use sea_orm::{EntityTrait, QueryFilter, QueryOrder, ColumnTrait};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
 use crate::AppState;
 use crate::entities::rebalances;
+use crate::services::rebalance_math::CoinFee;
 
 /// A single rebalance event from history.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +28,11 @@ pub struct RebalanceEventResponse {
     pub coins: serde_json::Value,
     pub rebalance_type: String,
     pub deployed: Option<bool>,
+    /// Trading fees the rebalance paid, already deducted from the index
+    /// value; None for rebalances without computed fees
+    pub total_fees: Option<Decimal>,
+    /// Traded value and fee per position
+    pub fee_breakdown: Option<Vec<CoinFee>>,
 }
 
 /// Response for the rebalance history endpoint.
@@ -98,6 +105,12 @@ pub async fn get_rebalance_history(
             coins: r.coins,
             rebalance_type: r.rebalance_type,
             deployed: r.deployed,
+            total_fees: r.total_fees,
+            fee_breakdown: r.fee_breakdown.and_then(|breakdown| {
+                serde_json::from_value(breakdown)
+                    .inspect_err(|e| warn!(rebalance_id = r.id, error = %e, "Unreadable fee breakdown"))
+                    .ok()
+            }),
         })
         .collect();
 
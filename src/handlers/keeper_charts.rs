@@ -8,13 +8,14 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
 
 use crate::entities::{keeper_claimable_data, prelude::*};
 use crate::models::token::ErrorResponse;
+use crate::services::pricing_time;
 use crate::AppState;
 
 /// Validate that a string is a valid Ethereum address format (0x + 40 hex chars)
@@ -142,9 +143,8 @@ pub async fn get_keeper_history(
     // Use effective_start (first non-zero) if no start_date provided
     if let Some(start_str) = &query.start_date {
         if let Ok(start_date) = NaiveDate::parse_from_str(start_str, "%Y-%m-%d") {
-            let start_datetime = start_date.and_hms_opt(0, 0, 0).unwrap();
             db_query = db_query.filter(
-                keeper_claimable_data::Column::RecordedAt.gte(start_datetime),
+                keeper_claimable_data::Column::RecordedAt.gte(pricing_time::cutoff(start_date)),
             );
         }
     } else if let Some(start_dt) = effective_start {
@@ -156,9 +156,10 @@ pub async fn get_keeper_history(
 
     if let Some(end_str) = &query.end_date {
         if let Ok(end_date) = NaiveDate::parse_from_str(end_str, "%Y-%m-%d") {
-            let end_datetime = end_date.and_hms_opt(23, 59, 59).unwrap();
+            // Up to the next day's cutoff, sub-second records included
+            let next_day = end_date + Duration::days(1);
             db_query = db_query.filter(
-                keeper_claimable_data::Column::RecordedAt.lte(end_datetime),
+                keeper_claimable_data::Column::RecordedAt.lt(pricing_time::cutoff(next_day)),
             );
         }
     }
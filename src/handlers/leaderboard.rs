@@ -0,0 +1,54 @@
+//! Index leaderboard handler
+//!
+//! GET /indexes/leaderboard ranks indexes for the landing page's "top
+//! performing indexes" widget (see services::leaderboard).
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+
+use crate::models::leaderboard::{LeaderboardQuery, LeaderboardResponse};
+use crate::models::token::ErrorResponse;
+use crate::services::leaderboard::{self, LeaderboardMetric, LeaderboardWindow};
+use crate::AppState;
+
+/// GET /indexes/leaderboard?window=7d|30d|ytd&metric=return|volume|tvl
+///
+/// Indexes ranked best first by `metric` (default: return) over `window`
+/// (default: 30d).
+///
+/// # Response
+/// - 200: Ranked indexes
+/// - 400: Invalid window or metric
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+    let window = match query.window.as_deref() {
+        Some(window) => LeaderboardWindow::parse(window)
+            .ok_or_else(|| bad_request(format!("Invalid window '{}', expected 7d, 30d or ytd", window)))?,
+        None => LeaderboardWindow::Days30,
+    };
+    let metric = match query.metric.as_deref() {
+        Some(metric) => LeaderboardMetric::parse(metric)
+            .ok_or_else(|| bad_request(format!("Invalid metric '{}', expected return, volume or tvl", metric)))?,
+        None => LeaderboardMetric::Return,
+    };
+
+    let response = leaderboard::leaderboard(&state.db, window, metric, Utc::now().date_naive())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    Ok(Json(response))
+}
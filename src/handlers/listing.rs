@@ -0,0 +1,117 @@
+//! Exchange listing handlers
+//!
+//! GET /api/listings serves the listing and delisting dates scraped from
+//! exchange announcements, each with the announcement it came from (see
+//! services::crypto_listings). GET /crypto-listings pages through the same
+//! dataset, or exports it as CSV, for research.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::models::listing::{CryptoListingsPage, CryptoListingsQuery, ListingResponse, ListingsQuery};
+use crate::models::token::ErrorResponse;
+use crate::services::crypto_listings::{self, ListingFilter};
+use crate::AppState;
+
+/// GET /api/listings?coin_id=&exchange=&status=&limit=
+///
+/// Newest listings first. `sourceAnnouncement` is null for listings whose
+/// announcement couldn't be matched.
+pub async fn get_listings(
+    State(state): State<AppState>,
+    Query(query): Query<ListingsQuery>,
+) -> Result<Json<Vec<ListingResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let filter = ListingFilter {
+        coin_id: query.coin_id,
+        exchange: query.exchange,
+        status: query.status,
+        symbol: None,
+        limit: query.limit,
+    };
+
+    let listings = crypto_listings::list(&state.db, &filter).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    Ok(Json(
+        listings
+            .into_iter()
+            .map(|(listing, announcement)| ListingResponse::new(listing, announcement))
+            .collect(),
+    ))
+}
+
+/// GET /crypto-listings?exchange=&status=&symbol=&page=&page_size=&format=json|csv
+///
+/// One page of listings, newest first, with the total number of matches. With
+/// `format=csv` every matching listing is returned instead, oldest first, as
+/// a CSV download; `page` and `page_size` are ignored.
+pub async fn get_crypto_listings(
+    State(state): State<AppState>,
+    Query(query): Query<CryptoListingsQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let csv = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => return Err(bad_request(format!("Invalid format '{}', expected json or csv", other))),
+    };
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(bad_request("page starts at 1".to_string()));
+    }
+    let page_size = query.page_size.unwrap_or(crypto_listings::DEFAULT_LIMIT).clamp(1, crypto_listings::MAX_LIMIT);
+
+    let filter = ListingFilter {
+        coin_id: None,
+        exchange: query.exchange,
+        status: query.status,
+        symbol: query.symbol,
+        limit: None,
+    };
+    let db_error = |e: sea_orm::DbErr| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    };
+
+    if csv {
+        let listings = crypto_listings::export(&state.db, &filter).await.map_err(db_error)?;
+        let mut response = crypto_listings::to_csv(&listings).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_static("attachment; filename=\"crypto_listings.csv\""),
+        );
+        return Ok(response);
+    }
+
+    let (listings, total) = crypto_listings::page(&state.db, &filter, page, page_size).await.map_err(db_error)?;
+    Ok(Json(CryptoListingsPage {
+        listings: listings
+            .into_iter()
+            .map(|(listing, announcement)| ListingResponse::new(listing, announcement))
+            .collect(),
+        total,
+        page,
+        page_size,
+        total_pages: total.div_ceil(page_size),
+    })
+    .into_response())
+}
+
+fn bad_request(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+}
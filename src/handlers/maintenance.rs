@@ -0,0 +1,71 @@
+//! Read-only maintenance mode
+//!
+//! While the maintenance_mode flag is on (see services::feature_flags), a
+//! middleware layer on the router answers every write with 503, except under
+//! /admin so operators can still work on the system and switch the mode off.
+//! Reads, which include POSTs to the read-only /graphql, are served as usual. Handlers behind other flags use
+//! `require_feature` to answer 503 while their feature is switched off.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::models::token::ErrorResponse;
+use crate::services::feature_flags::flags;
+use crate::AppState;
+
+/// Seconds clients are asked to wait before retrying a write
+const RETRY_AFTER_SECS: u32 = 300;
+
+/// Middleware rejecting writes during maintenance
+pub async fn maintenance(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let read = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+        || request.uri().path() == "/graphql";
+    if read || request.uri().path().starts_with("/admin/") {
+        return next.run(request).await;
+    }
+    if !state.feature_flags.is_enabled(flags::MAINTENANCE_MODE).await {
+        return next.run(request).await;
+    }
+
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ErrorResponse {
+            error: "The API is in read-only maintenance mode, try again later".to_string(),
+        }),
+    )
+        .into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+    response
+}
+
+/// 503 unless the feature behind `key` is switched on
+pub(crate) async fn require_feature(state: &AppState, key: &str) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if state.feature_flags.is_enabled(key).await {
+        return Ok(());
+    }
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ErrorResponse {
+            error: format!("Feature '{}' is temporarily disabled, try again later", key),
+        }),
+    ))
+}
+
+/// 503 once today's CoinGecko credit budget is spent (see
+/// services::coingecko_usage)
+pub(crate) fn require_coingecko_budget(state: &AppState) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if !state.coingecko.budget_exhausted() {
+        return Ok(());
+    }
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ErrorResponse {
+            error: "Daily CoinGecko credit budget exhausted, on-the-fly lookups resume tomorrow (UTC)".to_string(),
+        }),
+    ))
+}
@@ -7,9 +7,15 @@ use crate::{
     entities::{category_membership, coingecko_categories, coins, coins_historical_prices, prelude::*},
     models::{
         market_cap::{MarketCapDataPoint, MarketCapHistoryQuery, MarketCapHistoryResponse,
-                     TopCategoryQuery, TopCategoryResponse, TopCategoryCoin},
+                     TopCategoryQuery, TopCategoryResponse, TopCategoryCoin,
+                     TopMarketCapQuery, TopMarketCapResponse,
+                     MarketCapHistoryBatchRequest, MarketCapHistoryBatchResponse},
         token::ErrorResponse,
     },
+    handlers::maintenance::require_coingecko_budget,
+    services::market_cap::{history_for_coins, top_by_market_cap},
+    services::price_retention::{PriceCoverage, RetentionConfig},
+    services::pricing_time,
     AppState,
 };
 
@@ -150,6 +156,7 @@ pub async fn get_market_cap_history(
             "No historical data found for {} in database, attempting CoinGecko fetch",
             query.coin_id
         );
+        require_coingecko_budget(&state)?;
 
         // Attempt to fetch from CoinGecko API
         match fetch_from_coingecko_and_cache(
@@ -237,11 +244,7 @@ pub async fn get_market_cap_history(
             .and_then(|v| v.to_f64())
             .unwrap_or(0.0);
 
-        let date_time = record
-            .date
-            .and_hms_opt(0, 0, 0)
-            .unwrap_or_else(|| record.date.and_hms_opt(0, 0, 1).unwrap())
-            .and_utc();
+        let date_time = pricing_time::cutoff_utc(record.date);
 
         data_points.push(MarketCapDataPoint {
             date: date_time,
@@ -258,6 +261,78 @@ pub async fn get_market_cap_history(
     }))
 }
 
+/// Longest date range of a batch history request (5 years)
+const MAX_BATCH_DAYS: i64 = 1826;
+
+/// Handler for POST /api/market-cap/history/batch
+/// Stored market cap history of up to 100 coins over a date range, aligned on the same days
+pub async fn get_market_cap_history_batch(
+    State(state): State<AppState>,
+    Json(request): Json<MarketCapHistoryBatchRequest>,
+) -> Result<Json<MarketCapHistoryBatchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(e) = request.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: e }),
+        ));
+    }
+
+    // Formats already checked by validate()
+    let parse = |date: &Option<String>| {
+        date.as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    };
+    let today = Utc::now().date_naive();
+    let end_date = parse(&request.end_date).unwrap_or(today);
+    let start_date = parse(&request.start_date).unwrap_or(end_date - Duration::days(365));
+
+    if start_date > end_date {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "start_date must be before or equal to end_date".to_string(),
+            }),
+        ));
+    }
+    if end_date > today {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "end_date cannot be in the future".to_string(),
+            }),
+        ));
+    }
+    if (end_date - start_date).num_days() >= MAX_BATCH_DAYS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Date range cannot exceed {} days", MAX_BATCH_DAYS),
+            }),
+        ));
+    }
+
+    let coin_ids = request.unique_coin_ids();
+    tracing::info!(
+        "Fetching market cap history for {} coins ({} to {})",
+        coin_ids.len(),
+        start_date,
+        end_date
+    );
+
+    let response = history_for_coins(&state.db, &coin_ids, start_date, end_date)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
+    Ok(Json(response))
+}
+
 /// Fetch historical market cap data from CoinGecko API and cache in database
 async fn fetch_from_coingecko_and_cache(
     state: &AppState,
@@ -281,16 +356,8 @@ async fn fetch_from_coingecko_and_cache(
     let symbol = coin.map(|c| c.symbol).unwrap_or_else(|| "UNKNOWN".to_string());
 
     // Convert dates to Unix timestamps
-    let start_timestamp = start_date
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp();
-    let end_timestamp = end_date
-        .and_hms_opt(23, 59, 59)
-        .unwrap()
-        .and_utc()
-        .timestamp();
+    let start_timestamp = pricing_time::cutoff_timestamp(start_date);
+    let end_timestamp = pricing_time::day_end_timestamp(end_date);
 
     // Fetch from CoinGecko
     let data = state
@@ -323,13 +390,12 @@ async fn fetch_from_coingecko_and_cache(
                 let volume_24h = v_arr[1].as_f64().unwrap_or(0.0);
 
                 // Convert timestamp to NaiveDate
-                let date = chrono::DateTime::from_timestamp(timestamp_ms / 1000, 0)
-                    .unwrap()
-                    .naive_utc()
-                    .date();
+                let Some(date) = pricing_time::pricing_date_from_millis(timestamp_ms) else {
+                    continue;
+                };
 
                 // Create data point for response
-                let date_time = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+                let date_time = pricing_time::cutoff_utc(date);
                 data_points.push(MarketCapDataPoint {
                     date: date_time,
                     market_cap: market_cap_val,
@@ -616,6 +682,60 @@ pub async fn get_top_category(
     }))
 }
 
+/// Handler for GET /api/market-cap/top
+/// Global top N cryptocurrencies by market capitalization as of a date, from stored historical prices.
+/// Dates past the price retention cutoff are ranked on the history retention kept for them.
+pub async fn get_top_market_cap(
+    State(state): State<AppState>,
+    Query(query): Query<TopMarketCapQuery>,
+) -> Result<Json<TopMarketCapResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(e) = query.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: e }),
+        ));
+    }
+
+    let n = query.get_n();
+    let today = Utc::now().date_naive();
+    let target_date = match query.date {
+        // Format already checked by validate()
+        Some(ref date_str) => NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap_or(today),
+        None => today,
+    };
+
+    if target_date > today {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "date cannot be in the future".to_string(),
+            }),
+        ));
+    }
+
+    let coverage = RetentionConfig::from_env().coverage(today, target_date);
+    let (from, to) = coverage.window(target_date);
+    let coins = top_by_market_cap(&state.db, from, to, n).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    tracing::info!("Returning {} top coins by market cap on {}", coins.len(), target_date);
+
+    Ok(Json(TopMarketCapResponse {
+        date: target_date.to_string(),
+        n,
+        prices_from: from.to_string(),
+        prices_to: to.to_string(),
+        partial: coverage == PriceCoverage::ReferencedOnly,
+        coins: coins.as_ref().clone(),
+    }))
+}
+
 /// Query parameters for live category data endpoint
 #[derive(Debug, serde::Deserialize)]
 pub struct LiveCategoryQuery {
@@ -696,410 +816,3 @@ pub async fn get_live_category(
         coins,
     }))
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-        Router,
-        routing::get,
-    };
-    use http_body_util::BodyExt;
-    use tower::ServiceExt;
-    use sea_orm::{Database, DatabaseConnection};
-    use crate::services::coingecko::CoinGeckoService;
-    use crate::services::exchange_api::ExchangeApiService;
-
-    async fn setup_test_app() -> Router {
-        // Load environment for DATABASE_URL
-        dotenvy::dotenv().ok();
-        
-        let database_url = std::env::var("DATABASE_URL")
-            .expect("DATABASE_URL must be set for integration tests");
-        
-        let db = Database::connect(&database_url)
-            .await
-            .expect("Failed to connect to test database");
-
-        let coingecko_api_key = std::env::var("COINGECKO_API_KEY")
-            .unwrap_or_else(|_| "test_key".to_string());
-        let coingecko_base_url = std::env::var("COINGECKO_BASE_URL")
-            .unwrap_or_else(|_| "https://pro-api.coingecko.com/api/v3".to_string());
-        
-        let coingecko = CoinGeckoService::new(coingecko_api_key, coingecko_base_url);
-        let exchange_api = ExchangeApiService::new(600);
-        let itp_listing = crate::services::itp_listing::ItpListingService::new();
-        let realtime_prices = crate::services::realtime_prices::RealTimePriceService::new(60);
-
-        let state = AppState {
-            db,
-            coingecko,
-            exchange_api,
-            itp_listing,
-            realtime_prices,
-        };
-
-        Router::new()
-            .route("/api/market-cap/history", get(get_market_cap_history))
-            .route("/api/market-cap/top-category", get(get_top_category))
-            .with_state(state)
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_invalid_date_format() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=bitcoin&start_date=2024/01/01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("Invalid start_date format"));
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_invalid_coin_id() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("coin_id cannot be empty"));
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_coin_not_found() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=nonexistent-coin-xyz-123")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("not found in system"));
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_future_end_date() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=bitcoin&end_date=2030-01-01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("cannot be in the future"));
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_start_after_end() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=bitcoin&start_date=2024-12-31&end_date=2024-01-01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("start_date must be before"));
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_valid_request_with_bitcoin() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=bitcoin&start_date=2024-01-01&end_date=2024-01-05")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        // Should be 200 OK or 404 if no data (both acceptable for integration test)
-        assert!(
-            response.status() == StatusCode::OK || response.status() == StatusCode::NOT_FOUND,
-            "Expected 200 OK or 404 NOT FOUND, got: {}",
-            response.status()
-        );
-
-        if response.status() == StatusCode::OK {
-            let body = response.into_body().collect().await.unwrap().to_bytes();
-            let body_str = String::from_utf8(body.to_vec()).unwrap();
-            assert!(body_str.contains("coin_id"));
-            assert!(body_str.contains("symbol"));
-            assert!(body_str.contains("data"));
-        }
-    }
-
-    #[tokio::test]
-    async fn test_market_cap_history_default_date_range() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/history?coin_id=bitcoin")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        // Should handle default 365 days range
-        assert!(
-            response.status() == StatusCode::OK || response.status() == StatusCode::NOT_FOUND,
-            "Expected 200 OK or 404, got: {}",
-            response.status()
-        );
-    }
-
-    #[tokio::test]
-    async fn test_top_category_invalid_category_id() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("category_id cannot be empty"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_top_out_of_range_below() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=defi&top=0")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("between 1 and 250"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_top_out_of_range_above() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=defi&top=251")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("between 1 and 250"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_future_date() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=defi&date=2030-01-01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("cannot be in the future"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_invalid_date_format() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=defi&date=2024/01/01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("Invalid date format"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_not_found() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=nonexistent-category-xyz-123")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
-        
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let body_str = String::from_utf8(body.to_vec()).unwrap();
-        assert!(body_str.contains("not found in system"));
-    }
-
-    #[tokio::test]
-    async fn test_top_category_default_top_value() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=decentralized-finance-defi")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        // Should be 200 OK (empty array) or 404 if category doesn't exist
-        assert!(
-            response.status() == StatusCode::OK || response.status() == StatusCode::NOT_FOUND,
-            "Expected 200 OK or 404, got: {}",
-            response.status()
-        );
-
-        if response.status() == StatusCode::OK {
-            let body = response.into_body().collect().await.unwrap().to_bytes();
-            let body_str = String::from_utf8(body.to_vec()).unwrap();
-            assert!(body_str.contains("category_id"));
-            assert!(body_str.contains("coins"));
-            // Default top should be 10
-            assert!(body_str.contains("\"top\":10"));
-        }
-    }
-
-    #[tokio::test]
-    async fn test_top_category_custom_top_and_date() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=decentralized-finance-defi&top=25&date=2024-06-01")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        // Should be 200 OK (empty or with data) or 404 if category doesn't exist
-        assert!(
-            response.status() == StatusCode::OK || response.status() == StatusCode::NOT_FOUND,
-            "Expected 200 OK or 404, got: {}",
-            response.status()
-        );
-
-        if response.status() == StatusCode::OK {
-            let body = response.into_body().collect().await.unwrap().to_bytes();
-            let body_str = String::from_utf8(body.to_vec()).unwrap();
-            assert!(body_str.contains("\"top\":25"));
-            assert!(body_str.contains("2024-06-01"));
-        }
-    }
-
-    #[tokio::test]
-    async fn test_top_category_valid_response_structure() {
-        let response = setup_test_app().await
-            .oneshot(
-                Request::builder()
-                    .uri("/api/market-cap/top-category?category_id=layer-1&top=5")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        if response.status() == StatusCode::OK {
-            let body = response.into_body().collect().await.unwrap().to_bytes();
-            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-            
-            // Verify response structure
-            assert!(json.get("category_id").is_some());
-            assert!(json.get("category_name").is_some());
-            assert!(json.get("date").is_some());
-            assert!(json.get("top").is_some());
-            assert!(json.get("coins").is_some());
-            assert!(json["coins"].is_array());
-            
-            // If coins exist, verify coin structure
-            if let Some(coins) = json["coins"].as_array() {
-                if !coins.is_empty() {
-                    let first_coin = &coins[0];
-                    assert!(first_coin.get("rank").is_some());
-                    assert!(first_coin.get("coin_id").is_some());
-                    assert!(first_coin.get("symbol").is_some());
-                    assert!(first_coin.get("name").is_some());
-                    assert!(first_coin.get("market_cap").is_some());
-                    assert!(first_coin.get("price").is_some());
-                    assert!(first_coin.get("volume_24h").is_some());
-                    
-                    // Verify rank starts at 1
-                    assert_eq!(first_coin["rank"], 1);
-                }
-            }
-        }
-    }
-}
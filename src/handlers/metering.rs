@@ -0,0 +1,116 @@
+//! API key authentication and usage metering
+//!
+//! A middleware layer on the router checks requests carrying an X-API-Key
+//! issued through /admin/api-keys (see services::api_keys): unknown or
+//! revoked keys are rejected, read-only tiers are limited to GET/HEAD outside
+//! /admin, admin-tier keys reach only the /admin routes in ADMIN_TIER_ROUTES,
+//! and keys over their daily quota get 429 until the next UTC day.
+//! Accepted requests are counted per key, day and matched route.
+//!
+//! Requests without a key, and those with the shared ADMIN_API_KEY, pass
+//! through unmetered.
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use tracing::{error, warn};
+
+use crate::models::token::ErrorResponse;
+use crate::services::api_keys::{self, ApiKeyTier};
+use crate::AppState;
+
+/// /admin route prefixes admin-tier keys may use: rebalance approvals need a
+/// second credential besides ADMIN_API_KEY (see services::rebalance_approvals)
+const ADMIN_TIER_ROUTES: &[&str] = &["/admin/rebalances/"];
+
+/// Middleware authenticating and metering issued API keys
+pub async fn meter(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(key) = request.headers().get("x-api-key").and_then(|v| v.to_str().ok()) else {
+        return next.run(request).await;
+    };
+    if std::env::var("ADMIN_API_KEY").is_ok_and(|admin_key| admin_key == key) {
+        return next.run(request).await;
+    }
+    // Unmatched routes 404 regardless of the key
+    let Some(endpoint) = request.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string()) else {
+        return next.run(request).await;
+    };
+
+    let api_key = match api_keys::authenticate(&state.db, key).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => {
+            warn!(endpoint = %endpoint, "Invalid or revoked API key");
+            return reject(StatusCode::UNAUTHORIZED, "Invalid or missing API key");
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to look up API key");
+            return reject(StatusCode::INTERNAL_SERVER_ERROR, "Database error");
+        }
+    };
+
+    let tier = ApiKeyTier::parse(&api_key.tier).unwrap_or(ApiKeyTier::Public);
+    let read = request.method() == Method::GET || request.method() == Method::HEAD;
+    let admin_allowed = tier == ApiKeyTier::Admin && ADMIN_TIER_ROUTES.iter().any(|route| endpoint.starts_with(route));
+    if (endpoint.starts_with("/admin/") && !admin_allowed) || (tier.read_only() && !read) {
+        return reject(
+            StatusCode::FORBIDDEN,
+            &format!("API key tier '{}' can't access {} {}", tier.as_str(), request.method(), endpoint),
+        );
+    }
+
+    let now = Utc::now().naive_utc();
+    let today = now.date();
+    if let Some(quota) = api_key.daily_quota {
+        match api_keys::requests_on(&state.db, api_key.id, today).await {
+            Ok(used) if used >= quota as i64 => {
+                let mut response = reject(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    &format!("Daily quota of {} requests exceeded", quota),
+                );
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds_until_tomorrow(now)));
+                return response;
+            }
+            Ok(_) => {}
+            // Don't fail requests over metering; the next one will check again
+            Err(e) => error!(error = %e, key_id = api_key.id, "Failed to read API key usage"),
+        }
+    }
+
+    let response = next.run(request).await;
+
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        if let Err(e) = api_keys::record(&db, api_key.id, today, &endpoint).await {
+            error!(error = %e, key_id = api_key.id, endpoint = %endpoint, "Failed to record API key usage");
+        }
+    });
+
+    response
+}
+
+fn reject(status: StatusCode, error: &str) -> Response {
+    (status, Json(ErrorResponse { error: error.to_string() })).into_response()
+}
+
+fn seconds_until_tomorrow(now: NaiveDateTime) -> i64 {
+    let tomorrow = (now.date() + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap();
+    (tomorrow - now).num_seconds().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_seconds_until_tomorrow() {
+        let day = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(seconds_until_tomorrow(day.and_hms_opt(23, 59, 0).unwrap()), 60);
+        assert_eq!(seconds_until_tomorrow(day.and_hms_opt(0, 0, 0).unwrap()), 86_400);
+    }
+}
@@ -0,0 +1,75 @@
+//! Index methodology document handlers
+//!
+//! GET /indexes/{index_id}/methodology lists the published versions of an
+//! index's methodology; GET /indexes/{index_id}/methodology/{version} serves
+//! one in full. Versions are published through
+//! POST /admin/indexes/{index_id}/methodology.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use sea_orm::EntityTrait;
+
+use crate::entities::prelude::*;
+use crate::models::methodology::{MethodologyDocument, MethodologyVersionRef, MethodologyVersionsResponse};
+use crate::models::token::ErrorResponse;
+use crate::services::methodology_documents;
+use crate::AppState;
+
+/// GET /indexes/{index_id}/methodology
+pub async fn list_methodology_versions(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+) -> Result<Json<MethodologyVersionsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| not_found(format!("Index {} not found", index_id)))?;
+
+    let documents = methodology_documents::list(&state.db, index_id).await.map_err(db_error)?;
+
+    // Versions are listed newest first and effective dates never decrease
+    let today = Utc::now().date_naive();
+    let current_version = documents
+        .iter()
+        .find(|document| document.effective_from <= today)
+        .map(|document| document.version);
+
+    Ok(Json(MethodologyVersionsResponse {
+        index_id,
+        current_version,
+        versions: documents.iter().map(MethodologyVersionRef::from).collect(),
+    }))
+}
+
+/// GET /indexes/{index_id}/methodology/{version}
+pub async fn get_methodology_version(
+    State(state): State<AppState>,
+    Path((index_id, version)): Path<(i32, i32)>,
+) -> Result<Json<MethodologyDocument>, (StatusCode, Json<ErrorResponse>)> {
+    let document = methodology_documents::get(&state.db, index_id, version)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            not_found(format!("Methodology version {} of index {} not found", version, index_id))
+        })?;
+
+    Ok(Json(document.into()))
+}
+
+fn not_found(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::NOT_FOUND, Json(ErrorResponse { error }))
+}
+
+fn db_error(e: sea_orm::DbErr) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: format!("Database error: {}", e),
+        }),
+    )
+}
@@ -18,3 +18,27 @@ pub mod itp_rebalances;
 pub mod orderbook;
 pub mod orderbook_ws;
 pub mod operations_ws;
+pub mod admin;
+pub mod cache_control;
+pub mod methodology;
+pub mod metering;
+pub mod auth;
+pub mod statements;
+pub mod composition;
+pub mod itp_drift;
+pub mod itp_orders;
+pub mod solvers;
+pub mod itp_execution_quote;
+pub mod listing;
+pub mod maintenance;
+pub mod leaderboard;
+pub mod tvl;
+pub mod stats;
+pub mod feeds;
+pub mod analytics;
+pub mod snapshots;
+pub mod events;
+pub mod index_categories;
+pub mod backtest;
+#[cfg(feature = "graphql")]
+pub mod graphql;
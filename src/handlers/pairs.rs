@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -10,8 +10,11 @@ use crate::{
     entities::{coins, prelude::Coins},
     models::{
         pairs::{TradeablePairsQuery, TradeablePairsResponse, TradeablePairInfo, AllTradeableAssetsResponse, TradeableAssetInfo, CoinMappingResponse, CoinMapping},
+        pairs::{ExchangePairsQuery, ExchangePairsResponse, LivePairStatus, PairAvailability},
         token::ErrorResponse,
     },
+    handlers::maintenance::require_feature,
+    services::{crypto_listings, exchange_api::{QUOTE_ASSETS, SUPPORTED_EXCHANGES}, feature_flags::flags},
     AppState,
 };
 
@@ -22,6 +25,7 @@ pub async fn get_tradeable_pairs(
     Query(query): Query<TradeablePairsQuery>,
 ) -> Result<(StatusCode, Json<TradeablePairsResponse>), (StatusCode, Json<ErrorResponse>)> {
     tracing::info!("Fetching tradeable pairs with query: {:?}", query);
+    require_feature(&state, flags::LIVE_EXCHANGE_CHECKS).await?;
 
     // Parse coin_ids from comma-separated string
     let symbols: Vec<String> = if let Some(ref coin_ids_str) = query.coin_ids {
@@ -266,6 +270,7 @@ pub async fn get_all_tradeable_assets(
     State(state): State<AppState>,
 ) -> Result<(StatusCode, Json<AllTradeableAssetsResponse>), (StatusCode, Json<ErrorResponse>)> {
     tracing::info!("Fetching all tradeable assets");
+    require_feature(&state, flags::LIVE_EXCHANGE_CHECKS).await?;
 
     match state.exchange_api.get_all_tradeable_symbols().await {
         Ok(tokens) => {
@@ -405,3 +410,76 @@ mod tests {
         assert_eq!(adjust_priority(5, false), 5);
     }
 }
+
+/// Handler for GET /exchanges/{exchange}/pairs?symbol=
+/// Live USDC/USDT pairs of a symbol on Binance or Bitget (from the cached
+/// exchange info), plus its listing history from crypto_listings, so
+/// curators can check tradeability before setting exchanges_allowed
+pub async fn get_exchange_pairs(
+    State(state): State<AppState>,
+    Path(exchange): Path<String>,
+    Query(query): Query<ExchangePairsQuery>,
+) -> Result<Json<ExchangePairsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }));
+
+    let exchange = exchange.to_lowercase();
+    if !SUPPORTED_EXCHANGES.contains(&exchange.as_str()) {
+        return Err(bad_request(format!(
+            "Unsupported exchange '{}', expected one of: {}",
+            exchange,
+            SUPPORTED_EXCHANGES.join(", ")
+        )));
+    }
+
+    let symbol = query.symbol.unwrap_or_default().trim().to_uppercase();
+    if symbol.is_empty() || symbol.len() > 20 || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(bad_request("symbol must be 1 to 20 letters or digits".to_string()));
+    }
+    require_feature(&state, flags::LIVE_EXCHANGE_CHECKS).await?;
+
+    let live = state.exchange_api.get_live_quote_assets(&exchange, &symbol).await.map_err(|e| {
+        tracing::error!("Failed to fetch {} pairs for {}: {}", exchange, symbol, e);
+        (
+            StatusCode::BAD_GATEWAY,
+            Json(ErrorResponse {
+                error: format!("Failed to fetch exchange data: {}", e),
+            }),
+        )
+    })?;
+
+    let history = crypto_listings::for_exchange_symbol(&state.db, &exchange, &symbol).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    let pairs: Vec<LivePairStatus> = QUOTE_ASSETS
+        .iter()
+        .map(|quote| LivePairStatus {
+            trading_pair: format!("{}{}", symbol, quote),
+            quote_currency: quote.to_string(),
+            status: if live.iter().any(|q| q == quote) { "trading" } else { "unavailable" }.to_string(),
+        })
+        .collect();
+
+    Ok(Json(ExchangePairsResponse {
+        exchange,
+        symbol,
+        tradeable: !live.is_empty(),
+        pairs,
+        history: history
+            .into_iter()
+            .map(|listing| PairAvailability {
+                trading_pair: listing.trading_pair,
+                status: listing.status,
+                listing_date: listing.listing_date,
+                delisting_announcement_date: listing.delisting_announcement_date,
+                delisting_date: listing.delisting_date,
+            })
+            .collect(),
+        cache_expires_in_secs: state.exchange_api.get_cache_age_secs().await,
+    }))
+}
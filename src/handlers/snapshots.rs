@@ -0,0 +1,51 @@
+//! Snapshot proofs
+//!
+//! GET /indexes/{index_id}/snapshot-proof/{date} serves the hash-chained
+//! snapshot log entries of an index's rebalances and daily NAV for a day
+//! (see services::snapshot_log), so published history can be checked
+//! against a chain head recorded earlier.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+
+use crate::models::snapshot::SnapshotProofResponse;
+use crate::models::token::ErrorResponse;
+use crate::services::snapshot_log;
+use crate::AppState;
+
+/// GET /indexes/{index_id}/snapshot-proof/{date}
+pub async fn get_snapshot_proof(
+    State(state): State<AppState>,
+    Path((index_id, date)): Path<(i32, String)>,
+) -> Result<Json<SnapshotProofResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Invalid date format. Use YYYY-MM-DD".to_string(),
+            }),
+        )
+    })?;
+
+    let proof = snapshot_log::proof(&state.db, index_id, date).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    proof.map(Json).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No snapshot logged for index {} on {}", index_id, date),
+            }),
+        )
+    })
+}
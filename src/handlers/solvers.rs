@@ -0,0 +1,73 @@
+//! Solver registry handlers
+//!
+//! - POST /api/solvers/register - register (or update) the signed-in wallet's solver
+//! - POST /api/solvers/{id}/heartbeat - liveness ping from the solver's wallet
+//! - GET /api/solvers?asset=&exchange=&online= - registered solvers, for the UI
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use tracing::error;
+
+use crate::handlers::auth::require_session;
+use crate::models::solver::{RegisterSolverRequest, SolverResponse, SolversQuery};
+use crate::models::token::ErrorResponse;
+use crate::services::solvers::{self, SolverConfig, SolverError};
+use crate::AppState;
+
+/// POST /api/solvers/register
+pub async fn register_solver(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RegisterSolverRequest>,
+) -> Result<Json<SolverResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let wallet = require_session(&state.auth, &headers)?;
+
+    let solver = solvers::register(&state.db, &SolverConfig::from_env(), &wallet, &request)
+        .await
+        .map_err(solver_error)?;
+
+    Ok(Json(solver))
+}
+
+/// POST /api/solvers/{id}/heartbeat
+pub async fn solver_heartbeat(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Json<SolverResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let wallet = require_session(&state.auth, &headers)?;
+
+    let solver = solvers::heartbeat(&state.db, &SolverConfig::from_env(), id, &wallet)
+        .await
+        .map_err(solver_error)?;
+
+    Ok(Json(solver))
+}
+
+/// GET /api/solvers
+pub async fn list_solvers(
+    State(state): State<AppState>,
+    Query(query): Query<SolversQuery>,
+) -> Result<Json<Vec<SolverResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let solvers = solvers::list(&state.db, &SolverConfig::from_env(), &query)
+        .await
+        .map_err(|e| solver_error(SolverError::Database(e)))?;
+
+    Ok(Json(solvers))
+}
+
+fn solver_error(e: SolverError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        SolverError::InvalidRegistration(_) => StatusCode::BAD_REQUEST,
+        SolverError::NotFound(_) => StatusCode::NOT_FOUND,
+        SolverError::NotOwner(_) => StatusCode::FORBIDDEN,
+        SolverError::Database(_) => {
+            error!("Solver registry error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, Json(ErrorResponse { error: e.to_string() }))
+}
@@ -0,0 +1,70 @@
+//! Wallet statements
+//!
+//! GET /me/statements?year=&format= returns the annual statement of the
+//! signed-in wallet (see services::statements), as JSON or as a CSV download
+//! for tax reporting.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Datelike, Utc};
+use tracing::error;
+
+use crate::handlers::auth::require_session;
+use crate::models::statement::StatementQuery;
+use crate::models::token::ErrorResponse;
+use crate::services::statements;
+use crate::AppState;
+
+/// First year statements can be requested for
+const FIRST_YEAR: i32 = 2024;
+
+/// GET /me/statements?year=&format=json|csv
+pub async fn get_my_statement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<StatementQuery>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let address = require_session(&state.auth, &headers)?;
+
+    let now = Utc::now().naive_utc();
+    let year = query.year.unwrap_or(now.year());
+    if !(FIRST_YEAR..=now.year()).contains(&year) {
+        return Err(bad_request(format!("year must be between {} and {}", FIRST_YEAR, now.year())));
+    }
+    let csv = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => return Err(bad_request(format!("Invalid format '{}', expected json or csv", other))),
+    };
+
+    let statement = statements::build(&state.db, &address, year, now).await.map_err(|e| {
+        error!(error = %e, address = %address, year = year, "Failed to build statement");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to build statement: {}", e),
+            }),
+        )
+    })?;
+
+    if !csv {
+        return Ok(Json(statement).into_response());
+    }
+
+    let mut response = statements::to_csv(&statement).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"statement_{}_{}.csv\"", address, year)).unwrap(),
+    );
+    Ok(response)
+}
+
+fn bad_request(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+}
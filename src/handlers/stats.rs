@@ -0,0 +1,27 @@
+//! Protocol statistics handler
+
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::models::stats::ProtocolStatsResponse;
+use crate::models::token::ErrorResponse;
+use crate::services::protocol_stats;
+use crate::AppState;
+
+/// GET /stats
+///
+/// Protocol-wide totals for the public stats banner (see
+/// services::protocol_stats), refreshed at most once a minute.
+pub async fn get_stats(
+    State(state): State<AppState>,
+) -> Result<Json<ProtocolStatsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let stats = protocol_stats::stats(&state.db).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    Ok(Json(stats))
+}
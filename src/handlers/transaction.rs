@@ -1,20 +1,41 @@
-use axum::{extract::State, http::StatusCode, Json, extract::Path};
+use axum::{extract::State, http::StatusCode, Json, extract::Path, extract::Query};
 use chrono::{DateTime, FixedOffset, NaiveDateTime};
 use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 
 use crate::entities::{blockchain_events, prelude::*};
+use crate::models::list_query::ListQuery;
 use crate::models::token::ErrorResponse;
 use crate::models::transaction::{TransactionAmount, UserTransaction, UserTransactionResponse};
+use crate::services::list_query::{self, Field};
+use crate::services::{event_amounts, index_deployments};
 use crate::AppState;
 
-const NETWORK: &str = "base";
+/// Filter and sort fields of GET /indexes/{index_id}/transactions
+const TRANSACTION_FIELDS: &[Field<blockchain_events::Column>] = &[
+    Field::text("type", blockchain_events::Column::EventType),
+    Field::text("wallet", blockchain_events::Column::UserAddress),
+    Field::text("network", blockchain_events::Column::Network),
+    Field::text("hash", blockchain_events::Column::TxHash),
+    Field::integer("block", blockchain_events::Column::BlockNumber),
+    Field::sort_only("timestamp", blockchain_events::Column::Timestamp),
+];
 
 // Similar to @Get('/getUserTransactionData/:indexId') in old backend
+//
+// Newest first unless `sort` is given; `filter` and `sort` take type, wallet,
+// network, hash and block, and sort also timestamp (see services::list_query).
 pub async fn get_index_transactions(
     State(state): State<AppState>,
     Path(index_id): Path<i32>,
+    Query(list): Query<ListQuery>,
 ) -> Result<Json<UserTransactionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |e: list_query::ListQueryError| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() }));
+    let filters = list_query::parse_filters(TRANSACTION_FIELDS, list.filter.as_deref()).map_err(bad_request)?;
+    let sorts = list_query::parse_sort(TRANSACTION_FIELDS, list.sort.as_deref()).map_err(bad_request)?;
+    let sorted = !sorts.is_empty();
+    let select = list_query::apply(BlockchainEvents::find(), filters, sorts);
+
     // Get index metadata from database
     let index_data = IndexMetadata::find_by_id(index_id)
         .one(&state.db)
@@ -36,12 +57,20 @@ pub async fn get_index_transactions(
             )
         })?;
 
-    let contract_address = index_data.address.to_lowercase();
+    let deployments = index_deployments::for_index(&state.db, &index_data)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Database error: {}", e),
+                }),
+            )
+        })?;
 
-    // Query blockchain events
-    let events = BlockchainEvents::find()
-        .filter(blockchain_events::Column::ContractAddress.eq(&contract_address))
-        .filter(blockchain_events::Column::Network.eq(NETWORK))
+    // Query blockchain events on every network the index is deployed on
+    let events = select
+        .filter(index_deployments::events_condition(&deployments))
         .filter(
             blockchain_events::Column::EventType
                 .is_in(vec!["mint", "deposit", "withdraw"]),
@@ -63,15 +92,13 @@ pub async fn get_index_transactions(
         .enumerate()
         .map(|(i, event)| {
             let wallet = event.user_address.clone();
-            let amount = event
-                .amount
+            let amount = event_amounts::amount(&event)
                 .unwrap_or(Decimal::ZERO)
                 .to_string()
                 .parse::<f64>()
                 .unwrap_or(0.0);
             
-            let quantity = event
-                .quantity
+            let quantity = event_amounts::quantity(&event)
                 .unwrap_or(Decimal::ZERO)
                 .to_string();
 
@@ -100,12 +127,14 @@ pub async fn get_index_transactions(
         })
         .collect();
 
-    // Sort by date descending
-    activities.sort_by(|a, b| {
-        let date_a = parse_datetime(&a.date_time);
-        let date_b = parse_datetime(&b.date_time);
-        date_b.cmp(&date_a)
-    });
+    // Sort by date descending, unless sorted as requested
+    if !sorted {
+        activities.sort_by(|a, b| {
+            let date_a = parse_datetime(&a.date_time);
+            let date_b = parse_datetime(&b.date_time);
+            date_b.cmp(&date_a)
+        });
+    }
 
     Ok(Json(activities))
 }
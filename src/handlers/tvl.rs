@@ -0,0 +1,82 @@
+//! Index TVL handlers
+//!
+//! GET /indexes/{index_id}/tvl-history serves the daily TVL recorded by the
+//! TVL snapshot job (see services::index_tvl).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+use sea_orm::EntityTrait;
+
+use crate::entities::prelude::*;
+use crate::models::token::ErrorResponse;
+use crate::models::tvl::{TvlHistoryQuery, TvlHistoryResponse, TvlPoint};
+use crate::services::index_tvl;
+use crate::AppState;
+
+/// GET /indexes/{index_id}/tvl-history?start_date=&end_date=
+///
+/// Daily net supply, NAV and TVL of an index, oldest first. Both dates are
+/// optional and inclusive.
+///
+/// # Response
+/// - 200: TVL history
+/// - 400: Invalid date
+/// - 404: Unknown index
+pub async fn get_tvl_history(
+    State(state): State<AppState>,
+    Path(index_id): Path<i32>,
+    Query(query): Query<TvlHistoryQuery>,
+) -> Result<Json<TvlHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let start = parse_date("start_date", query.start_date.as_deref())?;
+    let end = parse_date("end_date", query.end_date.as_deref())?;
+
+    let index = IndexMetadata::find_by_id(index_id)
+        .one(&state.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Index {} not found", index_id),
+                }),
+            )
+        })?;
+
+    let points = index_tvl::history(&state.db, index_id, start, end).await.map_err(db_error)?;
+
+    Ok(Json(TvlHistoryResponse {
+        index_id,
+        name: index.name,
+        ticker: index.symbol,
+        points: points.into_iter().map(TvlPoint::from).collect(),
+    }))
+}
+
+fn parse_date(param: &str, value: Option<&str>) -> Result<Option<NaiveDate>, (StatusCode, Json<ErrorResponse>)> {
+    value
+        .map(|value| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Invalid {} format: '{}'. Expected YYYY-MM-DD", param, value),
+                    }),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn db_error(e: sea_orm::DbErr) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            error: format!("Database error: {}", e),
+        }),
+    )
+}
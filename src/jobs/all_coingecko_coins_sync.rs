@@ -1,10 +1,14 @@
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, Set,
+};
 use tokio::time::{interval, Duration};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::entities::{coins, prelude::*};
 use crate::services::coingecko::{CoinGeckoService, CoinListItem};
+use crate::services::locking::{self, JobOutcome};
 use crate::services::sync_status::{self, jobs, intervals};
 
 pub async fn start_all_coingecko_coins_sync_job(
@@ -19,11 +23,12 @@ pub async fn start_all_coingecko_coins_sync_job(
             Ok(true) => {
                 tracing::info!("Starting all CoinGecko coins sync (startup or interval elapsed)");
                 match sync_all_coingecko_coins(&db, &coingecko).await {
-                    Ok(_) => {
+                    Ok(JobOutcome::Ran(_)) => {
                         if let Err(e) = sync_status::record_success(&db, jobs::ALL_COINGECKO_COINS, intervals::ALL_COINGECKO_COINS).await {
                             tracing::warn!("Failed to record sync success: {}", e);
                         }
                     }
+                    Ok(JobOutcome::Skipped) => {}
                     Err(e) => {
                         tracing::error!("Failed to sync all CoinGecko coins on startup: {}", e);
                         if let Err(e2) = sync_status::record_failure(&db, jobs::ALL_COINGECKO_COINS, &e.to_string(), intervals::ALL_COINGECKO_COINS).await {
@@ -51,11 +56,12 @@ pub async fn start_all_coingecko_coins_sync_job(
                 Ok(true) => {
                     tracing::info!("Starting scheduled all CoinGecko coins sync");
                     match sync_all_coingecko_coins(&db, &coingecko).await {
-                        Ok(_) => {
+                        Ok(JobOutcome::Ran(_)) => {
                             if let Err(e) = sync_status::record_success(&db, jobs::ALL_COINGECKO_COINS, intervals::ALL_COINGECKO_COINS).await {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync all CoinGecko coins: {}", e);
                             if let Err(e2) = sync_status::record_failure(&db, jobs::ALL_COINGECKO_COINS, &e.to_string(), intervals::ALL_COINGECKO_COINS).await {
@@ -78,7 +84,11 @@ pub async fn start_all_coingecko_coins_sync_job(
 async fn sync_all_coingecko_coins(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::ALL_COINGECKO_COINS).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Check if coins table is empty
     let coin_count = Coins::find().count(db).await?;
 
@@ -90,7 +100,7 @@ async fn sync_all_coingecko_coins(
         sync_all_coins_incremental(db, coingecko).await?;
     }
 
-    Ok(())
+    Ok(JobOutcome::Ran(()))
 }
 
 /// Initial sync: Fetch ALL coins (active + inactive)
@@ -124,6 +134,7 @@ async fn sync_all_coins_initial(
                 platforms: Set(None), // Leave as NULL for now
                 active: Set(is_active),
                 activated_at: Set(None),
+                deactivated: Set(false),
                 ..Default::default()
             };
 
@@ -137,7 +148,37 @@ async fn sync_all_coins_initial(
     Ok(())
 }
 
-/// Incremental sync: Update existing + add new coins
+/// Minimum share of known coins that must be present upstream before we trust
+/// the list enough to deactivate the missing ones (guards against truncated responses)
+const MIN_UPSTREAM_COVERAGE: f64 = 0.9;
+
+/// Coin ids that are new upstream and coin ids that disappeared from upstream
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CoinListDiff {
+    new_ids: Vec<String>,
+    missing_ids: Vec<String>,
+}
+
+fn diff_coin_lists(existing: &HashSet<String>, upstream: &HashSet<String>) -> CoinListDiff {
+    let mut new_ids: Vec<String> = upstream.difference(existing).cloned().collect();
+    let mut missing_ids: Vec<String> = existing.difference(upstream).cloned().collect();
+    new_ids.sort();
+    missing_ids.sort();
+    CoinListDiff { new_ids, missing_ids }
+}
+
+/// Whether the upstream list covers enough of the known coins to act on deletions
+fn upstream_is_trustworthy(existing_count: usize, missing_count: usize) -> bool {
+    if existing_count == 0 {
+        return true;
+    }
+    let present = existing_count.saturating_sub(missing_count) as f64;
+    present / existing_count as f64 >= MIN_UPSTREAM_COVERAGE
+}
+
+/// Incremental sync: insert new coin ids, update changed ones, and deactivate
+/// coins missing from the upstream list. Coins are never hard-deleted so that
+/// prices and listings referencing them stay valid.
 async fn sync_all_coins_incremental(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
@@ -145,86 +186,80 @@ async fn sync_all_coins_incremental(
     // Fetch active coins
     tracing::info!("Fetching active coins...");
     let active_coins = coingecko.fetch_all_coins_list("active").await?;
-    
-    // Fetch inactive coins
+
+    // Fetch inactive coins (needed to tell delisted-but-known coins from deleted ones)
     tracing::info!("Fetching inactive coins...");
-    // let inactive_coins = coingecko.fetch_all_coins_list("inactive").await?;
-    let inactive_coins: Vec<CoinListItem> = Vec::new();
-    
-    // Fetch newly added coins
+    let inactive_coins = coingecko.fetch_all_coins_list("inactive").await?;
+
+    // Fetch newly added coins (only used for their activation timestamps)
     tracing::info!("Fetching newly added coins...");
     let new_coins = coingecko.fetch_new_coins_list().await?;
+    let activated_at_map: HashMap<String, i64> = new_coins
+        .into_iter()
+        .map(|c| (c.id, c.activated_at))
+        .collect();
 
-    // Build a map: coin_id -> active status
-    let mut coin_active_map: HashMap<String, bool> = HashMap::new();
-    
-    for coin in &active_coins {
-        coin_active_map.insert(coin.id.clone(), true);
-    }
-    
-    for coin in &inactive_coins {
-        coin_active_map.insert(coin.id.clone(), false);
+    // Build complete upstream map: coin_id -> (info, active)
+    let mut upstream: HashMap<String, (CoinListItem, bool)> = HashMap::new();
+    for coin in inactive_coins {
+        upstream.insert(coin.id.clone(), (coin, false));
     }
-
-    // Build complete coins map for updates
-    let mut all_coins_map: HashMap<String, _> = HashMap::new();
-    
     for coin in active_coins {
-        all_coins_map.insert(coin.id.clone(), coin);
-    }
-    
-    for coin in inactive_coins {
-        all_coins_map.insert(coin.id.clone(), coin);
+        upstream.insert(coin.id.clone(), (coin, true));
     }
 
-    let mut inserted = 0;
+    let existing_coins = Coins::find().all(db).await?;
+    let existing_ids: HashSet<String> = existing_coins.iter().map(|c| c.coin_id.clone()).collect();
+    let upstream_ids: HashSet<String> = upstream.keys().cloned().collect();
+
+    let diff = diff_coin_lists(&existing_ids, &upstream_ids);
+    let now = Utc::now().naive_utc();
+
     let mut updated = 0;
+    let mut reactivated = 0;
 
-    // Update existing coins
-    let existing_coins = Coins::find().all(db).await?;
-    
+    // Update existing coins that are still upstream
     for existing_coin in existing_coins {
-        if let Some(coin_info) = all_coins_map.get(&existing_coin.coin_id) {
-            let is_active = coin_active_map.get(&existing_coin.coin_id).copied().unwrap_or(true);
-
-            // Check if anything changed
-            let needs_update = existing_coin.symbol != coin_info.symbol
-                || existing_coin.name != coin_info.name
-                || existing_coin.active != is_active;
-
-            if needs_update {
-                let mut active_model: coins::ActiveModel = existing_coin.into();
-                active_model.symbol = Set(coin_info.symbol.clone());
-                active_model.name = Set(coin_info.name.clone());
-                active_model.active = Set(is_active);
-                active_model.updated_at = Set(Some(Utc::now().naive_utc()));
-
-                active_model.update(db).await?;
-                updated += 1;
-            }
-        }
-    }
+        let Some((coin_info, is_active)) = upstream.get(&existing_coin.coin_id) else {
+            continue;
+        };
 
-    // Insert newly added coins
-    for new_coin_info in new_coins {
-        let exists = Coins::find()
-            .filter(coins::Column::CoinId.eq(&new_coin_info.id))
-            .one(db)
-            .await?;
+        let was_deactivated = existing_coin.deactivated;
+        let needs_update = existing_coin.symbol != coin_info.symbol
+            || existing_coin.name != coin_info.name
+            || existing_coin.active != *is_active
+            || was_deactivated;
 
-        if exists.is_some() {
-            continue;
+        if needs_update {
+            let mut active_model: coins::ActiveModel = existing_coin.into();
+            active_model.symbol = Set(coin_info.symbol.clone());
+            active_model.name = Set(coin_info.name.clone());
+            active_model.active = Set(*is_active);
+            active_model.deactivated = Set(false);
+            active_model.deactivated_at = Set(None);
+            active_model.updated_at = Set(Some(now));
+
+            active_model.update(db).await?;
+            updated += 1;
+            if was_deactivated {
+                reactivated += 1;
+            }
         }
+    }
 
-        let is_active = coin_active_map.get(&new_coin_info.id).copied().unwrap_or(true);
+    // Insert coin ids we have never seen
+    let mut inserted = 0;
+    for coin_id in &diff.new_ids {
+        let (coin_info, is_active) = &upstream[coin_id];
 
         let new_coin = coins::ActiveModel {
-            coin_id: Set(new_coin_info.id.clone()),
-            symbol: Set(new_coin_info.symbol.clone()),
-            name: Set(new_coin_info.name.clone()),
+            coin_id: Set(coin_info.id.clone()),
+            symbol: Set(coin_info.symbol.clone()),
+            name: Set(coin_info.name.clone()),
             platforms: Set(None), // Leave as NULL for now
-            active: Set(is_active),
-            activated_at: Set(Some(new_coin_info.activated_at)),
+            active: Set(*is_active),
+            activated_at: Set(activated_at_map.get(coin_id).copied()),
+            deactivated: Set(false),
             ..Default::default()
         };
 
@@ -232,11 +267,62 @@ async fn sync_all_coins_incremental(
         inserted += 1;
     }
 
+    // Soft-delete coins that vanished upstream
+    let mut deactivated = 0;
+    if upstream_is_trustworthy(existing_ids.len(), diff.missing_ids.len()) {
+        if !diff.missing_ids.is_empty() {
+            let result = Coins::update_many()
+                .col_expr(coins::Column::Deactivated, Expr::value(true))
+                .col_expr(coins::Column::DeactivatedAt, Expr::value(now))
+                .col_expr(coins::Column::UpdatedAt, Expr::value(now))
+                .filter(coins::Column::CoinId.is_in(diff.missing_ids.clone()))
+                .filter(coins::Column::Deactivated.eq(false))
+                .exec(db)
+                .await?;
+            deactivated = result.rows_affected;
+        }
+    } else {
+        tracing::warn!(
+            "Upstream coin list is missing {} of {} known coins, skipping deactivation",
+            diff.missing_ids.len(),
+            existing_ids.len()
+        );
+    }
+
     tracing::info!(
-        "Incremental sync complete: {} new, {} updated",
+        "Incremental sync complete: {} new, {} updated ({} reactivated), {} deactivated",
         inserted,
-        updated
+        updated,
+        reactivated,
+        deactivated
     );
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_coin_lists() {
+        let existing = set(&["bitcoin", "ethereum", "old-coin"]);
+        let upstream = set(&["bitcoin", "ethereum", "new-coin"]);
+        let diff = diff_coin_lists(&existing, &upstream);
+        assert_eq!(diff.new_ids, vec!["new-coin".to_string()]);
+        assert_eq!(diff.missing_ids, vec!["old-coin".to_string()]);
+    }
+
+    #[test]
+    fn test_upstream_is_trustworthy() {
+        assert!(upstream_is_trustworthy(0, 0));
+        assert!(upstream_is_trustworthy(1000, 10));
+        assert!(upstream_is_trustworthy(1000, 100));
+        // A truncated response missing most coins must not deactivate them
+        assert!(!upstream_is_trustworthy(1000, 500));
+    }
+}
@@ -7,6 +7,9 @@ use crate::scrapers::binance::BinanceScraper;
 use crate::scrapers::bitget::BitgetScraper;
 use crate::scrapers::coin_resolver::resolve_symbol_to_coin_id;
 use crate::scrapers::{ScrapedAnnouncement, ScrapedListing, ScraperConfig};
+use crate::services::crypto_listings::find_announcement_id;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::jobs;
 
 pub async fn start_announcement_scraper_job(
     db: DatabaseConnection,
@@ -29,7 +32,11 @@ pub async fn start_announcement_scraper_job(
 async fn scrape_all_exchanges(
     db: &DatabaseConnection,
     config: &ScraperConfig,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::ANNOUNCEMENT_SCRAPER).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Get start dates using max(timestamp) logic
     let start_dates = get_scrape_start_dates(db).await?;
 
@@ -69,7 +76,7 @@ async fn scrape_all_exchanges(
         }
     }
 
-    Ok(())
+    Ok(JobOutcome::Ran(()))
 }
 
 struct ScraperStartDates {
@@ -194,6 +201,15 @@ pub async fn save_scraped_data(
             }
         };
 
+        // The announcement was saved above; link the listing to it
+        let source_announcement_id = find_announcement_id(
+            db,
+            &listing.source,
+            listing.announcement_date,
+            Some(&listing.announcement_title),
+        )
+        .await?;
+
         // ✅ Step 2: Check if listing already exists (for merging)
         let existing = CryptoListings::find()
             .filter(crypto_listings::Column::CoinId.eq(&coin_id))
@@ -208,9 +224,12 @@ pub async fn save_scraped_data(
             let existing_listing_announcement_date = existing_listing.listing_announcement_date;
             let existing_delisting_date = existing_listing.delisting_date;
             let existing_delisting_announcement_date = existing_listing.delisting_announcement_date;
+            let existing_source_announcement_id = existing_listing.source_announcement_id;
         
             // ✅ Now we can move it
             let mut active: crypto_listings::ActiveModel = existing_listing.into();
+            // Whether this announcement's dates replaced the stored ones
+            let mut dates_applied = false;
         
             // Merge listing data
             if listing.listing_date.is_some() {
@@ -218,6 +237,7 @@ pub async fn save_scraped_data(
                 if existing_listing_date.is_none() || 
                    (listing.listing_date < existing_listing_date) {
                     active.listing_date = Set(listing.listing_date);
+                    dates_applied = true;
                 }
 
                 if existing_listing_announcement_date.is_none() ||
@@ -232,6 +252,7 @@ pub async fn save_scraped_data(
                 if existing_delisting_date.is_none() ||
                    (listing.delisting_date > existing_delisting_date) {
                     active.delisting_date = Set(listing.delisting_date);
+                    dates_applied = true;
                 }
 
                 if existing_delisting_announcement_date.is_none() ||
@@ -241,6 +262,12 @@ pub async fn save_scraped_data(
 
                 active.status = Set("delisted".to_string());
             }
+
+            // Point at the announcement behind the latest applied date, or
+            // at least at one of the listing's announcements
+            if source_announcement_id.is_some() && (dates_applied || existing_source_announcement_id.is_none()) {
+                active.source_announcement_id = Set(source_announcement_id);
+            }
         
             active.updated_at = Set(Some(Utc::now().naive_utc()));
             active.update(db).await?;
@@ -278,6 +305,7 @@ pub async fn save_scraped_data(
                 }),
                 delisting_date: Set(listing.delisting_date),
                 status: Set(status.to_string()),
+                source_announcement_id: Set(source_announcement_id),
                 ..Default::default()
             };
 
@@ -0,0 +1,221 @@
+//! Worker for the background_tasks queue
+//!
+//! Polls for pending tasks, runs them one at a time and records the outcome.
+//! While a task runs its heartbeat (and progress, for backfills) is refreshed
+//! so clients can follow it and other instances can tell it apart from a task
+//! abandoned by a crash.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration};
+use tracing::Instrument;
+
+use crate::entities::background_tasks;
+use crate::services::background_tasks::{self as tasks, task_types, HEARTBEAT_INTERVAL_SECS};
+use crate::services::coingecko::CoinGeckoService;
+use crate::services::daily_prices::backfill_daily_prices;
+use crate::services::locking;
+use crate::services::rebalancing::{BackfillProgress, RebalancingService};
+
+/// How often the worker looks for new tasks
+const POLL_INTERVAL_SECS: u64 = 10;
+
+/// Result of running a claimed task
+enum Outcome {
+    Completed,
+    /// Could not run right now (e.g. index locked elsewhere); back to pending
+    Deferred,
+}
+
+pub async fn start_background_tasks_worker(db: DatabaseConnection, coingecko: CoinGeckoService) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(POLL_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            match tasks::recover_stale(&db).await {
+                Ok(0) => {}
+                Ok(count) => tracing::warn!("Recovered {} abandoned background tasks", count),
+                Err(e) => tracing::warn!("Failed to recover stale background tasks: {}", e),
+            }
+
+            // Drain the queue before sleeping again
+            loop {
+                let task = match tasks::claim_next(&db).await {
+                    Ok(Some(task)) => task,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!("Failed to claim background task: {}", e);
+                        break;
+                    }
+                };
+
+                // A deferred task isn't due again yet, so this moves on to
+                // the tasks behind it
+                run_task(&db, &coingecko, task).await;
+            }
+        }
+    });
+}
+
+/// Run a claimed task to completion, heartbeating meanwhile
+async fn run_task(db: &DatabaseConnection, coingecko: &CoinGeckoService, task: background_tasks::Model) {
+    tracing::info!(
+        task_id = task.id,
+        task_type = %task.task_type,
+        index_id = ?task.index_id,
+        attempt = task.attempts,
+        "Starting background task"
+    );
+
+    let (progress_tx, mut progress_rx) = watch::channel(BackfillProgress::default());
+
+    let result = {
+        let span = tracing::info_span!(
+            "background_task",
+            task_id = task.id,
+            task_type = %task.task_type,
+            index_id = ?task.index_id,
+        );
+        let work = execute(db, coingecko, &task, &progress_tx).instrument(span);
+        tokio::pin!(work);
+
+        let mut heartbeat = interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+        heartbeat.tick().await; // First tick completes immediately
+
+        loop {
+            tokio::select! {
+                result = &mut work => break result,
+                _ = heartbeat.tick() => {
+                    let update = if progress_rx.has_changed().unwrap_or(false) {
+                        let progress = progress_rx.borrow_and_update().clone();
+                        report_progress(db, &task, &progress).await
+                    } else {
+                        tasks::heartbeat(db, task.id).await
+                    };
+                    if let Err(e) = update {
+                        tracing::warn!(task_id = task.id, error = %e, "Failed to record task heartbeat");
+                    }
+                }
+            }
+        }
+    };
+
+    let task_id = task.id;
+    let update = match result {
+        Ok(Outcome::Completed) => {
+            tracing::info!(task_id = task_id, status = "completed", "Background task completed");
+            tasks::mark_completed(db, task).await
+        }
+        Ok(Outcome::Deferred) => {
+            tracing::info!(task_id = task_id, status = "deferred", "Background task deferred");
+            tasks::defer(db, task).await
+        }
+        Err(e) => {
+            tracing::error!(task_id = task_id, status = "failed", error = %e, "Background task failed");
+            tasks::mark_failed(db, task, &e.to_string()).await
+        }
+    };
+
+    if let Err(e) = update {
+        tracing::warn!(task_id = task_id, error = %e, "Failed to update background task");
+    }
+}
+
+/// Persist and log a progress snapshot of a running task
+async fn report_progress(
+    db: &DatabaseConnection,
+    task: &background_tasks::Model,
+    progress: &BackfillProgress,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (done, total) = (progress.done as i32, progress.total as i32);
+    let now = Utc::now().naive_utc();
+    let eta = task
+        .started_at
+        .and_then(|started_at| tasks::estimate_eta(started_at, now, done, total));
+
+    tracing::info!(
+        task_id = task.id,
+        index_id = ?task.index_id,
+        done = done,
+        total = total,
+        percent = ?tasks::progress_percent(done, total),
+        current_date = ?progress.current_date,
+        eta = ?eta,
+        "Backfill progress"
+    );
+
+    tasks::record_progress(db, task.id, done, total, progress.current_date).await
+}
+
+async fn execute(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    task: &background_tasks::Model,
+    progress: &watch::Sender<BackfillProgress>,
+) -> Result<Outcome, Box<dyn std::error::Error + Send + Sync>> {
+    match task.task_type.as_str() {
+        task_types::INDEX_BACKFILL => {
+            let index_id = task.index_id.ok_or("Backfill task has no index_id")?;
+            run_index_backfill(db, coingecko, task.id, index_id, progress).await
+        }
+        other => Err(format!("Unknown background task type '{}'", other).into()),
+    }
+}
+
+/// Backfill historical rebalances, then daily prices, for a new index
+async fn run_index_backfill(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    task_id: i32,
+    index_id: i32,
+    progress: &watch::Sender<BackfillProgress>,
+) -> Result<Outcome, Box<dyn std::error::Error + Send + Sync>> {
+    // Hold the index lock for the whole backfill so the scheduled
+    // rebalance job on another instance doesn't race us
+    let Some(index_lock) = locking::try_acquire_index(db, index_id).await? else {
+        return Ok(Outcome::Deferred);
+    };
+
+    // Step 1: Backfill rebalances
+    let rebalancing_service = RebalancingService::new(db.clone(), coingecko.clone(), None);
+    rebalancing_service
+        .backfill_historical_rebalances_with_progress(index_id, Some(progress))
+        .await
+        .map_err(|e| format!("Rebalances backfill failed: {}", e))?;
+
+    // Persist the final counts now rather than waiting for the next heartbeat
+    let done = progress.borrow().clone();
+    if let Err(e) = tasks::record_progress(db, task_id, done.done as i32, done.total as i32, None).await {
+        tracing::warn!(task_id = task_id, error = %e, "Failed to record backfill progress");
+    }
+
+    tracing::info!(
+        task_id = task_id,
+        index_id = index_id,
+        stage = "rebalances",
+        status = "completed",
+        "Rebalances backfill complete"
+    );
+
+    // Step 2: Backfill daily prices (only after rebalances are done)
+    backfill_daily_prices(db, coingecko, index_id)
+        .await
+        .map_err(|e| format!("Daily prices backfill failed: {}", e))?;
+
+    tracing::info!(
+        task_id = task_id,
+        index_id = index_id,
+        stage = "daily_prices",
+        status = "completed",
+        "Daily prices backfill complete"
+    );
+
+    if let Err(e) = index_lock.release().await {
+        tracing::warn!(index_id = index_id, error = %e, "Failed to release index lock");
+    }
+
+    Ok(Outcome::Completed)
+}
@@ -7,6 +7,9 @@ use serde::Deserialize;
 use tokio::time::{interval, Duration};
 
 use crate::entities::{coins, coins_historical_prices, crypto_listings, prelude::*};
+use crate::services::job_runs;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::pricing_time;
 use crate::services::sync_status::{self, jobs, intervals};
 
 /// Bitget kline response
@@ -54,7 +57,7 @@ pub async fn start_bitget_historical_prices_sync_job(db: DatabaseConnection) {
                 Ok(true) => {
                     tracing::info!("Starting Bitget historical prices sync");
                     match sync_bitget_historical_prices(&db, &client).await {
-                        Ok(_) => {
+                        Ok(JobOutcome::Ran(_)) => {
                             if let Err(e) = sync_status::record_success(
                                 &db,
                                 jobs::BITGET_HISTORICAL_PRICES,
@@ -65,6 +68,7 @@ pub async fn start_bitget_historical_prices_sync_job(db: DatabaseConnection) {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync Bitget historical prices: {}", e);
                             if let Err(e2) = sync_status::record_failure(
@@ -97,7 +101,21 @@ pub async fn start_bitget_historical_prices_sync_job(db: DatabaseConnection) {
 async fn sync_bitget_historical_prices(
     db: &DatabaseConnection,
     client: &reqwest::Client,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::BITGET_HISTORICAL_PRICES).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    job_runs::track(db, jobs::BITGET_HISTORICAL_PRICES, sync_listings(db, client)).await?;
+    Ok(JobOutcome::Ran(()))
+}
+
+/// One run over the active Bitget listings; returns the number of price
+/// rows stored
+async fn sync_listings(
+    db: &DatabaseConnection,
+    client: &reqwest::Client,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
     let today = Utc::now().date_naive();
 
     // Get all active Bitget listings
@@ -119,6 +137,7 @@ async fn sync_bitget_historical_prices(
         .collect();
 
     let mut synced_count = 0;
+    let mut stored_count = 0;
     let mut skipped_count = 0;
     let mut error_count = 0;
 
@@ -179,6 +198,7 @@ async fn sync_bitget_historical_prices(
                 if stored > 0 {
                     tracing::debug!("Stored {} historical prices for {}", stored, symbol);
                     synced_count += 1;
+                    stored_count += stored;
                 } else {
                     skipped_count += 1;
                 }
@@ -194,13 +214,14 @@ async fn sync_bitget_historical_prices(
     }
 
     tracing::info!(
-        "✅ Bitget historical prices sync complete: {} synced, {} skipped, {} errors",
+        "✅ Bitget historical prices sync complete: {} synced ({} prices), {} skipped, {} errors",
         synced_count,
+        stored_count,
         skipped_count,
         error_count
     );
 
-    Ok(())
+    Ok(stored_count)
 }
 
 /// Get the last synced date for a coin
@@ -225,17 +246,8 @@ async fn fetch_bitget_klines(
     to: NaiveDate,
 ) -> Result<Vec<(NaiveDate, Decimal, Option<Decimal>)>, Box<dyn std::error::Error + Send + Sync>> {
     let mut all_klines = Vec::new();
-    let mut end_time = to
-        .and_hms_opt(23, 59, 59)
-        .unwrap()
-        .and_utc()
-        .timestamp_millis();
-
-    let from_timestamp = from
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp_millis();
+    let mut end_time = pricing_time::day_end_millis(to);
+    let from_timestamp = pricing_time::cutoff_millis(from);
 
     // Bitget API: max 1000 candles per request, use pagination
     loop {
@@ -279,8 +291,8 @@ async fn fetch_bitget_klines(
                 return Ok(all_klines);
             }
 
-            let date = match chrono::DateTime::from_timestamp_millis(timestamp_ms) {
-                Some(dt) => dt.date_naive(),
+            let date = match pricing_time::pricing_date_from_millis(timestamp_ms) {
+                Some(date) => date,
                 None => continue,
             };
 
@@ -0,0 +1,107 @@
+//! Consistency checker for category membership intervals
+//!
+//! Point-in-time index selection relies on `added_date`/`removed_date` being
+//! accurate. This job scans category_membership daily and flags coins whose
+//! intervals overlap, are inverted, or reference a coin missing from `coins`.
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::category_service::{self, MembershipIssueKind};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::{self, jobs, intervals};
+
+/// Maximum number of individual issues logged per run (the rest are counted only)
+const MAX_LOGGED_ISSUES: usize = 50;
+
+pub async fn start_category_membership_consistency_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(
+                &db,
+                jobs::CATEGORY_MEMBERSHIP_CONSISTENCY,
+                intervals::CATEGORY_MEMBERSHIP_CONSISTENCY,
+            )
+            .await
+            {
+                Ok(true) => {
+                    tracing::info!("Starting category membership consistency check");
+                    match run_consistency_check(&db).await {
+                        Ok(JobOutcome::Ran(())) => {
+                            if let Err(e) = sync_status::record_success(
+                                &db,
+                                jobs::CATEGORY_MEMBERSHIP_CONSISTENCY,
+                                intervals::CATEGORY_MEMBERSHIP_CONSISTENCY,
+                            )
+                            .await
+                            {
+                                tracing::warn!("Failed to record sync success: {}", e);
+                            }
+                        }
+                        Ok(JobOutcome::Skipped) => {}
+                        Err(e) => {
+                            tracing::error!("Category membership consistency check failed: {}", e);
+                            if let Err(e2) = sync_status::record_failure(
+                                &db,
+                                jobs::CATEGORY_MEMBERSHIP_CONSISTENCY,
+                                &e.to_string(),
+                                intervals::CATEGORY_MEMBERSHIP_CONSISTENCY,
+                            )
+                            .await
+                            {
+                                tracing::warn!("Failed to record sync failure: {}", e2);
+                            }
+                        }
+                    }
+                }
+                Ok(false) => {
+                    tracing::debug!("Skipping category membership consistency check (recently run)");
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                }
+            }
+        }
+    });
+}
+
+async fn run_consistency_check(
+    db: &DatabaseConnection,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::CATEGORY_MEMBERSHIP_CONSISTENCY).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let issues = category_service::check_membership_consistency(db).await?;
+
+    if issues.is_empty() {
+        tracing::info!("Category membership consistency check passed: no issues found");
+        return Ok(JobOutcome::Ran(()));
+    }
+
+    let count_of = |kind: MembershipIssueKind| issues.iter().filter(|i| i.kind == kind).count();
+
+    tracing::warn!(
+        overlaps = count_of(MembershipIssueKind::Overlap),
+        inverted = count_of(MembershipIssueKind::InvertedInterval),
+        missing_coins = count_of(MembershipIssueKind::MissingCoin),
+        "Category membership consistency check found {} issues",
+        issues.len()
+    );
+
+    for issue in issues.iter().take(MAX_LOGGED_ISSUES) {
+        tracing::warn!(
+            kind = issue.kind.as_str(),
+            coin_id = %issue.coin_id,
+            category_id = %issue.category_id,
+            membership_ids = ?issue.membership_ids,
+            "Inconsistent category membership"
+        );
+    }
+
+    Ok(JobOutcome::Ran(()))
+}
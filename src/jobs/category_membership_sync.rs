@@ -1,12 +1,13 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, Set
+    sea_query::Expr, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use tokio::time::{interval, Duration};
 
-use crate::entities::{category_membership, prelude::*};
+use crate::entities::{category_change_events, category_membership, prelude::*};
 use crate::services::coingecko::CoinGeckoService;
+use crate::services::locking::{self, JobOutcome};
 use crate::services::sync_status::{self, jobs, intervals};
 
 pub async fn start_category_membership_sync_job(
@@ -21,11 +22,12 @@ pub async fn start_category_membership_sync_job(
             Ok(true) => {
                 tracing::info!("Starting category membership sync (startup or interval elapsed)");
                 match sync_category_membership(&db, &coingecko).await {
-                    Ok(_) => {
+                    Ok(JobOutcome::Ran(_)) => {
                         if let Err(e) = sync_status::record_success(&db, jobs::CATEGORY_MEMBERSHIP, intervals::CATEGORY_MEMBERSHIP).await {
                             tracing::warn!("Failed to record sync success: {}", e);
                         }
                     }
+                    Ok(JobOutcome::Skipped) => {}
                     Err(e) => {
                         tracing::error!("Failed to sync category membership on startup: {}", e);
                         if let Err(e2) = sync_status::record_failure(&db, jobs::CATEGORY_MEMBERSHIP, &e.to_string(), intervals::CATEGORY_MEMBERSHIP).await {
@@ -50,11 +52,12 @@ pub async fn start_category_membership_sync_job(
                 Ok(true) => {
                     tracing::info!("Starting scheduled category membership sync");
                     match sync_category_membership(&db, &coingecko).await {
-                        Ok(_) => {
+                        Ok(JobOutcome::Ran(_)) => {
                             if let Err(e) = sync_status::record_success(&db, jobs::CATEGORY_MEMBERSHIP, intervals::CATEGORY_MEMBERSHIP).await {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync category membership: {}", e);
                             if let Err(e2) = sync_status::record_failure(&db, jobs::CATEGORY_MEMBERSHIP, &e.to_string(), intervals::CATEGORY_MEMBERSHIP).await {
@@ -74,10 +77,36 @@ pub async fn start_category_membership_sync_job(
     });
 }
 
+/// Change type values stored in category_change_events.change_type
+pub mod change_types {
+    pub const ADDED: &str = "added";
+    pub const REMOVED: &str = "removed";
+}
+
+/// Result of comparing the previous membership snapshot with the upstream list
+#[derive(Debug, Default, PartialEq, Eq)]
+struct MembershipDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+/// Compute adds/removes between the active snapshot and the current upstream set
+fn diff_membership(active: &HashSet<String>, current: &HashSet<String>) -> MembershipDiff {
+    let mut added: Vec<String> = current.difference(active).cloned().collect();
+    let mut removed: Vec<String> = active.difference(current).cloned().collect();
+    added.sort();
+    removed.sort();
+    MembershipDiff { added, removed }
+}
+
 async fn sync_category_membership(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::CATEGORY_MEMBERSHIP).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Get all categories
     let categories = CoingeckoCategories::find().all(db).await?;
 
@@ -85,13 +114,27 @@ async fn sync_category_membership(
 
     tracing::debug!("all categories: {}", categories.len());
 
-    let mut counter = 0;
-    for category in categories {
+    // Load the previous snapshot (all active memberships) once, grouped by category
+    let mut snapshot: HashMap<String, Vec<category_membership::Model>> = HashMap::new();
+    for membership in CategoryMembership::find()
+        .filter(category_membership::Column::RemovedDate.is_null())
+        .all(db)
+        .await?
+    {
+        snapshot
+            .entry(membership.category_id.clone())
+            .or_default()
+            .push(membership);
+    }
+
+    let mut total_added = 0;
+    let mut total_removed = 0;
+
+    for (counter, category) in categories.into_iter().enumerate() {
         // Rate limiting: 150ms between calls
         tokio::time::sleep(Duration::from_millis(150)).await;
 
         tracing::debug!("{}: Syncing category: {}", counter, category.category_id);
-        counter += 1;
 
         // Fetch current tokens in this category from CoinGecko
         let current_coins = match coingecko.fetch_coins_by_category(&category.category_id).await {
@@ -102,82 +145,155 @@ async fn sync_category_membership(
             }
         };
 
+        let active_memberships = snapshot.remove(&category.category_id).unwrap_or_default();
+
+        // An empty upstream list for a populated category is far more likely an API
+        // glitch than every coin leaving at once - don't close out the whole category
+        if current_coins.is_empty() && !active_memberships.is_empty() {
+            tracing::warn!(
+                "CoinGecko returned no coins for category {} ({} active members), skipping",
+                category.category_id,
+                active_memberships.len()
+            );
+            continue;
+        }
+
         // Build map of coin_id -> symbol for efficient lookup
-        let coin_symbol_map: std::collections::HashMap<String, String> = current_coins
+        let coin_symbol_map: HashMap<String, String> = current_coins
             .iter()
-            .map(|c| (c.id.clone(), c.symbol.clone().to_uppercase()))
+            .map(|c| (c.id.clone(), c.symbol.to_uppercase()))
             .collect();
 
-        let current_tokens: HashSet<String> = current_coins
-            .into_iter()
-            .map(|c| c.id)
-            .collect();
-
-        // Get active tokens in category from database
-        let active_memberships = CategoryMembership::find()
-            .filter(category_membership::Column::CategoryId.eq(&category.category_id))
-            .filter(category_membership::Column::RemovedDate.is_null())
-            .all(db)
-            .await?;
-
+        let current_tokens: HashSet<String> = coin_symbol_map.keys().cloned().collect();
         let active_tokens: HashSet<String> = active_memberships
             .iter()
             .map(|m| m.coin_id.clone())
             .collect();
 
-        let current_tokens_set: HashSet<String> = current_tokens.into_iter().collect();
+        let diff = diff_membership(&active_tokens, &current_tokens);
+        if diff.added.is_empty() && diff.removed.is_empty() {
+            continue;
+        }
 
-        // Find new tokens (in current but not in active)
-        let new_tokens: Vec<_> = current_tokens_set
-            .difference(&active_tokens)
-            .cloned()
+        let removed_ids: Vec<i32> = active_memberships
+            .iter()
+            .filter(|m| diff.removed.contains(&m.coin_id))
+            .map(|m| m.id)
             .collect();
 
-        // Find removed tokens (in active but not in current)
-        let removed_tokens: Vec<_> = active_tokens
-            .difference(&current_tokens_set)
-            .cloned()
-            .collect();
+        let mut events = Vec::with_capacity(diff.added.len() + diff.removed.len());
+        let mut new_memberships = Vec::with_capacity(diff.added.len());
 
-        // Add new tokens
-        for coin_id in new_tokens {
-            let symbol = coin_symbol_map.get(&coin_id).cloned();
-            
-            let new_membership = category_membership::ActiveModel {
+        for coin_id in &diff.added {
+            let symbol = coin_symbol_map.get(coin_id).cloned();
+            new_memberships.push(category_membership::ActiveModel {
                 coin_id: Set(coin_id.clone()),
                 category_id: Set(category.category_id.clone()),
                 added_date: Set(today),
                 removed_date: Set(None),
                 symbol: Set(symbol.clone()),
                 ..Default::default()
-            };
-
-            new_membership.insert(db).await?;
-            // tracing::info!(
-            //     "Added {} (symbol: {}) to category {}", 
-            //     coin_id, 
-            //     symbol.unwrap_or_else(|| "unknown".to_string()),
-            //     category.category_id
-            // );
+            });
+            events.push(change_event(&category.category_id, coin_id, symbol, change_types::ADDED, today));
         }
 
-        // Mark removed tokens
-        for coin_id in removed_tokens {
-            // Find the active membership
-            if let Some(membership) = active_memberships
-                .iter()
-                .find(|m| m.coin_id == coin_id)
-            {
-                let mut active_model = membership.clone().into_active_model();
-                active_model.removed_date = Set(Some(today));
-                active_model.updated_at = Set(Some(today));
-                active_model.update(db).await?;
-                
-                tracing::info!("Removed {} from category {}", coin_id, category.category_id);
-            }
+        for membership in active_memberships.iter().filter(|m| diff.removed.contains(&m.coin_id)) {
+            events.push(change_event(
+                &category.category_id,
+                &membership.coin_id,
+                membership.symbol.clone(),
+                change_types::REMOVED,
+                today,
+            ));
+        }
+
+        // Apply membership changes and their events atomically so consumers never
+        // see an event without the matching membership row (or vice versa)
+        let txn = db.begin().await?;
+
+        if !new_memberships.is_empty() {
+            CategoryMembership::insert_many(new_memberships).exec(&txn).await?;
+        }
+
+        if !removed_ids.is_empty() {
+            CategoryMembership::update_many()
+                .col_expr(category_membership::Column::RemovedDate, Expr::value(today))
+                .col_expr(category_membership::Column::UpdatedAt, Expr::value(today))
+                .filter(category_membership::Column::Id.is_in(removed_ids))
+                .exec(&txn)
+                .await?;
         }
+
+        CategoryChangeEvents::insert_many(events).exec(&txn).await?;
+
+        txn.commit().await?;
+
+        tracing::info!(
+            "Category {}: {} added, {} removed",
+            category.category_id,
+            diff.added.len(),
+            diff.removed.len()
+        );
+
+        total_added += diff.added.len();
+        total_removed += diff.removed.len();
     }
 
-    tracing::info!("Category membership sync complete");
-    Ok(())
+    tracing::info!(
+        "Category membership sync complete: {} added, {} removed",
+        total_added,
+        total_removed
+    );
+    Ok(JobOutcome::Ran(()))
+}
+
+fn change_event(
+    category_id: &str,
+    coin_id: &str,
+    symbol: Option<String>,
+    change_type: &str,
+    effective_at: NaiveDateTime,
+) -> category_change_events::ActiveModel {
+    category_change_events::ActiveModel {
+        category_id: Set(category_id.to_string()),
+        coin_id: Set(coin_id.to_string()),
+        symbol: Set(symbol),
+        change_type: Set(change_type.to_string()),
+        effective_at: Set(effective_at),
+        delivered_at: Set(None),
+        created_at: Set(effective_at),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_membership_adds_and_removes() {
+        let active = set(&["bitcoin", "ethereum", "solana"]);
+        let current = set(&["ethereum", "solana", "avalanche-2", "cardano"]);
+        let diff = diff_membership(&active, &current);
+        assert_eq!(diff.added, vec!["avalanche-2".to_string(), "cardano".to_string()]);
+        assert_eq!(diff.removed, vec!["bitcoin".to_string()]);
+    }
+
+    #[test]
+    fn test_diff_membership_unchanged() {
+        let active = set(&["bitcoin", "ethereum"]);
+        let diff = diff_membership(&active, &active.clone());
+        assert_eq!(diff, MembershipDiff::default());
+    }
+
+    #[test]
+    fn test_diff_membership_from_empty_snapshot() {
+        let diff = diff_membership(&HashSet::new(), &set(&["bitcoin"]));
+        assert_eq!(diff.added, vec!["bitcoin".to_string()]);
+        assert!(diff.removed.is_empty());
+    }
 }
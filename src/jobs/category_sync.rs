@@ -4,6 +4,7 @@ use tokio::time::{interval, Duration};
 
 use crate::entities::{coingecko_categories, prelude::*};
 use crate::services::coingecko::CoinGeckoService;
+use crate::services::locking::{self, JobOutcome};
 use crate::services::sync_status::{self, jobs, intervals};
 
 pub async fn start_category_sync_job(
@@ -18,11 +19,12 @@ pub async fn start_category_sync_job(
             Ok(true) => {
                 tracing::info!("Starting category sync (startup or interval elapsed)");
                 match sync_categories(&db, &coingecko).await {
-                    Ok(_) => {
+                    Ok(JobOutcome::Ran(_)) => {
                         if let Err(e) = sync_status::record_success(&db, jobs::CATEGORY_SYNC, intervals::CATEGORY_SYNC).await {
                             tracing::warn!("Failed to record sync success: {}", e);
                         }
                     }
+                    Ok(JobOutcome::Skipped) => {}
                     Err(e) => {
                         tracing::error!("Failed to sync categories on startup: {}", e);
                         if let Err(e2) = sync_status::record_failure(&db, jobs::CATEGORY_SYNC, &e.to_string(), intervals::CATEGORY_SYNC).await {
@@ -50,11 +52,12 @@ pub async fn start_category_sync_job(
                 Ok(true) => {
                     tracing::info!("Starting scheduled CoinGecko categories sync");
                     match sync_categories(&db, &coingecko).await {
-                        Ok(_) => {
+                        Ok(JobOutcome::Ran(_)) => {
                             if let Err(e) = sync_status::record_success(&db, jobs::CATEGORY_SYNC, intervals::CATEGORY_SYNC).await {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync categories: {}", e);
                             if let Err(e2) = sync_status::record_failure(&db, jobs::CATEGORY_SYNC, &e.to_string(), intervals::CATEGORY_SYNC).await {
@@ -77,7 +80,11 @@ pub async fn start_category_sync_job(
 async fn sync_categories(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::CATEGORY_SYNC).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Fetch categories from CoinGecko
     let categories = coingecko.fetch_categories().await?;
 
@@ -119,5 +126,5 @@ async fn sync_categories(
         updated_count
     );
 
-    Ok(())
+    Ok(JobOutcome::Ran(()))
 }
@@ -1,21 +1,28 @@
+use std::collections::HashSet;
+
 use chrono::{NaiveDate, Utc};
 use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, Set,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, Set,
 };
-use serde::Deserialize;
 use tokio::time::{interval, Duration};
 
 use crate::entities::{coins, coins_historical_prices, prelude::*};
-use crate::services::coingecko::CoinGeckoService;
+use crate::services::coin_sync_cursors::{self, RunBudget, Tier};
+use crate::services::coingecko::{CoinGeckoError, CoinGeckoService};
+use crate::services::coingecko_usage::CREDITS_PER_CALL;
+use crate::services::job_failures;
+use crate::services::job_runs;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::pricing_time;
 use crate::services::sync_status::{self, jobs, intervals};
 
-#[derive(Debug, Deserialize)]
-struct MarketChartResponse {
-    prices: Vec<[f64; 2]>,
-    market_caps: Vec<[f64; 2]>,
-    total_volumes: Vec<[f64; 2]>,
-}
+/// First wait after a 429, doubled on each retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 429s in a row on one coin before the run stops
+const MAX_CONSECUTIVE_RATE_LIMITS: u32 = 3;
 
 #[derive(Debug, Clone)]
 struct CoinSyncInfo {
@@ -37,11 +44,12 @@ pub async fn start_coins_historical_prices_sync_job(
             Ok(true) => {
                 tracing::info!("Starting coins historical prices sync (startup or interval elapsed)");
                 match sync_coins_historical_prices(&db, &coingecko).await {
-                    Ok(_) => {
+                    Ok(JobOutcome::Ran(_)) => {
                         if let Err(e) = sync_status::record_success(&db, jobs::COINS_HISTORICAL_PRICES, intervals::COINS_HISTORICAL_PRICES).await {
                             tracing::warn!("Failed to record sync success: {}", e);
                         }
                     }
+                    Ok(JobOutcome::Skipped) => {}
                     Err(e) => {
                         tracing::error!("Failed to sync coins historical prices on startup: {}", e);
                         if let Err(e2) = sync_status::record_failure(&db, jobs::COINS_HISTORICAL_PRICES, &e.to_string(), intervals::COINS_HISTORICAL_PRICES).await {
@@ -69,11 +77,12 @@ pub async fn start_coins_historical_prices_sync_job(
                 Ok(true) => {
                     tracing::info!("Starting scheduled coins historical prices sync");
                     match sync_coins_historical_prices(&db, &coingecko).await {
-                        Ok(_) => {
+                        Ok(JobOutcome::Ran(_)) => {
                             if let Err(e) = sync_status::record_success(&db, jobs::COINS_HISTORICAL_PRICES, intervals::COINS_HISTORICAL_PRICES).await {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync coins historical prices: {}", e);
                             if let Err(e2) = sync_status::record_failure(&db, jobs::COINS_HISTORICAL_PRICES, &e.to_string(), intervals::COINS_HISTORICAL_PRICES).await {
@@ -96,7 +105,20 @@ pub async fn start_coins_historical_prices_sync_job(
 async fn sync_coins_historical_prices(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::COINS_HISTORICAL_PRICES).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    job_runs::track(db, jobs::COINS_HISTORICAL_PRICES, sync_prioritized_coins(db, coingecko)).await?;
+    Ok(JobOutcome::Ran(()))
+}
+
+/// One run over the selected coins; returns the number of price rows stored
+async fn sync_prioritized_coins(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
     let today = Utc::now().date_naive();
 
     // Get all active coins
@@ -115,6 +137,9 @@ async fn sync_coins_historical_prices(
         all_last_dates.len()
     );
 
+    let cursors = coin_sync_cursors::load(db).await?;
+    let constituents = coin_sync_cursors::constituent_coin_ids(db).await?;
+
     // Build map: coin_id -> (last_date, market_cap)
     let last_date_map: std::collections::HashMap<String, (Option<NaiveDate>, Option<Decimal>)> =
         all_last_dates
@@ -131,6 +156,10 @@ async fn sync_coins_historical_prices(
                 .cloned()
                 .unwrap_or((None, None));
 
+            // The cursor covers fetches that stored nothing new
+            let synced_through = cursors.get(&coin.coin_id).and_then(|c| c.synced_through);
+            let last_date = last_date.max(synced_through);
+
             CoinSyncInfo {
                 coin_id: coin.coin_id,
                 symbol: coin.symbol,
@@ -140,23 +169,28 @@ async fn sync_coins_historical_prices(
         })
         .collect();
 
-    // OPTIMIZATION: Filter to top 1000 by market cap + all new tokens (NULL market cap)
-    let coins_to_sync = select_top_coins_to_sync(all_coin_sync_info, 1000);
+    // OPTIMIZATION: Filter to top 1000 by market cap + index constituents + all new tokens (NULL market cap)
+    let mut coins_to_sync = select_top_coins_to_sync(all_coin_sync_info, 1000, &constituents);
+    coin_sync_cursors::prioritize(&mut coins_to_sync, |(tier, info)| (*tier, info.coin_id.as_str()), &cursors);
 
     tracing::info!(
-        "Selected {} coins to sync (top 1000 by market cap + new tokens)",
+        "Selected {} coins to sync (index constituents + top 1000 by market cap + new tokens)",
         coins_to_sync.len()
     );
 
+    let mut budget = RunBudget::new(coin_sync_cursors::run_budget_from_env());
     let mut fetched_count = 0;
+    let mut stored_count = 0;
     let mut up_to_date_count = 0;
     let mut error_count = 0;
     let mut marked_inactive_count = 0;
     let mut new_token_count = 0;
+    let mut rate_limited_count = 0;
+    let mut stopped_early = None;
 
     let total = coins_to_sync.len();
     
-    for (index, coin_info) in coins_to_sync.iter().enumerate() {
+    for (index, (_, coin_info)) in coins_to_sync.iter().enumerate() {
         let progress = index + 1;
 
         // Check if needs update
@@ -185,6 +219,11 @@ async fn sync_coins_historical_prices(
             continue;
         }
 
+        if !budget.try_spend(CREDITS_PER_CALL) {
+            stopped_early = Some(format!("run budget of {} credits spent", budget.limit));
+            break;
+        }
+
         // Calculate days to fetch
         let days_to_fetch = if coin_info.market_cap.is_none() {
             // New token (no market_cap): fetch all history
@@ -205,14 +244,33 @@ async fn sync_coins_historical_prices(
             }
         };
 
-        // Fetch and store prices
-        match fetch_and_store_prices(db, coingecko, &coin_info.coin_id, &coin_info.symbol, &days_to_fetch)
-            .await
-        {
-            Ok(count) => {
-                if count > 0 {
-                    tracing::debug!("Stored {} new prices for {}", count, coin_info.symbol);
+        // Fetch and store prices, backing off while CoinGecko rate limits us
+        let mut result =
+            fetch_and_store_prices(db, coingecko, &coin_info.coin_id, &coin_info.symbol, &days_to_fetch).await;
+        let mut backoff = RATE_LIMIT_BACKOFF;
+        let mut rate_limits = 0;
+        while matches!(result, Err(FetchError::RateLimited)) {
+            rate_limits += 1;
+            if rate_limits >= MAX_CONSECUTIVE_RATE_LIMITS || !budget.try_spend(CREDITS_PER_CALL) {
+                break;
+            }
+            tracing::warn!("CoinGecko rate limited {}, retrying in {:?}", coin_info.coin_id, backoff);
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            result =
+                fetch_and_store_prices(db, coingecko, &coin_info.coin_id, &coin_info.symbol, &days_to_fetch).await;
+        }
+
+        let now = Utc::now().naive_utc();
+        match result {
+            Ok(stored) => {
+                if stored.count > 0 {
+                    tracing::debug!("Stored {} new prices for {}", stored.count, coin_info.symbol);
                     fetched_count += 1;
+                    stored_count += stored.count;
+                }
+                if let Err(e) = coin_sync_cursors::record_success(db, &coin_info.coin_id, stored.latest_date, now).await {
+                    tracing::warn!("Failed to update sync cursor of {}: {}", coin_info.coin_id, e);
                 }
             }
             Err(FetchError::CoinNotFound) => {
@@ -231,8 +289,18 @@ async fn sync_coins_historical_prices(
                 
                 error_count += 1;
             }
+            Err(FetchError::RateLimited) => {
+                // Still limited after backing off: leave this coin and the
+                // rest for the next run, which starts with them
+                rate_limited_count += 1;
+                if let Err(e) = coin_sync_cursors::record_failure(db, &coin_info.coin_id, now).await {
+                    tracing::warn!("Failed to update sync cursor of {}: {}", coin_info.coin_id, e);
+                }
+                stopped_early = Some(format!("CoinGecko rate limit persisted on {}", coin_info.coin_id));
+                break;
+            }
             Err(FetchError::Other(e)) => {
-                // Other errors (network, rate limit, etc.)
+                // Other errors (network, server errors, etc.)
                 tracing::warn!(
                     "Failed to fetch prices for {} ({}): {}",
                     coin_info.symbol,
@@ -240,6 +308,24 @@ async fn sync_coins_historical_prices(
                     e
                 );
                 error_count += 1;
+
+                if let Err(e2) = coin_sync_cursors::record_failure(db, &coin_info.coin_id, now).await {
+                    tracing::warn!("Failed to update sync cursor of {}: {}", coin_info.coin_id, e2);
+                }
+
+                // Hand the coin to the dead-letter queue for retry with backoff
+                if let Err(e2) = job_failures::record_failure(
+                    db,
+                    jobs::COINS_HISTORICAL_PRICES,
+                    job_failures::entity_types::COIN,
+                    &coin_info.coin_id,
+                    Some(serde_json::json!({ "symbol": coin_info.symbol })),
+                    &e,
+                )
+                .await
+                {
+                    tracing::warn!("Failed to record job failure for {}: {}", coin_info.coin_id, e2);
+                }
             }
         }
 
@@ -249,27 +335,83 @@ async fn sync_coins_historical_prices(
         // Progress summary every 100 coins
         if progress % 100 == 0 {
             tracing::info!(
-                "📊 Progress: {}/{} coins | Success: {} | Errors: {} | Marked inactive: {}",
+                "📊 Progress: {}/{} coins | Success: {} | Errors: {} | Marked inactive: {} | Credits: {}/{}",
                 progress,
                 total,
                 fetched_count,
                 error_count,
-                marked_inactive_count
+                marked_inactive_count,
+                budget.spent,
+                budget.limit
             );
         }
     }
 
+    if let Some(reason) = stopped_early {
+        tracing::warn!(
+            "Coins historical prices sync stopped early ({}); the remaining coins go first next run",
+            reason
+        );
+    }
+
     tracing::info!(
-        "✅ Coins historical prices sync complete: {} updated, {} up-to-date, {} new tokens, {} errors, {} marked inactive (total synced: {} coins)",
+        "✅ Coins historical prices sync complete: {} updated ({} prices), {} up-to-date, {} new tokens, {} errors, {} rate limited, {} marked inactive, {} credits spent (total selected: {} coins)",
         fetched_count,
+        stored_count,
         up_to_date_count,
         new_token_count,
         error_count,
+        rate_limited_count,
         marked_inactive_count,
+        budget.spent,
         coins_to_sync.len()
     );
 
-    Ok(())
+    Ok(stored_count)
+}
+
+/// Retry a single coin from the dead-letter queue
+///
+/// Fetches everything since the coin's last stored date (or full history if it
+/// has none). Returns the number of new price rows stored.
+pub async fn retry_coin(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    coin_id: &str,
+    symbol: &str,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let today = Utc::now().date_naive();
+
+    let last_date = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
+        .order_by_desc(coins_historical_prices::Column::Date)
+        .one(db)
+        .await?
+        .map(|p| p.date);
+
+    let days = match last_date {
+        Some(last) if last >= today => return Ok(0),
+        Some(last) if last >= NaiveDate::from_ymd_opt(2019, 1, 1).unwrap() => {
+            (today - last).num_days().to_string()
+        }
+        _ => "max".to_string(),
+    };
+
+    let now = Utc::now().naive_utc();
+    match fetch_and_store_prices(db, coingecko, coin_id, symbol, &days).await {
+        Ok(stored) => {
+            coin_sync_cursors::record_success(db, coin_id, stored.latest_date, now).await?;
+            Ok(stored.count)
+        }
+        Err(FetchError::CoinNotFound) => {
+            mark_coin_inactive(db, coin_id).await?;
+            Ok(0)
+        }
+        Err(e) => {
+            coin_sync_cursors::record_failure(db, coin_id, now).await?;
+            Err(Box::new(e))
+        }
+    }
 }
 
 /// Get ALL coins' last dates + market caps in ONE batch query using DISTINCT ON
@@ -318,8 +460,14 @@ async fn get_all_coins_last_dates_batch(
     Ok(results)
 }
 
-/// Select top N coins by market cap + all coins with NULL market cap (new tokens)
-fn select_top_coins_to_sync(all_coins: Vec<CoinSyncInfo>, top_n: usize) -> Vec<CoinSyncInfo> {
+/// Select current index constituents, the top N coins by market cap and
+/// all coins with NULL market cap (new tokens), tagged with their tier in
+/// market cap order
+fn select_top_coins_to_sync(
+    all_coins: Vec<CoinSyncInfo>,
+    top_n: usize,
+    constituents: &HashSet<String>,
+) -> Vec<(Tier, CoinSyncInfo)> {
     // Partition by market_cap existence
     let (with_mcap, without_mcap): (Vec<_>, Vec<_>) = all_coins
         .into_iter()
@@ -333,18 +481,32 @@ fn select_top_coins_to_sync(all_coins: Vec<CoinSyncInfo>, top_n: usize) -> Vec<C
             .cmp(&a.market_cap.unwrap_or(Decimal::ZERO))
     });
 
-    // Take top N by market cap
-    let mut result: Vec<_> = sorted_by_mcap.into_iter().take(top_n).collect();
+    // Top N by market cap, plus constituents ranked below it
+    let tier_of = |coin: &CoinSyncInfo, fallback: Tier| {
+        if constituents.contains(&coin.coin_id) {
+            Tier::Constituent
+        } else {
+            fallback
+        }
+    };
+    let mut result: Vec<_> = sorted_by_mcap
+        .into_iter()
+        .enumerate()
+        .filter(|(rank, coin)| *rank < top_n || constituents.contains(&coin.coin_id))
+        .map(|(_, coin)| (tier_of(&coin, Tier::TopMarketCap), coin))
+        .collect();
+    let with_mcap_count = result.len();
 
     // Append all coins without market_cap (new tokens)
     let new_tokens_count = without_mcap.len();
-    result.extend(without_mcap);
+    result.extend(without_mcap.into_iter().map(|coin| (tier_of(&coin, Tier::NewToken), coin)));
 
     tracing::info!(
-        "Selected {} coins: {} top by market cap + {} new tokens",
+        "Selected {} coins: {} by market cap or index membership + {} new tokens ({} index constituents)",
         result.len(),
-        top_n.min(result.len() - new_tokens_count),
-        new_tokens_count
+        with_mcap_count,
+        new_tokens_count,
+        result.iter().filter(|(tier, _)| *tier == Tier::Constituent).count()
     );
 
     result
@@ -354,6 +516,8 @@ fn select_top_coins_to_sync(all_coins: Vec<CoinSyncInfo>, top_n: usize) -> Vec<C
 #[derive(Debug)]
 enum FetchError {
     CoinNotFound,
+    /// 429 from CoinGecko
+    RateLimited,
     Other(String),
 }
 
@@ -361,6 +525,7 @@ impl std::fmt::Display for FetchError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FetchError::CoinNotFound => write!(f, "Coin not found on CoinGecko"),
+            FetchError::RateLimited => write!(f, "Rate limited by CoinGecko"),
             FetchError::Other(msg) => write!(f, "{}", msg),
         }
     }
@@ -368,6 +533,14 @@ impl std::fmt::Display for FetchError {
 
 impl std::error::Error for FetchError {}
 
+/// Outcome of one successful fetch
+struct StoredPrices {
+    /// New price rows stored
+    count: usize,
+    /// Latest date CoinGecko returned, stored now or before
+    latest_date: Option<NaiveDate>,
+}
+
 /// Fetch historical prices from CoinGecko and store in database
 async fn fetch_and_store_prices(
     db: &DatabaseConnection,
@@ -375,63 +548,33 @@ async fn fetch_and_store_prices(
     coin_id: &str,
     symbol: &str,
     days: &str,
-) -> Result<usize, FetchError> {
-    let url = format!("{}/coins/{}/market_chart", coingecko.base_url(), coin_id);
-
-    // Send request with explicit error handling
-    let response = match coingecko
-        .client()
-        .get(&url)
-        .header("x-cg-pro-api-key", coingecko.api_key())
-        .query(&[
-            ("vs_currency", "usd"),
-            ("days", days),
-            ("interval", "daily"),
-        ])
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => return Err(FetchError::Other(format!("Request failed: {}", e))),
-    };
-
-    // Check response status
-    let status = response.status();
-    if !status.is_success() {
-        // 404 = coin not found/delisted
-        if status.as_u16() == 404 {
-            return Err(FetchError::CoinNotFound);
-        }
-        
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(FetchError::Other(format!("API error {}: {}", status, error_text)));
-    }
-
-    // Parse JSON response
-    let data: MarketChartResponse = match response.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            // Decoding error often means coin doesn't exist or API changed
-            // Treat as "not found"
+) -> Result<StoredPrices, FetchError> {
+    let data = match coingecko.fetch_daily_market_chart(coin_id, days).await {
+        Ok(data) => data,
+        // 404 = coin not found/delisted. A decoding error often means the
+        // coin doesn't exist or the API changed; treat as "not found" too
+        Err(CoinGeckoError::NotFound) => return Err(FetchError::CoinNotFound),
+        Err(CoinGeckoError::Decode(e)) => {
             tracing::debug!("JSON decode error for {}: {}", coin_id, e);
             return Err(FetchError::CoinNotFound);
         }
+        Err(CoinGeckoError::Api { status: 429, .. }) => return Err(FetchError::RateLimited),
+        Err(e) => return Err(FetchError::Other(e.to_string())),
     };
 
+    let mut stored = StoredPrices { count: 0, latest_date: None };
     if data.prices.is_empty() {
-        return Ok(0); // No data, but not an error
+        return Ok(stored); // No data, but not an error
     }
 
-    let mut stored_count = 0;
-
     for i in 0..data.prices.len() {
         let timestamp_ms = data.prices[i][0] as i64;
         let price = data.prices[i][1];
         let market_cap = data.market_caps.get(i).map(|m| m[1]);
         let volume = data.total_volumes.get(i).map(|v| v[1]);
 
-        let date = match chrono::DateTime::from_timestamp_millis(timestamp_ms) {
-            Some(dt) => dt.date_naive(),
+        let date = match pricing_time::pricing_date_from_millis(timestamp_ms) {
+            Some(date) => date,
             None => {
                 tracing::warn!("Invalid timestamp {} for {}", timestamp_ms, coin_id);
                 continue;
@@ -453,6 +596,7 @@ async fn fetch_and_store_prices(
         };
 
         if exists.is_some() {
+            stored.latest_date = stored.latest_date.max(Some(date));
             continue; // Skip duplicates
         }
 
@@ -481,10 +625,11 @@ async fn fetch_and_store_prices(
             continue;
         }
 
-        stored_count += 1;
+        stored.count += 1;
+        stored.latest_date = stored.latest_date.max(Some(date));
     }
 
-    Ok(stored_count)
+    Ok(stored)
 }
 
 /// Mark a coin as inactive in the database
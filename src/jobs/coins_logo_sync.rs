@@ -10,6 +10,7 @@ use tokio::time::{interval, Duration};
 
 use crate::entities::{coins, prelude::*};
 use crate::services::coingecko::CoinGeckoService;
+use crate::services::locking::{self, JobOutcome};
 use crate::services::sync_status::{self, jobs, intervals};
 
 /// Start the logo sync background job
@@ -25,12 +26,13 @@ pub async fn start_coins_logo_sync_job(
             Ok(true) => {
                 tracing::info!("Starting coins logo sync (startup or interval elapsed)");
                 match sync_coins_logos(&db, &coingecko).await {
-                    Ok(updated) => {
+                    Ok(JobOutcome::Ran(updated)) => {
                         tracing::info!("Logo sync complete: {} logos updated", updated);
                         if let Err(e) = sync_status::record_success(&db, jobs::COINS_LOGO_SYNC, intervals::COINS_LOGO_SYNC).await {
                             tracing::warn!("Failed to record sync success: {}", e);
                         }
                     }
+                    Ok(JobOutcome::Skipped) => {}
                     Err(e) => {
                         tracing::error!("Failed to sync coin logos: {}", e);
                         if let Err(e2) = sync_status::record_failure(&db, jobs::COINS_LOGO_SYNC, &e.to_string(), intervals::COINS_LOGO_SYNC).await {
@@ -58,12 +60,13 @@ pub async fn start_coins_logo_sync_job(
                 Ok(true) => {
                     tracing::info!("Starting scheduled coins logo sync");
                     match sync_coins_logos(&db, &coingecko).await {
-                        Ok(updated) => {
+                        Ok(JobOutcome::Ran(updated)) => {
                             tracing::info!("Scheduled logo sync complete: {} logos updated", updated);
                             if let Err(e) = sync_status::record_success(&db, jobs::COINS_LOGO_SYNC, intervals::COINS_LOGO_SYNC).await {
                                 tracing::warn!("Failed to record sync success: {}", e);
                             }
                         }
+                        Ok(JobOutcome::Skipped) => {}
                         Err(e) => {
                             tracing::error!("Failed to sync coin logos: {}", e);
                             if let Err(e2) = sync_status::record_failure(&db, jobs::COINS_LOGO_SYNC, &e.to_string(), intervals::COINS_LOGO_SYNC).await {
@@ -87,7 +90,11 @@ pub async fn start_coins_logo_sync_job(
 async fn sync_coins_logos(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome<usize>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::COINS_LOGO_SYNC).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Find all active coins without a logo
     let coins_without_logos = Coins::find()
         .filter(coins::Column::Active.eq(true))
@@ -97,7 +104,7 @@ async fn sync_coins_logos(
 
     if coins_without_logos.is_empty() {
         tracing::info!("All active coins already have logos");
-        return Ok(0);
+        return Ok(JobOutcome::Ran(0));
     }
 
     tracing::info!(
@@ -153,5 +160,5 @@ async fn sync_coins_logos(
 
     tracing::info!("Logo sync complete: {} coins updated", updated_count);
 
-    Ok(updated_count)
+    Ok(JobOutcome::Ran(updated_count))
 }
@@ -0,0 +1,39 @@
+//! Coins historical price partition maintenance job
+//!
+//! Runs at startup and daily, creating the yearly coins_historical_prices
+//! partitions for this year and next (see `services::price_partitions`).
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::price_partitions;
+use crate::services::sync_status::jobs;
+
+pub async fn start_coins_price_partitions_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(86400)); // Every day
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = maintain_partitions(&db).await {
+                tracing::error!("Coins price partition maintenance failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn maintain_partitions(db: &DatabaseConnection) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::COINS_PRICE_PARTITIONS).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let created = price_partitions::ensure_partitions(db, Utc::now().date_naive()).await?;
+    if created.is_empty() {
+        tracing::debug!("Coins price partitions up to date");
+    }
+
+    Ok(JobOutcome::Ran(()))
+}
@@ -0,0 +1,98 @@
+//! Coins historical price retention job
+//!
+//! Weekly, compacts coins_historical_prices for coins no index references
+//! (see `services::price_retention`). Runs as a dry run that only logs what
+//! it would remove unless PRICE_RETENTION_DRY_RUN=false.
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::price_retention::{self, RetentionConfig};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_coins_price_retention_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let config = RetentionConfig::from_env();
+        tracing::info!(
+            max_age_years = config.max_age_years,
+            mode = config.mode.as_str(),
+            dry_run = config.dry_run,
+            "Coins price retention configured"
+        );
+
+        let mut interval = interval(Duration::from_secs(86400)); // Check every day
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::COINS_PRICE_RETENTION, intervals::COINS_PRICE_RETENTION).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping coins price retention (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_retention(&db, &config).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(
+                        &db,
+                        jobs::COINS_PRICE_RETENTION,
+                        intervals::COINS_PRICE_RETENTION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Coins price retention failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::COINS_PRICE_RETENTION,
+                        &e.to_string(),
+                        intervals::COINS_PRICE_RETENTION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_retention(
+    db: &DatabaseConnection,
+    config: &RetentionConfig,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::COINS_PRICE_RETENTION).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let report = price_retention::run(db, config).await?;
+
+    tracing::info!(
+        mode = %report.mode,
+        dry_run = report.dry_run,
+        cutoff_date = %report.cutoff_date,
+        referenced_coins = report.referenced_coins,
+        affected_coins = report.affected_coins,
+        rows_removed = report.rows_removed,
+        "{}",
+        if report.dry_run {
+            "Coins price retention dry run complete"
+        } else {
+            "Coins price retention complete"
+        }
+    );
+
+    Ok(JobOutcome::Ran(()))
+}
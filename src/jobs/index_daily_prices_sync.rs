@@ -1,30 +1,84 @@
+//! Index daily prices sync
+//!
+//! Prices every active index (see services::index_status) each day from its
+//! latest rebalance's basket. Each run catches up on every date since the
+//! index's last stored price, so days missed while the service was down or
+//! the index paused are filled in order; an index with no price yet is
+//! backfilled from its inception. An index's catch-up stops at the first
+//! date that can't be priced and resumes from there on the next run.
+//! Indexes locked by another instance (e.g. a create_index backfill) are
+//! left for the next run, and dates already priced are never rewritten.
+
 use chrono::{Duration, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Order,
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, Order,
     QueryFilter, QueryOrder, QuerySelect, Set,
 };
-use std::collections::HashMap;
 use tokio::time::{interval, Duration as TokioDuration};
 
-use crate::entities::{daily_prices, rebalances, prelude::*};
+use crate::entities::{daily_prices, index_metadata, rebalances, prelude::*};
 use crate::services::coingecko::CoinGeckoService;
-use crate::services::price_utils::get_or_fetch_coins_historical_price;
+use crate::services::daily_prices::{backfill_daily_prices, basket_value};
+use crate::services::index_status;
+use crate::services::job_runs;
+use crate::services::leverage;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
 use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::services::sync_status::{self, intervals, jobs};
+use crate::services::yield_accrual::YieldAccrual;
 
 pub async fn start_index_daily_prices_sync_job(
     db: DatabaseConnection,
     coingecko: CoinGeckoService,
 ) {
     tokio::spawn(async move {
-        let mut interval = interval(TokioDuration::from_secs(86400)); // Every 24 hours
+        let mut interval = interval(TokioDuration::from_secs(3600)); // Check every hour
 
         loop {
             interval.tick().await;
-            tracing::info!("Starting scheduled index daily prices sync");
 
-            if let Err(e) = sync_index_daily_prices(&db, &coingecko).await {
-                tracing::error!("Failed to sync index daily prices: {}", e);
+            match sync_status::should_sync(&db, jobs::INDEX_DAILY_PRICES, intervals::INDEX_DAILY_PRICES).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping index daily prices sync (recently synced)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            tracing::info!("Starting scheduled index daily prices sync");
+            match sync_index_daily_prices(&db, &coingecko).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(
+                        &db,
+                        jobs::INDEX_DAILY_PRICES,
+                        intervals::INDEX_DAILY_PRICES,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Failed to sync index daily prices: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::INDEX_DAILY_PRICES,
+                        &e.to_string(),
+                        intervals::INDEX_DAILY_PRICES,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
             }
         }
     });
@@ -33,20 +87,44 @@ pub async fn start_index_daily_prices_sync_job(
 async fn sync_index_daily_prices(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Get all indexes
-    let indexes = IndexMetadata::find().all(db).await?;
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::INDEX_DAILY_PRICES).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    job_runs::track(db, jobs::INDEX_DAILY_PRICES, sync_active_indexes(db, coingecko)).await?;
+    Ok(JobOutcome::Ran(()))
+}
+
+/// One run over the active indexes; returns the number of prices stored.
+/// Fails only if every index with dates to fill failed.
+async fn sync_active_indexes(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let indexes = IndexMetadata::find()
+        .filter(index_metadata::Column::Status.eq(index_status::ACTIVE))
+        .all(db)
+        .await?;
 
     if indexes.is_empty() {
-        tracing::info!("No indexes found, skipping daily prices sync");
-        return Ok(());
+        tracing::info!("No active indexes, skipping daily prices sync");
+        return Ok(0);
     }
 
-    tracing::info!("Syncing daily prices for {} indexes", indexes.len());
+    tracing::info!("Syncing daily prices for {} active indexes", indexes.len());
 
     let today = Utc::now().date_naive();
+    let mut stored = 0;
+    let mut attempted = 0;
+    let mut failures = Vec::new();
 
     for index in indexes {
+        // Another instance may be backfilling this index
+        let Some(_index_lock) = locking::try_acquire_index(db, index.index_id).await? else {
+            continue;
+        };
+
         // Get last stored date for this index
         let last_date = DailyPrices::find()
             .filter(daily_prices::Column::IndexId.eq(index.index_id.to_string()))
@@ -56,82 +134,85 @@ async fn sync_index_daily_prices(
             .await?
             .map(|row| row.date);
 
-        let start_date = match last_date {
-            Some(date) => date + Duration::days(1),
+        let result = match last_date {
+            Some(date) if date >= today => {
+                tracing::debug!("Index {} is up to date (last date: {})", index.index_id, date);
+                continue;
+            }
+            Some(date) => {
+                attempted += 1;
+                catch_up(db, coingecko, &index, date + Duration::days(1), today).await
+            }
             None => {
-                tracing::debug!(
-                    "No existing prices for index {}, may still be backfilling. Using today.",
-                    index.index_id
-                );
-                today
+                attempted += 1;
+                tracing::info!("No prices yet for index {}, backfilling from inception", index.index_id);
+                backfill_daily_prices(db, coingecko, index.index_id).await
             }
         };
 
-        if start_date > today {
-            tracing::debug!(
-                "Index {} is up to date (last date: {:?})",
-                index.index_id,
-                last_date
-            );
-            continue;
+        match result {
+            Ok(count) => stored += count,
+            Err(e) => {
+                tracing::warn!("Daily prices sync of index {} failed: {}", index.index_id, e);
+                failures.push(format!("{}: {}", index.index_id, e));
+            }
         }
+    }
 
-        tracing::info!(
-            "Syncing index {} from {} to {} ({} days)",
-            index.index_id,
-            start_date,
-            today,
-            (today - start_date).num_days() + 1
-        );
+    if attempted > 0 && failures.len() == attempted {
+        return Err(format!("No index could be priced ({})", failures.join("; ")).into());
+    }
 
-        // Fill each missing day
-        let mut date = start_date;
-        let mut processed = 0;
-
-        while date <= today {
-            match calculate_and_store_index_price(db, coingecko, index.index_id, date).await {
-                Ok(price) => {
-                    tracing::info!(
-                        "Stored daily price for index {} ({}): {} on {}",
-                        index.index_id,
-                        index.symbol,
-                        price,
-                        date
-                    );
-                    processed += 1;
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to calculate price for index {} on {}: {}",
-                        index.index_id,
-                        date,
-                        e
-                    );
-                    // Continue with next date instead of failing entire sync
-                }
-            }
+    tracing::info!(
+        "Index daily prices sync complete: {} prices stored, {} indexes failed",
+        stored,
+        failures.len()
+    );
+    Ok(stored)
+}
 
-            date = date + Duration::days(1);
-        }
+/// Price `index` for each date from `from` to `to`, in order; returns the
+/// number of prices stored, and fails at the first date that can't be priced
+/// with that date left for the next run
+async fn catch_up(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    index: &index_metadata::Model,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    tracing::info!(
+        "Syncing index {} from {} to {} ({} days)",
+        index.index_id,
+        from,
+        to,
+        (to - from).num_days() + 1
+    );
 
-        tracing::info!(
-            "Processed {} days for index {}",
-            processed,
-            index.index_id
-        );
+    let leverage_factor = leverage::for_index(index);
+    let mut stored = 0;
+    let mut date = from;
+    while date <= to {
+        let stored_now = calculate_and_store_index_price(db, coingecko, index.index_id, date, leverage_factor)
+            .await
+            .map_err(|e| format!("{} on {} ({} earlier dates stored)", e, date, stored))?;
+        if stored_now {
+            stored += 1;
+        }
+        date += Duration::days(1);
     }
-
-    tracing::info!("Index daily prices sync complete");
-    Ok(())
+    Ok(stored)
 }
 
 /// Calculate index price for a specific date and store in daily_prices
+/// Returns Ok(true) if stored, Ok(false) if it already existed
 async fn calculate_and_store_index_price(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
     index_id: i32,
     target_date: NaiveDate,
-) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
+    leverage_factor: Option<Decimal>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
     // Check if price already exists for this date
     let existing = DailyPrices::find()
         .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
@@ -146,15 +227,11 @@ async fn calculate_and_store_index_price(
             target_date,
             existing_price.price
         );
-        return Ok(existing_price.price);
+        return Ok(false);
     }
 
     // Get the latest rebalance before or on target_date
-    let target_timestamp = target_date
-        .and_hms_opt(23, 59, 59)
-        .unwrap()
-        .and_utc()
-        .timestamp();
+    let target_timestamp = pricing_time::day_end_timestamp(target_date);
 
     let rebalance = Rebalances::find()
         .filter(rebalances::Column::IndexId.eq(index_id))
@@ -169,75 +246,30 @@ async fn calculate_and_store_index_price(
         ))?;
 
     // Parse coins from rebalance
-    let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(rebalance.coins)?;
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins)?;
 
     if coins.is_empty() {
         return Err("Rebalance has no coins".into());
     }
 
-    // Calculate index price: sum of (weight * quantity * token_price)
-    let mut index_price = Decimal::ZERO;
-    let mut quantities_map: HashMap<String, f64> = HashMap::new();
-    let mut missing_prices = Vec::new();
-
-    for coin in &coins {
-        // Use self-healing price fetcher
-        let token_price_result = get_or_fetch_coins_historical_price(
-            db,
-            coingecko,
-            &coin.coin_id,
-            &coin.symbol,
-            target_date,
-        )
-        .await;
-
-        match token_price_result {
-            Ok(price) => {
-                let weight: Decimal = coin.weight.parse()?;
-                let quantity: Decimal = coin.quantity.parse()?;
-                let price_decimal = Decimal::from_f64_retain(price)
-                    .ok_or("Invalid price conversion")?;
-
-                // index_price += weight * quantity * price
-                index_price += weight * quantity * price_decimal;
-
-                // Store quantity for the quantities field
-                quantities_map.insert(coin.coin_id.clone(), quantity.to_string().parse()?);
-
-                tracing::debug!(
-                    "  {} ({}): weight={}, qty={}, price={}, contribution={}",
-                    coin.symbol,
-                    coin.coin_id,
-                    weight,
-                    quantity,
-                    price,
-                    weight * quantity * price_decimal
-                );
-            }
-            Err(e) => {
-                missing_prices.push(coin.coin_id.clone());
-                tracing::warn!(
-                    "Failed to get price for {} ({}) on {}: {}",
-                    coin.symbol,
-                    coin.coin_id,
-                    target_date,
-                    e
-                );
-            }
-        }
-    }
+    // Staking yield accrued since the rebalance (indexes with accrue_yield only)
+    let rebalance_date = pricing_time::rebalance_date(rebalance.timestamp).ok_or("Invalid rebalance timestamp")?;
+    let coin_ids: Vec<String> = coins.iter().map(|coin| coin.coin_id.clone()).collect();
+    let accrual = YieldAccrual::for_index(db, index_id, &coin_ids, rebalance_date).await?;
 
-    if !missing_prices.is_empty() {
-        return Err(format!(
-            "Missing prices for {} tokens: {}",
-            missing_prices.len(),
-            missing_prices.join(", ")
-        )
-        .into());
+    // Index price: sum of (weight * quantity * token_price), with fallbacks
+    // for missing prices (see services::price_fallback)
+    let basket = basket_value(db, coingecko, &coins, &accrual, target_date).await?;
+    let mut index_price = basket.value;
+
+    // Leveraged indexes carry their NAV from the previous day's (see services::leverage)
+    if let Some(factor) = leverage_factor {
+        let basket_before = basket_value(db, coingecko, &coins, &accrual, target_date - Duration::days(1)).await?;
+        index_price = leverage::nav_on(db, index_id, target_date, factor, basket_before.value, index_price).await?;
     }
 
     // Store in daily_prices
-    let quantities_json = serde_json::to_value(&quantities_map)?;
+    let quantities_json = serde_json::to_value(&basket.quantities)?;
 
     let new_price = daily_prices::ActiveModel {
         index_id: Set(index_id.to_string()),
@@ -248,7 +280,18 @@ async fn calculate_and_store_index_price(
         updated_at: Set(Some(Utc::now().naive_utc())),
     };
 
-    new_price.insert(db).await?;
+    // Another writer (e.g. a backfill) may have priced the date meanwhile
+    let inserted = DailyPrices::insert(new_price)
+        .on_conflict(
+            OnConflict::columns([daily_prices::Column::IndexId, daily_prices::Column::Date])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    if inserted == 0 {
+        return Ok(false);
+    }
 
     tracing::info!(
         "Calculated index price for index {} on {}: {} (from {} tokens)",
@@ -258,7 +301,7 @@ async fn calculate_and_store_index_price(
         coins.len()
     );
 
-    Ok(index_price)
+    Ok(true)
 }
 
 #[cfg(test)]
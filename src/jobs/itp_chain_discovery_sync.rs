@@ -23,6 +23,8 @@ use tracing::{debug, error, info, warn};
 use asset_registry::AssetRegistry;
 use crate::entities::{itps, prelude::Itps};
 use crate::services::itp_chain_discovery::{DiscoveredItp, ItpChainDiscoveryService};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::jobs;
 
 // Castle interface for voting and quote updates
 sol! {
@@ -255,7 +257,11 @@ async fn run_discovery_cycle(
     db: &DatabaseConnection,
     service: &ItpChainDiscoveryService,
     orbit_voter: Option<&OrbitVoter>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::ITP_CHAIN_DISCOVERY).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     info!("Starting ITP chain discovery cycle");
 
     let discovered = service.discover_all_itps().await.map_err(|e| {
@@ -264,7 +270,7 @@ async fn run_discovery_cycle(
 
     if discovered.is_empty() {
         info!("No ITPs found on-chain");
-        return Ok(());
+        return Ok(JobOutcome::Ran(()));
     }
 
     let mut inserted = 0;
@@ -355,7 +361,7 @@ async fn run_discovery_cycle(
         "ITP chain discovery cycle complete"
     );
 
-    Ok(())
+    Ok(JobOutcome::Ran(()))
 }
 
 /// Insert a newly discovered ITP into the database
@@ -0,0 +1,84 @@
+//! ITP drift monitor job
+//!
+//! Every few hours, records how far each active ITP's weights have drifted
+//! from its targets (see `services::itp_drift`) and logs the ITPs whose drift
+//! passed the threshold as due for a rebalance.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::itp_drift::{self, DriftConfig};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_itp_drift_monitor_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let config = DriftConfig::from_env();
+        tracing::info!(threshold_bps = config.threshold_bps, "ITP drift monitor configured");
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::ITP_DRIFT_MONITOR, intervals::ITP_DRIFT_MONITOR).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping ITP drift monitor (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_drift_monitor(&db, &config).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) =
+                        sync_status::record_success(&db, jobs::ITP_DRIFT_MONITOR, intervals::ITP_DRIFT_MONITOR).await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("ITP drift monitor failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::ITP_DRIFT_MONITOR,
+                        &e.to_string(),
+                        intervals::ITP_DRIFT_MONITOR,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_drift_monitor(
+    db: &DatabaseConnection,
+    config: &DriftConfig,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::ITP_DRIFT_MONITOR).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let summary = itp_drift::run(db, config, Utc::now().date_naive()).await?;
+
+    tracing::info!(
+        itps = summary.itps,
+        checked = summary.checked,
+        incomplete = summary.incomplete,
+        flagged = summary.flagged,
+        max_drift_bps = summary.max_drift_bps,
+        "ITP drift monitor complete"
+    );
+
+    Ok(JobOutcome::Ran(()))
+}
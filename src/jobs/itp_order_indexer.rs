@@ -0,0 +1,47 @@
+//! ITP order indexer job
+//!
+//! Every minute, moves open ITP orders to filled or settled once their
+//! on-chain events have been ingested into blockchain_events (see
+//! `services::itp_orders`).
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::itp_orders;
+use crate::services::locking;
+use crate::services::sync_status::jobs;
+
+/// How often open orders are matched against new events
+const POLL_INTERVAL_SECS: u64 = 60;
+
+pub async fn start_itp_order_indexer_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(POLL_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let _lock = match locking::try_acquire_job(&db, jobs::ITP_ORDER_INDEXER).await {
+                Ok(Some(lock)) => lock,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to acquire ITP order indexer lock: {}", e);
+                    continue;
+                }
+            };
+
+            match itp_orders::index_orders(&db).await {
+                Ok(summary) if summary.filled > 0 || summary.settled > 0 => {
+                    tracing::info!(
+                        open = summary.open,
+                        filled = summary.filled,
+                        settled = summary.settled,
+                        "ITP order indexer advanced orders"
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("ITP order indexer failed: {}", e),
+            }
+        }
+    });
+}
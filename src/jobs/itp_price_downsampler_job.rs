@@ -9,6 +9,8 @@ use tokio::time::{interval, Duration as TokioDuration};
 use tracing::{error, info};
 
 use crate::services::itp_price_downsampler::ItpPriceDownsampler;
+use crate::services::locking;
+use crate::services::sync_status::jobs;
 
 /// Default downsampling interval in seconds (24 hours)
 const DEFAULT_DOWNSAMPLE_INTERVAL_SECS: u64 = 86400;
@@ -52,7 +54,7 @@ pub async fn start_itp_price_downsampler_job(db: DatabaseConnection) {
             "Initializing ITP price downsampler job"
         );
 
-        let downsampler = ItpPriceDownsampler::new(db);
+        let downsampler = ItpPriceDownsampler::new(db.clone());
 
         info!("ITP price downsampler job started successfully");
 
@@ -75,6 +77,15 @@ pub async fn start_itp_price_downsampler_job(db: DatabaseConnection) {
                         continue;
                     }
 
+                    let _lock = match locking::try_acquire_job(&db, jobs::ITP_PRICE_DOWNSAMPLER).await {
+                        Ok(Some(lock)) => lock,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            error!(error = %e, "Failed to acquire ITP price downsampler lock");
+                            continue;
+                        }
+                    };
+
                     match downsampler.run_downsampling().await {
                         Ok(stats) => {
                             info!(
@@ -10,6 +10,8 @@ use tokio::time::{interval, Duration as TokioDuration};
 use tracing::{error, info, warn};
 
 use crate::services::itp_price_snapshot::ItpPriceSnapshotService;
+use crate::services::locking;
+use crate::services::sync_status::jobs;
 
 /// Default snapshot interval in seconds (5 minutes)
 const DEFAULT_SNAPSHOT_INTERVAL_SECS: u64 = 300;
@@ -116,6 +118,15 @@ pub async fn start_itp_price_snapshot_job(db: DatabaseConnection) {
                         continue;
                     }
 
+                    let _lock = match locking::try_acquire_job(&db, jobs::ITP_PRICE_SNAPSHOT).await {
+                        Ok(Some(lock)) => lock,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            error!(error = %e, "Failed to acquire ITP price snapshot lock");
+                            continue;
+                        }
+                    };
+
                     match snapshot_service.snapshot_all_itp_prices().await {
                         Ok(count) => {
                             info!(count = count, "ITP price snapshot completed");
@@ -0,0 +1,126 @@
+//! Retry worker for the job_failures dead-letter queue
+//!
+//! Every few minutes, picks pending failures whose retry time has come and
+//! replays them through the job that produced them. Successes are marked
+//! resolved; failures are rescheduled with backoff until exhausted.
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::entities::job_failures;
+use crate::jobs::{coins_historical_prices_sync, rebalance_sync};
+use crate::services::coingecko::CoinGeckoService;
+use crate::services::exchange_api::ExchangeApiService;
+use crate::services::job_failures as dlq;
+use crate::services::locking;
+use crate::services::rebalancing::RebalancingService;
+use crate::services::sync_status::jobs;
+
+/// How often the worker looks for due items
+const POLL_INTERVAL_SECS: u64 = 300; // 5 minutes
+
+/// Max items replayed per poll (keeps CoinGecko usage bounded)
+const BATCH_SIZE: u64 = 50;
+
+pub async fn start_job_failures_retry_job(
+    db: DatabaseConnection,
+    coingecko: CoinGeckoService,
+    exchange_api: ExchangeApiService,
+) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(POLL_INTERVAL_SECS));
+
+        let rebalancing_service = RebalancingService::new(
+            db.clone(),
+            coingecko.clone(),
+            Some(exchange_api),
+        );
+
+        loop {
+            interval.tick().await;
+
+            let _lock = match locking::try_acquire_job(&db, jobs::JOB_FAILURES_RETRY).await {
+                Ok(Some(lock)) => lock,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to acquire job failures retry lock: {}", e);
+                    continue;
+                }
+            };
+
+            let due = match dlq::due_failures(&db, BATCH_SIZE).await {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::warn!("Failed to load due job failures: {}", e);
+                    continue;
+                }
+            };
+
+            if due.is_empty() {
+                continue;
+            }
+
+            tracing::info!("Retrying {} failed job items", due.len());
+
+            for record in due {
+                let result = replay(&db, &coingecko, &rebalancing_service, &record).await;
+                let (job_name, entity_id) = (record.job_name.clone(), record.entity_id.clone());
+
+                let update = match result {
+                    Ok(()) => {
+                        tracing::info!("[{}] Retry succeeded for {}", job_name, entity_id);
+                        dlq::mark_resolved(&db, record).await
+                    }
+                    Err(e) => {
+                        tracing::warn!("[{}] Retry failed for {}: {}", job_name, entity_id, e);
+                        dlq::mark_retry_failed(&db, record, &e.to_string()).await.map(|updated| {
+                            if updated.status == dlq::statuses::EXHAUSTED {
+                                tracing::error!(
+                                    "[{}] Giving up on {} after {} attempts",
+                                    job_name,
+                                    entity_id,
+                                    updated.attempt_count
+                                );
+                            }
+                        })
+                    }
+                };
+
+                if let Err(e) = update {
+                    tracing::warn!("Failed to update job failure for {}: {}", entity_id, e);
+                }
+
+                // Rate limiting between replays
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        }
+    });
+}
+
+/// Dispatch a failed item back to the job that owns it
+async fn replay(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    rebalancing_service: &RebalancingService,
+    record: &job_failures::Model,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match record.job_name.as_str() {
+        jobs::COINS_HISTORICAL_PRICES => {
+            let symbol = record
+                .payload
+                .as_ref()
+                .and_then(|p| p.get("symbol"))
+                .and_then(|s| s.as_str())
+                .unwrap_or(&record.entity_id)
+                .to_string();
+            coins_historical_prices_sync::retry_coin(db, coingecko, &record.entity_id, &symbol)
+                .await
+                .map(|_| ())
+        }
+        jobs::REBALANCE_SYNC => {
+            let index_id: i32 = record.entity_id.parse()?;
+            rebalance_sync::retry_index(db, rebalancing_service, index_id, record.payload.as_ref()).await
+        }
+        other => Err(format!("No retry handler for job '{}'", other).into()),
+    }
+}
@@ -12,7 +12,9 @@ use tokio::time::{interval, Duration as TokioDuration};
 
 use alloy::primitives::Address;
 use crate::entities::keeper_claimable_data;
+use crate::services::locking;
 use crate::services::orbit_keeper::{OrbitKeeperService, KeeperClaimableResult};
+use crate::services::sync_status::jobs;
 
 /// Default polling interval in seconds (30 seconds for detailed charts)
 const DEFAULT_POLL_INTERVAL_SECS: u64 = 30;
@@ -130,6 +132,15 @@ pub async fn start_keeper_chart_sync_job(db: DatabaseConnection) {
         loop {
             interval.tick().await;
 
+            let _lock = match locking::try_acquire_job(&db, jobs::KEEPER_CHART_SYNC).await {
+                Ok(Some(lock)) => lock,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to acquire keeper chart sync lock");
+                    continue;
+                }
+            };
+
             // Discover all vaults on each tick (handles newly created ITPs)
             let vaults = match orbit_service.discover_vaults().await {
                 Ok(v) => v,
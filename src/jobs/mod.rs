@@ -1,6 +1,7 @@
 pub mod category_sync;
 pub mod rebalance_sync;
 pub mod category_membership_sync;
+pub mod category_membership_consistency;
 pub mod announcement_scraper;
 pub mod index_daily_prices_sync;
 pub mod all_coingecko_coins_sync;
@@ -10,4 +11,22 @@ pub mod keeper_chart_sync;
 pub mod itp_price_snapshot_sync;
 pub mod itp_price_downsampler_job;
 pub mod bitget_historical_prices_sync;
-pub mod itp_chain_discovery_sync;
\ No newline at end of file
+pub mod itp_chain_discovery_sync;
+pub mod job_failures_retry;
+pub mod background_tasks_worker;
+pub mod supply_reconciliation;
+pub mod coins_price_retention;
+pub mod coins_price_partitions;
+pub mod price_reconciliation;
+pub mod itp_drift_monitor;
+pub mod itp_order_indexer;
+pub mod tvl_snapshot;
+pub mod rebalance_deployer;
+pub mod wallet_balance_monitor;
+pub mod yield_rates_sync;
+pub mod rolling_stats;
+pub mod snapshot_log;
+pub mod snapshot_anchor;
+pub mod report_generation;
+pub mod spread_sampling;
+pub mod pair_volume_sampling;
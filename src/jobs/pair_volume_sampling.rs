@@ -0,0 +1,83 @@
+//! Pair volume sampling job
+//!
+//! Once a day, records the 24h volume of every USDC/USDT pair on the
+//! supported exchanges (see `services::pair_volumes`), the figures the
+//! constituent volume screen and execution quotes read.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::exchange_api::ExchangeApiService;
+use crate::services::job_runs;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::pair_volumes;
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_pair_volume_sampling_job(db: DatabaseConnection, exchange_api: ExchangeApiService) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::PAIR_VOLUME_SAMPLING, intervals::PAIR_VOLUME_SAMPLING).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping pair volume sampling (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_sampling(&db, &exchange_api).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(
+                        &db,
+                        jobs::PAIR_VOLUME_SAMPLING,
+                        intervals::PAIR_VOLUME_SAMPLING,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Pair volume sampling failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::PAIR_VOLUME_SAMPLING,
+                        &e.to_string(),
+                        intervals::PAIR_VOLUME_SAMPLING,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_sampling(
+    db: &DatabaseConnection,
+    exchange_api: &ExchangeApiService,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::PAIR_VOLUME_SAMPLING).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let recorded = job_runs::track(
+        db,
+        jobs::PAIR_VOLUME_SAMPLING,
+        pair_volumes::sample_all(db, exchange_api, Utc::now().naive_utc()),
+    )
+    .await?;
+    tracing::info!(recorded, "Pair volume sampling complete");
+    Ok(JobOutcome::Ran(()))
+}
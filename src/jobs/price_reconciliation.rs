@@ -0,0 +1,127 @@
+//! Price reconciliation job
+//!
+//! Daily, checks a sample of yesterday's stored coin prices against Binance
+//! klines (see `services::price_reconciliation`), records the comparisons and
+//! alerts on deviations above the threshold, then alerts on cash buffer
+//! stablecoins off their peg (see `services::cash_buffer::depegged`).
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+use crate::services::cash_buffer;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::price_reconciliation::{self, BinanceKlines, ReconciliationConfig};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_price_reconciliation_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let config = ReconciliationConfig::from_env();
+        let source = BinanceKlines::new(&config.binance_api_url);
+        tracing::info!(
+            sample_size = config.sample_size,
+            threshold_bps = config.threshold_bps,
+            "Price reconciliation configured"
+        );
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::PRICE_RECONCILIATION, intervals::PRICE_RECONCILIATION).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping price reconciliation (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_reconciliation(&db, &source, &config).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(
+                        &db,
+                        jobs::PRICE_RECONCILIATION,
+                        intervals::PRICE_RECONCILIATION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Price reconciliation failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::PRICE_RECONCILIATION,
+                        &e.to_string(),
+                        intervals::PRICE_RECONCILIATION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_reconciliation(
+    db: &DatabaseConnection,
+    source: &BinanceKlines,
+    config: &ReconciliationConfig,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::PRICE_RECONCILIATION).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    // Yesterday's row is the latest that should hold a 00:00 UTC price
+    let date = Utc::now().date_naive() - chrono::Duration::days(1);
+    let summary = price_reconciliation::run(db, source, config, date).await?;
+
+    tracing::info!(
+        date = %date,
+        sampled = summary.sampled,
+        checked = summary.checked,
+        unavailable = summary.unavailable,
+        errors = summary.errors,
+        flagged = summary.flagged,
+        max_deviation_bps = summary.max_deviation_bps,
+        "Price reconciliation complete"
+    );
+    if summary.flagged > 0 {
+        alerting::notify(Alert::new(
+            AlertKind::DataQuality,
+            Severity::Warning,
+            format!("{} stored prices for {} deviate from Binance", summary.flagged, date),
+            format!(
+                "Max deviation {} bps (threshold {} bps), see GET /admin/price-reconciliation",
+                summary.max_deviation_bps, config.threshold_bps
+            ),
+        ));
+    }
+
+    for (symbol, price) in cash_buffer::depegged(db, date).await? {
+        alerting::notify(Alert::new(
+            AlertKind::Depeg,
+            Severity::Critical,
+            format!("{} is off its peg", symbol),
+            format!(
+                "{} closed {} at {}, but cash buffers value it at {}",
+                symbol,
+                date,
+                price,
+                cash_buffer::PRICE
+            ),
+        ));
+    }
+
+    Ok(JobOutcome::Ran(()))
+}
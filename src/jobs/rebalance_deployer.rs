@@ -0,0 +1,174 @@
+//! Rebalance deployer job
+//!
+//! Submits the latest undeployed rebalance of every index backing an active
+//! ITP as an on-chain weight update through the BridgeProxy, stores its tx
+//! hash as soon as it's sent, then marks the rebalance deployed with its
+//! block once mined (see `services::rebalance_deployment`). A rebalance
+//! submitted by an earlier run is reconciled by its receipt and only resent
+//! if that transaction reverted or was dropped. Rebalances without an
+//! approved, unexpired approval (see `services::rebalance_approvals`) are
+//! not submitted.
+//!
+//! Sends transactions, so it only runs with REBALANCE_DEPLOYER_ENABLED=true.
+
+use std::env;
+use std::sync::Arc;
+
+use asset_registry::AssetRegistry;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+use crate::services::itp_creation::{ItpCreationService, RebalanceTxStatus};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::rebalance_approvals;
+use crate::services::rebalance_deployment::{self, PendingDeployment};
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::services::signers;
+use crate::services::sync_status::jobs;
+
+/// Seconds between checks for undeployed rebalances
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+
+const ENV_ENABLED: &str = "REBALANCE_DEPLOYER_ENABLED";
+const ENV_INTERVAL: &str = "REBALANCE_DEPLOYER_INTERVAL_SECS";
+
+pub async fn start_rebalance_deployer_job(db: DatabaseConnection, asset_registry: Arc<AssetRegistry>) {
+    tokio::spawn(async move {
+        if !env::var(ENV_ENABLED).is_ok_and(|v| v == "true") {
+            info!("REBALANCE_DEPLOYER_ENABLED not set - rebalance deployer disabled");
+            return;
+        }
+
+        let Ok(rpc_url) = env::var("ARB_RPC_URL") else {
+            warn!("ARB_RPC_URL not set - rebalance deployer disabled");
+            return;
+        };
+        let wallet = match signers::wallet_from_env().await {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                warn!(error = %e, "No transaction signer - rebalance deployer disabled");
+                return;
+            }
+        };
+        let Ok(bridge_proxy) = env::var("BRIDGE_PROXY_ADDRESS") else {
+            warn!("BRIDGE_PROXY_ADDRESS not set - rebalance deployer disabled");
+            return;
+        };
+
+        let service = match ItpCreationService::with_wallet(&rpc_url, wallet, &bridge_proxy).await {
+            Ok(service) => service.with_spend_recording(db.clone()),
+            Err(e) => {
+                error!(error = %e, "Failed to initialize rebalance deployer");
+                return;
+            }
+        };
+
+        let interval_secs = env::var(ENV_INTERVAL)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+        info!(interval_secs, "Rebalance deployer started");
+
+        let mut interval = interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = run_deployments(&db, &service, &asset_registry).await {
+                error!("Rebalance deployment failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn run_deployments(
+    db: &DatabaseConnection,
+    service: &ItpCreationService,
+    registry: &AssetRegistry,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::REBALANCE_DEPLOYER).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    for pending in rebalance_deployment::pending(db).await? {
+        let rebalance_id = pending.rebalance.id;
+        let index_id = pending.rebalance.index_id;
+        if pending.rebalance.tx_hash.is_none()
+            && !rebalance_approvals::is_approved(db, rebalance_id, chrono::Utc::now().naive_utc()).await?
+        {
+            debug!(rebalance_id, index_id, "Rebalance awaiting approval");
+            continue;
+        }
+        // One failing index shouldn't hold back the others; it's retried next run
+        if let Err(e) = deploy(db, service, registry, &pending).await {
+            error!(rebalance_id, index_id, error = %e, "Failed to deploy rebalance");
+            alerting::notify(Alert::new(
+                AlertKind::JobFailure,
+                Severity::Critical,
+                format!("Rebalance {} of index {} failed to deploy", rebalance_id, index_id),
+                e.to_string(),
+            ));
+        }
+    }
+    Ok(JobOutcome::Ran(()))
+}
+
+async fn deploy(
+    db: &DatabaseConnection,
+    service: &ItpCreationService,
+    registry: &AssetRegistry,
+    pending: &PendingDeployment,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let rebalance_id = pending.rebalance.id;
+    let index_id = pending.rebalance.index_id;
+    let orbit_itp = &pending.itp.orbit_address;
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(pending.rebalance.coins.clone())?;
+
+    let status = match &pending.rebalance.tx_hash {
+        // Sent by an earlier run: its receipt decides, it's never sent twice
+        Some(tx_hash) => service.rebalance_status(tx_hash, orbit_itp).await?,
+        None => {
+            let weights = rebalance_deployment::onchain_weights(&coins, registry)?;
+            let tx_hash = service.submit_rebalance(orbit_itp, weights.asset_ids, weights.weights_bps).await?;
+            rebalance_deployment::mark_submitted(db, rebalance_id, &tx_hash).await?;
+            service.wait_for_rebalance(&tx_hash, orbit_itp).await?
+        }
+    };
+
+    let result = match status {
+        RebalanceTxStatus::Confirmed(result) => result,
+        RebalanceTxStatus::Pending => {
+            info!(rebalance_id, index_id, tx_hash = ?pending.rebalance.tx_hash, "Rebalance transaction still pending");
+            return Ok(());
+        }
+        RebalanceTxStatus::Reverted | RebalanceTxStatus::Dropped => {
+            rebalance_deployment::clear_submission(db, rebalance_id).await?;
+            let outcome = if matches!(status, RebalanceTxStatus::Reverted) { "reverted" } else { "was dropped" };
+            return Err(format!("Rebalance transaction {}, it will be sent again next run", outcome).into());
+        }
+    };
+    rebalance_deployment::mark_deployed(db, rebalance_id, &result.tx_hash, result.block_number).await?;
+
+    info!(
+        rebalance_id,
+        index_id,
+        tx_hash = %result.tx_hash,
+        block_number = result.block_number,
+        "Rebalance deployed on-chain"
+    );
+    alerting::notify(Alert::new(
+        AlertKind::RebalanceDeployed,
+        Severity::Info,
+        format!("Rebalance {} of index {} deployed on-chain", rebalance_id, index_id),
+        format!(
+            "{} constituents on {}, tx {} (block {})",
+            coins.len(),
+            orbit_itp,
+            result.tx_hash,
+            result.block_number
+        ),
+    ));
+    Ok(())
+}
@@ -3,9 +3,13 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrai
 use tokio::time::{interval, Duration};
 
 use crate::entities::{rebalances, prelude::*};
+use crate::services::cash_buffer;
 use crate::services::coingecko::CoinGeckoService;
 use crate::services::exchange_api::ExchangeApiService;
+use crate::services::job_failures;
+use crate::services::locking::{self, JobOutcome};
 use crate::services::rebalancing::{RebalancingService, RebalanceReason};
+use crate::services::sync_status::jobs;
 
 pub async fn start_rebalance_sync_job(
     db: DatabaseConnection,
@@ -35,7 +39,11 @@ pub async fn start_rebalance_sync_job(
 async fn check_and_rebalance(
     db: &DatabaseConnection,
     rebalancing_service: &RebalancingService,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::REBALANCE_SYNC).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
     // Get all indexes
     let indexes = IndexMetadata::find().all(db).await?;
 
@@ -67,6 +75,11 @@ async fn check_and_rebalance(
             }
         };
 
+        // Another instance may be working on this index (e.g. a create_index backfill)
+        let Some(_index_lock) = locking::try_acquire_index(db, index.index_id).await? else {
+            continue;
+        };
+
         // Calculate expected number of rebalances from initial_date to today
         let today = Utc::now().date_naive();
         let expected_rebalances = calculate_expected_rebalances(
@@ -100,6 +113,8 @@ async fn check_and_rebalance(
                 }
                 Err(e) => {
                     tracing::error!("Failed to backfill index {}: {}", index.index_id, e);
+                    let payload = serde_json::json!({ "action": "backfill" });
+                    record_index_failure(db, index.index_id, payload, &e.to_string()).await;
                 }
             }
             
@@ -153,7 +168,8 @@ async fn check_and_rebalance(
             };
 
             let current_date = Utc::now().date_naive();
-            
+            let reason_str = reason.as_str().to_string();
+
             match rebalancing_service
                 .perform_rebalance_for_date(index.index_id, current_date, reason)
                 .await
@@ -162,6 +178,12 @@ async fn check_and_rebalance(
                 Err(e) => {
                     tracing::error!("Failed to rebalance index {}: {}", index.index_id, e);
                     tracing::error!("Skipping this rebalance cycle due to error. Will retry later.");
+                    let payload = serde_json::json!({
+                        "action": "rebalance",
+                        "date": current_date.to_string(),
+                        "reason": reason_str,
+                    });
+                    record_index_failure(db, index.index_id, payload, &e.to_string()).await;
                 }
             }
 
@@ -178,7 +200,66 @@ async fn check_and_rebalance(
         }
     }
 
-    Ok(())
+    Ok(JobOutcome::Ran(()))
+}
+
+/// Hand a failed index to the dead-letter queue for retry with backoff
+async fn record_index_failure(
+    db: &DatabaseConnection,
+    index_id: i32,
+    payload: serde_json::Value,
+    error: &str,
+) {
+    if let Err(e) = job_failures::record_failure(
+        db,
+        jobs::REBALANCE_SYNC,
+        job_failures::entity_types::INDEX,
+        &index_id.to_string(),
+        Some(payload),
+        error,
+    )
+    .await
+    {
+        tracing::warn!("Failed to record job failure for index {}: {}", index_id, e);
+    }
+}
+
+/// Replay a failed index item from the dead-letter queue
+///
+/// The payload is the one written by `record_index_failure`. Fails (and is
+/// rescheduled) if another instance currently holds the index lock.
+pub async fn retry_index(
+    db: &DatabaseConnection,
+    rebalancing_service: &RebalancingService,
+    index_id: i32,
+    payload: Option<&serde_json::Value>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_index_lock) = locking::try_acquire_index(db, index_id).await? else {
+        return Err(format!("Index {} is locked by another instance", index_id).into());
+    };
+
+    let action = payload
+        .and_then(|p| p.get("action"))
+        .and_then(|a| a.as_str())
+        .unwrap_or("backfill");
+
+    match action {
+        "rebalance" => {
+            let date = payload
+                .and_then(|p| p.get("date"))
+                .and_then(|d| d.as_str())
+                .ok_or("Rebalance retry payload has no date")?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+            let reason = match payload.and_then(|p| p.get("reason")).and_then(|r| r.as_str()) {
+                Some("delisting") => RebalanceReason::Delisting("constituent_delisted".to_string()),
+                _ => RebalanceReason::Periodic,
+            };
+            rebalancing_service
+                .perform_rebalance_for_date(index_id, date, reason)
+                .await
+        }
+        _ => rebalancing_service.backfill_historical_rebalances(index_id).await,
+    }
 }
 
 /// Check if any constituent from last rebalance is delisted (not tradeable anymore)
@@ -200,7 +281,7 @@ async fn check_for_delistings(
 
     // Parse constituents from last rebalance
     let constituents: Vec<crate::services::rebalancing::CoinRebalanceInfo> =
-        serde_json::from_value(last_rebalance.coins.clone())?;
+        crate::services::rebalance_schema::decode(last_rebalance.coins.clone())?;
 
     if constituents.is_empty() {
         return Ok(false);
@@ -212,8 +293,8 @@ async fn check_for_delistings(
         index_id
     );
 
-    // Check each constituent
-    for constituent in &constituents {
+    // Check each constituent; the cash buffer isn't traded on an exchange
+    for constituent in constituents.iter().filter(|c| !cash_buffer::is_cash(&c.coin_id)) {
         // Priority order: Binance USDC > USDT > Bitget USDC > USDT
         let exchanges_to_check = [
             ("binance", "usdc"),
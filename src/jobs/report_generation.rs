@@ -0,0 +1,85 @@
+//! Report generation job
+//!
+//! Every 6 hours, renders the reports of each template's last complete
+//! period that aren't stored yet (see `services::reports`) and, when email
+//! is configured, sends the ones not delivered yet to the subscribers. Only
+//! the latest period is delivered, so turning email on doesn't send a
+//! backlog of old reports.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::email::Mailer;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::reports::{self, ReportTemplate};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_report_generation_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mailer = Mailer::from_env();
+        if mailer.is_none() {
+            tracing::info!("EMAIL_API_URL or EMAIL_FROM not set - reports are stored but not emailed");
+        }
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::REPORT_GENERATION, intervals::REPORT_GENERATION).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping report generation (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_reports(&db, mailer.as_ref()).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) =
+                        sync_status::record_success(&db, jobs::REPORT_GENERATION, intervals::REPORT_GENERATION).await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Report generation failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::REPORT_GENERATION,
+                        &e.to_string(),
+                        intervals::REPORT_GENERATION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_reports(
+    db: &DatabaseConnection,
+    mailer: Option<&Mailer>,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::REPORT_GENERATION).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let now = Utc::now().naive_utc();
+    for template in ReportTemplate::ALL {
+        let stored = reports::generate(db, template, now.date()).await?;
+        if let Some(mailer) = mailer {
+            reports::deliver(db, mailer, &stored, now).await?;
+        }
+    }
+    Ok(JobOutcome::Ran(()))
+}
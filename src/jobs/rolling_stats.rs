@@ -0,0 +1,68 @@
+//! Rolling stats job
+//!
+//! Once a day, stores the 30/90/365-day return stats of every index and
+//! constituent coin up to yesterday, the latest complete day, filling in any
+//! days missed in the past week (see `services::rolling_stats`).
+
+use chrono::{Duration as ChronoDuration, Utc};
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::rolling_stats;
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_rolling_stats_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::ROLLING_STATS, intervals::ROLLING_STATS).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping rolling stats (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_stats(&db).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(&db, jobs::ROLLING_STATS, intervals::ROLLING_STATS).await {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Rolling stats failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::ROLLING_STATS, &e.to_string(), intervals::ROLLING_STATS).await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_stats(db: &DatabaseConnection) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::ROLLING_STATS).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let now = Utc::now().naive_utc();
+    let summary = rolling_stats::run(db, now.date() - ChronoDuration::days(1), now).await?;
+    tracing::info!(
+        subjects = summary.subjects,
+        stored = summary.stored,
+        skipped = summary.skipped,
+        "Rolling stats complete"
+    );
+    Ok(JobOutcome::Ran(()))
+}
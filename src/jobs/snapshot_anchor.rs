@@ -0,0 +1,133 @@
+//! Snapshot anchor job
+//!
+//! Once a day, posts the snapshot chain head of every index that changed
+//! since its last anchor to the snapshot registry contract and records the
+//! transaction on the entry (see `services::snapshot_anchor`). Anchors sent
+//! by an earlier run but not confirmed are reconciled by their receipt
+//! first, and only resent if they reverted or were dropped.
+//!
+//! Sends transactions, so it only runs with SNAPSHOT_ANCHOR_ENABLED=true.
+
+use std::env;
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::signers;
+use crate::services::snapshot_anchor::{self, AnchorTxStatus, SnapshotAnchorService, ENV_ENABLED, ENV_REGISTRY_ADDRESS};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_snapshot_anchor_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        if !env::var(ENV_ENABLED).is_ok_and(|v| v == "true") {
+            info!("{} not set - snapshot anchoring disabled", ENV_ENABLED);
+            return;
+        }
+
+        let Ok(rpc_url) = env::var("ARB_RPC_URL") else {
+            warn!("ARB_RPC_URL not set - snapshot anchoring disabled");
+            return;
+        };
+        let Ok(registry) = env::var(ENV_REGISTRY_ADDRESS) else {
+            warn!("{} not set - snapshot anchoring disabled", ENV_REGISTRY_ADDRESS);
+            return;
+        };
+        let wallet = match signers::wallet_from_env().await {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                warn!(error = %e, "No transaction signer - snapshot anchoring disabled");
+                return;
+            }
+        };
+        let service = match SnapshotAnchorService::new(&rpc_url, wallet, &registry, db.clone()).await {
+            Ok(service) => service,
+            Err(e) => {
+                error!(error = %e, "Failed to initialize snapshot anchoring");
+                return;
+            }
+        };
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::SNAPSHOT_ANCHOR, intervals::SNAPSHOT_ANCHOR).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping snapshot anchoring (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_anchors(&db, &service).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(&db, jobs::SNAPSHOT_ANCHOR, intervals::SNAPSHOT_ANCHOR).await {
+                        warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    error!("Snapshot anchoring failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::SNAPSHOT_ANCHOR, &e.to_string(), intervals::SNAPSHOT_ANCHOR).await
+                    {
+                        warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_anchors(
+    db: &DatabaseConnection,
+    service: &SnapshotAnchorService,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::SNAPSHOT_ANCHOR).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    for entry in snapshot_anchor::submitted_anchors(db).await? {
+        let (index_id, seq) = (entry.index_id, entry.seq);
+        let tx_hash = entry.anchor_tx_hash.clone().unwrap_or_default();
+        match service.reconcile(&entry, Utc::now().naive_utc()).await {
+            Ok(AnchorTxStatus::Pending) => info!(index_id, seq, tx_hash = %tx_hash, "Snapshot anchor still pending"),
+            Ok(AnchorTxStatus::Confirmed(block_number)) => {
+                info!(index_id, seq, tx_hash = %tx_hash, block_number, "Snapshot chain head anchored")
+            }
+            Ok(status) => warn!(index_id, seq, tx_hash = %tx_hash, ?status, "Snapshot anchor not mined, cleared"),
+            Err(e) => error!(index_id, seq, error = %e, "Failed to reconcile snapshot anchor"),
+        }
+    }
+
+    for head in snapshot_anchor::unanchored_heads(db).await? {
+        // One failing index shouldn't hold back the others; it's retried next run
+        match service.anchor(&head, Utc::now().naive_utc()).await {
+            Ok(anchored) => info!(
+                index_id = anchored.index_id,
+                seq = anchored.seq,
+                tx_hash = anchored.anchor_tx_hash.as_deref().unwrap_or_default(),
+                "Snapshot chain head anchored"
+            ),
+            Err(e) => {
+                error!(index_id = head.index_id, seq = head.seq, error = %e, "Failed to anchor snapshot chain head");
+                alerting::notify(Alert::new(
+                    AlertKind::JobFailure,
+                    Severity::Warning,
+                    format!("Snapshot anchor of index {} failed", head.index_id),
+                    e.to_string(),
+                ));
+            }
+        }
+    }
+    Ok(JobOutcome::Ran(()))
+}
@@ -0,0 +1,67 @@
+//! Snapshot log job
+//!
+//! Every hour, appends the rebalances and daily NAV records not logged yet
+//! to each index's hash chain (see `services::snapshot_log`). Only runs with
+//! SNAPSHOT_LOG_ENABLED=true.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::snapshot_log;
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_snapshot_log_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        if !snapshot_log::enabled_from_env() {
+            tracing::info!("{} not set - snapshot log disabled", snapshot_log::ENV_ENABLED);
+            return;
+        }
+
+        let mut interval = interval(Duration::from_secs(600)); // Check every 10 minutes
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::SNAPSHOT_LOG, intervals::SNAPSHOT_LOG).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping snapshot log (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_log(&db).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(&db, jobs::SNAPSHOT_LOG, intervals::SNAPSHOT_LOG).await {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Snapshot log failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::SNAPSHOT_LOG, &e.to_string(), intervals::SNAPSHOT_LOG).await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_log(db: &DatabaseConnection) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::SNAPSHOT_LOG).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let appended = snapshot_log::append_all(db, Utc::now().naive_utc()).await?;
+    tracing::info!(appended, "Snapshot log complete");
+    Ok(JobOutcome::Ran(()))
+}
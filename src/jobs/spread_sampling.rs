@@ -0,0 +1,75 @@
+//! Spread sampling job
+//!
+//! Once a day, records the top-of-book spread of every pair held by an
+//! index's latest rebalance (see `services::spread_calibration`), the
+//! observations indexes with use_observed_spread are charged from.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::exchange_api::ExchangeApiService;
+use crate::services::job_runs;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::spread_calibration;
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_spread_sampling_job(db: DatabaseConnection, exchange_api: ExchangeApiService) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::SPREAD_SAMPLING, intervals::SPREAD_SAMPLING).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping spread sampling (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_sampling(&db, &exchange_api).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) =
+                        sync_status::record_success(&db, jobs::SPREAD_SAMPLING, intervals::SPREAD_SAMPLING).await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Spread sampling failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::SPREAD_SAMPLING, &e.to_string(), intervals::SPREAD_SAMPLING)
+                            .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_sampling(
+    db: &DatabaseConnection,
+    exchange_api: &ExchangeApiService,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::SPREAD_SAMPLING).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let recorded = job_runs::track(
+        db,
+        jobs::SPREAD_SAMPLING,
+        spread_calibration::sample_all(db, exchange_api, Utc::now().naive_utc()),
+    )
+    .await?;
+    tracing::info!(recorded, "Spread sampling complete");
+    Ok(JobOutcome::Ran(()))
+}
@@ -0,0 +1,116 @@
+//! Supply reconciliation job
+//!
+//! Every few hours compares each index's event-derived supply with its
+//! on-chain totalSupply() and logs indexes that diverge, which usually means
+//! mint or burn events were never pushed to the backend.
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+use crate::services::locking::{self, JobOutcome};
+use crate::services::supply_reconciliation::{SupplyReconciliationService, ENV_BASE_RPC_URL};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_supply_reconciliation_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let service = match SupplyReconciliationService::from_env(db.clone()) {
+            Ok(service) => service,
+            Err(e) => {
+                tracing::warn!(
+                    "Supply reconciliation job disabled ({}). Set {} to enable.",
+                    e,
+                    ENV_BASE_RPC_URL
+                );
+                return;
+            }
+        };
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::SUPPLY_RECONCILIATION, intervals::SUPPLY_RECONCILIATION).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping supply reconciliation (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_reconciliation(&db, &service).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(
+                        &db,
+                        jobs::SUPPLY_RECONCILIATION,
+                        intervals::SUPPLY_RECONCILIATION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Supply reconciliation failed: {}", e);
+                    if let Err(e2) = sync_status::record_failure(
+                        &db,
+                        jobs::SUPPLY_RECONCILIATION,
+                        &e.to_string(),
+                        intervals::SUPPLY_RECONCILIATION,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_reconciliation(
+    db: &DatabaseConnection,
+    service: &SupplyReconciliationService,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::SUPPLY_RECONCILIATION).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let reports = service.reconcile_all().await?;
+    let flagged: Vec<_> = reports.iter().filter(|r| r.discrepancy).collect();
+
+    for report in &flagged {
+        tracing::warn!(
+            index_id = report.index_id,
+            address = %report.address,
+            event_supply = %report.event_supply,
+            onchain_supply = %report.onchain_supply,
+            difference = %report.difference,
+            difference_bps = ?report.difference_bps,
+            "Index supply does not match on-chain totalSupply"
+        );
+        alerting::notify(Alert::new(
+            AlertKind::DataQuality,
+            Severity::Critical,
+            format!("Index {} supply does not match on-chain totalSupply", report.index_id),
+            format!(
+                "Events: {}, on-chain: {} (difference {}) at {}",
+                report.event_supply, report.onchain_supply, report.difference, report.address
+            ),
+        ));
+    }
+
+    tracing::info!(
+        checked = reports.len(),
+        discrepancies = flagged.len(),
+        "Supply reconciliation complete"
+    );
+
+    Ok(JobOutcome::Ran(()))
+}
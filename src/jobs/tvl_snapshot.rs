@@ -0,0 +1,61 @@
+//! TVL snapshot job
+//!
+//! Every few hours records each index's daily TVL (net supply x NAV) in
+//! index_tvl (see `services::index_tvl`), backfilling days that have a price
+//! but no snapshot yet.
+
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::index_tvl;
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::{self, intervals, jobs};
+
+pub async fn start_tvl_snapshot_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::TVL_SNAPSHOT, intervals::TVL_SNAPSHOT).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping TVL snapshot (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_snapshot(&db).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(&db, jobs::TVL_SNAPSHOT, intervals::TVL_SNAPSHOT).await {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("TVL snapshot failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::TVL_SNAPSHOT, &e.to_string(), intervals::TVL_SNAPSHOT).await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_snapshot(db: &DatabaseConnection) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::TVL_SNAPSHOT).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let written = index_tvl::record_snapshots(db).await?;
+    tracing::info!(rows = written, "TVL snapshot complete");
+    Ok(JobOutcome::Ran(()))
+}
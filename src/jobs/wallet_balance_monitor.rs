@@ -0,0 +1,82 @@
+//! Wallet balance monitor job
+//!
+//! Every few minutes, records the signer wallet's ETH balance on each
+//! network with an RPC configured and logs the ones under their threshold
+//! (see `services::wallet_balances`), so the wallet is topped up before
+//! transactions start failing for lack of gas.
+
+use std::env;
+
+use alloy::primitives::Address;
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::signers;
+use crate::services::sync_status::jobs;
+use crate::services::wallet_balances::{self, MonitoredNetwork};
+
+/// Seconds between balance checks
+const DEFAULT_INTERVAL_SECS: u64 = 600;
+
+const ENV_INTERVAL: &str = "WALLET_BALANCE_MONITOR_INTERVAL_SECS";
+
+pub async fn start_wallet_balance_monitor_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let networks = wallet_balances::networks_from_env();
+        if networks.is_empty() {
+            info!("No RPC configured - wallet balance monitor disabled");
+            return;
+        }
+
+        let address = match signers::wallet_from_env().await {
+            Ok(wallet) => wallet.default_signer().address(),
+            Err(e) => {
+                warn!(error = %e, "No transaction signer - wallet balance monitor disabled");
+                return;
+            }
+        };
+
+        let interval_secs = env::var(ENV_INTERVAL)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|secs: &u64| *secs > 0)
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+        info!(
+            address = %address,
+            networks = ?networks.iter().map(|n| n.name).collect::<Vec<_>>(),
+            interval_secs,
+            "Wallet balance monitor started"
+        );
+
+        let mut interval = interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = run_balance_checks(&db, address, &networks).await {
+                error!("Wallet balance monitor failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn run_balance_checks(
+    db: &DatabaseConnection,
+    address: Address,
+    networks: &[MonitoredNetwork],
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::WALLET_BALANCE_MONITOR).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let summary = wallet_balances::run(db, address, networks, Utc::now().naive_utc()).await?;
+    info!(
+        checked = summary.checked,
+        low = summary.low,
+        failed = summary.failed,
+        "Wallet balance check complete"
+    );
+    Ok(JobOutcome::Ran(()))
+}
@@ -0,0 +1,78 @@
+//! Yield rates sync job
+//!
+//! Once a day, refreshes coin_yield_rates from the DefiLlama pools in
+//! `STAKING_APY_POOLS` (see `services::yield_accrual`). Coins with a manual
+//! rate are left alone. Disabled when no pool is configured.
+
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+
+use crate::services::locking::{self, JobOutcome};
+use crate::services::sync_status::{self, intervals, jobs};
+use crate::services::yield_accrual::{self, DefiLlamaYields, YieldSyncConfig};
+
+pub async fn start_yield_rates_sync_job(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let config = YieldSyncConfig::from_env();
+        if config.pools.is_empty() {
+            tracing::info!("No STAKING_APY_POOLS configured - yield rates sync disabled");
+            return;
+        }
+        let source = DefiLlamaYields::new(&config.defillama_url);
+
+        let mut interval = interval(Duration::from_secs(3600)); // Check every hour
+
+        loop {
+            interval.tick().await;
+
+            match sync_status::should_sync(&db, jobs::YIELD_RATES_SYNC, intervals::YIELD_RATES_SYNC).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::debug!("Skipping yield rates sync (recently run)");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check sync status: {}", e);
+                    continue;
+                }
+            }
+
+            match run_sync(&db, &source, &config).await {
+                Ok(JobOutcome::Ran(())) => {
+                    if let Err(e) = sync_status::record_success(&db, jobs::YIELD_RATES_SYNC, intervals::YIELD_RATES_SYNC).await {
+                        tracing::warn!("Failed to record sync success: {}", e);
+                    }
+                }
+                Ok(JobOutcome::Skipped) => {}
+                Err(e) => {
+                    tracing::error!("Yield rates sync failed: {}", e);
+                    if let Err(e2) =
+                        sync_status::record_failure(&db, jobs::YIELD_RATES_SYNC, &e.to_string(), intervals::YIELD_RATES_SYNC).await
+                    {
+                        tracing::warn!("Failed to record sync failure: {}", e2);
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn run_sync(
+    db: &DatabaseConnection,
+    source: &DefiLlamaYields,
+    config: &YieldSyncConfig,
+) -> Result<JobOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(_lock) = locking::try_acquire_job(db, jobs::YIELD_RATES_SYNC).await? else {
+        return Ok(JobOutcome::Skipped);
+    };
+
+    let summary = yield_accrual::sync(db, source, config, Utc::now().naive_utc()).await?;
+    tracing::info!(
+        updated = summary.updated,
+        skipped = summary.skipped,
+        failed = summary.failed,
+        "Yield rates sync complete"
+    );
+    Ok(JobOutcome::Ran(()))
+}
@@ -8,6 +8,8 @@ use services::{
     itp_listing::ItpListingService,
     realtime_prices::RealTimePriceService,
     live_orderbook_cache::LiveOrderbookCache,
+    auth::AuthConfig,
+    feature_flags::FeatureFlagService,
 };
 use handlers::operations_ws::OperationBroadcaster;
 
@@ -23,6 +25,10 @@ pub struct AppState {
     pub asset_registry: Arc<asset_registry::AssetRegistry>,
     /// Story 3-2: Operation status broadcaster for WebSocket clients
     pub operation_broadcaster: Arc<OperationBroadcaster>,
+    /// SIWE / session token settings for wallet-scoped endpoints
+    pub auth: AuthConfig,
+    /// Runtime feature flags and maintenance mode
+    pub feature_flags: FeatureFlagService,
 }
 
 pub mod entities {
@@ -44,10 +50,42 @@ pub mod entities {
     pub mod itp_price_history;
     pub mod itps;
     pub mod operations;
+    pub mod category_change_events;
+    pub mod sync_status;
+    pub mod job_failures;
+    pub mod background_tasks;
+    pub mod index_deployments;
+    pub mod price_reconciliation_checks;
+    pub mod methodology_documents;
+    pub mod api_keys;
+    pub mod api_key_usage;
+    pub mod auth_nonces;
+    pub mod itp_drift_checks;
+    pub mod itp_orders;
+    pub mod solvers;
+    pub mod labels;
+    pub mod feature_flags;
+    pub mod coin_logos;
+    pub mod index_tvl;
+    pub mod rebalance_approvals;
+    pub mod chain_transactions;
+    pub mod wallet_balance_checks;
+    pub mod coin_yield_rates;
+    pub mod index_translations;
+    pub mod rolling_stats;
+    pub mod snapshot_log;
+    pub mod reports;
+    pub mod spread_observations;
+    pub mod pair_volumes;
+    pub mod coin_sync_cursors;
+    pub mod job_runs;
+    pub mod token_migrations;
 }
 
 pub mod services {
     pub mod coingecko;
+    pub mod coingecko_fake;
+    pub mod coingecko_usage;
     pub mod exchange_api;
     pub mod rebalancing;
     pub mod price_utils;
@@ -58,6 +96,53 @@ pub mod services {
     pub mod category_service;
     pub mod orbit_keeper;
     pub mod itp_creation;
+    pub mod itp_validation;
+    pub mod composition;
+    pub mod itp_drift;
+    pub mod itp_orders;
+    pub mod solvers;
+    pub mod itp_execution_quote;
+    pub mod labels;
+    pub mod crypto_listings;
+    pub mod feature_flags;
+    pub mod coin_logos;
+    pub mod leaderboard;
+    pub mod index_tvl;
+    pub mod protocol_stats;
+    pub mod rebalance_deployment;
+    pub mod rebalance_approvals;
+    pub mod tx_simulator;
+    pub mod signers;
+    pub mod chain_spend;
+    pub mod wallet_balances;
+    pub mod index_constraints;
+    pub mod cash_buffer;
+    pub mod yield_accrual;
+    pub mod leverage;
+    pub mod index_translations;
+    pub mod alerting;
+    pub mod correlation;
+    pub mod rolling_stats;
+    pub mod snapshot_log;
+    pub mod snapshot_anchor;
+    pub mod list_query;
+    pub mod pricing_time;
+    pub mod inception;
+    pub mod price_fallback;
+    pub mod index_events;
+    pub mod index_categories;
+    pub mod sparkline;
+    pub mod email;
+    pub mod reports;
+    pub mod spread_calibration;
+    pub mod pair_volumes;
+    pub mod backtest;
+    pub mod coin_sync_cursors;
+    pub mod job_runs;
+    pub mod index_status;
+    pub mod coin_merge;
+    pub mod rebalance_schema;
+    pub mod portfolio_continuity;
     pub mod itp_listing;
     pub mod itp_price_snapshot;
     pub mod itp_price_downsampler;
@@ -66,7 +151,27 @@ pub mod services {
     pub mod orderbook_aggregator;
     pub mod live_orderbook_cache;
     pub mod bitget_ws_feeder;
+    pub mod sync_status;
+    pub mod data_freshness;
+    pub mod job_failures;
+    pub mod locking;
+    pub mod background_tasks;
+    pub mod rebalance_math;
+    pub mod units;
+    pub mod event_amounts;
+    pub mod supply_reconciliation;
+    pub mod index_deployments;
+    pub mod price_retention;
+    pub mod price_reconciliation;
+    pub mod methodology_documents;
+    pub mod api_keys;
+    pub mod auth;
+    pub mod statements;
+    pub mod price_partitions;
+    pub mod schema_drift;
+    pub mod seed;
 }
 
+pub mod config;
 pub mod models;
 pub mod handlers;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use asset_registry::AssetRegistry;
-use axum::{routing::{get, post}, Router};
+use axum::{routing::{delete, get, post, put}, Router};
 use sea_orm::{Database, DatabaseConnection};
 use sea_orm_migration::MigratorTrait;
 use std::env;
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use tower_http::cors::{CorsLayer, Any};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod config;
 mod entities;
 mod jobs;
 mod handlers;
@@ -19,6 +20,7 @@ use jobs::{
     category_sync,
     rebalance_sync,
     category_membership_sync,
+    category_membership_consistency,
     announcement_scraper,
     index_daily_prices_sync,
     keeper_chart_sync,
@@ -26,11 +28,31 @@ use jobs::{
     itp_price_downsampler_job,
     bitget_historical_prices_sync,
     itp_chain_discovery_sync,
+    job_failures_retry,
+    background_tasks_worker,
+    supply_reconciliation,
+    coins_price_retention,
+    price_reconciliation,
+    coins_price_partitions,
+    itp_drift_monitor,
+    itp_order_indexer,
+    tvl_snapshot,
+    rebalance_deployer,
+    wallet_balance_monitor,
+    yield_rates_sync,
+    rolling_stats,
+    snapshot_log,
+    snapshot_anchor,
+    report_generation,
+    spread_sampling,
+    pair_volume_sampling,
 };
 use services::coingecko::CoinGeckoService;
 use services::itp_listing::ItpListingService;
 use services::realtime_prices::RealTimePriceService;
 use services::live_orderbook_cache::LiveOrderbookCache;
+use services::auth::AuthConfig;
+use services::feature_flags::FeatureFlagService;
 use services::bitget_ws_feeder::BitgetWsFeeder;
 use handlers::operations_ws::OperationBroadcaster;
 
@@ -48,6 +70,10 @@ pub struct AppState {
     pub asset_registry: Arc<AssetRegistry>,
     /// Story 3-2: Operation status broadcaster for WebSocket clients
     pub operation_broadcaster: Arc<OperationBroadcaster>,
+    /// SIWE / session token settings for wallet-scoped endpoints
+    pub auth: AuthConfig,
+    /// Runtime feature flags and maintenance mode
+    pub feature_flags: FeatureFlagService,
 }
 
 #[tokio::main]
@@ -153,6 +179,13 @@ async fn main() {
         feeder.start(symbols).await;
     });
 
+    // Feature flags (reloaded every 30 seconds so all instances follow operator changes)
+    let feature_flags = FeatureFlagService::new(30);
+    if let Err(e) = feature_flags.refresh(&db).await {
+        tracing::error!("Failed to load feature flags, using defaults: {}", e);
+    }
+    feature_flags.start_polling(db.clone());
+
     // Story 3-2: Initialize operation broadcaster for WebSocket clients
     let operation_broadcaster = Arc::new(OperationBroadcaster::new());
 
@@ -165,6 +198,8 @@ async fn main() {
         live_orderbook_cache,
         asset_registry: asset_registry.clone(),
         operation_broadcaster,
+        auth: AuthConfig::from_env(),
+        feature_flags,
     };
 
     // Start background jobs
@@ -183,13 +218,23 @@ async fn main() {
     // Finds coins related to each category - useful for blacklistings
     category_membership_sync::start_category_membership_sync_job(db.clone(), coingecko.clone()).await;
 
+    // Flags overlapping/inverted membership intervals that would corrupt point-in-time selection
+    category_membership_consistency::start_category_membership_consistency_job(db.clone()).await;
+
     // Rebalancer job, runs daily and check for rebalance period OR special (delisting) rebalancing
     rebalance_sync::start_rebalance_sync_job(db.clone(), coingecko.clone(), exchange_api.clone()).await;
 
+    // Runs queued background tasks (e.g. the historical backfill after create_index)
+    background_tasks_worker::start_background_tasks_worker(db.clone(), coingecko.clone()).await;
+
+    // Dead-letter queue worker - retries coins/indexes that failed in the jobs above
+    job_failures_retry::start_job_failures_retry_job(db.clone(), coingecko.clone(), exchange_api.clone()).await;
+
     // Scraper service for Binance/Bitget
     announcement_scraper::start_announcement_scraper_job(db.clone(), scraper_config).await;
 
-    // Computes price of each index (based on last rebalance quantities + coins daily prices)
+    // Computes price of each active index (based on last rebalance quantities + coins daily prices),
+    // catching up on any days missed since its last stored price
     index_daily_prices_sync::start_index_daily_prices_sync_job(db.clone(), coingecko.clone()).await;
 
     // Keeper chart sync - polls Orbit VAULT for claimable data (Story 3.5)
@@ -207,6 +252,54 @@ async fn main() {
     // ITP chain discovery - scans ItpCreated events on Arbitrum to discover bridge-deployed ITPs
     itp_chain_discovery_sync::start_itp_chain_discovery_job(db.clone(), asset_registry.clone()).await;
 
+    // Supply reconciliation - compares event-derived index supply with on-chain totalSupply()
+    supply_reconciliation::start_supply_reconciliation_job(db.clone()).await;
+
+    // Coins price retention - compacts old prices of coins no index references (dry run by default)
+    coins_price_retention::start_coins_price_retention_job(db.clone()).await;
+
+    // Coins price partitions - creates yearly coins_historical_prices partitions ahead of time
+    coins_price_partitions::start_coins_price_partitions_job(db.clone()).await;
+
+    // Price reconciliation - checks a daily sample of stored prices against Binance klines
+    price_reconciliation::start_price_reconciliation_job(db.clone()).await;
+
+    // ITP drift monitor - records drift from target weights and flags ITPs due for a rebalance
+    itp_drift_monitor::start_itp_drift_monitor_job(db.clone()).await;
+
+    // ITP order indexer - advances mint/redeem orders as their on-chain events arrive
+    itp_order_indexer::start_itp_order_indexer_job(db.clone()).await;
+
+    // TVL snapshot - records daily net supply x NAV per index for the TVL history
+    tvl_snapshot::start_tvl_snapshot_job(db.clone()).await;
+
+    // Rebalance deployer - submits undeployed rebalances of live ITPs as on-chain weight updates (opt-in)
+    rebalance_deployer::start_rebalance_deployer_job(db.clone(), asset_registry.clone()).await;
+
+    // Wallet balance monitor - records the signer wallet's ETH balance per network and flags low ones
+    wallet_balance_monitor::start_wallet_balance_monitor_job(db.clone()).await;
+
+    // Yield rates sync - refreshes staking APYs from DefiLlama for NAV yield accrual (opt-in)
+    yield_rates_sync::start_yield_rates_sync_job(db.clone()).await;
+
+    // Rolling stats - stores 30/90/365-day return stats of coins and indexes for analytics
+    rolling_stats::start_rolling_stats_job(db.clone()).await;
+
+    // Snapshot log - hash-chains rebalances and daily NAV records for auditability (opt-in)
+    snapshot_log::start_snapshot_log_job(db.clone()).await;
+
+    // Snapshot anchor - posts each index's snapshot chain head on-chain daily (opt-in)
+    snapshot_anchor::start_snapshot_anchor_job(db.clone()).await;
+
+    // Report generation - renders weekly/monthly reports and emails them to subscribers
+    report_generation::start_report_generation_job(db.clone()).await;
+
+    // Spread sampling - records daily order-book spreads of held pairs for the fee model
+    spread_sampling::start_spread_sampling_job(db.clone(), exchange_api.clone()).await;
+
+    // Pair volume sampling - records exchange-native 24h volume per trading pair
+    pair_volume_sampling::start_pair_volume_sampling_job(db.clone(), exchange_api.clone()).await;
+
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -214,39 +307,74 @@ async fn main() {
         .allow_headers(Any);
 
     // Build router
-    let app = Router::new()
+    let routes = Router::new()
         .route("/", get(handlers::health::hello_indexmaker))
+        .route("/readyz", get(handlers::health::readyz))
+        .route("/metrics", get(handlers::health::metrics))
         .route("/indexes", get(handlers::index::get_index_list))
+        .route("/indexes/leaderboard", get(handlers::leaderboard::get_leaderboard))
+        .route("/index-categories", get(handlers::index_categories::get_index_categories))
+        .route("/stats", get(handlers::stats::get_stats))
         .route("/create-index", post(handlers::index::create_index))
         .route("/api/index/manual", post(handlers::index::create_manual_index))
         .route("/api/index/{index_id}/rebalance", post(handlers::index::add_manual_rebalance))
+        .route("/indexes/{index_id}/seed-inception", post(handlers::index::seed_inception))
         .route("/remove-index", post(handlers::index::remove_index))
         .route("/current-index-weight/{index_id}", get(handlers::index::get_current_index_weight))
         .route("/get-index-config/{index_id}", get(handlers::index::get_index_config))
+        .route("/indexes/{index_id}/translations", get(handlers::index::get_index_translations))
+        .route("/indexes/{index_id}/methodology", get(handlers::methodology::list_methodology_versions))
+        .route("/indexes/{index_id}/methodology/{version}", get(handlers::methodology::get_methodology_version))
         .route("/save-blockchain-event", post(handlers::blockchain_event::save_blockchain_event))
         .route("/get-index-maker-info", get(handlers::index_maker::get_index_maker_info))
         .route("/get-deposit-transaction-data/{index_id}/{address}", get(handlers::deposit::get_deposit_transaction_data))
         .route("/fetch-coin-historical-data/{coin_id}", get(handlers::historical::fetch_coin_historical_data))
         .route("/indexes/{index_id}/transactions", get(handlers::transaction::get_index_transactions))
+        .route("/indexes/{index_id}/tvl-history", get(handlers::tvl::get_tvl_history))
         .route("/download-daily-price-data/{index_id}", get(handlers::historical::download_daily_price_data))
         .route("/subscribe", post(handlers::subscription::subscribe))
+        // Sign-In-With-Ethereum sessions for wallet-scoped endpoints
+        .route("/auth/nonce", post(handlers::auth::create_nonce))
+        .route("/auth/verify", post(handlers::auth::verify))
+        .route("/me/statements", get(handlers::statements::get_my_statement))
         .route("/coingecko-categories", get(handlers::category::get_coingecko_categories))
         .route("/api/categories/with-counts", get(handlers::category::get_categories_with_counts))
+        .route("/categories/{category_id}/members", get(handlers::category::get_category_members))
         .route("/fetch-index-historical-data/{index_id}", get(handlers::historical::fetch_index_historical_data))
         .route("/indexes/{index_id}/price-at-date", get(handlers::index::get_index_price_at_date))
         .route("/indexes/{index_id}/last-price", get(handlers::index::get_index_last_price))
+        .route("/indexes/{index_id}/sparkline", get(handlers::index::get_index_sparkline))
+        .route("/indexes/{index_id}/backfill-status", get(handlers::index::get_backfill_status))
         .route("/fetch-all-assets", get(handlers::asset::fetch_all_assets))
         .route("/fetch-vault-assets/{index_id}", get(handlers::asset::fetch_vault_assets))
         .route("/api/market-cap/history", get(handlers::market_cap::get_market_cap_history))
+        .route("/api/market-cap/history/batch", post(handlers::market_cap::get_market_cap_history_batch))
+        .route("/api/analytics/correlation", post(handlers::analytics::get_correlation))
+        .route("/api/analytics/rolling-stats", get(handlers::analytics::get_rolling_stats))
+        .route("/api/backtest/compare", post(handlers::backtest::compare_backtests))
+        .route("/indexes/{index_id}/snapshot-proof/{date}", get(handlers::snapshots::get_snapshot_proof))
         .route("/api/market-cap/top-category", get(handlers::market_cap::get_top_category))
+        .route("/api/market-cap/top", get(handlers::market_cap::get_top_market_cap))
         .route("/api/market-cap/live-category", get(handlers::market_cap::get_live_category))
         .route("/api/exchange/tradeable-pairs", get(handlers::pairs::get_tradeable_pairs))
         .route("/api/exchange/all-tradeable-assets", get(handlers::pairs::get_all_tradeable_assets))
+        .route("/exchanges/{exchange}/pairs", get(handlers::pairs::get_exchange_pairs))
         .route("/api/coins/symbol-mapping", get(handlers::pairs::get_coin_symbol_mapping))
+        .route("/coins/by-contract/{chain}/{address}", get(handlers::asset::lookup_by_contract))
+        .route("/assets/{coin_id}/logo", get(handlers::asset::get_coin_logo))
+        .route("/api/listings", get(handlers::listing::get_listings))
+        .route("/crypto-listings", get(handlers::listing::get_crypto_listings))
+        // Atom feeds for feed readers and chat integrations
+        .route("/feeds/announcements.xml", get(handlers::feeds::announcements_feed))
+        .route("/feeds/indexes/{index_id}/events.xml", get(handlers::feeds::index_events_feed))
+        // Live index progress as server-sent events
+        .route("/events/indexes/{index_id}", get(handlers::events::index_events))
         // Keeper charts API (Story 3.5)
         .route("/api/keeper-charts/all", get(handlers::keeper_charts::get_all_keepers))
         .route("/api/keeper-charts/{keeper_address}/history", get(handlers::keeper_charts::get_keeper_history))
         .route("/api/keeper-charts/{keeper_address}/latest", get(handlers::keeper_charts::get_keeper_latest))
+        // Composition generation (asset weights for a new ITP)
+        .route("/api/compositions/generate", post(handlers::composition::generate_composition))
         // ITP creation API (Story 6.6)
         .route("/api/itp/create", post(handlers::itp::create_itp))
         // ITP status check API (real-time progress tracking)
@@ -257,6 +385,23 @@ async fn main() {
         .route("/api/itp/{id}/history", get(handlers::itp_history::get_itp_price_history))
         // ITP rebalance history API (Story 0-1 AC5)
         .route("/api/itp/{index_id}/rebalances", get(handlers::itp_rebalances::get_rebalance_history))
+        // ITP composition drift from target weights
+        .route("/api/itp/{address}/drift", get(handlers::itp_drift::get_itp_drift))
+        // ITP mint execution cost from live order books
+        .route(
+            "/api/itp/{address}/execution-quote",
+            get(handlers::itp_execution_quote::get_execution_quote),
+        )
+        // ITP order tracking (mint/redeem lifecycle)
+        .route(
+            "/api/itp/{address}/orders",
+            get(handlers::itp_orders::list_orders).post(handlers::itp_orders::create_order),
+        )
+        .route("/api/itp/orders/{order_id}", get(handlers::itp_orders::get_order))
+        // Solver registry (execution agents filling ITP orders)
+        .route("/api/solvers", get(handlers::solvers::list_solvers))
+        .route("/api/solvers/register", post(handlers::solvers::register_solver))
+        .route("/api/solvers/{id}/heartbeat", post(handlers::solvers::solver_heartbeat))
         // Virtual orderbook for index composition preview
         .route("/api/orderbook/virtual", post(handlers::orderbook::get_virtual_orderbook))
         // WebSocket for live orderbook streaming
@@ -266,6 +411,52 @@ async fn main() {
         .route("/api/operations", get(handlers::operations_ws::get_operations))
         .route("/api/operations/ws", get(handlers::operations_ws::operations_websocket))
         .route("/api/operations/update", post(handlers::operations_ws::update_operation))
+        // Admin / operations
+        .route("/admin/data-freshness", get(handlers::admin::get_data_freshness))
+        .route("/admin/job-failures", get(handlers::admin::list_job_failures))
+        .route("/admin/job-failures/{id}/requeue", post(handlers::admin::requeue_job_failure))
+        .route("/admin/job-failures/{id}/discard", post(handlers::admin::discard_job_failure))
+        .route("/admin/indexes/{index_id}/supply-reconciliation", get(handlers::admin::get_supply_reconciliation))
+        .route("/admin/price-retention", get(handlers::admin::get_price_retention_report))
+        .route("/admin/price-reconciliation", get(handlers::admin::get_price_reconciliation_report))
+        .route("/admin/indexes/{index_id}/deployments", get(handlers::admin::get_index_deployments).put(handlers::admin::update_index_deployments))
+        .route("/admin/indexes/{index_id}/methodology", post(handlers::admin::create_methodology_version))
+        .route("/admin/indexes/{index_id}/status", put(handlers::admin::update_index_status))
+        .route("/admin/indexes/{index_id}/continuity", get(handlers::admin::get_portfolio_continuity))
+        .route(
+            "/admin/indexes/{index_id}/translations/{locale}",
+            put(handlers::admin::upsert_index_translation).delete(handlers::admin::delete_index_translation),
+        )
+        .route("/admin/api-keys", get(handlers::admin::list_api_keys).post(handlers::admin::create_api_key))
+        .route("/admin/api-keys/{id}/revoke", post(handlers::admin::revoke_api_key))
+        .route("/admin/labels", get(handlers::admin::list_labels).post(handlers::admin::create_label))
+        .route("/admin/labels/{id}", delete(handlers::admin::delete_label))
+        .route("/admin/api-usage", get(handlers::admin::get_api_usage))
+        .route("/admin/coingecko-usage", get(handlers::admin::get_coingecko_usage))
+        .route("/admin/feature-flags", get(handlers::admin::list_feature_flags))
+        .route("/admin/feature-flags/{key}", put(handlers::admin::update_feature_flag))
+        .route("/admin/rebalances/{id}/approval", get(handlers::admin::get_rebalance_approval))
+        .route("/admin/rebalances/{id}/request-approval", post(handlers::admin::request_rebalance_approval))
+        .route("/admin/rebalances/{id}/approve", post(handlers::admin::approve_rebalance))
+        .route("/admin/rebalances/{id}/reject", post(handlers::admin::reject_rebalance))
+        .route("/admin/chain-spend", get(handlers::admin::get_chain_spend))
+        .route("/admin/yield-rates", get(handlers::admin::list_yield_rates))
+        .route("/admin/yield-rates/{coin_id}", put(handlers::admin::set_yield_rate).delete(handlers::admin::delete_yield_rate))
+        .route("/admin/coins/merge", post(handlers::admin::merge_coins))
+        .route("/admin/reports", get(handlers::admin::list_reports))
+        .route("/admin/reports/{id}/content", get(handlers::admin::get_report_content));
+
+    // GraphQL over the same data (see handlers::graphql)
+    #[cfg(feature = "graphql")]
+    let routes = routes.route("/graphql", get(handlers::graphql::graphiql).post(handlers::graphql::graphql));
+
+    let app = routes
+        // Read-only maintenance mode (see handlers::maintenance)
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::maintenance::maintenance))
+        // API key tiers, quotas and usage metering (see handlers::metering)
+        .layer(axum::middleware::from_fn_with_state(state.clone(), handlers::metering::meter))
+        // Per-endpoint Cache-Control (see handlers::cache_control)
+        .layer(axum::middleware::from_fn(handlers::cache_control::cache_control))
         .layer(cors)
         .with_state(state);
 
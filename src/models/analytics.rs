@@ -0,0 +1,80 @@
+//! Analytics models for POST /api/analytics/correlation and
+//! GET /api/analytics/rolling-stats
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationRequest {
+    pub coin_ids: Vec<String>,
+    /// Daily returns looked at, ending on `end_date` (default: 90)
+    pub window: Option<u32>,
+    /// YYYY-MM-DD (default: yesterday, the latest complete day)
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinVolatility {
+    pub coin_id: String,
+    pub symbol: String,
+    /// Daily log returns in the window
+    pub observations: usize,
+    /// Standard deviation of daily log returns; null under two returns
+    pub daily_volatility: Option<f64>,
+    /// Daily volatility × √365
+    pub annualized_volatility: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationResponse {
+    /// Date of the first return (against the day before)
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub window: u32,
+    pub coins: Vec<CoinVolatility>,
+    /// Pearson correlation of daily log returns, rows and columns in the
+    /// order of `coins`; null for pairs with under two common returns
+    pub correlation: Vec<Vec<Option<f64>>>,
+    /// Requested coins not in the system
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RollingStatsQuery {
+    /// "coin" or "index"
+    pub subject: String,
+    /// Coin ID or index ID
+    pub id: String,
+    /// YYYY-MM-DD; stats of the latest computed day up to it (default: latest)
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowStatsResponse {
+    pub window_days: i32,
+    /// Daily log returns in the window
+    pub observations: i32,
+    /// Last price over the first one, minus one
+    pub total_return: Option<f64>,
+    /// Mean daily log return
+    pub mean_return: Option<f64>,
+    /// Standard deviation of daily log returns
+    pub volatility: Option<f64>,
+    /// Volatility × √365
+    pub annualized_volatility: Option<f64>,
+    /// Largest fall from a running peak, as a positive fraction
+    pub max_drawdown: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingStatsResponse {
+    pub subject: String,
+    pub id: String,
+    pub as_of: NaiveDate,
+    pub windows: Vec<WindowStatsResponse>,
+}
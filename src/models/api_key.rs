@@ -0,0 +1,95 @@
+//! API key models for /admin/api-keys and /admin/api-usage
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::api_keys;
+
+/// Request body for POST /admin/api-keys
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// public, partner or admin
+    pub tier: String,
+    /// Requests per UTC day (default: the tier's quota)
+    pub daily_quota: Option<i32>,
+}
+
+/// An issued key, without the key itself
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyResponse {
+    pub id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub tier: String,
+    pub daily_quota: Option<i32>,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// Response for POST /admin/api-keys; the only time the key is returned
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsageQuery {
+    /// Days of usage to report, including today (default: 7, max: 90)
+    pub days: Option<u32>,
+    /// Report a single key
+    pub key_id: Option<i32>,
+}
+
+/// Response for GET /admin/api-usage
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiUsageReport {
+    /// Usage on or after this day is included
+    pub since: NaiveDate,
+    pub keys: Vec<ApiKeyUsageSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyUsageSummary {
+    pub id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub tier: String,
+    pub daily_quota: Option<i32>,
+    pub active: bool,
+    pub requests_today: i64,
+    pub requests: i64,
+    /// Requests per endpoint over the period, busiest first
+    pub endpoints: Vec<EndpointUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointUsage {
+    pub endpoint: String,
+    pub requests: i64,
+}
+
+impl From<api_keys::Model> for ApiKeyResponse {
+    fn from(m: api_keys::Model) -> Self {
+        Self {
+            id: m.id,
+            name: m.name,
+            key_prefix: m.key_prefix,
+            tier: m.tier,
+            daily_quota: m.daily_quota,
+            active: m.active,
+            created_at: m.created_at,
+            revoked_at: m.revoked_at,
+        }
+    }
+}
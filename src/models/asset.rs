@@ -36,6 +36,25 @@ pub struct CoinGeckoMarketData {
     pub max_supply: Option<f64>,
 }
 
+/// Response of GET /coins/by-contract/{chain}/{address}
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinByContractResponse {
+    /// CoinGecko asset platform id, e.g. "ethereum" or "arbitrum-one"
+    pub platform: String,
+    pub contract_address: String,
+    pub coin_id: String,
+    pub symbol: String,
+    pub name: String,
+    /// Market data is None when CoinGecko has no market for the coin
+    pub image: Option<String>,
+    pub price_usd: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub market_cap_rank: Option<i32>,
+    pub total_volume: Option<f64>,
+    pub circulating_supply: Option<f64>,
+    pub total_supply: Option<f64>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -51,3 +70,10 @@ pub struct VaultAsset {
     pub quantity: f64,
 }
 
+
+/// Query of GET /assets/{coin_id}/logo
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogoQuery {
+    /// Fit the logo into `size` x `size` pixels (16-256); full size if unset
+    pub size: Option<u32>,
+}
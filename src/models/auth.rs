@@ -0,0 +1,33 @@
+//! Wallet authentication models for /auth/*
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Response for POST /auth/nonce
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceResponse {
+    /// Goes in the `Nonce:` field of the SIWE message
+    pub nonce: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Request body for POST /auth/verify
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyRequest {
+    /// The EIP-4361 message, exactly as signed
+    pub message: String,
+    /// 65-byte personal_sign signature, hex encoded
+    pub signature: String,
+}
+
+/// Response for POST /auth/verify
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    /// Send as `Authorization: Bearer <token>`
+    pub token: String,
+    pub address: String,
+    pub expires_at: NaiveDateTime,
+}
@@ -0,0 +1,155 @@
+//! Backtest models for POST /api/backtest/compare
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::services::leverage;
+
+/// Most parameter sets compared in one request
+pub const MAX_SCENARIOS: usize = 10;
+
+/// Longest date range of a backtest (5 years)
+pub const MAX_BACKTEST_DAYS: i64 = 1826;
+
+/// Largest top_n of a scenario
+pub const MAX_TOP_N: usize = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestCompareRequest {
+    /// CoinGecko category the universe is drawn from, with its membership
+    /// as of each rebalance (default: every coin)
+    pub category: Option<String>,
+    /// Explicit universe, ranked by market cap like a category's
+    #[serde(default)]
+    pub coin_ids: Vec<String>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Portfolio value on start_date (default: 1000)
+    pub initial_value: Option<f64>,
+    pub scenarios: Vec<BacktestScenario>,
+}
+
+/// One parameter set, run over the request's universe and dates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestScenario {
+    /// Label in the results (default: "scenario N")
+    pub name: Option<String>,
+    /// Constituents held
+    pub top_n: usize,
+    /// Days between rebalances (default: 30)
+    pub rebalance_period_days: Option<u32>,
+    /// Extra ranks a constituent may fall to before it's replaced; 0
+    /// rebuilds the top N from scratch at every rebalance
+    #[serde(default)]
+    pub ranking_buffer: usize,
+    /// "equal" or "marketcap" (default: marketcap)
+    pub weight_strategy: Option<String>,
+    /// Max weight of one constituent, in percent (e.g. 25.0 for 25%)
+    pub max_weight_pct: Option<f64>,
+    /// Fraction of traded value (default: 0.001)
+    pub trading_fee: Option<Decimal>,
+    /// Bid-ask spread as a fraction of mid, half of which is charged (default: 0.0005)
+    pub spread: Option<Decimal>,
+    /// Daily multiple of the basket's return, e.g. -1 or 2, with the limits
+    /// of index leverage (default: 1, see services::leverage)
+    pub leverage_factor: Option<Decimal>,
+}
+
+impl BacktestCompareRequest {
+    /// Validates the request body; scenario names are only defaulted later
+    pub fn validate(&self) -> Result<(), String> {
+        if self.start_date >= self.end_date {
+            return Err("startDate must be before endDate".to_string());
+        }
+        if (self.end_date - self.start_date).num_days() >= MAX_BACKTEST_DAYS {
+            return Err(format!("Date range cannot exceed {} days", MAX_BACKTEST_DAYS));
+        }
+        if self.category.is_some() && !self.coin_ids.is_empty() {
+            return Err("Provide either category or coinIds, not both".to_string());
+        }
+        if let Some(value) = self.initial_value
+            && !(value.is_finite() && value > 0.0)
+        {
+            return Err("initialValue must be positive".to_string());
+        }
+        if self.scenarios.is_empty() || self.scenarios.len() > MAX_SCENARIOS {
+            return Err(format!("scenarios must list 1 to {} parameter sets", MAX_SCENARIOS));
+        }
+
+        for (i, scenario) in self.scenarios.iter().enumerate() {
+            let name = scenario.name.clone().unwrap_or_else(|| format!("scenario {}", i + 1));
+            if !(1..=MAX_TOP_N).contains(&scenario.top_n) {
+                return Err(format!("{}: topN must be between 1 and {}", name, MAX_TOP_N));
+            }
+            if scenario.rebalance_period_days == Some(0) {
+                return Err(format!("{}: rebalancePeriodDays must be at least 1", name));
+            }
+            if scenario.ranking_buffer > MAX_TOP_N {
+                return Err(format!("{}: rankingBuffer can't be above {}", name, MAX_TOP_N));
+            }
+            if let Some(strategy) = &scenario.weight_strategy
+                && !matches!(strategy.to_lowercase().as_str(), "equal" | "marketcap")
+            {
+                return Err(format!("{}: weightStrategy must be 'equal' or 'marketcap'", name));
+            }
+            if let Some(max) = scenario.max_weight_pct
+                && !(max > 0.0 && max <= 100.0)
+            {
+                return Err(format!("{}: maxWeightPct must be above 0 and at most 100", name));
+            }
+            for (field, value) in [("tradingFee", scenario.trading_fee), ("spread", scenario.spread)] {
+                if let Some(value) = value
+                    && !(Decimal::ZERO..Decimal::ONE).contains(&value)
+                {
+                    return Err(format!("{}: {} must be at least 0 and below 1", name, field));
+                }
+            }
+            if let Some(factor) = scenario.leverage_factor
+                && let Err(e) = leverage::validate_factor(factor)
+            {
+                return Err(format!("{}: {}", name, e));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestScenarioResult {
+    pub name: String,
+    /// The parameters as run, defaults filled in
+    pub scenario: BacktestScenario,
+    pub final_value: f64,
+    /// Final value over initial value, minus one
+    pub total_return: Option<f64>,
+    pub annualized_return: Option<f64>,
+    /// Standard deviation of daily log returns × √365
+    pub annualized_volatility: Option<f64>,
+    /// Annualized return over annualized volatility (no risk-free rate)
+    pub sharpe_ratio: Option<f64>,
+    /// Largest fall from a running peak, as a positive fraction
+    pub max_drawdown: Option<f64>,
+    pub rebalances: usize,
+    /// Mean one-way turnover of the rebalances after the first, as a fraction of value
+    pub avg_turnover: Option<f64>,
+    /// Constituents added over the rebalances after the first
+    pub constituent_changes: usize,
+    /// Mean constituents held per rebalance
+    pub avg_constituents: f64,
+    /// Trading costs paid, in the unit of initial_value
+    pub total_fees: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestCompareResponse {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub initial_value: f64,
+    /// One per scenario, in request order
+    pub results: Vec<BacktestScenarioResult>,
+}
@@ -17,6 +17,31 @@ pub struct CreateBlockchainEventRequest {
     pub amount: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<Decimal>,
+    /// Raw on-chain amount (uint256 as a base-10 string); when set, `amount`
+    /// is derived from it and `decimals` is required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u32>,
+    /// Raw on-chain quantity; when set, `quantity` is derived from it and
+    /// `quantity_decimals` is required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_quantity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity_decimals: Option<u32>,
+    /// Unix timestamp (seconds) of the event's block; stored as the event
+    /// timestamp so back-dated events keep their real time. Defaults to now.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<i64>,
+}
+
+/// Body of POST /save-blockchain-event: one event, or a batch ordered by
+/// (blockNumber, logIndex)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SaveBlockchainEventRequest {
+    Single(Box<CreateBlockchainEventRequest>),
+    Batch(Vec<CreateBlockchainEventRequest>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,5 +61,47 @@ pub struct BlockchainEventResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_quantity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity_decimals: Option<i32>,
+    /// False when the event had already been stored (retried delivery)
+    pub created: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<NaiveDateTime>,
-}
\ No newline at end of file
+}
+
+/// Outcome of one event of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchEventResult {
+    /// Position of the event in the request
+    pub index: usize,
+    pub tx_hash: String,
+    pub log_index: i32,
+    /// created, updated or failed
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<BlockchainEventResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSaveBlockchainEventsResponse {
+    pub created: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub results: Vec<BatchEventResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SaveBlockchainEventResponse {
+    Single(Box<BlockchainEventResponse>),
+    Batch(BatchSaveBlockchainEventsResponse),
+}
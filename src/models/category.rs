@@ -17,3 +17,27 @@ pub struct CategoryWithCountResponse {
 
 pub type CategoriesListResponse = Vec<CategoryResponse>;
 pub type CategoriesWithCountResponse = Vec<CategoryWithCountResponse>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CategoryMembersQuery {
+    /// Point-in-time date (YYYY-MM-DD), defaults to today
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryMemberResponse {
+    pub coin_id: String,
+    pub symbol: Option<String>,
+    pub added_date: String,
+    pub removed_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryMembersResponse {
+    pub category_id: String,
+    pub date: String,
+    pub count: usize,
+    pub members: Vec<CategoryMemberResponse>,
+}
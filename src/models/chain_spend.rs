@@ -0,0 +1,82 @@
+//! Chain spend models for GET /admin/chain-spend
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::chain_transactions;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainSpendQuery {
+    /// YYYY-MM (default: the current month)
+    pub month: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSpendReport {
+    /// YYYY-MM
+    pub month: String,
+    pub transaction_count: usize,
+    /// Reverted transactions, whose gas is included in the totals
+    pub failed_count: usize,
+    /// Transactions sent without a known ETH price, left out of cost_usd
+    pub unpriced_count: usize,
+    pub gas_used: i64,
+    pub cost_eth: Decimal,
+    pub cost_usd: Decimal,
+    pub by_kind: Vec<ChainSpendByKind>,
+    /// Oldest first
+    pub transactions: Vec<ChainTransactionResponse>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSpendByKind {
+    /// create_itp or rebalance
+    pub kind: String,
+    pub transaction_count: usize,
+    pub gas_used: i64,
+    pub cost_eth: Decimal,
+    pub cost_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainTransactionResponse {
+    pub tx_hash: String,
+    pub kind: String,
+    pub reference: Option<String>,
+    pub chain_id: i64,
+    pub from_address: String,
+    pub to_address: Option<String>,
+    pub block_number: i64,
+    pub succeeded: bool,
+    pub gas_used: i64,
+    pub effective_gas_price_wei: Decimal,
+    pub cost_eth: Decimal,
+    pub eth_price_usd: Option<Decimal>,
+    pub cost_usd: Option<Decimal>,
+    pub sent_at: NaiveDateTime,
+}
+
+impl From<chain_transactions::Model> for ChainTransactionResponse {
+    fn from(m: chain_transactions::Model) -> Self {
+        Self {
+            tx_hash: m.tx_hash,
+            kind: m.kind,
+            reference: m.reference,
+            chain_id: m.chain_id,
+            from_address: m.from_address,
+            to_address: m.to_address,
+            block_number: m.block_number,
+            succeeded: m.succeeded,
+            gas_used: m.gas_used,
+            effective_gas_price_wei: m.effective_gas_price_wei,
+            cost_eth: m.cost_eth,
+            eth_price_usd: m.eth_price_usd,
+            cost_usd: m.cost_usd,
+            sent_at: m.sent_at,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Models for the /admin/coins/merge endpoint
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeCoinsRequest {
+    /// Coin merged away
+    pub source_coin_id: String,
+    /// Coin that keeps its id and receives the source's rows
+    pub target_coin_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeCoinsResponse {
+    pub source_coin_id: String,
+    pub target_coin_id: String,
+    /// Rows now referencing the target, by table
+    pub rows_moved: BTreeMap<String, u64>,
+    /// Source rows deleted as the target already had them, by table
+    pub rows_dropped: BTreeMap<String, u64>,
+    pub merged_at: NaiveDateTime,
+}
@@ -0,0 +1,68 @@
+//! Composition (asset weights) models shared by ITP creation and composition generation
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Per-request composition limits
+///
+/// Each limit can only tighten the server-wide one (see config::CompositionLimits).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompositionLimitOverrides {
+    /// Largest weight of a single asset, in basis points
+    #[serde(default)]
+    pub max_asset_weight_bps: Option<u128>,
+    /// Smallest weight of a single asset, in basis points
+    #[serde(default)]
+    pub min_asset_weight_bps: Option<u128>,
+    /// Most assets in the composition
+    #[serde(default)]
+    pub max_assets: Option<usize>,
+}
+
+/// Request for POST /api/compositions/generate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateCompositionRequest {
+    /// Asset symbols, e.g. ["BTC", "ETH", "SOL"]
+    pub assets: Vec<String>,
+    /// "marketcap" (default) or "equal"
+    #[serde(default = "default_weighting")]
+    pub weighting: String,
+    #[serde(default)]
+    pub limits: Option<CompositionLimitOverrides>,
+}
+
+fn default_weighting() -> String {
+    "marketcap".to_string()
+}
+
+/// One asset of a generated composition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositionAsset {
+    pub asset_id: u128,
+    pub symbol: String,
+    /// CoinGecko coin the market cap was taken from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coin_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<Decimal>,
+    pub weight_bps: u128,
+}
+
+/// Response of POST /api/compositions/generate
+///
+/// `asset_ids` and `weights` can be sent as-is to POST /api/itp/create.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedComposition {
+    pub weighting: String,
+    pub max_asset_weight_bps: u128,
+    pub min_asset_weight_bps: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_assets: Option<usize>,
+    /// Date of the market caps used, for market cap weighting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap_date: Option<NaiveDate>,
+    pub assets: Vec<CompositionAsset>,
+    pub asset_ids: Vec<u128>,
+    pub weights: Vec<u128>,
+}
@@ -0,0 +1,44 @@
+//! Data freshness report models
+//!
+//! Response for GET /admin/data-freshness: one entry per data domain with the
+//! last successful sync, row counts, and the most stale coin/index.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+/// Last sync outcome for a background job, from the sync_status table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSyncInfo {
+    pub job_name: String,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub last_attempt_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub error_count: i64,
+}
+
+/// The entity (coin, index, category) whose latest data is the oldest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleEntity {
+    pub id: String,
+    pub last_data_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainFreshness {
+    pub domain: String,
+    pub jobs: Vec<JobSyncInfo>,
+    pub row_count: i64,
+    /// Most recent data point in the domain (max date / updated_at)
+    pub latest_data_at: Option<NaiveDateTime>,
+    pub most_stale: Option<StaleEntity>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFreshnessResponse {
+    pub generated_at: NaiveDateTime,
+    pub domains: Vec<DomainFreshness>,
+}
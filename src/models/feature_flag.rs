@@ -0,0 +1,34 @@
+//! Feature flag models for the /admin/feature-flags endpoints
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::services::feature_flags::FlagState;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateFeatureFlagRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagResponse {
+    pub key: String,
+    pub enabled: bool,
+    pub default_enabled: bool,
+    pub description: String,
+    /// Last operator change; null while the flag has its default
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl From<FlagState> for FeatureFlagResponse {
+    fn from(state: FlagState) -> Self {
+        Self {
+            key: state.definition.key.to_string(),
+            enabled: state.enabled,
+            default_enabled: state.definition.default,
+            description: state.definition.description.to_string(),
+            updated_at: state.updated_at,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Health check models for GET /readyz
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::entities::wallet_balance_checks;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessResponse {
+    /// ready or unavailable
+    pub status: String,
+    pub database: bool,
+    /// Some signer wallet balance is under its threshold
+    pub low_wallet_balance: bool,
+    /// Latest balance of the signer wallet per network
+    pub wallets: Vec<WalletBalanceStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletBalanceStatus {
+    pub network: String,
+    pub address: String,
+    pub balance_eth: Decimal,
+    pub threshold_eth: Decimal,
+    pub low: bool,
+    pub checked_at: NaiveDateTime,
+}
+
+impl From<wallet_balance_checks::Model> for WalletBalanceStatus {
+    fn from(m: wallet_balance_checks::Model) -> Self {
+        Self {
+            network: m.network,
+            address: m.address,
+            balance_eth: m.balance_eth,
+            threshold_eth: m.threshold_eth,
+            low: m.low,
+            checked_at: m.checked_at,
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::models::methodology::MethodologyVersionRef;
+use crate::services::price_fallback::PriceFallback;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexListEntry {
@@ -27,7 +32,12 @@ pub struct IndexListEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub performance: Option<Performance>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub index_price: Option<f64>,
+    pub index_price: Option<Decimal>,
+    /// Localized description, with the locale of the translation used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +96,8 @@ impl Default for IndexListEntry {
             ratings: None,
             performance: None,
             index_price: None,
+            description: None,
+            locale: None,
         }
     }
 }
@@ -118,6 +130,29 @@ pub struct CreateIndexRequest {
 
     #[serde(default)]
     pub blacklisted_categories: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub constraints: Option<IndexConstraints>,
+
+    /// Percent held in a stablecoin, e.g. 5.0 for 5% (see services::cash_buffer)
+    #[serde(default)]
+    pub cash_buffer_pct: Option<Decimal>,
+    /// Stablecoin of the buffer, USDC by default
+    #[serde(default)]
+    pub cash_buffer_symbol: Option<String>,
+
+    /// Accrue constituents' staking yield into the NAV (see services::yield_accrual)
+    #[serde(default)]
+    pub accrue_yield: bool,
+
+    /// Daily multiple of the basket's return, e.g. -1 or 2 (see services::leverage)
+    #[serde(default)]
+    pub leverage_factor: Option<Decimal>,
+
+    /// Charge rebalance spreads at the observed trailing average once
+    /// available (see services::spread_calibration)
+    #[serde(default)]
+    pub use_observed_spread: bool,
 }
 
 impl CreateIndexRequest {
@@ -148,6 +183,59 @@ impl CreateIndexRequest {
     }
 }
 
+/// Composition constraints of an index (index_metadata.constraints)
+///
+/// Enforced by every rebalance, see services::index_constraints.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexConstraints {
+    /// Max weight per CoinGecko category id, in percent of the index (e.g. 25.0 for 25%)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub max_category_weight: BTreeMap<String, Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_constituents: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_constituents: Option<usize>,
+    /// Minimum 24h USD volume of a candidate's exchange pair, see services::pair_volumes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_pair_volume_usd: Option<Decimal>,
+}
+
+impl IndexConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.max_category_weight.is_empty()
+            && self.max_constituents.is_none()
+            && self.min_constituents.is_none()
+            && self.min_pair_volume_usd.is_none()
+    }
+
+    /// Validates the caps are percentages and the constituent limits consistent
+    pub fn validate(&self) -> Result<(), String> {
+        for (category, max_weight) in &self.max_category_weight {
+            if *max_weight <= Decimal::ZERO || *max_weight > Decimal::ONE_HUNDRED {
+                return Err(format!(
+                    "maxCategoryWeight for '{}' must be above 0 and at most 100, got {}",
+                    category, max_weight
+                ));
+            }
+        }
+        if self.max_constituents == Some(0) {
+            return Err("maxConstituents must be at least 1".to_string());
+        }
+        if let (Some(min), Some(max)) = (self.min_constituents, self.max_constituents)
+            && min > max
+        {
+            return Err(format!("minConstituents ({}) can't be above maxConstituents ({})", min, max));
+        }
+        if let Some(min_volume) = self.min_pair_volume_usd
+            && min_volume <= Decimal::ZERO
+        {
+            return Err(format!("minPairVolumeUsd must be positive, got {}", min_volume));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateIndexResponse {
@@ -173,6 +261,17 @@ pub struct CreateIndexResponse {
     pub weight_threshold: Option<String>,
 
     pub blacklisted_categories: Option<Vec<String>>,
+
+    pub constraints: Option<IndexConstraints>,
+
+    pub cash_buffer_pct: Option<String>,
+    pub cash_buffer_symbol: Option<String>,
+
+    pub accrue_yield: bool,
+
+    pub leverage_factor: Option<String>,
+
+    pub use_observed_spread: bool,
 }
 
 // Default value helper
@@ -193,6 +292,16 @@ pub struct IndexConfigResponse {
     pub exchange_trading_fees: String,
     pub exchange_avg_spread: String,
     pub rebalance_period: i32,
+    /// Official daily price cutoff, e.g. "00:00 UTC" (see services::pricing_time)
+    pub pricing_cutoff: String,
+    /// Methodology version in effect today
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub methodology: Option<MethodologyVersionRef>,
+    /// Localized description, with the locale of the translation used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -206,8 +315,12 @@ pub struct IndexPriceAtDateRequest {
 pub struct IndexPriceAtDateResponse {
     pub index_id: i32,
     pub date: String,
-    pub price: f64,
+    pub price: Decimal,
     pub constituents: Vec<ConstituentPriceInfo>,
+    /// Set when a constituent's price for the date couldn't be fetched and a
+    /// fallback applied (see ConstituentPriceInfo::fallback)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -215,10 +328,14 @@ pub struct IndexPriceAtDateResponse {
 pub struct IndexLastPriceResponse {
     pub index_id: i32,
     pub timestamp: i64,        // Unix timestamp of last rebalance
-    pub last_price: f64,       // Current index price
-    pub last_bid: Option<f64>, // Not implemented yet
-    pub last_ask: Option<f64>, // Not implemented yet
+    pub last_price: Decimal,       // Current index price
+    pub last_bid: Option<Decimal>, // Not implemented yet
+    pub last_ask: Option<Decimal>, // Not implemented yet
     pub constituents: Vec<ConstituentPriceInfo>,
+    /// Set when a constituent's price for today fell back (see
+    /// IndexPriceAtDateResponse)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -228,8 +345,33 @@ pub struct ConstituentPriceInfo {
     pub symbol: String,
     pub quantity: String,
     pub weight: String,
-    pub price: f64,
-    pub value: f64, // weight × quantity × price
+    pub price: Decimal,
+    pub value: Decimal, // weight × quantity × price
+    /// How the price was arrived at when the day's couldn't be had; an
+    /// excluded constituent is shown at the price it stands in for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<PriceFallback>,
+    /// Where `price` came from; None for cash and excluded constituents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_source: Option<PriceSource>,
+    /// Unix timestamp `price` is for: the cutoff of the day it was struck,
+    /// or when a live exchange price was polled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_timestamp: Option<i64>,
+}
+
+/// Where a constituent's price came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PriceSource {
+    /// Stored in coins_historical_prices
+    DbCache,
+    /// Fetched from CoinGecko while answering the request
+    CoingeckoLive,
+    /// Latest ticker polled from Bitget or Binance (last price only)
+    ExchangeLive,
+    /// An earlier day's stored price (see ConstituentPriceInfo::fallback)
+    CarriedForward,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -264,10 +406,10 @@ pub struct ConstituentWeight {
     pub coin_id: String,
     pub symbol: String,
     pub weight: String,
-    pub weight_percentage: f64,
+    pub weight_percentage: Decimal,
     pub quantity: String,
-    pub price: f64,
-    pub value: f64,
+    pub price: Decimal,
+    pub value: Decimal,
     pub exchange: String,
     pub trading_pair: String,
 }
@@ -392,6 +534,99 @@ pub struct ManualRebalanceResponse {
     pub message: String,
 }
 
+/// Request model for POST /indexes/{index_id}/seed-inception
+///
+/// Without `coins`, the existing inception rows are only checked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedInceptionRequest {
+    /// Defaults to the index's initial_date
+    pub date: Option<NaiveDate>,
+    /// Defaults to the index's initial_price
+    pub price: Option<Decimal>,
+    pub coins: Option<Vec<RebalanceCoin>>,
+}
+
+/// Response model for POST /indexes/{index_id}/seed-inception
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InceptionReport {
+    pub index_id: i32,
+    pub date: Option<NaiveDate>,
+    pub price: Option<Decimal>,
+    pub rebalance_id: Option<i32>,
+    /// Whether this request wrote the inception rows
+    pub seeded: bool,
+    pub valid: bool,
+    pub issues: Vec<String>,
+}
+
+/// Response model for GET /indexes/{index_id}/backfill-status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillStatusResponse {
+    pub index_id: i32,
+    pub task_id: i32,
+    /// pending, running, completed, failed
+    pub status: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    /// Last heartbeat while running
+    pub updated_at: NaiveDateTime,
+    /// Rebalance dates backfilled so far
+    pub rebalances_done: Option<i32>,
+    /// Rebalance dates planned for this run
+    pub rebalances_total: Option<i32>,
+    pub progress_percent: Option<f64>,
+    /// Rebalance date currently being processed
+    pub current_date: Option<NaiveDate>,
+    /// Estimated completion time (running tasks only)
+    pub eta: Option<NaiveDateTime>,
+}
+
+/// One contract of an index token on a network
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexDeployment {
+    /// Network name as sent with blockchain events (e.g. "base")
+    pub network: String,
+    pub contract_address: String,
+}
+
+/// Request body for PUT /admin/indexes/{index_id}/deployments
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIndexDeploymentsRequest {
+    pub deployments: Vec<IndexDeployment>,
+}
+
+/// Request body for PUT /admin/indexes/{index_id}/status
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIndexStatusRequest {
+    /// active, paused or retired
+    pub status: String,
+}
+
+/// Response for PUT /admin/indexes/{index_id}/status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatusResponse {
+    pub index_id: i32,
+    pub status: String,
+}
+
+/// Response for GET/PUT /admin/indexes/{index_id}/deployments
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexDeploymentsResponse {
+    pub index_id: i32,
+    pub deployments: Vec<IndexDeployment>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,9 +653,48 @@ mod tests {
             weight_strategy: "equal".to_string(),
             weight_threshold: None,
             blacklisted_categories: None,
+            constraints: None,
+            cash_buffer_pct: None,
+            cash_buffer_symbol: None,
+            accrue_yield: false,
+            leverage_factor: None,
+            use_observed_spread: false,
         }
     }
 
+    #[test]
+    fn test_constraints_validation() {
+        let constraints: IndexConstraints = serde_json::from_value(serde_json::json!({
+            "maxCategoryWeight": {"meme-token": 25},
+            "maxConstituents": 20,
+            "minConstituents": 5
+        }))
+        .unwrap();
+        assert_eq!(constraints.max_category_weight["meme-token"], dec!(25));
+        assert!(constraints.validate().is_ok());
+        assert!(IndexConstraints::default().is_empty());
+
+        let over_100 = IndexConstraints {
+            max_category_weight: [("meme-token".to_string(), dec!(120))].into(),
+            ..Default::default()
+        };
+        assert!(over_100.validate().is_err());
+
+        let min_above_max = IndexConstraints {
+            max_constituents: Some(5),
+            min_constituents: Some(10),
+            ..Default::default()
+        };
+        assert!(min_above_max.validate().is_err());
+        assert!(IndexConstraints { max_constituents: Some(0), ..Default::default() }.validate().is_err());
+
+        let volume_floor: IndexConstraints =
+            serde_json::from_value(serde_json::json!({"minPairVolumeUsd": 5000000})).unwrap();
+        assert_eq!(volume_floor.min_pair_volume_usd, Some(dec!(5000000)));
+        assert!(!volume_floor.is_empty());
+        assert!(IndexConstraints { min_pair_volume_usd: Some(dec!(0)), ..Default::default() }.validate().is_err());
+    }
+
     #[test]
     fn test_top_x_validation_valid_range() {
         let mut req = create_test_request();
@@ -744,3 +1018,22 @@ mod tests {
         assert!(json.contains("\"portfolioValue\":\"1000.0\""));
     }
 }
+
+/// Query parameters for GET /indexes/{index_id}/sparkline
+#[derive(Debug, Clone, Deserialize)]
+pub struct SparklineQuery {
+    /// Days of history (default: 30)
+    pub days: Option<i64>,
+    /// Most points returned (default: 60)
+    pub points: Option<usize>,
+}
+
+/// Response model for GET /indexes/{index_id}/sparkline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparklineResponse {
+    pub index_id: i32,
+    pub days: i64,
+    /// [Unix timestamp of the day's cutoff, price], oldest first
+    pub points: Vec<(i64, f64)>,
+}
@@ -0,0 +1,25 @@
+//! Index directory models for GET /index-categories
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexCategoryEntry {
+    pub category: Option<String>,
+    pub asset_class: Option<String>,
+    pub index_count: i64,
+    /// Sum of the indexes' latest recorded TVL
+    pub total_tvl_usd: f64,
+    /// Mean year-to-date return of the indexes priced on January 1st, in
+    /// percent; null when none is
+    pub avg_ytd_return: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexCategoriesResponse {
+    /// Returns run from January 1st to the latest price up to this day
+    pub as_of: NaiveDate,
+    pub categories: Vec<IndexCategoryEntry>,
+}
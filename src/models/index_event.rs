@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::index::BackfillStatusResponse;
+
+/// Event of GET /events/indexes/{index_id}; `name` is its SSE event type
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum IndexEvent {
+    /// The backfill task was created or made progress
+    Backfill(BackfillStatusResponse),
+    /// A rebalance was created
+    RebalanceCreated(RebalanceEvent),
+    /// A rebalance's weight update was submitted on-chain
+    RebalanceDeployed(RebalanceEvent),
+    /// An ITP of the index was created or changed state
+    Itp(ItpStatusEvent),
+}
+
+impl IndexEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexEvent::Backfill(_) => "backfill",
+            IndexEvent::RebalanceCreated(_) => "rebalance-created",
+            IndexEvent::RebalanceDeployed(_) => "rebalance-deployed",
+            IndexEvent::Itp(_) => "itp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebalanceEvent {
+    pub index_id: i32,
+    pub rebalance_id: i32,
+    /// Unix timestamp the rebalance takes effect at
+    pub timestamp: i64,
+    pub rebalance_type: String,
+    pub deployed: bool,
+    pub tx_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItpStatusEvent {
+    pub index_id: i32,
+    pub itp_id: i32,
+    pub symbol: String,
+    pub orbit_address: String,
+    pub arbitrum_address: Option<String>,
+    /// 0=initiated, 1=active/approved, 2=paused, 3=deprecated
+    pub state: i16,
+    pub deploy_tx_hash: Option<String>,
+}
@@ -0,0 +1,36 @@
+//! Index translation models for the /indexes/{index_id}/translations endpoints
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::index_translations;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertIndexTranslationRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTranslationResponse {
+    pub index_id: i32,
+    pub locale: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<index_translations::Model> for IndexTranslationResponse {
+    fn from(m: index_translations::Model) -> Self {
+        Self {
+            index_id: m.index_id,
+            locale: m.locale,
+            name: m.name,
+            description: m.description,
+            updated_at: m.updated_at,
+        }
+    }
+}
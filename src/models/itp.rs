@@ -5,6 +5,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::composition::CompositionLimitOverrides;
+
 /// Request to create a new ITP via BridgeProxy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateItpRequest {
@@ -35,10 +37,16 @@ pub struct CreateItpRequest {
     /// Wait for bridge confirmation (default: false)
     #[serde(default)]
     pub sync: bool,
+    /// Only simulate the creation against the latest block, don't send it (default: false)
+    #[serde(default)]
+    pub dry_run: bool,
     /// Admin/issuer wallet address (Story 2-3 AC#6)
     /// Used to associate the ITP with its creator for portfolio views
     #[serde(default)]
     pub admin_address: Option<String>,
+    /// Tighter weight/asset-count limits than the server-wide ones
+    #[serde(default)]
+    pub limits: Option<CompositionLimitOverrides>,
 }
 
 /// Default max order size: 1000 USDC (6 decimals)
@@ -76,6 +84,17 @@ pub struct CreateItpSyncResponse {
     pub status: String,
 }
 
+/// Response when dry_run=true
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateItpSimulationResponse {
+    /// Block the creation was simulated against
+    pub block_number: u64,
+    /// Gas the transaction would use
+    pub gas_estimate: u64,
+    /// Always "simulated"
+    pub status: String,
+}
+
 /// Error response for ITP creation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItpErrorResponse {
@@ -84,6 +103,24 @@ pub struct ItpErrorResponse {
     /// Error code for programmatic handling
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
+    /// Every problem found with the requested assets (code INVALID_ASSETS)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<ItpValidationViolation>,
+}
+
+/// One problem with a requested asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItpValidationViolation {
+    /// Position in asset_ids
+    pub index: usize,
+    pub asset_id: u128,
+    /// Registry symbol, if the asset is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// UNKNOWN_ASSET, DUPLICATE_ASSET, NOT_TRADEABLE, EXCLUDED_ASSET,
+    /// WEIGHT_ABOVE_CAP or WEIGHT_BELOW_MIN
+    pub code: String,
+    pub message: String,
 }
 
 /// Query params for status check
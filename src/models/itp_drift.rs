@@ -0,0 +1,37 @@
+//! ITP composition drift models for GET /api/itp/{address}/drift
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// One constituent of a drift check, stored in itp_drift_checks.constituents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItpDriftConstituent {
+    pub symbol: String,
+    /// Target weight (1.0 = 100%)
+    pub target_weight: f64,
+    /// Weight at current prices after drifting from the target since base_date
+    pub current_weight: f64,
+    /// current_weight - target_weight, in basis points
+    pub drift_bps: i32,
+    /// Price on the base date
+    pub base_price: Decimal,
+    /// Latest stored price
+    pub price: Decimal,
+}
+
+/// Response of GET /api/itp/{address}/drift: the ITP's latest drift check
+#[derive(Debug, Clone, Serialize)]
+pub struct ItpDriftResponse {
+    pub orbit_address: String,
+    pub symbol: String,
+    pub date: NaiveDate,
+    pub base_date: NaiveDate,
+    pub max_drift_bps: i32,
+    pub total_drift_bps: i32,
+    pub threshold_bps: i32,
+    /// max_drift_bps reached threshold_bps
+    pub rebalance_due: bool,
+    pub checked_at: NaiveDateTime,
+    pub constituents: Vec<ItpDriftConstituent>,
+}
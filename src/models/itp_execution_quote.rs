@@ -0,0 +1,67 @@
+//! ITP execution quote models for GET /api/itp/{address}/execution-quote
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionQuoteQuery {
+    /// Mint size in USD
+    pub notional: Option<f64>,
+}
+
+/// Cost of buying one constituent's share of the mint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionQuoteLeg {
+    pub symbol: String,
+    /// Target weight (1.0 = 100%)
+    pub weight: f64,
+    pub exchange: String,
+    /// e.g. "BTCUSDC"
+    pub trading_pair: String,
+    /// USD to spend on this leg (weight * notional)
+    pub notional: f64,
+    /// USD the fetched asks could absorb; below `notional` when the book is too thin
+    pub filled_notional: f64,
+    pub quantity: f64,
+    pub mid_price: f64,
+    pub avg_price: f64,
+    /// Average price above mid, in basis points
+    pub slippage_bps: f64,
+    pub slippage_usd: f64,
+    pub fee_bps: f64,
+    pub fee_usd: f64,
+    /// USD value of all fetched ask levels
+    pub ask_depth_usd: f64,
+    pub fully_filled: bool,
+    /// The pair's own 24h USD volume on `exchange`, from the latest daily
+    /// snapshot; None when it hasn't been sampled
+    pub volume_24h_usd: Option<f64>,
+    /// `notional` as a percentage of volume_24h_usd
+    pub volume_share_pct: Option<f64>,
+}
+
+/// A constituent that could not be quoted
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnquotedLeg {
+    pub symbol: String,
+    pub weight: f64,
+    pub reason: String,
+}
+
+/// Response of GET /api/itp/{address}/execution-quote
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionQuoteResponse {
+    pub orbit_address: String,
+    pub symbol: String,
+    pub notional: f64,
+    pub legs: Vec<ExecutionQuoteLeg>,
+    /// Constituents that aren't tradeable or whose order book failed to load
+    pub unquoted: Vec<UnquotedLeg>,
+    pub total_slippage_usd: f64,
+    pub total_fees_usd: f64,
+    /// Slippage plus fees
+    pub total_cost_usd: f64,
+    /// total_cost_usd relative to the quoted notional, in basis points
+    pub total_cost_bps: f64,
+    /// Every constituent was quoted and filled within the fetched depth
+    pub fully_fillable: bool,
+}
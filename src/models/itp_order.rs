@@ -0,0 +1,65 @@
+//! ITP order models for /api/itp/{address}/orders and /api/itp/orders/{order_id}
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::itp_orders;
+
+/// Request for POST /api/itp/{address}/orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateItpOrderRequest {
+    /// "mint" or "redeem"
+    pub side: String,
+    /// Collateral (USDC) to mint with, or ITP tokens to redeem
+    pub amount: Decimal,
+    /// Transaction the order was submitted in; when given, only an event of
+    /// this transaction fills the order
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+}
+
+/// An order and its progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItpOrderResponse {
+    pub order_id: String,
+    /// ITP contract address on Orbit chain
+    pub itp_address: String,
+    pub wallet_address: String,
+    pub side: String,
+    pub amount: Decimal,
+    /// "pending", "filled" or "settled"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filled_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settle_tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl ItpOrderResponse {
+    pub fn new(order: itp_orders::Model, itp_address: &str) -> Self {
+        Self {
+            order_id: order.order_id,
+            itp_address: itp_address.to_string(),
+            wallet_address: order.wallet_address,
+            side: order.side,
+            amount: order.amount,
+            status: order.status,
+            submit_tx_hash: order.submit_tx_hash,
+            fill_tx_hash: order.fill_tx_hash,
+            filled_at: order.filled_at,
+            settle_tx_hash: order.settle_tx_hash,
+            settled_at: order.settled_at,
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Dead-letter queue models for the /admin/job-failures endpoints
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::job_failures;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobFailuresQuery {
+    /// pending, resolved, discarded, exhausted (default: all)
+    pub status: Option<String>,
+    /// Filter by job name (e.g. coins_historical_prices_sync)
+    pub job: Option<String>,
+    /// Max results (default: 100, max: 1000)
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFailureResponse {
+    pub id: i32,
+    pub job_name: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub payload: Option<serde_json::Value>,
+    pub error: String,
+    pub attempt_count: i32,
+    pub status: String,
+    pub next_retry_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<job_failures::Model> for JobFailureResponse {
+    fn from(m: job_failures::Model) -> Self {
+        Self {
+            id: m.id,
+            job_name: m.job_name,
+            entity_type: m.entity_type,
+            entity_id: m.entity_id,
+            payload: m.payload,
+            error: m.error,
+            attempt_count: m.attempt_count,
+            status: m.status,
+            next_retry_at: m.next_retry_at,
+            created_at: m.created_at,
+            updated_at: m.updated_at,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Label models for the /admin/labels endpoints and label filters
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::labels;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLabelRequest {
+    /// "index" or "coin"
+    pub entity_type: String,
+    /// Index id or CoinGecko coin id
+    pub entity_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelsQuery {
+    /// "index" or "coin" (default: both)
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+}
+
+/// Label filter of list endpoints, e.g. `?label=theme:ai,tier:core`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LabelFilterQuery {
+    /// Comma-separated `key` or `key:value` filters, all of which must match
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelResponse {
+    pub id: i32,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub key: String,
+    pub value: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<labels::Model> for LabelResponse {
+    fn from(m: labels::Model) -> Self {
+        Self {
+            id: m.id,
+            entity_type: m.entity_type,
+            entity_id: m.entity_id,
+            key: m.key,
+            value: m.value,
+            created_at: m.created_at,
+        }
+    }
+}
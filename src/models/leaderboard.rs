@@ -0,0 +1,41 @@
+//! Index leaderboard models for GET /indexes/leaderboard
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeaderboardQuery {
+    /// 7d, 30d or ytd (default: 30d)
+    pub window: Option<String>,
+    /// return, volume or tvl (default: return)
+    pub metric: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub index_id: i32,
+    pub name: String,
+    pub ticker: String,
+    /// Value of the ranking metric
+    pub value: f64,
+    /// Price change over the window, in percent; null without a price at the window start
+    pub return_pct: Option<f64>,
+    /// USD collateral minted and redeemed during the window
+    pub volume_usd: f64,
+    /// Net supply at the latest price
+    pub tvl_usd: f64,
+    pub latest_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardResponse {
+    pub window: String,
+    pub metric: String,
+    /// First day of the window
+    pub start_date: NaiveDate,
+    pub as_of: NaiveDate,
+    pub entries: Vec<LeaderboardEntry>,
+}
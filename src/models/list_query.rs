@@ -0,0 +1,12 @@
+//! Filter and sort parameters shared by list endpoints
+
+use serde::Deserialize;
+
+/// `?filter=field:value,...&sort=-field,...`, see services::list_query
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListQuery {
+    /// Comma-separated `field:value` filters, all of which must match
+    pub filter: Option<String>,
+    /// Comma-separated fields, `-` prefixed for descending
+    pub sort: Option<String>,
+}
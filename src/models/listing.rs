@@ -0,0 +1,102 @@
+//! Exchange listing models for GET /api/listings and GET /crypto-listings
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{announcements, crypto_listings};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListingsQuery {
+    pub coin_id: Option<String>,
+    pub exchange: Option<String>,
+    /// active or delisted
+    pub status: Option<String>,
+    /// Maximum number of results (default: 100, max: 1000)
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CryptoListingsQuery {
+    pub exchange: Option<String>,
+    /// active or delisted
+    pub status: Option<String>,
+    /// Case-insensitive, e.g. btc
+    pub symbol: Option<String>,
+    /// Page number, from 1 (default: 1)
+    pub page: Option<u64>,
+    /// Listings per page (default: 100, max: 1000)
+    pub page_size: Option<u64>,
+    /// json (default) or csv
+    pub format: Option<String>,
+}
+
+/// The announcement a listing's dates were taken from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceAnnouncement {
+    pub id: i32,
+    pub title: String,
+    pub source: String,
+    pub announce_date: NaiveDateTime,
+    pub url: Option<String>,
+}
+
+impl From<announcements::Model> for SourceAnnouncement {
+    fn from(m: announcements::Model) -> Self {
+        Self {
+            id: m.id,
+            title: m.title,
+            source: m.source,
+            announce_date: m.announce_date,
+            url: m.url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingResponse {
+    pub id: i32,
+    pub coin_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub trading_pair: String,
+    pub status: String,
+    pub listing_announcement_date: Option<NaiveDateTime>,
+    pub listing_date: Option<NaiveDateTime>,
+    pub delisting_announcement_date: Option<NaiveDateTime>,
+    pub delisting_date: Option<NaiveDateTime>,
+    pub source_announcement_id: Option<i32>,
+    pub source_announcement: Option<SourceAnnouncement>,
+}
+
+impl ListingResponse {
+    pub fn new(listing: crypto_listings::Model, announcement: Option<announcements::Model>) -> Self {
+        Self {
+            id: listing.id,
+            coin_id: listing.coin_id,
+            symbol: listing.symbol,
+            exchange: listing.exchange,
+            trading_pair: listing.trading_pair,
+            status: listing.status,
+            listing_announcement_date: listing.listing_announcement_date,
+            listing_date: listing.listing_date,
+            delisting_announcement_date: listing.delisting_announcement_date,
+            delisting_date: listing.delisting_date,
+            source_announcement_id: listing.source_announcement_id,
+            source_announcement: announcement.map(SourceAnnouncement::from),
+        }
+    }
+}
+
+/// A page of GET /crypto-listings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoListingsPage {
+    pub listings: Vec<ListingResponse>,
+    /// Listings matching the filters
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+    pub total_pages: u64,
+}
@@ -9,6 +9,17 @@ pub struct MarketCapHistoryQuery {
     pub end_date: Option<String>,   // YYYY-MM-DD format
 }
 
+/// Most coins a POST /api/market-cap/history/batch request may ask for
+pub const MAX_BATCH_COINS: usize = 100;
+
+/// Body of POST /api/market-cap/history/batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketCapHistoryBatchRequest {
+    pub coin_ids: Vec<String>,
+    pub start_date: Option<String>, // YYYY-MM-DD format
+    pub end_date: Option<String>,   // YYYY-MM-DD format
+}
+
 /// Query parameters for GET /api/market-cap/top-category
 #[derive(Debug, Clone, Deserialize)]
 pub struct TopCategoryQuery {
@@ -17,6 +28,52 @@ pub struct TopCategoryQuery {
     pub date: Option<String>,    // YYYY-MM-DD format, Default: today
 }
 
+/// Query parameters for GET /api/market-cap/top
+#[derive(Debug, Deserialize)]
+pub struct TopMarketCapQuery {
+    pub date: Option<String>, // YYYY-MM-DD format, Default: today
+    pub n: Option<u32>,       // Default: 10, Max: 250
+}
+
+/// One coin's history on the dates of a batch response; None where the coin
+/// has no data that day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedMarketCapSeries {
+    pub coin_id: String,
+    pub symbol: String,
+    pub market_caps: Vec<Option<f64>>,
+    pub prices: Vec<Option<f64>>,
+    pub volumes: Vec<Option<f64>>,
+}
+
+/// Response structure for the batch market cap history endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketCapHistoryBatchResponse {
+    pub start_date: String,
+    pub end_date: String,
+    /// Every day of the range, the index into each series' values
+    pub dates: Vec<String>,
+    pub coins: Vec<AlignedMarketCapSeries>,
+    /// Requested coins not in the system
+    pub missing: Vec<String>,
+}
+
+/// Response structure for the global top-N endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopMarketCapResponse {
+    pub date: String,
+    pub n: u32,
+    /// First and last day of the prices ranked: the date itself, or its
+    /// week when the date is past the price retention cutoff and history
+    /// is kept weekly (each coin is ranked on its last price of the week)
+    pub prices_from: String,
+    pub prices_to: String,
+    /// Past the retention cutoff in drop mode: only coins used by an index
+    /// still have prices, so the ranking leaves out every other coin
+    pub partial: bool,
+    pub coins: Vec<TopCategoryCoin>,
+}
+
 /// Single coin in top category response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopCategoryCoin {
@@ -90,6 +147,44 @@ impl MarketCapHistoryQuery {
     }
 }
 
+impl MarketCapHistoryBatchRequest {
+    /// Validates the request body
+    pub fn validate(&self) -> Result<(), String> {
+        if self.coin_ids.is_empty() {
+            return Err("coin_ids cannot be empty".to_string());
+        }
+        if self.coin_ids.len() > MAX_BATCH_COINS {
+            return Err(format!(
+                "At most {} coin_ids per request, got: {}",
+                MAX_BATCH_COINS,
+                self.coin_ids.len()
+            ));
+        }
+        if self.coin_ids.iter().any(|id| id.trim().is_empty()) {
+            return Err("coin_ids cannot contain empty values".to_string());
+        }
+
+        for (name, date) in [("start_date", &self.start_date), ("end_date", &self.end_date)] {
+            if let Some(date) = date
+                && chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err()
+            {
+                return Err(format!("Invalid {} format: '{}'. Expected YYYY-MM-DD", name, date));
+            }
+        }
+        Ok(())
+    }
+
+    /// Requested coin ids, trimmed, without duplicates, in request order
+    pub fn unique_coin_ids(&self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        self.coin_ids
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| seen.insert(id.clone()))
+            .collect()
+    }
+}
+
 impl TopCategoryQuery {
     /// Validates query parameters
     pub fn validate(&self) -> Result<(), String> {
@@ -127,6 +222,28 @@ impl TopCategoryQuery {
     }
 }
 
+impl TopMarketCapQuery {
+    /// Validates query parameters
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(n) = self.n
+            && !(1..=250).contains(&n)
+        {
+            return Err(format!("n must be between 1 and 250, got: {}", n));
+        }
+        if let Some(ref date_str) = self.date
+            && chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d").is_err()
+        {
+            return Err(format!("Invalid date format: '{}'. Expected YYYY-MM-DD", date_str));
+        }
+        Ok(())
+    }
+
+    /// Get the n value with default of 10
+    pub fn get_n(&self) -> u32 {
+        self.n.unwrap_or(10)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,4 +515,37 @@ mod tests {
         assert!(json.contains("2025-01-12"));
         assert!(json.contains("coins"));
     }
+
+    // TopMarketCapQuery tests
+    #[test]
+    fn test_top_market_cap_query() {
+        let query = |date: Option<&str>, n| TopMarketCapQuery {
+            date: date.map(str::to_string),
+            n,
+        };
+        assert!(query(Some("2025-01-12"), Some(250)).validate().is_ok());
+        assert_eq!(query(None, None).get_n(), 10);
+        assert!(query(None, Some(0)).validate().unwrap_err().contains("between 1 and 250"));
+        assert!(query(None, Some(251)).validate().is_err());
+        assert!(query(Some("2025/01/12"), None).validate().unwrap_err().contains("Invalid date format"));
+    }
+
+    // MarketCapHistoryBatchRequest tests
+    #[test]
+    fn test_batch_request_validation() {
+        let request = |coin_ids: Vec<&str>, start_date: Option<&str>| MarketCapHistoryBatchRequest {
+            coin_ids: coin_ids.into_iter().map(str::to_string).collect(),
+            start_date: start_date.map(str::to_string),
+            end_date: None,
+        };
+        assert!(request(vec!["bitcoin", "ethereum"], Some("2025-01-01")).validate().is_ok());
+        assert!(request(vec![], None).validate().unwrap_err().contains("cannot be empty"));
+        assert!(request(vec!["bitcoin", " "], None).validate().is_err());
+        assert!(request(vec!["bitcoin"; MAX_BATCH_COINS + 1], None).validate().unwrap_err().contains("At most"));
+        assert!(request(vec!["bitcoin"], Some("01/01/2025")).validate().unwrap_err().contains("start_date"));
+        assert_eq!(
+            request(vec!["bitcoin", "ethereum ", "bitcoin"], None).unique_coin_ids(),
+            vec!["bitcoin", "ethereum"]
+        );
+    }
 }
@@ -0,0 +1,79 @@
+//! Index methodology document models
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::methodology_documents;
+
+/// Request body for POST /admin/indexes/{index_id}/methodology
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMethodologyRequest {
+    /// "markdown" (default) or "text"
+    pub format: Option<String>,
+    pub content: String,
+    /// First day the new version applies to (default: today)
+    pub effective_from: Option<NaiveDate>,
+}
+
+/// A methodology version without its content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodologyVersionRef {
+    pub version: i32,
+    pub effective_from: NaiveDate,
+    /// Where the full document is served
+    pub url: String,
+}
+
+/// Response for GET /indexes/{index_id}/methodology
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodologyVersionsResponse {
+    pub index_id: i32,
+    /// Version in effect today, if any
+    pub current_version: Option<i32>,
+    /// All versions, newest first
+    pub versions: Vec<MethodologyVersionRef>,
+}
+
+/// One full methodology version
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodologyDocument {
+    pub index_id: i32,
+    pub version: i32,
+    pub format: String,
+    pub content: String,
+    pub effective_from: NaiveDate,
+    pub created_at: NaiveDateTime,
+}
+
+impl MethodologyVersionRef {
+    pub fn url(index_id: i32, version: i32) -> String {
+        format!("/indexes/{}/methodology/{}", index_id, version)
+    }
+}
+
+impl From<&methodology_documents::Model> for MethodologyVersionRef {
+    fn from(document: &methodology_documents::Model) -> Self {
+        Self {
+            version: document.version,
+            effective_from: document.effective_from,
+            url: MethodologyVersionRef::url(document.index_id, document.version),
+        }
+    }
+}
+
+impl From<methodology_documents::Model> for MethodologyDocument {
+    fn from(document: methodology_documents::Model) -> Self {
+        Self {
+            index_id: document.index_id,
+            version: document.version,
+            format: document.format,
+            content: document.content,
+            effective_from: document.effective_from,
+            created_at: document.created_at,
+        }
+    }
+}
@@ -14,3 +14,37 @@ pub mod itp;
 pub mod itp_history;
 pub mod itp_listing;
 pub mod operation;
+pub mod data_freshness;
+pub mod job_failure;
+pub mod supply_reconciliation;
+pub mod price_retention;
+pub mod price_reconciliation;
+pub mod methodology;
+pub mod api_key;
+pub mod auth;
+pub mod statement;
+pub mod composition;
+pub mod itp_drift;
+pub mod itp_order;
+pub mod solver;
+pub mod itp_execution_quote;
+pub mod label;
+pub mod listing;
+pub mod feature_flag;
+pub mod leaderboard;
+pub mod tvl;
+pub mod stats;
+pub mod rebalance_approval;
+pub mod chain_spend;
+pub mod health;
+pub mod yield_rate;
+pub mod index_translation;
+pub mod analytics;
+pub mod snapshot;
+pub mod list_query;
+pub mod index_event;
+pub mod index_category;
+pub mod report;
+pub mod backtest;
+pub mod coin_merge;
+pub mod portfolio_continuity;
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 /// Query parameters for GET /api/exchange/tradeable-pairs
@@ -207,3 +208,38 @@ mod tests {
         assert!(json.contains("600"));
     }
 }
+
+/// Query parameters for GET /exchanges/{exchange}/pairs
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExchangePairsQuery {
+    pub symbol: Option<String>,        // Base asset, e.g. "BTC"
+}
+
+/// Live status of one USDC/USDT pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePairStatus {
+    pub trading_pair: String,          // "BTCUSDC"
+    pub quote_currency: String,        // "USDC" or "USDT"
+    pub status: String,                // "trading" or "unavailable"
+}
+
+/// Listing history of one pair, from scraped exchange announcements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairAvailability {
+    pub trading_pair: String,          // Quote asset as recorded, e.g. "usdt"
+    pub status: String,                // "active" or "delisted"
+    pub listing_date: Option<NaiveDateTime>,
+    pub delisting_announcement_date: Option<NaiveDateTime>,
+    pub delisting_date: Option<NaiveDateTime>,
+}
+
+/// Response structure for exchange pairs endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangePairsResponse {
+    pub exchange: String,
+    pub symbol: String,
+    pub tradeable: bool,               // Any live pair is trading
+    pub pairs: Vec<LivePairStatus>,
+    pub history: Vec<PairAvailability>,
+    pub cache_expires_in_secs: u64,
+}
@@ -0,0 +1,45 @@
+//! Portfolio continuity models for GET /admin/indexes/{index_id}/continuity
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PortfolioContinuityQuery {
+    /// Largest accepted deviation in bps (default: 10)
+    pub tolerance_bps: Option<u32>,
+}
+
+/// Continuity of an index's value across its rebalances
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioContinuityReport {
+    pub index_id: i32,
+    pub tolerance_bps: u32,
+    /// Rebalances compared with the one before them
+    pub checked: usize,
+    pub flagged: usize,
+    /// Every check, oldest first
+    pub checks: Vec<PortfolioContinuityCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioContinuityCheck {
+    pub rebalance_id: i32,
+    pub date: NaiveDate,
+    pub rebalance_type: String,
+    pub previous_rebalance_id: i32,
+    /// The rebalance's portfolio value plus the fees it paid
+    pub value_before_fees: Decimal,
+    /// The previous basket at the rebalance date's prices; None when a
+    /// price is missing
+    pub marked_value: Option<Decimal>,
+    pub deviation_bps: Option<i32>,
+    /// False for rebalances stored without their fees; their value before
+    /// fees is the net value, so fees show up as deviation
+    pub fees_recorded: bool,
+    /// Coins of the previous basket without a stored price on the date
+    pub missing_prices: Vec<String>,
+    pub flagged: bool,
+}
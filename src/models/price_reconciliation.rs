@@ -0,0 +1,56 @@
+//! Price reconciliation models for GET /admin/price-reconciliation
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::price_reconciliation_checks;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceReconciliationQuery {
+    /// Days of checks to summarize (default: 30, max: 365)
+    pub days: Option<u32>,
+}
+
+/// Deviation statistics of stored prices against the reference source
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceReconciliationReport {
+    /// Checks dated on or after this day are included
+    pub since: NaiveDate,
+    pub checks: usize,
+    pub flagged: usize,
+    pub median_deviation_bps: Option<i32>,
+    pub p95_deviation_bps: Option<i32>,
+    pub max_deviation_bps: Option<i32>,
+    /// Checks above the alert threshold, newest first
+    pub flagged_checks: Vec<PriceReconciliationCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceReconciliationCheck {
+    pub coin_id: String,
+    pub symbol: String,
+    pub date: NaiveDate,
+    pub source: String,
+    pub stored_price: Decimal,
+    pub reference_price: Decimal,
+    pub deviation_bps: i32,
+    pub checked_at: NaiveDateTime,
+}
+
+impl From<price_reconciliation_checks::Model> for PriceReconciliationCheck {
+    fn from(m: price_reconciliation_checks::Model) -> Self {
+        Self {
+            coin_id: m.coin_id,
+            symbol: m.symbol,
+            date: m.date,
+            source: m.source,
+            stored_price: m.stored_price,
+            reference_price: m.reference_price,
+            deviation_bps: m.deviation_bps,
+            checked_at: m.checked_at,
+        }
+    }
+}
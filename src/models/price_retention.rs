@@ -0,0 +1,23 @@
+//! Coins historical price retention report
+//!
+//! Returned by GET /admin/price-retention (always a dry run) and logged by
+//! the price retention job after each run.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceRetentionReport {
+    /// "downsample" (keep one row per coin and week) or "drop"
+    pub mode: String,
+    pub dry_run: bool,
+    /// Rows dated before this are subject to retention
+    pub cutoff_date: NaiveDate,
+    /// Coins used by any index (constituents or past rebalances); never touched
+    pub referenced_coins: i64,
+    /// Unreferenced coins with rows to remove
+    pub affected_coins: i64,
+    /// Rows removed, or that would be removed in a dry run
+    pub rows_removed: i64,
+}
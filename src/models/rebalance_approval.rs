@@ -0,0 +1,36 @@
+//! Rebalance approval models for the /admin/rebalances/{id} endpoints
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::entities::rebalance_approvals;
+use crate::services::rebalance_approvals::effective_status;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebalanceApprovalResponse {
+    pub id: i32,
+    pub rebalance_id: i32,
+    /// pending, approved, rejected or expired
+    pub status: String,
+    pub requested_by: String,
+    pub decided_by: Option<String>,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub decided_at: Option<NaiveDateTime>,
+}
+
+impl RebalanceApprovalResponse {
+    pub fn new(m: rebalance_approvals::Model, now: NaiveDateTime) -> Self {
+        Self {
+            status: effective_status(&m, now).to_string(),
+            id: m.id,
+            rebalance_id: m.rebalance_id,
+            requested_by: m.requested_by,
+            decided_by: m.decided_by,
+            expires_at: m.expires_at,
+            created_at: m.created_at,
+            decided_at: m.decided_at,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Scheduled report models for the /admin/reports endpoints
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::services::reports::ReportSummary;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportsQuery {
+    /// weekly_performance or monthly_rebalance (default: all)
+    pub template: Option<String>,
+    /// Max results (default: 100, max: 1000)
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportResponse {
+    pub id: i32,
+    pub template: String,
+    /// html, pdf or csv
+    pub format: String,
+    pub period_start: NaiveDate,
+    /// Last day covered, inclusive
+    pub period_end: NaiveDate,
+    pub title: String,
+    pub content_type: String,
+    /// Size of the artifact in bytes
+    pub size: i32,
+    /// Subscribers the report was emailed to
+    pub recipients: i32,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ReportSummary> for ReportResponse {
+    fn from(r: ReportSummary) -> Self {
+        Self {
+            id: r.id,
+            template: r.template,
+            format: r.format,
+            period_start: r.period_start,
+            period_end: r.period_end,
+            title: r.title,
+            content_type: r.content_type,
+            size: r.size,
+            recipients: r.recipients,
+            delivered_at: r.delivered_at,
+            created_at: r.created_at,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Snapshot log models for GET /indexes/{index_id}/snapshot-proof/{date}
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotEntry {
+    pub seq: i32,
+    /// "rebalance" or "daily_price"
+    pub kind: String,
+    pub record_key: String,
+    /// The record as logged; hash its normalized JSON to check `payload_hash`
+    pub payload: serde_json::Value,
+    pub payload_hash: String,
+    pub prev_hash: Option<String>,
+    pub hash: String,
+    /// Whether the record currently served still matches the logged one
+    pub matches_current: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotProofResponse {
+    pub index_id: i32,
+    pub date: NaiveDate,
+    pub entries: Vec<SnapshotEntry>,
+    /// Payload hashes of the entries after the date's last one, in order:
+    /// folding `hash = keccak256(hash ‖ payload_hash)` over them from that
+    /// entry's hash yields `head_hash`
+    pub path: Vec<String>,
+    pub head_seq: i32,
+    pub head_hash: String,
+    /// Whether every stored hash recomputes from the chain's first entry
+    pub chain_valid: bool,
+    /// First entry from the date's last one on whose hash was posted
+    /// on-chain: the first `anchor.seq - entries.last().seq` items of `path`
+    /// lead to it
+    pub anchor: Option<SnapshotAnchor>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotAnchor {
+    pub seq: i32,
+    pub hash: String,
+    pub tx_hash: String,
+    pub block_number: Option<i64>,
+    pub anchored_at: Option<chrono::NaiveDateTime>,
+}
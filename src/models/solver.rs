@@ -0,0 +1,50 @@
+//! Solver registry models for /api/solvers
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Request body for POST /api/solvers/register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterSolverRequest {
+    pub name: String,
+    /// Asset symbols the solver can trade, e.g. ["BTC", "ETH"]
+    pub assets: Vec<String>,
+    /// Exchanges the solver executes on, e.g. ["bitget"]
+    pub exchanges: Vec<String>,
+    /// http(s) URL the solver can be reached at
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// Query parameters for GET /api/solvers
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolversQuery {
+    /// Only solvers supporting this asset symbol
+    pub asset: Option<String>,
+    /// Only solvers executing on this exchange
+    pub exchange: Option<String>,
+    /// Only solvers whose last heartbeat is recent (true) or stale (false)
+    pub online: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverResponse {
+    pub id: i32,
+    pub address: String,
+    pub name: String,
+    pub assets: Vec<String>,
+    pub exchanges: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub registered_at: NaiveDateTime,
+    pub last_heartbeat_at: Option<NaiveDateTime>,
+    /// Heartbeat received within the timeout
+    pub online: bool,
+}
@@ -0,0 +1,95 @@
+//! Annual wallet statement models for GET /me/statements
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatementQuery {
+    /// Calendar year, UTC (default: current year)
+    pub year: Option<i32>,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
+
+/// One wallet's index token activity over a calendar year
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnualStatement {
+    pub address: String,
+    pub year: i32,
+    pub generated_at: NaiveDateTime,
+    pub totals: StatementTotals,
+    pub indexes: Vec<IndexStatement>,
+    /// Mints and burns in the year, oldest first
+    pub events: Vec<StatementEvent>,
+    /// Mints and burns up to the year's end left out of events and
+    /// positions because they couldn't be read
+    pub excluded: Vec<ExcludedEvent>,
+}
+
+/// USD totals over all indexes
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementTotals {
+    /// Collateral paid for mints
+    pub invested: Decimal,
+    /// Collateral received for burns
+    pub withdrawn: Decimal,
+    pub fees: Decimal,
+    pub realized_pnl: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatement {
+    pub index_id: i32,
+    pub symbol: String,
+    pub minted_quantity: Decimal,
+    pub burned_quantity: Decimal,
+    pub invested: Decimal,
+    pub withdrawn: Decimal,
+    pub fees: Decimal,
+    pub realized_pnl: Decimal,
+    /// Position carried into the next year
+    pub year_end_quantity: Decimal,
+    pub year_end_cost_basis: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementEvent {
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+    pub network: String,
+    pub index_id: i32,
+    pub symbol: String,
+    /// "mint" or "burn"
+    pub event_type: String,
+    pub quantity: Decimal,
+    /// Index price on the event's day; None when no daily price exists
+    pub index_price: Option<Decimal>,
+    /// quantity * index_price (the collateral amount without a price)
+    pub market_value: Decimal,
+    /// Collateral paid (mint) or received (burn)
+    pub amount: Decimal,
+    /// Collateral paid above / received below market value
+    pub fee: Decimal,
+    /// Average cost of the burned quantity (burns only)
+    pub cost_basis: Option<Decimal>,
+    /// market_value - cost_basis (burns only)
+    pub realized_pnl: Option<Decimal>,
+}
+
+/// A mint or burn left out of a statement
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedEvent {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub tx_hash: String,
+    pub network: String,
+    pub index_id: i32,
+    pub symbol: String,
+    pub event_type: String,
+    pub reason: String,
+}
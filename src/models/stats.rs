@@ -0,0 +1,23 @@
+//! Protocol-wide statistics for GET /stats
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolStatsResponse {
+    /// Sum of every index's net supply at its latest price
+    pub total_tvl_usd: f64,
+    pub indexes: usize,
+    /// ITPs that aren't deprecated
+    pub itps_deployed: u64,
+    /// Addresses holding a positive minted-minus-redeemed balance of any index
+    pub unique_holders: usize,
+    /// USD collateral minted in the last 24 hours
+    pub mint_volume_24h_usd: f64,
+    /// USD collateral redeemed in the last 24 hours
+    pub burn_volume_24h_usd: f64,
+    /// Active coins tracked from CoinGecko
+    pub tracked_coins: u64,
+    pub as_of: DateTime<Utc>,
+}
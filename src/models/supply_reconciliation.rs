@@ -0,0 +1,33 @@
+//! Supply reconciliation report models
+//!
+//! Response for GET /admin/indexes/{index_id}/supply-reconciliation: the net
+//! supply implied by ingested blockchain_events next to the on-chain
+//! `totalSupply()` of the index token.
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplyReconciliationResponse {
+    pub index_id: i32,
+    pub address: String,
+    pub network: String,
+    pub decimals: u32,
+    /// Sum of minted quantities from events
+    pub minted: String,
+    /// Sum of burned/withdrawn quantities from events
+    pub burned: String,
+    /// minted - burned
+    pub event_supply: String,
+    /// totalSupply() read on-chain
+    pub onchain_supply: String,
+    /// onchain_supply - event_supply (positive means events are missing mints)
+    pub difference: String,
+    /// |difference| relative to the on-chain supply, in basis points
+    pub difference_bps: Option<String>,
+    pub threshold_bps: u64,
+    pub discrepancy: bool,
+    pub event_count: usize,
+    pub checked_at: NaiveDateTime,
+}
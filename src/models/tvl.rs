@@ -0,0 +1,45 @@
+//! Index TVL models for GET /indexes/{index_id}/tvl-history
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::index_tvl;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TvlHistoryQuery {
+    pub start_date: Option<String>, // YYYY-MM-DD format
+    pub end_date: Option<String>,   // YYYY-MM-DD format
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TvlPoint {
+    pub date: NaiveDate,
+    /// Index tokens outstanding at the end of the day
+    pub net_supply: Decimal,
+    /// Index price (NAV per token) on the day
+    pub nav: Decimal,
+    pub tvl_usd: Decimal,
+}
+
+impl From<index_tvl::Model> for TvlPoint {
+    fn from(m: index_tvl::Model) -> Self {
+        Self {
+            date: m.date,
+            net_supply: m.net_supply,
+            nav: m.nav,
+            tvl_usd: m.tvl_usd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TvlHistoryResponse {
+    pub index_id: i32,
+    pub name: String,
+    pub ticker: String,
+    /// Oldest first
+    pub points: Vec<TvlPoint>,
+}
@@ -0,0 +1,38 @@
+//! Yield rate models for the /admin/yield-rates endpoints
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::coin_yield_rates;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetYieldRateRequest {
+    /// Annual yield in percent, e.g. 3.2
+    pub apy_pct: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YieldRateResponse {
+    pub coin_id: String,
+    pub apy_pct: Decimal,
+    /// "manual" or "defillama"
+    pub source: String,
+    /// DefiLlama pool id of synced rates
+    pub source_ref: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<coin_yield_rates::Model> for YieldRateResponse {
+    fn from(m: coin_yield_rates::Model) -> Self {
+        Self {
+            coin_id: m.coin_id,
+            apy_pct: m.apy_pct,
+            source: m.source,
+            source_ref: m.source_ref,
+            updated_at: m.updated_at,
+        }
+    }
+}
@@ -261,6 +261,7 @@ impl BinanceScraper {
                         symbol: token,
                         trading_pair,
                         announcement_date: article_date,
+                        announcement_title: article.title.clone(),
                         listing_date: if listing_type == "listing" {
                             Some(article_date)
                         } else {
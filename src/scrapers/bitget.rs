@@ -248,6 +248,7 @@ impl BitgetScraper {
                         symbol: token,
                         trading_pair,
                         announcement_date: item_date,
+                        announcement_title: detail.title.clone(),
                         listing_date: if listing_type == "listing" {
                             Some(item_date)
                         } else {
@@ -360,6 +361,14 @@ async fn save_scraped_data(
 
     // Save listings to crypto_listings
     for listing in listings {
+        let source_announcement_id = crate::services::crypto_listings::find_announcement_id(
+            db,
+            &listing.source,
+            listing.announcement_date,
+            Some(&listing.announcement_title),
+        )
+        .await?;
+
         // Check if exists
         let existing = CryptoListings::find()
             .filter(crypto_listings::Column::CoinId.eq(&listing.token.to_lowercase()))
@@ -383,6 +392,10 @@ async fn save_scraped_data(
                 active.status = Set("delisted".to_string());
             }
 
+            if source_announcement_id.is_some() {
+                active.source_announcement_id = Set(source_announcement_id);
+            }
+
             active.updated_at = Set(Some(Utc::now().naive_utc()));
             active.update(db).await?;
         } else {
@@ -410,6 +423,7 @@ async fn save_scraped_data(
                 } else {
                     "active".to_string()
                 }),
+                source_announcement_id: Set(source_announcement_id),
                 ..Default::default()
             };
 
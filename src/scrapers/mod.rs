@@ -13,6 +13,9 @@ pub struct ScrapedListing {
     pub symbol: String,
     pub trading_pair: String,
     pub announcement_date: NaiveDateTime,
+    /// Title of the announcement the listing was parsed from; with source and
+    /// announcement_date it identifies the stored announcement
+    pub announcement_title: String,
     pub listing_date: Option<NaiveDateTime>,
     pub delisting_date: Option<NaiveDateTime>,
     pub source: String,
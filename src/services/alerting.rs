@@ -0,0 +1,375 @@
+//! Operational alerts to Slack and Discord
+//!
+//! Jobs and data-quality monitors raise an `Alert` with `notify`; it's logged
+//! and posted to the webhook of the channel its kind is routed to. By
+//! default job failures, job run regressions and data-quality findings go
+//! to `ops`, stablecoin depegs to `trading` and on-chain rebalance
+//! deployments to `product`. An alert whose kind has no route or whose
+//! channel has no webhook is only logged. The same alert (kind and title) is posted at most once an hour,
+//! so a job failing on every attempt doesn't flood the channel.
+//!
+//! Configuration (environment):
+//! - `ALERT_WEBHOOKS` - channels as `name=url` pairs separated by commas, e.g.
+//!   `ops=https://hooks.slack.com/services/...,trading=https://discord.com/api/webhooks/...`.
+//!   Discord webhook URLs get Discord's payload, any other URL Slack's.
+//! - `ALERT_ROUTES` - `kind=channel` pairs replacing the default routes of
+//!   those kinds; an empty channel (`depeg=`) turns a kind off
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+const ENV_WEBHOOKS: &str = "ALERT_WEBHOOKS";
+const ENV_ROUTES: &str = "ALERT_ROUTES";
+
+/// Minimum time between two posts of the same alert
+const REPEAT_AFTER: Duration = Duration::from_secs(3600);
+
+/// Discord rejects messages over 2000 characters; Slack's limit is higher
+const MAX_MESSAGE_LEN: usize = 1900;
+
+static ALERTER: LazyLock<Alerter> = LazyLock::new(|| Alerter::new(AlertConfig::from_env()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// A background job failed
+    JobFailure,
+    /// A background job ran much slower than usual or processed nothing
+    JobRegression,
+    /// A monitor found stored data disagreeing with a second source
+    DataQuality,
+    /// A stablecoin held by an index moved off its peg
+    Depeg,
+    /// A rebalance was deployed on-chain
+    RebalanceDeployed,
+}
+
+impl AlertKind {
+    pub const ALL: [AlertKind; 5] = [
+        AlertKind::JobFailure,
+        AlertKind::JobRegression,
+        AlertKind::DataQuality,
+        AlertKind::Depeg,
+        AlertKind::RebalanceDeployed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertKind::JobFailure => "job_failure",
+            AlertKind::JobRegression => "job_regression",
+            AlertKind::DataQuality => "data_quality",
+            AlertKind::Depeg => "depeg",
+            AlertKind::RebalanceDeployed => "rebalance_deployed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == s)
+    }
+
+    fn default_channel(self) -> &'static str {
+        match self {
+            AlertKind::JobFailure | AlertKind::JobRegression | AlertKind::DataQuality => "ops",
+            AlertKind::Depeg => "trading",
+            AlertKind::RebalanceDeployed => "product",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn emoji(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ️",
+            Severity::Warning => "⚠️",
+            Severity::Critical => "🚨",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: Severity,
+    /// One line; also what identifies repeats of the alert
+    pub title: String,
+    pub body: String,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, severity: Severity, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity,
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkKind {
+    Slack,
+    Discord,
+}
+
+impl SinkKind {
+    /// Discord for Discord webhook URLs, Slack (or anything Slack-compatible)
+    /// otherwise
+    pub fn for_url(url: &str) -> Self {
+        let host = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .split(['/', '?'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if host == "discord.com" || host.ends_with(".discord.com") || host == "discordapp.com" {
+            SinkKind::Discord
+        } else {
+            SinkKind::Slack
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub sink: SinkKind,
+    pub url: String,
+}
+
+/// Webhook request body for an alert
+pub fn payload(sink: SinkKind, alert: &Alert) -> serde_json::Value {
+    let (title, field) = match sink {
+        SinkKind::Slack => (format!("*{}*", alert.title), "text"),
+        SinkKind::Discord => (format!("**{}**", alert.title), "content"),
+    };
+    let mut message = format!("{} {}", alert.severity.emoji(), title);
+    if !alert.body.is_empty() {
+        message.push('\n');
+        message.push_str(&alert.body);
+    }
+    if let Some((end, _)) = message.char_indices().nth(MAX_MESSAGE_LEN) {
+        message.truncate(end);
+        message.push('…');
+    }
+    json!({ (field): message })
+}
+
+/// Channels and the routes of alert kinds to them
+#[derive(Debug, Clone, Default)]
+pub struct AlertConfig {
+    pub channels: HashMap<String, Webhook>,
+    pub routes: HashMap<AlertKind, String>,
+}
+
+impl AlertConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(env::var(ENV_WEBHOOKS).ok().as_deref(), env::var(ENV_ROUTES).ok().as_deref())
+    }
+
+    pub fn from_values(webhooks: Option<&str>, routes: Option<&str>) -> Self {
+        let channels = pairs(ENV_WEBHOOKS, webhooks)
+            .filter_map(|(name, url)| {
+                if !url.starts_with("https://") && !url.starts_with("http://") {
+                    tracing::warn!("Invalid {} URL for channel '{}', ignoring it", ENV_WEBHOOKS, name);
+                    return None;
+                }
+                let webhook = Webhook {
+                    sink: SinkKind::for_url(url),
+                    url: url.to_string(),
+                };
+                Some((name.to_string(), webhook))
+            })
+            .collect();
+
+        let mut config_routes: HashMap<AlertKind, String> = AlertKind::ALL
+            .into_iter()
+            .map(|kind| (kind, kind.default_channel().to_string()))
+            .collect();
+        for (kind, channel) in pairs(ENV_ROUTES, routes) {
+            match AlertKind::parse(kind) {
+                Some(kind) if channel.is_empty() => {
+                    config_routes.remove(&kind);
+                }
+                Some(kind) => {
+                    config_routes.insert(kind, channel.to_string());
+                }
+                None => tracing::warn!("Unknown alert kind '{}' in {}, ignoring it", kind, ENV_ROUTES),
+            }
+        }
+
+        Self {
+            channels,
+            routes: config_routes,
+        }
+    }
+
+    /// Channel name and webhook an alert kind is posted to
+    pub fn route(&self, kind: AlertKind) -> Option<(&str, &Webhook)> {
+        let channel = self.routes.get(&kind)?;
+        Some((channel, self.channels.get(channel)?))
+    }
+}
+
+/// `key=value` entries of a comma separated list
+fn pairs<'a>(var: &'static str, value: Option<&'a str>) -> impl Iterator<Item = (&'a str, &'a str)> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(move |entry| match entry.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Some((key.trim(), value.trim())),
+            _ => {
+                tracing::warn!("Invalid {} entry '{}', expected key=value", var, entry);
+                None
+            }
+        })
+}
+
+pub struct Alerter {
+    config: AlertConfig,
+    client: reqwest::Client,
+    /// Last post of each (kind, title)
+    sent: Mutex<HashMap<(AlertKind, String), Instant>>,
+}
+
+impl Alerter {
+    pub fn new(config: AlertConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            config,
+            client,
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether an alert should be posted now, remembering it if so
+    fn claim(&self, alert: &Alert, now: Instant) -> bool {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        sent.retain(|_, at| now.duration_since(*at) < REPEAT_AFTER);
+        let key = (alert.kind, alert.title.clone());
+        if sent.contains_key(&key) {
+            return false;
+        }
+        sent.insert(key, now);
+        true
+    }
+
+    /// Post an alert to its channel; returns whether it was posted (false
+    /// without a route or for a repeat)
+    pub async fn send(&self, alert: &Alert) -> Result<bool, reqwest::Error> {
+        let Some((channel, webhook)) = self.config.route(alert.kind) else {
+            return Ok(false);
+        };
+        if !self.claim(alert, Instant::now()) {
+            tracing::debug!(kind = alert.kind.as_str(), title = %alert.title, "Repeated alert not posted");
+            return Ok(false);
+        }
+
+        let posted = self
+            .client
+            .post(&webhook.url)
+            .json(&payload(webhook.sink, alert))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = posted {
+            // Not posted, so a retry shouldn't count as a repeat
+            self.sent
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&(alert.kind, alert.title.clone()));
+            return Err(e);
+        }
+        tracing::debug!(kind = alert.kind.as_str(), channel, "Alert posted");
+        Ok(true)
+    }
+}
+
+/// Log an alert and post it in the background to its channel, if it has one
+pub fn notify(alert: Alert) {
+    tracing::warn!(kind = alert.kind.as_str(), body = %alert.body, "Alert: {}", alert.title);
+    if ALERTER.config.route(alert.kind).is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        if let Err(e) = ALERTER.send(&alert).await {
+            tracing::warn!(kind = alert.kind.as_str(), error = %e, "Failed to post alert");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(title: &str) -> Alert {
+        Alert::new(AlertKind::JobFailure, Severity::Warning, title, "boom")
+    }
+
+    #[test]
+    fn test_sink_for_url() {
+        assert_eq!(SinkKind::for_url("https://discord.com/api/webhooks/1/abc"), SinkKind::Discord);
+        assert_eq!(SinkKind::for_url("https://canary.discord.com/api/webhooks/1/abc"), SinkKind::Discord);
+        assert_eq!(SinkKind::for_url("https://hooks.slack.com/services/T/B/x"), SinkKind::Slack);
+        assert_eq!(SinkKind::for_url("https://example.com/discord.com"), SinkKind::Slack);
+    }
+
+    #[test]
+    fn test_default_routes_and_overrides() {
+        let config = AlertConfig::from_values(
+            Some("ops=https://hooks.slack.com/services/T/B/x, trading=https://discord.com/api/webhooks/1/y, bad=ftp://x"),
+            Some("data_quality=trading,rebalance_deployed=,nope=ops"),
+        );
+        assert_eq!(config.channels.len(), 2);
+        assert_eq!(config.route(AlertKind::JobFailure).unwrap().0, "ops");
+        assert_eq!(config.route(AlertKind::Depeg).unwrap().1.sink, SinkKind::Discord);
+        assert_eq!(config.route(AlertKind::DataQuality).unwrap().0, "trading");
+        assert!(config.route(AlertKind::RebalanceDeployed).is_none());
+
+        // Routed to a channel without a webhook
+        let config = AlertConfig::from_values(None, None);
+        assert_eq!(config.routes[&AlertKind::RebalanceDeployed], "product");
+        assert!(config.route(AlertKind::RebalanceDeployed).is_none());
+    }
+
+    #[test]
+    fn test_payload() {
+        assert_eq!(
+            payload(SinkKind::Slack, &alert("Job x failed")),
+            json!({ "text": "⚠️ *Job x failed*\nboom" })
+        );
+        assert_eq!(
+            payload(SinkKind::Discord, &alert("Job x failed")),
+            json!({ "content": "⚠️ **Job x failed**\nboom" })
+        );
+
+        let long = Alert::new(AlertKind::Depeg, Severity::Critical, "t", "x".repeat(5000));
+        let message = payload(SinkKind::Discord, &long)["content"].as_str().unwrap().to_string();
+        assert_eq!(message.chars().count(), MAX_MESSAGE_LEN + 1);
+    }
+
+    #[test]
+    fn test_repeats_are_suppressed_for_an_hour() {
+        let alerter = Alerter::new(AlertConfig::default());
+        let now = Instant::now();
+        assert!(alerter.claim(&alert("Job x failed"), now));
+        assert!(!alerter.claim(&alert("Job x failed"), now + Duration::from_secs(60)));
+        assert!(alerter.claim(&alert("Job y failed"), now + Duration::from_secs(60)));
+        assert!(alerter.claim(&alert("Job x failed"), now + REPEAT_AFTER));
+    }
+}
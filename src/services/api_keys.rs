@@ -0,0 +1,273 @@
+//! API keys for external consumers
+//!
+//! Keys are issued per consumer through /admin/api-keys and sent in the
+//! X-API-Key header. Each key has a tier:
+//! - `public` - read-only (GET on non-admin routes), 1,000 requests/day
+//! - `partner` - read-only, 100,000 requests/day
+//! - `admin` - any non-admin route and method, no quota
+//!
+//! A key's quota can be overridden when it is issued. The /admin endpoints
+//! stay behind the shared ADMIN_API_KEY, except rebalance approvals, which
+//! admin-tier keys can also make. Requests made with a key are
+//! counted per key, UTC day and endpoint in api_key_usage (see the metering
+//! middleware in handlers::metering); requests without a key are not metered.
+//!
+//! Only the keccak256 hash of a key is stored. Lookups are cached for a
+//! minute, so a revoked key may keep working for up to that long.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use alloy::primitives::keccak256;
+use chrono::{NaiveDate, Utc};
+use moka::future::Cache;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult,
+    QueryFilter, QueryOrder, Set, Statement,
+};
+
+use crate::entities::{api_key_usage, api_keys, prelude::*};
+use crate::models::api_key::{ApiKeyUsageSummary, ApiUsageReport, EndpointUsage};
+
+/// Characters of a key kept in api_keys.key_prefix
+const KEY_PREFIX_LEN: usize = 12;
+
+static KEY_CACHE: LazyLock<Cache<String, Option<api_keys::Model>>> = LazyLock::new(|| {
+    Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(60))
+        .build()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyTier {
+    Public,
+    Partner,
+    Admin,
+}
+
+impl ApiKeyTier {
+    pub fn parse(tier: &str) -> Option<Self> {
+        match tier {
+            "public" => Some(ApiKeyTier::Public),
+            "partner" => Some(ApiKeyTier::Partner),
+            "admin" => Some(ApiKeyTier::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiKeyTier::Public => "public",
+            ApiKeyTier::Partner => "partner",
+            ApiKeyTier::Admin => "admin",
+        }
+    }
+
+    /// Quota of keys issued without an explicit one
+    pub fn default_daily_quota(self) -> Option<i32> {
+        match self {
+            ApiKeyTier::Public => Some(1_000),
+            ApiKeyTier::Partner => Some(100_000),
+            ApiKeyTier::Admin => None,
+        }
+    }
+
+    /// Whether keys of this tier are limited to GET/HEAD
+    pub fn read_only(self) -> bool {
+        self != ApiKeyTier::Admin
+    }
+}
+
+/// A new random key
+pub fn generate_key() -> String {
+    format!("imk_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// Hex keccak256 of a key, as stored in api_keys.key_hash
+pub fn hash_key(key: &str) -> String {
+    hex::encode(keccak256(key.as_bytes()))
+}
+
+/// Issue a key; returns the stored row and the key, which isn't stored
+pub async fn create(
+    db: &DatabaseConnection,
+    name: &str,
+    tier: ApiKeyTier,
+    daily_quota: Option<i32>,
+) -> Result<(api_keys::Model, String), DbErr> {
+    let key = generate_key();
+    let api_key = api_keys::ActiveModel {
+        name: Set(name.to_string()),
+        key_prefix: Set(key[..KEY_PREFIX_LEN].to_string()),
+        key_hash: Set(hash_key(&key)),
+        tier: Set(tier.as_str().to_string()),
+        daily_quota: Set(daily_quota.or(tier.default_daily_quota())),
+        active: Set(true),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok((api_key, key))
+}
+
+/// All issued keys, newest first
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<api_keys::Model>, DbErr> {
+    ApiKeys::find().order_by_desc(api_keys::Column::Id).all(db).await
+}
+
+/// Deactivate a key; None if it doesn't exist
+pub async fn revoke(db: &DatabaseConnection, id: i32) -> Result<Option<api_keys::Model>, DbErr> {
+    let Some(api_key) = ApiKeys::find_by_id(id).one(db).await? else {
+        return Ok(None);
+    };
+    if !api_key.active {
+        return Ok(Some(api_key));
+    }
+
+    let mut active: api_keys::ActiveModel = api_key.into();
+    active.active = Set(false);
+    active.revoked_at = Set(Some(Utc::now().naive_utc()));
+    Ok(Some(active.update(db).await?))
+}
+
+/// The active key matching `key`, if any
+pub async fn authenticate(db: &DatabaseConnection, key: &str) -> Result<Option<api_keys::Model>, DbErr> {
+    let hash = hash_key(key);
+    if let Some(cached) = KEY_CACHE.get(&hash).await {
+        return Ok(cached);
+    }
+
+    let api_key = ApiKeys::find()
+        .filter(api_keys::Column::KeyHash.eq(&hash))
+        .filter(api_keys::Column::Active.eq(true))
+        .one(db)
+        .await?;
+    KEY_CACHE.insert(hash, api_key.clone()).await;
+    Ok(api_key)
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RequestTotal {
+    requests: i64,
+}
+
+/// Requests made with a key on `date`, over all endpoints
+pub async fn requests_on(db: &DatabaseConnection, api_key_id: i32, date: NaiveDate) -> Result<i64, DbErr> {
+    let total = RequestTotal::find_by_statement(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+        SELECT COALESCE(SUM(request_count), 0)::BIGINT AS requests
+        FROM api_key_usage
+        WHERE api_key_id = $1 AND date = $2
+        "#,
+        [api_key_id.into(), date.into()],
+    ))
+    .one(db)
+    .await?;
+
+    Ok(total.map_or(0, |total| total.requests))
+}
+
+/// Count one request made with a key
+pub async fn record(db: &DatabaseConnection, api_key_id: i32, date: NaiveDate, endpoint: &str) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+        INSERT INTO api_key_usage (api_key_id, date, endpoint, request_count)
+        VALUES ($1, $2, $3, 1)
+        ON CONFLICT (api_key_id, date, endpoint)
+        DO UPDATE SET request_count = api_key_usage.request_count + 1
+        "#,
+        [api_key_id.into(), date.into(), endpoint.into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Usage of every key (or one) over the last `days` days up to `today`
+pub async fn usage_report(
+    db: &DatabaseConnection,
+    days: u32,
+    key_id: Option<i32>,
+    today: NaiveDate,
+) -> Result<ApiUsageReport, DbErr> {
+    let since = today - chrono::Duration::days(days.saturating_sub(1) as i64);
+
+    let mut keys = ApiKeys::find().order_by_asc(api_keys::Column::Id);
+    if let Some(key_id) = key_id {
+        keys = keys.filter(api_keys::Column::Id.eq(key_id));
+    }
+    let keys = keys.all(db).await?;
+
+    let rows = ApiKeyUsage::find()
+        .filter(api_key_usage::Column::Date.gte(since))
+        .filter(api_key_usage::Column::Date.lte(today))
+        .all(db)
+        .await?;
+
+    let mut by_key: HashMap<i32, Vec<api_key_usage::Model>> = HashMap::new();
+    for row in rows {
+        by_key.entry(row.api_key_id).or_default().push(row);
+    }
+
+    let keys = keys
+        .into_iter()
+        .map(|api_key| {
+            let rows = by_key.remove(&api_key.id).unwrap_or_default();
+
+            let mut endpoints: HashMap<String, i64> = HashMap::new();
+            for row in &rows {
+                *endpoints.entry(row.endpoint.clone()).or_default() += row.request_count;
+            }
+            let mut endpoints: Vec<EndpointUsage> = endpoints
+                .into_iter()
+                .map(|(endpoint, requests)| EndpointUsage { endpoint, requests })
+                .collect();
+            endpoints.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.endpoint.cmp(&b.endpoint)));
+
+            ApiKeyUsageSummary {
+                id: api_key.id,
+                name: api_key.name,
+                key_prefix: api_key.key_prefix,
+                tier: api_key.tier,
+                daily_quota: api_key.daily_quota,
+                active: api_key.active,
+                requests_today: rows.iter().filter(|row| row.date == today).map(|row| row.request_count).sum(),
+                requests: rows.iter().map(|row| row.request_count).sum(),
+                endpoints,
+            }
+        })
+        .collect();
+
+    Ok(ApiUsageReport { since, keys })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers() {
+        for tier in [ApiKeyTier::Public, ApiKeyTier::Partner, ApiKeyTier::Admin] {
+            assert_eq!(ApiKeyTier::parse(tier.as_str()), Some(tier));
+        }
+        assert_eq!(ApiKeyTier::parse("root"), None);
+        assert!(ApiKeyTier::Partner.read_only());
+        assert!(!ApiKeyTier::Admin.read_only());
+        assert_eq!(ApiKeyTier::Admin.default_daily_quota(), None);
+    }
+
+    #[test]
+    fn test_generated_keys_are_unique_and_hashed() {
+        let (a, b) = (generate_key(), generate_key());
+        assert_ne!(a, b);
+        assert!(a.starts_with("imk_"));
+        assert_eq!(a.len(), 4 + 64);
+        assert_eq!(hash_key(&a).len(), 64);
+        assert_eq!(hash_key(&a), hash_key(&a));
+        assert_ne!(hash_key(&a), hash_key(&b));
+    }
+}
@@ -0,0 +1,379 @@
+//! Wallet authentication with Sign-In-With-Ethereum (EIP-4361)
+//!
+//! A client asks for a nonce (POST /auth/nonce), has the wallet sign a SIWE
+//! message containing it, and exchanges message and signature for a
+//! short-lived JWT (POST /auth/verify) whose subject is the lowercased
+//! wallet address. Endpoints returning a wallet's own data require that JWT
+//! as `Authorization: Bearer <token>`.
+//!
+//! Only EOA signatures are accepted (no EIP-1271 contract wallets). Nonces
+//! live in auth_nonces so any instance can verify a nonce another handed out.
+//!
+//! Configuration (environment):
+//! - `JWT_SECRET` - HMAC key for session tokens (required)
+//! - `SIWE_DOMAIN` - domain signed messages must be issued for (required)
+//! - `SIWE_ORIGIN` - origin the messages' URI must be on (default
+//!   `https://` + SIWE_DOMAIN)
+//! - `SIWE_CHAIN_IDS` - comma-separated chain ids messages may be signed for
+//!   (default 8453,42161: Base and Arbitrum, where index tokens live)
+//! - `AUTH_TOKEN_TTL_SECS` - session lifetime (default 900)
+
+use std::str::FromStr;
+
+use alloy::primitives::{Address, PrimitiveSignature};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set, Statement,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{auth_nonces, prelude::*};
+
+const ENV_JWT_SECRET: &str = "JWT_SECRET";
+const ENV_SIWE_DOMAIN: &str = "SIWE_DOMAIN";
+const ENV_SIWE_ORIGIN: &str = "SIWE_ORIGIN";
+const ENV_SIWE_CHAIN_IDS: &str = "SIWE_CHAIN_IDS";
+const ENV_TOKEN_TTL_SECS: &str = "AUTH_TOKEN_TTL_SECS";
+
+const DEFAULT_TOKEN_TTL_SECS: i64 = 900;
+
+/// Base and Arbitrum
+const DEFAULT_CHAIN_IDS: [u64; 2] = [8453, 42161];
+
+/// How long a nonce can be used after it is issued
+const NONCE_TTL_SECS: i64 = 300;
+
+/// Tolerated clock difference with the signing client
+const CLOCK_SKEW_SECS: i64 = 300;
+
+const TOKEN_ISSUER: &str = "indexmaker-backend";
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    pub jwt_secret: Option<String>,
+    pub domain: Option<String>,
+    /// Origin of the URI in signed messages; None for `https://{domain}`
+    pub origin: Option<String>,
+    /// Chain ids signed messages may name
+    pub chain_ids: Vec<u64>,
+    pub token_ttl_secs: i64,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        Self {
+            jwt_secret: std::env::var(ENV_JWT_SECRET).ok().filter(|s| !s.is_empty()),
+            domain: std::env::var(ENV_SIWE_DOMAIN).ok().filter(|s| !s.is_empty()),
+            origin: std::env::var(ENV_SIWE_ORIGIN)
+                .ok()
+                .map(|s| s.trim_end_matches('/').to_string())
+                .filter(|s| !s.is_empty()),
+            chain_ids: std::env::var(ENV_SIWE_CHAIN_IDS)
+                .ok()
+                .map(|s| s.split(',').filter_map(|id| id.trim().parse().ok()).collect::<Vec<u64>>())
+                .filter(|ids| !ids.is_empty())
+                .unwrap_or_else(|| DEFAULT_CHAIN_IDS.to_vec()),
+            token_ttl_secs: std::env::var(ENV_TOKEN_TTL_SECS)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|ttl| *ttl > 0)
+                .unwrap_or(DEFAULT_TOKEN_TTL_SECS),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    NotConfigured,
+    InvalidMessage(String),
+    InvalidSignature,
+    /// The nonce was never issued, already used or expired
+    InvalidNonce,
+    Expired,
+    InvalidToken,
+    Database(DbErr),
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::NotConfigured => write!(f, "Wallet authentication is not configured"),
+            AuthError::InvalidMessage(msg) => write!(f, "Invalid SIWE message: {}", msg),
+            AuthError::InvalidSignature => write!(f, "Signature doesn't match the message address"),
+            AuthError::InvalidNonce => write!(f, "Unknown, used or expired nonce"),
+            AuthError::Expired => write!(f, "SIWE message is expired or not yet valid"),
+            AuthError::InvalidToken => write!(f, "Invalid or expired session token"),
+            AuthError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<DbErr> for AuthError {
+    fn from(e: DbErr) -> Self {
+        AuthError::Database(e)
+    }
+}
+
+/// The fields of an EIP-4361 message this backend checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: Address,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+}
+
+impl FromStr for SiweMessage {
+    type Err = AuthError;
+
+    fn from_str(message: &str) -> Result<Self, Self::Err> {
+        let invalid = |msg: &str| AuthError::InvalidMessage(msg.to_string());
+        let mut lines = message.lines();
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(" wants you to sign in with your Ethereum account:"))
+            .ok_or_else(|| invalid("missing preamble"))?;
+        let address = lines
+            .next()
+            .and_then(|line| Address::from_str(line.trim()).ok())
+            .ok_or_else(|| invalid("missing or invalid address"))?;
+
+        // Fields follow the optional statement, starting with URI
+        let fields: Vec<&str> = lines.skip_while(|line| !line.starts_with("URI: ")).collect();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(str::to_string)
+        };
+        let time = |value: Option<String>, name: &str| -> Result<Option<DateTime<Utc>>, AuthError> {
+            value
+                .map(|v| DateTime::parse_from_rfc3339(&v).map(|t| t.with_timezone(&Utc)))
+                .transpose()
+                .map_err(|_| AuthError::InvalidMessage(format!("invalid {}", name)))
+        };
+
+        let uri = field("URI").ok_or_else(|| invalid("missing URI"))?;
+        let version = field("Version").ok_or_else(|| invalid("missing Version"))?;
+        let chain_id = field("Chain ID")
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid("missing or invalid Chain ID"))?;
+        let nonce = field("Nonce").ok_or_else(|| invalid("missing Nonce"))?;
+        let issued_at = time(field("Issued At"), "Issued At")?.ok_or_else(|| invalid("missing Issued At"))?;
+        let expiration_time = time(field("Expiration Time"), "Expiration Time")?;
+        let not_before = time(field("Not Before"), "Not Before")?;
+
+        Ok(SiweMessage {
+            domain: domain.to_string(),
+            address,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+        })
+    }
+}
+
+/// JWT claims of a wallet session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Lowercased wallet address
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+}
+
+/// A verified wallet session
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub address: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Hand out a new single-use nonce, valid for a few minutes
+pub async fn issue_nonce(db: &DatabaseConnection, now: NaiveDateTime) -> Result<auth_nonces::Model, DbErr> {
+    AuthNonces::delete_many()
+        .filter(auth_nonces::Column::ExpiresAt.lt(now))
+        .exec(db)
+        .await?;
+
+    let nonce = auth_nonces::Model {
+        nonce: uuid::Uuid::new_v4().simple().to_string(),
+        expires_at: now + Duration::seconds(NONCE_TTL_SECS),
+        created_at: now,
+    };
+    AuthNonces::insert(auth_nonces::ActiveModel {
+        nonce: Set(nonce.nonce.clone()),
+        expires_at: Set(nonce.expires_at),
+        created_at: Set(nonce.created_at),
+    })
+    .exec(db)
+    .await?;
+
+    Ok(nonce)
+}
+
+/// Verify a signed SIWE message and start a session for its address
+pub async fn verify(
+    db: &DatabaseConnection,
+    config: &AuthConfig,
+    message: &str,
+    signature: &str,
+    now: DateTime<Utc>,
+) -> Result<Session, AuthError> {
+    let (Some(secret), Some(domain)) = (&config.jwt_secret, &config.domain) else {
+        return Err(AuthError::NotConfigured);
+    };
+
+    let siwe = SiweMessage::from_str(message)?;
+    if siwe.domain != *domain {
+        return Err(AuthError::InvalidMessage(format!("domain '{}' is not '{}'", siwe.domain, domain)));
+    }
+    let origin = config.origin.clone().unwrap_or_else(|| format!("https://{}", domain));
+    if siwe.uri != origin && !siwe.uri.starts_with(&format!("{}/", origin)) {
+        return Err(AuthError::InvalidMessage(format!("URI '{}' is not on '{}'", siwe.uri, origin)));
+    }
+    if siwe.version != "1" {
+        return Err(AuthError::InvalidMessage(format!("unsupported version '{}'", siwe.version)));
+    }
+    if !config.chain_ids.contains(&siwe.chain_id) {
+        return Err(AuthError::InvalidMessage(format!("unsupported chain id {}", siwe.chain_id)));
+    }
+    let skew = Duration::seconds(CLOCK_SKEW_SECS);
+    if siwe.issued_at > now + skew
+        || siwe.expiration_time.is_some_and(|t| t <= now)
+        || siwe.not_before.is_some_and(|t| t > now + skew)
+    {
+        return Err(AuthError::Expired);
+    }
+
+    let signature = PrimitiveSignature::from_str(signature).map_err(|_| AuthError::InvalidSignature)?;
+    let signer = signature
+        .recover_address_from_msg(message)
+        .map_err(|_| AuthError::InvalidSignature)?;
+    if signer != siwe.address {
+        return Err(AuthError::InvalidSignature);
+    }
+
+    // Checked last, so a bad signature doesn't burn the nonce
+    if !consume_nonce(db, &siwe.nonce, now.naive_utc()).await? {
+        return Err(AuthError::InvalidNonce);
+    }
+
+    let address = format!("{:#x}", siwe.address);
+    let expires_at = now + Duration::seconds(config.token_ttl_secs);
+    let claims = Claims {
+        sub: address.clone(),
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+        iss: TOKEN_ISSUER.to_string(),
+    };
+    let token = jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|_| AuthError::NotConfigured)?;
+
+    Ok(Session { token, address, expires_at: expires_at.naive_utc() })
+}
+
+/// Delete a nonce if it is still valid; false if there was none to use
+async fn consume_nonce(db: &DatabaseConnection, nonce: &str, now: NaiveDateTime) -> Result<bool, DbErr> {
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "DELETE FROM auth_nonces WHERE nonce = $1 AND expires_at > $2",
+            [nonce.into(), now.into()],
+        ))
+        .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// The wallet address of a session token
+pub fn wallet_from_token(config: &AuthConfig, token: &str) -> Result<String, AuthError> {
+    let secret = config.jwt_secret.as_ref().ok_or(AuthError::NotConfigured)?;
+
+    let mut validation = Validation::default();
+    validation.set_issuer(&[TOKEN_ISSUER]);
+    let data = jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .map_err(|_| AuthError::InvalidToken)?;
+
+    Ok(data.claims.sub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "app.indexmaker.global wants you to sign in with your Ethereum account:
+0x5eed000000000000000000000000000000000001
+
+Sign in to IndexMaker
+
+URI: https://app.indexmaker.global
+Version: 1
+Chain ID: 8453
+Nonce: 8f3a2b1c9d8e7f60
+Issued At: 2025-06-01T12:00:00Z
+Expiration Time: 2025-06-01T12:10:00Z";
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            jwt_secret: Some("test-secret".to_string()),
+            domain: Some("app.indexmaker.global".to_string()),
+            origin: None,
+            chain_ids: DEFAULT_CHAIN_IDS.to_vec(),
+            token_ttl_secs: 900,
+        }
+    }
+
+    #[test]
+    fn test_parse_siwe_message() {
+        let siwe = SiweMessage::from_str(MESSAGE).unwrap();
+        assert_eq!(siwe.domain, "app.indexmaker.global");
+        assert_eq!(format!("{:#x}", siwe.address), "0x5eed000000000000000000000000000000000001");
+        assert_eq!(siwe.uri, "https://app.indexmaker.global");
+        assert_eq!(siwe.version, "1");
+        assert_eq!(siwe.chain_id, 8453);
+        assert_eq!(siwe.nonce, "8f3a2b1c9d8e7f60");
+        assert_eq!(siwe.issued_at.to_rfc3339(), "2025-06-01T12:00:00+00:00");
+        assert!(siwe.expiration_time.is_some());
+        assert_eq!(siwe.not_before, None);
+    }
+
+    #[test]
+    fn test_reject_malformed_messages() {
+        assert!(SiweMessage::from_str("hello").is_err());
+        assert!(SiweMessage::from_str(&MESSAGE.replace("Nonce: 8f3a2b1c9d8e7f60\n", "")).is_err());
+        assert!(SiweMessage::from_str(&MESSAGE.replace("2025-06-01T12:00:00Z", "yesterday")).is_err());
+    }
+
+    #[test]
+    fn test_token_round_trip() {
+        let claims = Claims {
+            sub: "0xabc".to_string(),
+            iat: Utc::now().timestamp(),
+            exp: Utc::now().timestamp() + 60,
+            iss: TOKEN_ISSUER.to_string(),
+        };
+        let token =
+            jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(b"test-secret")).unwrap();
+
+        assert_eq!(wallet_from_token(&config(), &token).unwrap(), "0xabc");
+
+        let other = AuthConfig { jwt_secret: Some("other-secret".to_string()), ..config() };
+        assert!(matches!(wallet_from_token(&other, &token), Err(AuthError::InvalidToken)));
+        assert!(matches!(wallet_from_token(&AuthConfig::default(), &token), Err(AuthError::NotConfigured)));
+    }
+}
@@ -0,0 +1,337 @@
+//! Persistent queue for long-running background tasks
+//!
+//! Handlers enqueue work (e.g. the historical backfill after create_index)
+//! instead of spawning it fire-and-forget. The background_tasks worker claims
+//! pending tasks, heartbeats while running and records the outcome. Tasks left
+//! `running` by a crashed instance go back to pending once their heartbeat is
+//! stale, so work resumes after restarts.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbBackend, EntityTrait,
+    Order, QueryFilter, QueryOrder, Set, Statement,
+};
+
+use crate::entities::{background_tasks, prelude::*};
+use crate::models::index::BackfillStatusResponse;
+
+/// Task type values for background_tasks.task_type
+pub mod task_types {
+    pub const INDEX_BACKFILL: &str = "index_backfill";
+}
+
+/// Status values for background_tasks.status
+pub mod statuses {
+    pub const PENDING: &str = "pending";
+    pub const RUNNING: &str = "running";
+    pub const COMPLETED: &str = "completed";
+    pub const FAILED: &str = "failed";
+}
+
+/// Times a task may be picked up (including restarts after a crash)
+pub const MAX_ATTEMPTS: i32 = 3;
+
+/// How often a running task refreshes `updated_at` (and its progress)
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+/// How long a deferred task waits before it may be claimed again
+const DEFER_DELAY_SECS: i64 = 60;
+
+/// A running task without a heartbeat for this long is considered abandoned
+const STALE_AFTER_SECS: i64 = 600; // 10 minutes
+
+/// Running tasks whose last heartbeat is older than this are abandoned
+fn stale_cutoff(now: NaiveDateTime) -> NaiveDateTime {
+    now - Duration::seconds(STALE_AFTER_SECS)
+}
+
+/// Add a task to the queue
+pub async fn enqueue(
+    db: &DatabaseConnection,
+    task_type: &str,
+    index_id: Option<i32>,
+    payload: Option<serde_json::Value>,
+) -> Result<background_tasks::Model, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let task = background_tasks::ActiveModel {
+        task_type: Set(task_type.to_string()),
+        index_id: Set(index_id),
+        payload: Set(payload),
+        status: Set(statuses::PENDING.to_string()),
+        attempts: Set(0),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+    Ok(task.insert(db).await?)
+}
+
+/// Atomically claim the oldest pending task that is due
+///
+/// Uses `FOR UPDATE SKIP LOCKED` so concurrent workers on different
+/// instances never claim the same task. Deferred tasks are skipped until
+/// their `run_after`, so they don't block the tasks queued behind them.
+pub async fn claim_next(
+    db: &DatabaseConnection,
+) -> Result<Option<background_tasks::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let task = BackgroundTasks::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE background_tasks
+            SET status = $1, attempts = attempts + 1, started_at = $2, updated_at = $2
+            WHERE id = (
+                SELECT id FROM background_tasks
+                WHERE status = $3 AND (run_after IS NULL OR run_after <= $2)
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+            [statuses::RUNNING.into(), now.into(), statuses::PENDING.into()],
+        ))
+        .one(db)
+        .await?;
+    Ok(task)
+}
+
+/// Refresh the heartbeat of a running task
+pub async fn heartbeat(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    BackgroundTasks::update_many()
+        .col_expr(background_tasks::Column::UpdatedAt, Expr::value(Utc::now().naive_utc()))
+        .filter(background_tasks::Column::Id.eq(id))
+        .filter(background_tasks::Column::Status.eq(statuses::RUNNING))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Store the progress of a running task (also counts as a heartbeat)
+pub async fn record_progress(
+    db: &DatabaseConnection,
+    id: i32,
+    done: i32,
+    total: i32,
+    current_date: Option<NaiveDate>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    BackgroundTasks::update_many()
+        .col_expr(background_tasks::Column::ProgressDone, Expr::value(done))
+        .col_expr(background_tasks::Column::ProgressTotal, Expr::value(total))
+        .col_expr(background_tasks::Column::ProgressDate, Expr::value(current_date))
+        .col_expr(background_tasks::Column::UpdatedAt, Expr::value(Utc::now().naive_utc()))
+        .filter(background_tasks::Column::Id.eq(id))
+        .filter(background_tasks::Column::Status.eq(statuses::RUNNING))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Percentage of planned work done, if the total is known
+pub fn progress_percent(done: i32, total: i32) -> Option<f64> {
+    if total <= 0 {
+        return None;
+    }
+    Some((done.clamp(0, total) as f64 / total as f64) * 100.0)
+}
+
+/// Estimated completion time, extrapolating the average pace so far
+pub fn estimate_eta(
+    started_at: NaiveDateTime,
+    now: NaiveDateTime,
+    done: i32,
+    total: i32,
+) -> Option<NaiveDateTime> {
+    if done <= 0 || total <= 0 || done > total {
+        return None;
+    }
+    let elapsed_ms = (now - started_at).num_milliseconds().max(0);
+    let remaining = (total - done) as i64;
+    Some(now + Duration::milliseconds(elapsed_ms / done as i64 * remaining))
+}
+
+/// Put a claimed task back without counting the attempt
+/// (e.g. the index is locked by another instance)
+///
+/// The task isn't claimed again for DEFER_DELAY_SECS.
+pub async fn defer(
+    db: &DatabaseConnection,
+    task: background_tasks::Model,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let attempts = (task.attempts - 1).max(0);
+    let mut active_model: background_tasks::ActiveModel = task.into();
+    active_model.status = Set(statuses::PENDING.to_string());
+    active_model.attempts = Set(attempts);
+    active_model.started_at = Set(None);
+    active_model.updated_at = Set(now);
+    active_model.run_after = Set(Some(now + Duration::seconds(DEFER_DELAY_SECS)));
+    active_model.update(db).await?;
+    Ok(())
+}
+
+/// Mark a task as finished successfully
+pub async fn mark_completed(
+    db: &DatabaseConnection,
+    task: background_tasks::Model,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    finish(db, task, statuses::COMPLETED, None).await
+}
+
+/// Mark a task as failed with the error that stopped it
+pub async fn mark_failed(
+    db: &DatabaseConnection,
+    task: background_tasks::Model,
+    error: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    finish(db, task, statuses::FAILED, Some(error.to_string())).await
+}
+
+async fn finish(
+    db: &DatabaseConnection,
+    task: background_tasks::Model,
+    status: &str,
+    error: Option<String>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let mut active_model: background_tasks::ActiveModel = task.into();
+    active_model.status = Set(status.to_string());
+    active_model.error = Set(error);
+    active_model.finished_at = Set(Some(now));
+    active_model.updated_at = Set(now);
+    active_model.update(db).await?;
+    Ok(())
+}
+
+/// Return abandoned running tasks to the queue (or fail them once out of attempts)
+///
+/// Returns the number of tasks recovered.
+pub async fn recover_stale(
+    db: &DatabaseConnection,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let cutoff = stale_cutoff(now);
+
+    let exhausted = BackgroundTasks::update_many()
+        .col_expr(background_tasks::Column::Status, Expr::value(statuses::FAILED))
+        .col_expr(
+            background_tasks::Column::Error,
+            Expr::value("Worker stopped responding and the task ran out of attempts"),
+        )
+        .col_expr(background_tasks::Column::FinishedAt, Expr::value(now))
+        .col_expr(background_tasks::Column::UpdatedAt, Expr::value(now))
+        .filter(background_tasks::Column::Status.eq(statuses::RUNNING))
+        .filter(background_tasks::Column::UpdatedAt.lt(cutoff))
+        .filter(background_tasks::Column::Attempts.gte(MAX_ATTEMPTS))
+        .exec(db)
+        .await?;
+
+    let requeued = BackgroundTasks::update_many()
+        .col_expr(background_tasks::Column::Status, Expr::value(statuses::PENDING))
+        .col_expr(background_tasks::Column::UpdatedAt, Expr::value(now))
+        .filter(background_tasks::Column::Status.eq(statuses::RUNNING))
+        .filter(background_tasks::Column::UpdatedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+
+    Ok(exhausted.rows_affected + requeued.rows_affected)
+}
+
+/// Most recent task of a given type for an index
+pub async fn latest_for_index(
+    db: &DatabaseConnection,
+    index_id: i32,
+    task_type: &str,
+) -> Result<Option<background_tasks::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let task = BackgroundTasks::find()
+        .filter(background_tasks::Column::IndexId.eq(index_id))
+        .filter(background_tasks::Column::TaskType.eq(task_type))
+        .order_by(background_tasks::Column::CreatedAt, Order::Desc)
+        .one(db)
+        .await?;
+    Ok(task)
+}
+
+/// Status of an index's backfill task as served by the API, with progress
+/// and ETA as of `now`
+pub fn backfill_status(index_id: i32, task: background_tasks::Model, now: NaiveDateTime) -> BackfillStatusResponse {
+    let (progress_percent, eta) = match (task.progress_done, task.progress_total) {
+        (Some(done), Some(total)) => {
+            let eta = match (task.status.as_str(), task.started_at) {
+                (statuses::RUNNING, Some(started_at)) => estimate_eta(started_at, now, done, total),
+                _ => None,
+            };
+            (progress_percent(done, total), eta)
+        }
+        _ => (None, None),
+    };
+
+    BackfillStatusResponse {
+        index_id,
+        task_id: task.id,
+        status: task.status,
+        attempts: task.attempts,
+        error: task.error,
+        created_at: task.created_at,
+        started_at: task.started_at,
+        finished_at: task.finished_at,
+        updated_at: task.updated_at,
+        rebalances_done: task.progress_done,
+        rebalances_total: task.progress_total,
+        progress_percent,
+        current_date: task.progress_date,
+        eta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, 30).unwrap().and_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_progress_percent() {
+        assert_eq!(progress_percent(0, 4), Some(0.0));
+        assert_eq!(progress_percent(1, 4), Some(25.0));
+        assert_eq!(progress_percent(4, 4), Some(100.0));
+        assert_eq!(progress_percent(5, 4), Some(100.0));
+    }
+
+    #[test]
+    fn test_progress_percent_unknown_total() {
+        assert_eq!(progress_percent(0, 0), None);
+    }
+
+    #[test]
+    fn test_estimate_eta_extrapolates_pace() {
+        // 2 of 6 dates in 10 minutes -> 20 more minutes
+        assert_eq!(estimate_eta(at(10, 0), at(10, 10), 2, 6), Some(at(10, 30)));
+    }
+
+    #[test]
+    fn test_estimate_eta_needs_progress() {
+        assert_eq!(estimate_eta(at(10, 0), at(10, 10), 0, 6), None);
+        assert_eq!(estimate_eta(at(10, 0), at(10, 10), 3, 0), None);
+    }
+
+    #[test]
+    fn test_stale_cutoff() {
+        assert_eq!(stale_cutoff(at(10, 10)), at(10, 0));
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_task_fresh() {
+        // A task heartbeating on schedule never falls behind the cutoff
+        let now = at(10, 10);
+        let last_heartbeat = now - Duration::seconds(HEARTBEAT_INTERVAL_SECS as i64);
+        assert!(last_heartbeat > stale_cutoff(now));
+    }
+}
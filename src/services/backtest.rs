@@ -0,0 +1,649 @@
+//! Side-by-side backtests of index methodologies
+//!
+//! POST /api/backtest/compare runs one universe and date range under several
+//! parameter sets at once, so tuning a methodology doesn't take a request
+//! per variant. Each scenario starts in cash on start_date and, every
+//! `rebalancePeriodDays` from then on, ranks its universe by that day's
+//! market cap (coins_historical_prices, or the week's for history the
+//! retention policy downsampled) and:
+//!
+//! 1. Keeps the constituents still ranked within topN + rankingBuffer, then
+//!    fills up to topN with the best-ranked other coins. The buffer stops
+//!    coins hovering around the cutoff from being swapped at every rebalance.
+//! 2. Weights them equally or by market cap. With maxWeightPct, weights over
+//!    the cap are set to it and the excess spread over the others in
+//!    proportion, until none is over; a cap below 1 / constituents can't be
+//!    met and gives equal weights.
+//! 3. Trades to the new weights, paying `trading_fee + spread / 2` on the
+//!    traded value like real rebalances (see services::rebalance_math).
+//!
+//! A scenario with a leverageFactor reports the daily-reset NAV of that
+//! multiple of the basket's returns instead, with the factor limits of
+//! leveraged indexes (see services::leverage); fees and turnover stay the
+//! basket's.
+//!
+//! Between rebalances the quantities held are valued daily at stored prices,
+//! with the fallbacks of index prices (services::price_fallback): a missing
+//! price carries the coin's last one forward for at most a week, then the
+//! coin is excluded and valued like the rest of the basket, and a day with
+//! too much of the basket excluded has no value. A rebalance day without
+//! ranked coins, or whose holdings can't be valued, keeps the holdings (or
+//! the cash, before the first). Rankings and prices are loaded once and
+//! shared by every scenario.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+use crate::entities::{coins_historical_prices, prelude::*};
+use crate::models::backtest::{
+    BacktestCompareRequest, BacktestCompareResponse, BacktestScenario, BacktestScenarioResult,
+};
+use crate::services::category_service;
+use crate::services::leverage;
+use crate::services::price_fallback::{self, MAX_CARRY_FORWARD_DAYS};
+use crate::services::price_retention::{PriceCoverage, RetentionConfig};
+use crate::services::price_utils::{self, PriceMap};
+use crate::services::rebalance_math::FeeConfig;
+use crate::services::rolling_stats::{self, DAYS_PER_YEAR};
+use crate::services::weight_calculator::WeightStrategy;
+
+pub const DEFAULT_INITIAL_VALUE: f64 = 1000.0;
+pub const DEFAULT_REBALANCE_PERIOD_DAYS: u32 = 30;
+const DEFAULT_WEIGHT_STRATEGY: &str = "marketcap";
+const DEFAULT_TRADING_FEE: Decimal = dec!(0.001);
+const DEFAULT_SPREAD: Decimal = dec!(0.0005);
+
+/// A universe coin on a rebalance day
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedCoin {
+    pub coin_id: String,
+    pub market_cap: f64,
+    pub price: f64,
+}
+
+/// Resolved parameters of a scenario
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioParams {
+    pub top_n: usize,
+    pub rebalance_period_days: u32,
+    pub ranking_buffer: usize,
+    pub weight_strategy: WeightStrategy,
+    /// Max weight as a fraction
+    pub max_weight: Option<f64>,
+    /// Fee rate on traded value
+    pub fee_rate: f64,
+    /// Leverage factor, None for an unleveraged scenario
+    pub leverage: Option<Decimal>,
+}
+
+impl ScenarioParams {
+    /// Parameters of a scenario with its defaults filled in
+    pub fn from_scenario(scenario: &BacktestScenario) -> Self {
+        let fees = FeeConfig {
+            trading_fee: scenario.trading_fee.unwrap_or(DEFAULT_TRADING_FEE),
+            spread: scenario.spread.unwrap_or(DEFAULT_SPREAD),
+        };
+        Self {
+            top_n: scenario.top_n,
+            rebalance_period_days: scenario.rebalance_period_days.unwrap_or(DEFAULT_REBALANCE_PERIOD_DAYS).max(1),
+            ranking_buffer: scenario.ranking_buffer,
+            weight_strategy: scenario
+                .weight_strategy
+                .as_deref()
+                .and_then(WeightStrategy::from_str)
+                .unwrap_or(WeightStrategy::MarketCap),
+            max_weight: scenario.max_weight_pct.map(|pct| pct / 100.0),
+            fee_rate: fees.rate().to_f64().unwrap_or(0.0),
+            leverage: scenario.leverage_factor.filter(|factor| *factor != Decimal::ONE),
+        }
+    }
+}
+
+/// Daily values and trading record of one scenario
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Simulation {
+    /// Portfolio value at the close of each day; None when it can't be
+    /// priced (see price_fallback)
+    pub values: Vec<Option<f64>>,
+    pub rebalances: usize,
+    /// One-way turnover of each rebalance after the first
+    pub turnovers: Vec<f64>,
+    pub constituent_changes: usize,
+    pub constituent_counts: Vec<usize>,
+    pub total_fees: f64,
+}
+
+/// Constituents after a rebalance, in rank order: incumbents ranked within
+/// `top_n + buffer`, then the best-ranked newcomers up to `top_n`
+pub fn select_with_buffer(
+    ranking: &[RankedCoin],
+    incumbents: &HashSet<String>,
+    top_n: usize,
+    buffer: usize,
+) -> Vec<String> {
+    let kept: HashSet<&str> = ranking
+        .iter()
+        .take(top_n + buffer)
+        .filter(|coin| incumbents.contains(&coin.coin_id))
+        .take(top_n)
+        .map(|coin| coin.coin_id.as_str())
+        .collect();
+    let mut newcomers = top_n - kept.len();
+    ranking
+        .iter()
+        .filter(|coin| {
+            if kept.contains(coin.coin_id.as_str()) {
+                return true;
+            }
+            if newcomers == 0 {
+                return false;
+            }
+            newcomers -= 1;
+            true
+        })
+        .map(|coin| coin.coin_id.clone())
+        .collect()
+}
+
+/// `raw` weights scaled to sum to 1 with none above `cap`; equal weights
+/// when the cap can't be met
+pub fn cap_weights(raw: &[f64], cap: Option<f64>) -> Vec<f64> {
+    let n = raw.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let equal = vec![1.0 / n as f64; n];
+    let total: f64 = raw.iter().sum();
+    let mut weights: Vec<f64> = if total > 0.0 { raw.iter().map(|w| w / total).collect() } else { equal.clone() };
+    let Some(cap) = cap else {
+        return weights;
+    };
+    if cap * (n as f64) < 1.0 - 1e-12 {
+        return equal;
+    }
+
+    let mut capped = vec![false; n];
+    loop {
+        let over: Vec<usize> = (0..n).filter(|&i| !capped[i] && weights[i] > cap + 1e-12).collect();
+        if over.is_empty() {
+            return weights;
+        }
+        for i in over {
+            capped[i] = true;
+            weights[i] = cap;
+        }
+        let fixed: f64 = (0..n).filter(|&i| capped[i]).map(|i| weights[i]).sum();
+        let free: f64 = (0..n).filter(|&i| !capped[i]).map(|i| weights[i]).sum();
+        if free <= 0.0 {
+            return weights;
+        }
+        let scale = (1.0 - fixed) / free;
+        for i in (0..n).filter(|&i| !capped[i]) {
+            weights[i] *= scale;
+        }
+    }
+}
+
+/// Value of each holding on `date`: its quantity at its price, carried
+/// forward at most `MAX_CARRY_FORWARD_DAYS`. Holdings without a price are
+/// excluded and grow like the rest of the basket since the last rebalance
+/// (see price_fallback::stand_in_ratio); fails when they held too much of it.
+fn holding_values(
+    quantities: &HashMap<String, f64>,
+    rebalance_values: &HashMap<String, f64>,
+    last_prices: &HashMap<String, (f64, NaiveDate)>,
+    date: NaiveDate,
+) -> Result<HashMap<String, f64>, String> {
+    let at_rebalance = |coin_id: &str| rebalance_values.get(coin_id).copied().unwrap_or(0.0);
+    let day_values: Vec<(&String, Option<f64>)> = quantities
+        .iter()
+        .map(|(coin_id, quantity)| {
+            let value = last_prices
+                .get(coin_id)
+                .filter(|(_, from)| (date - *from).num_days() <= MAX_CARRY_FORWARD_DAYS)
+                .map(|(price, _)| quantity * price);
+            (coin_id, value)
+        })
+        .collect();
+
+    let decimal = |value: f64| price_utils::decimal_from_f64(value).unwrap_or(Decimal::ZERO);
+    let values: Vec<(Decimal, Option<Decimal>)> = day_values
+        .iter()
+        .map(|(coin_id, value)| (decimal(at_rebalance(coin_id)), value.map(decimal)))
+        .collect();
+    let ratio = price_fallback::stand_in_ratio(&values)?.and_then(|ratio| ratio.to_f64());
+
+    Ok(day_values
+        .into_iter()
+        .map(|(coin_id, value)| {
+            let value = value.unwrap_or_else(|| at_rebalance(coin_id) * ratio.unwrap_or(0.0));
+            (coin_id.clone(), value)
+        })
+        .collect())
+}
+
+/// Run one scenario over `dates` (consecutive days)
+pub fn simulate(
+    params: &ScenarioParams,
+    dates: &[NaiveDate],
+    rankings: &HashMap<NaiveDate, Vec<RankedCoin>>,
+    prices: &PriceMap,
+    initial_value: f64,
+) -> Simulation {
+    let mut simulation = Simulation::default();
+    let mut cash = initial_value;
+    let mut quantities: HashMap<String, f64> = HashMap::new();
+    let mut rebalance_values: HashMap<String, f64> = HashMap::new();
+    let mut last_prices: HashMap<String, (f64, NaiveDate)> = HashMap::new();
+
+    for (day, date) in dates.iter().enumerate() {
+        for coin_id in quantities.keys() {
+            let price = prices.get(&(coin_id.clone(), *date)).and_then(|price| price.to_f64());
+            if let Some(price) = price.filter(|price| *price > 0.0) {
+                last_prices.insert(coin_id.clone(), (price, *date));
+            }
+        }
+
+        let rebalance_day = day % params.rebalance_period_days as usize == 0;
+        let ranking = rankings.get(date).filter(|r| rebalance_day && !r.is_empty());
+        for coin in ranking.into_iter().flatten() {
+            last_prices.insert(coin.coin_id.clone(), (coin.price, *date));
+        }
+        let held = holding_values(&quantities, &rebalance_values, &last_prices, *date);
+
+        if let (Some(ranking), Ok(held)) = (ranking, &held) {
+            let value = cash + held.values().sum::<f64>();
+
+            let incumbents: HashSet<String> = quantities.keys().cloned().collect();
+            let selected = select_with_buffer(ranking, &incumbents, params.top_n, params.ranking_buffer);
+            let raw: Vec<f64> = match params.weight_strategy {
+                WeightStrategy::Equal => vec![1.0; selected.len()],
+                WeightStrategy::MarketCap => {
+                    let caps: HashMap<&str, f64> =
+                        ranking.iter().map(|c| (c.coin_id.as_str(), c.market_cap)).collect();
+                    selected.iter().map(|c| caps[c.as_str()]).collect()
+                }
+            };
+            let weights: HashMap<&str, f64> = selected
+                .iter()
+                .map(String::as_str)
+                .zip(cap_weights(&raw, params.max_weight))
+                .collect();
+
+            // Sells of dropped coins and trades of held ones to their target
+            let traded: f64 = incumbents
+                .iter()
+                .map(String::as_str)
+                .chain(selected.iter().map(String::as_str).filter(|c| !incumbents.contains(*c)))
+                .map(|c| (value * weights.get(c).copied().unwrap_or(0.0) - held.get(c).copied().unwrap_or(0.0)).abs())
+                .sum();
+            let fees = traded * params.fee_rate;
+            let invested = value - fees;
+
+            if simulation.rebalances > 0 && value > 0.0 {
+                simulation.turnovers.push(traded / 2.0 / value);
+                simulation.constituent_changes += selected.iter().filter(|c| !incumbents.contains(*c)).count();
+            }
+            rebalance_values = selected
+                .iter()
+                .map(|c| (c.clone(), invested * weights[c.as_str()]))
+                .collect();
+            quantities = rebalance_values
+                .iter()
+                .map(|(c, value)| (c.clone(), value / last_prices[c].0))
+                .collect();
+            cash = 0.0;
+            simulation.rebalances += 1;
+            simulation.constituent_counts.push(selected.len());
+            simulation.total_fees += fees;
+            simulation.values.push(Some(invested));
+            continue;
+        }
+
+        simulation.values.push(held.ok().map(|held| cash + held.values().sum::<f64>()));
+    }
+    if let Some(factor) = params.leverage {
+        simulation.values = leveraged_values(&simulation.values, factor);
+    }
+    simulation
+}
+
+/// Daily NAV of a leveraged scenario from its basket values, reset daily
+/// like a leveraged index's (see services::leverage) and starting from the
+/// first value. Days without a basket value have no NAV; the next day
+/// valued applies the return since the last one.
+pub fn leveraged_values(values: &[Option<f64>], factor: Decimal) -> Vec<Option<f64>> {
+    let mut last: Option<(Decimal, Decimal)> = None;
+    values
+        .iter()
+        .map(|value| {
+            let basket = Decimal::from_f64_retain((*value)?)?;
+            let nav = match last {
+                Some((basket_before, nav)) => leverage::next_nav(nav, basket_before, basket, factor),
+                None => basket,
+            };
+            last = Some((basket, nav));
+            nav.to_f64()
+        })
+        .collect()
+}
+
+/// Stats of a finished simulation
+pub fn summarize(name: String, scenario: BacktestScenario, simulation: &Simulation, initial_value: f64) -> BacktestScenarioResult {
+    let stats = rolling_stats::compute(&simulation.values);
+    let final_value = simulation.values.iter().rev().find_map(|value| *value).unwrap_or(initial_value);
+    let days = simulation.values.len().saturating_sub(1);
+
+    let total_return = (initial_value > 0.0).then(|| final_value / initial_value - 1.0);
+    let annualized_return = total_return
+        .filter(|r| days > 0 && *r > -1.0)
+        .map(|r| (1.0 + r).powf(DAYS_PER_YEAR / days as f64) - 1.0);
+    let sharpe_ratio = match (annualized_return, stats.annualized_volatility) {
+        (Some(r), Some(v)) if v > 0.0 => Some(r / v),
+        _ => None,
+    };
+    let mean = |values: &[f64]| (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+    let counts: Vec<f64> = simulation.constituent_counts.iter().map(|c| *c as f64).collect();
+
+    BacktestScenarioResult {
+        name,
+        scenario,
+        final_value,
+        total_return,
+        annualized_return,
+        annualized_volatility: stats.annualized_volatility,
+        sharpe_ratio,
+        max_drawdown: stats.max_drawdown,
+        rebalances: simulation.rebalances,
+        avg_turnover: mean(&simulation.turnovers),
+        constituent_changes: simulation.constituent_changes,
+        avg_constituents: mean(&counts).unwrap_or(0.0),
+        total_fees: simulation.total_fees,
+    }
+}
+
+/// The `limit` largest coins of the universe on `date` with a market cap
+/// and price, largest first
+///
+/// Dates the retention policy downsampled are ranked by each coin's latest
+/// row of the week kept (see price_retention::PriceCoverage).
+async fn rank_universe(
+    db: &DatabaseConnection,
+    category: Option<&str>,
+    coin_ids: &[String],
+    date: NaiveDate,
+    coverage: PriceCoverage,
+    limit: usize,
+) -> Result<Vec<RankedCoin>, Box<dyn std::error::Error + Send + Sync>> {
+    let (from, to) = coverage.window(date);
+    let mut query = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::Date.between(from, to))
+        .filter(coins_historical_prices::Column::MarketCap.gt(Decimal::ZERO))
+        .filter(coins_historical_prices::Column::Price.gt(Decimal::ZERO));
+    if let Some(category) = category {
+        let members: Vec<String> = category_service::get_category_members_at(db, category, date)
+            .await?
+            .into_iter()
+            .map(|m| m.coin_id)
+            .collect();
+        if members.is_empty() {
+            return Ok(Vec::new());
+        }
+        query = query.filter(coins_historical_prices::Column::CoinId.is_in(members));
+    } else if !coin_ids.is_empty() {
+        query = query.filter(coins_historical_prices::Column::CoinId.is_in(coin_ids.to_vec()));
+    }
+    if from == to {
+        query = query.limit(limit as u64);
+    }
+
+    let rows = query
+        .order_by_desc(coins_historical_prices::Column::Date)
+        .order_by_desc(coins_historical_prices::Column::MarketCap)
+        .order_by_asc(coins_historical_prices::Column::CoinId)
+        .all(db)
+        .await?;
+
+    // Each coin's latest row of the window
+    let mut seen = HashSet::new();
+    let mut ranking: Vec<RankedCoin> = rows
+        .into_iter()
+        .filter(|row| seen.insert(row.coin_id.clone()))
+        .filter_map(|row| {
+            Some(RankedCoin {
+                market_cap: row.market_cap?.to_f64()?,
+                price: row.price.to_f64()?,
+                coin_id: row.coin_id,
+            })
+        })
+        .collect();
+    ranking.sort_by(|a, b| b.market_cap.total_cmp(&a.market_cap).then_with(|| a.coin_id.cmp(&b.coin_id)));
+    ranking.truncate(limit);
+    Ok(ranking)
+}
+
+/// Run every scenario of a validated request
+pub async fn compare(
+    db: &DatabaseConnection,
+    request: &BacktestCompareRequest,
+) -> Result<BacktestCompareResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let initial_value = request.initial_value.unwrap_or(DEFAULT_INITIAL_VALUE);
+    let scenarios: Vec<(String, BacktestScenario)> = request
+        .scenarios
+        .iter()
+        .enumerate()
+        .map(|(i, scenario)| {
+            let params = ScenarioParams::from_scenario(scenario);
+            let resolved = BacktestScenario {
+                rebalance_period_days: Some(params.rebalance_period_days),
+                weight_strategy: Some(
+                    scenario.weight_strategy.as_deref().unwrap_or(DEFAULT_WEIGHT_STRATEGY).to_lowercase(),
+                ),
+                trading_fee: Some(scenario.trading_fee.unwrap_or(DEFAULT_TRADING_FEE)),
+                spread: Some(scenario.spread.unwrap_or(DEFAULT_SPREAD)),
+                ..scenario.clone()
+            };
+            (scenario.name.clone().unwrap_or_else(|| format!("scenario {}", i + 1)), resolved)
+        })
+        .collect();
+
+    let days = (request.end_date - request.start_date).num_days();
+    let dates: Vec<NaiveDate> = (0..=days).map(|d| request.start_date + Duration::days(d)).collect();
+    let rebalance_dates: BTreeSet<NaiveDate> = scenarios
+        .iter()
+        .flat_map(|(_, scenario)| {
+            let period = ScenarioParams::from_scenario(scenario).rebalance_period_days as usize;
+            dates.iter().step_by(period).copied().collect::<Vec<_>>()
+        })
+        .collect();
+    let depth = scenarios.iter().map(|(_, s)| s.top_n + s.ranking_buffer).max().unwrap_or(0);
+
+    let mut seen = HashSet::new();
+    let coin_ids: Vec<String> = request
+        .coin_ids
+        .iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect();
+    let category = request.category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+
+    let retention = RetentionConfig::from_env();
+    let today = Utc::now().date_naive();
+    let mut rankings = HashMap::new();
+    for date in rebalance_dates {
+        let coverage = retention.coverage(today, date);
+        rankings.insert(date, rank_universe(db, category, &coin_ids, date, coverage, depth).await?);
+    }
+    let universe: Vec<String> = rankings
+        .values()
+        .flatten()
+        .map(|coin| coin.coin_id.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let prices = price_utils::load_coins_historical_prices(db, &universe, request.start_date, request.end_date).await?;
+
+    tracing::info!(
+        scenarios = scenarios.len(),
+        coins = universe.len(),
+        "Backtesting {} to {}",
+        request.start_date,
+        request.end_date
+    );
+
+    let results = scenarios
+        .into_iter()
+        .map(|(name, scenario)| {
+            let params = ScenarioParams::from_scenario(&scenario);
+            let simulation = simulate(&params, &dates, &rankings, &prices, initial_value);
+            summarize(name, scenario, &simulation, initial_value)
+        })
+        .collect();
+
+    Ok(BacktestCompareResponse {
+        start_date: request.start_date,
+        end_date: request.end_date,
+        initial_value,
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(coins: &[(&str, f64)]) -> Vec<RankedCoin> {
+        coins
+            .iter()
+            .map(|(coin_id, market_cap)| RankedCoin { coin_id: coin_id.to_string(), market_cap: *market_cap, price: 1.0 })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_with_buffer() {
+        let ranking = ranked(&[("a", 5.0), ("b", 4.0), ("c", 3.0), ("d", 2.0), ("e", 1.0)]);
+        let incumbents: HashSet<String> = ["a", "d"].iter().map(|c| c.to_string()).collect();
+
+        assert_eq!(select_with_buffer(&ranking, &incumbents, 2, 0), ["a", "b"]);
+        // d (rank 4) stays within 2 + 2 ranks
+        assert_eq!(select_with_buffer(&ranking, &incumbents, 2, 2), ["a", "d"]);
+        assert_eq!(select_with_buffer(&ranking, &incumbents, 3, 1), ["a", "b", "d"]);
+        assert_eq!(select_with_buffer(&ranking, &HashSet::new(), 10, 3).len(), 5);
+    }
+
+    #[test]
+    fn test_cap_weights() {
+        let weights = cap_weights(&[70.0, 20.0, 10.0], Some(0.5));
+        assert!((weights[0] - 0.5).abs() < 1e-12);
+        // The 0.2 excess goes 2:1 to the others
+        assert!((weights[1] - 20.0 / 30.0 * 0.5).abs() < 1e-12);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        assert_eq!(cap_weights(&[3.0, 1.0], None), vec![0.75, 0.25]);
+        // Two coins can't both stay under 40%
+        assert_eq!(cap_weights(&[3.0, 1.0], Some(0.4)), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_simulate_charges_fees_and_tracks_prices() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let dates: Vec<NaiveDate> = (0..4).map(|d| start + Duration::days(d)).collect();
+        let rankings = HashMap::from([
+            (dates[0], ranked(&[("a", 1.0), ("b", 1.0)])),
+            (dates[2], ranked(&[("a", 1.0), ("c", 1.0)])),
+        ]);
+        // a doubles on day 1 and falls back on day 2; b has no price after day 0
+        let prices: PriceMap = [
+            (("a", 1), dec!(2)),
+            (("a", 2), dec!(1)),
+            (("a", 3), dec!(2)),
+            (("c", 3), dec!(1)),
+        ]
+        .into_iter()
+        .map(|((coin, day), price)| ((coin.to_string(), dates[day]), price))
+        .collect();
+        let params = ScenarioParams {
+            top_n: 2,
+            rebalance_period_days: 2,
+            ranking_buffer: 0,
+            weight_strategy: WeightStrategy::Equal,
+            max_weight: None,
+            fee_rate: 0.01,
+            leverage: None,
+        };
+
+        let simulation = simulate(&params, &dates, &rankings, &prices, 100.0);
+        assert_eq!(simulation.rebalances, 2);
+        assert!((simulation.values[0].unwrap() - 99.0).abs() < 1e-9);
+        // a doubled, b carried at its last price
+        assert!((simulation.values[1].unwrap() - 148.5).abs() < 1e-9);
+        // Day 2: b drops out of the ranking and is sold for c
+        assert_eq!(simulation.constituent_changes, 1);
+        assert_eq!(simulation.constituent_counts, [2, 2]);
+        assert!((simulation.turnovers[0] - 0.5).abs() < 1e-9);
+        assert!((simulation.total_fees - (1.0 + 0.99)).abs() < 1e-9);
+
+        let scenario = BacktestScenario {
+            name: None,
+            top_n: 2,
+            rebalance_period_days: Some(2),
+            ranking_buffer: 0,
+            weight_strategy: None,
+            max_weight_pct: None,
+            trading_fee: None,
+            spread: None,
+            leverage_factor: None,
+        };
+        let result = summarize("s".to_string(), scenario, &simulation, 100.0);
+        assert_eq!(result.rebalances, 2);
+        assert!((result.total_return.unwrap() - (simulation.values[3].unwrap() / 100.0 - 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_simulate_excludes_coins_without_recent_prices() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let dates: Vec<NaiveDate> = (0..10).map(|d| start + Duration::days(d)).collect();
+        // a is priced daily and doubles on day 8; b has no price after day 0
+        let prices: PriceMap = (1..10)
+            .map(|day| (("a".to_string(), dates[day]), if day < 8 { dec!(1) } else { dec!(2) }))
+            .collect();
+        let params = ScenarioParams {
+            top_n: 2,
+            rebalance_period_days: 30,
+            ranking_buffer: 0,
+            weight_strategy: WeightStrategy::MarketCap,
+            max_weight: None,
+            fee_rate: 0.0,
+            leverage: None,
+        };
+        let run = |caps: &[(&str, f64)]| {
+            let rankings = HashMap::from([(dates[0], ranked(caps))]);
+            simulate(&params, &dates, &rankings, &prices, 100.0).values
+        };
+
+        // b carried forward for 7 days, then its 10% grows like a
+        let values = run(&[("a", 9.0), ("b", 1.0)]);
+        assert!((values[7].unwrap() - 100.0).abs() < 1e-9);
+        assert!((values[8].unwrap() - 200.0).abs() < 1e-9);
+
+        // 30% is too much to exclude
+        let values = run(&[("a", 7.0), ("b", 3.0)]);
+        assert!(values[7].is_some());
+        assert_eq!(values[8], None);
+    }
+
+    #[test]
+    fn test_leveraged_values() {
+        let values = [Some(100.0), Some(110.0), Some(99.0)];
+        assert_eq!(leveraged_values(&values, dec!(-1)), [Some(100.0), Some(90.0), Some(99.0)]);
+        assert_eq!(leveraged_values(&values, dec!(2)), [Some(100.0), Some(120.0), Some(96.0)]);
+
+        // The return over a gap is applied once, and losses stop at zero
+        assert_eq!(leveraged_values(&[Some(100.0), None, Some(120.0)], dec!(2)), [Some(100.0), None, Some(140.0)]);
+        assert_eq!(leveraged_values(&[Some(100.0), Some(40.0), Some(80.0)], dec!(-3)), [Some(100.0), Some(280.0), Some(0.0)]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Stablecoin cash buffer of an index
+//!
+//! A conservative index can hold part of its value in a stablecoin instead of
+//! constituents: index_metadata.cash_buffer_pct (e.g. 5.0 for 5%) in
+//! cash_buffer_symbol (USDC when unset). Rebalances add it as a last position
+//! with coin_id `cash:<SYMBOL>` and weight/value computed by
+//! `rebalance_math::compose`. The buffer bypasses constituent
+//! selection, so the category blacklist (which excludes stablecoins) doesn't
+//! apply to it.
+//!
+//! The buffer is valued at a fixed price of 1: price lookups (see
+//! `price_utils::get_or_fetch_coins_historical_price`) answer `PRICE` for cash
+//! coin ids without touching coins_historical_prices or CoinGecko. Since
+//! that only holds while the stablecoin keeps its peg, `depegged` checks the
+//! buffer stablecoins' market prices (raised as alerts by the price
+//! reconciliation job).
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::entities::{coins_historical_prices, index_metadata, prelude::*};
+use crate::services::price_reconciliation::deviation_bps;
+use crate::services::rebalance_math::CashAllocation;
+
+/// Prefix of the coin_id of cash positions
+pub const COIN_ID_PREFIX: &str = "cash:";
+
+pub const DEFAULT_SYMBOL: &str = "USDC";
+
+/// Price of a cash position, whatever the date
+pub const PRICE: f64 = 1.0;
+
+/// Distance from 1 past which a buffer stablecoin counts as depegged
+pub const DEPEG_THRESHOLD_BPS: i32 = 100;
+
+/// Whether `coin_id` is a cash position rather than a CoinGecko coin
+pub fn is_cash(coin_id: &str) -> bool {
+    coin_id.starts_with(COIN_ID_PREFIX)
+}
+
+/// Coin id of the cash position held in `symbol`
+pub fn coin_id(symbol: &str) -> String {
+    format!("{}{}", COIN_ID_PREFIX, symbol.to_uppercase())
+}
+
+/// Validates a buffer percentage: above 0 and below 100
+pub fn validate_pct(pct: Decimal) -> Result<(), String> {
+    if pct <= Decimal::ZERO || pct >= Decimal::ONE_HUNDRED {
+        return Err(format!("cash_buffer_pct must be above 0 and below 100, got {}", pct));
+    }
+    Ok(())
+}
+
+/// Cash buffer of an index with its symbol, if it has a valid one
+pub fn for_index(index: &index_metadata::Model) -> Option<(String, CashAllocation)> {
+    let pct = index.cash_buffer_pct.filter(|pct| !pct.is_zero())?;
+    if let Err(e) = validate_pct(pct) {
+        tracing::warn!(index_id = index.index_id, "Ignoring cash buffer: {}", e);
+        return None;
+    }
+
+    let symbol = index
+        .cash_buffer_symbol
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_SYMBOL)
+        .to_uppercase();
+    let allocation = CashAllocation {
+        coin_id: coin_id(&symbol),
+        share: pct / Decimal::ONE_HUNDRED,
+    };
+    Some((symbol, allocation))
+}
+
+/// Buffer stablecoins whose market price on `date` is off peg, with that
+/// price
+///
+/// A symbol can belong to several coins, so each one's price is taken from
+/// its coin with the largest market cap that day.
+pub async fn depegged(db: &DatabaseConnection, date: NaiveDate) -> Result<Vec<(String, Decimal)>, sea_orm::DbErr> {
+    let symbols: BTreeSet<String> = IndexMetadata::find()
+        .filter(index_metadata::Column::CashBufferPct.is_not_null())
+        .all(db)
+        .await?
+        .iter()
+        .filter_map(|index| for_index(index).map(|(symbol, _)| symbol))
+        .collect();
+    if symbols.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut prices: HashMap<String, coins_historical_prices::Model> = HashMap::new();
+    for row in CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::Symbol.is_in(symbols.iter().cloned()))
+        .filter(coins_historical_prices::Column::Date.eq(date))
+        .all(db)
+        .await?
+    {
+        let largest = prices.get(&row.symbol).and_then(|p| p.market_cap);
+        if largest.is_none() || row.market_cap > largest {
+            prices.insert(row.symbol.clone(), row);
+        }
+    }
+
+    let mut depegged: Vec<(String, Decimal)> = prices
+        .into_values()
+        .filter(|row| deviation_bps(row.price, Decimal::ONE) > DEPEG_THRESHOLD_BPS)
+        .map(|row| (row.symbol, row.price))
+        .collect();
+    depegged.sort();
+    Ok(depegged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_cash_coin_ids() {
+        assert_eq!(coin_id("usdc"), "cash:USDC");
+        assert!(is_cash("cash:USDC"));
+        assert!(!is_cash("usd-coin"));
+    }
+
+    #[test]
+    fn test_validate_pct() {
+        assert!(validate_pct(dec!(5)).is_ok());
+        assert!(validate_pct(dec!(0)).is_err());
+        assert!(validate_pct(dec!(100)).is_err());
+        assert!(validate_pct(dec!(-1)).is_err());
+    }
+}
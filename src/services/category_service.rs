@@ -1,6 +1,9 @@
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::{HashMap, HashSet};
 
-use crate::entities::{category_membership, coingecko_categories, prelude::*};
+use crate::entities::{category_membership, coingecko_categories, coins, prelude::*};
+use crate::services::pricing_time;
 
 /// Get the primary category/sector for a coin
 /// Returns the human-readable category name (e.g., "Layer 1 (L1)")
@@ -29,4 +32,247 @@ pub async fn get_coin_category(
 
     // Default if no category found
     Ok("Uncategorized".to_string())
-}
\ No newline at end of file
+}
+
+/// Point-in-time membership rule: a coin belongs to a category at `at` when it was
+/// added on or before `at` and not yet removed (removal is exclusive).
+pub fn is_member_at(membership: &category_membership::Model, at: NaiveDateTime) -> bool {
+    membership.added_date <= at && membership.removed_date.is_none_or(|removed| removed > at)
+}
+
+/// Get the memberships of a category as of `date`'s pricing cutoff
+pub async fn get_category_members_at(
+    db: &DatabaseConnection,
+    category_id: &str,
+    date: NaiveDate,
+) -> Result<Vec<category_membership::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let date_time = pricing_time::cutoff(date);
+
+    let memberships = CategoryMembership::find()
+        .filter(category_membership::Column::CategoryId.eq(category_id))
+        .filter(category_membership::Column::AddedDate.lte(date_time))
+        .filter(
+            Condition::any()
+                .add(category_membership::Column::RemovedDate.is_null())
+                .add(category_membership::Column::RemovedDate.gt(date_time)),
+        )
+        .order_by_asc(category_membership::Column::CoinId)
+        .all(db)
+        .await?;
+
+    // SQL filter mirrors is_member_at; re-apply so the rule has one definition
+    Ok(memberships
+        .into_iter()
+        .filter(|m| is_member_at(m, date_time))
+        .collect())
+}
+
+/// Kind of inconsistency found in category membership intervals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipIssueKind {
+    /// Two intervals for the same coin/category overlap in time
+    Overlap,
+    /// `removed_date` is on or before `added_date`
+    InvertedInterval,
+    /// Membership references a coin_id that is not in the coins table
+    MissingCoin,
+}
+
+impl MembershipIssueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MembershipIssueKind::Overlap => "overlap",
+            MembershipIssueKind::InvertedInterval => "inverted_interval",
+            MembershipIssueKind::MissingCoin => "missing_coin",
+        }
+    }
+}
+
+/// A single membership inconsistency, identified by the offending row ids
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipIssue {
+    pub kind: MembershipIssueKind,
+    pub coin_id: String,
+    pub category_id: String,
+    pub membership_ids: Vec<i32>,
+}
+
+/// Find overlapping and inverted intervals per (coin_id, category_id)
+///
+/// Pure function so the point-in-time rules can be tested without a database.
+pub fn find_interval_issues(memberships: &[category_membership::Model]) -> Vec<MembershipIssue> {
+    let mut grouped: HashMap<(&str, &str), Vec<&category_membership::Model>> = HashMap::new();
+    for m in memberships {
+        grouped
+            .entry((m.coin_id.as_str(), m.category_id.as_str()))
+            .or_default()
+            .push(m);
+    }
+
+    let mut issues = Vec::new();
+    for ((coin_id, category_id), mut rows) in grouped {
+        rows.sort_by_key(|m| (m.added_date, m.id));
+
+        for m in &rows {
+            if m.removed_date.is_some_and(|removed| removed <= m.added_date) {
+                issues.push(MembershipIssue {
+                    kind: MembershipIssueKind::InvertedInterval,
+                    coin_id: coin_id.to_string(),
+                    category_id: category_id.to_string(),
+                    membership_ids: vec![m.id],
+                });
+            }
+        }
+
+        // Sorted by start: an interval overlaps its successor when it is still
+        // open (or ends after) the successor's start
+        for pair in rows.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            let overlaps = match prev.removed_date {
+                None => true,
+                Some(removed) => removed > next.added_date,
+            };
+            if overlaps {
+                issues.push(MembershipIssue {
+                    kind: MembershipIssueKind::Overlap,
+                    coin_id: coin_id.to_string(),
+                    category_id: category_id.to_string(),
+                    membership_ids: vec![prev.id, next.id],
+                });
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        (&a.category_id, &a.coin_id, &a.membership_ids).cmp(&(&b.category_id, &b.coin_id, &b.membership_ids))
+    });
+    issues
+}
+
+/// Scan all category memberships for overlapping/inverted intervals and
+/// memberships whose coin is missing from the coins table
+pub async fn check_membership_consistency(
+    db: &DatabaseConnection,
+) -> Result<Vec<MembershipIssue>, Box<dyn std::error::Error + Send + Sync>> {
+    let memberships = CategoryMembership::find().all(db).await?;
+
+    let mut issues = find_interval_issues(&memberships);
+
+    let known_coins: HashSet<String> = Coins::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c: coins::Model| c.coin_id)
+        .collect();
+
+    let mut missing: HashMap<(String, String), Vec<i32>> = HashMap::new();
+    for m in &memberships {
+        if !known_coins.contains(&m.coin_id) {
+            missing
+                .entry((m.coin_id.clone(), m.category_id.clone()))
+                .or_default()
+                .push(m.id);
+        }
+    }
+
+    for ((coin_id, category_id), membership_ids) in missing {
+        issues.push(MembershipIssue {
+            kind: MembershipIssueKind::MissingCoin,
+            coin_id,
+            category_id,
+            membership_ids,
+        });
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(date: &str) -> NaiveDateTime {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn membership(id: i32, coin_id: &str, added: &str, removed: Option<&str>) -> category_membership::Model {
+        category_membership::Model {
+            id,
+            coin_id: coin_id.to_string(),
+            category_id: "layer-1".to_string(),
+            added_date: dt(added),
+            removed_date: removed.map(dt),
+            created_at: None,
+            updated_at: None,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn test_is_member_at_boundaries() {
+        let m = membership(1, "bitcoin", "2024-01-01", Some("2024-06-01"));
+        assert!(!is_member_at(&m, dt("2023-12-31")));
+        assert!(is_member_at(&m, dt("2024-01-01")));
+        assert!(is_member_at(&m, dt("2024-05-31")));
+        // Removal date is exclusive
+        assert!(!is_member_at(&m, dt("2024-06-01")));
+    }
+
+    #[test]
+    fn test_is_member_at_open_interval() {
+        let m = membership(1, "bitcoin", "2024-01-01", None);
+        assert!(is_member_at(&m, dt("2030-01-01")));
+    }
+
+    #[test]
+    fn test_adjacent_intervals_are_consistent() {
+        let rows = vec![
+            membership(1, "bitcoin", "2024-01-01", Some("2024-03-01")),
+            membership(2, "bitcoin", "2024-03-01", None),
+        ];
+        assert!(find_interval_issues(&rows).is_empty());
+    }
+
+    #[test]
+    fn test_detects_overlap() {
+        let rows = vec![
+            membership(1, "bitcoin", "2024-01-01", Some("2024-04-01")),
+            membership(2, "bitcoin", "2024-03-01", None),
+        ];
+        let issues = find_interval_issues(&rows);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, MembershipIssueKind::Overlap);
+        assert_eq!(issues[0].membership_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_detects_two_open_intervals() {
+        let rows = vec![
+            membership(1, "bitcoin", "2024-01-01", None),
+            membership(2, "bitcoin", "2024-02-01", None),
+        ];
+        let issues = find_interval_issues(&rows);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, MembershipIssueKind::Overlap);
+    }
+
+    #[test]
+    fn test_detects_inverted_interval() {
+        let rows = vec![membership(1, "bitcoin", "2024-02-01", Some("2024-01-01"))];
+        let issues = find_interval_issues(&rows);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, MembershipIssueKind::InvertedInterval);
+    }
+
+    #[test]
+    fn test_different_coins_do_not_overlap() {
+        let rows = vec![
+            membership(1, "bitcoin", "2024-01-01", None),
+            membership(2, "ethereum", "2024-01-01", None),
+        ];
+        assert!(find_interval_issues(&rows).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Gas and transaction spend accounting
+//!
+//! Every transaction the backend sends through the BridgeProxy (ITP creation,
+//! weight updates) is recorded in chain_transactions from its receipt,
+//! reverted ones included since they pay for gas too. The USD cost uses the
+//! latest ETH price in coins_historical_prices on or before the day it was
+//! sent; a transaction sent without a known price keeps only its ETH cost.
+//! GET /admin/chain-spend?month= totals a calendar month for finance.
+
+use std::collections::BTreeMap;
+
+use alloy::{primitives::Address, rpc::types::TransactionReceipt};
+use chrono::{Months, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::entities::{chain_transactions, coins_historical_prices, prelude::*};
+use crate::models::chain_spend::{ChainSpendByKind, ChainSpendReport};
+
+/// Values of chain_transactions.kind
+pub mod kinds {
+    pub const CREATE_ITP: &str = "create_itp";
+    pub const REBALANCE: &str = "rebalance";
+    pub const SNAPSHOT_ANCHOR: &str = "snapshot_anchor";
+}
+
+/// CoinGecko id whose price converts gas costs to USD
+const ETH_COIN_ID: &str = "ethereum";
+
+/// How far back an ETH price is still used for a transaction
+const PRICE_LOOKBACK_DAYS: i64 = 7;
+
+const WEI_DECIMALS: u32 = 18;
+
+/// A mined transaction sent by the backend
+#[derive(Debug, Clone)]
+pub struct ChainTransaction {
+    pub kind: &'static str,
+    /// ITP symbol for creations, Orbit ITP address for weight updates,
+    /// "index:{id}" for snapshot anchors
+    pub reference: Option<String>,
+    pub tx_hash: String,
+    pub chain_id: u64,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub block_number: u64,
+    pub succeeded: bool,
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+}
+
+impl ChainTransaction {
+    pub fn from_receipt(
+        kind: &'static str,
+        reference: Option<String>,
+        chain_id: u64,
+        receipt: &TransactionReceipt,
+    ) -> Self {
+        Self {
+            kind,
+            reference,
+            tx_hash: format!("{:?}", receipt.transaction_hash),
+            chain_id,
+            from: receipt.from,
+            to: receipt.to,
+            block_number: receipt.block_number.unwrap_or(0),
+            succeeded: receipt.status(),
+            gas_used: receipt.gas_used as u64,
+            effective_gas_price: receipt.effective_gas_price,
+        }
+    }
+}
+
+/// Wei as a Decimal, scaled by `scale` decimals
+///
+/// The 96-bit mantissa covers about 79 billion ETH, far beyond any fee.
+fn wei(value: u128, scale: u32) -> Decimal {
+    Decimal::try_from_i128_with_scale(value.min(i128::MAX as u128) as i128, scale).unwrap_or(Decimal::MAX)
+}
+
+/// An amount of wei in ETH
+pub fn wei_to_eth(amount: u128) -> Decimal {
+    wei(amount, WEI_DECIMALS).normalize()
+}
+
+/// ETH paid for `gas_used` at `effective_gas_price` wei
+pub fn cost_eth(gas_used: u64, effective_gas_price: u128) -> Decimal {
+    wei_to_eth((gas_used as u128).saturating_mul(effective_gas_price))
+}
+
+/// Latest ETH price on or before `on`, within the lookback
+pub async fn eth_price_usd(db: &DatabaseConnection, on: NaiveDate) -> Result<Option<Decimal>, sea_orm::DbErr> {
+    Ok(CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq(ETH_COIN_ID))
+        .filter(coins_historical_prices::Column::Date.lte(on))
+        .filter(coins_historical_prices::Column::Date.gte(on - chrono::Duration::days(PRICE_LOOKBACK_DAYS)))
+        .order_by_desc(coins_historical_prices::Column::Date)
+        .one(db)
+        .await?
+        .map(|row| row.price))
+}
+
+/// Store a sent transaction with its ETH and USD cost
+pub async fn record(
+    db: &DatabaseConnection,
+    tx: ChainTransaction,
+    sent_at: NaiveDateTime,
+) -> Result<chain_transactions::Model, sea_orm::DbErr> {
+    let cost_eth = cost_eth(tx.gas_used, tx.effective_gas_price);
+    let eth_price_usd = eth_price_usd(db, sent_at.date()).await?;
+
+    chain_transactions::ActiveModel {
+        tx_hash: Set(tx.tx_hash),
+        kind: Set(tx.kind.to_string()),
+        reference: Set(tx.reference),
+        chain_id: Set(tx.chain_id as i64),
+        from_address: Set(tx.from.to_string()),
+        to_address: Set(tx.to.map(|to| to.to_string())),
+        block_number: Set(tx.block_number as i64),
+        succeeded: Set(tx.succeeded),
+        gas_used: Set(tx.gas_used as i64),
+        effective_gas_price_wei: Set(wei(tx.effective_gas_price, 0)),
+        cost_eth: Set(cost_eth),
+        eth_price_usd: Set(eth_price_usd),
+        cost_usd: Set(eth_price_usd.map(|price| (cost_eth * price).round_dp(6))),
+        sent_at: Set(sent_at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// First day of a `YYYY-MM` month
+pub fn parse_month(month: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()
+}
+
+/// Spend of the calendar month starting on `month`
+pub async fn monthly_report(db: &DatabaseConnection, month: NaiveDate) -> Result<ChainSpendReport, sea_orm::DbErr> {
+    let start = month.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let end = start + Months::new(1);
+    let transactions = ChainTransactions::find()
+        .filter(chain_transactions::Column::SentAt.gte(start))
+        .filter(chain_transactions::Column::SentAt.lt(end))
+        .order_by_asc(chain_transactions::Column::SentAt)
+        .all(db)
+        .await?;
+
+    Ok(summarize(month, transactions))
+}
+
+fn summarize(month: NaiveDate, transactions: Vec<chain_transactions::Model>) -> ChainSpendReport {
+    let mut by_kind: BTreeMap<String, ChainSpendByKind> = BTreeMap::new();
+    for tx in &transactions {
+        let kind = by_kind.entry(tx.kind.clone()).or_insert_with(|| ChainSpendByKind {
+            kind: tx.kind.clone(),
+            ..Default::default()
+        });
+        kind.transaction_count += 1;
+        kind.gas_used += tx.gas_used;
+        kind.cost_eth += tx.cost_eth;
+        kind.cost_usd += tx.cost_usd.unwrap_or_default();
+    }
+
+    ChainSpendReport {
+        month: month.format("%Y-%m").to_string(),
+        transaction_count: transactions.len(),
+        failed_count: transactions.iter().filter(|tx| !tx.succeeded).count(),
+        unpriced_count: transactions.iter().filter(|tx| tx.cost_usd.is_none()).count(),
+        gas_used: by_kind.values().map(|kind| kind.gas_used).sum(),
+        cost_eth: by_kind.values().map(|kind| kind.cost_eth).sum(),
+        cost_usd: by_kind.values().map(|kind| kind.cost_usd).sum(),
+        by_kind: by_kind.into_values().collect(),
+        transactions: transactions.into_iter().map(Into::into).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn transaction(kind: &str, succeeded: bool, cost_eth: Decimal, cost_usd: Option<Decimal>) -> chain_transactions::Model {
+        chain_transactions::Model {
+            id: 1,
+            tx_hash: "0xabc".to_string(),
+            kind: kind.to_string(),
+            reference: None,
+            chain_id: 42161,
+            from_address: Address::ZERO.to_string(),
+            to_address: None,
+            block_number: 1,
+            succeeded,
+            gas_used: 100_000,
+            effective_gas_price_wei: dec!(10000000),
+            cost_eth,
+            eth_price_usd: None,
+            cost_usd,
+            sent_at: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_cost_eth() {
+        // 300k gas at 0.01 gwei
+        assert_eq!(cost_eth(300_000, 10_000_000), dec!(0.000003));
+        assert_eq!(cost_eth(0, 10_000_000), Decimal::ZERO);
+        assert_eq!(cost_eth(21_000, 1_000_000_000_000), dec!(0.021));
+    }
+
+    #[test]
+    fn test_parse_month() {
+        assert_eq!(parse_month("2026-02"), NaiveDate::from_ymd_opt(2026, 2, 1));
+        assert_eq!(parse_month("2026-13"), None);
+        assert_eq!(parse_month("february"), None);
+    }
+
+    #[test]
+    fn test_summarize_by_kind() {
+        let month = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let report = summarize(
+            month,
+            vec![
+                transaction(kinds::REBALANCE, true, dec!(0.001), Some(dec!(3))),
+                transaction(kinds::CREATE_ITP, false, dec!(0.002), Some(dec!(6))),
+                transaction(kinds::REBALANCE, true, dec!(0.001), None),
+            ],
+        );
+
+        assert_eq!(report.month, "2026-02");
+        assert_eq!(report.transaction_count, 3);
+        assert_eq!(report.failed_count, 1);
+        assert_eq!(report.unpriced_count, 1);
+        assert_eq!(report.gas_used, 300_000);
+        assert_eq!(report.cost_eth, dec!(0.004));
+        assert_eq!(report.cost_usd, dec!(9));
+        let kinds: Vec<_> = report.by_kind.iter().map(|k| (k.kind.as_str(), k.transaction_count)).collect();
+        assert_eq!(kinds, vec![(kinds::CREATE_ITP, 1), (kinds::REBALANCE, 2)]);
+    }
+}
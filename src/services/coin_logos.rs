@@ -0,0 +1,235 @@
+//! Coin logo proxy
+//!
+//! GET /assets/{coin_id}/logo serves coin logos from the coin_logos table
+//! instead of letting the frontend hot-link the CoinGecko image URLs stored in
+//! coins.logo_address (which rate-limit and break). A logo is fetched on its
+//! first request, scaled down to at most MAX_SIZE pixels and stored as PNG; it
+//! is fetched again only when the coin's logo_address changes. Images the
+//! decoder can't read (e.g. SVG) are stored as fetched.
+
+use std::io::Cursor;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use image::{imageops::FilterType, ImageFormat};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+};
+
+use crate::entities::{coin_logos, coins, prelude::*};
+
+/// Largest stored width or height, in pixels
+pub const MAX_SIZE: u32 = 256;
+/// Smallest width or height a logo can be requested at
+pub const MIN_SIZE: u32 = 16;
+/// Larger source images are rejected
+const MAX_SOURCE_BYTES: usize = 2 * 1024 * 1024;
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("Failed to build logo HTTP client")
+});
+
+#[derive(Debug)]
+pub enum LogoError {
+    CoinNotFound(String),
+    NoLogo(String),
+    Fetch(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for LogoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogoError::CoinNotFound(coin_id) => write!(f, "Coin '{}' not found", coin_id),
+            LogoError::NoLogo(coin_id) => write!(f, "Coin '{}' has no logo", coin_id),
+            LogoError::Fetch(e) => write!(f, "Failed to fetch logo: {}", e),
+            LogoError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LogoError {}
+
+impl From<sea_orm::DbErr> for LogoError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        LogoError::Database(e)
+    }
+}
+
+/// Image bytes ready to serve
+#[derive(Debug, Clone)]
+pub struct Logo {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// `coin_id`'s logo, fetched and stored on first use
+///
+/// With `size`, PNG logos are scaled down to fit `size` x `size`.
+pub async fn get_logo(db: &DatabaseConnection, coin_id: &str, size: Option<u32>) -> Result<Logo, LogoError> {
+    let coin = Coins::find()
+        .filter(coins::Column::CoinId.eq(coin_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| LogoError::CoinNotFound(coin_id.to_string()))?;
+    let source_url = coin
+        .logo_address
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| LogoError::NoLogo(coin_id.to_string()))?;
+
+    let stored = CoinLogos::find_by_id(coin_id).one(db).await?;
+    let logo = match stored {
+        Some(stored) if stored.source_url == source_url => Logo { content_type: stored.content_type, data: stored.data },
+        stored => match fetch(&source_url).await {
+            Ok(logo) => {
+                save(db, coin_id, &source_url, &logo).await?;
+                logo
+            }
+            // Keep serving the old logo until the new one can be fetched
+            Err(e) => match stored {
+                Some(stored) => {
+                    tracing::warn!(coin_id = %coin_id, error = %e, "Failed to refresh logo, serving stored copy");
+                    Logo { content_type: stored.content_type, data: stored.data }
+                }
+                None => return Err(e),
+            },
+        },
+    };
+
+    match size {
+        Some(size) if size < MAX_SIZE => Ok(resize(logo, size)),
+        _ => Ok(logo),
+    }
+}
+
+async fn fetch(url: &str) -> Result<Logo, LogoError> {
+    let response = CLIENT
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| LogoError::Fetch(e.to_string()))?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let data = response.bytes().await.map_err(|e| LogoError::Fetch(e.to_string()))?;
+    if data.len() > MAX_SOURCE_BYTES {
+        return Err(LogoError::Fetch(format!("image is larger than {} bytes", MAX_SOURCE_BYTES)));
+    }
+
+    normalize(&data, &content_type)
+}
+
+/// The logo as PNG of at most MAX_SIZE pixels, or unchanged if it can't be decoded
+fn normalize(data: &[u8], content_type: &str) -> Result<Logo, LogoError> {
+    match image::load_from_memory(data) {
+        Ok(image) => {
+            let image = if image.width() > MAX_SIZE || image.height() > MAX_SIZE {
+                image.resize(MAX_SIZE, MAX_SIZE, FilterType::Lanczos3)
+            } else {
+                image
+            };
+            Ok(Logo { content_type: "image/png".to_string(), data: encode_png(&image)? })
+        }
+        Err(_) if content_type.starts_with("image/") => {
+            Ok(Logo { content_type: content_type.to_string(), data: data.to_vec() })
+        }
+        Err(e) => Err(LogoError::Fetch(format!("not an image ({})", e))),
+    }
+}
+
+fn resize(logo: Logo, size: u32) -> Logo {
+    if logo.content_type != "image/png" {
+        return logo;
+    }
+    let Ok(image) = image::load_from_memory_with_format(&logo.data, ImageFormat::Png) else {
+        return logo;
+    };
+    if image.width() <= size && image.height() <= size {
+        return logo;
+    }
+    match encode_png(&image.resize(size, size, FilterType::Lanczos3)) {
+        Ok(data) => Logo { content_type: logo.content_type, data },
+        Err(_) => logo,
+    }
+}
+
+fn encode_png(image: &image::DynamicImage) -> Result<Vec<u8>, LogoError> {
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .map_err(|e| LogoError::Fetch(format!("failed to encode PNG: {}", e)))?;
+    Ok(data)
+}
+
+async fn save(db: &DatabaseConnection, coin_id: &str, source_url: &str, logo: &Logo) -> Result<(), sea_orm::DbErr> {
+    let row = coin_logos::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        source_url: Set(source_url.to_string()),
+        content_type: Set(logo.content_type.clone()),
+        data: Set(logo.data.clone()),
+        fetched_at: Set(chrono::Utc::now().naive_utc()),
+    };
+    CoinLogos::insert(row)
+        .on_conflict(
+            OnConflict::column(coin_logos::Column::CoinId)
+                .update_columns([
+                    coin_logos::Column::SourceUrl,
+                    coin_logos::Column::ContentType,
+                    coin_logos::Column::Data,
+                    coin_logos::Column::FetchedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        encode_png(&DynamicImage::ImageRgba8(RgbaImage::new(width, height))).unwrap()
+    }
+
+    fn dimensions(logo: &Logo) -> (u32, u32) {
+        let image = image::load_from_memory(&logo.data).unwrap();
+        (image.width(), image.height())
+    }
+
+    #[test]
+    fn test_normalize_scales_down_large_images() {
+        let logo = normalize(&png(1024, 512), "image/png").unwrap();
+        assert_eq!(logo.content_type, "image/png");
+        assert_eq!(dimensions(&logo), (256, 128));
+
+        let logo = normalize(&png(64, 64), "application/octet-stream").unwrap();
+        assert_eq!(dimensions(&logo), (64, 64));
+    }
+
+    #[test]
+    fn test_normalize_keeps_undecodable_images() {
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+        let logo = normalize(svg, "image/svg+xml").unwrap();
+        assert_eq!(logo.content_type, "image/svg+xml");
+        assert_eq!(logo.data, svg);
+
+        assert!(matches!(normalize(b"<html></html>", "text/html"), Err(LogoError::Fetch(_))));
+    }
+
+    #[test]
+    fn test_resize() {
+        let logo = Logo { content_type: "image/png".to_string(), data: png(256, 256) };
+        assert_eq!(dimensions(&resize(logo.clone(), 32)), (32, 32));
+        assert_eq!(dimensions(&resize(Logo { data: png(20, 20), ..logo }, 32)), (20, 20));
+    }
+}
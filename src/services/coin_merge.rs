@@ -0,0 +1,302 @@
+//! Merging duplicate coins
+//!
+//! CoinGecko occasionally re-ids a coin, and the sync then knows it under
+//! two coin_ids: the old one with its history, listings and rebalances, and
+//! the new one it keeps fetching. `merge` moves every reference to the old
+//! (source) coin over to the new (target) one in a single transaction,
+//! deletes the source from coins and records the merge in token_migrations.
+//!
+//! Where the target already has a row for the same key (a price on the same
+//! date, a listing of the same pair on the same exchange, a yield rate, a
+//! logo, a label or a window's statistics) the target's row is kept and the
+//! source's dropped.
+//!
+//! A rebalance already holding the target as well as the source can't be
+//! renamed without listing the coin twice, so the merge fails with
+//! `MergeError::Conflict` and changes nothing. Snapshot proofs (see
+//! services::snapshot_log) rename merged coins in logged rebalances the
+//! same way, so rewritten rebalances still match what was logged.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, Set, Statement, TransactionTrait,
+};
+use serde_json::Value;
+
+use crate::entities::{coins, prelude::*, rebalances, token_migrations};
+use crate::services::{labels, rebalance_schema, rolling_stats};
+
+/// Tables referencing a coin by coin_id, with the columns that, together
+/// with coin_id, make a row unique (None where coin_id is not part of any
+/// unique key, so every row can move)
+const COIN_TABLES: [(&str, Option<&[&str]>); 11] = [
+    ("coins_historical_prices", Some(&["date"])),
+    ("crypto_listings", Some(&["exchange", "trading_pair"])),
+    ("index_constituents", Some(&["index_id"])),
+    ("market_cap_rankings", Some(&["date"])),
+    ("category_membership", Some(&["category_id", "removed_date"])),
+    ("coin_yield_rates", Some(&[])),
+    ("coin_sync_cursors", Some(&[])),
+    ("coin_logos", Some(&[])),
+    ("price_reconciliation_checks", Some(&["date", "source"])),
+    ("spread_observations", None),
+    ("category_change_events", None),
+];
+
+/// Tables referencing coins among other subjects: (table, type column, id
+/// column, type of a coin, columns that with type and id make a row unique)
+const SUBJECT_TABLES: [(&str, &str, &str, &str, &[&str]); 2] = [
+    ("labels", "entity_type", "entity_id", labels::entity_types::COIN, &["key", "value"]),
+    ("rolling_stats", "subject_type", "subject_id", rolling_stats::subjects::COIN, &["window_days", "as_of"]),
+];
+
+#[derive(Debug)]
+pub enum MergeError {
+    SameCoin,
+    CoinNotFound(String),
+    /// A rebalance holds both coins
+    Conflict { rebalance_id: i32 },
+    Database(DbErr),
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::SameCoin => write!(f, "Source and target coin must differ"),
+            MergeError::CoinNotFound(coin_id) => write!(f, "Coin {} not found", coin_id),
+            MergeError::Conflict { rebalance_id } => {
+                write!(f, "Rebalance {} holds both coins; merge its constituents first", rebalance_id)
+            }
+            MergeError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+impl From<DbErr> for MergeError {
+    fn from(e: DbErr) -> Self {
+        MergeError::Database(e)
+    }
+}
+
+/// Rows a merge touched, by table
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Rows now referencing the target
+    pub rows_moved: BTreeMap<String, u64>,
+    /// Source rows deleted because the target already had them (and the
+    /// source coin itself)
+    pub rows_dropped: BTreeMap<String, u64>,
+}
+
+/// Merge `source_coin_id` into `target_coin_id`; both must exist in coins
+pub async fn merge(
+    db: &DatabaseConnection,
+    source_coin_id: &str,
+    target_coin_id: &str,
+    now: NaiveDateTime,
+) -> Result<MergeSummary, MergeError> {
+    if source_coin_id == target_coin_id {
+        return Err(MergeError::SameCoin);
+    }
+
+    let txn = db.begin().await?;
+    for coin_id in [source_coin_id, target_coin_id] {
+        let exists = Coins::find()
+            .filter(coins::Column::CoinId.eq(coin_id))
+            .one(&txn)
+            .await?
+            .is_some();
+        if !exists {
+            return Err(MergeError::CoinNotFound(coin_id.to_string()));
+        }
+    }
+
+    let mut summary = MergeSummary::default();
+    for (table, key) in COIN_TABLES {
+        let (moved, dropped) = move_rows(&txn, table, "coin_id", None, key, source_coin_id, target_coin_id).await?;
+        summary.rows_moved.insert(table.to_string(), moved);
+        summary.rows_dropped.insert(table.to_string(), dropped);
+    }
+    for (table, type_column, id_column, coin_type, key) in SUBJECT_TABLES {
+        let subject = Some((type_column, coin_type));
+        let (moved, dropped) =
+            move_rows(&txn, table, id_column, subject, Some(key), source_coin_id, target_coin_id).await?;
+        summary.rows_moved.insert(table.to_string(), moved);
+        summary.rows_dropped.insert(table.to_string(), dropped);
+    }
+
+    let rewritten = rewrite_rebalances(&txn, source_coin_id, target_coin_id).await?;
+    summary.rows_moved.insert("rebalances".to_string(), rewritten);
+
+    let deleted = Coins::delete_many()
+        .filter(coins::Column::CoinId.eq(source_coin_id))
+        .exec(&txn)
+        .await?;
+    summary.rows_dropped.insert("coins".to_string(), deleted.rows_affected);
+
+    token_migrations::ActiveModel {
+        source_coin_id: Set(source_coin_id.to_string()),
+        target_coin_id: Set(target_coin_id.to_string()),
+        rows_moved: Set(serde_json::json!(summary.rows_moved)),
+        merged_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok(summary)
+}
+
+/// Point `table`'s source rows at the target, unless the target already
+/// has a row with the same `key`; those are deleted instead. `subject`
+/// (type column, type value) limits both to the rows of coins in tables
+/// shared with other subjects. Returns the rows moved and dropped.
+async fn move_rows(
+    txn: &DatabaseTransaction,
+    table: &str,
+    id_column: &str,
+    subject: Option<(&str, &str)>,
+    key: Option<&[&str]>,
+    source_coin_id: &str,
+    target_coin_id: &str,
+) -> Result<(u64, u64), DbErr> {
+    let backend = txn.get_database_backend();
+    // The type filter binds the last placeholder of either statement
+    let of_subject = |placeholder: usize| {
+        subject.map_or(String::new(), |(type_column, _)| format!(" AND {type_column} = ${placeholder}"))
+    };
+    let with_type = |mut values: Vec<sea_orm::Value>| {
+        values.extend(subject.map(|(_, coin_type)| coin_type.into()));
+        values
+    };
+    // NULLs count as equal, as for the active (removed_date IS NULL) memberships
+    let unless_taken = match key {
+        None => String::new(),
+        Some(key) => {
+            let same_key: String = subject
+                .map(|(type_column, _)| type_column)
+                .into_iter()
+                .chain(key.iter().copied())
+                .map(|column| format!(" AND t.{column} IS NOT DISTINCT FROM s.{column}"))
+                .collect();
+            format!("AND NOT EXISTS (SELECT 1 FROM {table} t WHERE t.{id_column} = $2{same_key})")
+        }
+    };
+
+    let moved = txn
+        .execute(Statement::from_sql_and_values(
+            backend,
+            format!(
+                "UPDATE {table} s SET {id_column} = $2 WHERE s.{id_column} = $1{} {unless_taken}",
+                of_subject(3)
+            ),
+            with_type(vec![source_coin_id.into(), target_coin_id.into()]),
+        ))
+        .await?
+        .rows_affected();
+
+    let dropped = txn
+        .execute(Statement::from_sql_and_values(
+            backend,
+            format!("DELETE FROM {table} WHERE {id_column} = $1{}", of_subject(2)),
+            with_type(vec![source_coin_id.into()]),
+        ))
+        .await?
+        .rows_affected();
+
+    Ok((moved, dropped))
+}
+
+/// Replace the source coin_id in the coins JSON of every rebalance holding
+/// it; returns the rebalances rewritten
+///
+/// Rows are upgraded (so v1 `coinId` entries match too) and written back
+/// in the current schema version, with every other field as stored.
+async fn rewrite_rebalances(
+    txn: &DatabaseTransaction,
+    source_coin_id: &str,
+    target_coin_id: &str,
+) -> Result<u64, MergeError> {
+    let holding = Rebalances::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            txn.get_database_backend(),
+            "SELECT * FROM rebalances
+             WHERE coins @> jsonb_build_array(jsonb_build_object('coin_id', $1::text))
+                OR coins @> jsonb_build_array(jsonb_build_object('coinId', $1::text))",
+            [source_coin_id.into()],
+        ))
+        .all(txn)
+        .await?;
+
+    let mut rewritten = 0;
+    for rebalance in holding {
+        let mut coins = rebalance_schema::upgrade(rebalance.coins.clone())
+            .map_err(|e| DbErr::Custom(format!("Rebalance {}: {}", rebalance.id, e)))?;
+        if holds(&coins, target_coin_id) {
+            return Err(MergeError::Conflict { rebalance_id: rebalance.id });
+        }
+        if !replace_coin_id(&mut coins, source_coin_id, target_coin_id) {
+            continue;
+        }
+        let mut active: rebalances::ActiveModel = rebalance.into();
+        active.coins = Set(coins);
+        active.update(txn).await?;
+        rewritten += 1;
+    }
+    Ok(rewritten)
+}
+
+/// Whether rebalance coins in the current schema version list `coin_id`
+pub fn holds(coins: &Value, coin_id: &str) -> bool {
+    coins
+        .as_array()
+        .is_some_and(|entries| entries.iter().any(|entry| entry["coin_id"] == coin_id))
+}
+
+/// Rename a coin in rebalance coins in the current schema version; returns
+/// whether any changed
+pub fn replace_coin_id(coins: &mut Value, source_coin_id: &str, target_coin_id: &str) -> bool {
+    let mut changed = false;
+    let entries = coins.as_array_mut().into_iter().flatten();
+    for entry in entries.filter(|entry| entry["coin_id"] == source_coin_id) {
+        entry["coin_id"] = target_coin_id.into();
+        changed = true;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn coin(coin_id: &str) -> Value {
+        json!({
+            "coin_id": coin_id, "symbol": "ABC", "quantity": "1", "weight": "0.5", "price": 1.0,
+            "exchange": "binance", "trading_pair": "usdc", "schema_version": 2,
+        })
+    }
+
+    #[test]
+    fn test_replace_coin_id() {
+        let mut coins = json!([coin("old-id"), coin("bitcoin")]);
+        assert!(replace_coin_id(&mut coins, "old-id", "new-id"));
+        assert_eq!(coins, json!([coin("new-id"), coin("bitcoin")]));
+        assert!(!replace_coin_id(&mut coins, "old-id", "new-id"));
+    }
+
+    #[test]
+    fn test_holds() {
+        let coins = json!([coin("old-id"), coin("new-id")]);
+        assert!(holds(&coins, "old-id"));
+        assert!(holds(&coins, "new-id"));
+        assert!(!holds(&coins, "bitcoin"));
+        assert!(!holds(&json!({}), "bitcoin"));
+    }
+}
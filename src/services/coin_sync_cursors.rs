@@ -0,0 +1,229 @@
+//! Per-coin progress of the coins historical prices sync
+//!
+//! Each coin the sync fetches gets a cursor in coin_sync_cursors: the
+//! latest date stored by its last successful fetch and when it was last
+//! attempted. A run visits coins in priority order — current index
+//! constituents first, then the top coins by market cap, then new tokens
+//! without one — and within a tier the least recently attempted first, so
+//! a run that stops early picks up next time where it left off instead of
+//! retrying the same coins at the head of the list.
+//!
+//! A run stops once it has spent its credit budget
+//! (COINS_HISTORICAL_PRICES_RUN_BUDGET CoinGecko calls, default
+//! `DEFAULT_RUN_BUDGET`) or after repeated 429s; either way it's recorded
+//! as a success and the coins it didn't reach come first on the next run.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QuerySelect, Set,
+};
+
+use crate::entities::{coin_sync_cursors, index_constituents, prelude::*};
+
+/// CoinGecko calls one run may make when the env var is unset
+pub const DEFAULT_RUN_BUDGET: u64 = 1500;
+
+/// Per-run budget from COINS_HISTORICAL_PRICES_RUN_BUDGET
+pub fn run_budget_from_env() -> u64 {
+    run_budget_from_value(std::env::var("COINS_HISTORICAL_PRICES_RUN_BUDGET").ok().as_deref())
+}
+
+pub fn run_budget_from_value(value: Option<&str>) -> u64 {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return DEFAULT_RUN_BUDGET;
+    };
+    match value.parse::<u64>() {
+        Ok(budget) if budget > 0 => budget,
+        _ => {
+            tracing::warn!(
+                "Invalid COINS_HISTORICAL_PRICES_RUN_BUDGET '{}', using {}",
+                value,
+                DEFAULT_RUN_BUDGET
+            );
+            DEFAULT_RUN_BUDGET
+        }
+    }
+}
+
+/// Credits left in a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunBudget {
+    pub limit: u64,
+    pub spent: u64,
+}
+
+impl RunBudget {
+    pub fn new(limit: u64) -> Self {
+        Self { limit, spent: 0 }
+    }
+
+    /// Take `credits` if the run can still afford them
+    pub fn try_spend(&mut self, credits: u64) -> bool {
+        if self.spent + credits > self.limit {
+            return false;
+        }
+        self.spent += credits;
+        true
+    }
+}
+
+/// Sync priority of a coin, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tier {
+    /// Held by an index right now
+    Constituent,
+    /// Among the top coins by market cap
+    TopMarketCap,
+    /// No market cap yet (never fetched, or just listed)
+    NewToken,
+}
+
+/// Order `coins` for a run: by tier, then never-attempted coins, then the
+/// least recently attempted. Ties keep their incoming order (e.g. market
+/// cap), so the sort is stable.
+pub fn prioritize<T>(
+    coins: &mut [T],
+    key: impl Fn(&T) -> (Tier, &str),
+    cursors: &HashMap<String, coin_sync_cursors::Model>,
+) {
+    coins.sort_by_cached_key(|coin| {
+        let (tier, coin_id) = key(coin);
+        let last_attempt = cursors.get(coin_id).map(|c| c.last_attempt_at);
+        (tier, last_attempt)
+    });
+}
+
+/// Every cursor, by coin id
+pub async fn load(db: &DatabaseConnection) -> Result<HashMap<String, coin_sync_cursors::Model>, DbErr> {
+    Ok(CoinSyncCursors::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|cursor| (cursor.coin_id.clone(), cursor))
+        .collect())
+}
+
+/// Coins currently held by any index
+pub async fn constituent_coin_ids(db: &DatabaseConnection) -> Result<HashSet<String>, DbErr> {
+    let coin_ids: Vec<String> = IndexConstituents::find()
+        .select_only()
+        .column(index_constituents::Column::CoinId)
+        .filter(index_constituents::Column::RemovedAt.is_null())
+        .distinct()
+        .into_tuple()
+        .all(db)
+        .await?;
+    Ok(coin_ids.into_iter().collect())
+}
+
+/// Record a successful fetch of `coin_id`; `synced_through` is the latest
+/// date it returned, None keeping the previous one (e.g. no new prices)
+pub async fn record_success(
+    db: &DatabaseConnection,
+    coin_id: &str,
+    synced_through: Option<NaiveDate>,
+    now: NaiveDateTime,
+) -> Result<(), DbErr> {
+    let mut update = vec![
+        coin_sync_cursors::Column::LastAttemptAt,
+        coin_sync_cursors::Column::LastSuccessAt,
+        coin_sync_cursors::Column::ConsecutiveFailures,
+    ];
+    if synced_through.is_some() {
+        update.push(coin_sync_cursors::Column::SyncedThrough);
+    }
+
+    CoinSyncCursors::insert(coin_sync_cursors::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        synced_through: Set(synced_through),
+        last_attempt_at: Set(now),
+        last_success_at: Set(Some(now)),
+        consecutive_failures: Set(0),
+    })
+    .on_conflict(
+        OnConflict::column(coin_sync_cursors::Column::CoinId)
+            .update_columns(update)
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed fetch of `coin_id`, leaving its synced date alone
+pub async fn record_failure(db: &DatabaseConnection, coin_id: &str, now: NaiveDateTime) -> Result<(), DbErr> {
+    CoinSyncCursors::insert(coin_sync_cursors::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        synced_through: Set(None),
+        last_attempt_at: Set(now),
+        last_success_at: Set(None),
+        consecutive_failures: Set(1),
+    })
+    .on_conflict(
+        OnConflict::column(coin_sync_cursors::Column::CoinId)
+            .update_column(coin_sync_cursors::Column::LastAttemptAt)
+            .value(
+                coin_sync_cursors::Column::ConsecutiveFailures,
+                Expr::col((CoinSyncCursors, coin_sync_cursors::Column::ConsecutiveFailures)).add(1),
+            )
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_budget_from_value() {
+        assert_eq!(run_budget_from_value(None), DEFAULT_RUN_BUDGET);
+        assert_eq!(run_budget_from_value(Some(" ")), DEFAULT_RUN_BUDGET);
+        assert_eq!(run_budget_from_value(Some("400")), 400);
+        assert_eq!(run_budget_from_value(Some("0")), DEFAULT_RUN_BUDGET);
+        assert_eq!(run_budget_from_value(Some("lots")), DEFAULT_RUN_BUDGET);
+    }
+
+    #[test]
+    fn test_run_budget_try_spend() {
+        let mut budget = RunBudget::new(2);
+        assert!(budget.try_spend(1));
+        assert!(budget.try_spend(1));
+        assert!(!budget.try_spend(1));
+        assert_eq!(budget.spent, 2);
+    }
+
+    #[test]
+    fn test_prioritize() {
+        let at = |hour| NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        let cursor = |coin_id: &str, hour| coin_sync_cursors::Model {
+            coin_id: coin_id.to_string(),
+            synced_through: None,
+            last_attempt_at: at(hour),
+            last_success_at: None,
+            consecutive_failures: 0,
+        };
+        let cursors: HashMap<_, _> = [cursor("btc", 3), cursor("eth", 1), cursor("new", 0), cursor("link", 2)]
+            .into_iter()
+            .map(|c| (c.coin_id.clone(), c))
+            .collect();
+
+        // Incoming in market cap order
+        let mut coins = vec![
+            ("btc", Tier::TopMarketCap),
+            ("eth", Tier::TopMarketCap),
+            ("sol", Tier::TopMarketCap),
+            ("new", Tier::NewToken),
+            ("link", Tier::Constituent),
+            ("doge", Tier::TopMarketCap),
+        ];
+        prioritize(&mut coins, |(id, tier)| (*tier, *id), &cursors);
+        let order: Vec<_> = coins.iter().map(|(id, _)| *id).collect();
+        assert_eq!(order, ["link", "sol", "doge", "eth", "btc", "new"]);
+    }
+}
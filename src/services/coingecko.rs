@@ -1,18 +1,268 @@
-use chrono::DateTime;
+//! CoinGecko API client
+//!
+//! Everything that talks to CoinGecko goes through the `CoinGeckoApi` trait.
+//! `CoinGeckoService`, the handle held in `AppState` and passed to jobs, wraps
+//! a shared implementation: `CoinGeckoHttp` in production, or an in-memory
+//! fake (`services::coingecko_fake`) in tests. Requests sent by
+//! `CoinGeckoHttp` are counted in the service's `CoinGeckoUsage` (see
+//! services::coingecko_usage).
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use moka::future::Cache;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 use crate::models::asset::CoinGeckoMarketData;
+use crate::services::coingecko_usage::{self, calls, CoinGeckoUsage};
+use crate::services::pricing_time;
 
 
+/// Operations the backend uses from the CoinGecko API
+#[async_trait]
+pub trait CoinGeckoApi: Send + Sync {
+    /// Price points `(timestamp_ms, price)` for the last `days` days
+    async fn get_token_market_chart(
+        &self,
+        coin_id: &str,
+        currency: &str,
+        days: u32,
+    ) -> Result<Vec<(i64, f64)>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Raw market chart JSON (prices, market_caps, total_volumes) between two
+    /// unix timestamps
+    async fn fetch_market_chart(
+        &self,
+        coin_id: &str,
+        from_timestamp: i64,
+        to_timestamp: i64,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Daily USD prices, market caps and volumes for the last `days` days
+    /// (a number, or "max")
+    async fn fetch_daily_market_chart(
+        &self,
+        coin_id: &str,
+        days: &str,
+    ) -> Result<DailyMarketChart, CoinGeckoError>;
+
+    /// USD price of a coin on `date` (see services::pricing_time), or None
+    /// when CoinGecko has no data point for that day
+    async fn fetch_price_on_date(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+    ) -> Result<Option<f64>, CoinGeckoError>;
+
+    async fn fetch_categories(&self) -> Result<Vec<CategoryInfo>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Coins in a category (basic info only)
+    async fn fetch_coins_by_category(
+        &self,
+        category_id: &str,
+    ) -> Result<Vec<CoinInCategory>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Top `per_page` coins of a category by market cap, with market data
+    async fn fetch_category_market_data(
+        &self,
+        category_id: &str,
+        per_page: u32,
+    ) -> Result<Vec<CoinGeckoMarketData>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// All coins with the given status ("active" or "inactive")
+    async fn fetch_all_coins_list(
+        &self,
+        status: &str,
+    ) -> Result<Vec<CoinListItem>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Recently listed coins
+    async fn fetch_new_coins_list(
+        &self,
+    ) -> Result<Vec<NewCoinListItem>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Market data for the given coins
+    async fn fetch_markets(
+        &self,
+        coin_ids: &[String],
+    ) -> Result<Vec<CoinGeckoMarketData>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Id of the coin whose token contract is `address` on `platform` (a
+    /// CoinGecko asset platform id such as "ethereum")
+    async fn fetch_coin_id_by_contract(
+        &self,
+        platform: &str,
+        address: &str,
+    ) -> Result<String, CoinGeckoError>;
+}
+
+/// Shared handle to a CoinGecko implementation
+///
+/// Derefs to `dyn CoinGeckoApi`, so callers use the API methods directly.
 #[derive(Clone)]
 pub struct CoinGeckoService {
+    api: Arc<dyn CoinGeckoApi>,
+    usage: Arc<CoinGeckoUsage>,
+    /// Recent `get_price_on_date` results; concurrent lookups of the same
+    /// (coin, date) share one request
+    prices_on_date: Cache<(String, NaiveDate), Option<f64>>,
+}
+
+impl CoinGeckoService {
+    /// Client for the real CoinGecko API, with the daily credit budget from
+    /// COINGECKO_DAILY_CREDIT_BUDGET
+    pub fn new(api_key: String, base_url: String) -> Self {
+        Self::with_budget(api_key, base_url, coingecko_usage::daily_budget_from_env())
+    }
+
+    /// Client for the real CoinGecko API with the given daily credit budget
+    pub fn with_budget(api_key: String, base_url: String, daily_budget: Option<u64>) -> Self {
+        let usage = Arc::new(CoinGeckoUsage::new(daily_budget));
+        Self {
+            api: Arc::new(CoinGeckoHttp::new(api_key, base_url).with_usage(usage.clone())),
+            usage,
+            prices_on_date: prices_on_date_cache(),
+        }
+    }
+
+    /// Wrap any implementation, e.g. a fake for tests; nothing is counted
+    pub fn from_api(api: Arc<dyn CoinGeckoApi>) -> Self {
+        Self {
+            api,
+            usage: Arc::new(CoinGeckoUsage::new(None)),
+            prices_on_date: prices_on_date_cache(),
+        }
+    }
+
+    /// Credits used through this client
+    pub fn usage(&self) -> &CoinGeckoUsage {
+        &self.usage
+    }
+
+    /// Whether on-the-fly fetches should stop for the day
+    pub fn budget_exhausted(&self) -> bool {
+        self.usage.budget_exhausted()
+    }
+
+    /// Price of a coin on a single day, for on-the-fly lookups
+    ///
+    /// Rate limits, server errors and failed requests are retried with
+    /// backoff, unless the credit budget runs out in between. Concurrent
+    /// calls for the same coin and day are coalesced into one fetch, whose
+    /// result is reused for `PRICE_COALESCE_TTL`; errors aren't kept.
+    pub async fn get_price_on_date(&self, coin_id: &str, date: NaiveDate) -> Result<Option<f64>, CoinGeckoError> {
+        self.prices_on_date
+            .try_get_with((coin_id.to_string(), date), self.fetch_price_with_retry(coin_id, date))
+            .await
+            .map_err(|e| (*e).clone())
+    }
+
+    async fn fetch_price_with_retry(&self, coin_id: &str, date: NaiveDate) -> Result<Option<f64>, CoinGeckoError> {
+        let mut delay = PRICE_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.api.fetch_price_on_date(coin_id, date).await {
+                Err(e) if e.is_transient() && attempt < PRICE_ATTEMPTS && !self.budget_exhausted() => {
+                    tracing::warn!(
+                        "CoinGecko price of {} on {} failed (attempt {}/{}): {}",
+                        coin_id,
+                        date,
+                        attempt,
+                        PRICE_ATTEMPTS,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Attempts of `get_price_on_date`, and the delay before the first retry
+const PRICE_ATTEMPTS: u32 = 3;
+const PRICE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How long a `get_price_on_date` result is reused
+const PRICE_COALESCE_TTL: Duration = Duration::from_secs(60);
+
+fn prices_on_date_cache() -> Cache<(String, NaiveDate), Option<f64>> {
+    Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(PRICE_COALESCE_TTL)
+        .build()
+}
+
+impl Deref for CoinGeckoService {
+    type Target = dyn CoinGeckoApi;
+
+    fn deref(&self) -> &Self::Target {
+        self.api.as_ref()
+    }
+}
+
+/// Errors from `fetch_daily_market_chart`
+///
+/// Kept apart from the boxed errors of the other calls so that sync jobs can
+/// tell a delisted coin from a transient failure.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoinGeckoError {
+    /// 404: the coin doesn't exist or was delisted
+    NotFound,
+    /// Any other non-success status
+    Api { status: u16, body: String },
+    Request(String),
+    /// Response body didn't match the expected shape
+    Decode(String),
+}
+
+impl std::fmt::Display for CoinGeckoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoinGeckoError::NotFound => write!(f, "Coin not found on CoinGecko"),
+            CoinGeckoError::Api { status, body } => write!(f, "CoinGecko API error {}: {}", status, body),
+            CoinGeckoError::Request(e) => write!(f, "Request failed: {}", e),
+            CoinGeckoError::Decode(e) => write!(f, "Invalid CoinGecko response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CoinGeckoError {}
+
+impl CoinGeckoError {
+    /// Whether the same request may succeed later: rate limits, server
+    /// errors and failed requests
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CoinGeckoError::Api { status, .. } => *status == 429 || *status >= 500,
+            CoinGeckoError::Request(_) => true,
+            CoinGeckoError::NotFound | CoinGeckoError::Decode(_) => false,
+        }
+    }
+}
+
+/// `/coins/{id}/market_chart?interval=daily` response
+///
+/// Each entry is `[timestamp_ms, value]`; the three arrays are index-aligned.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyMarketChart {
+    pub prices: Vec<[f64; 2]>,
+    #[serde(default)]
+    pub market_caps: Vec<[f64; 2]>,
+    #[serde(default)]
+    pub total_volumes: Vec<[f64; 2]>,
+}
+
+/// HTTP client for the CoinGecko Pro API
+pub struct CoinGeckoHttp {
     client: Client,
     api_key: String,
     base_url: String,
     cache: Arc<Cache<String, Vec<(i64, f64)>>>,
+    usage: Arc<CoinGeckoUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +270,29 @@ struct MarketChartResponse {
     prices: Vec<(i64, f64)>,
 }
 
+/// Half-width of the range requested around a day's cutoff; wide enough to
+/// hold a daily point, which is all CoinGecko returns for older ranges
+const PRICE_WINDOW_SECS: i64 = 12 * 3600;
+
+/// Price of `date` among market chart points: the latest one for today,
+/// whose close isn't struck yet, otherwise the one closest to the cutoff
+fn price_on_date(prices: &[(i64, f64)], date: NaiveDate, today: NaiveDate) -> Option<f64> {
+    if date >= today {
+        return prices.last().map(|(_, price)| *price);
+    }
+    let cutoff = pricing_time::cutoff_millis(date);
+    prices
+        .iter()
+        .min_by_key(|(timestamp_ms, _)| (timestamp_ms - cutoff).abs())
+        .map(|(_, price)| *price)
+}
+
+/// The part of `/coins/{platform}/contract/{address}` we use
+#[derive(Debug, Deserialize)]
+struct ContractCoinResponse {
+    id: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryInfo {
     pub category_id: String,
@@ -33,13 +306,6 @@ pub struct CoinInCategory {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CoinListEntry {
-    pub id: String,
-    pub symbol: String,
-    pub name: String,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoinListItem {
     pub id: String,
@@ -57,7 +323,7 @@ pub struct NewCoinListItem {
     pub activated_at: i64, // Unix timestamp
 }
 
-impl CoinGeckoService {
+impl CoinGeckoHttp {
     pub fn new(api_key: String, base_url: String) -> Self {
         let cache = Cache::builder()
             .max_capacity(100) // Store up to 100 different coins
@@ -69,10 +335,20 @@ impl CoinGeckoService {
             api_key,
             base_url,
             cache: Arc::new(cache),
+            usage: Arc::new(CoinGeckoUsage::new(None)),
         }
     }
 
-    pub async fn get_token_market_chart(
+    /// Count requests in `usage`
+    pub fn with_usage(mut self, usage: Arc<CoinGeckoUsage>) -> Self {
+        self.usage = usage;
+        self
+    }
+}
+
+#[async_trait]
+impl CoinGeckoApi for CoinGeckoHttp {
+    async fn get_token_market_chart(
         &self,
         coin_id: &str,
         currency: &str,
@@ -91,6 +367,7 @@ impl CoinGeckoService {
         // Fetch from API
         let url = format!("{}/coins/{}/market_chart", self.base_url, coin_id);
         
+        self.usage.record(calls::MARKET_CHART);
         let response = self
             .client
             .get(&url)
@@ -131,7 +408,7 @@ impl CoinGeckoService {
 
     /// Fetch full market chart data (prices, market_caps, volumes) for a date range
     /// Used by the market-cap-history endpoint
-    pub async fn fetch_market_chart(
+    async fn fetch_market_chart(
         &self,
         coin_id: &str,
         from_timestamp: i64,
@@ -146,6 +423,7 @@ impl CoinGeckoService {
 
         let url = format!("{}/coins/{}/market_chart/range", self.base_url, coin_id);
 
+        self.usage.record(calls::MARKET_CHART_RANGE);
         let response = self
             .client
             .get(&url)
@@ -178,11 +456,102 @@ impl CoinGeckoService {
         Ok(data)
     }
 
-    pub async fn fetch_categories(&self) -> Result<Vec<CategoryInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn fetch_daily_market_chart(
+        &self,
+        coin_id: &str,
+        days: &str,
+    ) -> Result<DailyMarketChart, CoinGeckoError> {
+        let url = format!("{}/coins/{}/market_chart", self.base_url, coin_id);
+
+        self.usage.record(calls::MARKET_CHART_DAILY);
+        let response = self
+            .client
+            .get(&url)
+            .header("x-cg-pro-api-key", &self.api_key)
+            .query(&[
+                ("vs_currency", "usd"),
+                ("days", days),
+                ("interval", "daily"),
+            ])
+            .send()
+            .await
+            .map_err(|e| CoinGeckoError::Request(e.to_string()))?;
+
+        let status = response.status();
+        if status.as_u16() == 404 {
+            return Err(CoinGeckoError::NotFound);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CoinGeckoError::Api { status: status.as_u16(), body });
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| CoinGeckoError::Decode(e.to_string()))
+    }
+
+    /// Uses the range endpoint over a day around the cutoff rather than a
+    /// chart reaching back from today, so old dates cost the same
+    async fn fetch_price_on_date(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+    ) -> Result<Option<f64>, CoinGeckoError> {
+        let today = pricing_time::pricing_date(Utc::now());
+        let cache_key = format!("{}_usd_on_{}", coin_id, date);
+        if date < today
+            && let Some(cached) = self.cache.get(&cache_key).await
+        {
+            tracing::debug!("Cache hit for {}", cache_key);
+            return Ok(price_on_date(&cached, date, today));
+        }
+
+        let cutoff = pricing_time::cutoff_timestamp(date);
+        let url = format!("{}/coins/{}/market_chart/range", self.base_url, coin_id);
+
+        self.usage.record(calls::MARKET_CHART_RANGE);
+        let response = self
+            .client
+            .get(&url)
+            .header("accept", "application/json")
+            .header("x-cg-pro-api-key", &self.api_key)
+            .query(&[
+                ("vs_currency", "usd"),
+                ("from", &(cutoff - PRICE_WINDOW_SECS).to_string()),
+                ("to", &(cutoff + PRICE_WINDOW_SECS).to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| CoinGeckoError::Request(e.to_string()))?;
+
+        let status = response.status();
+        if status.as_u16() == 404 {
+            return Err(CoinGeckoError::NotFound);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CoinGeckoError::Api { status: status.as_u16(), body });
+        }
+
+        let data: MarketChartResponse = response
+            .json()
+            .await
+            .map_err(|e| CoinGeckoError::Decode(e.to_string()))?;
+        let price = price_on_date(&data.prices, date, today);
+        if date < today {
+            self.cache.insert(cache_key, data.prices).await;
+        }
+        Ok(price)
+    }
+
+    async fn fetch_categories(&self) -> Result<Vec<CategoryInfo>, Box<dyn std::error::Error + Send + Sync>> {
         tracing::info!("Fetching categories from CoinGecko");
 
         let url = format!("{}/coins/categories/list", self.base_url);
 
+        self.usage.record(calls::CATEGORIES_LIST);
         let response = self
             .client
             .get(&url)
@@ -205,7 +574,7 @@ impl CoinGeckoService {
     }
 
     /// Fetch all coins in a specific category (basic info only)
-    pub async fn fetch_coins_by_category(
+    async fn fetch_coins_by_category(
         &self,
         category_id: &str,
     ) -> Result<Vec<CoinInCategory>, Box<dyn std::error::Error + Send + Sync>> {
@@ -213,6 +582,7 @@ impl CoinGeckoService {
 
         let url = format!("{}/coins/markets", self.base_url);
 
+        self.usage.record(calls::CATEGORY_COINS);
         let response = self
             .client
             .get(&url)
@@ -241,7 +611,7 @@ impl CoinGeckoService {
     }
 
     /// Fetch coins in a category with full market data (market cap, price, volume)
-    pub async fn fetch_category_market_data(
+    async fn fetch_category_market_data(
         &self,
         category_id: &str,
         per_page: u32,
@@ -250,6 +620,7 @@ impl CoinGeckoService {
 
         let url = format!("{}/coins/markets", self.base_url);
 
+        self.usage.record(calls::CATEGORY_MARKETS);
         let response = self
             .client
             .get(&url)
@@ -280,7 +651,7 @@ impl CoinGeckoService {
     }
 
     /// Fetch ALL coins from CoinGecko (initial sync)
-    pub async fn fetch_all_coins_list(
+    async fn fetch_all_coins_list(
         &self,
         status: &str, // "active" or "inactive"
     ) -> Result<Vec<CoinListItem>, Box<dyn std::error::Error + Send + Sync>> {
@@ -288,6 +659,7 @@ impl CoinGeckoService {
 
         let url = format!("{}/coins/list", self.base_url);
 
+        self.usage.record(calls::COINS_LIST);
         let response = self
             .client
             .get(&url)
@@ -311,13 +683,14 @@ impl CoinGeckoService {
     }
 
     /// Fetch only NEW coins from CoinGecko (incremental sync)
-    pub async fn fetch_new_coins_list(
+    async fn fetch_new_coins_list(
         &self,
     ) -> Result<Vec<NewCoinListItem>, Box<dyn std::error::Error + Send + Sync>> {
         tracing::info!("Fetching NEW coins from CoinGecko /coins/list/new");
 
         let url = format!("{}/coins/list/new", self.base_url);
 
+        self.usage.record(calls::COINS_LIST_NEW);
         let response = self
             .client
             .get(&url)
@@ -341,7 +714,7 @@ impl CoinGeckoService {
 
     /// Fetch market data for multiple coins from CoinGecko
     /// Matches: GET /api/v3/coins/markets
-    pub async fn fetch_markets(
+    async fn fetch_markets(
         &self,
         coin_ids: &[String],
     ) -> Result<Vec<CoinGeckoMarketData>, Box<dyn std::error::Error + Send + Sync>> {
@@ -358,6 +731,7 @@ impl CoinGeckoService {
 
         let url = format!("{}/coins/markets", self.base_url);
         
+        self.usage.record(calls::COINS_MARKETS);
         let response = self
             .client
             .get(&url)
@@ -397,16 +771,37 @@ impl CoinGeckoService {
 
         Ok(data)
     }
+    /// Matches: GET /api/v3/coins/{platform}/contract/{address}
+    async fn fetch_coin_id_by_contract(
+        &self,
+        platform: &str,
+        address: &str,
+    ) -> Result<String, CoinGeckoError> {
+        let url = format!("{}/coins/{}/contract/{}", self.base_url, platform, address);
 
-    pub fn client(&self) -> &reqwest::Client {
-        &self.client
-    }
+        self.usage.record(calls::COIN_BY_CONTRACT);
+        let response = self
+            .client
+            .get(&url)
+            .header("accept", "application/json")
+            .header("x-cg-pro-api-key", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| CoinGeckoError::Request(e.to_string()))?;
 
-    pub fn api_key(&self) -> &str {
-        &self.api_key
-    }
+        let status = response.status();
+        if status.as_u16() == 404 {
+            return Err(CoinGeckoError::NotFound);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CoinGeckoError::Api { status: status.as_u16(), body });
+        }
 
-    pub fn base_url(&self) -> &str {
-        &self.base_url
+        let coin: ContractCoinResponse = response
+            .json()
+            .await
+            .map_err(|e| CoinGeckoError::Decode(e.to_string()))?;
+        Ok(coin.id)
     }
 }
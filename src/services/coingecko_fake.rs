@@ -0,0 +1,390 @@
+//! In-memory CoinGecko for tests
+//!
+//! `FakeCoinGecko` implements `CoinGeckoApi` over data set up with its
+//! builder methods, so code that fetches from CoinGecko (price backfills,
+//! rebalancing, category sync) can run deterministically and offline:
+//!
+//! ```ignore
+//! let fake = Arc::new(FakeCoinGecko::new().with_prices("bitcoin", &[(date, 50000.0)]));
+//! let coingecko = CoinGeckoService::from_api(fake.clone());
+//! ```
+//!
+//! Daily points are stamped at midnight UTC. Every call is recorded (see
+//! `requests`) so tests can assert what was, or wasn't, fetched.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::Mutex;
+
+use crate::models::asset::CoinGeckoMarketData;
+use crate::services::pricing_time;
+use crate::services::coingecko::{
+    CategoryInfo, CoinGeckoApi, CoinGeckoError, CoinInCategory, CoinListItem, DailyMarketChart,
+    NewCoinListItem,
+};
+
+#[derive(Debug, Clone, Default)]
+struct FakeCoin {
+    symbol: String,
+    name: String,
+    prices: BTreeMap<NaiveDate, f64>,
+    market_caps: BTreeMap<NaiveDate, f64>,
+    market: Option<CoinGeckoMarketData>,
+}
+
+#[derive(Default)]
+pub struct FakeCoinGecko {
+    coins: BTreeMap<String, FakeCoin>,
+    /// category_id -> (name, coin ids)
+    categories: BTreeMap<String, (String, Vec<String>)>,
+    /// (platform, lowercase address) -> coin id
+    contracts: BTreeMap<(String, String), String>,
+    today: Option<NaiveDate>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl FakeCoinGecko {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pin "today" for `days`-relative lookups (defaults to the real date)
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    /// Register a coin, so it shows up in the coins list
+    pub fn with_coin(mut self, coin_id: &str, symbol: &str, name: &str) -> Self {
+        let coin = self.coins.entry(coin_id.to_string()).or_default();
+        coin.symbol = symbol.to_string();
+        coin.name = name.to_string();
+        self
+    }
+
+    /// Daily USD prices of a coin
+    pub fn with_prices(mut self, coin_id: &str, prices: &[(NaiveDate, f64)]) -> Self {
+        self.coin_mut(coin_id).prices.extend(prices.iter().copied());
+        self
+    }
+
+    /// Daily market caps of a coin
+    ///
+    /// Charts include market caps only when every priced day has one, since
+    /// CoinGecko's arrays are index-aligned.
+    pub fn with_market_caps(mut self, coin_id: &str, market_caps: &[(NaiveDate, f64)]) -> Self {
+        self.coin_mut(coin_id).market_caps.extend(market_caps.iter().copied());
+        self
+    }
+
+    /// Current market data of a coin, as returned by `/coins/markets`
+    pub fn with_market(mut self, coin_id: &str, price: f64, market_cap: f64) -> Self {
+        let coin = self.coin_mut(coin_id);
+        coin.market = Some(CoinGeckoMarketData {
+            id: coin_id.to_string(),
+            symbol: coin.symbol.clone(),
+            name: coin.name.clone(),
+            image: String::new(),
+            current_price: Some(price),
+            market_cap: Some(market_cap),
+            market_cap_rank: None,
+            fully_diluted_valuation: None,
+            total_volume: None,
+            circulating_supply: None,
+            total_supply: None,
+            max_supply: None,
+        });
+        self
+    }
+
+    pub fn with_category(mut self, category_id: &str, name: &str, coin_ids: &[&str]) -> Self {
+        for coin_id in coin_ids {
+            self.coin_mut(coin_id);
+        }
+        self.categories.insert(
+            category_id.to_string(),
+            (name.to_string(), coin_ids.iter().map(|id| id.to_string()).collect()),
+        );
+        self
+    }
+
+    /// Token contract of a coin on an asset platform
+    pub fn with_contract(mut self, coin_id: &str, platform: &str, address: &str) -> Self {
+        self.coin_mut(coin_id);
+        self.contracts.insert((platform.to_string(), address.to_lowercase()), coin_id.to_string());
+        self
+    }
+
+    /// Calls made so far, as `"<method> <argument>"`
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().clone()
+    }
+
+    fn coin_mut(&mut self, coin_id: &str) -> &mut FakeCoin {
+        self.coins.entry(coin_id.to_string()).or_insert_with(|| FakeCoin {
+            symbol: coin_id.to_string(),
+            name: coin_id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn record(&self, request: String) {
+        self.requests.lock().push(request);
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.today.unwrap_or_else(|| Utc::now().date_naive())
+    }
+
+    /// Daily chart of a coin restricted to `from..=to`
+    fn chart(&self, coin_id: &str, from: NaiveDate, to: NaiveDate) -> Result<DailyMarketChart, CoinGeckoError> {
+        let coin = self.coins.get(coin_id).ok_or(CoinGeckoError::NotFound)?;
+        let days: Vec<_> = coin.prices.range(from..=to).collect();
+
+        let prices = days.iter().map(|(date, price)| [timestamp_ms(**date), **price]).collect();
+        let market_caps = days
+            .iter()
+            .map(|(date, _)| coin.market_caps.get(*date).map(|mcap| [timestamp_ms(**date), *mcap]))
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default();
+
+        Ok(DailyMarketChart { prices, market_caps, total_volumes: Vec::new() })
+    }
+
+    fn market_data(&self, coin_ids: impl Iterator<Item = String>) -> Vec<CoinGeckoMarketData> {
+        let mut markets: Vec<_> = coin_ids
+            .filter_map(|id| self.coins.get(&id).and_then(|coin| coin.market.clone()))
+            .collect();
+        markets.sort_by(|a, b| b.market_cap.unwrap_or(0.0).total_cmp(&a.market_cap.unwrap_or(0.0)));
+        markets
+    }
+}
+
+fn timestamp_ms(date: NaiveDate) -> f64 {
+    pricing_time::cutoff_millis(date) as f64
+}
+
+fn chart_json(chart: &DailyMarketChart) -> serde_json::Value {
+    serde_json::to_value(chart).unwrap_or_default()
+}
+
+#[async_trait]
+impl CoinGeckoApi for FakeCoinGecko {
+    async fn get_token_market_chart(
+        &self,
+        coin_id: &str,
+        _currency: &str,
+        days: u32,
+    ) -> Result<Vec<(i64, f64)>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("get_token_market_chart {}", coin_id));
+        let today = self.today();
+        let chart = self.chart(coin_id, today - chrono::Duration::days(days as i64), today)?;
+        Ok(chart.prices.iter().map(|[ts, price]| (*ts as i64, *price)).collect())
+    }
+
+    async fn fetch_market_chart(
+        &self,
+        coin_id: &str,
+        from_timestamp: i64,
+        to_timestamp: i64,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("fetch_market_chart {}", coin_id));
+        let date = |ts: i64| DateTime::from_timestamp(ts, 0).map(|dt| dt.date_naive()).ok_or("Invalid timestamp");
+        let chart = self.chart(coin_id, date(from_timestamp)?, date(to_timestamp)?)?;
+        Ok(chart_json(&chart))
+    }
+
+    async fn fetch_daily_market_chart(
+        &self,
+        coin_id: &str,
+        days: &str,
+    ) -> Result<DailyMarketChart, CoinGeckoError> {
+        self.record(format!("fetch_daily_market_chart {}", coin_id));
+        let today = self.today();
+        let from = match days {
+            "max" => NaiveDate::MIN,
+            days => {
+                let days: i64 = days.parse().map_err(|_| CoinGeckoError::Api {
+                    status: 400,
+                    body: format!("Invalid days '{}'", days),
+                })?;
+                today - chrono::Duration::days(days)
+            }
+        };
+        self.chart(coin_id, from, today)
+    }
+
+    async fn fetch_price_on_date(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+    ) -> Result<Option<f64>, CoinGeckoError> {
+        self.record(format!("fetch_price_on_date {}", coin_id));
+        let coin = self.coins.get(coin_id).ok_or(CoinGeckoError::NotFound)?;
+        Ok(coin.prices.get(&date).copied())
+    }
+
+    async fn fetch_categories(&self) -> Result<Vec<CategoryInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record("fetch_categories".to_string());
+        Ok(self
+            .categories
+            .iter()
+            .map(|(category_id, (name, _))| CategoryInfo { category_id: category_id.clone(), name: name.clone() })
+            .collect())
+    }
+
+    async fn fetch_coins_by_category(
+        &self,
+        category_id: &str,
+    ) -> Result<Vec<CoinInCategory>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("fetch_coins_by_category {}", category_id));
+        let coin_ids = self.categories.get(category_id).map(|(_, ids)| ids.as_slice()).unwrap_or_default();
+        Ok(coin_ids
+            .iter()
+            .filter_map(|id| {
+                self.coins.get(id).map(|coin| CoinInCategory {
+                    id: id.clone(),
+                    symbol: coin.symbol.clone(),
+                    name: coin.name.clone(),
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_category_market_data(
+        &self,
+        category_id: &str,
+        per_page: u32,
+    ) -> Result<Vec<CoinGeckoMarketData>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("fetch_category_market_data {}", category_id));
+        let coin_ids = self.categories.get(category_id).map(|(_, ids)| ids.clone()).unwrap_or_default();
+        let mut markets = self.market_data(coin_ids.into_iter());
+        markets.truncate(per_page as usize);
+        Ok(markets)
+    }
+
+    async fn fetch_all_coins_list(
+        &self,
+        status: &str,
+    ) -> Result<Vec<CoinListItem>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("fetch_all_coins_list {}", status));
+        if status != "active" {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .coins
+            .iter()
+            .map(|(id, coin)| CoinListItem {
+                id: id.clone(),
+                symbol: coin.symbol.clone(),
+                name: coin.name.clone(),
+                platforms: serde_json::json!({}),
+            })
+            .collect())
+    }
+
+    async fn fetch_new_coins_list(
+        &self,
+    ) -> Result<Vec<NewCoinListItem>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record("fetch_new_coins_list".to_string());
+        Ok(Vec::new())
+    }
+
+    async fn fetch_markets(
+        &self,
+        coin_ids: &[String],
+    ) -> Result<Vec<CoinGeckoMarketData>, Box<dyn std::error::Error + Send + Sync>> {
+        self.record(format!("fetch_markets {}", coin_ids.join(",")));
+        Ok(self.market_data(coin_ids.iter().cloned()))
+    }
+
+    async fn fetch_coin_id_by_contract(
+        &self,
+        platform: &str,
+        address: &str,
+    ) -> Result<String, CoinGeckoError> {
+        self.record(format!("fetch_coin_id_by_contract {}/{}", platform, address));
+        self.contracts
+            .get(&(platform.to_string(), address.to_lowercase()))
+            .cloned()
+            .ok_or(CoinGeckoError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+    }
+
+    fn fake() -> FakeCoinGecko {
+        FakeCoinGecko::new()
+            .with_today(date(10))
+            .with_coin("bitcoin", "btc", "Bitcoin")
+            .with_prices("bitcoin", &[(date(1), 90000.0), (date(9), 95000.0), (date(10), 96000.0)])
+            .with_market_caps("bitcoin", &[(date(1), 1.8e12), (date(9), 1.9e12), (date(10), 1.9e12)])
+            .with_prices("ethereum", &[(date(10), 3300.0)])
+            .with_market("bitcoin", 96000.0, 1.9e12)
+            .with_market("ethereum", 3300.0, 4.0e11)
+            .with_category("layer-1", "Layer 1", &["ethereum", "bitcoin"])
+    }
+
+    #[tokio::test]
+    async fn test_daily_chart_respects_days() {
+        let fake = fake();
+
+        let chart = fake.fetch_daily_market_chart("bitcoin", "1").await.unwrap();
+        assert_eq!(chart.prices, vec![[timestamp_ms(date(9)), 95000.0], [timestamp_ms(date(10)), 96000.0]]);
+        assert_eq!(chart.market_caps.len(), 2);
+
+        let all = fake.fetch_daily_market_chart("bitcoin", "max").await.unwrap();
+        assert_eq!(all.prices.len(), 3);
+
+        // No market caps set for ethereum, so none are returned
+        let eth = fake.fetch_daily_market_chart("ethereum", "30").await.unwrap();
+        assert_eq!(eth.prices.len(), 1);
+        assert!(eth.market_caps.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_coin_is_not_found() {
+        let fake = fake();
+        assert_eq!(fake.fetch_daily_market_chart("dogecoin", "30").await, Err(CoinGeckoError::NotFound));
+        assert!(fake.get_token_market_chart("dogecoin", "usd", 30).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contract_lookup_ignores_address_case() {
+        let fake = fake().with_contract("chainlink", "ethereum", "0x514910771AF9Ca656af840dff83E8264EcF986CA");
+        let coin_id = fake.fetch_coin_id_by_contract("ethereum", "0x514910771af9ca656af840dff83e8264ecf986ca").await;
+        assert_eq!(coin_id, Ok("chainlink".to_string()));
+        assert_eq!(
+            fake.fetch_coin_id_by_contract("base", "0x514910771af9ca656af840dff83e8264ecf986ca").await,
+            Err(CoinGeckoError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_category_market_data_is_ranked_and_truncated() {
+        let fake = fake();
+        let markets = fake.fetch_category_market_data("layer-1", 1).await.unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].id, "bitcoin");
+        assert_eq!(markets[0].symbol, "btc");
+
+        let coins = fake.fetch_coins_by_category("layer-1").await.unwrap();
+        assert_eq!(coins.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), vec!["ethereum", "bitcoin"]);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_recorded() {
+        let fake = fake();
+        fake.fetch_markets(&["bitcoin".to_string()]).await.unwrap();
+        fake.fetch_categories().await.unwrap();
+        assert_eq!(fake.requests(), vec!["fetch_markets bitcoin", "fetch_categories"]);
+    }
+}
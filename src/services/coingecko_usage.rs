@@ -0,0 +1,251 @@
+//! CoinGecko API credit accounting
+//!
+//! `CoinGeckoHttp` records every request it sends here, by call type; cache
+//! hits cost nothing and aren't counted. Usage is kept per UTC day and since
+//! startup, per instance, and is served by GET /admin/coingecko-usage and
+//! GET /metrics.
+//!
+//! With COINGECKO_DAILY_CREDIT_BUDGET set, on-the-fly fetches made while
+//! serving a request stop once the day's usage reaches the budget: index
+//! prices fall back to the latest stored ones (flagged `stale`) and other
+//! on-the-fly lookups return 503 until the next UTC day. Sync jobs keep
+//! running, so their usage counts against the budget without being capped.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+
+/// Credits charged per request on the CoinGecko paid plans
+pub const CREDITS_PER_CALL: u64 = 1;
+
+/// Call types, one per CoinGecko endpoint used
+pub mod calls {
+    pub const MARKET_CHART: &str = "market_chart";
+    pub const MARKET_CHART_RANGE: &str = "market_chart_range";
+    pub const MARKET_CHART_DAILY: &str = "market_chart_daily";
+    pub const CATEGORIES_LIST: &str = "categories_list";
+    pub const CATEGORY_COINS: &str = "category_coins";
+    pub const CATEGORY_MARKETS: &str = "category_markets";
+    pub const COINS_LIST: &str = "coins_list";
+    pub const COINS_LIST_NEW: &str = "coins_list_new";
+    pub const COINS_MARKETS: &str = "coins_markets";
+    pub const COIN_BY_CONTRACT: &str = "coin_by_contract";
+}
+
+/// Daily budget from COINGECKO_DAILY_CREDIT_BUDGET; None (unlimited) when
+/// unset or invalid
+pub fn daily_budget_from_env() -> Option<u64> {
+    daily_budget_from_value(std::env::var("COINGECKO_DAILY_CREDIT_BUDGET").ok().as_deref())
+}
+
+pub fn daily_budget_from_value(value: Option<&str>) -> Option<u64> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty())?;
+    match value.parse::<u64>() {
+        Ok(budget) if budget > 0 => Some(budget),
+        _ => {
+            tracing::warn!("Invalid COINGECKO_DAILY_CREDIT_BUDGET '{}', no budget enforced", value);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallUsage {
+    pub call_type: String,
+    pub calls_today: u64,
+    pub credits_today: u64,
+    pub calls_total: u64,
+    pub credits_total: u64,
+}
+
+/// Usage at a point in time, as served by GET /admin/coingecko-usage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSnapshot {
+    /// UTC day `credits_today` covers
+    pub day: NaiveDate,
+    pub daily_budget: Option<u64>,
+    pub credits_today: u64,
+    pub remaining_today: Option<u64>,
+    pub budget_exhausted: bool,
+    /// Since this instance started
+    pub credits_total: u64,
+    pub calls: Vec<CallUsage>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    day: Option<NaiveDate>,
+    /// Calls by type: (today, since startup)
+    calls: BTreeMap<&'static str, (u64, u64)>,
+}
+
+impl Counters {
+    /// Start a new day's counts on the first use after midnight UTC
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            for (calls_today, _) in self.calls.values_mut() {
+                *calls_today = 0;
+            }
+        }
+    }
+
+    fn credits_today(&self) -> u64 {
+        self.calls.values().map(|(today, _)| today * CREDITS_PER_CALL).sum()
+    }
+}
+
+/// Credit counters of one CoinGecko client
+#[derive(Debug)]
+pub struct CoinGeckoUsage {
+    daily_budget: Option<u64>,
+    counters: Mutex<Counters>,
+}
+
+impl CoinGeckoUsage {
+    pub fn new(daily_budget: Option<u64>) -> Self {
+        Self {
+            daily_budget,
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    pub fn daily_budget(&self) -> Option<u64> {
+        self.daily_budget
+    }
+
+    /// Count one request of `call_type`
+    pub fn record(&self, call_type: &'static str) {
+        self.record_on(call_type, Utc::now().date_naive());
+    }
+
+    pub fn record_on(&self, call_type: &'static str, today: NaiveDate) {
+        let mut counters = self.counters.lock().unwrap();
+        counters.roll_over(today);
+        let (calls_today, calls_total) = counters.calls.entry(call_type).or_default();
+        *calls_today += 1;
+        *calls_total += 1;
+    }
+
+    /// Whether today's usage reached the daily budget
+    pub fn budget_exhausted(&self) -> bool {
+        self.snapshot().budget_exhausted
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        self.snapshot_on(Utc::now().date_naive())
+    }
+
+    pub fn snapshot_on(&self, today: NaiveDate) -> UsageSnapshot {
+        let mut counters = self.counters.lock().unwrap();
+        counters.roll_over(today);
+        let credits_today = counters.credits_today();
+
+        UsageSnapshot {
+            day: today,
+            daily_budget: self.daily_budget,
+            credits_today,
+            remaining_today: self.daily_budget.map(|budget| budget.saturating_sub(credits_today)),
+            budget_exhausted: self.daily_budget.is_some_and(|budget| credits_today >= budget),
+            credits_total: counters.calls.values().map(|(_, total)| total * CREDITS_PER_CALL).sum(),
+            calls: counters
+                .calls
+                .iter()
+                .map(|(call_type, (today, total))| CallUsage {
+                    call_type: call_type.to_string(),
+                    calls_today: *today,
+                    credits_today: today * CREDITS_PER_CALL,
+                    calls_total: *total,
+                    credits_total: total * CREDITS_PER_CALL,
+                })
+                .collect(),
+        }
+    }
+
+    /// Prometheus text exposition of the counters
+    pub fn render_metrics(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+
+        out.push_str("# HELP coingecko_credits_total CoinGecko API credits used since startup, by call type\n");
+        out.push_str("# TYPE coingecko_credits_total counter\n");
+        for call in &snapshot.calls {
+            out.push_str(&format!(
+                "coingecko_credits_total{{call_type=\"{}\"}} {}\n",
+                call.call_type, call.credits_total
+            ));
+        }
+
+        out.push_str("# HELP coingecko_credits_today CoinGecko API credits used on the current UTC day\n");
+        out.push_str("# TYPE coingecko_credits_today gauge\n");
+        out.push_str(&format!("coingecko_credits_today {}\n", snapshot.credits_today));
+
+        if let Some(budget) = snapshot.daily_budget {
+            out.push_str("# HELP coingecko_daily_credit_budget Daily CoinGecko credit budget for on-the-fly fetches\n");
+            out.push_str("# TYPE coingecko_daily_credit_budget gauge\n");
+            out.push_str(&format!("coingecko_daily_credit_budget {}\n", budget));
+        }
+
+        out.push_str("# HELP coingecko_budget_exhausted Whether on-the-fly CoinGecko fetches are paused for the day\n");
+        out.push_str("# TYPE coingecko_budget_exhausted gauge\n");
+        out.push_str(&format!("coingecko_budget_exhausted {}\n", snapshot.budget_exhausted as u8));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, d).unwrap()
+    }
+
+    #[test]
+    fn test_daily_budget_from_value() {
+        assert_eq!(daily_budget_from_value(Some("5000")), Some(5000));
+        assert_eq!(daily_budget_from_value(Some(" ")), None);
+        assert_eq!(daily_budget_from_value(Some("0")), None);
+        assert_eq!(daily_budget_from_value(Some("lots")), None);
+        assert_eq!(daily_budget_from_value(None), None);
+    }
+
+    #[test]
+    fn test_usage_rolls_over_daily() {
+        let usage = CoinGeckoUsage::new(Some(3));
+        usage.record_on(calls::COINS_MARKETS, day(1));
+        usage.record_on(calls::COINS_MARKETS, day(1));
+        usage.record_on(calls::MARKET_CHART, day(1));
+
+        let snapshot = usage.snapshot_on(day(1));
+        assert_eq!(snapshot.credits_today, 3);
+        assert_eq!(snapshot.remaining_today, Some(0));
+        assert!(snapshot.budget_exhausted);
+        assert_eq!(snapshot.calls[0].call_type, calls::COINS_MARKETS);
+        assert_eq!(snapshot.calls[0].calls_today, 2);
+
+        // A new day starts from zero but keeps the running total
+        usage.record_on(calls::MARKET_CHART, day(2));
+        let snapshot = usage.snapshot_on(day(2));
+        assert_eq!(snapshot.credits_today, 1);
+        assert!(!snapshot.budget_exhausted);
+        assert_eq!(snapshot.credits_total, 4);
+        assert_eq!(snapshot.calls[0].calls_today, 0);
+        assert_eq!(snapshot.calls[1].calls_total, 2);
+    }
+
+    #[test]
+    fn test_unlimited_usage_is_never_exhausted() {
+        let usage = CoinGeckoUsage::new(None);
+        for _ in 0..10 {
+            usage.record_on(calls::COINS_LIST, day(1));
+        }
+        let snapshot = usage.snapshot_on(day(1));
+        assert!(!snapshot.budget_exhausted);
+        assert_eq!(snapshot.remaining_today, None);
+    }
+}
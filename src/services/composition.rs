@@ -0,0 +1,351 @@
+//! Composition generation
+//!
+//! Turns a list of asset symbols into registry asset IDs and basis-point
+//! weights for a new ITP, weighted equally or by market cap and held within
+//! the composition limits (config::CompositionLimits). Shared by the web UI,
+//! the MCP server and tests through POST /api/compositions/generate.
+//!
+//! Market caps are the latest from coins_historical_prices in the past week.
+//! A symbol shared by several coins takes the largest of them.
+
+use std::collections::{HashMap, HashSet};
+
+use asset_registry::AssetRegistry;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::config::{CompositionLimits, TOTAL_WEIGHT_BPS};
+use crate::models::composition::{CompositionAsset, GenerateCompositionRequest, GeneratedComposition};
+use crate::services::itp_validation::registry_symbol;
+use crate::services::price_utils;
+use crate::services::weight_calculator::WeightStrategy;
+
+/// How far back market caps are looked up
+const MARKET_CAP_LOOKBACK_DAYS: i64 = 7;
+
+#[derive(Debug)]
+pub enum CompositionError {
+    InvalidWeighting(String),
+    InvalidLimits(String),
+    NoAssets,
+    TooManyAssets { max: usize, requested: usize },
+    DuplicateAssets(Vec<String>),
+    UnknownAssets(Vec<String>),
+    MissingMarketCaps(Vec<String>),
+    /// The weights couldn't be brought within the limits
+    LimitsUnmet(String),
+    Database(DbErr),
+}
+
+impl std::fmt::Display for CompositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompositionError::InvalidWeighting(weighting) => {
+                write!(f, "Unknown weighting '{}', expected marketcap or equal", weighting)
+            }
+            CompositionError::InvalidLimits(reason) => write!(f, "{}", reason),
+            CompositionError::NoAssets => write!(f, "At least one asset is required"),
+            CompositionError::TooManyAssets { max, requested } => {
+                write!(f, "At most {} assets allowed, got {}", max, requested)
+            }
+            CompositionError::DuplicateAssets(symbols) => write!(f, "Duplicate assets: {}", symbols.join(", ")),
+            CompositionError::UnknownAssets(symbols) => {
+                write!(f, "Assets not in the asset registry: {}", symbols.join(", "))
+            }
+            CompositionError::MissingMarketCaps(symbols) => {
+                write!(f, "No recent market cap for: {}", symbols.join(", "))
+            }
+            CompositionError::LimitsUnmet(reason) => write!(f, "{}", reason),
+            CompositionError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CompositionError {}
+
+impl From<DbErr> for CompositionError {
+    fn from(e: DbErr) -> Self {
+        CompositionError::Database(e)
+    }
+}
+
+/// Generate a composition for `request` under `limits` narrowed by the request's
+pub async fn generate(
+    db: &DatabaseConnection,
+    registry: &AssetRegistry,
+    request: &GenerateCompositionRequest,
+    limits: &CompositionLimits,
+    today: NaiveDate,
+) -> Result<GeneratedComposition, CompositionError> {
+    let strategy = WeightStrategy::from_str(&request.weighting)
+        .ok_or_else(|| CompositionError::InvalidWeighting(request.weighting.clone()))?;
+
+    let limits = limits.narrowed(request.limits.as_ref());
+    if let Some(reason) = limits.infeasible() {
+        return Err(CompositionError::InvalidLimits(reason));
+    }
+
+    let symbols: Vec<String> = request.assets.iter().map(|s| s.trim().to_uppercase()).collect();
+    if symbols.is_empty() {
+        return Err(CompositionError::NoAssets);
+    }
+    if let Some(max) = limits.max_assets.filter(|max| symbols.len() > *max) {
+        return Err(CompositionError::TooManyAssets { max, requested: symbols.len() });
+    }
+
+    let mut seen = HashSet::new();
+    let duplicates: Vec<String> = symbols.iter().filter(|s| !seen.insert(*s)).cloned().collect();
+    if !duplicates.is_empty() {
+        return Err(CompositionError::DuplicateAssets(duplicates));
+    }
+
+    let by_symbol: HashMap<String, u128> = registry
+        .all()
+        .iter()
+        .map(|asset| (registry_symbol(asset), asset.id))
+        .collect();
+    let unknown: Vec<String> = symbols.iter().filter(|s| !by_symbol.contains_key(*s)).cloned().collect();
+    if !unknown.is_empty() {
+        return Err(CompositionError::UnknownAssets(unknown));
+    }
+
+    let (market_caps, raw) = match strategy {
+        WeightStrategy::Equal => (HashMap::new(), vec![Decimal::ONE; symbols.len()]),
+        WeightStrategy::MarketCap => {
+            let mut market_caps = price_utils::latest_symbol_prices(db, &symbols, today, MARKET_CAP_LOOKBACK_DAYS).await?;
+            market_caps.retain(|_, price| price.market_cap.is_some_and(|m| m > Decimal::ZERO));
+            let missing: Vec<String> = symbols.iter().filter(|s| !market_caps.contains_key(*s)).cloned().collect();
+            if !missing.is_empty() {
+                return Err(CompositionError::MissingMarketCaps(missing));
+            }
+            let raw = symbols.iter().map(|s| market_caps[s].market_cap.unwrap_or_default()).collect();
+            (market_caps, raw)
+        }
+    };
+
+    let weights = allocate(&raw, &limits)?;
+
+    let assets: Vec<CompositionAsset> = symbols
+        .iter()
+        .zip(&weights)
+        .map(|(symbol, weight_bps)| {
+            let market_cap = market_caps.get(symbol);
+            CompositionAsset {
+                asset_id: by_symbol[symbol],
+                symbol: symbol.clone(),
+                coin_id: market_cap.map(|m| m.coin_id.clone()),
+                market_cap: market_cap.and_then(|m| m.market_cap),
+                weight_bps: *weight_bps,
+            }
+        })
+        .collect();
+
+    Ok(GeneratedComposition {
+        weighting: match strategy {
+            WeightStrategy::Equal => "equal",
+            WeightStrategy::MarketCap => "marketcap",
+        }
+        .to_string(),
+        max_asset_weight_bps: limits.max_asset_weight_bps,
+        min_asset_weight_bps: limits.min_asset_weight_bps,
+        max_assets: limits.max_assets,
+        market_cap_date: market_caps.values().map(|m| m.date).min(),
+        asset_ids: assets.iter().map(|a| a.asset_id).collect(),
+        weights,
+        assets,
+    })
+}
+
+/// Basis-point weights proportional to `raw`, each within the min/max asset weight
+///
+/// Water-filling: every round spreads the weight not yet pinned over the free
+/// assets in proportion to `raw`. If that puts free assets outside the bounds,
+/// the side with the larger total violation is pinned at its bound and the
+/// round repeats. Each round pins at least one asset, so it ends within n rounds.
+///
+/// Pinning only the larger side is what keeps pinned assets correct: with E
+/// the total above the cap and D the total below the minimum, E >= D means
+/// re-spreading can only raise the remaining free weights, so an asset pinned
+/// at the cap never needs to come back under it (and symmetrically for D > E).
+/// Given n * min <= 10000 <= n * max, this converges to weights that sum to
+/// 10000 with none outside the bounds.
+///
+/// Weights are then rounded by largest remainder: floor each, then add one bps
+/// to the largest fractional parts until the sum is exactly 10000. The bounds
+/// are whole bps, so a weight within them stays within them.
+fn allocate(raw: &[Decimal], limits: &CompositionLimits) -> Result<Vec<u128>, CompositionError> {
+    let total: Decimal = raw.iter().sum();
+    if raw.is_empty() || total <= Decimal::ZERO || raw.iter().any(|r| r.is_sign_negative()) {
+        return Err(CompositionError::NoAssets);
+    }
+
+    let count = raw.len() as u128;
+    if count * limits.min_asset_weight_bps > TOTAL_WEIGHT_BPS || count * limits.max_asset_weight_bps < TOTAL_WEIGHT_BPS {
+        return Err(CompositionError::LimitsUnmet(format!(
+            "{} assets can't each be within {}-{} bps and sum to {} bps",
+            count, limits.min_asset_weight_bps, limits.max_asset_weight_bps, TOTAL_WEIGHT_BPS
+        )));
+    }
+
+    let total_bps = Decimal::from(TOTAL_WEIGHT_BPS);
+    let max = Decimal::from(limits.max_asset_weight_bps);
+    let min = Decimal::from(limits.min_asset_weight_bps);
+    let mut pinned: Vec<Option<Decimal>> = vec![None; raw.len()];
+    let mut weights = vec![Decimal::ZERO; raw.len()];
+
+    loop {
+        let remaining = total_bps - pinned.iter().flatten().sum::<Decimal>();
+        let free: Vec<usize> = (0..raw.len()).filter(|i| pinned[*i].is_none()).collect();
+        if free.is_empty() {
+            break;
+        }
+        let free_raw: Decimal = free.iter().map(|i| raw[*i]).sum();
+        for &i in &free {
+            weights[i] = if free_raw.is_zero() {
+                remaining / Decimal::from(free.len())
+            } else {
+                remaining * raw[i] / free_raw
+            };
+        }
+
+        let excess: Decimal = free.iter().map(|i| weights[*i] - max).filter(|e| e.is_sign_positive()).sum();
+        let deficit: Decimal = free.iter().map(|i| min - weights[*i]).filter(|d| d.is_sign_positive()).sum();
+        if excess.is_zero() && deficit.is_zero() {
+            break;
+        }
+        for &i in &free {
+            if excess >= deficit && weights[i] > max {
+                pinned[i] = Some(max);
+                weights[i] = max;
+            } else if deficit > excess && weights[i] < min {
+                pinned[i] = Some(min);
+                weights[i] = min;
+            }
+        }
+    }
+
+    // Drop division noise (e.g. 3999.99...9) before flooring
+    let weights: Vec<Decimal> = weights.iter().map(|w| w.round_dp(9)).collect();
+    let mut bps: Vec<u128> = weights.iter().map(|w| w.floor().try_into().unwrap_or(0)).collect();
+    let mut remainder = TOTAL_WEIGHT_BPS.saturating_sub(bps.iter().sum::<u128>());
+    let mut by_fraction: Vec<usize> = (0..weights.len()).collect();
+    by_fraction.sort_by(|a, b| weights[*b].fract().cmp(&weights[*a].fract()));
+    for index in by_fraction {
+        if remainder == 0 {
+            break;
+        }
+        if bps[index] < limits.max_asset_weight_bps {
+            bps[index] += 1;
+            remainder -= 1;
+        }
+    }
+
+    debug_assert_eq!(bps.iter().sum::<u128>(), TOTAL_WEIGHT_BPS);
+    debug_assert!(bps
+        .iter()
+        .all(|w| (limits.min_asset_weight_bps..=limits.max_asset_weight_bps).contains(w)));
+    Ok(bps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rust_decimal_macros::dec;
+
+    fn limits(max: u128, min: u128) -> CompositionLimits {
+        CompositionLimits { max_asset_weight_bps: max, min_asset_weight_bps: min, max_assets: None }
+    }
+
+    #[test]
+    fn test_allocate_proportional() {
+        let bps = allocate(&[dec!(3), dec!(1)], &CompositionLimits::default()).unwrap();
+        assert_eq!(bps, vec![7500, 2500]);
+
+        let bps = allocate(&[dec!(1), dec!(1), dec!(1)], &CompositionLimits::default()).unwrap();
+        assert_eq!(bps.iter().sum::<u128>(), 10_000);
+        assert_eq!(bps, vec![3334, 3333, 3333]);
+    }
+
+    #[test]
+    fn test_allocate_caps_and_redistributes() {
+        // 80/10/5/5 capped at 40%: the excess goes to the rest in proportion
+        let bps = allocate(&[dec!(80), dec!(10), dec!(5), dec!(5)], &limits(4000, 0)).unwrap();
+        assert_eq!(bps, vec![4000, 3000, 1500, 1500]);
+    }
+
+    #[test]
+    fn test_allocate_raises_weights_to_minimum() {
+        let bps = allocate(&[dec!(99), dec!(1)], &limits(10_000, 500)).unwrap();
+        assert_eq!(bps, vec![9500, 500]);
+
+        // The minimum comes out of the others in proportion
+        let bps = allocate(&[dec!(60), dec!(30), dec!(9), dec!(1)], &limits(10_000, 1000)).unwrap();
+        assert_eq!(bps, vec![5333, 2667, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_allocate_both_bounds() {
+        // Re-spreading the capped weight pushes the third asset to the cap too,
+        // and the last one stays pinned at the minimum
+        let bps = allocate(&[dec!(50), dec!(45), dec!(4), dec!(1)], &limits(3000, 1000)).unwrap();
+        assert_eq!(bps, vec![3000, 3000, 3000, 1000]);
+    }
+
+    #[test]
+    fn test_allocate_infeasible_limits() {
+        let result = allocate(&[dec!(1), dec!(1), dec!(1)], &limits(3000, 0));
+        assert!(matches!(result, Err(CompositionError::LimitsUnmet(_))));
+        let result = allocate(&[dec!(1), dec!(1), dec!(1)], &limits(10_000, 4000));
+        assert!(matches!(result, Err(CompositionError::LimitsUnmet(_))));
+    }
+
+    fn raw_and_limits() -> impl Strategy<Value = (Vec<Decimal>, CompositionLimits)> {
+        prop::collection::vec(1u64..1_000_000_000_000, 1..40).prop_flat_map(|raw| {
+            let count = raw.len() as u128;
+            let raw: Vec<Decimal> = raw.into_iter().map(Decimal::from).collect();
+            (
+                Just(raw),
+                TOTAL_WEIGHT_BPS.div_ceil(count)..=TOTAL_WEIGHT_BPS,
+                0..=TOTAL_WEIGHT_BPS / count,
+            )
+                .prop_map(|(raw, max, min)| (raw, limits(max, min)))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_allocate_sums_to_total_within_bounds((raw, limits) in raw_and_limits()) {
+            let bps = allocate(&raw, &limits).unwrap();
+            prop_assert_eq!(bps.iter().sum::<u128>(), TOTAL_WEIGHT_BPS);
+            for weight in &bps {
+                prop_assert!(*weight <= limits.max_asset_weight_bps, "{} above cap in {:?}", weight, bps);
+                prop_assert!(*weight >= limits.min_asset_weight_bps, "{} below minimum in {:?}", weight, bps);
+            }
+        }
+
+        #[test]
+        fn prop_allocate_preserves_order((raw, limits) in raw_and_limits()) {
+            let bps = allocate(&raw, &limits).unwrap();
+            for i in 0..raw.len() {
+                for j in 0..raw.len() {
+                    if raw[i] > raw[j] {
+                        prop_assert!(bps[i] >= bps[j], "{} < {} for raw {} > {}", bps[i], bps[j], raw[i], raw[j]);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn prop_allocate_unbounded_is_proportional(raw in prop::collection::vec(1u64..1_000_000_000_000, 1..40)) {
+            let raw: Vec<Decimal> = raw.into_iter().map(Decimal::from).collect();
+            let total: Decimal = raw.iter().sum();
+            let bps = allocate(&raw, &CompositionLimits::default()).unwrap();
+            for (r, weight) in raw.iter().zip(&bps) {
+                let exact = r / total * Decimal::from(TOTAL_WEIGHT_BPS);
+                prop_assert!((Decimal::from(*weight) - exact).abs() < Decimal::ONE);
+            }
+        }
+    }
+}
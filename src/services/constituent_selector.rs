@@ -1,4 +1,5 @@
 use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect};
 use lazy_static::lazy_static;
 use std::collections::HashSet;
@@ -7,6 +8,7 @@ use crate::entities::{
     category_membership, coins_historical_prices, crypto_listings, index_constituents, prelude::*,
 };
 use crate::services::exchange_api::ExchangeApiService;
+use crate::services::{index_constraints, pair_volumes};
 
 lazy_static! {
     /// Whitelisted coin_ids that should be included even if in blacklisted categories
@@ -219,6 +221,7 @@ impl FixedConstituentSelector {
 pub struct TopMarketCapSelector {
     top_n: usize,
     blacklisted_categories: Option<Vec<String>>,
+    min_pair_volume_usd: Option<Decimal>,
 }
 
 impl TopMarketCapSelector {
@@ -226,9 +229,16 @@ impl TopMarketCapSelector {
         Self { 
             top_n,
             blacklisted_categories,
+            min_pair_volume_usd: None,
         }
     }
 
+    /// Skip tokens whose exchange pair traded less than `min_pair_volume_usd` in 24h
+    pub fn with_min_pair_volume(mut self, min_pair_volume_usd: Option<Decimal>) -> Self {
+        self.min_pair_volume_usd = min_pair_volume_usd;
+        self
+    }
+
     pub async fn select_constituents(
         &self,
         db: &DatabaseConnection,
@@ -322,6 +332,9 @@ impl TopMarketCapSelector {
             )
             .await?
             {
+                if !passes_volume_screen(db, &token, date, self.min_pair_volume_usd).await? {
+                    continue;
+                }
                 tradeable.push(token);
 
                 // Stop once we have enough
@@ -357,6 +370,7 @@ impl TopMarketCapSelector {
 pub struct CategoryBasedSelector {
     category_id: String,
     blacklisted_categories: Option<Vec<String>>,
+    min_pair_volume_usd: Option<Decimal>,
 }
 
 impl CategoryBasedSelector {
@@ -364,9 +378,16 @@ impl CategoryBasedSelector {
         Self { 
             category_id,
             blacklisted_categories,
+            min_pair_volume_usd: None,
         }
     }
 
+    /// Skip tokens whose exchange pair traded less than `min_pair_volume_usd` in 24h
+    pub fn with_min_pair_volume(mut self, min_pair_volume_usd: Option<Decimal>) -> Self {
+        self.min_pair_volume_usd = min_pair_volume_usd;
+        self
+    }
+
     pub async fn select_constituents(
         &self,
         db: &DatabaseConnection,
@@ -465,6 +486,9 @@ impl CategoryBasedSelector {
             )
             .await?
             {
+                if !passes_volume_screen(db, &token, date, self.min_pair_volume_usd).await? {
+                    continue;
+                }
                 tradeable.push(token);
             }
         }
@@ -487,18 +511,8 @@ impl CategoryBasedSelector {
         db: &DatabaseConnection,
         date: NaiveDate,
     ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let date_time = date.and_hms_opt(0, 0, 0).unwrap();
-
-        let memberships = CategoryMembership::find()
-            .filter(category_membership::Column::CategoryId.eq(&self.category_id))
-            .filter(category_membership::Column::AddedDate.lte(date_time))
-            .filter(
-                category_membership::Column::RemovedDate
-                    .is_null()
-                    .or(category_membership::Column::RemovedDate.gt(date_time)),
-            )
-            .all(db)
-            .await?;
+        let memberships =
+            crate::services::category_service::get_category_members_at(db, &self.category_id, date).await?;
 
         Ok(memberships.into_iter().map(|m| m.coin_id).collect())
     }
@@ -560,6 +574,9 @@ impl ConstituentSelectorFactory {
             .as_ref()
             .and_then(|v| serde_json::from_value(v.clone()).ok());
 
+        // Fixed constituents are chosen explicitly and aren't volume-screened
+        let min_pair_volume_usd = index_constraints::for_index(index).min_pair_volume_usd;
+
         // Log blacklist config
         if let Some(ref blacklist) = blacklisted_categories {
             tracing::info!(
@@ -606,6 +623,7 @@ impl ConstituentSelectorFactory {
             );
             return Ok(ConstituentSelectorEnum::TopMarketCap(
                 TopMarketCapSelector::new(top_x as usize, blacklisted_categories)
+                    .with_min_pair_volume(min_pair_volume_usd)
             ));
         }
 
@@ -626,6 +644,7 @@ impl ConstituentSelectorFactory {
 
             return Ok(ConstituentSelectorEnum::TopMarketCap(
                 TopMarketCapSelector::new(top_n, blacklisted_categories)
+                    .with_min_pair_volume(min_pair_volume_usd)
             ));
         }
 
@@ -639,6 +658,7 @@ impl ConstituentSelectorFactory {
             );
             return Ok(ConstituentSelectorEnum::CategoryBased(
                 CategoryBasedSelector::new(category.clone(), blacklisted_categories)
+                    .with_min_pair_volume(min_pair_volume_usd)
             ));
         }
 
@@ -705,6 +725,32 @@ async fn query_market_caps_for_coins(
         .collect())
 }
 
+/// Whether the pair `token` would trade on meets the index's 24h volume
+/// floor, from exchange-native volume snapshots (services::pair_volumes)
+async fn passes_volume_screen(
+    db: &DatabaseConnection,
+    token: &ConstituentToken,
+    date: NaiveDate,
+    min_pair_volume_usd: Option<Decimal>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(min_volume) = min_pair_volume_usd else {
+        return Ok(true);
+    };
+    let passes =
+        pair_volumes::meets_floor(db, &token.exchange, &token.symbol, &token.trading_pair, date, min_volume).await?;
+    if !passes {
+        tracing::debug!(
+            "Filtered out: {} ({}) - {} {} pair below {} USD 24h volume",
+            token.symbol,
+            token.coin_id,
+            token.exchange,
+            token.trading_pair,
+            min_volume
+        );
+    }
+    Ok(passes)
+}
+
 /// Find tradeable token info with priority: Binance USDC > USDT > Bitget USDC > USDT
 /// 
 /// If exchange_api is provided (scheduled mode), uses live APIs
@@ -0,0 +1,188 @@
+//! Return correlations and volatilities of a set of coins
+//!
+//! Daily log returns `ln(p(t) / p(t-1))` are computed from
+//! coins_historical_prices over a window of days; a day without a price
+//! breaks the returns on both sides of it rather than spanning the gap.
+//! Volatility is the sample standard deviation of a coin's returns, and
+//! correlations are pairwise: each pair uses the days both coins have a
+//! return, so a recently listed coin doesn't shorten everyone's sample.
+//!
+//! For the standard windows, volatilities already stored by the rolling
+//! stats job are reused (see services::rolling_stats).
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::entities::{prelude::*, rolling_stats};
+use crate::models::analytics::{CoinVolatility, CorrelationResponse};
+use crate::services::market_cap::history_for_coins;
+use crate::services::rolling_stats::{subjects, DAYS_PER_YEAR, WINDOWS};
+
+pub const DEFAULT_WINDOW: u32 = 90;
+pub const MAX_WINDOW: u32 = 730;
+
+/// Daily log returns of a price series; None where either day has no price
+pub fn log_returns(prices: &[Option<f64>]) -> Vec<Option<f64>> {
+    prices
+        .windows(2)
+        .map(|pair| match (pair[0], pair[1]) {
+            (Some(before), Some(after)) if before > 0.0 && after > 0.0 => Some((after / before).ln()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation; None under two values
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values);
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Pearson correlation over the days both series have a return; None under
+/// two such days or when either series is flat on them
+pub fn correlation(a: &[Option<f64>], b: &[Option<f64>]) -> Option<f64> {
+    let (xs, ys): (Vec<f64>, Vec<f64>) = a
+        .iter()
+        .zip(b)
+        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+        .unzip();
+    if xs.len() < 2 {
+        return None;
+    }
+
+    let (mean_x, mean_y) = (mean(&xs), mean(&ys));
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(&ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    // Rounding can push a perfect correlation a hair past ±1
+    Some((covariance / (var_x * var_y).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Correlation matrix and volatilities of `coin_ids` over the `window` daily
+/// returns ending on `end`
+pub async fn analyze(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    end: NaiveDate,
+    window: u32,
+) -> Result<CorrelationResponse, sea_orm::DbErr> {
+    // One more price than returns
+    let history = history_for_coins(db, coin_ids, end - Duration::days(window as i64), end).await?;
+
+    let returns: Vec<Vec<Option<f64>>> = history.coins.iter().map(|coin| log_returns(&coin.prices)).collect();
+
+    let mut stored: HashMap<String, rolling_stats::Model> = HashMap::new();
+    if WINDOWS.contains(&window) {
+        stored = RollingStats::find()
+            .filter(rolling_stats::Column::SubjectType.eq(subjects::COIN))
+            .filter(rolling_stats::Column::SubjectId.is_in(coin_ids.iter().cloned()))
+            .filter(rolling_stats::Column::WindowDays.eq(window as i32))
+            .filter(rolling_stats::Column::AsOf.eq(end))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|row| (row.subject_id.clone(), row))
+            .collect();
+    }
+
+    let coins = history
+        .coins
+        .iter()
+        .zip(&returns)
+        .map(|(coin, returns)| {
+            if let Some(row) = stored.get(&coin.coin_id) {
+                return CoinVolatility {
+                    coin_id: coin.coin_id.clone(),
+                    symbol: coin.symbol.clone(),
+                    observations: row.observations as usize,
+                    daily_volatility: row.volatility.and_then(|v| v.to_f64()),
+                    annualized_volatility: row.annualized_volatility.and_then(|v| v.to_f64()),
+                };
+            }
+            let values: Vec<f64> = returns.iter().flatten().copied().collect();
+            let daily_volatility = std_dev(&values);
+            CoinVolatility {
+                coin_id: coin.coin_id.clone(),
+                symbol: coin.symbol.clone(),
+                observations: values.len(),
+                daily_volatility,
+                annualized_volatility: daily_volatility.map(|v| v * DAYS_PER_YEAR.sqrt()),
+            }
+        })
+        .collect();
+
+    let correlation = returns
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            returns
+                .iter()
+                .enumerate()
+                .map(|(j, b)| if i == j { Some(1.0) } else { correlation(a, b) })
+                .collect()
+        })
+        .collect();
+
+    Ok(CorrelationResponse {
+        start_date: end - Duration::days(window as i64 - 1),
+        end_date: end,
+        window,
+        coins,
+        correlation,
+        missing: history.missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_log_returns_skip_gaps() {
+        let returns = log_returns(&[Some(100.0), Some(110.0), None, Some(121.0), Some(121.0)]);
+        assert_eq!(returns.len(), 4);
+        assert!(close(returns[0].unwrap(), 1.1f64.ln()));
+        assert_eq!(returns[1], None);
+        assert_eq!(returns[2], None);
+        assert_eq!(returns[3], Some(0.0));
+    }
+
+    #[test]
+    fn test_std_dev() {
+        assert_eq!(std_dev(&[0.1]), None);
+        assert!(close(std_dev(&[1.0, 2.0, 3.0, 4.0]).unwrap(), (5.0f64 / 3.0).sqrt()));
+    }
+
+    #[test]
+    fn test_correlation() {
+        let a = [Some(0.01), Some(-0.02), Some(0.03), None];
+        let doubled = [Some(0.02), Some(-0.04), Some(0.06), Some(0.5)];
+        let inverse = [Some(-0.01), Some(0.02), Some(-0.03), Some(0.1)];
+        assert!(close(correlation(&a, &doubled).unwrap(), 1.0));
+        assert!(close(correlation(&a, &inverse).unwrap(), -1.0));
+        // Flat or too short
+        assert_eq!(correlation(&a, &[Some(0.0), Some(0.0), Some(0.0), None]), None);
+        assert_eq!(correlation(&a, &[None, None, Some(0.1), Some(0.2)]), None);
+    }
+}
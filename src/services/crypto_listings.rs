@@ -0,0 +1,177 @@
+//! Exchange listings and their source announcements
+//!
+//! Each crypto_listings row records when a coin was listed on and delisted
+//! from an exchange pair. The scrapers and the dump importer set
+//! source_announcement_id to the stored announcement behind the row's most
+//! recently applied listing or delisting date, so every date can be traced
+//! back to the exchange announcement it came from.
+
+use chrono::NaiveDateTime;
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+
+use crate::entities::{announcements, crypto_listings, prelude::*};
+
+/// Default and maximum number of listings returned
+pub const DEFAULT_LIMIT: u64 = 100;
+pub const MAX_LIMIT: u64 = 1000;
+
+/// Most rows a CSV export returns
+pub const MAX_EXPORT_ROWS: u64 = 100_000;
+
+/// Id of the stored `source` announcement published at `announce_date`
+///
+/// With a title this is the exact announcement the scrapers saved. Without
+/// one (the dump importer only has dates), the date must match a single
+/// announcement of that exchange; None if no announcement or several match.
+pub async fn find_announcement_id(
+    db: &DatabaseConnection,
+    source: &str,
+    announce_date: NaiveDateTime,
+    title: Option<&str>,
+) -> Result<Option<i32>, sea_orm::DbErr> {
+    let mut query = Announcements::find()
+        .filter(announcements::Column::Source.eq(source))
+        .filter(announcements::Column::AnnounceDate.eq(announce_date));
+    if let Some(title) = title {
+        query = query.filter(announcements::Column::Title.eq(title));
+    }
+
+    let matches = query.order_by_asc(announcements::Column::Id).limit(2).all(db).await?;
+    match matches.as_slice() {
+        [announcement] => Ok(Some(announcement.id)),
+        [first, ..] if title.is_some() => Ok(Some(first.id)),
+        _ => Ok(None),
+    }
+}
+
+/// Filters of the listings endpoint
+#[derive(Debug, Clone, Default)]
+pub struct ListingFilter {
+    pub coin_id: Option<String>,
+    pub exchange: Option<String>,
+    pub status: Option<String>,
+    /// Matched case-insensitively
+    pub symbol: Option<String>,
+    pub limit: Option<u64>,
+}
+
+impl ListingFilter {
+    fn apply(&self, mut query: Select<CryptoListings>) -> Select<CryptoListings> {
+        if let Some(coin_id) = &self.coin_id {
+            query = query.filter(crypto_listings::Column::CoinId.eq(coin_id.trim().to_lowercase()));
+        }
+        if let Some(exchange) = &self.exchange {
+            query = query.filter(crypto_listings::Column::Exchange.eq(exchange.trim().to_lowercase()));
+        }
+        if let Some(status) = &self.status {
+            query = query.filter(crypto_listings::Column::Status.eq(status.trim().to_lowercase()));
+        }
+        if let Some(symbol) = &self.symbol {
+            query = query.filter(
+                Expr::expr(Func::upper(Expr::col(crypto_listings::Column::Symbol))).eq(symbol.trim().to_uppercase()),
+            );
+        }
+        query
+    }
+}
+
+/// Listings with their source announcement, newest first
+pub async fn list(
+    db: &DatabaseConnection,
+    filter: &ListingFilter,
+) -> Result<Vec<(crypto_listings::Model, Option<announcements::Model>)>, sea_orm::DbErr> {
+    filter
+        .apply(CryptoListings::find())
+        .find_also_related(Announcements)
+        .order_by_desc(crypto_listings::Column::Id)
+        .limit(filter.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+        .all(db)
+        .await
+}
+
+/// Page `page` (from 1) of the listings matching `filter`, newest first,
+/// with their source announcement, and the total number of matches
+pub async fn page(
+    db: &DatabaseConnection,
+    filter: &ListingFilter,
+    page: u64,
+    page_size: u64,
+) -> Result<(Vec<(crypto_listings::Model, Option<announcements::Model>)>, u64), sea_orm::DbErr> {
+    let total = filter.apply(CryptoListings::find()).count(db).await?;
+    let listings = filter
+        .apply(CryptoListings::find())
+        .find_also_related(Announcements)
+        .order_by_desc(crypto_listings::Column::Id)
+        .offset((page.max(1) - 1) * page_size)
+        .limit(page_size)
+        .all(db)
+        .await?;
+    Ok((listings, total))
+}
+
+/// Every listing matching `filter` (up to `MAX_EXPORT_ROWS`), oldest first
+pub async fn export(
+    db: &DatabaseConnection,
+    filter: &ListingFilter,
+) -> Result<Vec<crypto_listings::Model>, sea_orm::DbErr> {
+    filter
+        .apply(CryptoListings::find())
+        .order_by_asc(crypto_listings::Column::Id)
+        .limit(MAX_EXPORT_ROWS)
+        .all(db)
+        .await
+}
+
+/// Listings as CSV, one row per listing
+pub fn to_csv(listings: &[crypto_listings::Model]) -> String {
+    let mut csv = String::from(
+        "id,coin_id,symbol,token_name,exchange,trading_pair,status,listing_announcement_date,listing_date,\
+         delisting_announcement_date,delisting_date,source_announcement_id\n",
+    );
+    let date = |value: Option<NaiveDateTime>| value.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string()).unwrap_or_default();
+
+    for listing in listings {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            listing.id,
+            csv_field(&listing.coin_id),
+            csv_field(&listing.symbol),
+            csv_field(&listing.token_name),
+            csv_field(&listing.exchange),
+            csv_field(&listing.trading_pair),
+            csv_field(&listing.status),
+            date(listing.listing_announcement_date),
+            date(listing.listing_date),
+            date(listing.delisting_announcement_date),
+            date(listing.delisting_date),
+            listing.source_announcement_id.map(|id| id.to_string()).unwrap_or_default(),
+        ));
+    }
+    csv
+}
+
+/// Quote a value containing a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Every recorded listing of `symbol` on `exchange`, one per trading pair
+pub async fn for_exchange_symbol(
+    db: &DatabaseConnection,
+    exchange: &str,
+    symbol: &str,
+) -> Result<Vec<crypto_listings::Model>, sea_orm::DbErr> {
+    CryptoListings::find()
+        .filter(crypto_listings::Column::Exchange.eq(exchange.to_lowercase()))
+        .filter(Expr::expr(Func::upper(Expr::col(crypto_listings::Column::Symbol))).eq(symbol.to_uppercase()))
+        .order_by_asc(crypto_listings::Column::TradingPair)
+        .all(db)
+        .await
+}
@@ -8,15 +8,25 @@ use std::collections::HashMap;
 
 use crate::entities::{daily_prices, rebalances, prelude::*};
 use crate::services::coingecko::CoinGeckoService;
+use crate::services::leverage;
+use crate::services::price_fallback::{self, PriceFallback};
 use crate::services::price_utils::get_or_fetch_coins_historical_price;
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
 use crate::services::rebalancing::CoinRebalanceInfo;
-
-/// Backfill daily prices for an index from initial_date to yesterday
+use crate::services::yield_accrual::YieldAccrual;
+
+/// Backfill daily prices for an index from its inception to today
+///
+/// The inception date is priced at the first rebalance's portfolio value
+/// (see services::inception), every later date by valuing the basket of the
+/// rebalance before it. Dates already priced are left alone. Returns the
+/// number of prices stored.
 pub async fn backfill_daily_prices(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
     index_id: i32,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
     tracing::info!("Starting daily prices backfill for index {}", index_id);
 
     // Get all rebalances for this index
@@ -28,7 +38,7 @@ pub async fn backfill_daily_prices(
 
     if rebalances.is_empty() {
         tracing::info!("No rebalances found for index {}, skipping daily prices backfill", index_id);
-        return Ok(());
+        return Ok(0);
     }
 
     tracing::info!(
@@ -38,22 +48,28 @@ pub async fn backfill_daily_prices(
     );
 
     let today = chrono::Utc::now().date_naive();
+    let leverage_factor = IndexMetadata::find_by_id(index_id)
+        .one(db)
+        .await?
+        .and_then(|index| leverage::for_index(&index));
+
+    let mut stored = 0;
+    if store_inception_price(db, index_id, &rebalances[0]).await? {
+        tracing::info!("Stored inception price for index {}", index_id);
+        stored += 1;
+    }
 
     // Loop through rebalance periods
     for i in 0..rebalances.len() {
         let current_rebalance = &rebalances[i];
         
         // Start date: day after current rebalance
-        let rebalance_date = chrono::DateTime::from_timestamp(current_rebalance.timestamp, 0)
-            .unwrap()
-            .date_naive();
+        let rebalance_date = pricing_time::rebalance_date(current_rebalance.timestamp).unwrap_or_default();
         let start_date = rebalance_date + chrono::Duration::days(1);
 
         // End date: next rebalance date (or today if last rebalance)
         let end_date = if i + 1 < rebalances.len() {
-            chrono::DateTime::from_timestamp(rebalances[i + 1].timestamp, 0)
-                .unwrap()
-                .date_naive()
+            pricing_time::rebalance_date(rebalances[i + 1].timestamp).unwrap_or_default()
         } else {
             today
         };
@@ -78,7 +94,9 @@ pub async fn backfill_daily_prices(
         );
 
         // Parse coins from this rebalance
-        let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(current_rebalance.coins.clone())?;
+        let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(current_rebalance.coins.clone())?;
+        let coin_ids: Vec<String> = coins.iter().map(|coin| coin.coin_id.clone()).collect();
+        let accrual = YieldAccrual::for_index(db, index_id, &coin_ids, rebalance_date).await?;
 
         // Fill prices for each day in this period
         let mut date = start_date;
@@ -86,7 +104,7 @@ pub async fn backfill_daily_prices(
         let mut skipped = 0;
 
         while date <= end_date {
-            match calculate_and_store_index_price(db, coingecko, index_id, date, &coins).await {
+            match calculate_and_store_index_price(db, coingecko, index_id, date, &coins, &accrual, leverage_factor).await {
                 Ok(true) => processed += 1,
                 Ok(false) => skipped += 1,
                 Err(e) => {
@@ -115,20 +133,63 @@ pub async fn backfill_daily_prices(
             processed,
             skipped
         );
+        stored += processed;
     }
 
-    tracing::info!("Daily prices backfill complete for index {}", index_id);
-    Ok(())
+    tracing::info!("Daily prices backfill complete for index {} ({} prices stored)", index_id, stored);
+    Ok(stored)
+}
+
+/// Store the first rebalance's portfolio value as the daily price of its date
+/// Returns Ok(true) if inserted, Ok(false) if already exists
+async fn store_inception_price(
+    db: &DatabaseConnection,
+    index_id: i32,
+    first_rebalance: &rebalances::Model,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let date = pricing_time::rebalance_date(first_rebalance.timestamp).ok_or("Invalid rebalance timestamp")?;
+    let existing = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Ok(false);
+    }
+
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(first_rebalance.coins.clone())?;
+    let quantities: HashMap<String, f64> = coins
+        .iter()
+        .map(|coin| Ok((coin.coin_id.clone(), coin.quantity.parse()?)))
+        .collect::<Result<_, std::num::ParseFloatError>>()?;
+
+    daily_prices::ActiveModel {
+        index_id: Set(index_id.to_string()),
+        date: Set(date),
+        price: Set(first_rebalance.portfolio_value),
+        quantities: Set(Some(serde_json::to_value(&quantities)?)),
+        created_at: Set(Some(chrono::Utc::now().naive_utc())),
+        updated_at: Set(Some(chrono::Utc::now().naive_utc())),
+    }
+    .insert(db)
+    .await?;
+    Ok(true)
 }
 
 /// Calculate index price for a specific date and store in daily_prices
 /// Returns Ok(true) if inserted, Ok(false) if already exists
+///
+/// Quantities include the yield accrued since the rebalance, if any. A
+/// leveraged index's price is its NAV, carried from the previous day's (see
+/// services::leverage).
 async fn calculate_and_store_index_price(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
     index_id: i32,
     target_date: NaiveDate,
     coins: &[CoinRebalanceInfo],
+    accrual: &YieldAccrual,
+    leverage_factor: Option<Decimal>,
 ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
     // Check if price already exists for this date
     let existing = DailyPrices::find()
@@ -146,73 +207,18 @@ async fn calculate_and_store_index_price(
         return Ok(false);
     }
 
-    if coins.is_empty() {
-        return Err("Rebalance has no coins".into());
-    }
-
-    // Calculate index price: sum of (weight * quantity * token_price)
-    let mut index_price = Decimal::ZERO;
-    let mut quantities_map: HashMap<String, f64> = HashMap::new();
-    let mut missing_prices = Vec::new();
-
-    for coin in coins {
-        // Use self-healing price fetcher (same as rebalancing)
-        let token_price_result = get_or_fetch_coins_historical_price(
-            db,
-            coingecko,
-            &coin.coin_id,
-            &coin.symbol,
-            target_date,
-        )
-        .await;
-
-        match token_price_result {
-            Ok(price) => {
-                let weight: Decimal = coin.weight.parse()?;
-                let quantity: Decimal = coin.quantity.parse()?;
-                let price_decimal = Decimal::from_f64_retain(price)
-                    .ok_or("Invalid price conversion")?;
-
-                // index_price += weight * quantity * price
-                index_price += weight * quantity * price_decimal;
-
-                // Store quantity for the quantities field
-                quantities_map.insert(coin.coin_id.clone(), quantity.to_string().parse()?);
-
-                tracing::trace!(
-                    "  {} ({}): weight={}, qty={}, price={}, contribution={}",
-                    coin.symbol,
-                    coin.coin_id,
-                    weight,
-                    quantity,
-                    price,
-                    weight * quantity * price_decimal
-                );
-            }
-            Err(e) => {
-                missing_prices.push(coin.coin_id.clone());
-                tracing::warn!(
-                    "Failed to get price for {} ({}) on {}: {}",
-                    coin.symbol,
-                    coin.coin_id,
-                    target_date,
-                    e
-                );
-            }
+    let basket = basket_value(db, coingecko, coins, accrual, target_date).await?;
+    let index_price = match leverage_factor {
+        None => basket.value,
+        Some(factor) => {
+            let day_before = target_date - chrono::Duration::days(1);
+            let basket_before = basket_value(db, coingecko, coins, accrual, day_before).await?;
+            leverage::nav_on(db, index_id, target_date, factor, basket_before.value, basket.value).await?
         }
-    }
-
-    if !missing_prices.is_empty() {
-        return Err(format!(
-            "Missing prices for {} tokens: {}",
-            missing_prices.len(),
-            missing_prices.join(", ")
-        )
-        .into());
-    }
+    };
 
     // Store in daily_prices
-    let quantities_json = serde_json::to_value(&quantities_map)?;
+    let quantities_json = serde_json::to_value(&basket.quantities)?;
 
     let new_price = daily_prices::ActiveModel {
         index_id: Set(index_id.to_string()),
@@ -233,4 +239,77 @@ async fn calculate_and_store_index_price(
     );
 
     Ok(true)
-}
\ No newline at end of file
+}
+
+/// Value of a rebalance's basket at `target_date` prices
+pub(crate) struct BasketValue {
+    pub value: Decimal,
+    /// Quantities it was valued with
+    pub quantities: HashMap<String, f64>,
+}
+
+/// Value of a rebalance's basket at `target_date` prices
+///
+/// Constituents without a price fall back as per services::price_fallback;
+/// fails when that isn't enough.
+pub(crate) async fn basket_value(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    coins: &[CoinRebalanceInfo],
+    accrual: &YieldAccrual,
+    target_date: NaiveDate,
+) -> Result<BasketValue, Box<dyn std::error::Error + Send + Sync>> {
+    if coins.is_empty() {
+        return Err("Rebalance has no coins".into());
+    }
+
+    // Value of each constituent at the rebalance and on the day (None when
+    // excluded): weight * quantity * token_price
+    let mut values = Vec::new();
+    let mut quantities: HashMap<String, f64> = HashMap::new();
+    let mut fallbacks = Vec::new();
+
+    for coin in coins {
+        let weight: Decimal = coin.weight.parse()?;
+        let quantity = accrual.quantity(&coin.coin_id, coin.quantity.parse()?, target_date);
+        let rebalance_price = coin.price;
+        quantities.insert(coin.coin_id.clone(), quantity.to_string().parse()?);
+
+        // Use self-healing price fetcher (same as rebalancing)
+        let price = match get_or_fetch_coins_historical_price(db, coingecko, &coin.coin_id, &coin.symbol, target_date).await {
+            Ok(price) => Some(Decimal::from_f64_retain(price).ok_or("Invalid price conversion")?),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to get price for {} ({}) on {}: {}",
+                    coin.symbol,
+                    coin.coin_id,
+                    target_date,
+                    e
+                );
+                let (price, fallback) = match price_fallback::carried_forward_price(db, &coin.coin_id, target_date).await? {
+                    Some((price, fallback)) => (Some(price), fallback),
+                    None => (None, PriceFallback::Excluded),
+                };
+                fallbacks.push((coin.coin_id.clone(), fallback));
+                price
+            }
+        };
+
+        tracing::trace!(
+            "  {} ({}): weight={}, qty={}, price={:?}",
+            coin.symbol,
+            coin.coin_id,
+            weight,
+            quantity,
+            price
+        );
+        values.push((weight * quantity * rebalance_price, price.map(|price| weight * quantity * price)));
+    }
+
+    let value = price_fallback::renormalized_value(&values)?;
+    if !fallbacks.is_empty() {
+        tracing::warn!("Basket valued on {} with price fallbacks: {:?}", target_date, fallbacks);
+    }
+
+    Ok(BasketValue { value, quantities })
+}
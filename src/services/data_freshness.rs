@@ -0,0 +1,195 @@
+//! Data freshness report
+//!
+//! Aggregates sync_status records and table-level statistics per data domain so
+//! operators can spot silently dead jobs without reading logs.
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, Statement};
+
+use crate::entities::sync_status::{self, Entity as SyncStatus};
+use crate::models::data_freshness::{
+    DataFreshnessResponse, DomainFreshness, JobSyncInfo, StaleEntity,
+};
+use crate::services::sync_status::jobs;
+
+#[derive(Debug, FromQueryResult)]
+struct TableStats {
+    row_count: i64,
+    latest: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct StaleRow {
+    id: String,
+    last_date: NaiveDate,
+}
+
+/// Build the freshness report for all data domains
+pub async fn build_report(
+    db: &DatabaseConnection,
+) -> Result<DataFreshnessResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let statuses = SyncStatus::find().all(db).await?;
+    let job_info = |names: &[&str]| -> Vec<JobSyncInfo> {
+        names
+            .iter()
+            .map(|name| {
+                let record = statuses.iter().find(|s| s.job_name == *name);
+                to_job_info(name, record)
+            })
+            .collect()
+    };
+
+    let mut domains = Vec::new();
+
+    // Coins list
+    let stats = table_stats(db, "SELECT COUNT(*) AS row_count, MAX(updated_at) AS latest FROM coins").await?;
+    domains.push(DomainFreshness {
+        domain: "coins".to_string(),
+        jobs: job_info(&[jobs::ALL_COINGECKO_COINS, jobs::COINS_LOGO_SYNC]),
+        row_count: stats.row_count,
+        latest_data_at: stats.latest,
+        most_stale: None,
+    });
+
+    // Historical prices: most stale = active coin whose latest price row is oldest
+    let stats = table_stats(
+        db,
+        "SELECT COUNT(*) AS row_count, MAX(date)::timestamp AS latest FROM coins_historical_prices",
+    )
+    .await?;
+    let most_stale = stale_entity(
+        db,
+        r#"
+        SELECT p.coin_id AS id, MAX(p.date) AS last_date
+        FROM coins_historical_prices p
+        JOIN coins c ON c.coin_id = p.coin_id
+        WHERE c.active = true AND c.deactivated = false
+        GROUP BY p.coin_id
+        ORDER BY last_date ASC
+        LIMIT 1
+        "#,
+    )
+    .await?;
+    domains.push(DomainFreshness {
+        domain: "historical_prices".to_string(),
+        jobs: job_info(&[jobs::COINS_HISTORICAL_PRICES, jobs::BITGET_HISTORICAL_PRICES]),
+        row_count: stats.row_count,
+        latest_data_at: stats.latest,
+        most_stale,
+    });
+
+    // Categories and their membership
+    let stats = table_stats(
+        db,
+        "SELECT COUNT(*) AS row_count, MAX(updated_at) AS latest FROM coingecko_categories",
+    )
+    .await?;
+    let most_stale = stale_entity(
+        db,
+        r#"
+        SELECT category_id AS id, updated_at::date AS last_date
+        FROM coingecko_categories
+        WHERE updated_at IS NOT NULL
+        ORDER BY updated_at ASC
+        LIMIT 1
+        "#,
+    )
+    .await?;
+    domains.push(DomainFreshness {
+        domain: "categories".to_string(),
+        jobs: job_info(&[jobs::CATEGORY_SYNC, jobs::CATEGORY_MEMBERSHIP]),
+        row_count: stats.row_count,
+        latest_data_at: stats.latest,
+        most_stale,
+    });
+
+    // Exchange listings scraped from announcements
+    let stats = table_stats(
+        db,
+        "SELECT COUNT(*) AS row_count, MAX(updated_at) AS latest FROM crypto_listings",
+    )
+    .await?;
+    domains.push(DomainFreshness {
+        domain: "listings".to_string(),
+        jobs: job_info(&[jobs::ANNOUNCEMENT_SCRAPER]),
+        row_count: stats.row_count,
+        latest_data_at: stats.latest,
+        most_stale: None,
+    });
+
+    // Daily index prices: most stale = index whose latest daily price is oldest
+    let stats = table_stats(
+        db,
+        "SELECT COUNT(*) AS row_count, MAX(date)::timestamp AS latest FROM daily_prices",
+    )
+    .await?;
+    let most_stale = stale_entity(
+        db,
+        r#"
+        SELECT index_id AS id, MAX(date) AS last_date
+        FROM daily_prices
+        GROUP BY index_id
+        ORDER BY last_date ASC
+        LIMIT 1
+        "#,
+    )
+    .await?;
+    domains.push(DomainFreshness {
+        domain: "daily_index_prices".to_string(),
+        jobs: job_info(&[jobs::INDEX_DAILY_PRICES, jobs::REBALANCE_SYNC]),
+        row_count: stats.row_count,
+        latest_data_at: stats.latest,
+        most_stale,
+    });
+
+    Ok(DataFreshnessResponse {
+        generated_at: Utc::now().naive_utc(),
+        domains,
+    })
+}
+
+fn to_job_info(job_name: &str, record: Option<&sync_status::Model>) -> JobSyncInfo {
+    match record {
+        Some(r) => JobSyncInfo {
+            job_name: job_name.to_string(),
+            last_success_at: r.last_success_at,
+            last_attempt_at: r.last_attempt_at,
+            last_error: r.last_error.clone(),
+            error_count: r.error_count,
+        },
+        None => JobSyncInfo {
+            job_name: job_name.to_string(),
+            last_success_at: None,
+            last_attempt_at: None,
+            last_error: None,
+            error_count: 0,
+        },
+    }
+}
+
+async fn table_stats(
+    db: &DatabaseConnection,
+    sql: &str,
+) -> Result<TableStats, Box<dyn std::error::Error + Send + Sync>> {
+    let stats = TableStats::find_by_statement(Statement::from_string(db.get_database_backend(), sql))
+        .one(db)
+        .await?
+        .unwrap_or(TableStats {
+            row_count: 0,
+            latest: None,
+        });
+    Ok(stats)
+}
+
+async fn stale_entity(
+    db: &DatabaseConnection,
+    sql: &str,
+) -> Result<Option<StaleEntity>, Box<dyn std::error::Error + Send + Sync>> {
+    let row = StaleRow::find_by_statement(Statement::from_string(db.get_database_backend(), sql))
+        .one(db)
+        .await?;
+    Ok(row.map(|r| StaleEntity {
+        id: r.id,
+        last_data_date: r.last_date,
+    }))
+}
@@ -0,0 +1,175 @@
+//! Email delivery through an HTTP email API
+//!
+//! Messages are posted as JSON in the shape Resend and compatible APIs
+//! accept (`from`, `to`, `subject`, `html` and base64-encoded
+//! `attachments`), authenticated with a bearer key. Each recipient gets
+//! their own message so subscriber addresses aren't disclosed to each other.
+//!
+//! Configuration (environment):
+//! - `EMAIL_API_URL` - endpoint messages are posted to; email is off when unset
+//! - `EMAIL_API_KEY` - bearer token for it
+//! - `EMAIL_FROM` - sender address, e.g. `IndexMaker <reports@indexmaker.global>`
+
+use std::env;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
+
+const ENV_API_URL: &str = "EMAIL_API_URL";
+const ENV_API_KEY: &str = "EMAIL_API_KEY";
+const ENV_FROM: &str = "EMAIL_FROM";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum EmailError {
+    Request(reqwest::Error),
+    /// The API answered with a non-success status
+    Rejected { recipient: String, status: u16, body: String },
+}
+
+impl std::fmt::Display for EmailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmailError::Request(e) => write!(f, "Email request failed: {}", e),
+            EmailError::Rejected { recipient, status, body } => {
+                write!(f, "Email to {} rejected with {}: {}", recipient, status, body)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmailError {}
+
+impl From<reqwest::Error> for EmailError {
+    fn from(e: reqwest::Error) -> Self {
+        EmailError::Request(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub subject: String,
+    pub html: String,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub api_url: String,
+    pub api_key: Option<String>,
+    pub from: String,
+}
+
+impl EmailConfig {
+    /// None when `EMAIL_API_URL` or `EMAIL_FROM` isn't set
+    pub fn from_env() -> Option<Self> {
+        let api_url = env::var(ENV_API_URL).ok().filter(|v| !v.trim().is_empty())?;
+        let from = env::var(ENV_FROM).ok().filter(|v| !v.trim().is_empty())?;
+        Some(Self {
+            api_url,
+            api_key: env::var(ENV_API_KEY).ok().filter(|v| !v.is_empty()),
+            from,
+        })
+    }
+}
+
+/// The API payload of `email` to one recipient
+pub fn payload(from: &str, to: &str, email: &Email) -> serde_json::Value {
+    let attachments: Vec<_> = email
+        .attachments
+        .iter()
+        .map(|a| {
+            json!({
+                "filename": a.filename,
+                "content_type": a.content_type,
+                "content": STANDARD.encode(&a.content),
+            })
+        })
+        .collect();
+    json!({
+        "from": from,
+        "to": [to],
+        "subject": email.subject,
+        "html": email.html,
+        "attachments": attachments,
+    })
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    client: reqwest::Client,
+    config: EmailConfig,
+}
+
+impl Mailer {
+    pub fn new(config: EmailConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build HTTP client");
+        Self { client, config }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        EmailConfig::from_env().map(Self::new)
+    }
+
+    /// Send `email` to each recipient; returns the deliveries that failed
+    ///
+    /// Every recipient is attempted, so one bad address doesn't hold back
+    /// the others.
+    pub async fn send(&self, recipients: &[String], email: &Email) -> Vec<EmailError> {
+        let mut failures = Vec::new();
+        for to in recipients {
+            if let Err(e) = self.send_one(to, email).await {
+                failures.push(e);
+            }
+        }
+        failures
+    }
+
+    async fn send_one(&self, to: &str, email: &Email) -> Result<(), EmailError> {
+        let mut request = self.client.post(&self.config.api_url).json(&payload(&self.config.from, to, email));
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(EmailError::Rejected { recipient: to.to_string(), status: status.as_u16(), body });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_encodes_attachments() {
+        let email = Email {
+            subject: "Weekly".to_string(),
+            html: "<p>hi</p>".to_string(),
+            attachments: vec![Attachment {
+                filename: "report.csv".to_string(),
+                content_type: "text/csv".to_string(),
+                content: b"a,b\n".to_vec(),
+            }],
+        };
+        let payload = payload("reports@example.com", "alice@example.com", &email);
+        assert_eq!(payload["to"], json!(["alice@example.com"]));
+        assert_eq!(payload["attachments"][0]["filename"], "report.csv");
+        assert_eq!(payload["attachments"][0]["content"], "YSxiCg==");
+    }
+}
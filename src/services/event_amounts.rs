@@ -0,0 +1,109 @@
+//! Amounts of blockchain_events rows
+//!
+//! Events ingested with their raw on-chain values (`raw_amount`/`decimals`,
+//! `raw_quantity`/`quantity_decimals`) are read from those, exactly. Older
+//! rows only have the pre-converted `amount`/`quantity` Decimals, which are
+//! used as a fallback.
+
+use alloy::primitives::U256;
+use rust_decimal::Decimal;
+
+use crate::entities::blockchain_events;
+use crate::services::units::{self, UnitsError};
+
+/// Amount (collateral side) in base units of a `decimals`-decimal token
+pub fn amount_base_units(event: &blockchain_events::Model, decimals: u32) -> Result<U256, UnitsError> {
+    base_units(event.raw_amount.as_deref(), event.decimals, event.amount, decimals)
+}
+
+/// Quantity (index side) in base units of a `decimals`-decimal token
+pub fn quantity_base_units(event: &blockchain_events::Model, decimals: u32) -> Result<U256, UnitsError> {
+    base_units(event.raw_quantity.as_deref(), event.quantity_decimals, event.quantity, decimals)
+}
+
+/// Human-readable amount, derived from the raw value when present
+pub fn amount(event: &blockchain_events::Model) -> Option<Decimal> {
+    display(event.raw_amount.as_deref(), event.decimals, event.amount)
+}
+
+/// Human-readable quantity, derived from the raw value when present
+pub fn quantity(event: &blockchain_events::Model) -> Option<Decimal> {
+    display(event.raw_quantity.as_deref(), event.quantity_decimals, event.quantity)
+}
+
+fn base_units(
+    raw: Option<&str>,
+    raw_decimals: Option<i32>,
+    fallback: Option<Decimal>,
+    decimals: u32,
+) -> Result<U256, UnitsError> {
+    match (raw, raw_decimals) {
+        (Some(raw), Some(raw_decimals)) => {
+            units::rescale(units::parse_raw(raw)?, raw_decimals as u32, decimals)
+        }
+        _ => units::to_base_units(fallback.unwrap_or(Decimal::ZERO), decimals),
+    }
+}
+
+fn display(raw: Option<&str>, raw_decimals: Option<i32>, fallback: Option<Decimal>) -> Option<Decimal> {
+    match (raw, raw_decimals) {
+        (Some(raw), Some(raw_decimals)) => {
+            match units::parse_raw(raw).and_then(|r| units::from_base_units(r, raw_decimals as u32)) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Falling back to stored value for raw amount {}: {}", raw, e);
+                    fallback
+                }
+            }
+        }
+        _ => fallback,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn event(amount: Option<Decimal>, raw_amount: Option<&str>, decimals: Option<i32>) -> blockchain_events::Model {
+        blockchain_events::Model {
+            id: 1,
+            tx_hash: "0xabc".to_string(),
+            block_number: 1,
+            log_index: 0,
+            event_type: "mint".to_string(),
+            contract_address: "0xindex".to_string(),
+            network: "base".to_string(),
+            user_address: None,
+            amount,
+            quantity: None,
+            timestamp: None,
+            raw_amount: raw_amount.map(str::to_string),
+            decimals,
+            raw_quantity: None,
+            quantity_decimals: None,
+        }
+    }
+
+    #[test]
+    fn test_raw_amount_wins_over_stored_decimal() {
+        // Stored Decimal was converted with the wrong decimals
+        let ev = event(Some(dec!(1500)), Some("1500000"), Some(6));
+        assert_eq!(amount(&ev), Some(dec!(1.5)));
+        assert_eq!(amount_base_units(&ev, 6).unwrap(), U256::from(1_500_000u64));
+    }
+
+    #[test]
+    fn test_falls_back_to_stored_decimal() {
+        let ev = event(Some(dec!(2.5)), None, None);
+        assert_eq!(amount(&ev), Some(dec!(2.5)));
+        assert_eq!(amount_base_units(&ev, 6).unwrap(), U256::from(2_500_000u64));
+        assert_eq!(quantity_base_units(&ev, 30).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_raw_is_rescaled_to_requested_decimals() {
+        let ev = event(None, Some("2500000000000000000"), Some(18));
+        assert_eq!(amount_base_units(&ev, 30).unwrap(), units::to_base_units(dec!(2.5), 30).unwrap());
+    }
+}
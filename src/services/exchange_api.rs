@@ -1,10 +1,18 @@
 use reqwest::Client;
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::time::{Duration, SystemTime};
 
+/// Exchanges whose pairs are tracked
+pub const SUPPORTED_EXCHANGES: [&str; 2] = ["binance", "bitget"];
+
+/// Quote assets whose pairs are tracked
+pub const QUOTE_ASSETS: [&str; 2] = ["USDC", "USDT"];
+
 /// Tradeable token information from exchanges
 #[derive(Debug, Clone)]
 pub struct TradeableToken {
@@ -15,6 +23,28 @@ pub struct TradeableToken {
     pub priority: u8, // Lower = higher priority (1=Binance USDC, 4=Bitget USDT)
 }
 
+/// Order book snapshot of one trading pair
+#[derive(Debug, Clone)]
+pub struct OrderBookDepth {
+    pub exchange: String,
+    pub trading_pair: String, // e.g., "BTCUSDC"
+    pub bids: Vec<(f64, f64)>, // (price, quantity), best first
+    pub asks: Vec<(f64, f64)>,
+}
+
+/// Rolling 24h traded volume of one trading pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairVolume {
+    pub exchange: String,
+    pub trading_pair: String, // e.g., "BTCUSDC"
+    pub base_asset: String,
+    pub quote_asset: String,
+    /// Base asset traded
+    pub base_volume: Decimal,
+    /// Quote asset traded; USD for the stablecoin QUOTE_ASSETS
+    pub quote_volume: Decimal,
+}
+
 /// Exchange API service for checking real-time tradeability
 #[derive(Clone)]
 pub struct ExchangeApiService {
@@ -80,6 +110,44 @@ struct BitgetSymbol {
     status: String,
 }
 
+// Order book responses (Binance /api/v3/depth, Bitget /api/v2/spot/market/orderbook)
+#[derive(Debug, Deserialize)]
+struct BinanceDepth {
+    bids: Vec<Vec<String>>,
+    asks: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitgetDepthResponse {
+    code: String,
+    msg: String,
+    data: Option<BinanceDepth>,
+}
+
+// 24h ticker responses (Binance /api/v3/ticker/24hr, Bitget /api/v2/spot/market/tickers)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTicker {
+    symbol: String,
+    volume: String,
+    quote_volume: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitgetTicker {
+    symbol: String,
+    base_volume: String,
+    quote_volume: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitgetTickersResponse {
+    code: String,
+    msg: String,
+    data: Option<Vec<BitgetTicker>>,
+}
+
 impl ExchangeApiService {
     pub fn new(cache_ttl_secs: u64) -> Self {
         Self {
@@ -182,6 +250,31 @@ impl ExchangeApiService {
         }
     }
 
+    /// Quote assets (of QUOTE_ASSETS) `symbol` is currently trading against on `exchange`
+    pub async fn get_live_quote_assets(
+        &self,
+        exchange: &str,
+        symbol: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        // Refresh cache if expired
+        {
+            let cache = self.cache.read().await;
+            if cache.is_expired(self.cache_ttl_secs) {
+                drop(cache);
+                self.refresh_cache().await?;
+            }
+        }
+
+        let cache = self.cache.read().await;
+        let pairs = match exchange.to_lowercase().as_str() {
+            "binance" => &cache.binance_pairs,
+            "bitget" => &cache.bitget_pairs,
+            _ => return Err(format!("Unsupported exchange: {}", exchange).into()),
+        };
+
+        Ok(pairs.get(&symbol.to_uppercase()).cloned().unwrap_or_default())
+    }
+
     /// Get tradeable tokens from exchanges for given symbols
     /// Returns tokens prioritized by: Binance USDC > Binance USDT > Bitget USDC > Bitget USDT
     pub async fn get_tradeable_tokens(
@@ -416,6 +509,88 @@ impl ExchangeApiService {
         self.refresh_cache().await
     }
 
+    /// Fetch the top `limit` order book levels of a pair
+    pub async fn get_order_book(
+        &self,
+        exchange: &str,
+        symbol: &str,
+        quote_asset: &str,
+        limit: usize,
+    ) -> Result<OrderBookDepth, Box<dyn std::error::Error + Send + Sync>> {
+        let trading_pair = format!("{}{}", symbol.to_uppercase(), quote_asset.to_uppercase());
+
+        let depth = match exchange.to_lowercase().as_str() {
+            "binance" => {
+                let url = format!(
+                    "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
+                    trading_pair,
+                    limit.min(5000) // Binance max is 5000
+                );
+                self.fetch_with_retry(&url, 3).await?.json::<BinanceDepth>().await?
+            }
+            "bitget" => {
+                let url = format!(
+                    "https://api.bitget.com/api/v2/spot/market/orderbook?symbol={}&limit={}",
+                    trading_pair,
+                    limit.min(150) // Bitget max is 150
+                );
+                let response: BitgetDepthResponse = self.fetch_with_retry(&url, 3).await?.json().await?;
+                if response.code != "00000" {
+                    return Err(format!("Bitget API error: {}", response.msg).into());
+                }
+                response.data.ok_or("No orderbook data")?
+            }
+            _ => return Err(format!("Unsupported exchange: {}", exchange).into()),
+        };
+
+        Ok(OrderBookDepth {
+            exchange: exchange.to_lowercase(),
+            trading_pair,
+            bids: parse_levels(&depth.bids),
+            asks: parse_levels(&depth.asks),
+        })
+    }
+
+    /// Fetch the 24h volume of every pair of `exchange` quoted in one of
+    /// QUOTE_ASSETS, in a single ticker request
+    pub async fn get_24h_volumes(
+        &self,
+        exchange: &str,
+    ) -> Result<Vec<PairVolume>, Box<dyn std::error::Error + Send + Sync>> {
+        let exchange = exchange.to_lowercase();
+        let tickers: Vec<(String, String, String)> = match exchange.as_str() {
+            "binance" => {
+                let url = "https://api.binance.com/api/v3/ticker/24hr";
+                self.fetch_with_retry(url, 3)
+                    .await?
+                    .json::<Vec<BinanceTicker>>()
+                    .await?
+                    .into_iter()
+                    .map(|t| (t.symbol, t.volume, t.quote_volume))
+                    .collect()
+            }
+            "bitget" => {
+                let url = "https://api.bitget.com/api/v2/spot/market/tickers";
+                let response: BitgetTickersResponse = self.fetch_with_retry(url, 3).await?.json().await?;
+                if response.code != "00000" {
+                    return Err(format!("Bitget API error: {}", response.msg).into());
+                }
+                response
+                    .data
+                    .ok_or("No ticker data")?
+                    .into_iter()
+                    .map(|t| (t.symbol, t.base_volume, t.quote_volume))
+                    .collect()
+            }
+            _ => return Err(format!("Unsupported exchange: {}", exchange).into()),
+        };
+
+        Ok(tickers
+            .into_iter()
+            .filter_map(|(symbol, base, quote)| parse_pair_volume(&exchange, &symbol, &base, &quote))
+            .collect())
+    }
+
     /// Get all tradeable symbols from Bitget only
     /// Returns symbols with their trading pair (USDC preferred)
     pub async fn get_all_tradeable_symbols(
@@ -464,6 +639,37 @@ impl ExchangeApiService {
     }
 }
 
+/// Parse ["price", "quantity", ...] levels, skipping malformed ones
+fn parse_levels(levels: &[Vec<String>]) -> Vec<(f64, f64)> {
+    levels
+        .iter()
+        .filter_map(|level| {
+            let price = level.first()?.parse::<f64>().ok()?;
+            let qty = level.get(1)?.parse::<f64>().ok()?;
+            Some((price, qty))
+        })
+        .collect()
+}
+
+/// A ticker's volume if its pair is quoted in one of QUOTE_ASSETS and both
+/// volumes parse
+fn parse_pair_volume(exchange: &str, symbol: &str, base_volume: &str, quote_volume: &str) -> Option<PairVolume> {
+    let trading_pair = symbol.to_uppercase();
+    let quote_asset = QUOTE_ASSETS.iter().find(|quote| trading_pair.ends_with(*quote))?;
+    let base_asset = trading_pair.strip_suffix(quote_asset)?;
+    if base_asset.is_empty() {
+        return None;
+    }
+    Some(PairVolume {
+        exchange: exchange.to_string(),
+        base_asset: base_asset.to_string(),
+        quote_asset: quote_asset.to_string(),
+        base_volume: Decimal::from_str(base_volume).ok()?,
+        quote_volume: Decimal::from_str(quote_volume).ok()?,
+        trading_pair,
+    })
+}
+
 /// Parse trading pair like "BTCUSDC" into ("BTC", "USDC")
 fn parse_trading_pair(
     trading_pair: &str,
@@ -504,4 +710,26 @@ mod tests {
         // Should find BTC, ETH, SOL but not INVALID_TOKEN_XYZ
         assert!(tradeable.len() >= 3);
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_parse_levels() {
+        let levels = vec![
+            vec!["100.5".to_string(), "2".to_string()],
+            vec!["bad".to_string(), "1".to_string()],
+            vec!["99".to_string()],
+            vec!["98".to_string(), "0.25".to_string(), "1700000000".to_string()],
+        ];
+        assert_eq!(parse_levels(&levels), vec![(100.5, 2.0), (98.0, 0.25)]);
+    }
+
+    #[test]
+    fn test_parse_pair_volume() {
+        let volume = parse_pair_volume("binance", "BTCUSDC", "12.5", "1250000.75").unwrap();
+        assert_eq!((volume.base_asset.as_str(), volume.quote_asset.as_str()), ("BTC", "USDC"));
+        assert_eq!(volume.trading_pair, "BTCUSDC");
+        assert_eq!(volume.quote_volume, Decimal::from_str("1250000.75").unwrap());
+
+        assert!(parse_pair_volume("binance", "ETHBTC", "1", "1").is_none());
+        assert!(parse_pair_volume("binance", "USDT", "1", "1").is_none());
+        assert!(parse_pair_volume("bitget", "SOLUSDT", "1", "n/a").is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! Runtime feature flags and maintenance mode
+//!
+//! Operators can switch off expensive features without a deploy: on-the-fly
+//! CoinGecko price fetches, live exchange checks and ITP creation, or put the
+//! whole API into read-only maintenance mode (see handlers::maintenance).
+//! Overrides live in the feature_flags table; every instance keeps them in
+//! memory and reloads them periodically, so handlers check a flag without a
+//! database round trip. A flag without an override keeps its default.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use sea_orm::{sea_query::OnConflict, DatabaseConnection, EntityTrait, Set};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::entities::{feature_flags, prelude::*};
+
+/// Flag keys
+pub mod flags {
+    /// Reject writes outside /admin with 503
+    pub const MAINTENANCE_MODE: &str = "maintenance_mode";
+    /// Fetch prices missing from the database from CoinGecko while serving a request
+    pub const COINGECKO_ON_THE_FLY: &str = "coingecko_on_the_fly";
+    /// Query Binance and Bitget while serving a request
+    pub const LIVE_EXCHANGE_CHECKS: &str = "live_exchange_checks";
+    /// Accept ITP creation requests
+    pub const ITP_CREATION: &str = "itp_creation";
+}
+
+/// A known flag and its default
+#[derive(Debug, Clone, Copy)]
+pub struct FlagDefinition {
+    pub key: &'static str,
+    pub default: bool,
+    pub description: &'static str,
+}
+
+pub const FLAGS: [FlagDefinition; 4] = [
+    FlagDefinition {
+        key: flags::MAINTENANCE_MODE,
+        default: false,
+        description: "Read-only maintenance mode: writes outside /admin return 503",
+    },
+    FlagDefinition {
+        key: flags::COINGECKO_ON_THE_FLY,
+        default: true,
+        description: "Fetch prices missing from the database from CoinGecko during requests",
+    },
+    FlagDefinition {
+        key: flags::LIVE_EXCHANGE_CHECKS,
+        default: true,
+        description: "Query Binance and Bitget during requests (tradeable pairs, quotes, ITP validation)",
+    },
+    FlagDefinition {
+        key: flags::ITP_CREATION,
+        default: true,
+        description: "Accept ITP creation requests",
+    },
+];
+
+pub fn definition(key: &str) -> Option<&'static FlagDefinition> {
+    FLAGS.iter().find(|flag| flag.key == key)
+}
+
+/// A flag's effective state
+#[derive(Debug, Clone)]
+pub struct FlagState {
+    pub definition: &'static FlagDefinition,
+    pub enabled: bool,
+    /// When the override was last changed; None if the flag has its default
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug)]
+pub enum FeatureFlagError {
+    UnknownFlag(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for FeatureFlagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureFlagError::UnknownFlag(key) => write!(f, "Unknown feature flag '{}'", key),
+            FeatureFlagError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FeatureFlagError {}
+
+impl From<sea_orm::DbErr> for FeatureFlagError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        FeatureFlagError::Database(e)
+    }
+}
+
+/// In-memory copy of the flag overrides
+#[derive(Clone)]
+pub struct FeatureFlagService {
+    overrides: Arc<RwLock<HashMap<String, feature_flags::Model>>>,
+    refresh_interval_secs: u64,
+}
+
+impl FeatureFlagService {
+    pub fn new(refresh_interval_secs: u64) -> Self {
+        Self {
+            overrides: Arc::new(RwLock::new(HashMap::new())),
+            refresh_interval_secs,
+        }
+    }
+
+    /// Start the background task reloading overrides from the database
+    pub fn start_polling(&self, db: DatabaseConnection) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            info!("Starting feature flag refresh (every {} seconds)", service.refresh_interval_secs);
+            let mut interval = tokio::time::interval(Duration::from_secs(service.refresh_interval_secs));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = service.refresh(&db).await {
+                    error!("Feature flag refresh failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Reload overrides from the database
+    pub async fn refresh(&self, db: &DatabaseConnection) -> Result<(), sea_orm::DbErr> {
+        let overrides = FeatureFlags::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|flag| (flag.key.clone(), flag))
+            .collect();
+        *self.overrides.write().await = overrides;
+        Ok(())
+    }
+
+    /// Whether `key` is on; unknown flags are off
+    pub async fn is_enabled(&self, key: &str) -> bool {
+        match self.overrides.read().await.get(key) {
+            Some(flag) => flag.enabled,
+            None => definition(key).is_some_and(|flag| flag.default),
+        }
+    }
+
+    /// Every known flag with its effective state
+    pub async fn list(&self) -> Vec<FlagState> {
+        let overrides = self.overrides.read().await;
+        FLAGS
+            .iter()
+            .map(|definition| {
+                let flag = overrides.get(definition.key);
+                FlagState {
+                    definition,
+                    enabled: flag.map_or(definition.default, |flag| flag.enabled),
+                    updated_at: flag.map(|flag| flag.updated_at),
+                }
+            })
+            .collect()
+    }
+
+    /// Override a flag; takes effect here at once and on other instances at their next refresh
+    pub async fn set(&self, db: &DatabaseConnection, key: &str, enabled: bool) -> Result<FlagState, FeatureFlagError> {
+        let definition = definition(key).ok_or_else(|| FeatureFlagError::UnknownFlag(key.to_string()))?;
+
+        let flag = feature_flags::ActiveModel {
+            key: Set(definition.key.to_string()),
+            enabled: Set(enabled),
+            updated_at: Set(chrono::Utc::now().naive_utc()),
+        };
+        FeatureFlags::insert(flag)
+            .on_conflict(
+                OnConflict::column(feature_flags::Column::Key)
+                    .update_columns([feature_flags::Column::Enabled, feature_flags::Column::UpdatedAt])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+
+        let flag = FeatureFlags::find_by_id(definition.key)
+            .one(db)
+            .await?
+            .ok_or_else(|| sea_orm::DbErr::RecordNotFound(format!("feature flag {}", key)))?;
+        let state = FlagState { definition, enabled: flag.enabled, updated_at: Some(flag.updated_at) };
+        self.overrides.write().await.insert(flag.key.clone(), flag);
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_defaults_apply_without_overrides() {
+        let service = FeatureFlagService::new(30);
+        assert!(!service.is_enabled(flags::MAINTENANCE_MODE).await);
+        assert!(service.is_enabled(flags::ITP_CREATION).await);
+        assert!(!service.is_enabled("no_such_flag").await);
+
+        let listed = service.list().await;
+        assert_eq!(listed.len(), FLAGS.len());
+        assert!(listed.iter().all(|flag| flag.enabled == flag.definition.default && flag.updated_at.is_none()));
+    }
+}
@@ -0,0 +1,348 @@
+//! Explicit index inception
+//!
+//! An index starts with a rebalance and a daily price on its inception date
+//! (index_metadata.initial_date), the daily price being the rebalance's
+//! portfolio value. Normally the historical backfill writes both; for
+//! indexes launched from known data, `seed` writes them from what the caller
+//! supplies instead, and `check` reports whether the rows that exist are a
+//! consistent inception. The inception date shown by GET /indexes is
+//! initial_date, falling back to the earliest daily price for indexes
+//! created without one.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use crate::entities::{coins, daily_prices, index_metadata, prelude::*, rebalances};
+use crate::models::index::{InceptionReport, RebalanceCoin};
+use crate::services::price_utils;
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+
+pub const REBALANCE_TYPE: &str = "inception";
+
+/// Allowed distance of the weights' sum from 1
+const WEIGHT_TOLERANCE: Decimal = dec!(0.01);
+
+#[derive(Debug)]
+pub enum InceptionError {
+    IndexNotFound(i32),
+    Invalid(String),
+    /// The index already has history the seed would contradict
+    Conflict(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for InceptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InceptionError::IndexNotFound(index_id) => write!(f, "Index {} not found", index_id),
+            InceptionError::Invalid(msg) | InceptionError::Conflict(msg) => write!(f, "{}", msg),
+            InceptionError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for InceptionError {}
+
+impl From<sea_orm::DbErr> for InceptionError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        InceptionError::Database(e)
+    }
+}
+
+/// Inception data supplied by the caller
+#[derive(Debug, Clone)]
+pub struct InceptionSeed {
+    pub date: NaiveDate,
+    pub price: Decimal,
+    pub coins: Vec<RebalanceCoin>,
+}
+
+/// Constituents, their weight sum and their quantities by coin_id
+type Constituents = (Vec<CoinRebalanceInfo>, Decimal, HashMap<String, Decimal>);
+
+/// Constituents as stored in a rebalance, with the sum of their weights
+/// and their quantities by coin_id; checks weights and quantities parse and
+/// the weights sum to 1
+pub fn constituents(
+    coins: &[RebalanceCoin],
+) -> Result<Constituents, InceptionError> {
+    if coins.is_empty() {
+        return Err(InceptionError::Invalid("At least one constituent is required".to_string()));
+    }
+
+    let mut seen = HashSet::new();
+    let mut total_weight = Decimal::ZERO;
+    let mut infos = Vec::with_capacity(coins.len());
+    let mut quantities = HashMap::with_capacity(coins.len());
+    for coin in coins {
+        if !seen.insert(coin.coin_id.as_str()) {
+            return Err(InceptionError::Invalid(format!("Duplicate constituent {}", coin.coin_id)));
+        }
+        let weight: Decimal = coin.weight.parse().ok().filter(|w: &Decimal| *w > Decimal::ZERO).ok_or_else(|| {
+            InceptionError::Invalid(format!("Invalid weight '{}' for {}", coin.weight, coin.coin_id))
+        })?;
+        let quantity: Decimal = coin.quantity.parse().ok().filter(|q: &Decimal| *q >= Decimal::ZERO).ok_or_else(|| {
+            InceptionError::Invalid(format!("Invalid quantity '{}' for {}", coin.quantity, coin.coin_id))
+        })?;
+        let price = price_utils::decimal_from_f64(coin.price).filter(|p| *p > Decimal::ZERO).ok_or_else(|| {
+            InceptionError::Invalid(format!("Invalid price {} for {}", coin.price, coin.coin_id))
+        })?;
+        total_weight += weight;
+        quantities.insert(coin.coin_id.clone(), quantity);
+        infos.push(CoinRebalanceInfo {
+            coin_id: coin.coin_id.clone(),
+            symbol: coin.symbol.clone(),
+            quantity: quantity.normalize().to_string(),
+            weight: weight.normalize().to_string(),
+            price,
+            exchange: coin.exchange.clone(),
+            trading_pair: coin.trading_pair.clone(),
+        });
+    }
+
+    if (total_weight - Decimal::ONE).abs() > WEIGHT_TOLERANCE {
+        return Err(InceptionError::Invalid(format!(
+            "Weights must sum to 1 (got {})",
+            total_weight.normalize()
+        )));
+    }
+    Ok((infos, total_weight, quantities))
+}
+
+async fn find_index(db: &DatabaseConnection, index_id: i32) -> Result<index_metadata::Model, InceptionError> {
+    IndexMetadata::find_by_id(index_id)
+        .one(db)
+        .await?
+        .ok_or(InceptionError::IndexNotFound(index_id))
+}
+
+async fn first_rebalance(db: &DatabaseConnection, index_id: i32) -> Result<Option<rebalances::Model>, sea_orm::DbErr> {
+    Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(index_id))
+        .order_by_asc(rebalances::Column::Timestamp)
+        .one(db)
+        .await
+}
+
+async fn first_daily_price(db: &DatabaseConnection, index_id: i32) -> Result<Option<daily_prices::Model>, sea_orm::DbErr> {
+    DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+        .order_by_asc(daily_prices::Column::Date)
+        .one(db)
+        .await
+}
+
+/// Inception date of an index: its initial_date, else its earliest daily price
+pub async fn inception_date(
+    db: &DatabaseConnection,
+    index: &index_metadata::Model,
+) -> Result<Option<NaiveDate>, sea_orm::DbErr> {
+    if index.initial_date.is_some() {
+        return Ok(index.initial_date);
+    }
+    Ok(first_daily_price(db, index.index_id).await?.map(|row| row.date))
+}
+
+/// Write the inception rebalance and daily price of an index from `seed`,
+/// and set its initial date and price to them
+pub async fn seed(
+    db: &DatabaseConnection,
+    index_id: i32,
+    seed: InceptionSeed,
+    now: NaiveDateTime,
+) -> Result<InceptionReport, InceptionError> {
+    let index = find_index(db, index_id).await?;
+    if seed.price <= Decimal::ZERO {
+        return Err(InceptionError::Invalid(format!("Invalid inception price {}", seed.price)));
+    }
+    if seed.date > pricing_time::pricing_date(now.and_utc()) {
+        return Err(InceptionError::Invalid(format!("Inception date {} is in the future", seed.date)));
+    }
+    let (infos, total_weight, quantities) = constituents(&seed.coins)?;
+
+    let coin_ids: Vec<String> = infos.iter().map(|coin| coin.coin_id.clone()).collect();
+    let known: HashSet<String> = Coins::find()
+        .filter(coins::Column::CoinId.is_in(coin_ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|coin| coin.coin_id)
+        .collect();
+    let unknown: Vec<String> = coin_ids.into_iter().filter(|id| !known.contains(id)).collect();
+    if !unknown.is_empty() {
+        return Err(InceptionError::Invalid(format!("Unknown coin ids: {}", unknown.join(", "))));
+    }
+
+    // Anything on or before the inception date would contradict it
+    if let Some(rebalance) = first_rebalance(db, index_id).await?
+        && rebalance.timestamp <= pricing_time::day_end_timestamp(seed.date)
+    {
+        return Err(InceptionError::Conflict(format!(
+            "Index {} already has a rebalance on {}",
+            index_id,
+            pricing_time::rebalance_date(rebalance.timestamp).unwrap_or_default()
+        )));
+    }
+    if let Some(price) = first_daily_price(db, index_id).await?
+        && price.date <= seed.date
+    {
+        return Err(InceptionError::Conflict(format!(
+            "Index {} already has a daily price on {}",
+            index_id, price.date
+        )));
+    }
+
+    let quantities: HashMap<&str, f64> = quantities
+        .iter()
+        .map(|(coin_id, quantity)| {
+            let quantity = quantity.to_f64().ok_or_else(|| {
+                InceptionError::Invalid(format!("Quantity {} of {} is out of range", quantity, coin_id))
+            })?;
+            Ok((coin_id.as_str(), quantity))
+        })
+        .collect::<Result<_, InceptionError>>()?;
+    let coins_json = rebalance_schema::encode(&infos).map_err(|e| InceptionError::Invalid(e.to_string()))?;
+    let quantities_json = serde_json::to_value(&quantities).map_err(|e| InceptionError::Invalid(e.to_string()))?;
+
+    let txn = db.begin().await?;
+    let rebalance = rebalances::ActiveModel {
+        index_id: Set(index_id),
+        coins: Set(coins_json),
+        portfolio_value: Set(seed.price),
+        total_weight: Set(total_weight),
+        timestamp: Set(pricing_time::rebalance_timestamp(seed.date)),
+        rebalance_type: Set(REBALANCE_TYPE.to_string()),
+        deployed: Set(Some(false)),
+        created_at: Set(Some(now)),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    daily_prices::ActiveModel {
+        index_id: Set(index_id.to_string()),
+        date: Set(seed.date),
+        price: Set(seed.price),
+        quantities: Set(Some(quantities_json)),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+    }
+    .insert(&txn)
+    .await?;
+    let mut index: index_metadata::ActiveModel = index.into();
+    index.initial_date = Set(Some(seed.date));
+    index.initial_price = Set(Some(seed.price));
+    index.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(InceptionReport {
+        index_id,
+        date: Some(seed.date),
+        price: Some(seed.price),
+        rebalance_id: Some(rebalance.id),
+        seeded: true,
+        valid: true,
+        issues: Vec::new(),
+    })
+}
+
+/// Whether the inception rows of an index exist and agree with each other
+/// and with its initial date (the price may be below its initial price, net
+/// of the first rebalance's fees)
+pub async fn check(db: &DatabaseConnection, index_id: i32) -> Result<InceptionReport, InceptionError> {
+    let index = find_index(db, index_id).await?;
+    let rebalance = first_rebalance(db, index_id).await?;
+    let price = first_daily_price(db, index_id).await?;
+    let mut issues = Vec::new();
+
+    let date = match index.initial_date {
+        Some(date) => Some(date),
+        None => {
+            issues.push("Index has no initial date".to_string());
+            price.as_ref().map(|row| row.date)
+        }
+    };
+
+    match (&rebalance, date) {
+        (None, _) => issues.push("Index has no rebalance".to_string()),
+        (Some(rebalance), Some(date)) => {
+            let rebalance_date = pricing_time::rebalance_date(rebalance.timestamp);
+            if rebalance_date != Some(date) {
+                issues.push(format!(
+                    "First rebalance is on {}, not on the inception date {}",
+                    rebalance_date.map_or("an invalid date".to_string(), |d| d.to_string()),
+                    date
+                ));
+            }
+        }
+        (Some(_), None) => {}
+    }
+
+    match (&price, date) {
+        (None, _) => issues.push("Index has no daily price".to_string()),
+        (Some(price), Some(date)) if price.date != date => issues.push(format!(
+            "First daily price is on {}, not on the inception date {}",
+            price.date, date
+        )),
+        (Some(price), _) => {
+            if let Some(rebalance) = &rebalance
+                && rebalance.portfolio_value != price.price
+            {
+                issues.push(format!(
+                    "Inception price {} differs from the first rebalance's portfolio value {}",
+                    price.price.normalize(),
+                    rebalance.portfolio_value.normalize()
+                ));
+            }
+        }
+    }
+
+    Ok(InceptionReport {
+        index_id,
+        date,
+        price: price.as_ref().map(|row| row.price),
+        rebalance_id: rebalance.as_ref().map(|rebalance| rebalance.id),
+        seeded: false,
+        valid: issues.is_empty(),
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(coin_id: &str, weight: &str, quantity: &str) -> RebalanceCoin {
+        RebalanceCoin {
+            coin_id: coin_id.to_string(),
+            symbol: coin_id.to_uppercase(),
+            weight: weight.to_string(),
+            quantity: quantity.to_string(),
+            price: 100.0,
+            exchange: "binance".to_string(),
+            trading_pair: "usdt".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_constituents() {
+        let (infos, total, quantities) = constituents(&[coin("btc", "0.60", "6.0"), coin("eth", "0.4", "4")]).unwrap();
+        assert_eq!(total, Decimal::ONE);
+        assert_eq!((infos[0].weight.as_str(), infos[0].quantity.as_str()), ("0.6", "6"));
+        assert_eq!(quantities, HashMap::from([("btc".to_string(), dec!(6)), ("eth".to_string(), dec!(4))]));
+
+        assert!(constituents(&[]).is_err());
+        assert!(constituents(&[coin("btc", "0.5", "1")]).is_err());
+        assert!(constituents(&[coin("btc", "0.5", "1"), coin("btc", "0.5", "1")]).is_err());
+        assert!(constituents(&[coin("btc", "1", "lots")]).is_err());
+        assert!(constituents(&[coin("btc", "-1", "1"), coin("eth", "2", "1")]).is_err());
+    }
+}
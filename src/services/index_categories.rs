@@ -0,0 +1,78 @@
+//! Index directory: indexes grouped by category and asset class
+//!
+//! One grouped query over index_metadata counts each group's indexes, sums
+//! their latest index_tvl row and averages their year-to-date return, from
+//! the January 1st daily price to the latest one up to `as_of`. Indexes
+//! without a January 1st price (launched later in the year) count towards
+//! the group but not its average return.
+
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ConnectionTrait, DatabaseConnection, FromQueryResult, Statement};
+
+use crate::models::index_category::{IndexCategoriesResponse, IndexCategoryEntry};
+
+#[derive(Debug, FromQueryResult)]
+struct CategoryRow {
+    category: Option<String>,
+    asset_class: Option<String>,
+    index_count: i64,
+    total_tvl_usd: Decimal,
+    avg_ytd_return: Option<Decimal>,
+}
+
+/// Categories of all indexes, with returns up to `as_of`
+pub async fn index_categories(
+    db: &DatabaseConnection,
+    as_of: NaiveDate,
+) -> Result<IndexCategoriesResponse, sea_orm::DbErr> {
+    let jan1 = NaiveDate::from_ymd_opt(as_of.year(), 1, 1).unwrap_or(as_of);
+    let rows = CategoryRow::find_by_statement(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+        WITH latest_tvl AS (
+            SELECT DISTINCT ON (index_id) index_id, tvl_usd
+            FROM index_tvl
+            ORDER BY index_id, date DESC
+        ),
+        ytd AS (
+            SELECT start.index_id, (latest.price - start.price) / start.price * 100 AS ytd_return
+            FROM daily_prices start
+            JOIN LATERAL (
+                SELECT price
+                FROM daily_prices
+                WHERE index_id = start.index_id AND date <= $2
+                ORDER BY date DESC
+                LIMIT 1
+            ) latest ON TRUE
+            WHERE start.date = $1 AND start.price <> 0
+        )
+        SELECT im.category,
+               im.asset_class,
+               COUNT(*) AS index_count,
+               ROUND(COALESCE(SUM(latest_tvl.tvl_usd), 0), 2) AS total_tvl_usd,
+               ROUND(AVG(ytd.ytd_return), 4) AS avg_ytd_return
+        FROM index_metadata im
+        LEFT JOIN latest_tvl ON latest_tvl.index_id = im.index_id
+        LEFT JOIN ytd ON ytd.index_id = im.index_id::text
+        GROUP BY im.category, im.asset_class
+        ORDER BY im.category NULLS LAST, im.asset_class NULLS LAST
+        "#,
+        [jan1.into(), as_of.into()],
+    ))
+    .all(db)
+    .await?;
+
+    let categories = rows
+        .into_iter()
+        .map(|row| IndexCategoryEntry {
+            category: row.category,
+            asset_class: row.asset_class,
+            index_count: row.index_count,
+            total_tvl_usd: row.total_tvl_usd.to_f64().unwrap_or_default(),
+            avg_ytd_return: row.avg_ytd_return.and_then(|ytd| ytd.to_f64()),
+        })
+        .collect();
+
+    Ok(IndexCategoriesResponse { as_of, categories })
+}
@@ -0,0 +1,314 @@
+//! Index composition constraints
+//!
+//! An index may limit its composition through index_metadata.constraints
+//! (models::index::IndexConstraints), e.g.
+//! `{"maxCategoryWeight": {"meme-token": 20}, "maxConstituents": 25, "minConstituents": 5}`.
+//! Every rebalance enforces them with deterministic trimming rules:
+//!
+//! 1. Over maxConstituents, the selection keeps the coins with the largest
+//!    market cap on the rebalance date. Coins without one go last and ties
+//!    are broken by coin_id.
+//! 2. A category whose members weigh more than its cap (in percent of the
+//!    total weight) has them scaled down to the cap. The excess is spread over
+//!    the coins outside every category capped so far, in proportion to their
+//!    weights, so the total weight doesn't change. The most-over category
+//!    (ties by category id) goes first and the rounds repeat until no category
+//!    is over. A capped category's members only ever shrink after that, so it
+//!    stays within its cap and this ends within one round per category.
+//! 3. Too few constituents can't be fixed by trimming and are only reported.
+//!
+//! `minPairVolumeUsd` isn't a trimming rule: the market cap and category
+//! selectors skip candidates below it (see services::pair_volumes), so the
+//! next eligible coin takes their place.
+//!
+//! Category membership is the coin's active CoinGecko categories
+//! (category_membership), matched case-insensitively like the blacklist.
+//! What a rebalance had to trim or couldn't satisfy is stored in
+//! rebalances.constraint_violations.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{category_membership, index_metadata, prelude::*};
+use crate::models::index::IndexConstraints;
+use crate::services::constituent_selector::ConstituentToken;
+
+/// A constraint the selected composition broke
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "constraint", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ConstraintViolation {
+    /// More coins were selected than allowed; `removed` were dropped
+    MaxConstituents { limit: usize, selected: usize, removed: Vec<String> },
+    /// Fewer coins were selected than required
+    MinConstituents { limit: usize, selected: usize },
+    /// A category weighed `weight_pct` of the index; `trimmed` is false when
+    /// there was no coin outside the capped categories to take the excess
+    MaxCategoryWeight { category: String, limit_pct: Decimal, weight_pct: Decimal, trimmed: bool },
+}
+
+/// Constraints of an index; unreadable ones are logged and ignored
+pub fn for_index(index: &index_metadata::Model) -> IndexConstraints {
+    let Some(value) = &index.constraints else {
+        return IndexConstraints::default();
+    };
+    match serde_json::from_value::<IndexConstraints>(value.clone()) {
+        Ok(constraints) => match constraints.validate() {
+            Ok(()) => constraints,
+            Err(e) => {
+                tracing::warn!(index_id = index.index_id, "Ignoring invalid constraints: {}", e);
+                IndexConstraints::default()
+            }
+        },
+        Err(e) => {
+            tracing::warn!(index_id = index.index_id, error = %e, "Ignoring unreadable constraints");
+            IndexConstraints::default()
+        }
+    }
+}
+
+/// Keep the `max` largest coins by market cap, in selection order
+///
+/// Returns the kept coins and the coin_ids of the dropped ones.
+pub fn trim_constituents(
+    constituents: Vec<ConstituentToken>,
+    market_caps: &HashMap<String, Decimal>,
+    max: usize,
+) -> (Vec<ConstituentToken>, Vec<String>) {
+    if constituents.len() <= max {
+        return (constituents, Vec::new());
+    }
+
+    let mut ranked: Vec<&ConstituentToken> = constituents.iter().collect();
+    ranked.sort_by(|a, b| {
+        match (market_caps.get(&a.coin_id), market_caps.get(&b.coin_id)) {
+            (Some(a), Some(b)) => b.cmp(a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+        .then_with(|| a.coin_id.cmp(&b.coin_id))
+    });
+    let kept: HashSet<String> = ranked.iter().take(max).map(|t| t.coin_id.clone()).collect();
+
+    let (kept, dropped): (Vec<_>, Vec<_>) = constituents.into_iter().partition(|t| kept.contains(&t.coin_id));
+    let mut removed: Vec<String> = dropped.into_iter().map(|t| t.coin_id).collect();
+    removed.sort();
+    (kept, removed)
+}
+
+/// Members among `coin_ids` of each capped category, keyed like `caps`
+pub async fn category_members(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    caps: &BTreeMap<String, Decimal>,
+) -> Result<BTreeMap<String, HashSet<String>>, sea_orm::DbErr> {
+    let by_lowercase: HashMap<String, &String> = caps.keys().map(|c| (c.to_lowercase(), c)).collect();
+
+    let memberships = CategoryMembership::find()
+        .filter(category_membership::Column::CoinId.is_in(coin_ids.iter().cloned()))
+        .filter(category_membership::Column::RemovedDate.is_null())
+        .all(db)
+        .await?;
+
+    let mut members: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+    for membership in memberships {
+        if let Some(category) = by_lowercase.get(&membership.category_id.to_lowercase()) {
+            members.entry((*category).clone()).or_default().insert(membership.coin_id);
+        }
+    }
+    Ok(members)
+}
+
+/// Scale categories over their cap down to it (rule 2)
+pub fn cap_categories(
+    weights: &mut HashMap<String, Decimal>,
+    members: &BTreeMap<String, HashSet<String>>,
+    caps: &BTreeMap<String, Decimal>,
+) -> Vec<ConstraintViolation> {
+    let total: Decimal = weights.values().sum();
+    if total <= Decimal::ZERO {
+        return Vec::new();
+    }
+    let mut violations = Vec::new();
+    let mut done: HashSet<&String> = HashSet::new();
+    let mut pinned: HashSet<String> = HashSet::new();
+
+    loop {
+        // Most over its cap first, ties by category id (BTreeMap order)
+        let mut over: Option<(&String, Decimal, Decimal)> = None;
+        for (category, coins) in members {
+            let Some(limit_pct) = caps.get(category) else { continue };
+            if done.contains(category) {
+                continue;
+            }
+            let limit = total * limit_pct / Decimal::ONE_HUNDRED;
+            let excess = category_weight(weights, coins) - limit;
+            if excess > Decimal::ZERO && over.is_none_or(|(_, _, most)| excess > most) {
+                over = Some((category, *limit_pct, excess));
+            }
+        }
+        let Some((category, limit_pct, excess)) = over else { break };
+        done.insert(category);
+
+        let coins = &members[category];
+        let weight = category_weight(weights, coins);
+        let weight_pct = (weight / total * Decimal::ONE_HUNDRED).round_dp(4);
+        let free: Vec<String> = weights
+            .keys()
+            .filter(|c| !coins.contains(*c) && !pinned.contains(*c))
+            .cloned()
+            .collect();
+        let free_weight: Decimal = free.iter().map(|c| weights[c]).sum();
+
+        let trimmed = free_weight > Decimal::ZERO;
+        if trimmed {
+            let scale_down = (weight - excess) / weight;
+            let scale_up = (free_weight + excess) / free_weight;
+            for coin in coins {
+                if let Some(w) = weights.get_mut(coin) {
+                    *w *= scale_down;
+                }
+            }
+            for coin in &free {
+                if let Some(w) = weights.get_mut(coin) {
+                    *w *= scale_up;
+                }
+            }
+            pinned.extend(coins.iter().cloned());
+        }
+
+        tracing::info!(
+            category = %category,
+            limit_pct = %limit_pct,
+            weight_pct = %weight_pct,
+            trimmed = trimmed,
+            "Category over its weight cap"
+        );
+        violations.push(ConstraintViolation::MaxCategoryWeight {
+            category: category.clone(),
+            limit_pct,
+            weight_pct,
+            trimmed,
+        });
+    }
+
+    violations
+}
+
+fn category_weight(weights: &HashMap<String, Decimal>, coins: &HashSet<String>) -> Decimal {
+    coins.iter().filter_map(|c| weights.get(c)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn token(coin_id: &str) -> ConstituentToken {
+        ConstituentToken {
+            coin_id: coin_id.to_string(),
+            symbol: coin_id.to_uppercase(),
+            exchange: "binance".to_string(),
+            trading_pair: "usdt".to_string(),
+        }
+    }
+
+    fn weights(entries: &[(&str, Decimal)]) -> HashMap<String, Decimal> {
+        entries.iter().map(|(c, w)| (c.to_string(), *w)).collect()
+    }
+
+    fn members(entries: &[(&str, &[&str])]) -> BTreeMap<String, HashSet<String>> {
+        entries
+            .iter()
+            .map(|(category, coins)| (category.to_string(), coins.iter().map(|c| c.to_string()).collect()))
+            .collect()
+    }
+
+    fn caps(entries: &[(&str, Decimal)]) -> BTreeMap<String, Decimal> {
+        entries.iter().map(|(c, w)| (c.to_string(), *w)).collect()
+    }
+
+    #[test]
+    fn test_trim_constituents_keeps_largest_market_caps() {
+        let constituents = vec![token("doge"), token("btc"), token("pepe"), token("eth"), token("shib")];
+        let market_caps = weights(&[("btc", dec!(1000)), ("eth", dec!(500)), ("doge", dec!(50)), ("shib", dec!(50))]);
+
+        let (kept, removed) = trim_constituents(constituents.clone(), &market_caps, 3);
+        // Selection order is kept; shib loses the tie with doge, pepe has no market cap
+        let kept: Vec<_> = kept.iter().map(|t| t.coin_id.as_str()).collect();
+        assert_eq!(kept, vec!["doge", "btc", "eth"]);
+        assert_eq!(removed, vec!["pepe", "shib"]);
+
+        let (kept, removed) = trim_constituents(constituents, &market_caps, 5);
+        assert_eq!(kept.len(), 5);
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn test_cap_categories_spreads_excess_over_other_coins() {
+        // meme is 60% of the index, capped at 30%
+        let mut w = weights(&[("doge", dec!(4)), ("pepe", dec!(2)), ("btc", dec!(3)), ("eth", dec!(1))]);
+        let violations = cap_categories(&mut w, &members(&[("meme", &["doge", "pepe"])]), &caps(&[("meme", dec!(30))]));
+
+        assert_eq!(w["doge"], dec!(2));
+        assert_eq!(w["pepe"], dec!(1));
+        assert_eq!(w["btc"], dec!(5.25));
+        assert_eq!(w["eth"], dec!(1.75));
+        assert_eq!(w.values().sum::<Decimal>(), dec!(10));
+        assert_eq!(
+            violations,
+            vec![ConstraintViolation::MaxCategoryWeight {
+                category: "meme".to_string(),
+                limit_pct: dec!(30),
+                weight_pct: dec!(60),
+                trimmed: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cap_categories_repeats_until_within_caps() {
+        // Capping meme pushes l2 over its cap, which is then capped without
+        // giving weight back to meme
+        let mut w = weights(&[("doge", dec!(5)), ("arb", dec!(3)), ("btc", dec!(2))]);
+        let violations = cap_categories(
+            &mut w,
+            &members(&[("l2", &["arb"]), ("meme", &["doge"])]),
+            &caps(&[("l2", dec!(40)), ("meme", dec!(20))]),
+        );
+
+        assert_eq!(w["doge"], dec!(2));
+        assert_eq!(w["arb"].round_dp(9), dec!(4));
+        assert_eq!(w["btc"].round_dp(9), dec!(4));
+        let categories: Vec<_> = violations
+            .iter()
+            .map(|v| match v {
+                ConstraintViolation::MaxCategoryWeight { category, .. } => category.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(categories, vec!["meme", "l2"]);
+    }
+
+    #[test]
+    fn test_cap_categories_within_caps_or_unmeetable() {
+        let mut w = weights(&[("doge", dec!(1)), ("btc", dec!(3))]);
+        assert!(cap_categories(&mut w, &members(&[("meme", &["doge"])]), &caps(&[("meme", dec!(25))])).is_empty());
+
+        // Every coin is a meme: nothing can take the excess
+        let mut w = weights(&[("doge", dec!(1)), ("pepe", dec!(1))]);
+        let violations = cap_categories(&mut w, &members(&[("meme", &["doge", "pepe"])]), &caps(&[("meme", dec!(50))]));
+        assert_eq!(w["doge"], dec!(1));
+        assert!(matches!(&violations[..], [ConstraintViolation::MaxCategoryWeight { trimmed: false, .. }]));
+    }
+
+    #[test]
+    fn test_violation_serialization() {
+        let json = serde_json::to_value(ConstraintViolation::MinConstituents { limit: 5, selected: 3 }).unwrap();
+        assert_eq!(json, serde_json::json!({"constraint": "minConstituents", "limit": 5, "selected": 3}));
+    }
+}
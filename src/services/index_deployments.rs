@@ -0,0 +1,181 @@
+//! Networks and contracts an index token is deployed on
+//!
+//! blockchain_events rows are keyed by (network, contract_address). Endpoints
+//! that aggregate events for an index (supply, transactions, deposits) use
+//! the index's rows in index_deployments. An index without any configured
+//! deployment falls back to its index_metadata.address on `DEFAULT_NETWORK`.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+
+use crate::entities::{blockchain_events, index_deployments, index_metadata, prelude::*};
+use crate::models::index::IndexDeployment;
+
+/// Network of indexes without configured deployments
+pub const DEFAULT_NETWORK: &str = "base";
+
+/// Normalize a deployment for storage and comparison
+pub fn normalize(deployment: &IndexDeployment) -> IndexDeployment {
+    IndexDeployment {
+        network: deployment.network.trim().to_lowercase(),
+        contract_address: deployment.contract_address.trim().to_lowercase(),
+    }
+}
+
+/// The single deployment implied by index_metadata.address
+fn fallback(index: &index_metadata::Model) -> Vec<IndexDeployment> {
+    if index.address.is_empty() {
+        return Vec::new();
+    }
+    vec![IndexDeployment {
+        network: DEFAULT_NETWORK.to_string(),
+        contract_address: index.address.to_lowercase(),
+    }]
+}
+
+impl From<index_deployments::Model> for IndexDeployment {
+    fn from(model: index_deployments::Model) -> Self {
+        IndexDeployment { network: model.network, contract_address: model.contract_address }
+    }
+}
+
+/// Deployments of one index
+pub async fn for_index(
+    db: &DatabaseConnection,
+    index: &index_metadata::Model,
+) -> Result<Vec<IndexDeployment>, sea_orm::DbErr> {
+    let rows = IndexDeployments::find()
+        .filter(index_deployments::Column::IndexId.eq(index.index_id))
+        .order_by_asc(index_deployments::Column::Id)
+        .all(db)
+        .await?;
+
+    if rows.is_empty() {
+        return Ok(fallback(index));
+    }
+    Ok(rows.into_iter().map(IndexDeployment::from).collect())
+}
+
+/// Deployments of several indexes, in one query
+pub async fn for_indexes(
+    db: &DatabaseConnection,
+    indexes: &[index_metadata::Model],
+) -> Result<HashMap<i32, Vec<IndexDeployment>>, sea_orm::DbErr> {
+    let rows = IndexDeployments::find()
+        .order_by_asc(index_deployments::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut configured: HashMap<i32, Vec<IndexDeployment>> = HashMap::new();
+    for row in rows {
+        configured.entry(row.index_id).or_default().push(row.into());
+    }
+
+    Ok(indexes
+        .iter()
+        .map(|index| {
+            let deployments = configured.remove(&index.index_id).unwrap_or_else(|| fallback(index));
+            (index.index_id, deployments)
+        })
+        .collect())
+}
+
+/// Replace the deployments of an index
+pub async fn replace(
+    db: &DatabaseConnection,
+    index_id: i32,
+    deployments: &[IndexDeployment],
+) -> Result<Vec<IndexDeployment>, sea_orm::DbErr> {
+    let mut normalized: Vec<IndexDeployment> = Vec::new();
+    for deployment in deployments.iter().map(normalize) {
+        if !normalized.contains(&deployment) {
+            normalized.push(deployment);
+        }
+    }
+
+    let txn = db.begin().await?;
+
+    IndexDeployments::delete_many()
+        .filter(index_deployments::Column::IndexId.eq(index_id))
+        .exec(&txn)
+        .await?;
+
+    if !normalized.is_empty() {
+        let now = Utc::now().naive_utc();
+        let rows = normalized.iter().map(|d| index_deployments::ActiveModel {
+            index_id: Set(index_id),
+            network: Set(d.network.clone()),
+            contract_address: Set(d.contract_address.clone()),
+            created_at: Set(now),
+            ..Default::default()
+        });
+        IndexDeployments::insert_many(rows).exec(&txn).await?;
+    }
+
+    txn.commit().await?;
+    Ok(normalized)
+}
+
+/// blockchain_events filter matching any of the deployments
+///
+/// Stored events are lowercase (the unique event log migration lowercased
+/// older rows, and new ones are normalized on write), so the raw columns are
+/// compared with lowercased deployments, keeping
+/// idx_blockchain_events_contract_network_type usable. Matches nothing when
+/// `deployments` is empty.
+pub fn events_condition(deployments: &[IndexDeployment]) -> Condition {
+    deployments.iter().fold(Condition::any(), |condition, d| {
+        condition.add(
+            Condition::all()
+                .add(blockchain_events::Column::Network.eq(d.network.to_lowercase()))
+                .add(blockchain_events::Column::ContractAddress.eq(d.contract_address.to_lowercase())),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    fn deployment(network: &str, address: &str) -> IndexDeployment {
+        IndexDeployment { network: network.to_string(), contract_address: address.to_string() }
+    }
+
+    fn events_sql(deployments: &[IndexDeployment]) -> String {
+        BlockchainEvents::find()
+            .filter(events_condition(deployments))
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(&deployment(" Base ", "0xAbC")), deployment("base", "0xabc"));
+    }
+
+    #[test]
+    fn test_events_condition_covers_all_deployments() {
+        let sql = events_sql(&[deployment("base", "0xaaa"), deployment("arbitrum", "0xbbb")]);
+        assert!(sql.contains(r#"("blockchain_events"."network" = 'base' AND "blockchain_events"."contract_address" = '0xaaa')"#));
+        assert!(sql.contains(" OR "));
+        assert!(sql.contains(r#""blockchain_events"."network" = 'arbitrum'"#));
+    }
+
+    #[test]
+    fn test_events_condition_lowercases_deployments_not_columns() {
+        let sql = events_sql(&[deployment("Base", "0xAbC")]);
+        assert!(sql.contains(r#""blockchain_events"."contract_address" = '0xabc'"#));
+        assert!(!sql.contains("LOWER("));
+    }
+
+    #[test]
+    fn test_events_condition_empty_matches_nothing() {
+        assert!(events_sql(&[]).contains("FALSE"));
+    }
+}
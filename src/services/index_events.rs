@@ -0,0 +1,149 @@
+//! Live progress of an index, for GET /events/indexes/{index_id}
+//!
+//! Background work records its progress in tables rather than publishing it:
+//! the backfill in background_tasks, the rebalance job and deployer in
+//! rebalances, ITP creation in itps. `IndexEventWatcher` bridges them into
+//! events by diffing their rows against what it saw on its previous poll.
+//! The first poll reports the backfill task and ITPs as they are and only
+//! records existing rebalances, so a client gets the current state without a
+//! replay of the index's history.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect};
+
+use crate::entities::{background_tasks, itps, prelude::*, rebalances};
+use crate::models::index_event::{IndexEvent, ItpStatusEvent, RebalanceEvent};
+use crate::services::background_tasks::{backfill_status, task_types};
+
+/// Fields of a backfill task whose change is reported
+type BackfillState = (i32, String, i32, Option<i32>, Option<NaiveDate>, Option<String>);
+
+/// Deployment fields of a rebalance
+type DeploymentState = (bool, Option<String>);
+
+/// Rebalance columns polled for changes: id, timestamp, type, deployed, tx_hash
+type RebalanceRow = (i32, i64, String, Option<bool>, Option<String>);
+
+pub struct IndexEventWatcher {
+    index_id: i32,
+    backfill: Option<BackfillState>,
+    /// None until the first poll
+    rebalances: Option<HashMap<i32, DeploymentState>>,
+    itps: HashMap<i32, ItpStatusEvent>,
+}
+
+impl IndexEventWatcher {
+    pub fn new(index_id: i32) -> Self {
+        Self { index_id, backfill: None, rebalances: None, itps: HashMap::new() }
+    }
+
+    /// Changes since the previous poll, oldest kind first: backfill, then
+    /// rebalances, then ITPs
+    pub async fn poll(&mut self, db: &DatabaseConnection) -> Result<Vec<IndexEvent>, DbErr> {
+        let mut events = Vec::new();
+        self.poll_backfill(db, &mut events).await?;
+        self.poll_rebalances(db, &mut events).await?;
+        self.poll_itps(db, &mut events).await?;
+        Ok(events)
+    }
+
+    async fn poll_backfill(&mut self, db: &DatabaseConnection, events: &mut Vec<IndexEvent>) -> Result<(), DbErr> {
+        let task = BackgroundTasks::find()
+            .filter(background_tasks::Column::IndexId.eq(self.index_id))
+            .filter(background_tasks::Column::TaskType.eq(task_types::INDEX_BACKFILL))
+            .order_by(background_tasks::Column::CreatedAt, Order::Desc)
+            .one(db)
+            .await?;
+        let Some(task) = task else {
+            return Ok(());
+        };
+
+        let state = (
+            task.id,
+            task.status.clone(),
+            task.attempts,
+            task.progress_done,
+            task.progress_date,
+            task.error.clone(),
+        );
+        if self.backfill.as_ref() != Some(&state) {
+            self.backfill = Some(state);
+            events.push(IndexEvent::Backfill(backfill_status(self.index_id, task, Utc::now().naive_utc())));
+        }
+        Ok(())
+    }
+
+    async fn poll_rebalances(&mut self, db: &DatabaseConnection, events: &mut Vec<IndexEvent>) -> Result<(), DbErr> {
+        let rows: Vec<RebalanceRow> = Rebalances::find()
+            .select_only()
+            .columns([
+                rebalances::Column::Id,
+                rebalances::Column::Timestamp,
+                rebalances::Column::RebalanceType,
+                rebalances::Column::Deployed,
+                rebalances::Column::TxHash,
+            ])
+            .filter(rebalances::Column::IndexId.eq(self.index_id))
+            .order_by(rebalances::Column::Timestamp, Order::Asc)
+            .order_by(rebalances::Column::Id, Order::Asc)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let Some(seen) = self.rebalances.as_mut() else {
+            self.rebalances = Some(
+                rows.into_iter()
+                    .map(|(id, _, _, deployed, tx_hash)| (id, (deployed.unwrap_or(false), tx_hash)))
+                    .collect(),
+            );
+            return Ok(());
+        };
+
+        for (id, timestamp, rebalance_type, deployed, tx_hash) in rows {
+            let deployment = (deployed.unwrap_or(false), tx_hash);
+            let event = RebalanceEvent {
+                index_id: self.index_id,
+                rebalance_id: id,
+                timestamp,
+                rebalance_type,
+                deployed: deployment.0,
+                tx_hash: deployment.1.clone(),
+            };
+            match seen.insert(id, deployment.clone()) {
+                None => events.push(IndexEvent::RebalanceCreated(event)),
+                Some(previous) if previous != deployment && deployment.0 => {
+                    events.push(IndexEvent::RebalanceDeployed(event))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn poll_itps(&mut self, db: &DatabaseConnection, events: &mut Vec<IndexEvent>) -> Result<(), DbErr> {
+        let rows = Itps::find()
+            .filter(itps::Column::IndexId.eq(i64::from(self.index_id)))
+            .order_by(itps::Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+
+        for itp in rows {
+            let status = ItpStatusEvent {
+                index_id: self.index_id,
+                itp_id: itp.id,
+                symbol: itp.symbol,
+                orbit_address: itp.orbit_address,
+                arbitrum_address: itp.arbitrum_address,
+                state: itp.state,
+                deploy_tx_hash: itp.deploy_tx_hash,
+            };
+            if self.itps.get(&itp.id) != Some(&status) {
+                self.itps.insert(itp.id, status.clone());
+                events.push(IndexEvent::Itp(status));
+            }
+        }
+        Ok(())
+    }
+}
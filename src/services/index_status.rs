@@ -0,0 +1,30 @@
+//! Index lifecycle status (index_metadata.status)
+//!
+//! Indexes are active when created. A paused index is kept as is but left
+//! out of the daily prices sync until it's active again, when the sync
+//! catches up on the days it missed; a retired one is left out for good.
+//! Set with PUT /admin/indexes/{index_id}/status.
+
+pub const ACTIVE: &str = "active";
+pub const PAUSED: &str = "paused";
+pub const RETIRED: &str = "retired";
+
+pub const ALL: [&str; 3] = [ACTIVE, PAUSED, RETIRED];
+
+/// The status named by `value` (case-insensitive), if it's one
+pub fn parse(value: &str) -> Option<&'static str> {
+    let value = value.trim().to_lowercase();
+    ALL.into_iter().find(|status| *status == value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("paused"), Some(PAUSED));
+        assert_eq!(parse(" Retired "), Some(RETIRED));
+        assert_eq!(parse("deleted"), None);
+    }
+}
@@ -0,0 +1,278 @@
+//! Localized index names and descriptions
+//!
+//! index_translations holds an index's name and description per locale, a
+//! language tag such as "fr" or "pt-BR". GET /indexes and /get-index-config
+//! answer with the translation that best matches the request's
+//! Accept-Language: preferences are tried by quality, each one exactly
+//! first, then by language ("fr-CH" falls back to "fr", "fr" takes any
+//! "fr-*"). Without a match, the index's own name is used.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    Set,
+};
+
+use crate::entities::{index_metadata, index_translations, prelude::*};
+
+const MAX_NAME_LEN: usize = 255;
+const MAX_DESCRIPTION_LEN: usize = 4000;
+
+/// Accept-Language entries looked at, so a huge header costs nothing
+const MAX_PREFERENCES: usize = 16;
+
+#[derive(Debug)]
+pub enum TranslationError {
+    Invalid(String),
+    IndexNotFound(i32),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslationError::Invalid(msg) => write!(f, "{}", msg),
+            TranslationError::IndexNotFound(index_id) => write!(f, "Index {} not found", index_id),
+            TranslationError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TranslationError {}
+
+impl From<sea_orm::DbErr> for TranslationError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        TranslationError::Database(e)
+    }
+}
+
+/// Normalize a language tag: `pt_br` becomes `pt-BR`, `zh-hant` `zh-Hant`
+pub fn normalize_locale(locale: &str) -> Result<String, TranslationError> {
+    let invalid = || {
+        TranslationError::Invalid(format!(
+            "Invalid locale '{}', expected a language tag such as 'fr' or 'pt-BR'",
+            locale
+        ))
+    };
+
+    let mut parts = locale.trim().split(['-', '_']);
+    let language = parts.next().unwrap_or_default();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+
+    let mut tag = language.to_ascii_lowercase();
+    for part in parts {
+        let subtag = match part.len() {
+            // Script, e.g. Hant
+            4 if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+                part[..1].to_ascii_uppercase() + &part[1..].to_ascii_lowercase()
+            }
+            // Region, e.g. BR or 419
+            2 if part.chars().all(|c| c.is_ascii_alphabetic()) => part.to_ascii_uppercase(),
+            3 if part.chars().all(|c| c.is_ascii_digit()) => part.to_string(),
+            _ => return Err(invalid()),
+        };
+        tag.push('-');
+        tag.push_str(&subtag);
+    }
+
+    if tag.len() > 16 {
+        return Err(invalid());
+    }
+    Ok(tag)
+}
+
+fn language_of(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+/// Locales of an Accept-Language header, most preferred first
+///
+/// Wildcards, `q=0` entries and malformed tags are skipped.
+pub fn parse_accept_language(header: Option<&str>) -> Vec<String> {
+    let Some(header) = header else {
+        return Vec::new();
+    };
+
+    let mut preferences: Vec<(String, f32)> = header
+        .split(',')
+        .take(MAX_PREFERENCES)
+        .filter_map(|entry| {
+            let mut params = entry.split(';');
+            let locale = normalize_locale(params.next()?).ok()?;
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((locale, quality))
+        })
+        .collect();
+    // Stable, so equal qualities keep the header's order
+    preferences.sort_by(|a, b| b.1.total_cmp(&a.1));
+    preferences.into_iter().map(|(locale, _)| locale).collect()
+}
+
+/// Best of the `available` locales for `preferred` (see the module docs)
+pub fn best_match<'a>(preferred: &[String], available: &[&'a str]) -> Option<&'a str> {
+    preferred.iter().find_map(|wanted| {
+        let language = language_of(wanted);
+        available
+            .iter()
+            .find(|locale| **locale == wanted.as_str())
+            .or_else(|| available.iter().find(|locale| **locale == language))
+            .or_else(|| available.iter().find(|locale| language_of(locale) == language))
+            .copied()
+    })
+}
+
+/// Translations of an index, by locale
+pub async fn list(db: &DatabaseConnection, index_id: i32) -> Result<Vec<index_translations::Model>, sea_orm::DbErr> {
+    IndexTranslations::find()
+        .filter(index_translations::Column::IndexId.eq(index_id))
+        .order_by_asc(index_translations::Column::Locale)
+        .all(db)
+        .await
+}
+
+/// Best translation of each index for the `preferred` locales
+pub async fn for_indexes(
+    db: &DatabaseConnection,
+    index_ids: &[i32],
+    preferred: &[String],
+) -> Result<HashMap<i32, index_translations::Model>, sea_orm::DbErr> {
+    if preferred.is_empty() || index_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut by_index: HashMap<i32, Vec<index_translations::Model>> = HashMap::new();
+    for translation in IndexTranslations::find()
+        .filter(index_translations::Column::IndexId.is_in(index_ids.iter().copied()))
+        .all(db)
+        .await?
+    {
+        by_index.entry(translation.index_id).or_default().push(translation);
+    }
+
+    Ok(by_index
+        .into_iter()
+        .filter_map(|(index_id, translations)| {
+            let locales: Vec<&str> = translations.iter().map(|t| t.locale.as_str()).collect();
+            let best = best_match(preferred, &locales)?.to_string();
+            let translation = translations.into_iter().find(|t| t.locale == best)?;
+            Some((index_id, translation))
+        })
+        .collect())
+}
+
+/// Create or replace the translation of an index in a locale
+pub async fn upsert(
+    db: &DatabaseConnection,
+    index_id: i32,
+    locale: &str,
+    name: &str,
+    description: Option<&str>,
+    now: NaiveDateTime,
+) -> Result<index_translations::Model, TranslationError> {
+    let locale = normalize_locale(locale)?;
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(TranslationError::Invalid(format!(
+            "name must be 1 to {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    let description = description.map(str::trim).filter(|d| !d.is_empty());
+    if description.is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LEN) {
+        return Err(TranslationError::Invalid(format!(
+            "description must be at most {} characters",
+            MAX_DESCRIPTION_LEN
+        )));
+    }
+
+    let exists = IndexMetadata::find()
+        .filter(index_metadata::Column::IndexId.eq(index_id))
+        .count(db)
+        .await?
+        > 0;
+    if !exists {
+        return Err(TranslationError::IndexNotFound(index_id));
+    }
+
+    let translation = index_translations::ActiveModel {
+        index_id: Set(index_id),
+        locale: Set(locale),
+        name: Set(name.to_string()),
+        description: Set(description.map(str::to_string)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+    Ok(IndexTranslations::insert(translation)
+        .on_conflict(
+            OnConflict::columns([index_translations::Column::IndexId, index_translations::Column::Locale])
+                .update_columns([
+                    index_translations::Column::Name,
+                    index_translations::Column::Description,
+                    index_translations::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await?)
+}
+
+/// Delete the translation of an index in a locale; returns whether it existed
+pub async fn remove(db: &DatabaseConnection, index_id: i32, locale: &str) -> Result<bool, TranslationError> {
+    let locale = normalize_locale(locale)?;
+    let result = IndexTranslations::delete_many()
+        .filter(index_translations::Column::IndexId.eq(index_id))
+        .filter(index_translations::Column::Locale.eq(locale))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("FR").unwrap(), "fr");
+        assert_eq!(normalize_locale("pt_br").unwrap(), "pt-BR");
+        assert_eq!(normalize_locale("zh-hant-tw").unwrap(), "zh-Hant-TW");
+        assert_eq!(normalize_locale("es-419").unwrap(), "es-419");
+        assert!(normalize_locale("").is_err());
+        assert!(normalize_locale("*").is_err());
+        assert!(normalize_locale("french").is_err());
+        assert!(normalize_locale("en-").is_err());
+    }
+
+    #[test]
+    fn test_parse_accept_language() {
+        assert!(parse_accept_language(None).is_empty());
+        assert_eq!(
+            parse_accept_language(Some("fr-CH, fr;q=0.9, en;q=0.8, de;q=0, *;q=0.5")),
+            vec!["fr-CH", "fr", "en"]
+        );
+        assert_eq!(parse_accept_language(Some("en;q=0.5,ja")), vec!["ja", "en"]);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let available = ["de", "fr-FR", "pt-BR", "pt"];
+        let pick = |header: &str| best_match(&parse_accept_language(Some(header)), &available);
+
+        assert_eq!(pick("pt-BR"), Some("pt-BR"));
+        // Language fallback prefers the bare language
+        assert_eq!(pick("pt-PT"), Some("pt"));
+        assert_eq!(pick("fr-CH"), Some("fr-FR"));
+        assert_eq!(pick("fr"), Some("fr-FR"));
+        // Preferences without any match are skipped
+        assert_eq!(pick("ja, de;q=0.5"), Some("de"));
+        assert_eq!(pick("ja, ko"), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! Daily TVL (AUM) of each index
+//!
+//! An index's TVL on a day is the net supply of its token at the end of the
+//! day (mints minus burns and withdrawals in blockchain_events) times the
+//! day's NAV from daily_prices. The TVL snapshot job records one index_tvl
+//! row per index and priced day: missing days are backfilled, and the latest
+//! day is recomputed on every run since its price and events can still change.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+
+use crate::entities::{blockchain_events, daily_prices, index_metadata, index_tvl, prelude::*};
+use crate::services::{event_amounts, index_deployments};
+
+/// Event types that create index tokens
+const MINT_EVENTS: &[&str] = &["mint"];
+
+/// Event types that destroy index tokens
+const BURN_EVENTS: &[&str] = &["burn", "withdraw"];
+
+/// Rows per insert statement
+const INSERT_BATCH: usize = 1000;
+
+/// Record the TVL of every index; returns the number of rows written
+pub async fn record_snapshots(db: &DatabaseConnection) -> Result<usize, sea_orm::DbErr> {
+    let indexes = IndexMetadata::find().all(db).await?;
+    let mut written = 0;
+    for index in &indexes {
+        written += record_index(db, index).await?;
+    }
+    Ok(written)
+}
+
+/// Record the days of `index` that have a price but no snapshot yet, plus the latest day
+pub async fn record_index(db: &DatabaseConnection, index: &index_metadata::Model) -> Result<usize, sea_orm::DbErr> {
+    let prices = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index.index_id.to_string()))
+        .order_by_asc(daily_prices::Column::Date)
+        .all(db)
+        .await?;
+    let Some(latest) = prices.last().map(|p| p.date) else {
+        return Ok(0);
+    };
+
+    let recorded: HashSet<NaiveDate> = IndexTvl::find()
+        .select_only()
+        .column(index_tvl::Column::Date)
+        .filter(index_tvl::Column::IndexId.eq(index.index_id))
+        .into_tuple()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    if prices.iter().all(|p| recorded.contains(&p.date) && p.date != latest) {
+        return Ok(0);
+    }
+
+    let deployments = index_deployments::for_index(db, index).await?;
+    let event_types: Vec<&str> = MINT_EVENTS.iter().chain(BURN_EVENTS).copied().collect();
+    let mut events = BlockchainEvents::find()
+        .filter(index_deployments::events_condition(&deployments))
+        .filter(blockchain_events::Column::EventType.is_in(event_types))
+        .all(db)
+        .await?;
+    let undated = events.iter().filter(|e| e.timestamp.is_none()).count();
+    if undated > 0 {
+        tracing::warn!(index_id = index.index_id, undated, "Ignoring supply events without timestamp");
+    }
+    events.retain(|e| e.timestamp.is_some());
+    events.sort_by_key(|e| e.timestamp);
+
+    let now = Utc::now().naive_utc();
+    let mut supply = Decimal::ZERO;
+    let mut pending = events.iter().peekable();
+    let mut rows = Vec::new();
+    for price in &prices {
+        while let Some(event) = pending.next_if(|e| e.timestamp.is_some_and(|t| t.date_naive() <= price.date)) {
+            let quantity = event_amounts::quantity(event).unwrap_or_default();
+            if MINT_EVENTS.contains(&event.event_type.as_str()) {
+                supply += quantity;
+            } else {
+                supply -= quantity;
+            }
+        }
+
+        if recorded.contains(&price.date) && price.date != latest {
+            continue;
+        }
+        let net_supply = supply.max(Decimal::ZERO);
+        rows.push(index_tvl::ActiveModel {
+            index_id: Set(index.index_id),
+            date: Set(price.date),
+            net_supply: Set(net_supply),
+            nav: Set(price.price),
+            tvl_usd: Set(net_supply * price.price),
+            recorded_at: Set(now),
+            ..Default::default()
+        });
+    }
+
+    let written = rows.len();
+    for batch in rows.chunks(INSERT_BATCH) {
+        IndexTvl::insert_many(batch.to_vec())
+            .on_conflict(
+                OnConflict::columns([index_tvl::Column::IndexId, index_tvl::Column::Date])
+                    .update_columns([
+                        index_tvl::Column::NetSupply,
+                        index_tvl::Column::Nav,
+                        index_tvl::Column::TvlUsd,
+                        index_tvl::Column::RecordedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(written)
+}
+
+/// Recorded snapshots of `index_id` between `start` and `end` (inclusive), oldest first
+pub async fn history(
+    db: &DatabaseConnection,
+    index_id: i32,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Result<Vec<index_tvl::Model>, sea_orm::DbErr> {
+    let mut query = IndexTvl::find().filter(index_tvl::Column::IndexId.eq(index_id));
+    if let Some(start) = start {
+        query = query.filter(index_tvl::Column::Date.gte(start));
+    }
+    if let Some(end) = end {
+        query = query.filter(index_tvl::Column::Date.lte(end));
+    }
+    query.order_by_asc(index_tvl::Column::Date).all(db).await
+}
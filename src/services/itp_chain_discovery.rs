@@ -13,6 +13,7 @@ use alloy::{
     transports::http::{Client, Http},
 };
 use asset_registry::AssetRegistry;
+use crate::services::itp_validation::registry_symbol;
 use std::str::FromStr;
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
@@ -354,17 +355,8 @@ impl ItpChainDiscoveryService {
         // Map asset IDs to symbols via registry
         let assets: Vec<String> = asset_ids.iter().map(|id| {
             match self.asset_registry.by_id(*id) {
-                Some(asset) => {
-                    // Derive symbol from bitget field (e.g., "BTCUSDC" -> "BTC")
-                    let bitget = &asset.bitget;
-                    if bitget.ends_with("USDC") {
-                        bitget.trim_end_matches("USDC").to_string()
-                    } else if bitget.ends_with("USDT") {
-                        bitget.trim_end_matches("USDT").to_string()
-                    } else {
-                        bitget.clone()
-                    }
-                }
+                // Derive symbol from bitget field (e.g., "BTCUSDC" -> "BTC")
+                Some(asset) => registry_symbol(asset),
                 None => {
                     warn!(asset_id = id, "Unknown asset ID in registry, using ID as placeholder");
                     format!("ASSET_{}", id)
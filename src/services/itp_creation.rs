@@ -5,17 +5,22 @@
 
 use alloy::{
     network::EthereumWallet,
-    primitives::{Address, U256},
+    primitives::{Address, B256, U256},
     providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::TransactionReceipt,
     signers::local::PrivateKeySigner,
     sol,
-    sol_types::SolEvent,
+    sol_types::{SolCall, SolEvent},
     transports::http::{Client, Http},
 };
+use sea_orm::DatabaseConnection;
 use std::str::FromStr;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
+use crate::services::chain_spend::{self, ChainTransaction};
+use crate::services::tx_simulator::{self, Simulation, SimulationError};
+
 /// Default gas limit for requestCreateItp (increased for new parameters)
 const DEFAULT_GAS_LIMIT: u64 = 500_000;
 
@@ -43,6 +48,12 @@ sol! {
             uint128[] calldata weights
         ) external;
 
+        function requestRebalance(
+            address orbitItp,
+            uint128[] calldata assets,
+            uint128[] calldata weights
+        ) external;
+
         event CreateItpRequested(
             address indexed admin,
             string name,
@@ -82,16 +93,35 @@ pub struct ItpCreationSyncResult {
     pub arbitrum_address: String,
 }
 
+/// Result of a rebalance (weight update) request
+#[derive(Debug, Clone)]
+pub struct ItpRebalanceResult {
+    pub tx_hash: String,
+    pub block_number: u64,
+}
+
+/// State of a sent rebalance transaction
+#[derive(Debug, Clone)]
+pub enum RebalanceTxStatus {
+    /// Known to the node but not mined yet
+    Pending,
+    /// Unknown to the node and not mined: it will never be, safe to resend
+    Dropped,
+    /// Mined but reverted, so the weights didn't change
+    Reverted,
+    Confirmed(ItpRebalanceResult),
+}
+
 /// Error types for ITP creation
 #[derive(Debug)]
 pub enum ItpCreationError {
     ProviderError(String),
     TransactionError(String),
-    /// Gas estimation failure - currently unused as estimate_gas_with_fallback
-    /// uses a fallback value for resilience, but kept for error mapping completeness
-    /// and potential future strict mode.
-    #[allow(dead_code)]
+    /// Gas estimation failure. ITP creation falls back to a default gas limit
+    /// (see estimate_gas_with_fallback); rebalance requests fail instead.
     GasEstimationError(String),
+    /// The transaction would revert (see tx_simulator); nothing was sent
+    SimulationReverted(String),
     EventParsingError(String),
     Timeout(String),
     InvalidConfig(String),
@@ -103,6 +133,7 @@ impl std::fmt::Display for ItpCreationError {
             ItpCreationError::ProviderError(msg) => write!(f, "Provider error: {}", msg),
             ItpCreationError::TransactionError(msg) => write!(f, "Transaction error: {}", msg),
             ItpCreationError::GasEstimationError(msg) => write!(f, "Gas estimation error: {}", msg),
+            ItpCreationError::SimulationReverted(reason) => write!(f, "Simulation reverted: {}", reason),
             ItpCreationError::EventParsingError(msg) => write!(f, "Event parsing error: {}", msg),
             ItpCreationError::Timeout(msg) => write!(f, "Timeout: {}", msg),
             ItpCreationError::InvalidConfig(msg) => write!(f, "Invalid config: {}", msg),
@@ -112,11 +143,23 @@ impl std::fmt::Display for ItpCreationError {
 
 impl std::error::Error for ItpCreationError {}
 
+impl From<SimulationError> for ItpCreationError {
+    fn from(e: SimulationError) -> Self {
+        match e {
+            SimulationError::Reverted(reason) => ItpCreationError::SimulationReverted(reason),
+            SimulationError::Provider(msg) => ItpCreationError::ProviderError(msg),
+        }
+    }
+}
+
 /// ITP Creation Service
 pub struct ItpCreationService {
     provider: RootProvider<Http<Client>>,
     wallet: EthereumWallet,
     bridge_proxy_address: Address,
+    chain_id: u64,
+    /// Where sent transactions are recorded, if anywhere (see `chain_spend`)
+    spend_db: Option<DatabaseConnection>,
 }
 
 impl ItpCreationService {
@@ -136,18 +179,29 @@ impl ItpCreationService {
         private_key: &str,
         bridge_proxy_address: &str,
     ) -> Result<Self, ItpCreationError> {
-        info!(
-            rpc_url = %rpc_url,
-            bridge_proxy = %bridge_proxy_address,
-            "Initializing ItpCreationService"
-        );
-
         // Parse private key
         let signer: PrivateKeySigner = private_key
             .parse()
             .map_err(|e| ItpCreationError::InvalidConfig(format!("Invalid private key: {}", e)))?;
 
-        let wallet = EthereumWallet::from(signer);
+        Self::with_wallet(rpc_url, EthereumWallet::from(signer), bridge_proxy_address).await
+    }
+
+    /// Create a new ItpCreationService signing with `wallet`
+    ///
+    /// Use with `signers::wallet_from_env` to sign through the configured
+    /// key backend (local key, AWS KMS or Vault).
+    pub async fn with_wallet(
+        rpc_url: &str,
+        wallet: EthereumWallet,
+        bridge_proxy_address: &str,
+    ) -> Result<Self, ItpCreationError> {
+        info!(
+            rpc_url = %rpc_url,
+            bridge_proxy = %bridge_proxy_address,
+            signer = %wallet.default_signer().address(),
+            "Initializing ItpCreationService"
+        );
 
         // Create provider
         let provider = ProviderBuilder::new()
@@ -183,9 +237,17 @@ impl ItpCreationService {
             provider,
             wallet,
             bridge_proxy_address: bridge_proxy,
+            chain_id,
+            spend_db: None,
         })
     }
 
+    /// Record every transaction this service sends in chain_transactions
+    pub fn with_spend_recording(mut self, db: DatabaseConnection) -> Self {
+        self.spend_db = Some(db);
+        self
+    }
+
     /// Request ITP creation via BridgeProxy (async mode)
     ///
     /// # Arguments
@@ -279,6 +341,7 @@ impl ItpCreationService {
             error!(error = %e, "Failed to get transaction receipt");
             ItpCreationError::TransactionError(format!("Receipt failed: {}", e))
         })?;
+        self.record_spend(chain_spend::kinds::CREATE_ITP, symbol.to_string(), &receipt).await;
 
         if !receipt.status() {
             return Err(ItpCreationError::TransactionError(
@@ -359,7 +422,117 @@ impl ItpCreationService {
         })
     }
 
-    /// Estimate gas for requestCreateItp with fallback
+    /// Send an on-chain weight update of an existing ITP via BridgeProxy
+    ///
+    /// # Arguments
+    ///
+    /// * `orbit_itp` - ITP contract address on Orbit
+    /// * `assets` - Array of asset IDs
+    /// * `weights` - Array of weights in basis points, summing to 10000
+    ///
+    /// The call is simulated first and not sent if it would revert. Unlike
+    /// ITP creation there is no fallback gas limit: a weight update that
+    /// can't be simulated isn't sent either.
+    ///
+    /// Returns the tx hash as soon as the transaction is broadcast, so the
+    /// caller can persist it before waiting (see `wait_for_rebalance`).
+    pub async fn submit_rebalance(
+        &self,
+        orbit_itp: &str,
+        assets: Vec<u128>,
+        weights: Vec<u128>,
+    ) -> Result<String, ItpCreationError> {
+        let orbit_itp = Address::from_str(orbit_itp)
+            .map_err(|e| ItpCreationError::InvalidConfig(format!("Invalid ITP address: {}", e)))?;
+        info!(orbit_itp = %orbit_itp, num_assets = assets.len(), "Requesting ITP rebalance");
+
+        let gas = match self.simulate_rebalance(orbit_itp, &assets, &weights).await {
+            Ok(simulation) => simulation.gas_estimate,
+            Err(SimulationError::Reverted(reason)) => return Err(ItpCreationError::SimulationReverted(reason)),
+            Err(SimulationError::Provider(msg)) => return Err(ItpCreationError::GasEstimationError(msg)),
+        };
+
+        let rpc_url = std::env::var("ARB_RPC_URL")
+            .map_err(|_| ItpCreationError::InvalidConfig("ARB_RPC_URL not configured".to_string()))?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(self.wallet.clone())
+            .on_http(
+                rpc_url
+                    .parse()
+                    .map_err(|e| ItpCreationError::ProviderError(format!("RPC URL error: {}", e)))?,
+            );
+
+        let pending_tx = IBridgeProxy::new(self.bridge_proxy_address, &provider)
+            .requestRebalance(orbit_itp, assets, weights)
+            .gas(gas * 120 / 100)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to send requestRebalance transaction");
+                ItpCreationError::TransactionError(format!("Send failed: {}", e))
+            })?;
+
+        let tx_hash = format!("{:?}", pending_tx.tx_hash());
+        info!(tx_hash = %tx_hash, "Rebalance transaction sent");
+        Ok(tx_hash)
+    }
+
+    /// Where a sent requestRebalance transaction stands (non-blocking)
+    ///
+    /// Mined transactions have their gas spend recorded against `orbit_itp`.
+    pub async fn rebalance_status(&self, tx_hash: &str, orbit_itp: &str) -> Result<RebalanceTxStatus, ItpCreationError> {
+        let hash = B256::from_str(tx_hash)
+            .map_err(|e| ItpCreationError::InvalidConfig(format!("Invalid tx hash '{}': {}", tx_hash, e)))?;
+
+        let receipt = self.provider.get_transaction_receipt(hash).await.map_err(|e| {
+            ItpCreationError::ProviderError(format!("Failed to get receipt: {}", e))
+        })?;
+        let Some(receipt) = receipt else {
+            // Not mined: either still in the mempool or dropped from it
+            let known = self.provider.get_transaction_by_hash(hash).await.map_err(|e| {
+                ItpCreationError::ProviderError(format!("Failed to get transaction: {}", e))
+            })?;
+            return Ok(if known.is_some() { RebalanceTxStatus::Pending } else { RebalanceTxStatus::Dropped });
+        };
+
+        self.record_spend(chain_spend::kinds::REBALANCE, orbit_itp.to_string(), &receipt).await;
+        if !receipt.status() {
+            return Ok(RebalanceTxStatus::Reverted);
+        }
+        let block_number = receipt.block_number.unwrap_or(0);
+        info!(tx_hash = %tx_hash, block_number = block_number, "ITP rebalance confirmed");
+        Ok(RebalanceTxStatus::Confirmed(ItpRebalanceResult {
+            tx_hash: tx_hash.to_string(),
+            block_number,
+        }))
+    }
+
+    /// Poll `rebalance_status` until the transaction is no longer pending
+    ///
+    /// Times out after SYNC_TIMEOUT_MS; the transaction may still be mined
+    /// afterwards, so callers check it again later rather than resending.
+    pub async fn wait_for_rebalance(&self, tx_hash: &str, orbit_itp: &str) -> Result<RebalanceTxStatus, ItpCreationError> {
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_millis(SYNC_TIMEOUT_MS);
+        let poll_interval = Duration::from_millis(POLL_INTERVAL_MS);
+
+        while start.elapsed() < timeout {
+            match self.rebalance_status(tx_hash, orbit_itp).await? {
+                RebalanceTxStatus::Pending => tokio::time::sleep(poll_interval).await,
+                status => return Ok(status),
+            }
+        }
+        Err(ItpCreationError::Timeout(format!(
+            "Timeout waiting for rebalance transaction {}",
+            tx_hash
+        )))
+    }
+
+    /// Simulate requestCreateItp and estimate its gas
+    ///
+    /// Fails if the call would revert; falls back to DEFAULT_GAS_LIMIT if it
+    /// can't be simulated.
     async fn estimate_gas_with_fallback(
         &self,
         name: &str,
@@ -371,34 +544,26 @@ impl ItpCreationService {
         assets: &[u128],
         weights: &[u128],
     ) -> Result<u64, ItpCreationError> {
-        let bridge_proxy = IBridgeProxy::new(self.bridge_proxy_address, &self.provider);
-        let price_u256 = U256::from(initial_price);
-
-        match bridge_proxy
-            .requestCreateItp(
-                name.to_string(),
-                symbol.to_string(),
-                description.to_string(),
-                methodology.to_string(),
-                price_u256,
-                max_order_size,
-                assets.to_vec(),
-                weights.to_vec(),
-            )
-            .estimate_gas()
+        match self
+            .simulate_create_itp(name, symbol, description, methodology, initial_price, max_order_size, assets, weights)
             .await
         {
-            Ok(gas) => {
+            Ok(simulation) => {
                 // Add 20% buffer
-                let gas_with_buffer = gas * 120 / 100;
+                let gas_with_buffer = simulation.gas_estimate * 120 / 100;
                 debug!(
-                    estimated = gas,
+                    estimated = simulation.gas_estimate,
                     with_buffer = gas_with_buffer,
+                    block_number = simulation.block_number,
                     "Gas estimation successful"
                 );
                 Ok(gas_with_buffer)
             }
-            Err(e) => {
+            Err(SimulationError::Reverted(reason)) => {
+                warn!(reason = %reason, "requestCreateItp would revert, not sending");
+                Err(ItpCreationError::SimulationReverted(reason))
+            }
+            Err(SimulationError::Provider(e)) => {
                 warn!(
                     error = %e,
                     fallback = DEFAULT_GAS_LIMIT,
@@ -409,6 +574,68 @@ impl ItpCreationService {
         }
     }
 
+    /// Dry run of requestCreateItp with the exact calldata that would be sent
+    pub async fn simulate_create_itp(
+        &self,
+        name: &str,
+        symbol: &str,
+        description: &str,
+        methodology: &str,
+        initial_price: u64,
+        max_order_size: u128,
+        assets: &[u128],
+        weights: &[u128],
+    ) -> Result<Simulation, SimulationError> {
+        let calldata = IBridgeProxy::requestCreateItpCall {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            description: description.to_string(),
+            methodology: methodology.to_string(),
+            initialPrice: U256::from(initial_price),
+            maxOrderSize: max_order_size,
+            assets: assets.to_vec(),
+            weights: weights.to_vec(),
+        }
+        .abi_encode();
+        self.simulate(calldata).await
+    }
+
+    /// Dry run of requestRebalance with the exact calldata that would be sent
+    pub async fn simulate_rebalance(
+        &self,
+        orbit_itp: Address,
+        assets: &[u128],
+        weights: &[u128],
+    ) -> Result<Simulation, SimulationError> {
+        let calldata = IBridgeProxy::requestRebalanceCall {
+            orbitItp: orbit_itp,
+            assets: assets.to_vec(),
+            weights: weights.to_vec(),
+        }
+        .abi_encode();
+        self.simulate(calldata).await
+    }
+
+    async fn simulate(&self, calldata: Vec<u8>) -> Result<Simulation, SimulationError> {
+        let from = self.wallet.default_signer().address();
+        tx_simulator::simulate(&self.provider, from, self.bridge_proxy_address, calldata.into()).await
+    }
+
+    /// Record a mined transaction's gas spend, if spend recording is on
+    ///
+    /// The transaction is already paid for, so a failure to record it is
+    /// logged rather than failing the request.
+    async fn record_spend(&self, kind: &'static str, reference: String, receipt: &TransactionReceipt) {
+        let Some(db) = &self.spend_db else {
+            return;
+        };
+        let tx = ChainTransaction::from_receipt(kind, Some(reference), self.chain_id, receipt);
+        let tx_hash = tx.tx_hash.clone();
+        if let Err(e) = chain_spend::record(db, tx, chrono::Utc::now().naive_utc()).await {
+            error!(tx_hash = %tx_hash, error = %e, "Failed to record transaction spend");
+        }
+    }
+
     /// Parse CreateItpRequested event from transaction logs
     fn parse_create_itp_requested_event(
         &self,
@@ -592,6 +819,7 @@ mod tests {
         let result = ItpCreationResult {
             tx_hash: "0x123".to_string(),
             nonce: 42,
+            confirmed_at_block: 100,
         };
         let cloned = result.clone();
         assert_eq!(cloned.tx_hash, "0x123");
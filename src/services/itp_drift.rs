@@ -0,0 +1,373 @@
+//! Composition drift of deployed ITPs
+//!
+//! Between rebalances an ITP holds fixed quantities, so its weights drift
+//! away from the targets as prices move. A constituent's current weight is
+//! its target weight grown by its price return since the base date,
+//! normalized over the ITP:
+//!
+//! ```text
+//! current_i = w_i * (P_i / B_i) / sum_j(w_j * P_j / B_j)
+//! ```
+//!
+//! Targets are the weights stored in itps and the base date is the ITP's
+//! creation date; P and B are the latest and base-date daily prices from
+//! coins_historical_prices. Each run records one row per active ITP per day
+//! in itp_drift_checks. An ITP whose largest constituent drift reaches the
+//! threshold is flagged and logged as due for a rebalance.
+//!
+//! Configuration (environment):
+//! - `ITP_DRIFT_THRESHOLD_BPS` - alert threshold on the largest drift (default 500 = 5%)
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::entities::{coins_historical_prices, itp_drift_checks, itps, prelude::*};
+use crate::models::itp_drift::{ItpDriftConstituent, ItpDriftResponse};
+use crate::services::{itp_listing, price_utils};
+
+const ENV_THRESHOLD_BPS: &str = "ITP_DRIFT_THRESHOLD_BPS";
+
+const DEFAULT_THRESHOLD_BPS: u32 = 500;
+
+/// Prices older than this many days before the check (or base) date aren't used
+const PRICE_LOOKBACK_DAYS: i64 = 7;
+
+/// itps.state of an active ITP
+const STATE_ACTIVE: i16 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftConfig {
+    pub threshold_bps: u32,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self { threshold_bps: DEFAULT_THRESHOLD_BPS }
+    }
+}
+
+impl DriftConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(std::env::var(ENV_THRESHOLD_BPS).ok().as_deref())
+    }
+
+    /// Build from raw setting values; invalid values fall back to the defaults
+    fn from_values(threshold_bps: Option<&str>) -> Self {
+        match threshold_bps.map(|v| v.trim().parse::<u32>()) {
+            Some(Ok(v)) if v > 0 && v <= 10_000 => Self { threshold_bps: v },
+            Some(_) => {
+                tracing::warn!("Invalid {}, using {}", ENV_THRESHOLD_BPS, DEFAULT_THRESHOLD_BPS);
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+}
+
+/// Target weight and prices of one constituent
+#[derive(Debug, Clone)]
+pub struct ConstituentPrices {
+    pub symbol: String,
+    pub target_weight: f64,
+    pub base_price: Decimal,
+    pub price: Decimal,
+}
+
+/// Drift of a whole ITP
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub constituents: Vec<ItpDriftConstituent>,
+    /// Largest |drift_bps| of a constituent
+    pub max_drift_bps: i32,
+    /// Half the sum of |drift|: the share of value a rebalance would move
+    pub total_drift_bps: i32,
+}
+
+fn to_bps(fraction: f64) -> i32 {
+    (fraction * 10_000.0).round() as i32
+}
+
+/// Current weights and drift of `constituents` from their targets
+///
+/// Target weights are normalized to sum to 1. None if they don't sum to a
+/// positive value or a base price isn't positive.
+pub fn compute_drift(constituents: &[ConstituentPrices]) -> Option<Drift> {
+    let target_total: f64 = constituents.iter().map(|c| c.target_weight).sum();
+    if target_total <= 0.0 || constituents.iter().any(|c| c.base_price <= Decimal::ZERO) {
+        return None;
+    }
+
+    let grown: Vec<f64> = constituents
+        .iter()
+        .map(|c| c.target_weight / target_total * (c.price / c.base_price).to_f64().unwrap_or(0.0))
+        .collect();
+    let grown_total: f64 = grown.iter().sum();
+    if grown_total <= 0.0 {
+        return None;
+    }
+
+    let mut total_drift = 0.0;
+    let constituents: Vec<ItpDriftConstituent> = constituents
+        .iter()
+        .zip(&grown)
+        .map(|(c, grown)| {
+            let target_weight = c.target_weight / target_total;
+            let current_weight = grown / grown_total;
+            total_drift += (current_weight - target_weight).abs();
+            ItpDriftConstituent {
+                symbol: c.symbol.clone(),
+                target_weight,
+                current_weight,
+                drift_bps: to_bps(current_weight - target_weight),
+                base_price: c.base_price,
+                price: c.price,
+            }
+        })
+        .collect();
+
+    Some(Drift {
+        max_drift_bps: constituents.iter().map(|c| c.drift_bps.abs()).max().unwrap_or(0),
+        total_drift_bps: to_bps(total_drift / 2.0),
+        constituents,
+    })
+}
+
+/// Latest price per coin on or before `on`, within the lookback
+async fn prices_by_coin(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    on: NaiveDate,
+) -> Result<HashMap<String, Decimal>, sea_orm::DbErr> {
+    let rows = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.is_in(coin_ids.to_vec()))
+        .filter(coins_historical_prices::Column::Date.gte(on - chrono::Duration::days(PRICE_LOOKBACK_DAYS)))
+        .filter(coins_historical_prices::Column::Date.lte(on))
+        .order_by_asc(coins_historical_prices::Column::Date)
+        .all(db)
+        .await?;
+
+    // Ascending by date, so later rows overwrite earlier ones
+    Ok(rows.into_iter().map(|row| (row.coin_id, row.price)).collect())
+}
+
+/// Outcome of one drift run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftSummary {
+    pub itps: usize,
+    pub checked: usize,
+    /// Without target weights or prices for every constituent
+    pub incomplete: usize,
+    pub flagged: usize,
+    pub max_drift_bps: i32,
+}
+
+/// Check the drift of every active ITP as of `today`
+pub async fn run(
+    db: &DatabaseConnection,
+    config: &DriftConfig,
+    today: NaiveDate,
+) -> Result<DriftSummary, sea_orm::DbErr> {
+    let active = Itps::find().filter(itps::Column::State.eq(STATE_ACTIVE)).all(db).await?;
+    let mut summary = DriftSummary { itps: active.len(), ..Default::default() };
+
+    for itp in &active {
+        let Some(drift) = check_itp(db, itp, today).await? else {
+            tracing::debug!(itp = %itp.orbit_address, "Skipping drift check, missing weights or prices");
+            summary.incomplete += 1;
+            continue;
+        };
+        let base_date = itp.created_at.map(|at| at.date_naive()).unwrap_or(today);
+        let flagged = drift.max_drift_bps >= config.threshold_bps as i32;
+
+        if flagged {
+            tracing::warn!(
+                itp = %itp.orbit_address,
+                symbol = %itp.symbol,
+                base_date = %base_date,
+                max_drift_bps = drift.max_drift_bps,
+                total_drift_bps = drift.total_drift_bps,
+                threshold_bps = config.threshold_bps,
+                "ITP composition drifted past threshold, rebalance due"
+            );
+        }
+
+        record_check(db, itp.id, today, base_date, &drift, config.threshold_bps, flagged).await?;
+
+        summary.checked += 1;
+        summary.max_drift_bps = summary.max_drift_bps.max(drift.max_drift_bps);
+        if flagged {
+            summary.flagged += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Drift of one ITP, or None if a weight or price is missing
+async fn check_itp(
+    db: &DatabaseConnection,
+    itp: &itps::Model,
+    today: NaiveDate,
+) -> Result<Option<Drift>, sea_orm::DbErr> {
+    let (Some(targets), Some(created_at)) = (itp_listing::target_weights(itp), itp.created_at) else {
+        return Ok(None);
+    };
+
+    let symbols: Vec<String> = targets.iter().map(|(symbol, _)| symbol.clone()).collect();
+    let latest = price_utils::latest_symbol_prices(db, &symbols, today, PRICE_LOOKBACK_DAYS).await?;
+    let coin_ids: Vec<String> = latest.values().map(|p| p.coin_id.clone()).collect();
+    let base_prices = prices_by_coin(db, &coin_ids, created_at.date_naive()).await?;
+
+    let mut constituents = Vec::with_capacity(targets.len());
+    for (symbol, target_weight) in targets {
+        let Some(latest) = latest.get(&symbol) else {
+            return Ok(None);
+        };
+        let Some(base_price) = base_prices.get(&latest.coin_id) else {
+            return Ok(None);
+        };
+        constituents.push(ConstituentPrices { symbol, target_weight, base_price: *base_price, price: latest.price });
+    }
+
+    Ok(compute_drift(&constituents))
+}
+
+async fn record_check(
+    db: &DatabaseConnection,
+    itp_id: i32,
+    date: NaiveDate,
+    base_date: NaiveDate,
+    drift: &Drift,
+    threshold_bps: u32,
+    flagged: bool,
+) -> Result<(), sea_orm::DbErr> {
+    let check = itp_drift_checks::ActiveModel {
+        itp_id: Set(itp_id),
+        date: Set(date),
+        base_date: Set(base_date),
+        max_drift_bps: Set(drift.max_drift_bps),
+        total_drift_bps: Set(drift.total_drift_bps),
+        threshold_bps: Set(threshold_bps as i32),
+        flagged: Set(flagged),
+        constituents: Set(serde_json::json!(drift.constituents)),
+        checked_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    ItpDriftChecks::insert(check)
+        .on_conflict(
+            OnConflict::columns([itp_drift_checks::Column::ItpId, itp_drift_checks::Column::Date])
+                .update_columns([
+                    itp_drift_checks::Column::BaseDate,
+                    itp_drift_checks::Column::MaxDriftBps,
+                    itp_drift_checks::Column::TotalDriftBps,
+                    itp_drift_checks::Column::ThresholdBps,
+                    itp_drift_checks::Column::Flagged,
+                    itp_drift_checks::Column::Constituents,
+                    itp_drift_checks::Column::CheckedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Most recent drift check of an ITP
+pub async fn latest_check(
+    db: &DatabaseConnection,
+    itp: &itps::Model,
+) -> Result<Option<ItpDriftResponse>, sea_orm::DbErr> {
+    let check = ItpDriftChecks::find()
+        .filter(itp_drift_checks::Column::ItpId.eq(itp.id))
+        .order_by_desc(itp_drift_checks::Column::Date)
+        .one(db)
+        .await?;
+
+    Ok(check.map(|check| ItpDriftResponse {
+        orbit_address: itp.orbit_address.clone(),
+        symbol: itp.symbol.clone(),
+        date: check.date,
+        base_date: check.base_date,
+        max_drift_bps: check.max_drift_bps,
+        total_drift_bps: check.total_drift_bps,
+        threshold_bps: check.threshold_bps,
+        rebalance_due: check.flagged,
+        checked_at: check.checked_at,
+        constituents: serde_json::from_value(check.constituents).unwrap_or_default(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn constituent(symbol: &str, target_weight: f64, base_price: Decimal, price: Decimal) -> ConstituentPrices {
+        ConstituentPrices { symbol: symbol.to_string(), target_weight, base_price, price }
+    }
+
+    #[test]
+    fn test_config_from_values() {
+        assert_eq!(DriftConfig::from_values(None), DriftConfig::default());
+        assert_eq!(DriftConfig::from_values(Some(" 250 ")).threshold_bps, 250);
+        assert_eq!(DriftConfig::from_values(Some("0")), DriftConfig::default());
+        assert_eq!(DriftConfig::from_values(Some("20000")), DriftConfig::default());
+    }
+
+    #[test]
+    fn test_no_price_change_has_no_drift() {
+        let drift = compute_drift(&[
+            constituent("BTC", 0.6, dec!(50000), dec!(50000)),
+            constituent("ETH", 0.4, dec!(3000), dec!(3000)),
+        ])
+        .unwrap();
+        assert_eq!(drift.max_drift_bps, 0);
+        assert_eq!(drift.total_drift_bps, 0);
+
+        // A uniform move doesn't change the weights either
+        let drift = compute_drift(&[
+            constituent("BTC", 0.6, dec!(50000), dec!(100000)),
+            constituent("ETH", 0.4, dec!(3000), dec!(6000)),
+        ])
+        .unwrap();
+        assert_eq!(drift.max_drift_bps, 0);
+    }
+
+    #[test]
+    fn test_drift_from_relative_moves() {
+        // BTC doubles, ETH flat: 0.5*2 / (0.5*2 + 0.5) = 2/3
+        let drift = compute_drift(&[
+            constituent("BTC", 0.5, dec!(50000), dec!(100000)),
+            constituent("ETH", 0.5, dec!(3000), dec!(3000)),
+        ])
+        .unwrap();
+        assert_eq!(drift.constituents[0].drift_bps, 1667);
+        assert_eq!(drift.constituents[1].drift_bps, -1667);
+        assert_eq!(drift.max_drift_bps, 1667);
+        assert_eq!(drift.total_drift_bps, 1667);
+        assert!((drift.constituents[0].current_weight - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_targets_are_normalized() {
+        let drift = compute_drift(&[
+            constituent("BTC", 60.0, dec!(1), dec!(1)),
+            constituent("ETH", 40.0, dec!(1), dec!(1)),
+        ])
+        .unwrap();
+        assert!((drift.constituents[0].target_weight - 0.6).abs() < 1e-12);
+        assert_eq!(drift.max_drift_bps, 0);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert_eq!(compute_drift(&[]), None);
+        assert_eq!(compute_drift(&[constituent("BTC", 1.0, dec!(0), dec!(1))]), None);
+        assert_eq!(compute_drift(&[constituent("BTC", 0.0, dec!(1), dec!(1))]), None);
+    }
+}
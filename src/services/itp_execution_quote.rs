@@ -0,0 +1,275 @@
+//! Execution cost estimate for minting an ITP
+//!
+//! Minting buys every constituent with its weight's share of the notional.
+//! Each leg goes to the constituent's preferred exchange pair (see
+//! ExchangeApiService::get_tradeable_tokens) and walks that pair's asks:
+//! slippage is the average fill price above the book's mid, and fees are the
+//! exchange's base taker fee on the amount spent. Legs the fetched depth can't
+//! fully absorb are reported as such, so large orders can be flagged before
+//! they are sent. Each leg also reports its size relative to the pair's own
+//! 24h volume (services::pair_volumes), a gauge of market impact beyond the
+//! fetched depth.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use futures_util::future::join_all;
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::entities::itps;
+use crate::models::itp_execution_quote::{ExecutionQuoteLeg, ExecutionQuoteResponse, UnquotedLeg};
+use crate::services::exchange_api::{ExchangeApiService, OrderBookDepth};
+use crate::services::{itp_listing, pair_volumes};
+
+/// Order book levels fetched per leg
+const ORDER_BOOK_LEVELS: usize = 100;
+
+/// Largest notional accepted, in USD
+pub const MAX_NOTIONAL_USD: f64 = 1_000_000_000.0;
+
+/// Base-tier spot taker fee on Binance and Bitget, in basis points
+const TAKER_FEE_BPS: f64 = 10.0;
+
+#[derive(Debug)]
+pub enum ExecutionQuoteError {
+    InvalidNotional,
+    /// The ITP has no usable target weights
+    MissingWeights,
+    Exchange(String),
+    Database(DbErr),
+}
+
+impl std::fmt::Display for ExecutionQuoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionQuoteError::InvalidNotional => {
+                write!(f, "notional must be positive and at most {} USD", MAX_NOTIONAL_USD)
+            }
+            ExecutionQuoteError::MissingWeights => write!(f, "ITP has no target weights"),
+            ExecutionQuoteError::Exchange(e) => write!(f, "Exchange error: {}", e),
+            ExecutionQuoteError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionQuoteError {}
+
+impl From<DbErr> for ExecutionQuoteError {
+    fn from(e: DbErr) -> Self {
+        ExecutionQuoteError::Database(e)
+    }
+}
+
+/// Cost of spending `notional` USD on the asks of `book`
+pub fn quote_leg(symbol: &str, weight: f64, notional: f64, book: &OrderBookDepth) -> Result<ExecutionQuoteLeg, String> {
+    let asks: Vec<(f64, f64)> = book.asks.iter().copied().filter(|(p, q)| *p > 0.0 && *q > 0.0).collect();
+    let Some(&(best_ask, _)) = asks.first() else {
+        return Err(format!("No asks on {} {}", book.exchange, book.trading_pair));
+    };
+    let mid_price = match book.bids.first() {
+        Some(&(best_bid, _)) if best_bid > 0.0 && best_bid < best_ask => (best_bid + best_ask) / 2.0,
+        _ => best_ask,
+    };
+
+    let mut spent = 0.0;
+    let mut quantity = 0.0;
+    for (price, qty) in &asks {
+        let take = (notional - spent).min(price * qty);
+        if take <= 0.0 {
+            break;
+        }
+        spent += take;
+        quantity += take / price;
+    }
+
+    let avg_price = spent / quantity;
+    let fee_bps = TAKER_FEE_BPS;
+    Ok(ExecutionQuoteLeg {
+        symbol: symbol.to_string(),
+        weight,
+        exchange: book.exchange.clone(),
+        trading_pair: book.trading_pair.clone(),
+        notional,
+        filled_notional: spent,
+        quantity,
+        mid_price,
+        avg_price,
+        slippage_bps: (avg_price - mid_price) / mid_price * 10_000.0,
+        slippage_usd: spent - quantity * mid_price,
+        fee_bps,
+        fee_usd: spent * fee_bps / 10_000.0,
+        ask_depth_usd: asks.iter().map(|(p, q)| p * q).sum(),
+        // Tolerate float rounding on the last level
+        fully_filled: spent >= notional * (1.0 - 1e-9),
+        volume_24h_usd: None,
+        volume_share_pct: None,
+    })
+}
+
+/// Totals over the quoted legs of the ITP at `orbit_address`
+pub fn summarize(
+    orbit_address: &str,
+    symbol: &str,
+    notional: f64,
+    legs: Vec<ExecutionQuoteLeg>,
+    unquoted: Vec<UnquotedLeg>,
+) -> ExecutionQuoteResponse {
+    let total_slippage_usd: f64 = legs.iter().map(|l| l.slippage_usd).sum();
+    let total_fees_usd: f64 = legs.iter().map(|l| l.fee_usd).sum();
+    let total_cost_usd = total_slippage_usd + total_fees_usd;
+    let filled: f64 = legs.iter().map(|l| l.filled_notional).sum();
+
+    ExecutionQuoteResponse {
+        orbit_address: orbit_address.to_string(),
+        symbol: symbol.to_string(),
+        notional,
+        fully_fillable: unquoted.is_empty() && legs.iter().all(|l| l.fully_filled),
+        total_cost_bps: if filled > 0.0 { total_cost_usd / filled * 10_000.0 } else { 0.0 },
+        legs,
+        unquoted,
+        total_slippage_usd,
+        total_fees_usd,
+        total_cost_usd,
+    }
+}
+
+/// Set `leg`'s pair volume and the share of it the leg's notional takes
+pub fn with_volume(mut leg: ExecutionQuoteLeg, volume_24h_usd: Option<f64>) -> ExecutionQuoteLeg {
+    leg.volume_24h_usd = volume_24h_usd;
+    leg.volume_share_pct = volume_24h_usd.filter(|v| *v > 0.0).map(|v| leg.notional / v * 100.0);
+    leg
+}
+
+/// Estimate the cost of minting `notional` USD of `itp` from live order books
+pub async fn quote(
+    db: &DatabaseConnection,
+    exchange_api: &ExchangeApiService,
+    itp: &itps::Model,
+    notional: f64,
+) -> Result<ExecutionQuoteResponse, ExecutionQuoteError> {
+    if !notional.is_finite() || notional <= 0.0 || notional > MAX_NOTIONAL_USD {
+        return Err(ExecutionQuoteError::InvalidNotional);
+    }
+    let targets = itp_listing::target_weights(itp).ok_or(ExecutionQuoteError::MissingWeights)?;
+    let total_weight: f64 = targets.iter().map(|(_, w)| w).sum();
+    if total_weight <= 0.0 {
+        return Err(ExecutionQuoteError::MissingWeights);
+    }
+
+    let symbols: Vec<String> = targets.iter().map(|(symbol, _)| symbol.clone()).collect();
+    let tokens: HashMap<String, _> = exchange_api
+        .get_tradeable_tokens(symbols)
+        .await
+        .map_err(|e| ExecutionQuoteError::Exchange(e.to_string()))?
+        .into_iter()
+        .map(|token| (token.symbol.clone(), token))
+        .collect();
+
+    let books = join_all(targets.iter().map(|(symbol, _)| async {
+        let token = tokens.get(symbol).ok_or("Not tradeable on Binance or Bitget".to_string())?;
+        exchange_api
+            .get_order_book(&token.exchange, &token.symbol, &token.trading_pair, ORDER_BOOK_LEVELS)
+            .await
+            .map_err(|e| format!("Order book unavailable: {}", e))
+    }))
+    .await;
+
+    let today = Utc::now().date_naive();
+    let mut legs = Vec::new();
+    let mut unquoted = Vec::new();
+    for ((symbol, weight), book) in targets.into_iter().zip(books) {
+        let weight = weight / total_weight;
+        match book.and_then(|book| quote_leg(&symbol, weight, notional * weight, &book)) {
+            Ok(leg) => {
+                let token = &tokens[&symbol];
+                let volume = pair_volumes::volume_on(db, &token.exchange, &token.symbol, &token.trading_pair, today)
+                    .await?
+                    .and_then(|v| v.to_f64());
+                legs.push(with_volume(leg, volume));
+            }
+            Err(reason) => {
+                tracing::debug!(itp = %itp.orbit_address, symbol = %symbol, reason = %reason, "Leg not quoted");
+                unquoted.push(UnquotedLeg { symbol, weight, reason });
+            }
+        }
+    }
+
+    Ok(summarize(&itp.orbit_address, &itp.symbol, notional, legs, unquoted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> OrderBookDepth {
+        OrderBookDepth {
+            exchange: "bitget".to_string(),
+            trading_pair: "BTCUSDT".to_string(),
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_leg_within_top_level() {
+        let leg = quote_leg("BTC", 0.5, 500.0, &book(&[(99.0, 1.0)], &[(101.0, 10.0)])).unwrap();
+        assert!(leg.fully_filled);
+        assert_eq!(leg.mid_price, 100.0);
+        assert_eq!(leg.avg_price, 101.0);
+        // Half the spread
+        assert!((leg.slippage_bps - 100.0).abs() < 1e-9);
+        assert!((leg.slippage_usd - 500.0 * (1.0 - 100.0 / 101.0)).abs() < 1e-9);
+        assert!((leg.fee_usd - 0.5).abs() < 1e-12);
+        assert_eq!(leg.ask_depth_usd, 1010.0);
+    }
+
+    #[test]
+    fn test_leg_walks_the_book() {
+        let asks = [(100.0, 1.0), (110.0, 1.0), (120.0, 1.0)];
+        let leg = quote_leg("BTC", 1.0, 155.0, &book(&[(100.0, 1.0)], &asks)).unwrap();
+        assert!(leg.fully_filled);
+        assert!((leg.quantity - 1.5).abs() < 1e-12);
+        assert!((leg.avg_price - 155.0 / 1.5).abs() < 1e-9);
+
+        // More than the whole book
+        let leg = quote_leg("BTC", 1.0, 1_000.0, &book(&[], &asks)).unwrap();
+        assert!(!leg.fully_filled);
+        assert_eq!(leg.filled_notional, 330.0);
+        assert_eq!(leg.mid_price, 100.0);
+    }
+
+    #[test]
+    fn test_leg_without_asks() {
+        assert!(quote_leg("BTC", 1.0, 100.0, &book(&[(99.0, 1.0)], &[])).is_err());
+        assert!(quote_leg("BTC", 1.0, 100.0, &book(&[], &[(0.0, 5.0)])).is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        let legs = vec![
+            quote_leg("BTC", 0.5, 500.0, &book(&[(99.0, 1.0)], &[(101.0, 10.0)])).unwrap(),
+            quote_leg("ETH", 0.5, 500.0, &book(&[(100.0, 1.0)], &[(100.0, 10.0)])).unwrap(),
+        ];
+        let expected_cost = legs[0].slippage_usd + legs[0].fee_usd + legs[1].fee_usd;
+
+        let summary = summarize("0x01", "TEST", 1_000.0, legs.clone(), Vec::new());
+        assert!(summary.fully_fillable);
+        assert!((summary.total_cost_usd - expected_cost).abs() < 1e-9);
+        assert!((summary.total_cost_bps - expected_cost / 1_000.0 * 10_000.0).abs() < 1e-9);
+
+        let unquoted = vec![UnquotedLeg { symbol: "SOL".to_string(), weight: 0.1, reason: "none".to_string() }];
+        assert!(!summarize("0x01", "TEST", 1_000.0, legs, unquoted).fully_fillable);
+    }
+
+    #[test]
+    fn test_leg_volume_share() {
+        let leg = quote_leg("BTC", 1.0, 500.0, &book(&[(99.0, 1.0)], &[(101.0, 10.0)])).unwrap();
+        assert_eq!(leg.volume_share_pct, None);
+
+        let leg = with_volume(leg, Some(100_000.0));
+        assert_eq!(leg.volume_24h_usd, Some(100_000.0));
+        assert!((leg.volume_share_pct.unwrap() - 0.5).abs() < 1e-12);
+        assert_eq!(with_volume(leg, Some(0.0)).volume_share_pct, None);
+    }
+}
@@ -162,6 +162,29 @@ impl ItpListingService {
     }
 }
 
+/// ITP with this Orbit or Arbitrum address (case-insensitive)
+pub async fn find_by_address(db: &DatabaseConnection, address: &str) -> Result<Option<itps::Model>, sea_orm::DbErr> {
+    let address = address.to_lowercase();
+    Itps::find()
+        .filter(
+            Condition::any()
+                .add(Expr::expr(Func::lower(Expr::col(itps::Column::OrbitAddress))).eq(address.clone()))
+                .add(Expr::expr(Func::lower(Expr::col(itps::Column::ArbitrumAddress))).eq(address)),
+        )
+        .one(db)
+        .await
+}
+
+/// (uppercase symbol, target weight) pairs stored for an ITP
+pub fn target_weights(itp: &itps::Model) -> Option<Vec<(String, f64)>> {
+    let assets: Vec<String> = serde_json::from_value(itp.assets.clone()?).ok()?;
+    let weights: Vec<f64> = serde_json::from_value(itp.weights.clone()?).ok()?;
+    if assets.is_empty() || assets.len() != weights.len() {
+        return None;
+    }
+    Some(assets.into_iter().map(|a| a.to_uppercase()).zip(weights).collect())
+}
+
 /// Get base prices for assets at a specific date from historical data
 async fn fetch_base_prices_at_date(
     db: &DatabaseConnection,
@@ -0,0 +1,417 @@
+//! ITP order tracking
+//!
+//! A wallet records its intent to mint or redeem an ITP with
+//! POST /api/itp/{address}/orders. The order indexer (jobs::itp_order_indexer)
+//! then follows it through the ITP's events in blockchain_events, which the
+//! relayer ingests from chain:
+//!
+//! | side   | pending -> filled | filled -> settled |
+//! |--------|-------------------|-------------------|
+//! | mint   | `deposit`         | `mint`            |
+//! | redeem | `burn`            | `withdraw`        |
+//!
+//! Events match an order when they are emitted by the ITP (Orbit or
+//! Arbitrum address) for the order's wallet. A fill must be in the order's
+//! submission transaction when one was given, otherwise no older than the
+//! order (minus a little clock skew); a settlement must be on the fill's
+//! network, at or after its block. Orders take the earliest matching event,
+//! oldest order first, and every event advances at most one order.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set,
+};
+
+use crate::entities::{blockchain_events, itp_orders, itps, prelude::*};
+use crate::models::itp_order::{CreateItpOrderRequest, ItpOrderResponse};
+
+/// Status values for itp_orders.status
+pub mod statuses {
+    pub const PENDING: &str = "pending";
+    pub const FILLED: &str = "filled";
+    pub const SETTLED: &str = "settled";
+}
+
+/// Side values for itp_orders.side
+pub mod sides {
+    pub const MINT: &str = "mint";
+    pub const REDEEM: &str = "redeem";
+}
+
+/// How much older than its order a fill event may be (the order is often
+/// recorded just after the wallet sent its transaction)
+const FILL_SKEW_SECS: i64 = 600;
+
+/// blockchain_events types that fill and settle an order of `side`
+fn lifecycle_events(side: &str) -> Option<(&'static str, &'static str)> {
+    match side {
+        sides::MINT => Some(("deposit", "mint")),
+        sides::REDEEM => Some(("burn", "withdraw")),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub enum ItpOrderError {
+    InvalidSide(String),
+    InvalidAmount,
+    InvalidTxHash(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for ItpOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItpOrderError::InvalidSide(side) => write!(f, "Invalid side '{}', expected mint or redeem", side),
+            ItpOrderError::InvalidAmount => write!(f, "amount must be positive"),
+            ItpOrderError::InvalidTxHash(hash) => write!(f, "Invalid transaction hash '{}'", hash),
+            ItpOrderError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ItpOrderError {}
+
+impl From<sea_orm::DbErr> for ItpOrderError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        ItpOrderError::Database(e)
+    }
+}
+
+/// Lowercase 0x-prefixed 32-byte hash
+fn normalize_tx_hash(hash: &str) -> Result<String, ItpOrderError> {
+    let hash = hash.trim().to_lowercase();
+    let valid = hash.len() == 66
+        && hash.starts_with("0x")
+        && hash[2..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(ItpOrderError::InvalidTxHash(hash));
+    }
+    Ok(hash)
+}
+
+/// Record a new pending order of `wallet` on `itp`
+pub async fn create(
+    db: &DatabaseConnection,
+    itp: &itps::Model,
+    wallet: &str,
+    request: &CreateItpOrderRequest,
+) -> Result<ItpOrderResponse, ItpOrderError> {
+    let side = request.side.trim().to_lowercase();
+    if lifecycle_events(&side).is_none() {
+        return Err(ItpOrderError::InvalidSide(request.side.clone()));
+    }
+    if request.amount <= Decimal::ZERO {
+        return Err(ItpOrderError::InvalidAmount);
+    }
+    let submit_tx_hash = request.tx_hash.as_deref().map(normalize_tx_hash).transpose()?;
+
+    let now = Utc::now().naive_utc();
+    let order = itp_orders::ActiveModel {
+        order_id: Set(uuid::Uuid::new_v4().to_string()),
+        itp_id: Set(itp.id),
+        wallet_address: Set(wallet.to_lowercase()),
+        side: Set(side),
+        amount: Set(request.amount),
+        status: Set(statuses::PENDING.to_string()),
+        submit_tx_hash: Set(submit_tx_hash),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(ItpOrderResponse::new(order, &itp.orbit_address))
+}
+
+/// Order with this public id
+pub async fn find(db: &DatabaseConnection, order_id: &str) -> Result<Option<ItpOrderResponse>, sea_orm::DbErr> {
+    let Some(order) = ItpOrders::find()
+        .filter(itp_orders::Column::OrderId.eq(order_id.trim().to_lowercase()))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let itp = Itps::find_by_id(order.itp_id).one(db).await?;
+    let itp_address = itp.map(|itp| itp.orbit_address).unwrap_or_default();
+    Ok(Some(ItpOrderResponse::new(order, &itp_address)))
+}
+
+/// Orders of `wallet` on `itp`, newest first
+pub async fn list_for_wallet(
+    db: &DatabaseConnection,
+    itp: &itps::Model,
+    wallet: &str,
+) -> Result<Vec<ItpOrderResponse>, sea_orm::DbErr> {
+    let orders = ItpOrders::find()
+        .filter(itp_orders::Column::ItpId.eq(itp.id))
+        .filter(itp_orders::Column::WalletAddress.eq(wallet.to_lowercase()))
+        .order_by_desc(itp_orders::Column::CreatedAt)
+        .order_by_desc(itp_orders::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(orders.into_iter().map(|order| ItpOrderResponse::new(order, &itp.orbit_address)).collect())
+}
+
+fn event_time(event: &blockchain_events::Model) -> Option<NaiveDateTime> {
+    event.timestamp.map(|ts| ts.naive_utc())
+}
+
+/// Earliest unused event that fills `order`
+///
+/// `events` are the ITP's events for the order's wallet, ordered by block
+/// and log index.
+pub fn find_fill<'a>(
+    order: &itp_orders::Model,
+    events: &'a [blockchain_events::Model],
+    used: &HashSet<i32>,
+) -> Option<&'a blockchain_events::Model> {
+    let (fill_type, _) = lifecycle_events(&order.side)?;
+    let earliest = order.created_at - Duration::seconds(FILL_SKEW_SECS);
+
+    events.iter().find(|event| {
+        event.event_type == fill_type
+            && !used.contains(&event.id)
+            && match &order.submit_tx_hash {
+                Some(tx_hash) => event.tx_hash.eq_ignore_ascii_case(tx_hash),
+                None => event_time(event).is_some_and(|at| at >= earliest),
+            }
+    })
+}
+
+/// Earliest unused event that settles `order`, filled by `fill`
+pub fn find_settlement<'a>(
+    order: &itp_orders::Model,
+    fill: &blockchain_events::Model,
+    events: &'a [blockchain_events::Model],
+    used: &HashSet<i32>,
+) -> Option<&'a blockchain_events::Model> {
+    let (_, settle_type) = lifecycle_events(&order.side)?;
+
+    events.iter().find(|event| {
+        event.event_type == settle_type
+            && !used.contains(&event.id)
+            && event.network == fill.network
+            && event.block_number >= fill.block_number
+    })
+}
+
+/// Outcome of one indexer pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderIndexSummary {
+    /// Pending and filled orders looked at
+    pub open: usize,
+    pub filled: usize,
+    pub settled: usize,
+}
+
+/// Events of `itp` for `wallet`, ordered by block and log index
+async fn wallet_events(
+    db: &DatabaseConnection,
+    itp: &itps::Model,
+    wallet: &str,
+) -> Result<Vec<blockchain_events::Model>, sea_orm::DbErr> {
+    let addresses: Vec<String> = std::iter::once(&itp.orbit_address)
+        .chain(itp.arbitrum_address.as_ref())
+        .map(|address| address.to_lowercase())
+        .collect();
+
+    BlockchainEvents::find()
+        .filter(blockchain_events::Column::ContractAddress.is_in(addresses))
+        .filter(Expr::expr(Func::lower(Expr::col(blockchain_events::Column::UserAddress))).eq(wallet))
+        .order_by_asc(blockchain_events::Column::BlockNumber)
+        .order_by_asc(blockchain_events::Column::LogIndex)
+        .all(db)
+        .await
+}
+
+/// Advance every open order whose fill or settlement event has been indexed
+pub async fn index_orders(db: &DatabaseConnection) -> Result<OrderIndexSummary, sea_orm::DbErr> {
+    let open = ItpOrders::find()
+        .filter(itp_orders::Column::Status.is_in([statuses::PENDING, statuses::FILLED]))
+        .order_by_asc(itp_orders::Column::CreatedAt)
+        .order_by_asc(itp_orders::Column::Id)
+        .all(db)
+        .await?;
+    let mut summary = OrderIndexSummary { open: open.len(), ..Default::default() };
+    if open.is_empty() {
+        return Ok(summary);
+    }
+
+    // Events already consumed by any order of these wallets
+    let wallets: HashSet<String> = open.iter().map(|o| o.wallet_address.clone()).collect();
+    let mut used: HashSet<i32> = ItpOrders::find()
+        .filter(itp_orders::Column::WalletAddress.is_in(wallets))
+        .all(db)
+        .await?
+        .into_iter()
+        .flat_map(|o| [o.fill_event_id, o.settle_event_id])
+        .flatten()
+        .collect();
+
+    let itp_ids: HashSet<i32> = open.iter().map(|o| o.itp_id).collect();
+    let itps: HashMap<i32, itps::Model> = Itps::find()
+        .filter(itps::Column::Id.is_in(itp_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|itp| (itp.id, itp))
+        .collect();
+    let mut events: HashMap<(i32, String), Vec<blockchain_events::Model>> = HashMap::new();
+
+    for order in open {
+        let Some(itp) = itps.get(&order.itp_id) else {
+            continue;
+        };
+        let key = (order.itp_id, order.wallet_address.clone());
+        if let Entry::Vacant(slot) = events.entry(key.clone()) {
+            slot.insert(wallet_events(db, itp, &order.wallet_address).await?);
+        }
+        let events = &events[&key];
+
+        let fill = match order.fill_event_id {
+            Some(id) => events.iter().find(|e| e.id == id),
+            None => find_fill(&order, events, &used),
+        };
+        let Some(fill) = fill else {
+            continue;
+        };
+        let settlement = find_settlement(&order, fill, events, &used);
+
+        let now = Utc::now().naive_utc();
+        let newly_filled = order.fill_event_id.is_none();
+        let mut update = order.clone().into_active_model();
+        if newly_filled {
+            update.fill_event_id = Set(Some(fill.id));
+            update.fill_tx_hash = Set(Some(fill.tx_hash.clone()));
+            update.filled_at = Set(Some(event_time(fill).unwrap_or(now)));
+            update.status = Set(statuses::FILLED.to_string());
+            used.insert(fill.id);
+            summary.filled += 1;
+        }
+        if let Some(settlement) = settlement {
+            update.settle_event_id = Set(Some(settlement.id));
+            update.settle_tx_hash = Set(Some(settlement.tx_hash.clone()));
+            update.settled_at = Set(Some(event_time(settlement).unwrap_or(now)));
+            update.status = Set(statuses::SETTLED.to_string());
+            used.insert(settlement.id);
+            summary.settled += 1;
+        } else if !newly_filled {
+            continue;
+        }
+        update.updated_at = Set(now);
+        update.update(db).await?;
+
+        tracing::info!(
+            order_id = %order.order_id,
+            wallet = %order.wallet_address,
+            side = %order.side,
+            status = if settlement.is_some() { statuses::SETTLED } else { statuses::FILLED },
+            "ITP order advanced"
+        );
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, FixedOffset};
+
+    fn at(secs: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(1_750_000_000 + secs, 0).unwrap().naive_utc()
+    }
+
+    fn order(side: &str, submit_tx_hash: Option<&str>) -> itp_orders::Model {
+        itp_orders::Model {
+            id: 1,
+            order_id: "order".to_string(),
+            itp_id: 1,
+            wallet_address: "0xa11ce".to_string(),
+            side: side.to_string(),
+            amount: Decimal::ONE,
+            status: statuses::PENDING.to_string(),
+            submit_tx_hash: submit_tx_hash.map(str::to_string),
+            fill_event_id: None,
+            fill_tx_hash: None,
+            filled_at: None,
+            settle_event_id: None,
+            settle_tx_hash: None,
+            settled_at: None,
+            created_at: at(0),
+            updated_at: at(0),
+        }
+    }
+
+    fn event(id: i32, event_type: &str, tx_hash: &str, block_number: i32, secs: i64) -> blockchain_events::Model {
+        blockchain_events::Model {
+            id,
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            log_index: 0,
+            event_type: event_type.to_string(),
+            contract_address: "0xitp".to_string(),
+            network: "arbitrum".to_string(),
+            user_address: Some("0xa11ce".to_string()),
+            amount: None,
+            quantity: None,
+            timestamp: Some(at(secs).and_utc().with_timezone(&FixedOffset::east_opt(0).unwrap())),
+            raw_amount: None,
+            decimals: None,
+            raw_quantity: None,
+            quantity_decimals: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_tx_hash() {
+        let hash = format!("0x{}", "AB".repeat(32));
+        assert_eq!(normalize_tx_hash(&hash).unwrap(), hash.to_lowercase());
+        assert!(normalize_tx_hash("0x1234").is_err());
+        assert!(normalize_tx_hash(&format!("0x{}", "zz".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn test_fill_matches_side_time_and_tx() {
+        let events = vec![
+            event(1, "deposit", "0xold", 10, -FILL_SKEW_SECS - 1),
+            event(2, "burn", "0xburn", 11, 5),
+            event(3, "deposit", "0xnew", 12, 5),
+            event(4, "deposit", "0xsent", 13, 30),
+        ];
+        let none = HashSet::new();
+
+        // Events from before the order (beyond the skew) don't count
+        assert_eq!(find_fill(&order(sides::MINT, None), &events, &none).map(|e| e.id), Some(3));
+        assert_eq!(find_fill(&order(sides::REDEEM, None), &events, &none).map(|e| e.id), Some(2));
+        assert_eq!(find_fill(&order(sides::MINT, Some("0xSENT")), &events, &none).map(|e| e.id), Some(4));
+        assert_eq!(find_fill(&order(sides::MINT, None), &events, &HashSet::from([3])).map(|e| e.id), Some(4));
+        assert_eq!(find_fill(&order("swap", None), &events, &none), None);
+    }
+
+    #[test]
+    fn test_settlement_follows_fill() {
+        let fill = event(2, "deposit", "0xfill", 20, 5);
+        let mut other_network = event(4, "mint", "0xelsewhere", 30, 5);
+        other_network.network = "base".to_string();
+        let events = vec![
+            event(1, "mint", "0xearlier", 19, 0),
+            fill.clone(),
+            other_network,
+            event(5, "mint", "0xfill", 20, 5),
+        ];
+
+        let mint = order(sides::MINT, None);
+        assert_eq!(find_settlement(&mint, &fill, &events, &HashSet::new()).map(|e| e.id), Some(5));
+        assert_eq!(find_settlement(&mint, &fill, &events, &HashSet::from([5])), None);
+        assert_eq!(find_settlement(&order(sides::REDEEM, None), &fill, &events, &HashSet::new()), None);
+    }
+}
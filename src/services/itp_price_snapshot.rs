@@ -18,10 +18,14 @@ use std::str::FromStr;
 use tracing::{debug, error, info, warn};
 
 use crate::entities::{itp_price_history, itps, prelude::*};
+use crate::services::units;
 
 /// Default Orbit chain ID
 const ORBIT_CHAIN_ID: u64 = 111222333;
 
+/// Decimals of prices returned by Castle.getIndexPrice
+const PRICE_DECIMALS: u32 = 18;
+
 /// Maximum retry attempts for RPC calls
 const MAX_RETRIES: u32 = 3;
 
@@ -286,16 +290,11 @@ impl ItpPriceSnapshotService {
             })?
             ._0;
 
-        // Convert U256 to Decimal (assuming 18 decimals)
-        // Price is returned as raw U256, we need to convert to Decimal
+        // Castle prices are 18-decimal fixed point
         let price_str = price_u256.to_string();
-        let price = Decimal::from_str(&price_str)
+        let normalized_price = units::from_base_units(price_u256, PRICE_DECIMALS)
             .map_err(|e| ItpPriceSnapshotError::ContractCallError(format!("Invalid price: {}", e)))?;
 
-        // Normalize to 18 decimal places (divide by 10^18)
-        let divisor = Decimal::from_str("1000000000000000000").unwrap_or(Decimal::ONE);
-        let normalized_price = price / divisor;
-
         debug!(
             raw_price = %price_str,
             normalized = %normalized_price,
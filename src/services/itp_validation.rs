@@ -0,0 +1,225 @@
+//! Asset checks for ITP creation requests
+//!
+//! Beyond the shape checks in handlers::itp, every requested asset must:
+//! - exist in the asset registry (vendor/assets.json)
+//! - appear only once
+//! - be tradeable on Binance or Bitget (USDC/USDT pair, see ExchangeApiService)
+//! - not be on the ITP_EXCLUDED_ASSETS list (registry IDs or symbols)
+//! - have a weight within the composition limits (config::CompositionLimits)
+//!
+//! All violations are reported together so a request can be fixed in one go.
+
+use std::collections::HashSet;
+
+use asset_registry::{Asset, AssetRegistry};
+
+use crate::config::CompositionLimits;
+use crate::models::itp::ItpValidationViolation;
+use crate::services::exchange_api::ExchangeApiService;
+
+const ENV_EXCLUDED_ASSETS: &str = "ITP_EXCLUDED_ASSETS";
+
+#[derive(Debug, Clone, Default)]
+pub struct ItpValidationConfig {
+    /// Uppercased registry IDs and symbols that can't be used in an ITP
+    pub excluded_assets: HashSet<String>,
+}
+
+impl ItpValidationConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(std::env::var(ENV_EXCLUDED_ASSETS).ok().as_deref())
+    }
+
+    fn from_values(excluded_assets: Option<&str>) -> Self {
+        let excluded_assets = excluded_assets
+            .unwrap_or_default()
+            .split(',')
+            .map(|entry| entry.trim().to_uppercase())
+            .filter(|entry| !entry.is_empty())
+            .collect();
+
+        Self { excluded_assets }
+    }
+
+    fn is_excluded(&self, asset_id: u128, symbol: &str) -> bool {
+        self.excluded_assets.contains(&asset_id.to_string()) || self.excluded_assets.contains(symbol)
+    }
+}
+
+/// Base symbol of a registry asset ("BTCUSDC" -> "BTC")
+pub fn registry_symbol(asset: &Asset) -> String {
+    let bitget = &asset.bitget;
+    if bitget.ends_with("USDC") {
+        bitget.trim_end_matches("USDC").to_string()
+    } else if bitget.ends_with("USDT") {
+        bitget.trim_end_matches("USDT").to_string()
+    } else {
+        bitget.clone()
+    }
+}
+
+/// Check the requested assets against the registry, exchanges and config
+///
+/// `weights` must already match `asset_ids` in length; the asset count is
+/// checked by the caller. Fails only if the exchanges can't be queried.
+pub async fn validate_assets(
+    registry: &AssetRegistry,
+    exchange_api: &ExchangeApiService,
+    config: &ItpValidationConfig,
+    limits: &CompositionLimits,
+    asset_ids: &[u128],
+    weights: &[u128],
+) -> Result<Vec<ItpValidationViolation>, Box<dyn std::error::Error + Send + Sync>> {
+    let symbols: Vec<Option<String>> = asset_ids
+        .iter()
+        .map(|id| registry.by_id(*id).map(registry_symbol))
+        .collect();
+
+    let known: Vec<String> = symbols.iter().flatten().cloned().collect();
+    let tradeable: HashSet<String> = if known.is_empty() {
+        HashSet::new()
+    } else {
+        exchange_api
+            .get_tradeable_tokens(known)
+            .await?
+            .into_iter()
+            .map(|token| token.symbol)
+            .collect()
+    };
+
+    Ok(check_assets(asset_ids, &symbols, weights, &tradeable, config, limits))
+}
+
+/// Violations of the resolved assets; `symbols[i]` is None if `asset_ids[i]`
+/// isn't in the registry
+fn check_assets(
+    asset_ids: &[u128],
+    symbols: &[Option<String>],
+    weights: &[u128],
+    tradeable: &HashSet<String>,
+    config: &ItpValidationConfig,
+    limits: &CompositionLimits,
+) -> Vec<ItpValidationViolation> {
+    let mut violations = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, (&asset_id, symbol)) in asset_ids.iter().zip(symbols).enumerate() {
+        let mut violation = |code: &str, message: String| {
+            violations.push(ItpValidationViolation {
+                index,
+                asset_id,
+                symbol: symbol.clone(),
+                code: code.to_string(),
+                message,
+            })
+        };
+
+        if !seen.insert(asset_id) {
+            violation("DUPLICATE_ASSET", format!("Asset {} is listed more than once", asset_id));
+        }
+
+        match symbol {
+            None => violation("UNKNOWN_ASSET", format!("Asset {} is not in the asset registry", asset_id)),
+            Some(symbol) => {
+                if config.is_excluded(asset_id, symbol) {
+                    violation("EXCLUDED_ASSET", format!("{} is on the exclusion list", symbol));
+                }
+                if !tradeable.contains(symbol) {
+                    violation("NOT_TRADEABLE", format!("{} has no USDC/USDT pair on Binance or Bitget", symbol));
+                }
+            }
+        }
+
+        match weights.get(index) {
+            Some(&weight) if weight > limits.max_asset_weight_bps => violation(
+                "WEIGHT_ABOVE_CAP",
+                format!("Weight {} bps exceeds the {} bps cap", weight, limits.max_asset_weight_bps),
+            ),
+            Some(&weight) if weight < limits.min_asset_weight_bps => violation(
+                "WEIGHT_BELOW_MIN",
+                format!("Weight {} bps is below the {} bps minimum", weight, limits.min_asset_weight_bps),
+            ),
+            _ => {}
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(violations: &[ItpValidationViolation]) -> Vec<(usize, &str)> {
+        violations.iter().map(|v| (v.index, v.code.as_str())).collect()
+    }
+
+    #[test]
+    fn test_config_from_values() {
+        let config = ItpValidationConfig::from_values(Some(" usdt, 42 ,,"));
+        assert!(config.is_excluded(1, "USDT"));
+        assert!(config.is_excluded(42, "FOO"));
+        assert!(!config.is_excluded(1, "BTC"));
+
+        assert!(ItpValidationConfig::from_values(None).excluded_assets.is_empty());
+    }
+
+    #[test]
+    fn test_registry_symbol() {
+        let asset = |bitget: &str| Asset { id: 1, bitget: bitget.to_string() };
+        assert_eq!(registry_symbol(&asset("BTCUSDC")), "BTC");
+        assert_eq!(registry_symbol(&asset("ETHUSDT")), "ETH");
+        assert_eq!(registry_symbol(&asset("SOL")), "SOL");
+    }
+
+    #[test]
+    fn test_check_assets_reports_every_violation() {
+        let config = ItpValidationConfig::from_values(Some("USDT"));
+        let limits = CompositionLimits { max_asset_weight_bps: 4000, min_asset_weight_bps: 500, max_assets: None };
+        let tradeable: HashSet<String> = ["BTC", "ETH", "USDT"].iter().map(|s| s.to_string()).collect();
+        let symbols = vec![
+            Some("BTC".to_string()),
+            None,
+            Some("USDT".to_string()),
+            Some("DOGE".to_string()),
+            Some("BTC".to_string()),
+            Some("ETH".to_string()),
+        ];
+
+        let violations = check_assets(
+            &[1, 99, 3, 4, 1, 2],
+            &symbols,
+            &[5000, 1000, 1000, 1000, 1700, 300],
+            &tradeable,
+            &config,
+            &limits,
+        );
+        assert_eq!(
+            codes(&violations),
+            vec![
+                (0, "WEIGHT_ABOVE_CAP"),
+                (1, "UNKNOWN_ASSET"),
+                (2, "EXCLUDED_ASSET"),
+                (3, "NOT_TRADEABLE"),
+                (4, "DUPLICATE_ASSET"),
+                (5, "WEIGHT_BELOW_MIN"),
+            ]
+        );
+        assert_eq!(violations[1].symbol, None);
+    }
+
+    #[test]
+    fn test_check_assets_valid() {
+        let tradeable: HashSet<String> = ["BTC", "ETH"].iter().map(|s| s.to_string()).collect();
+        let symbols = vec![Some("BTC".to_string()), Some("ETH".to_string())];
+        let violations = check_assets(
+            &[1, 2],
+            &symbols,
+            &[6000, 4000],
+            &tradeable,
+            &ItpValidationConfig::default(),
+            &CompositionLimits::default(),
+        );
+        assert!(violations.is_empty());
+    }
+}
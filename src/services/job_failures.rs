@@ -0,0 +1,258 @@
+//! Dead-letter queue for failed job items
+//!
+//! Jobs call `record_failure` when a single coin/index fails instead of only
+//! logging. Items are retried by the job_failures_retry worker with exponential
+//! backoff until they succeed or exhaust MAX_ATTEMPTS; admins can requeue or
+//! discard them.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, Order, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+
+use crate::entities::{job_failures, prelude::*};
+
+/// Status values for job_failures.status
+pub mod statuses {
+    pub const PENDING: &str = "pending";
+    pub const RESOLVED: &str = "resolved";
+    pub const DISCARDED: &str = "discarded";
+    pub const EXHAUSTED: &str = "exhausted";
+}
+
+/// Entity type values for job_failures.entity_type
+pub mod entity_types {
+    pub const COIN: &str = "coin";
+    pub const INDEX: &str = "index";
+}
+
+/// Attempts (including the original failure) before an item is marked exhausted
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// Base delay for the first retry
+const BASE_RETRY_DELAY_SECS: i64 = 300; // 5 minutes
+
+/// Upper bound on the retry delay
+const MAX_RETRY_DELAY_SECS: i64 = 86400; // 24 hours
+
+/// Delay before the next retry after `attempt_count` failed attempts
+/// (5m, 10m, 20m, ... capped at 24h)
+pub fn retry_delay(attempt_count: i32) -> Duration {
+    let exponent = (attempt_count.max(1) - 1).min(20) as u32;
+    let secs = BASE_RETRY_DELAY_SECS.saturating_mul(2_i64.pow(exponent));
+    Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
+
+/// Record a failed item, or bump the attempt count of its open failure
+pub async fn record_failure(
+    db: &DatabaseConnection,
+    job_name: &str,
+    entity_type: &str,
+    entity_id: &str,
+    payload: Option<serde_json::Value>,
+    error: &str,
+) -> Result<job_failures::Model, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+
+    let existing = find_open(db, job_name, entity_id).await?;
+
+    let model = match existing {
+        Some(record) => {
+            let attempts = record.attempt_count + 1;
+            let mut active_model: job_failures::ActiveModel = record.into();
+            active_model.error = Set(error.to_string());
+            active_model.attempt_count = Set(attempts);
+            if payload.is_some() {
+                active_model.payload = Set(payload);
+            }
+            apply_schedule(&mut active_model, attempts, now);
+            active_model.updated_at = Set(now);
+            active_model.update(db).await?
+        }
+        None => {
+            let mut active_model = job_failures::ActiveModel {
+                job_name: Set(job_name.to_string()),
+                entity_type: Set(entity_type.to_string()),
+                entity_id: Set(entity_id.to_string()),
+                payload: Set(payload),
+                error: Set(error.to_string()),
+                attempt_count: Set(1),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            };
+            apply_schedule(&mut active_model, 1, now);
+            active_model.insert(db).await?
+        }
+    };
+
+    tracing::debug!(
+        "[{}] Recorded failure for {} {} (attempt {}): {}",
+        job_name,
+        entity_type,
+        entity_id,
+        model.attempt_count,
+        error
+    );
+
+    Ok(model)
+}
+
+fn apply_schedule(active_model: &mut job_failures::ActiveModel, attempts: i32, now: NaiveDateTime) {
+    if attempts >= MAX_ATTEMPTS {
+        active_model.status = Set(statuses::EXHAUSTED.to_string());
+        active_model.next_retry_at = Set(None);
+    } else {
+        active_model.status = Set(statuses::PENDING.to_string());
+        active_model.next_retry_at = Set(Some(now + retry_delay(attempts)));
+    }
+}
+
+/// Find the open (pending or exhausted) failure for a job item
+async fn find_open(
+    db: &DatabaseConnection,
+    job_name: &str,
+    entity_id: &str,
+) -> Result<Option<job_failures::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let record = JobFailures::find()
+        .filter(job_failures::Column::JobName.eq(job_name))
+        .filter(job_failures::Column::EntityId.eq(entity_id))
+        .filter(
+            Condition::any()
+                .add(job_failures::Column::Status.eq(statuses::PENDING))
+                .add(job_failures::Column::Status.eq(statuses::EXHAUSTED)),
+        )
+        .one(db)
+        .await?;
+    Ok(record)
+}
+
+/// Pending items whose retry time has come, oldest first
+pub async fn due_failures(
+    db: &DatabaseConnection,
+    limit: u64,
+) -> Result<Vec<job_failures::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let records = JobFailures::find()
+        .filter(job_failures::Column::Status.eq(statuses::PENDING))
+        .filter(job_failures::Column::NextRetryAt.lte(now))
+        .order_by(job_failures::Column::NextRetryAt, Order::Asc)
+        .limit(limit)
+        .all(db)
+        .await?;
+    Ok(records)
+}
+
+/// Mark an item as successfully retried
+pub async fn mark_resolved(
+    db: &DatabaseConnection,
+    record: job_failures::Model,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    set_status(db, record, statuses::RESOLVED, None).await.map(|_| ())
+}
+
+/// Record another failed retry attempt for an existing item
+pub async fn mark_retry_failed(
+    db: &DatabaseConnection,
+    record: job_failures::Model,
+    error: &str,
+) -> Result<job_failures::Model, Box<dyn std::error::Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let attempts = record.attempt_count + 1;
+    let mut active_model: job_failures::ActiveModel = record.into();
+    active_model.error = Set(error.to_string());
+    active_model.attempt_count = Set(attempts);
+    apply_schedule(&mut active_model, attempts, now);
+    active_model.updated_at = Set(now);
+    Ok(active_model.update(db).await?)
+}
+
+/// Put an item back in the queue for immediate retry (admin action)
+///
+/// The attempt count is reset, so a requeued item gets a fresh MAX_ATTEMPTS
+/// rather than being exhausted again by its first failed retry.
+pub async fn requeue(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Option<job_failures::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(record) = JobFailures::find_by_id(id).one(db).await? else {
+        return Ok(None);
+    };
+    let now = Utc::now().naive_utc();
+    let mut active_model: job_failures::ActiveModel = record.into();
+    active_model.status = Set(statuses::PENDING.to_string());
+    active_model.attempt_count = Set(0);
+    active_model.next_retry_at = Set(Some(now));
+    active_model.updated_at = Set(now);
+    Ok(Some(active_model.update(db).await?))
+}
+
+/// Drop an item from the queue without retrying it (admin action)
+pub async fn discard(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Option<job_failures::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(record) = JobFailures::find_by_id(id).one(db).await? else {
+        return Ok(None);
+    };
+    set_status(db, record, statuses::DISCARDED, None).await.map(Some)
+}
+
+async fn set_status(
+    db: &DatabaseConnection,
+    record: job_failures::Model,
+    status: &str,
+    next_retry_at: Option<NaiveDateTime>,
+) -> Result<job_failures::Model, Box<dyn std::error::Error + Send + Sync>> {
+    let mut active_model: job_failures::ActiveModel = record.into();
+    active_model.status = Set(status.to_string());
+    active_model.next_retry_at = Set(next_retry_at);
+    active_model.updated_at = Set(Utc::now().naive_utc());
+    Ok(active_model.update(db).await?)
+}
+
+/// List failures, optionally filtered by status and job, newest first
+pub async fn list(
+    db: &DatabaseConnection,
+    status: Option<&str>,
+    job_name: Option<&str>,
+    limit: u64,
+) -> Result<Vec<job_failures::Model>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut query = JobFailures::find();
+    if let Some(status) = status {
+        query = query.filter(job_failures::Column::Status.eq(status));
+    }
+    if let Some(job_name) = job_name {
+        query = query.filter(job_failures::Column::JobName.eq(job_name));
+    }
+    let records = query
+        .order_by(job_failures::Column::UpdatedAt, Order::Desc)
+        .limit(limit)
+        .all(db)
+        .await?;
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), Duration::minutes(5));
+        assert_eq!(retry_delay(2), Duration::minutes(10));
+        assert_eq!(retry_delay(3), Duration::minutes(20));
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(20), Duration::hours(24));
+        assert_eq!(retry_delay(i32::MAX), Duration::hours(24));
+    }
+
+    #[test]
+    fn test_retry_delay_handles_zero() {
+        assert_eq!(retry_delay(0), Duration::minutes(5));
+    }
+}
@@ -0,0 +1,231 @@
+//! Background job run history and regression alerts
+//!
+//! Jobs wrap the work of a run in `track`, which records its duration,
+//! rows processed and outcome in job_runs. After a successful run it's
+//! compared with the job's trailing successful runs and raises a
+//! `JobRegression` alert when it
+//! - took more than `SLOWDOWN_FACTOR` times their median duration, or
+//! - processed no rows while their median is above zero.
+//!
+//! Runs that processed rows are only compared with others that did, so a
+//! job whose frequent no-op runs are quick isn't flagged whenever it has
+//! real work. Nothing is flagged until `MIN_HISTORY` comparable runs exist.
+
+use std::future::Future;
+use std::time::Instant;
+
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+
+use crate::entities::{job_runs, prelude::*};
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+
+/// Trailing successful runs a run is compared with
+pub const TRAILING_RUNS: u64 = 14;
+
+/// Comparable runs needed before anything is flagged
+pub const MIN_HISTORY: usize = 5;
+
+/// A run slower than this multiple of the trailing median is flagged
+pub const SLOWDOWN_FACTOR: f64 = 2.0;
+
+/// How a run departs from the job's history
+#[derive(Debug, Clone, PartialEq)]
+pub enum Regression {
+    Slowdown { duration_ms: i64, median_ms: i64 },
+    NoRows { median_rows: i64 },
+}
+
+impl std::fmt::Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Regression::Slowdown { duration_ms, median_ms } => write!(
+                f,
+                "took {:.1}s, {:.1}x the trailing median of {:.1}s",
+                *duration_ms as f64 / 1000.0,
+                *duration_ms as f64 / (*median_ms).max(1) as f64,
+                *median_ms as f64 / 1000.0
+            ),
+            Regression::NoRows { median_rows } => {
+                write!(f, "processed no rows (trailing median: {})", median_rows)
+            }
+        }
+    }
+}
+
+/// Run `work`, recording it as a run of `job_name`; `work` resolves to the
+/// rows it processed. Its result is passed through, and failing to record
+/// the run is only logged.
+pub async fn track<F, E>(db: &DatabaseConnection, job_name: &str, work: F) -> Result<usize, E>
+where
+    F: Future<Output = Result<usize, E>>,
+    E: std::fmt::Display,
+{
+    let started_at = Utc::now().naive_utc();
+    let started = Instant::now();
+    let result = work.await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let (rows, error) = match &result {
+        Ok(rows) => (Some(*rows as i64), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    if let Err(e) = record(db, job_name, started_at, duration_ms, rows, error).await {
+        tracing::warn!("[{}] Failed to record job run: {}", job_name, e);
+    }
+    result
+}
+
+/// Store a run and, if it succeeded, alert on any regression against the
+/// runs before it; returns the regressions found
+pub async fn record(
+    db: &DatabaseConnection,
+    job_name: &str,
+    started_at: NaiveDateTime,
+    duration_ms: i64,
+    rows_processed: Option<i64>,
+    error: Option<String>,
+) -> Result<Vec<Regression>, DbErr> {
+    let succeeded = error.is_none();
+    let history = if succeeded {
+        trailing_runs(db, job_name).await?
+    } else {
+        Vec::new()
+    };
+
+    job_runs::ActiveModel {
+        job_name: Set(job_name.to_string()),
+        started_at: Set(started_at),
+        finished_at: Set(started_at + chrono::Duration::milliseconds(duration_ms)),
+        duration_ms: Set(duration_ms),
+        rows_processed: Set(rows_processed),
+        succeeded: Set(succeeded),
+        error: Set(error),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    if !succeeded {
+        return Ok(Vec::new());
+    }
+    let regressions = find_regressions(duration_ms, rows_processed, &history);
+    for regression in &regressions {
+        alerting::notify(Alert::new(
+            AlertKind::JobRegression,
+            Severity::Warning,
+            format!("Job {} regressed", job_name),
+            format!("Run started {} {}", started_at.format("%Y-%m-%d %H:%M UTC"), regression),
+        ));
+    }
+    Ok(regressions)
+}
+
+/// The job's latest successful runs, newest first
+async fn trailing_runs(db: &DatabaseConnection, job_name: &str) -> Result<Vec<job_runs::Model>, DbErr> {
+    JobRuns::find()
+        .filter(job_runs::Column::JobName.eq(job_name))
+        .filter(job_runs::Column::Succeeded.eq(true))
+        .order_by_desc(job_runs::Column::StartedAt)
+        .limit(TRAILING_RUNS)
+        .all(db)
+        .await
+}
+
+/// Regressions of a successful run against `history` (successful runs of
+/// the same job)
+pub fn find_regressions(duration_ms: i64, rows_processed: Option<i64>, history: &[job_runs::Model]) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+
+    let did_work = |rows: Option<i64>| rows.is_some_and(|r| r > 0);
+    let comparable: Vec<i64> = history
+        .iter()
+        .filter(|run| did_work(run.rows_processed) == did_work(rows_processed))
+        .map(|run| run.duration_ms)
+        .collect();
+    if comparable.len() >= MIN_HISTORY
+        && let Some(median_ms) = median(comparable)
+        && duration_ms as f64 > median_ms as f64 * SLOWDOWN_FACTOR
+    {
+        regressions.push(Regression::Slowdown { duration_ms, median_ms });
+    }
+
+    if rows_processed == Some(0) {
+        let counted: Vec<i64> = history.iter().filter_map(|run| run.rows_processed).collect();
+        if counted.len() >= MIN_HISTORY
+            && let Some(median_rows) = median(counted)
+            && median_rows > 0
+        {
+            regressions.push(Regression::NoRows { median_rows });
+        }
+    }
+    regressions
+}
+
+/// Lower median
+fn median(mut values: Vec<i64>) -> Option<i64> {
+    values.sort_unstable();
+    values.get(values.len().saturating_sub(1) / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(duration_ms: i64, rows_processed: Option<i64>) -> job_runs::Model {
+        job_runs::Model {
+            id: 0,
+            job_name: "test".to_string(),
+            started_at: NaiveDateTime::default(),
+            finished_at: NaiveDateTime::default(),
+            duration_ms,
+            rows_processed,
+            succeeded: true,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![5]), Some(5));
+        assert_eq!(median(vec![9, 1, 5]), Some(5));
+        assert_eq!(median(vec![4, 1, 9, 2]), Some(2));
+    }
+
+    #[test]
+    fn test_slowdown() {
+        let history: Vec<_> = [900, 1000, 1100, 1000, 950].map(|ms| run(ms, Some(10))).to_vec();
+        assert!(find_regressions(1900, Some(10), &history).is_empty());
+        assert_eq!(
+            find_regressions(2500, Some(10), &history),
+            vec![Regression::Slowdown { duration_ms: 2500, median_ms: 1000 }]
+        );
+        // Too little history
+        assert!(find_regressions(2500, Some(10), &history[..4]).is_empty());
+    }
+
+    #[test]
+    fn test_slowdown_compares_runs_that_did_work() {
+        // Quick no-op runs between the daily runs with work
+        let mut history: Vec<_> = (0..9).map(|_| run(50, Some(0))).collect();
+        history.extend([1000, 1200, 900, 1100, 1000].map(|ms| run(ms, Some(400))));
+        assert!(find_regressions(1500, Some(400), &history).is_empty());
+        assert!(find_regressions(60, Some(0), &history).is_empty());
+        assert_eq!(find_regressions(3000, Some(400), &history).len(), 1);
+    }
+
+    #[test]
+    fn test_no_rows() {
+        let history: Vec<_> = (0..6).map(|_| run(1000, Some(120))).collect();
+        assert_eq!(
+            find_regressions(1000, Some(0), &history),
+            vec![Regression::NoRows { median_rows: 120 }]
+        );
+        // Usually processes nothing
+        let idle: Vec<_> = (0..6).map(|i| run(1000, Some(i % 3 / 2))).collect();
+        assert!(find_regressions(1000, Some(0), &idle).is_empty());
+        // Doesn't count rows
+        assert!(find_regressions(1000, None, &history).is_empty());
+    }
+}
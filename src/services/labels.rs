@@ -0,0 +1,281 @@
+//! Free-form labels on indexes and coins
+//!
+//! A label is a lowercase key/value pair such as theme=ai, attached to an
+//! index (by index id) or a coin (by CoinGecko coin id). An entity can carry
+//! several values for the same key. List endpoints take a `label` filter of
+//! comma-separated `key` or `key:value` terms, all of which must match:
+//! `?label=theme:ai,tier` keeps entities labelled theme=ai that also have
+//! any `tier` label.
+
+use std::collections::HashSet;
+
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    Set,
+};
+
+use crate::entities::{coins, index_metadata, labels, prelude::*};
+
+/// Entity type values for labels.entity_type
+pub mod entity_types {
+    pub const INDEX: &str = "index";
+    pub const COIN: &str = "coin";
+}
+
+const MAX_KEY_LEN: usize = 64;
+const MAX_VALUE_LEN: usize = 128;
+const MAX_FILTERS: usize = 10;
+
+#[derive(Debug)]
+pub enum LabelError {
+    Invalid(String),
+    /// The labelled index or coin doesn't exist
+    EntityNotFound { entity_type: String, entity_id: String },
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for LabelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelError::Invalid(msg) => write!(f, "{}", msg),
+            LabelError::EntityNotFound { entity_type, entity_id } => {
+                write!(f, "No {} with id '{}'", entity_type, entity_id)
+            }
+            LabelError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LabelError {}
+
+impl From<sea_orm::DbErr> for LabelError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        LabelError::Database(e)
+    }
+}
+
+pub fn normalize_entity_type(entity_type: &str) -> Result<String, LabelError> {
+    let entity_type = entity_type.trim().to_lowercase();
+    match entity_type.as_str() {
+        entity_types::INDEX | entity_types::COIN => Ok(entity_type),
+        _ => Err(LabelError::Invalid(format!(
+            "Invalid entity type '{}', expected index or coin",
+            entity_type
+        ))),
+    }
+}
+
+/// Lowercase key of letters, digits, `-` and `_`
+pub fn normalize_key(key: &str) -> Result<String, LabelError> {
+    let key = key.trim().to_lowercase();
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(LabelError::Invalid(format!(
+            "Invalid label key '{}': use 1 to {} letters, digits, '-' or '_'",
+            key, MAX_KEY_LEN
+        )));
+    }
+    Ok(key)
+}
+
+/// Lowercase, trimmed value without commas (they separate filter terms)
+pub fn normalize_value(value: &str) -> Result<String, LabelError> {
+    let value = value.trim().to_lowercase();
+    let valid = !value.is_empty()
+        && value.chars().count() <= MAX_VALUE_LEN
+        && !value.chars().any(|c| c == ',' || c.is_control());
+    if !valid {
+        return Err(LabelError::Invalid(format!(
+            "Invalid label value '{}': use 1 to {} characters, without commas",
+            value, MAX_VALUE_LEN
+        )));
+    }
+    Ok(value)
+}
+
+/// One term of a label filter: a key, optionally with a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// Parse `theme:ai,tier` into filter terms; None or blank means no filter
+pub fn parse_filters(filter: Option<&str>) -> Result<Vec<LabelFilter>, LabelError> {
+    let Some(filter) = filter.filter(|f| !f.trim().is_empty()) else {
+        return Ok(Vec::new());
+    };
+
+    let terms = filter
+        .split(',')
+        .map(|term| match term.split_once(':') {
+            Some((key, value)) => Ok(LabelFilter { key: normalize_key(key)?, value: Some(normalize_value(value)?) }),
+            None => Ok(LabelFilter { key: normalize_key(term)?, value: None }),
+        })
+        .collect::<Result<Vec<_>, LabelError>>()?;
+    if terms.len() > MAX_FILTERS {
+        return Err(LabelError::Invalid(format!("At most {} label filters are allowed", MAX_FILTERS)));
+    }
+    Ok(terms)
+}
+
+async fn entity_exists(db: &DatabaseConnection, entity_type: &str, entity_id: &str) -> Result<bool, sea_orm::DbErr> {
+    match entity_type {
+        entity_types::INDEX => match entity_id.parse::<i32>() {
+            Ok(index_id) => Ok(IndexMetadata::find()
+                .filter(index_metadata::Column::IndexId.eq(index_id))
+                .count(db)
+                .await?
+                > 0),
+            Err(_) => Ok(false),
+        },
+        _ => Ok(Coins::find().filter(coins::Column::CoinId.eq(entity_id)).count(db).await? > 0),
+    }
+}
+
+/// Label an index or coin; adding a label it already has returns the existing one
+pub async fn add(
+    db: &DatabaseConnection,
+    entity_type: &str,
+    entity_id: &str,
+    key: &str,
+    value: &str,
+) -> Result<labels::Model, LabelError> {
+    let entity_type = normalize_entity_type(entity_type)?;
+    let entity_id = entity_id.trim().to_string();
+    let key = normalize_key(key)?;
+    let value = normalize_value(value)?;
+
+    if !entity_exists(db, &entity_type, &entity_id).await? {
+        return Err(LabelError::EntityNotFound { entity_type, entity_id });
+    }
+
+    let label = labels::ActiveModel {
+        entity_type: Set(entity_type.clone()),
+        entity_id: Set(entity_id.clone()),
+        key: Set(key.clone()),
+        value: Set(value.clone()),
+        created_at: Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    Labels::insert(label)
+        .on_conflict(
+            OnConflict::columns([
+                labels::Column::EntityType,
+                labels::Column::EntityId,
+                labels::Column::Key,
+                labels::Column::Value,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await?;
+
+    Labels::find()
+        .filter(labels::Column::EntityType.eq(entity_type))
+        .filter(labels::Column::EntityId.eq(entity_id))
+        .filter(labels::Column::Key.eq(key))
+        .filter(labels::Column::Value.eq(value))
+        .one(db)
+        .await?
+        .ok_or_else(|| LabelError::Database(sea_orm::DbErr::RecordNotFound("label missing after insert".to_string())))
+}
+
+/// Delete a label; returns it if it existed
+pub async fn remove(db: &DatabaseConnection, id: i32) -> Result<Option<labels::Model>, sea_orm::DbErr> {
+    let Some(label) = Labels::find_by_id(id).one(db).await? else {
+        return Ok(None);
+    };
+    Labels::delete_by_id(id).exec(db).await?;
+    Ok(Some(label))
+}
+
+/// Labels, optionally restricted to an entity type and id
+pub async fn list(
+    db: &DatabaseConnection,
+    entity_type: Option<&str>,
+    entity_id: Option<&str>,
+) -> Result<Vec<labels::Model>, sea_orm::DbErr> {
+    let mut query = Labels::find();
+    if let Some(entity_type) = entity_type {
+        query = query.filter(labels::Column::EntityType.eq(entity_type.trim().to_lowercase()));
+    }
+    if let Some(entity_id) = entity_id {
+        query = query.filter(labels::Column::EntityId.eq(entity_id.trim()));
+    }
+
+    query
+        .order_by_asc(labels::Column::EntityType)
+        .order_by_asc(labels::Column::EntityId)
+        .order_by_asc(labels::Column::Key)
+        .order_by_asc(labels::Column::Value)
+        .all(db)
+        .await
+}
+
+/// Ids of the entities of `entity_type` matching every filter term
+pub async fn matching_entity_ids(
+    db: &DatabaseConnection,
+    entity_type: &str,
+    filters: &[LabelFilter],
+) -> Result<HashSet<String>, sea_orm::DbErr> {
+    let mut matching: Option<HashSet<String>> = None;
+
+    for filter in filters {
+        let mut query = Labels::find()
+            .filter(labels::Column::EntityType.eq(entity_type))
+            .filter(labels::Column::Key.eq(filter.key.as_str()));
+        if let Some(value) = &filter.value {
+            query = query.filter(labels::Column::Value.eq(value.as_str()));
+        }
+        let ids: HashSet<String> = query.all(db).await?.into_iter().map(|label| label.entity_id).collect();
+
+        let ids: HashSet<String> = match matching {
+            Some(previous) => previous.intersection(&ids).cloned().collect(),
+            None => ids,
+        };
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+        matching = Some(ids);
+    }
+
+    Ok(matching.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filters() {
+        assert_eq!(parse_filters(None).unwrap(), Vec::new());
+        assert_eq!(parse_filters(Some("  ")).unwrap(), Vec::new());
+        assert_eq!(
+            parse_filters(Some("Theme:AI, tier ,region:North America")).unwrap(),
+            vec![
+                LabelFilter { key: "theme".to_string(), value: Some("ai".to_string()) },
+                LabelFilter { key: "tier".to_string(), value: None },
+                LabelFilter { key: "region".to_string(), value: Some("north america".to_string()) },
+            ]
+        );
+        // Only the first colon separates key and value
+        assert_eq!(parse_filters(Some("pair:btc:usdt")).unwrap()[0].value.as_deref(), Some("btc:usdt"));
+    }
+
+    #[test]
+    fn test_invalid_filters_and_labels() {
+        assert!(parse_filters(Some("theme:")).is_err());
+        assert!(parse_filters(Some("theme,,tier")).is_err());
+        assert!(parse_filters(Some("the me:ai")).is_err());
+        assert!(parse_filters(Some(&["a"; MAX_FILTERS + 1].join(","))).is_err());
+        assert!(normalize_key(&"k".repeat(MAX_KEY_LEN + 1)).is_err());
+        assert!(normalize_value("a,b").is_err());
+        assert!(normalize_entity_type("Index").is_ok());
+        assert!(normalize_entity_type("itp").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! Index performance leaderboard
+//!
+//! Ranks every index over a window (7d, 30d or year to date) by one of:
+//! - return: change of the daily price from the window start to the latest day
+//! - volume: USD collateral of the mints and redemptions in the window
+//! - tvl: net supply from blockchain_events (mints minus burns) at the latest price
+//!
+//! Rankings are computed from daily_prices and blockchain_events and cached
+//! for a few minutes, as they only move when new prices or events land.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate};
+use moka::future::Cache;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::entities::{blockchain_events, daily_prices, prelude::*};
+use crate::models::leaderboard::{LeaderboardEntry, LeaderboardResponse};
+use crate::services::{event_amounts, index_deployments};
+
+/// Event types that create index tokens
+const MINT_EVENTS: &[&str] = &["mint"];
+
+/// Event types that destroy index tokens
+const BURN_EVENTS: &[&str] = &["burn", "withdraw"];
+
+/// Days before the window start searched for its opening price
+const PRICE_LOOKBACK_DAYS: i64 = 7;
+
+static CACHE: LazyLock<Cache<(LeaderboardWindow, LeaderboardMetric, NaiveDate), LeaderboardResponse>> =
+    LazyLock::new(|| {
+        Cache::builder()
+            .max_capacity(64)
+            .time_to_live(Duration::from_secs(300))
+            .build()
+    });
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderboardWindow {
+    Days7,
+    Days30,
+    YearToDate,
+}
+
+impl LeaderboardWindow {
+    pub fn parse(window: &str) -> Option<Self> {
+        match window.trim().to_lowercase().as_str() {
+            "7d" => Some(Self::Days7),
+            "30d" => Some(Self::Days30),
+            "ytd" => Some(Self::YearToDate),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Days7 => "7d",
+            Self::Days30 => "30d",
+            Self::YearToDate => "ytd",
+        }
+    }
+
+    /// First day of the window ending `today`
+    pub fn start(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Days7 => today - chrono::Duration::days(7),
+            Self::Days30 => today - chrono::Duration::days(30),
+            Self::YearToDate => NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderboardMetric {
+    Return,
+    Volume,
+    Tvl,
+}
+
+impl LeaderboardMetric {
+    pub fn parse(metric: &str) -> Option<Self> {
+        match metric.trim().to_lowercase().as_str() {
+            "return" => Some(Self::Return),
+            "volume" => Some(Self::Volume),
+            "tvl" => Some(Self::Tvl),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Return => "return",
+            Self::Volume => "volume",
+            Self::Tvl => "tvl",
+        }
+    }
+}
+
+/// The leaderboard as of `today`, from the cache when fresh
+pub async fn leaderboard(
+    db: &DatabaseConnection,
+    window: LeaderboardWindow,
+    metric: LeaderboardMetric,
+    today: NaiveDate,
+) -> Result<LeaderboardResponse, sea_orm::DbErr> {
+    let key = (window, metric, today);
+    if let Some(cached) = CACHE.get(&key).await {
+        return Ok(cached);
+    }
+
+    let response = compute(db, window, metric, today).await?;
+    CACHE.insert(key, response.clone()).await;
+    Ok(response)
+}
+
+/// Rank indexes without the cache; indexes lacking a value for `metric`
+/// (no price at the window start for returns) are left out
+pub async fn compute(
+    db: &DatabaseConnection,
+    window: LeaderboardWindow,
+    metric: LeaderboardMetric,
+    today: NaiveDate,
+) -> Result<LeaderboardResponse, sea_orm::DbErr> {
+    let start = window.start(today);
+    let indexes = IndexMetadata::find().all(db).await?;
+    let deployments = index_deployments::for_indexes(db, &indexes).await?;
+
+    let mut prices: HashMap<String, Vec<(NaiveDate, Decimal)>> = HashMap::new();
+    let price_rows = DailyPrices::find()
+        .filter(daily_prices::Column::Date.gte(start - chrono::Duration::days(PRICE_LOOKBACK_DAYS)))
+        .filter(daily_prices::Column::Date.lte(today))
+        .order_by_asc(daily_prices::Column::Date)
+        .all(db)
+        .await?;
+    for row in price_rows {
+        prices.entry(row.index_id).or_default().push((row.date, row.price));
+    }
+
+    let event_types: Vec<&str> = MINT_EVENTS.iter().chain(BURN_EVENTS).copied().collect();
+    let mut entries = Vec::new();
+    for index in indexes {
+        let index_prices = prices.remove(&index.index_id.to_string()).unwrap_or_default();
+        let latest_price = index_prices.last().map(|(_, price)| *price);
+        let start_price = index_prices.iter().rev().find(|(date, _)| *date <= start).map(|(_, price)| *price);
+        let return_pct = match (start_price, latest_price) {
+            (Some(start_price), Some(latest_price)) if !start_price.is_zero() => {
+                ((latest_price - start_price) / start_price * Decimal::ONE_HUNDRED).to_f64()
+            }
+            _ => None,
+        };
+
+        let index_deployments = deployments.get(&index.index_id).map(Vec::as_slice).unwrap_or_default();
+        let mut supply = Decimal::ZERO;
+        let mut volume = Decimal::ZERO;
+        if !index_deployments.is_empty() {
+            let events = BlockchainEvents::find()
+                .filter(index_deployments::events_condition(index_deployments))
+                .filter(blockchain_events::Column::EventType.is_in(event_types.clone()))
+                .all(db)
+                .await?;
+            for event in &events {
+                let quantity = event_amounts::quantity(event).unwrap_or_default();
+                if MINT_EVENTS.contains(&event.event_type.as_str()) {
+                    supply += quantity;
+                } else {
+                    supply -= quantity;
+                }
+                if event.timestamp.is_some_and(|t| t.date_naive() >= start) {
+                    volume += event_amounts::amount(event).unwrap_or_default();
+                }
+            }
+        }
+        let tvl = latest_price.map(|price| supply.max(Decimal::ZERO) * price).unwrap_or_default();
+
+        let volume_usd = volume.to_f64().unwrap_or_default();
+        let tvl_usd = tvl.to_f64().unwrap_or_default();
+        let value = match metric {
+            LeaderboardMetric::Return => return_pct,
+            LeaderboardMetric::Volume => Some(volume_usd),
+            LeaderboardMetric::Tvl => Some(tvl_usd),
+        };
+        let Some(value) = value else {
+            continue;
+        };
+
+        entries.push(LeaderboardEntry {
+            rank: 0,
+            index_id: index.index_id,
+            name: index.name,
+            ticker: index.symbol,
+            value,
+            return_pct,
+            volume_usd,
+            tvl_usd,
+            latest_price: latest_price.and_then(|price| price.to_f64()),
+        });
+    }
+
+    entries.sort_by(|a, b| b.value.total_cmp(&a.value).then(a.index_id.cmp(&b.index_id)));
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.rank = i + 1;
+    }
+
+    Ok(LeaderboardResponse {
+        window: window.as_str().to_string(),
+        metric: metric.as_str().to_string(),
+        start_date: start,
+        as_of: today,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_window_start() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        assert_eq!(LeaderboardWindow::parse("7D"), Some(LeaderboardWindow::Days7));
+        assert_eq!(LeaderboardWindow::parse("1y"), None);
+        assert_eq!(LeaderboardMetric::parse("tvl"), Some(LeaderboardMetric::Tvl));
+        assert_eq!(LeaderboardMetric::parse("price"), None);
+
+        assert_eq!(LeaderboardWindow::Days7.start(today), NaiveDate::from_ymd_opt(2025, 3, 8).unwrap());
+        assert_eq!(LeaderboardWindow::Days30.start(today), NaiveDate::from_ymd_opt(2025, 2, 13).unwrap());
+        assert_eq!(LeaderboardWindow::YearToDate.start(today), NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    }
+}
@@ -0,0 +1,147 @@
+//! Leveraged and inverse indexes
+//!
+//! An index with index_metadata.leverage_factor tracks that multiple of its
+//! basket's daily return, reset every day: with basket values B and NAV N,
+//! `N(t) = N(t-1) × (1 + factor × (B(t) / B(t-1) - 1))`. Rebalances still
+//! define the basket (constituents, quantities, unleveraged portfolio_value);
+//! the leverage only applies to the NAV in daily_prices, which starts from the
+//! first rebalance's portfolio_value. A day whose loss would exceed the NAV
+//! leaves it at zero, as the product would be wiped out.
+//!
+//! An unset factor, or 1, is an ordinary index.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::entities::{daily_prices, index_metadata, prelude::*, rebalances};
+use crate::services::pricing_time;
+
+pub const MIN_FACTOR: Decimal = dec!(-3);
+pub const MAX_FACTOR: Decimal = dec!(3);
+
+/// Decimals allowed in a factor, e.g. 1.5 or -0.5
+const FACTOR_DP: u32 = 2;
+
+/// Validates a leverage factor: non-zero, between -3 and 3
+pub fn validate_factor(factor: Decimal) -> Result<(), String> {
+    if factor.is_zero() || factor < MIN_FACTOR || factor > MAX_FACTOR {
+        return Err(format!(
+            "leverage_factor must be non-zero and between {} and {}, got {}",
+            MIN_FACTOR, MAX_FACTOR, factor
+        ));
+    }
+    if factor.normalize().scale() > FACTOR_DP {
+        return Err(format!("leverage_factor allows at most {} decimals, got {}", FACTOR_DP, factor));
+    }
+    Ok(())
+}
+
+/// Leverage factor of an index, None for an ordinary (1x) one
+pub fn for_index(index: &index_metadata::Model) -> Option<Decimal> {
+    let factor = index.leverage_factor.filter(|factor| *factor != Decimal::ONE)?;
+    if let Err(e) = validate_factor(factor) {
+        tracing::warn!(index_id = index.index_id, "Ignoring leverage: {}", e);
+        return None;
+    }
+    Some(factor)
+}
+
+/// NAV after a day on which the basket went from `basket_before` to
+/// `basket_after`
+pub fn next_nav(previous_nav: Decimal, basket_before: Decimal, basket_after: Decimal, factor: Decimal) -> Decimal {
+    if basket_before <= Decimal::ZERO {
+        return previous_nav;
+    }
+    let basket_return = basket_after / basket_before - Decimal::ONE;
+    (previous_nav * (Decimal::ONE + factor * basket_return)).max(Decimal::ZERO)
+}
+
+/// NAV of a leveraged index on the day before `date`
+///
+/// That's its daily price, or the first rebalance's portfolio_value on the
+/// day after that rebalance. None if the day before has no price, since the
+/// NAV can't be carried over a gap.
+pub async fn previous_nav(
+    db: &DatabaseConnection,
+    index_id: i32,
+    date: NaiveDate,
+) -> Result<Option<Decimal>, sea_orm::DbErr> {
+    let latest = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+        .filter(daily_prices::Column::Date.lt(date))
+        .order_by_desc(daily_prices::Column::Date)
+        .one(db)
+        .await?;
+
+    let day_before = date - chrono::Duration::days(1);
+    match latest {
+        Some(row) if row.date == day_before => Ok(Some(row.price)),
+        Some(_) => Ok(None),
+        None => Ok(Rebalances::find()
+            .filter(rebalances::Column::IndexId.eq(index_id))
+            .order_by_asc(rebalances::Column::Timestamp)
+            .one(db)
+            .await?
+            .filter(|first| {
+                pricing_time::rebalance_date(first.timestamp) == Some(day_before)
+            })
+            .map(|first| first.portfolio_value)),
+    }
+}
+
+/// NAV on `date` of a leveraged index whose basket went from `basket_before`
+/// the day before to `basket_after`
+pub async fn nav_on(
+    db: &DatabaseConnection,
+    index_id: i32,
+    date: NaiveDate,
+    factor: Decimal,
+    basket_before: Decimal,
+    basket_after: Decimal,
+) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
+    let previous = previous_nav(db, index_id, date).await?.ok_or_else(|| {
+        format!(
+            "No NAV for leveraged index {} on {}, its daily prices must be filled in order",
+            index_id,
+            date - chrono::Duration::days(1)
+        )
+    })?;
+    Ok(next_nav(previous, basket_before, basket_after, factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_factor() {
+        assert!(validate_factor(dec!(-1)).is_ok());
+        assert!(validate_factor(dec!(3)).is_ok());
+        assert!(validate_factor(dec!(1.5)).is_ok());
+        assert!(validate_factor(dec!(0)).is_err());
+        assert!(validate_factor(dec!(-3.5)).is_err());
+        assert!(validate_factor(dec!(4)).is_err());
+        assert!(validate_factor(dec!(1.255)).is_err());
+    }
+
+    #[test]
+    fn test_next_nav() {
+        // Basket +10%: 2x gains 20%, -1x loses 10%
+        assert_eq!(next_nav(dec!(100), dec!(50), dec!(55), dec!(2)), dec!(120));
+        assert_eq!(next_nav(dec!(100), dec!(50), dec!(55), dec!(-1)), dec!(90));
+        // A -40% day wipes out 3x
+        assert_eq!(next_nav(dec!(100), dec!(50), dec!(30), dec!(3)), Decimal::ZERO);
+        assert_eq!(next_nav(dec!(100), Decimal::ZERO, dec!(30), dec!(3)), dec!(100));
+    }
+
+    #[test]
+    fn test_daily_reset_decay() {
+        // +10% then back down: the basket is flat, 2x isn't
+        let nav = next_nav(dec!(100), dec!(100), dec!(110), dec!(2));
+        let nav = next_nav(nav, dec!(110), dec!(100), dec!(2));
+        assert!(nav < dec!(100));
+        assert_eq!(nav.round_dp(6), dec!(98.181818));
+    }
+}
@@ -0,0 +1,216 @@
+//! Whitelisted filters and sorts of list endpoints
+//!
+//! List endpoints take a `filter` of comma-separated `field:value` terms, all
+//! of which must match (`?filter=network:base,type:mint`), and a `sort` of
+//! comma-separated fields, descending when prefixed with `-`
+//! (`?sort=-symbol,name`). Each endpoint declares the fields it accepts and
+//! what each maps to, usually a SeaORM column; any other name is rejected
+//! with a 400, and values are bound as query parameters, never spliced into
+//! SQL. Text fields match case-insensitively.
+
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder, Select,
+};
+
+use crate::models::list_query::ListQuery;
+
+const MAX_FILTERS: usize = 10;
+const MAX_SORTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+    /// Can be sorted on but not filtered on
+    SortOnly,
+}
+
+/// A field a list endpoint accepts, and what it maps to
+#[derive(Debug, Clone, Copy)]
+pub struct Field<T> {
+    pub name: &'static str,
+    pub target: T,
+    pub kind: FieldKind,
+}
+
+impl<T> Field<T> {
+    pub const fn text(name: &'static str, target: T) -> Self {
+        Self { name, target, kind: FieldKind::Text }
+    }
+
+    pub const fn integer(name: &'static str, target: T) -> Self {
+        Self { name, target, kind: FieldKind::Integer }
+    }
+
+    pub const fn sort_only(name: &'static str, target: T) -> Self {
+        Self { name, target, kind: FieldKind::SortOnly }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+    Text(String),
+    Integer(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListQueryError(String);
+
+impl std::fmt::Display for ListQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ListQueryError {}
+
+fn names<T>(fields: &[Field<T>], filterable: bool) -> String {
+    fields
+        .iter()
+        .filter(|field| !filterable || field.kind != FieldKind::SortOnly)
+        .map(|field| field.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn find<'a, T>(fields: &'a [Field<T>], name: &str) -> Option<&'a Field<T>> {
+    fields.iter().find(|field| field.name == name)
+}
+
+/// Parse `filter` against `fields`; empty when absent
+pub fn parse_filters<T: Copy>(
+    fields: &[Field<T>],
+    filter: Option<&str>,
+) -> Result<Vec<(T, FilterValue)>, ListQueryError> {
+    let terms: Vec<&str> = filter
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .collect();
+    if terms.len() > MAX_FILTERS {
+        return Err(ListQueryError(format!("At most {} filters are allowed", MAX_FILTERS)));
+    }
+
+    terms
+        .into_iter()
+        .map(|term| {
+            let (name, value) = term
+                .split_once(':')
+                .map(|(name, value)| (name.trim().to_lowercase(), value.trim()))
+                .filter(|(_, value)| !value.is_empty())
+                .ok_or_else(|| ListQueryError(format!("Invalid filter '{}', expected field:value", term)))?;
+            let field = find(fields, &name)
+                .filter(|field| field.kind != FieldKind::SortOnly)
+                .ok_or_else(|| {
+                    ListQueryError(format!(
+                        "Unknown filter field '{}', expected one of: {}",
+                        name,
+                        names(fields, true)
+                    ))
+                })?;
+            let value = match field.kind {
+                FieldKind::Integer => FilterValue::Integer(value.parse().map_err(|_| {
+                    ListQueryError(format!("Invalid value '{}' for {}, expected an integer", value, field.name))
+                })?),
+                _ => FilterValue::Text(value.to_string()),
+            };
+            Ok((field.target, value))
+        })
+        .collect()
+}
+
+/// Parse `sort` against `fields`, in order of precedence; empty when absent
+pub fn parse_sort<T: Copy>(fields: &[Field<T>], sort: Option<&str>) -> Result<Vec<(T, Order)>, ListQueryError> {
+    let keys: Vec<&str> = sort
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .collect();
+    if keys.len() > MAX_SORTS {
+        return Err(ListQueryError(format!("At most {} sort fields are allowed", MAX_SORTS)));
+    }
+
+    keys.into_iter()
+        .map(|key| {
+            let (name, order) = match key.strip_prefix('-') {
+                Some(name) => (name, Order::Desc),
+                None => (key, Order::Asc),
+            };
+            let name = name.trim().to_lowercase();
+            let field = find(fields, &name).ok_or_else(|| {
+                ListQueryError(format!(
+                    "Unknown sort field '{}', expected one of: {}",
+                    name,
+                    names(fields, false)
+                ))
+            })?;
+            Ok((field.target, order))
+        })
+        .collect()
+}
+
+/// Add parsed filters and sorts to `select`
+pub fn apply<E: EntityTrait>(
+    mut select: Select<E>,
+    filters: Vec<(E::Column, FilterValue)>,
+    sorts: Vec<(E::Column, Order)>,
+) -> Select<E> {
+    for (column, value) in filters {
+        select = match value {
+            FilterValue::Text(text) => select.filter(
+                Expr::expr(Func::lower(Expr::col((column.entity_name(), column)))).eq(text.to_lowercase()),
+            ),
+            FilterValue::Integer(n) => select.filter(column.eq(n)),
+        };
+    }
+    for (column, order) in sorts {
+        select = select.order_by(column, order);
+    }
+    select
+}
+
+/// Parse `query` against `fields` and add it to `select`
+pub fn apply_query<E: EntityTrait>(
+    select: Select<E>,
+    fields: &[Field<E::Column>],
+    query: &ListQuery,
+) -> Result<Select<E>, ListQueryError> {
+    let filters = parse_filters(fields, query.filter.as_deref())?;
+    let sorts = parse_sort(fields, query.sort.as_deref())?;
+    Ok(apply(select, filters, sorts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: &[Field<u8>] = &[Field::integer("id", 1), Field::text("symbol", 2), Field::sort_only("date", 3)];
+
+    #[test]
+    fn test_parse_filters() {
+        let filters = parse_filters(FIELDS, Some(" Symbol:BTC , id:7,")).unwrap();
+        assert_eq!(
+            filters,
+            vec![(2, FilterValue::Text("BTC".to_string())), (1, FilterValue::Integer(7))]
+        );
+        assert!(parse_filters(FIELDS, None).unwrap().is_empty());
+
+        for invalid in ["symbol", "symbol:", "name:btc", "date:2025-01-01", "id:seven", "id:1;drop table coins"] {
+            assert!(parse_filters(FIELDS, Some(invalid)).is_err(), "{}", invalid);
+        }
+        let error = parse_filters(FIELDS, Some("name:btc")).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown filter field 'name', expected one of: id, symbol");
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let sorts = parse_sort(FIELDS, Some("-date, symbol")).unwrap();
+        assert_eq!(sorts, vec![(3, Order::Desc), (2, Order::Asc)]);
+        assert!(parse_sort(FIELDS, Some("")).unwrap().is_empty());
+        assert!(parse_sort(FIELDS, Some("-name")).is_err());
+        assert!(parse_sort(FIELDS, Some("id,symbol,date,id")).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Distributed locking via Postgres advisory locks
+//!
+//! Several backend replicas can run against the same database. Background
+//! jobs and per-index work (backfills, rebalances) take a session-level
+//! advisory lock first so only one instance executes them at a time.
+//!
+//! Each lock owns a dedicated connection detached from the pool, so a long
+//! running job never starves request handlers of pooled connections. The
+//! lock is held for as long as that session lives: dropping the guard closes
+//! the connection and Postgres releases the lock, even if the job panicked.
+
+use sea_orm::sqlx::{self, Connection, PgConnection};
+use sea_orm::{DatabaseConnection, DbErr};
+
+/// Lock keys for the different kinds of exclusive work
+pub mod keys {
+    use super::lock_key;
+
+    /// Lock for a whole background job (one runner across all instances)
+    pub fn job(job_name: &str) -> i64 {
+        lock_key("job", job_name)
+    }
+
+    /// Lock for work on a single index (backfill, rebalance, retries)
+    pub fn index(index_id: i32) -> i64 {
+        lock_key("index", &index_id.to_string())
+    }
+}
+
+/// Derive a stable 64-bit advisory lock key from a namespace and a name
+///
+/// Uses FNV-1a so keys are identical across builds and Rust versions,
+/// which `DefaultHasher` does not guarantee.
+pub fn lock_key(namespace: &str, name: &str) -> i64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in namespace.bytes().chain(std::iter::once(b':')).chain(name.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash as i64
+}
+
+/// A held advisory lock
+///
+/// Call `release` to unlock eagerly; otherwise the lock is freed when the
+/// guard is dropped and its session closes.
+pub struct AdvisoryLock {
+    key: i64,
+    conn: PgConnection,
+}
+
+impl AdvisoryLock {
+    /// Unlock and close the dedicated session
+    pub async fn release(mut self) -> Result<(), DbErr> {
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(self.key)
+            .execute(&mut self.conn)
+            .await
+            .map_err(sqlx_err)?;
+        self.conn.close().await.map_err(sqlx_err)
+    }
+}
+
+/// Try to take the lock without waiting
+///
+/// Returns `Ok(None)` when another session already holds it.
+pub async fn try_acquire(db: &DatabaseConnection, key: i64) -> Result<Option<AdvisoryLock>, DbErr> {
+    let mut conn = db
+        .get_postgres_connection_pool()
+        .acquire()
+        .await
+        .map_err(sqlx_err)?
+        .detach();
+
+    let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(key)
+        .fetch_one(&mut conn)
+        .await
+        .map_err(sqlx_err)?;
+
+    if acquired {
+        Ok(Some(AdvisoryLock { key, conn }))
+    } else {
+        // Best effort: the connection is closed on drop either way
+        let _ = conn.close().await;
+        Ok(None)
+    }
+}
+
+/// Outcome of a background job run guarded by its job lock
+///
+/// A skipped run didn't happen here, so callers record neither success nor
+/// failure for it: the instance holding the lock records its own outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome<T = ()> {
+    /// This instance held the lock and ran the job
+    Ran(T),
+    /// Another instance held the lock, nothing ran
+    Skipped,
+}
+
+/// Take the lock for a background job, logging when another instance has it
+pub async fn try_acquire_job(db: &DatabaseConnection, job_name: &str) -> Result<Option<AdvisoryLock>, DbErr> {
+    let lock = try_acquire(db, keys::job(job_name)).await?;
+    if lock.is_none() {
+        tracing::info!("[{}] Already running on another instance, skipping this run", job_name);
+    }
+    Ok(lock)
+}
+
+/// Take the lock for an index, logging when another instance has it
+pub async fn try_acquire_index(db: &DatabaseConnection, index_id: i32) -> Result<Option<AdvisoryLock>, DbErr> {
+    let lock = try_acquire(db, keys::index(index_id)).await?;
+    if lock.is_none() {
+        tracing::info!("Index {} is being processed by another instance, skipping", index_id);
+    }
+    Ok(lock)
+}
+
+fn sqlx_err(e: sqlx::Error) -> DbErr {
+    DbErr::Custom(format!("Advisory lock error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_key_is_stable() {
+        // Pinned value: changing the hash would let old and new replicas
+        // run the same job concurrently during a rolling deploy
+        assert_eq!(lock_key("job", "rebalance_sync"), 8859891466369214741);
+    }
+
+    #[test]
+    fn test_lock_keys_are_namespaced() {
+        assert_ne!(keys::job("42"), keys::index(42));
+        assert_ne!(keys::index(1), keys::index(2));
+        assert_eq!(keys::job("rebalance_sync"), lock_key("job", "rebalance_sync"));
+    }
+}
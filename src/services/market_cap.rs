@@ -3,13 +3,29 @@
 use chrono::NaiveDate;
 use moka::future::Cache;
 use reqwest::Client;
-use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, QueryFilter,
+    QuerySelect, Set, Statement,
+};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
 use crate::entities::market_cap_rankings;
+use crate::models::market_cap::{AlignedMarketCapSeries, MarketCapHistoryBatchResponse, TopCategoryCoin};
+
+/// Price window start, end and n of a top-N query
+type TopKey = (NaiveDate, NaiveDate, u32);
+
+/// Global top-N answers by (price window, n); a past date's ranking only
+/// changes when its prices are backfilled
+static TOP_CACHE: LazyLock<Cache<TopKey, Arc<Vec<TopCategoryCoin>>>> = LazyLock::new(|| {
+    Cache::builder()
+        .max_capacity(512)
+        .time_to_live(Duration::from_secs(600))
+        .build()
+});
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoinMarketData {
@@ -425,3 +441,136 @@ async fn store_rankings_in_db_internal(
 
     Ok(())
 }
+
+#[derive(Debug, FromQueryResult)]
+struct RankedPrice {
+    coin_id: String,
+    symbol: String,
+    name: Option<String>,
+    market_cap: Decimal,
+    price: Decimal,
+    volume: Option<Decimal>,
+}
+
+/// The `n` largest coins by market cap over `from..=to`, each coin ranked
+/// on its latest row in the window, from coins_historical_prices
+///
+/// A single day (`from == to`) ranks that day's prices; a longer window
+/// ranks history the retention policy kept weekly (see
+/// price_retention::PriceCoverage). Coins without a market cap aren't
+/// ranked. Answers are cached per (window, n).
+pub async fn top_by_market_cap(
+    db: &DatabaseConnection,
+    from: NaiveDate,
+    to: NaiveDate,
+    n: u32,
+) -> Result<Arc<Vec<TopCategoryCoin>>, sea_orm::DbErr> {
+    if let Some(cached) = TOP_CACHE.get(&(from, to, n)).await {
+        return Ok(cached);
+    }
+
+    let rows = RankedPrice::find_by_statement(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+        SELECT * FROM (
+            SELECT DISTINCT ON (p.coin_id)
+                p.coin_id, COALESCE(c.symbol, p.symbol) AS symbol, c.name, p.market_cap, p.price, p.volume
+            FROM coins_historical_prices p
+            LEFT JOIN coins c ON c.coin_id = p.coin_id
+            WHERE p.date BETWEEN $1 AND $2 AND p.market_cap > 0
+            ORDER BY p.coin_id, p.date DESC
+        ) latest
+        ORDER BY market_cap DESC, coin_id
+        LIMIT $3
+        "#,
+        [from.into(), to.into(), (n as i64).into()],
+    ))
+    .all(db)
+    .await?;
+
+    let coins: Vec<TopCategoryCoin> = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| TopCategoryCoin {
+            rank: (i + 1) as u32,
+            name: row.name.unwrap_or_else(|| row.symbol.clone()),
+            coin_id: row.coin_id,
+            symbol: row.symbol,
+            market_cap: row.market_cap.to_f64().unwrap_or(0.0),
+            price: row.price.to_f64().unwrap_or(0.0),
+            volume_24h: row.volume.and_then(|v| v.to_f64()).unwrap_or(0.0),
+            logo: None,
+        })
+        .collect();
+
+    let coins = Arc::new(coins);
+    TOP_CACHE.insert((from, to, n), coins.clone()).await;
+    Ok(coins)
+}
+
+/// Stored history of several coins from `start` to `end`, aligned on every
+/// day of the range
+///
+/// Coins are returned in the order of `coin_ids`; those not in the coins
+/// table are listed as missing. Unlike the single-coin endpoint, nothing is
+/// fetched from CoinGecko: days without a stored price are None.
+pub async fn history_for_coins(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<MarketCapHistoryBatchResponse, sea_orm::DbErr> {
+    use crate::entities::{coins, coins_historical_prices, prelude::*};
+    use std::collections::HashMap;
+
+    let symbols: HashMap<String, String> = Coins::find()
+        .filter(coins::Column::CoinId.is_in(coin_ids.iter().cloned()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|coin| (coin.coin_id, coin.symbol))
+        .collect();
+    let (found, missing): (Vec<&String>, Vec<&String>) = coin_ids.iter().partition(|id| symbols.contains_key(*id));
+
+    let dates: Vec<NaiveDate> = start.iter_days().take_while(|date| *date <= end).collect();
+    let day_index: HashMap<NaiveDate, usize> = dates.iter().enumerate().map(|(i, date)| (*date, i)).collect();
+
+    let mut series: HashMap<&str, AlignedMarketCapSeries> = found
+        .iter()
+        .map(|id| {
+            let empty = AlignedMarketCapSeries {
+                coin_id: id.to_string(),
+                symbol: symbols[*id].clone(),
+                market_caps: vec![None; dates.len()],
+                prices: vec![None; dates.len()],
+                volumes: vec![None; dates.len()],
+            };
+            (id.as_str(), empty)
+        })
+        .collect();
+
+    if !found.is_empty() {
+        for row in CoinsHistoricalPrices::find()
+            .filter(coins_historical_prices::Column::CoinId.is_in(found.iter().map(|id| id.as_str())))
+            .filter(coins_historical_prices::Column::Date.gte(start))
+            .filter(coins_historical_prices::Column::Date.lte(end))
+            .all(db)
+            .await?
+        {
+            let (Some(coin), Some(&i)) = (series.get_mut(row.coin_id.as_str()), day_index.get(&row.date)) else {
+                continue;
+            };
+            coin.prices[i] = row.price.to_f64();
+            coin.market_caps[i] = row.market_cap.and_then(|mc| mc.to_f64());
+            coin.volumes[i] = row.volume.and_then(|v| v.to_f64());
+        }
+    }
+
+    Ok(MarketCapHistoryBatchResponse {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        dates: dates.iter().map(NaiveDate::to_string).collect(),
+        coins: found.iter().filter_map(|id| series.remove(id.as_str())).collect(),
+        missing: missing.into_iter().cloned().collect(),
+    })
+}
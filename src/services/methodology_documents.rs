@@ -0,0 +1,135 @@
+//! Versioned index methodology documents
+//!
+//! An index's methodology is stored as a series of immutable versions in
+//! methodology_documents. Publishing a change appends version N+1 with the
+//! day it takes effect; earlier versions stay readable, so the rules that
+//! applied to any past rebalance can be looked up. The version in effect on
+//! a day is the newest one whose effective_from is on or before it.
+
+use chrono::NaiveDate;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+
+use crate::entities::{methodology_documents, prelude::*};
+
+/// Accepted values of methodology_documents.format
+pub const FORMATS: [&str; 2] = ["markdown", "text"];
+
+#[derive(Debug)]
+pub enum MethodologyError {
+    InvalidFormat(String),
+    EmptyContent,
+    /// A new version can't take effect before the latest one
+    EffectiveBeforeLatest { latest_version: i32, latest_effective_from: NaiveDate },
+    Database(DbErr),
+}
+
+impl std::fmt::Display for MethodologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MethodologyError::InvalidFormat(format) => {
+                write!(f, "Invalid format '{}', expected one of {}", format, FORMATS.join(", "))
+            }
+            MethodologyError::EmptyContent => write!(f, "Methodology content is empty"),
+            MethodologyError::EffectiveBeforeLatest { latest_version, latest_effective_from } => write!(
+                f,
+                "effectiveFrom must be on or after {} (effective date of version {})",
+                latest_effective_from, latest_version
+            ),
+            MethodologyError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MethodologyError {}
+
+impl From<DbErr> for MethodologyError {
+    fn from(e: DbErr) -> Self {
+        MethodologyError::Database(e)
+    }
+}
+
+/// Append a new version of an index's methodology
+pub async fn create(
+    db: &DatabaseConnection,
+    index_id: i32,
+    format: &str,
+    content: &str,
+    effective_from: NaiveDate,
+) -> Result<methodology_documents::Model, MethodologyError> {
+    if !FORMATS.contains(&format) {
+        return Err(MethodologyError::InvalidFormat(format.to_string()));
+    }
+    if content.trim().is_empty() {
+        return Err(MethodologyError::EmptyContent);
+    }
+
+    let txn = db.begin().await?;
+
+    let latest = MethodologyDocuments::find()
+        .filter(methodology_documents::Column::IndexId.eq(index_id))
+        .order_by_desc(methodology_documents::Column::Version)
+        .one(&txn)
+        .await?;
+
+    if let Some(latest) = latest.as_ref().filter(|latest| effective_from < latest.effective_from) {
+        return Err(MethodologyError::EffectiveBeforeLatest {
+            latest_version: latest.version,
+            latest_effective_from: latest.effective_from,
+        });
+    }
+
+    // Concurrent creates race for the same number and the loser fails on
+    // the (index_id, version) unique index
+    let document = methodology_documents::ActiveModel {
+        index_id: Set(index_id),
+        version: Set(latest.map_or(1, |latest| latest.version + 1)),
+        format: Set(format.to_string()),
+        content: Set(content.to_string()),
+        effective_from: Set(effective_from),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok(document)
+}
+
+/// All versions of an index's methodology, newest first
+pub async fn list(db: &DatabaseConnection, index_id: i32) -> Result<Vec<methodology_documents::Model>, DbErr> {
+    MethodologyDocuments::find()
+        .filter(methodology_documents::Column::IndexId.eq(index_id))
+        .order_by_desc(methodology_documents::Column::Version)
+        .all(db)
+        .await
+}
+
+/// One version of an index's methodology
+pub async fn get(
+    db: &DatabaseConnection,
+    index_id: i32,
+    version: i32,
+) -> Result<Option<methodology_documents::Model>, DbErr> {
+    MethodologyDocuments::find()
+        .filter(methodology_documents::Column::IndexId.eq(index_id))
+        .filter(methodology_documents::Column::Version.eq(version))
+        .one(db)
+        .await
+}
+
+/// The version in effect on `date`
+pub async fn effective_on(
+    db: &DatabaseConnection,
+    index_id: i32,
+    date: NaiveDate,
+) -> Result<Option<methodology_documents::Model>, DbErr> {
+    MethodologyDocuments::find()
+        .filter(methodology_documents::Column::IndexId.eq(index_id))
+        .filter(methodology_documents::Column::EffectiveFrom.lte(date))
+        .order_by_desc(methodology_documents::Column::Version)
+        .one(db)
+        .await
+}
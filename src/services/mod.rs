@@ -1,4 +1,5 @@
 pub mod coingecko;
+pub mod coingecko_usage;
 pub mod rebalancing;
 pub mod price_utils;
 pub mod market_cap;
@@ -18,4 +19,67 @@ pub mod bitget_websocket;
 pub mod orderbook_aggregator;
 pub mod live_orderbook_cache;
 pub mod bitget_ws_feeder;
-pub mod itp_chain_discovery;
\ No newline at end of file
+pub mod itp_chain_discovery;
+pub mod data_freshness;
+pub mod job_failures;
+pub mod locking;
+pub mod background_tasks;
+pub mod rebalance_math;
+pub mod units;
+pub mod event_amounts;
+pub mod supply_reconciliation;
+pub mod index_deployments;
+pub mod price_retention;
+pub mod price_reconciliation;
+pub mod methodology_documents;
+pub mod api_keys;
+pub mod auth;
+pub mod statements;
+pub mod price_partitions;
+pub mod itp_validation;
+pub mod composition;
+pub mod itp_drift;
+pub mod itp_orders;
+pub mod solvers;
+pub mod itp_execution_quote;
+pub mod labels;
+pub mod crypto_listings;
+pub mod feature_flags;
+pub mod coin_logos;
+pub mod leaderboard;
+pub mod index_tvl;
+pub mod protocol_stats;
+pub mod rebalance_deployment;
+pub mod rebalance_approvals;
+pub mod tx_simulator;
+pub mod signers;
+pub mod chain_spend;
+pub mod wallet_balances;
+pub mod index_constraints;
+pub mod cash_buffer;
+pub mod yield_accrual;
+pub mod leverage;
+pub mod index_translations;
+pub mod alerting;
+pub mod correlation;
+pub mod rolling_stats;
+pub mod snapshot_log;
+pub mod snapshot_anchor;
+pub mod list_query;
+pub mod pricing_time;
+pub mod inception;
+pub mod price_fallback;
+pub mod index_events;
+pub mod index_categories;
+pub mod sparkline;
+pub mod email;
+pub mod reports;
+pub mod spread_calibration;
+pub mod pair_volumes;
+pub mod backtest;
+pub mod coin_sync_cursors;
+pub mod job_runs;
+pub mod index_status;
+pub mod coin_merge;
+pub mod rebalance_schema;
+pub mod portfolio_continuity;
@@ -0,0 +1,135 @@
+//! Exchange-native traded volume per trading pair
+//!
+//! CoinGecko's total_volume sums every venue a coin trades on, including
+//! ones the indexes can't execute on. The pair volume sampling job instead
+//! records, once a day, each supported exchange's own 24h volume of its
+//! USDC/USDT pairs in pair_volumes. The constituent selectors screen
+//! candidates against an index's minPairVolumeUsd constraint with it and
+//! execution quotes report each leg's share of its pair's volume.
+//!
+//! A lookup uses the latest snapshot on or before the date, up to
+//! `MAX_SNAPSHOT_AGE_DAYS` old. Pairs without one (e.g. backfilled dates
+//! before sampling started) are unknown rather than zero, and screens let
+//! them through.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+use crate::entities::{pair_volumes, prelude::*};
+use crate::services::exchange_api::{ExchangeApiService, PairVolume, SUPPORTED_EXCHANGES};
+
+/// Oldest snapshot a lookup falls back to, in days
+pub const MAX_SNAPSHOT_AGE_DAYS: i64 = 7;
+
+/// Rows per upsert statement
+const INSERT_CHUNK: usize = 500;
+
+/// Snapshot the 24h pair volumes of every supported exchange; returns the
+/// number of pairs recorded. An exchange whose tickers can't be fetched is
+/// skipped and retried on the next run; the run fails only if all are.
+pub async fn sample_all(
+    db: &DatabaseConnection,
+    exchange_api: &ExchangeApiService,
+    now: NaiveDateTime,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut recorded = 0;
+    let mut failures = Vec::new();
+    for exchange in SUPPORTED_EXCHANGES {
+        match exchange_api.get_24h_volumes(exchange).await {
+            Ok(volumes) => recorded += record(db, &volumes, now).await?,
+            Err(e) => {
+                tracing::warn!("24h volumes of {} unavailable: {}", exchange, e);
+                failures.push(format!("{}: {}", exchange, e));
+            }
+        }
+    }
+
+    if failures.len() == SUPPORTED_EXCHANGES.len() {
+        return Err(format!("No exchange volumes fetched ({})", failures.join("; ")).into());
+    }
+    Ok(recorded)
+}
+
+/// Store `volumes` as the snapshot of `now`'s date, replacing that day's
+/// earlier snapshot of the same pairs
+pub async fn record(db: &DatabaseConnection, volumes: &[PairVolume], now: NaiveDateTime) -> Result<usize, DbErr> {
+    let date = now.date();
+    for chunk in volumes.chunks(INSERT_CHUNK) {
+        let rows = chunk.iter().map(|volume| pair_volumes::ActiveModel {
+            exchange: Set(volume.exchange.to_lowercase()),
+            trading_pair: Set(volume.trading_pair.to_uppercase()),
+            quote_asset: Set(volume.quote_asset.to_uppercase()),
+            date: Set(date),
+            base_volume: Set(volume.base_volume),
+            quote_volume: Set(volume.quote_volume),
+            observed_at: Set(now),
+            ..Default::default()
+        });
+        PairVolumes::insert_many(rows)
+            .on_conflict(
+                OnConflict::columns([
+                    pair_volumes::Column::Exchange,
+                    pair_volumes::Column::TradingPair,
+                    pair_volumes::Column::Date,
+                ])
+                .update_columns([
+                    pair_volumes::Column::BaseVolume,
+                    pair_volumes::Column::QuoteVolume,
+                    pair_volumes::Column::ObservedAt,
+                ])
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(volumes.len())
+}
+
+/// 24h quote volume (USD for stablecoin quotes) of `symbol`/`quote_asset`
+/// on `exchange` as of `date`; None without a recent enough snapshot
+pub async fn volume_on(
+    db: &DatabaseConnection,
+    exchange: &str,
+    symbol: &str,
+    quote_asset: &str,
+    date: NaiveDate,
+) -> Result<Option<Decimal>, DbErr> {
+    let trading_pair = format!("{}{}", symbol.to_uppercase(), quote_asset.to_uppercase());
+    let snapshot = PairVolumes::find()
+        .filter(pair_volumes::Column::Exchange.eq(exchange.to_lowercase()))
+        .filter(pair_volumes::Column::TradingPair.eq(trading_pair))
+        .filter(pair_volumes::Column::Date.lte(date))
+        .filter(pair_volumes::Column::Date.gt(date - Duration::days(MAX_SNAPSHOT_AGE_DAYS)))
+        .order_by_desc(pair_volumes::Column::Date)
+        .one(db)
+        .await?;
+    Ok(snapshot.map(|s| s.quote_volume))
+}
+
+/// Whether a pair passes a `min_volume_usd` floor as of `date`; pairs
+/// without a snapshot pass
+pub async fn meets_floor(
+    db: &DatabaseConnection,
+    exchange: &str,
+    symbol: &str,
+    quote_asset: &str,
+    date: NaiveDate,
+    min_volume_usd: Decimal,
+) -> Result<bool, DbErr> {
+    match volume_on(db, exchange, symbol, quote_asset, date).await? {
+        Some(volume) => Ok(volume >= min_volume_usd),
+        None => {
+            tracing::debug!(
+                "No volume snapshot of {}/{} on {} as of {}, not screening it",
+                symbol,
+                quote_asset,
+                exchange,
+                date
+            );
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Portfolio value continuity across rebalances
+//!
+//! A rebalance trades the previous basket into a new one, so its value
+//! before fees should equal the previous basket (with accrued yield) marked
+//! at the rebalance date's prices. A gap means the rebalance was priced
+//! with different prices than the ones stored for its date (e.g. prices
+//! corrected afterwards, or a manual rebalance with a wrong value) and
+//! shows up as a jump in the NAV chart.
+//!
+//! `check_index` compares every rebalance of an index with the one before
+//! it, using the prices stored in coins_historical_prices; cash buffer
+//! positions are valued at `cash_buffer::PRICE`. Rebalances further apart
+//! than `tolerance_bps`, or whose previous basket lacks a price on the
+//! date, are flagged. Historical backfills run it when they finish and
+//! raise a data-quality alert for anything flagged; the full report is
+//! served by GET /admin/indexes/{index_id}/continuity.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder};
+
+use crate::entities::{coins_historical_prices, prelude::*, rebalances};
+use crate::models::portfolio_continuity::{PortfolioContinuityCheck, PortfolioContinuityReport};
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
+use crate::services::cash_buffer;
+use crate::services::price_reconciliation::deviation_bps;
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
+use crate::services::yield_accrual::YieldAccrual;
+
+/// Deviation accepted when none is given (0.1%)
+pub const DEFAULT_TOLERANCE_BPS: u32 = 10;
+
+/// A held position: (coin_id, weight, quantity)
+type Holding = (String, Decimal, Decimal);
+
+/// Check every rebalance of an index against the one before it
+pub async fn check_index(
+    db: &DatabaseConnection,
+    index_id: i32,
+    tolerance_bps: u32,
+) -> Result<PortfolioContinuityReport, Box<dyn std::error::Error + Send + Sync>> {
+    let all = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(index_id))
+        .order_by(rebalances::Column::Timestamp, Order::Asc)
+        .order_by(rebalances::Column::Id, Order::Asc)
+        .all(db)
+        .await?;
+
+    let mut checks = Vec::with_capacity(all.len().saturating_sub(1));
+    for pair in all.windows(2) {
+        checks.push(check_rebalance(db, index_id, &pair[0], &pair[1], tolerance_bps).await?);
+    }
+
+    Ok(PortfolioContinuityReport {
+        index_id,
+        tolerance_bps,
+        checked: checks.len(),
+        flagged: checks.iter().filter(|check| check.flagged).count(),
+        checks,
+    })
+}
+
+/// Check an index and alert on any discontinuity; failures are only
+/// logged, the check never fails its caller
+pub async fn validate_index(db: &DatabaseConnection, index_id: i32) {
+    let report = match check_index(db, index_id, DEFAULT_TOLERANCE_BPS).await {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::warn!(index_id, "Portfolio continuity check failed: {}", e);
+            return;
+        }
+    };
+    if report.flagged == 0 {
+        return;
+    }
+
+    let dates: Vec<String> = report
+        .checks
+        .iter()
+        .filter(|check| check.flagged)
+        .map(|check| check.date.to_string())
+        .collect();
+    tracing::warn!(index_id, flagged = report.flagged, "Portfolio value discontinuities on {}", dates.join(", "));
+    alerting::notify(Alert::new(
+        AlertKind::DataQuality,
+        Severity::Warning,
+        format!("Index {} value jumps at {} rebalances", index_id, report.flagged),
+        format!(
+            "Rebalances on {} differ from the previous basket by more than {} bps, see GET /admin/indexes/{}/continuity",
+            dates.join(", "),
+            report.tolerance_bps,
+            index_id
+        ),
+    ));
+}
+
+async fn check_rebalance(
+    db: &DatabaseConnection,
+    index_id: i32,
+    previous: &rebalances::Model,
+    current: &rebalances::Model,
+    tolerance_bps: u32,
+) -> Result<PortfolioContinuityCheck, Box<dyn std::error::Error + Send + Sync>> {
+    let previous_date = pricing_time::rebalance_date(previous.timestamp).ok_or("Invalid rebalance timestamp")?;
+    let date = pricing_time::rebalance_date(current.timestamp).ok_or("Invalid rebalance timestamp")?;
+
+    // The previous basket as held on the date, with its accrued yield
+    let coins = rebalance_schema::decode(previous.coins.clone())?;
+    let coin_ids: Vec<String> = coins.iter().map(|coin| coin.coin_id.clone()).collect();
+    let accrual = YieldAccrual::for_index(db, index_id, &coin_ids, previous_date).await?;
+    let holdings: Vec<Holding> = coins
+        .into_iter()
+        .map(|coin| {
+            let quantity = accrual.quantity(&coin.coin_id, coin.quantity.parse::<Decimal>()?, date);
+            Ok((coin.coin_id, coin.weight.parse::<Decimal>()?, quantity))
+        })
+        .collect::<Result<_, rust_decimal::Error>>()?;
+
+    let prices = stored_prices(db, &coin_ids, date).await?;
+    let (marked_value, missing_prices) = marked_value(&holdings, &prices);
+
+    let value_before_fees = current.portfolio_value + current.total_fees.unwrap_or(Decimal::ZERO);
+    let deviation_bps = marked_value.map(|marked| deviation_bps(value_before_fees, marked));
+    let flagged = deviation_bps.is_none_or(|bps| bps > tolerance_bps as i32);
+
+    Ok(PortfolioContinuityCheck {
+        rebalance_id: current.id,
+        date,
+        rebalance_type: current.rebalance_type.clone(),
+        previous_rebalance_id: previous.id,
+        value_before_fees,
+        marked_value,
+        deviation_bps,
+        fees_recorded: current.total_fees.is_some(),
+        missing_prices,
+        flagged,
+    })
+}
+
+/// Stored prices of `coin_ids` on `date`; cash positions at their fixed price
+async fn stored_prices(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    date: chrono::NaiveDate,
+) -> Result<HashMap<String, Decimal>, sea_orm::DbErr> {
+    let mut prices: HashMap<String, Decimal> = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.is_in(coin_ids.iter().filter(|id| !cash_buffer::is_cash(id)).cloned()))
+        .filter(coins_historical_prices::Column::Date.eq(date))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|row| (row.coin_id, row.price))
+        .collect();
+    for coin_id in coin_ids.iter().filter(|id| cash_buffer::is_cash(id)) {
+        prices.insert(coin_id.clone(), Decimal::from_f64_retain(cash_buffer::PRICE).unwrap_or(Decimal::ONE));
+    }
+    Ok(prices)
+}
+
+/// Value of `holdings` at `prices`, or None with the coins lacking a price
+fn marked_value(holdings: &[Holding], prices: &HashMap<String, Decimal>) -> (Option<Decimal>, Vec<String>) {
+    let missing: Vec<String> = holdings
+        .iter()
+        .filter(|(coin_id, _, _)| !prices.contains_key(coin_id))
+        .map(|(coin_id, _, _)| coin_id.clone())
+        .collect();
+    if !missing.is_empty() {
+        return (None, missing);
+    }
+    let value: Decimal = holdings
+        .iter()
+        .map(|(coin_id, weight, quantity)| weight * quantity * prices[coin_id])
+        .sum();
+    (Some(value), missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_marked_value() {
+        let holdings = vec![
+            ("bitcoin".to_string(), dec!(1), dec!(0.01)),
+            ("ethereum".to_string(), dec!(2), dec!(0.1)),
+            ("cash:USDC".to_string(), dec!(0.5), dec!(100)),
+        ];
+        let mut prices = HashMap::from([
+            ("bitcoin".to_string(), dec!(60000)),
+            ("cash:USDC".to_string(), dec!(1)),
+        ]);
+        assert_eq!(marked_value(&holdings, &prices), (None, vec!["ethereum".to_string()]));
+
+        prices.insert("ethereum".to_string(), dec!(3000));
+        assert_eq!(marked_value(&holdings, &prices), (Some(dec!(1250)), vec![]));
+    }
+}
@@ -0,0 +1,116 @@
+//! Fallback pricing for constituents without a price on the day
+//!
+//! When a constituent's price for a date can be neither found nor fetched:
+//!
+//! 1. its latest stored price of the `MAX_CARRY_FORWARD_DAYS` before the date
+//!    is carried forward;
+//! 2. failing that, it is excluded and the basket's other constituents stand
+//!    in for it in proportion to their value at the rebalance (weight
+//!    renormalization), provided the excluded constituents held at most
+//!    `MAX_EXCLUDED_SHARE` of that value;
+//! 3. otherwise the basket can't be priced.
+//!
+//! Index prices served on request, the daily price job and the daily price
+//! backfill all value baskets this way.
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{coins_historical_prices, prelude::*};
+
+/// How old a stored price may be to be carried forward
+pub const MAX_CARRY_FORWARD_DAYS: i64 = 7;
+
+/// Largest share of a basket's rebalance value that may be excluded
+pub const MAX_EXCLUDED_SHARE: Decimal = dec!(0.2);
+
+/// Fallback applied to a constituent's price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "camelCase")]
+pub enum PriceFallback {
+    /// The price of an earlier day, `from`, was used
+    CarriedForward { from: NaiveDate },
+    /// No price was used; the other constituents stand in for this one
+    Excluded,
+}
+
+/// Latest stored price of a coin in the `MAX_CARRY_FORWARD_DAYS` before
+/// `date`, with the date it is for
+pub async fn carried_forward_price(
+    db: &DatabaseConnection,
+    coin_id: &str,
+    date: NaiveDate,
+) -> Result<Option<(Decimal, PriceFallback)>, DbErr> {
+    let latest = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
+        .filter(coins_historical_prices::Column::Date.lt(date))
+        .filter(coins_historical_prices::Column::Date.gte(date - Duration::days(MAX_CARRY_FORWARD_DAYS)))
+        .order_by(coins_historical_prices::Column::Date, Order::Desc)
+        .one(db)
+        .await?;
+    Ok(latest.map(|record| (record.price, PriceFallback::CarriedForward { from: record.date })))
+}
+
+/// Growth since the rebalance that excluded constituents are valued with:
+/// that of the priced ones, from each constituent's value at the rebalance
+/// and on the day (None when excluded)
+///
+/// None when nothing is excluded. Fails when the excluded constituents held
+/// more than `MAX_EXCLUDED_SHARE` of the rebalance value.
+pub fn stand_in_ratio(values: &[(Decimal, Option<Decimal>)]) -> Result<Option<Decimal>, String> {
+    let excluded = values.iter().filter(|(_, t1)| t1.is_none()).count();
+    if excluded == 0 {
+        return Ok(None);
+    }
+
+    let total_t0: Decimal = values.iter().map(|(t0, _)| t0).sum();
+    let priced_t0: Decimal = values.iter().filter(|(_, t1)| t1.is_some()).map(|(t0, _)| t0).sum();
+    let priced_t1: Decimal = values.iter().filter_map(|(_, t1)| *t1).sum();
+    if total_t0 <= Decimal::ZERO || priced_t0 <= Decimal::ZERO {
+        return Err(format!("No price for any of {} constituents", values.len()));
+    }
+    let excluded_share = (total_t0 - priced_t0) / total_t0;
+    if excluded_share > MAX_EXCLUDED_SHARE {
+        return Err(format!(
+            "{} constituents without a price hold {}% of the basket, more than the {}% that may be excluded",
+            excluded,
+            (excluded_share * dec!(100)).round_dp(2),
+            MAX_EXCLUDED_SHARE * dec!(100)
+        ));
+    }
+    Ok(Some(priced_t1 / priced_t0))
+}
+
+/// Value of a basket on the day, from its constituents' values as for
+/// `stand_in_ratio`: the sum of the day's values, or with exclusions the
+/// rebalance value grown by the stand-in ratio
+pub fn renormalized_value(values: &[(Decimal, Option<Decimal>)]) -> Result<Decimal, String> {
+    Ok(match stand_in_ratio(values)? {
+        None => values.iter().filter_map(|(_, t1)| *t1).sum(),
+        Some(ratio) => values.iter().map(|(t0, _)| t0).sum::<Decimal>() * ratio,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renormalized_value() {
+        // All priced: the plain sum
+        assert_eq!(renormalized_value(&[(dec!(50), Some(dec!(60))), (dec!(50), Some(dec!(40)))]), Ok(dec!(100)));
+
+        // A 10% constituent excluded: the rest's +20% applies to the whole basket
+        let values = [(dec!(90), Some(dec!(108))), (dec!(10), None)];
+        assert_eq!(stand_in_ratio(&values), Ok(Some(dec!(1.2))));
+        assert_eq!(renormalized_value(&values), Ok(dec!(120)));
+
+        // Too much excluded, or everything
+        assert!(renormalized_value(&[(dec!(70), Some(dec!(70))), (dec!(30), None)]).is_err());
+        assert!(renormalized_value(&[(dec!(100), None)]).is_err());
+        assert_eq!(renormalized_value(&[]), Ok(Decimal::ZERO));
+    }
+}
@@ -0,0 +1,150 @@
+//! Partition maintenance for coins_historical_prices
+//!
+//! The table is range-partitioned by year (see the
+//! m20260201_000001_partition_coins_historical_prices migration). Rows
+//! dated in a year without a partition land in the DEFAULT partition, so
+//! partitions are created a year ahead. If rows already reached the default
+//! partition, they are moved into the new partition as it is created.
+
+use chrono::{Datelike, NaiveDate};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, FromQueryResult, Statement, TransactionTrait};
+
+const PARENT_TABLE: &str = "coins_historical_prices";
+const DEFAULT_PARTITION: &str = "coins_historical_prices_default";
+
+/// Years ahead of the current one to keep partitions for
+const YEARS_AHEAD: i32 = 1;
+
+/// Name of the partition holding `year`
+pub fn partition_name(year: i32) -> String {
+    format!("{}_y{}", PARENT_TABLE, year)
+}
+
+/// Date range [from, to) covered by the partition of `year`
+fn partition_bounds(year: i32) -> Option<(NaiveDate, NaiveDate)> {
+    Some((NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year + 1, 1, 1)?))
+}
+
+/// Years that must have a partition on `today`
+pub fn required_years(today: NaiveDate) -> Vec<i32> {
+    (today.year()..=today.year() + YEARS_AHEAD).collect()
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Exists {
+    exists: bool,
+}
+
+async fn partition_exists(
+    db: &DatabaseConnection,
+    name: &str,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let row = Exists::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM pg_inherits i
+            JOIN pg_class c ON c.oid = i.inhrelid
+            JOIN pg_class p ON p.oid = i.inhparent
+            WHERE p.relname = $1 AND c.relname = $2
+        ) AS exists
+        "#,
+        [PARENT_TABLE.into(), name.into()],
+    ))
+    .one(db)
+    .await?;
+    Ok(row.map(|r| r.exists).unwrap_or(false))
+}
+
+/// Create the partition for `year`, moving any of its rows out of the
+/// default partition
+///
+/// A partition can't be created with `PARTITION OF` while the default
+/// partition holds rows in its range, so it is built as a standalone table,
+/// filled, and attached in one transaction.
+async fn create_partition(
+    db: &DatabaseConnection,
+    year: i32,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let (from, to) = partition_bounds(year).ok_or_else(|| format!("Invalid partition year {}", year))?;
+    let name = partition_name(year);
+    let range = format!("date >= '{}' AND date < '{}'", from, to);
+
+    let txn = db.begin().await?;
+    txn.execute_unprepared(&format!(
+        "CREATE TABLE {} (LIKE {} INCLUDING DEFAULTS)",
+        name, PARENT_TABLE
+    ))
+    .await?;
+    let moved = txn
+        .execute_unprepared(&format!(
+            "INSERT INTO {} SELECT * FROM {} WHERE {}",
+            name, DEFAULT_PARTITION, range
+        ))
+        .await?
+        .rows_affected();
+    if moved > 0 {
+        txn.execute_unprepared(&format!("DELETE FROM {} WHERE {}", DEFAULT_PARTITION, range))
+            .await?;
+    }
+    txn.execute_unprepared(&format!(
+        "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES FROM ('{}') TO ('{}')",
+        PARENT_TABLE, name, from, to
+    ))
+    .await?;
+    txn.commit().await?;
+
+    Ok(moved)
+}
+
+/// Make sure partitions exist for the current year and the years ahead
+///
+/// Returns the years whose partition was created.
+pub async fn ensure_partitions(
+    db: &DatabaseConnection,
+    today: NaiveDate,
+) -> Result<Vec<i32>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut created = Vec::new();
+
+    for year in required_years(today) {
+        if partition_exists(db, &partition_name(year)).await? {
+            continue;
+        }
+
+        let moved = create_partition(db, year).await?;
+        if moved > 0 {
+            tracing::warn!(year = year, rows = moved, "Moved rows out of the default price partition");
+        }
+        tracing::info!(year = year, "Created coins_historical_prices partition");
+        created.push(year);
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_name() {
+        assert_eq!(partition_name(2026), "coins_historical_prices_y2026");
+    }
+
+    #[test]
+    fn test_partition_bounds() {
+        assert_eq!(
+            partition_bounds(2026),
+            Some((
+                NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_required_years_include_next_year() {
+        let today = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        assert_eq!(required_years(today), vec![2026, 2027]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Reconciliation of stored coin prices against a second source
+//!
+//! coins_historical_prices comes almost entirely from CoinGecko. Once a day
+//! a sample of coins is priced independently from Binance daily klines and
+//! compared with the stored value; every comparison is recorded in
+//! price_reconciliation_checks, and deviations above the threshold are
+//! logged as data-quality alerts. The recorded checks back the accuracy
+//! claims of the pricing methodology (see GET /admin/price-reconciliation).
+//!
+//! CoinGecko's daily price for date D is a snapshot at 00:00 UTC on D, so it
+//! is compared with the open of Binance's D candle, quoted in USDT. Coins
+//! Binance doesn't list against USDT are skipped. Rows captured intraday
+//! (fetched while D was still the current day) show up as deviations too,
+//! which is intended: they are part of what the stored data really contains.
+//!
+//! Configuration (environment):
+//! - `PRICE_RECONCILIATION_SAMPLE_SIZE` - coins checked per day (default 20)
+//! - `PRICE_RECONCILIATION_THRESHOLD_BPS` - alert threshold (default 200 = 2%)
+//! - `BINANCE_API_URL` - Binance REST base URL (default https://api.binance.com)
+
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult,
+    QueryFilter, QueryOrder, Set, Statement,
+};
+
+use crate::entities::{prelude::*, price_reconciliation_checks};
+use crate::models::price_reconciliation::{PriceReconciliationCheck, PriceReconciliationReport};
+use crate::services::pricing_time;
+
+const ENV_SAMPLE_SIZE: &str = "PRICE_RECONCILIATION_SAMPLE_SIZE";
+const ENV_THRESHOLD_BPS: &str = "PRICE_RECONCILIATION_THRESHOLD_BPS";
+const ENV_BINANCE_API_URL: &str = "BINANCE_API_URL";
+
+const DEFAULT_SAMPLE_SIZE: u32 = 20;
+const DEFAULT_THRESHOLD_BPS: u32 = 200;
+const DEFAULT_BINANCE_API_URL: &str = "https://api.binance.com";
+
+/// Coins are sampled from the largest this many by market cap on the day
+const CANDIDATE_POOL_SIZE: i64 = 250;
+
+/// Name recorded in price_reconciliation_checks.source
+pub const SOURCE_BINANCE: &str = "binance";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconciliationConfig {
+    pub sample_size: u32,
+    pub threshold_bps: u32,
+    pub binance_api_url: String,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            threshold_bps: DEFAULT_THRESHOLD_BPS,
+            binance_api_url: DEFAULT_BINANCE_API_URL.to_string(),
+        }
+    }
+}
+
+impl ReconciliationConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(ENV_SAMPLE_SIZE).ok().as_deref(),
+            std::env::var(ENV_THRESHOLD_BPS).ok().as_deref(),
+            std::env::var(ENV_BINANCE_API_URL).ok().as_deref(),
+        )
+    }
+
+    /// Build from raw setting values; invalid values fall back to the defaults
+    fn from_values(sample_size: Option<&str>, threshold_bps: Option<&str>, binance_api_url: Option<&str>) -> Self {
+        let positive = |name: &str, value: Option<&str>, default: u32| match value.map(|v| v.trim().parse::<u32>()) {
+            Some(Ok(v)) if v > 0 => v,
+            Some(_) => {
+                tracing::warn!("Invalid {}, using {}", name, default);
+                default
+            }
+            None => default,
+        };
+
+        Self {
+            sample_size: positive(ENV_SAMPLE_SIZE, sample_size, DEFAULT_SAMPLE_SIZE),
+            threshold_bps: positive(ENV_THRESHOLD_BPS, threshold_bps, DEFAULT_THRESHOLD_BPS),
+            binance_api_url: binance_api_url
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .unwrap_or_else(|| DEFAULT_BINANCE_API_URL.to_string()),
+        }
+    }
+}
+
+/// Daily kline client for the Binance spot API
+pub struct BinanceKlines {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl BinanceKlines {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// USDT price of `symbol` at 00:00 UTC on `date` (the open of that day's
+    /// candle), or None if Binance has no such market or candle
+    pub async fn open_price(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Decimal>, Box<dyn std::error::Error + Send + Sync>> {
+        let start = day_start_ms(date);
+        let response = self
+            .client
+            .get(format!("{}/api/v3/klines", self.base_url))
+            .query(&[
+                ("symbol", format!("{}USDT", symbol.to_uppercase())),
+                ("interval", "1d".to_string()),
+                ("startTime", start.to_string()),
+                ("limit", "1".to_string()),
+            ])
+            .send()
+            .await?;
+
+        // 400 is returned for unknown symbols
+        if response.status().as_u16() == 400 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Binance API error {}: {}", status, body).into());
+        }
+
+        let klines: Vec<Vec<serde_json::Value>> = response.json().await?;
+        Ok(kline_open(&klines, start))
+    }
+}
+
+fn day_start_ms(date: NaiveDate) -> i64 {
+    pricing_time::cutoff_millis(date)
+}
+
+/// Open price of the kline starting at `start_ms`
+///
+/// Klines are `[open_time, open, high, low, close, ...]` with prices as
+/// strings. A listing that started later returns a later candle instead,
+/// which doesn't count.
+fn kline_open(klines: &[Vec<serde_json::Value>], start_ms: i64) -> Option<Decimal> {
+    let kline = klines.first()?;
+    if kline.first()?.as_i64()? != start_ms {
+        return None;
+    }
+    kline.get(1)?.as_str()?.parse().ok().filter(|price: &Decimal| *price > Decimal::ZERO)
+}
+
+/// |stored - reference| / reference in basis points, rounded
+pub fn deviation_bps(stored: Decimal, reference: Decimal) -> i32 {
+    if reference <= Decimal::ZERO {
+        return i32::MAX;
+    }
+    ((stored - reference).abs() / reference * Decimal::from(10_000))
+        .round()
+        .to_i32()
+        .unwrap_or(i32::MAX)
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SampledPrice {
+    coin_id: String,
+    symbol: String,
+    price: Decimal,
+}
+
+/// Pick up to `sample_size` coins priced on `date`
+///
+/// Drawn from the day's largest coins by market cap, index constituents
+/// first. The order is a hash of coin and date, so each day checks a
+/// different but reproducible sample.
+async fn sample_prices(
+    db: &DatabaseConnection,
+    date: NaiveDate,
+    sample_size: u32,
+) -> Result<Vec<SampledPrice>, sea_orm::DbErr> {
+    SampledPrice::find_by_statement(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+        WITH candidates AS (
+            SELECT coin_id, symbol, price
+            FROM coins_historical_prices
+            WHERE date = $1 AND price > 0
+            ORDER BY market_cap DESC NULLS LAST
+            LIMIT $2
+        )
+        SELECT coin_id, symbol, price
+        FROM candidates
+        ORDER BY coin_id IN (SELECT coin_id FROM index_constituents) DESC,
+                 md5(coin_id || $1::text)
+        LIMIT $3
+        "#,
+        [date.into(), CANDIDATE_POOL_SIZE.into(), (sample_size as i64).into()],
+    ))
+    .all(db)
+    .await
+}
+
+/// Outcome of one reconciliation run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconciliationSummary {
+    pub sampled: usize,
+    pub checked: usize,
+    /// Not listed on the reference source
+    pub unavailable: usize,
+    pub errors: usize,
+    pub flagged: usize,
+    pub max_deviation_bps: i32,
+}
+
+/// Check a sample of the prices stored for `date` against Binance
+pub async fn run(
+    db: &DatabaseConnection,
+    source: &BinanceKlines,
+    config: &ReconciliationConfig,
+    date: NaiveDate,
+) -> Result<ReconciliationSummary, Box<dyn std::error::Error + Send + Sync>> {
+    let sample = sample_prices(db, date, config.sample_size).await?;
+    let mut summary = ReconciliationSummary { sampled: sample.len(), ..Default::default() };
+
+    for (i, coin) in sample.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let reference = match source.open_price(&coin.symbol, date).await {
+            Ok(Some(price)) => price,
+            Ok(None) => {
+                summary.unavailable += 1;
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!(coin_id = %coin.coin_id, error = %e, "Failed to fetch reference price");
+                summary.errors += 1;
+                continue;
+            }
+        };
+
+        let deviation = deviation_bps(coin.price, reference);
+        let flagged = deviation > config.threshold_bps as i32;
+
+        if flagged {
+            tracing::warn!(
+                coin_id = %coin.coin_id,
+                symbol = %coin.symbol,
+                date = %date,
+                stored_price = %coin.price,
+                reference_price = %reference,
+                source = SOURCE_BINANCE,
+                deviation_bps = deviation,
+                threshold_bps = config.threshold_bps,
+                "Stored price deviates from reference source"
+            );
+        }
+
+        record_check(db, coin, date, reference, deviation, flagged).await?;
+
+        summary.checked += 1;
+        summary.max_deviation_bps = summary.max_deviation_bps.max(deviation);
+        if flagged {
+            summary.flagged += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+async fn record_check(
+    db: &DatabaseConnection,
+    coin: &SampledPrice,
+    date: NaiveDate,
+    reference: Decimal,
+    deviation: i32,
+    flagged: bool,
+) -> Result<(), sea_orm::DbErr> {
+    let check = price_reconciliation_checks::ActiveModel {
+        coin_id: Set(coin.coin_id.clone()),
+        symbol: Set(coin.symbol.clone()),
+        date: Set(date),
+        source: Set(SOURCE_BINANCE.to_string()),
+        stored_price: Set(coin.price),
+        reference_price: Set(reference),
+        deviation_bps: Set(deviation),
+        flagged: Set(flagged),
+        checked_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    PriceReconciliationChecks::insert(check)
+        .on_conflict(
+            OnConflict::columns([
+                price_reconciliation_checks::Column::CoinId,
+                price_reconciliation_checks::Column::Date,
+                price_reconciliation_checks::Column::Source,
+            ])
+            .update_columns([
+                price_reconciliation_checks::Column::Symbol,
+                price_reconciliation_checks::Column::StoredPrice,
+                price_reconciliation_checks::Column::ReferencePrice,
+                price_reconciliation_checks::Column::DeviationBps,
+                price_reconciliation_checks::Column::Flagged,
+                price_reconciliation_checks::Column::CheckedAt,
+            ])
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[i32], p: f64) -> Option<i32> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Summarize the checks recorded over the last `days` days
+pub async fn report(
+    db: &DatabaseConnection,
+    days: u32,
+    today: NaiveDate,
+) -> Result<PriceReconciliationReport, Box<dyn std::error::Error + Send + Sync>> {
+    let since = today - chrono::Duration::days(days as i64);
+    let checks = PriceReconciliationChecks::find()
+        .filter(price_reconciliation_checks::Column::Date.gte(since))
+        .order_by_desc(price_reconciliation_checks::Column::Date)
+        .order_by_desc(price_reconciliation_checks::Column::DeviationBps)
+        .all(db)
+        .await?;
+
+    Ok(summarize(since, checks))
+}
+
+fn summarize(since: NaiveDate, checks: Vec<price_reconciliation_checks::Model>) -> PriceReconciliationReport {
+    let mut deviations: Vec<i32> = checks.iter().map(|c| c.deviation_bps).collect();
+    deviations.sort_unstable();
+
+    let flagged: Vec<PriceReconciliationCheck> = checks
+        .into_iter()
+        .filter(|c| c.flagged)
+        .map(PriceReconciliationCheck::from)
+        .collect();
+
+    PriceReconciliationReport {
+        since,
+        checks: deviations.len(),
+        flagged: flagged.len(),
+        median_deviation_bps: percentile(&deviations, 50.0),
+        p95_deviation_bps: percentile(&deviations, 95.0),
+        max_deviation_bps: deviations.last().copied(),
+        flagged_checks: flagged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_config_defaults_and_overrides() {
+        assert_eq!(ReconciliationConfig::from_values(None, None, None), ReconciliationConfig::default());
+
+        let config = ReconciliationConfig::from_values(Some("5"), Some("50"), Some("http://localhost:9000/"));
+        assert_eq!(config.sample_size, 5);
+        assert_eq!(config.threshold_bps, 50);
+        assert_eq!(config.binance_api_url, "http://localhost:9000");
+
+        let invalid = ReconciliationConfig::from_values(Some("0"), Some("x"), Some(" "));
+        assert_eq!(invalid, ReconciliationConfig::default());
+    }
+
+    #[test]
+    fn test_deviation_bps() {
+        assert_eq!(deviation_bps(dec!(100), dec!(100)), 0);
+        assert_eq!(deviation_bps(dec!(102), dec!(100)), 200);
+        assert_eq!(deviation_bps(dec!(99.5), dec!(100)), 50);
+        assert_eq!(deviation_bps(dec!(1), dec!(0)), i32::MAX);
+    }
+
+    #[test]
+    fn test_kline_open_requires_matching_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let start = day_start_ms(date);
+        let kline = |open_time: i64| vec![json!(open_time), json!("94591.78"), json!("97839.50")];
+
+        assert_eq!(kline_open(&[kline(start)], start), Some(dec!(94591.78)));
+        // Listed later: Binance returns the first candle after startTime
+        assert_eq!(kline_open(&[kline(start + 86_400_000)], start), None);
+        assert_eq!(kline_open(&[], start), None);
+    }
+
+    #[test]
+    fn test_summarize_percentiles_and_flags() {
+        let since = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let check = |coin_id: &str, deviation_bps: i32| price_reconciliation_checks::Model {
+            id: 0,
+            coin_id: coin_id.to_string(),
+            symbol: coin_id.to_uppercase(),
+            date: since,
+            source: SOURCE_BINANCE.to_string(),
+            stored_price: dec!(1),
+            reference_price: dec!(1),
+            deviation_bps,
+            flagged: deviation_bps > 200,
+            checked_at: since.and_hms_opt(1, 0, 0).unwrap(),
+        };
+
+        let report = summarize(since, vec![check("a", 10), check("b", 5), check("c", 350), check("d", 20)]);
+        assert_eq!(report.checks, 4);
+        assert_eq!(report.flagged, 1);
+        assert_eq!(report.median_deviation_bps, Some(10));
+        assert_eq!(report.p95_deviation_bps, Some(350));
+        assert_eq!(report.max_deviation_bps, Some(350));
+        assert_eq!(report.flagged_checks[0].coin_id, "c");
+
+        let empty = summarize(since, vec![]);
+        assert_eq!(empty.median_deviation_bps, None);
+    }
+}
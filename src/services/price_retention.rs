@@ -0,0 +1,312 @@
+//! Retention policy for coins_historical_prices
+//!
+//! The table holds a daily row per coin for thousands of coins and grows
+//! without bound. Coins referenced by an index (current constituents or any
+//! past rebalance) keep full daily history since index prices and backfills
+//! are computed from it. For every other coin, rows older than the retention
+//! age are either downsampled to one row per week (the last day of each
+//! week) or dropped.
+//!
+//! Configuration (environment):
+//! - `PRICE_RETENTION_YEARS` - age after which rows are compacted (default 3)
+//! - `PRICE_RETENTION_MODE` - `downsample` (default) or `drop`
+//! - `PRICE_RETENTION_DRY_RUN` - only report what would be removed (default true)
+//!
+//! Note: backfilling a new index whose initial_date is older than the
+//! retention age selects among coins that may have been compacted, so keep
+//! the age above the oldest initial_date you expect to create.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, FromQueryResult, Statement};
+
+use crate::models::price_retention::PriceRetentionReport;
+
+const ENV_YEARS: &str = "PRICE_RETENTION_YEARS";
+const ENV_MODE: &str = "PRICE_RETENTION_MODE";
+const ENV_DRY_RUN: &str = "PRICE_RETENTION_DRY_RUN";
+
+const DEFAULT_YEARS: u32 = 3;
+
+/// What happens to old rows of unreferenced coins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    /// Keep the last row of each ISO week
+    Downsample,
+    /// Delete all rows
+    Drop,
+}
+
+impl RetentionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionMode::Downsample => "downsample",
+            RetentionMode::Drop => "drop",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "downsample" => Some(RetentionMode::Downsample),
+            "drop" => Some(RetentionMode::Drop),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionConfig {
+    pub max_age_years: u32,
+    pub mode: RetentionMode,
+    pub dry_run: bool,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self { max_age_years: DEFAULT_YEARS, mode: RetentionMode::Downsample, dry_run: true }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(ENV_YEARS).ok().as_deref(),
+            std::env::var(ENV_MODE).ok().as_deref(),
+            std::env::var(ENV_DRY_RUN).ok().as_deref(),
+        )
+    }
+
+    /// Build from raw setting values; invalid values fall back to the defaults
+    fn from_values(years: Option<&str>, mode: Option<&str>, dry_run: Option<&str>) -> Self {
+        let defaults = Self::default();
+        let max_age_years = match years.map(|y| y.trim().parse::<u32>()) {
+            Some(Ok(y)) if y > 0 => y,
+            Some(_) => {
+                tracing::warn!("Invalid {}, using {} years", ENV_YEARS, DEFAULT_YEARS);
+                DEFAULT_YEARS
+            }
+            None => DEFAULT_YEARS,
+        };
+        let mode = match mode {
+            Some(m) => RetentionMode::parse(m).unwrap_or_else(|| {
+                tracing::warn!("Invalid {} '{}', using downsample", ENV_MODE, m);
+                defaults.mode
+            }),
+            None => defaults.mode,
+        };
+        let dry_run = dry_run
+            .map(|v| !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "no"))
+            .unwrap_or(defaults.dry_run);
+
+        Self { max_age_years, mode, dry_run }
+    }
+
+    /// Rows dated strictly before this are subject to retention
+    pub fn cutoff(&self, today: NaiveDate) -> NaiveDate {
+        today
+            .checked_sub_months(Months::new(self.max_age_years * 12))
+            .unwrap_or(NaiveDate::MIN)
+    }
+
+    /// Which rows of `date` the policy leaves in coins_historical_prices
+    ///
+    /// Readers ranking every coin on a date use this so they don't silently
+    /// rank only the coins that happen to have a row that day.
+    pub fn coverage(&self, today: NaiveDate, date: NaiveDate) -> PriceCoverage {
+        let cutoff = self.cutoff(today);
+        if self.dry_run || date >= cutoff {
+            return PriceCoverage::Daily;
+        }
+        match self.mode {
+            RetentionMode::Drop => PriceCoverage::ReferencedOnly,
+            RetentionMode::Downsample => {
+                let from = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                let to = (from + Duration::days(6)).min(cutoff - Duration::days(1));
+                PriceCoverage::Weekly { from, to }
+            }
+        }
+    }
+}
+
+/// Rows of a date left by the retention policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceCoverage {
+    /// Every coin keeps its daily row
+    Daily,
+    /// Unreferenced coins keep one row of the date's week, dated within
+    /// `from..=to` (the last one before the cutoff)
+    Weekly { from: NaiveDate, to: NaiveDate },
+    /// Only coins referenced by an index have rows
+    ReferencedOnly,
+}
+
+impl PriceCoverage {
+    /// Dates whose latest row per coin stands for `date`
+    pub fn window(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match *self {
+            PriceCoverage::Weekly { from, to } => (from, to),
+            PriceCoverage::Daily | PriceCoverage::ReferencedOnly => (date, date),
+        }
+    }
+}
+
+/// Coins used by any index, now or in a past rebalance
+///
+/// Rebalance entries are read in every schema version (v1 manual
+/// rebalances stored `coinId`, see services::rebalance_schema).
+const REFERENCED_COINS_SQL: &str = r#"
+    SELECT coin_id FROM index_constituents
+    UNION
+    SELECT COALESCE(elem->>'coin_id', elem->>'coinId') FROM rebalances
+    CROSS JOIN LATERAL jsonb_array_elements(rebalances.coins) AS elem
+"#;
+
+/// WHERE clause selecting the rows of coins_historical_prices `p` to remove
+///
+/// `$1` is the cutoff date.
+fn removable_rows_condition(mode: RetentionMode) -> String {
+    let mut condition = format!(
+        "p.date < $1 AND NOT EXISTS (SELECT 1 FROM ({}) r WHERE r.coin_id = p.coin_id)",
+        REFERENCED_COINS_SQL
+    );
+    if mode == RetentionMode::Downsample {
+        condition.push_str(
+            " AND p.id NOT IN (
+                SELECT DISTINCT ON (coin_id, date_trunc('week', date)) id
+                FROM coins_historical_prices
+                WHERE date < $1
+                ORDER BY coin_id, date_trunc('week', date), date DESC
+            )",
+        );
+    }
+    condition
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RemovableStats {
+    row_count: i64,
+    coin_count: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+/// Apply the retention policy (or only report it when `config.dry_run`)
+pub async fn run(
+    db: &DatabaseConnection,
+    config: &RetentionConfig,
+) -> Result<PriceRetentionReport, Box<dyn std::error::Error + Send + Sync>> {
+    let backend = db.get_database_backend();
+    let cutoff = config.cutoff(Utc::now().date_naive());
+    let condition = removable_rows_condition(config.mode);
+
+    let referenced = Count::find_by_statement(Statement::from_string(
+        backend,
+        format!("SELECT COUNT(*) AS count FROM ({}) r", REFERENCED_COINS_SQL),
+    ))
+    .one(db)
+    .await?
+    .map(|c| c.count)
+    .unwrap_or(0);
+
+    let stats = RemovableStats::find_by_statement(Statement::from_sql_and_values(
+        backend,
+        format!(
+            "SELECT COUNT(*) AS row_count, COUNT(DISTINCT p.coin_id) AS coin_count
+             FROM coins_historical_prices p WHERE {}",
+            condition
+        ),
+        [cutoff.into()],
+    ))
+    .one(db)
+    .await?
+    .unwrap_or(RemovableStats { row_count: 0, coin_count: 0 });
+
+    let rows_removed = if config.dry_run || stats.row_count == 0 {
+        stats.row_count
+    } else {
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                backend,
+                format!("DELETE FROM coins_historical_prices p WHERE {}", condition),
+                [cutoff.into()],
+            ))
+            .await?;
+        result.rows_affected() as i64
+    };
+
+    Ok(PriceRetentionReport {
+        mode: config.mode.as_str().to_string(),
+        dry_run: config.dry_run,
+        cutoff_date: cutoff,
+        referenced_coins: referenced,
+        affected_coins: stats.coin_count,
+        rows_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults_to_safe_dry_run() {
+        let config = RetentionConfig::from_values(None, None, None);
+        assert_eq!(config, RetentionConfig::default());
+        assert!(config.dry_run);
+        assert_eq!(config.mode, RetentionMode::Downsample);
+    }
+
+    #[test]
+    fn test_config_from_values() {
+        let config = RetentionConfig::from_values(Some("5"), Some("DROP"), Some("false"));
+        assert_eq!(config.max_age_years, 5);
+        assert_eq!(config.mode, RetentionMode::Drop);
+        assert!(!config.dry_run);
+    }
+
+    #[test]
+    fn test_invalid_values_fall_back() {
+        let config = RetentionConfig::from_values(Some("0"), Some("archive"), Some("maybe"));
+        assert_eq!(config.max_age_years, DEFAULT_YEARS);
+        assert_eq!(config.mode, RetentionMode::Downsample);
+        assert!(config.dry_run);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let today = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let config = RetentionConfig { max_age_years: 3, dry_run: false, ..Default::default() };
+
+        assert_eq!(config.coverage(today, date(2024, 6, 5)), PriceCoverage::Daily);
+        // Wednesday 2022-06-01 is in the week of Monday 2022-05-30
+        let weekly = config.coverage(today, date(2022, 6, 1));
+        assert_eq!(weekly, PriceCoverage::Weekly { from: date(2022, 5, 30), to: date(2022, 6, 5) });
+        assert_eq!(weekly.window(date(2022, 6, 1)), (date(2022, 5, 30), date(2022, 6, 5)));
+        // The week holding the cutoff ends before it
+        assert_eq!(
+            config.coverage(today, date(2023, 2, 27)),
+            PriceCoverage::Weekly { from: date(2023, 2, 27), to: date(2023, 2, 27) }
+        );
+
+        let drop = RetentionConfig { mode: RetentionMode::Drop, ..config };
+        assert_eq!(drop.coverage(today, date(2022, 6, 1)), PriceCoverage::ReferencedOnly);
+        let dry_run = RetentionConfig { dry_run: true, ..drop };
+        assert_eq!(dry_run.coverage(today, date(2022, 6, 1)), PriceCoverage::Daily);
+    }
+
+    #[test]
+    fn test_cutoff() {
+        let config = RetentionConfig { max_age_years: 3, ..Default::default() };
+        let today = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        assert_eq!(config.cutoff(today), NaiveDate::from_ymd_opt(2023, 2, 28).unwrap());
+    }
+
+    #[test]
+    fn test_drop_mode_skips_weekly_keep() {
+        assert!(removable_rows_condition(RetentionMode::Downsample).contains("DISTINCT ON"));
+        assert!(!removable_rows_condition(RetentionMode::Drop).contains("DISTINCT ON"));
+    }
+}
@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+
 use chrono::{NaiveDate, Utc};
+use futures_util::stream::{self, StreamExt};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 
-use crate::{entities::{coins_historical_prices, prelude::*}, services::coingecko::CoinGeckoService};
+use crate::{entities::{coins_historical_prices, prelude::*}, services::{cash_buffer, coingecko::CoinGeckoService, pricing_time}};
 
 
 /// Get historical price for a coin on a specific date from coins_historical_prices table.
@@ -23,7 +27,11 @@ pub async fn get_coins_historical_price_for_date(
         .await?;
 
     if let Some(record) = price_record {
-        return Ok(Some(record.price.to_string().parse::<f64>().unwrap_or(0.0)));
+        let price = record
+            .price
+            .to_f64()
+            .ok_or_else(|| format!("Price {} of {} on {} is out of range", record.price, coin_id, target_date))?;
+        return Ok(Some(price));
     }
 
     // No price found for this date
@@ -40,11 +48,12 @@ pub async fn get_coins_historical_price_for_date(
 ///
 /// This is a SELF-HEALING version that:
 /// 1. Checks DB for exact date match
-/// 2. If missing, fetches from CoinGecko (from last_stored_date+1 to target_date)
-/// 3. Stores all fetched prices in DB
-/// 4. Returns the target date price
+/// 2. If missing, fetches that single day from CoinGecko (see
+///    `CoinGeckoService::get_price_on_date`)
+/// 3. Stores it in DB and returns it
 ///
-/// This prevents rebalancing failures due to missing price data.
+/// This prevents rebalancing failures due to missing price data. Wider gaps
+/// are left to the coins historical prices sync.
 pub async fn get_or_fetch_coins_historical_price(
     db: &DatabaseConnection,
     coingecko: &CoinGeckoService,
@@ -52,6 +61,11 @@ pub async fn get_or_fetch_coins_historical_price(
     symbol: &str,
     target_date: NaiveDate,
 ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    // Cash buffer positions have a fixed price
+    if cash_buffer::is_cash(coin_id) {
+        return Ok(cash_buffer::PRICE);
+    }
+
     // Step 1: Try to get from DB first
     if let Some(price) = get_coins_historical_price_for_date(db, coin_id, target_date).await? {
         tracing::debug!("Found price for {} on {} in DB: ${}", symbol, target_date, price);
@@ -66,78 +80,29 @@ pub async fn get_or_fetch_coins_historical_price(
         target_date
     );
 
-    // Get last stored date for this coin
-    let last_stored_date = get_last_stored_date_for_coin(db, coin_id).await?;
-
-    // Calculate days to fetch
-    let days_to_fetch = match last_stored_date {
-        Some(last_date) if last_date < target_date => {
-            // Fetch from last_date+1 to target_date
-            let days = (target_date - last_date).num_days();
-            tracing::info!(
-                "Fetching {} days of prices for {} (from {} to {})",
-                days,
-                symbol,
-                last_date.succ_opt().unwrap_or(last_date),
-                target_date
-            );
-            days.to_string()
-        }
-        _ => {
-            // No previous data or last_date >= target_date - fetch reasonable range
-            let today = Utc::now().date_naive();
-            let days = (today - target_date).num_days() + 1;
-            tracing::info!(
-                "Fetching {} days of prices for {} (no previous data)",
-                days,
-                symbol
-            );
-            days.to_string()
-        }
-    };
-
-    // Step 3: Fetch from CoinGecko
-    let stored_count = fetch_and_store_prices_for_coin(
-        db,
-        coingecko,
-        coin_id,
-        symbol,
-        &days_to_fetch,
-    )
-    .await?;
-
-    tracing::info!(
-        "Stored {} new prices for {} ({})",
-        stored_count,
-        symbol,
-        coin_id
-    );
-
-    // Step 4: Try to get the target date price again
-    get_coins_historical_price_for_date(db, coin_id, target_date)
-        .await?
-        .ok_or_else(|| {
-            format!(
-                "Failed to fetch price for {} ({}) on {} from CoinGecko",
-                symbol, coin_id, target_date
-            )
-            .into()
-        })
-}
+    let price = coingecko.get_price_on_date(coin_id, target_date).await?.ok_or_else(|| {
+        format!(
+            "Failed to fetch price for {} ({}) on {} from CoinGecko",
+            symbol, coin_id, target_date
+        )
+    })?;
 
-/// Get the last stored date for a coin
-async fn get_last_stored_date_for_coin(
-    db: &DatabaseConnection,
-    coin_id: &str,
-) -> Result<Option<NaiveDate>, Box<dyn std::error::Error + Send + Sync>> {
-    let last_record = CoinsHistoricalPrices::find()
-        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
-        .order_by(coins_historical_prices::Column::Date, Order::Desc)
-        .limit(1)
-        .one(db)
-        .await?;
+    // Step 3: Store it for the next lookup
+    let new_price = coins_historical_prices::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        symbol: Set(symbol.to_uppercase()),
+        date: Set(target_date),
+        price: Set(decimal_from_f64(price).ok_or("Invalid price")?),
+        market_cap: Set(None),
+        volume: Set(None),
+        ..Default::default()
+    };
+    match new_price.insert(db).await {
+        Ok(_) => tracing::info!("Stored price for {} on {}: {}", symbol, target_date, price),
+        Err(e) => tracing::warn!("Failed to store price for {} on {}: {}", symbol, target_date, e),
+    }
 
-    Ok(last_record.map(|r| r.date))
+    Ok(price)
 }
 
 /// Fetch historical prices from CoinGecko and store in database
@@ -149,38 +114,7 @@ async fn fetch_and_store_prices_for_coin(
     symbol: &str,
     days: &str,
 ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-    use serde::Deserialize;
-
-    #[derive(Debug, Deserialize)]
-    struct MarketChartResponse {
-        prices: Vec<[f64; 2]>,
-        #[serde(default)]
-        market_caps: Vec<[f64; 2]>,
-        #[serde(default)]
-        total_volumes: Vec<[f64; 2]>,
-    }
-
-    let url = format!("{}/coins/{}/market_chart", coingecko.base_url(), coin_id);
-
-    let response = coingecko
-        .client()
-        .get(&url)
-        .header("x-cg-pro-api-key", coingecko.api_key())
-        .query(&[
-            ("vs_currency", "usd"),
-            ("days", days),
-            ("interval", "daily"),
-        ])
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("CoinGecko API error {}: {}", status, error_text).into());
-    }
-
-    let data: MarketChartResponse = response.json().await?;
+    let data = coingecko.fetch_daily_market_chart(coin_id, days).await?;
 
     if data.prices.is_empty() {
         return Ok(0);
@@ -194,9 +128,7 @@ async fn fetch_and_store_prices_for_coin(
         let market_cap = data.market_caps.get(i).map(|m| m[1]);
         let volume = data.total_volumes.get(i).map(|v| v[1]);
 
-        let date = chrono::DateTime::from_timestamp_millis(timestamp_ms)
-            .ok_or("Invalid timestamp")?
-            .date_naive();
+        let date = pricing_time::pricing_date_from_millis(timestamp_ms).ok_or("Invalid timestamp")?;
 
         // Check if already exists
         let exists = CoinsHistoricalPrices::find()
@@ -225,4 +157,224 @@ async fn fetch_and_store_prices_for_coin(
     }
 
     Ok(stored_count)
-}
\ No newline at end of file
+}
+
+/// Convert an f64 price (e.g. from CoinGecko or a stored rebalance) to Decimal
+///
+/// Goes through the shortest round-trip string so 0.1 becomes exactly 0.1,
+/// unlike `Decimal::from_f64_retain` which keeps the binary expansion.
+pub fn decimal_from_f64(value: f64) -> Option<Decimal> {
+    if !value.is_finite() {
+        return None;
+    }
+    value
+        .to_string()
+        .parse::<Decimal>()
+        .ok()
+        .or_else(|| Decimal::from_f64_retain(value))
+}
+
+/// Prices keyed by (coin_id, date), as stored
+pub type PriceMap = HashMap<(String, NaiveDate), Decimal>;
+
+/// Max coins fetched from CoinGecko concurrently during a prefetch
+const PREFETCH_CONCURRENCY: usize = 4;
+
+/// Coins are loaded in chunks to keep the IN list bounded
+const PREFETCH_CHUNK_SIZE: usize = 500;
+
+/// Load all stored prices for `coin_ids` between `start` and `end` (inclusive)
+///
+/// One query per chunk of coins instead of one per (coin, date).
+pub async fn load_coins_historical_prices(
+    db: &DatabaseConnection,
+    coin_ids: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<PriceMap, Box<dyn std::error::Error + Send + Sync>> {
+    let mut prices = PriceMap::new();
+
+    for chunk in coin_ids.chunks(PREFETCH_CHUNK_SIZE) {
+        let rows = CoinsHistoricalPrices::find()
+            .filter(coins_historical_prices::Column::CoinId.is_in(chunk.to_vec()))
+            .filter(coins_historical_prices::Column::Date.gte(start))
+            .filter(coins_historical_prices::Column::Date.lte(end))
+            .all(db)
+            .await?;
+
+        for row in rows {
+            prices.insert((row.coin_id, row.date), row.price);
+        }
+    }
+
+    Ok(prices)
+}
+
+/// For each coin with at least one needed date missing from `prices`,
+/// the earliest missing date (sorted by coin_id)
+///
+/// `needed` holds (coin_id, symbol, date) triples.
+pub fn missing_price_ranges(
+    prices: &PriceMap,
+    needed: &[(String, String, NaiveDate)],
+) -> Vec<(String, String, NaiveDate)> {
+    let mut earliest: HashMap<&str, (&str, NaiveDate)> = HashMap::new();
+
+    for (coin_id, symbol, date) in needed {
+        if prices.contains_key(&(coin_id.clone(), *date)) {
+            continue;
+        }
+        earliest
+            .entry(coin_id.as_str())
+            .and_modify(|(_, d)| *d = (*d).min(*date))
+            .or_insert((symbol.as_str(), *date));
+    }
+
+    let mut missing: Vec<(String, String, NaiveDate)> = earliest
+        .into_iter()
+        .map(|(coin_id, (symbol, date))| (coin_id.to_string(), symbol.to_string(), date))
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// Make sure every needed (coin_id, symbol, date) price is available, in bulk
+///
+/// Loads what is already stored with one query per chunk, then fetches each
+/// coin with gaps from CoinGecko at most once (covering its earliest missing
+/// date to today) with bounded concurrency. Coins whose fetch fails are left
+/// out; callers fall back to `get_or_fetch_coins_historical_price`.
+pub async fn prefetch_coins_historical_prices(
+    db: &DatabaseConnection,
+    coingecko: &CoinGeckoService,
+    needed: &[(String, String, NaiveDate)],
+) -> Result<PriceMap, Box<dyn std::error::Error + Send + Sync>> {
+    let (Some(start), Some(end)) = (
+        needed.iter().map(|(_, _, d)| *d).min(),
+        needed.iter().map(|(_, _, d)| *d).max(),
+    ) else {
+        return Ok(PriceMap::new());
+    };
+
+    let mut coin_ids: Vec<String> = needed.iter().map(|(c, _, _)| c.clone()).collect();
+    coin_ids.sort();
+    coin_ids.dedup();
+
+    let mut prices = load_coins_historical_prices(db, &coin_ids, start, end).await?;
+    let missing = missing_price_ranges(&prices, needed);
+
+    if missing.is_empty() {
+        return Ok(prices);
+    }
+
+    tracing::info!(
+        "Prefetching prices for {} of {} coins from CoinGecko",
+        missing.len(),
+        coin_ids.len()
+    );
+
+    let today = Utc::now().date_naive();
+    let fetched: Vec<String> = stream::iter(missing)
+        .map(|(coin_id, symbol, from)| async move {
+            let days = ((today - from).num_days() + 1).max(1).to_string();
+            match fetch_and_store_prices_for_coin(db, coingecko, &coin_id, &symbol, &days).await {
+                Ok(_) => Some(coin_id),
+                Err(e) => {
+                    tracing::warn!("Failed to prefetch prices for {} ({}): {}", symbol, coin_id, e);
+                    None
+                }
+            }
+        })
+        .buffer_unordered(PREFETCH_CONCURRENCY)
+        .filter_map(|coin_id| async move { coin_id })
+        .collect()
+        .await;
+
+    prices.extend(load_coins_historical_prices(db, &fetched, start, end).await?);
+
+    Ok(prices)
+}
+
+/// Latest stored price of a coin found by ticker symbol
+#[derive(Debug, Clone)]
+pub struct SymbolPrice {
+    pub coin_id: String,
+    pub date: NaiveDate,
+    pub price: Decimal,
+    pub market_cap: Option<Decimal>,
+}
+
+/// Latest price per (uppercase) symbol on or before `on`, within `lookback_days`
+///
+/// A symbol shared by several coins resolves to the one with the largest
+/// market cap on its latest date. Symbols without a row are left out.
+pub async fn latest_symbol_prices(
+    db: &DatabaseConnection,
+    symbols: &[String],
+    on: NaiveDate,
+    lookback_days: i64,
+) -> Result<HashMap<String, SymbolPrice>, sea_orm::DbErr> {
+    let rows = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::Symbol.is_in(symbols.iter().map(|s| s.to_lowercase())))
+        .filter(coins_historical_prices::Column::Date.gte(on - chrono::Duration::days(lookback_days)))
+        .filter(coins_historical_prices::Column::Date.lte(on))
+        .all(db)
+        .await?;
+
+    let mut latest: HashMap<String, SymbolPrice> = HashMap::new();
+    for row in rows {
+        let candidate = SymbolPrice { coin_id: row.coin_id, date: row.date, price: row.price, market_cap: row.market_cap };
+        let current = latest.entry(row.symbol.to_uppercase()).or_insert_with(|| candidate.clone());
+        let rank = |p: &SymbolPrice| (p.date, p.market_cap.unwrap_or_default());
+        if rank(&candidate) > rank(current) {
+            *current = candidate;
+        }
+    }
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+    }
+
+    fn need(coin_id: &str, day: u32) -> (String, String, NaiveDate) {
+        (coin_id.to_string(), coin_id.to_uppercase(), d(day))
+    }
+
+    #[test]
+    fn test_decimal_from_f64_is_exact_for_short_values() {
+        assert_eq!(decimal_from_f64(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(decimal_from_f64(50000.25).unwrap().to_string(), "50000.25");
+        assert_eq!(decimal_from_f64(f64::NAN), None);
+        assert_eq!(decimal_from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn test_missing_price_ranges_none_missing() {
+        let mut prices = PriceMap::new();
+        prices.insert(("btc".to_string(), d(1)), dec!(100));
+        assert!(missing_price_ranges(&prices, &[need("btc", 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_missing_price_ranges_uses_earliest_gap_per_coin() {
+        let mut prices = PriceMap::new();
+        prices.insert(("btc".to_string(), d(1)), dec!(100));
+
+        let needed = vec![need("btc", 1), need("btc", 9), need("btc", 5), need("eth", 3)];
+        let missing = missing_price_ranges(&prices, &needed);
+
+        assert_eq!(
+            missing,
+            vec![
+                ("btc".to_string(), "BTC".to_string(), d(5)),
+                ("eth".to_string(), "ETH".to_string(), d(3)),
+            ]
+        );
+    }
+}
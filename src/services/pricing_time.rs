@@ -0,0 +1,96 @@
+//! Pricing-time policy: which instant a priced day refers to
+//!
+//! Days are UTC days, and the official daily cutoff is 00:00 UTC: the price
+//! of `date` (an index's NAV, a coin's historical price) is the close struck
+//! at `date` 00:00 UTC, the timestamp of CoinGecko's daily data points.
+//! Rebalances are stamped (Unix seconds) at the cutoff of the day they take
+//! effect, and a day is priced with the latest rebalance stamped no later
+//! than the end of that day, so older rows stamped later in the day still
+//! count for it.
+//!
+//! Date and timestamp conversions of prices, rebalances and returns go
+//! through here rather than through ad-hoc `and_hms_opt` calls.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// Time of day, UTC, at which a day's official price is struck
+pub const CUTOFF: NaiveTime = NaiveTime::MIN;
+
+/// The cutoff as shown in API responses, e.g. "00:00 UTC"
+pub fn cutoff_label() -> String {
+    format!("{} UTC", CUTOFF.format("%H:%M"))
+}
+
+/// Instant `date`'s price is struck at
+pub fn cutoff(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(CUTOFF)
+}
+
+pub fn cutoff_utc(date: NaiveDate) -> DateTime<Utc> {
+    cutoff(date).and_utc()
+}
+
+pub fn cutoff_timestamp(date: NaiveDate) -> i64 {
+    cutoff_utc(date).timestamp()
+}
+
+pub fn cutoff_millis(date: NaiveDate) -> i64 {
+    cutoff_utc(date).timestamp_millis()
+}
+
+/// Timestamp of a rebalance taking effect on `date`
+pub fn rebalance_timestamp(date: NaiveDate) -> i64 {
+    cutoff_timestamp(date)
+}
+
+/// Last second belonging to `date`
+pub fn day_end_timestamp(date: NaiveDate) -> i64 {
+    cutoff_timestamp(date + Duration::days(1)) - 1
+}
+
+/// Last millisecond belonging to `date`
+pub fn day_end_millis(date: NaiveDate) -> i64 {
+    cutoff_millis(date + Duration::days(1)) - 1
+}
+
+/// Day an instant belongs to
+pub fn pricing_date(instant: DateTime<Utc>) -> NaiveDate {
+    (instant.naive_utc() - (CUTOFF - NaiveTime::MIN)).date()
+}
+
+/// Day of a rebalance timestamp; None when out of range
+pub fn rebalance_date(timestamp: i64) -> Option<NaiveDate> {
+    DateTime::from_timestamp(timestamp, 0).map(pricing_date)
+}
+
+/// Day of a market data point in Unix milliseconds; None when out of range
+pub fn pricing_date_from_millis(timestamp_ms: i64) -> Option<NaiveDate> {
+    DateTime::from_timestamp_millis(timestamp_ms).map(pricing_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, d).unwrap()
+    }
+
+    #[test]
+    fn test_cutoff() {
+        assert_eq!(cutoff_label(), "00:00 UTC");
+        assert_eq!(cutoff_timestamp(day(1)), 1_748_736_000);
+        assert_eq!(cutoff_millis(day(1)), 1_748_736_000_000);
+        assert_eq!(rebalance_timestamp(day(1)), cutoff_timestamp(day(1)));
+        assert_eq!(day_end_timestamp(day(1)), cutoff_timestamp(day(2)) - 1);
+        assert_eq!(day_end_millis(day(1)), cutoff_millis(day(2)) - 1);
+    }
+
+    #[test]
+    fn test_dates_of_timestamps() {
+        assert_eq!(rebalance_date(cutoff_timestamp(day(1))), Some(day(1)));
+        assert_eq!(rebalance_date(day_end_timestamp(day(1))), Some(day(1)));
+        assert_eq!(pricing_date_from_millis(day_end_millis(day(1)) + 1), Some(day(2)));
+        assert_eq!(pricing_date_from_millis(i64::MAX), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Protocol-wide statistics for the public stats banner
+//!
+//! Aggregates TVL (from the leaderboard's per-index figures), ITP and coin
+//! counts, index token holders and the last 24 hours of mint and burn volume.
+//! Holders are derived from mint and burn events only: token transfers aren't
+//! ingested, so an address counts while it has redeemed less than it minted.
+//! The result is cached for a minute.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use chrono::Utc;
+use moka::future::Cache;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+
+use crate::entities::{blockchain_events, coins, itps, prelude::*};
+use crate::models::stats::ProtocolStatsResponse;
+use crate::services::event_amounts;
+use crate::services::leaderboard::{self, LeaderboardMetric, LeaderboardWindow};
+
+/// Event types that create index tokens
+const MINT_EVENTS: &[&str] = &["mint"];
+
+/// Event types that destroy index tokens
+const BURN_EVENTS: &[&str] = &["burn", "withdraw"];
+
+/// itps.state of deprecated ITPs
+const ITP_STATE_DEPRECATED: i16 = 3;
+
+static CACHE: LazyLock<Cache<(), ProtocolStatsResponse>> = LazyLock::new(|| {
+    Cache::builder()
+        .max_capacity(1)
+        .time_to_live(Duration::from_secs(60))
+        .build()
+});
+
+/// Current statistics, from the cache when fresh
+pub async fn stats(db: &DatabaseConnection) -> Result<ProtocolStatsResponse, sea_orm::DbErr> {
+    if let Some(cached) = CACHE.get(&()).await {
+        return Ok(cached);
+    }
+
+    let response = compute(db).await?;
+    CACHE.insert((), response.clone()).await;
+    Ok(response)
+}
+
+/// Statistics without the cache
+pub async fn compute(db: &DatabaseConnection) -> Result<ProtocolStatsResponse, sea_orm::DbErr> {
+    let now = Utc::now();
+
+    let board = leaderboard::leaderboard(db, LeaderboardWindow::Days7, LeaderboardMetric::Tvl, now.date_naive()).await?;
+    let total_tvl_usd = board.entries.iter().map(|entry| entry.tvl_usd).sum();
+
+    let itps_deployed = Itps::find()
+        .filter(itps::Column::State.ne(ITP_STATE_DEPRECATED))
+        .count(db)
+        .await?;
+    let tracked_coins = Coins::find()
+        .filter(coins::Column::Active.eq(true))
+        .filter(coins::Column::Deactivated.eq(false))
+        .count(db)
+        .await?;
+
+    let event_types: Vec<&str> = MINT_EVENTS.iter().chain(BURN_EVENTS).copied().collect();
+    let events = BlockchainEvents::find()
+        .filter(blockchain_events::Column::EventType.is_in(event_types))
+        .all(db)
+        .await?;
+
+    let since = now - chrono::Duration::hours(24);
+    let mut balances: HashMap<(String, String, String), Decimal> = HashMap::new();
+    let mut mint_volume = Decimal::ZERO;
+    let mut burn_volume = Decimal::ZERO;
+    for event in &events {
+        let mint = MINT_EVENTS.contains(&event.event_type.as_str());
+        if let Some(user) = &event.user_address {
+            let key = (event.network.clone(), event.contract_address.to_lowercase(), user.to_lowercase());
+            let quantity = event_amounts::quantity(event).unwrap_or_default();
+            *balances.entry(key).or_default() += if mint { quantity } else { -quantity };
+        }
+        if event.timestamp.is_some_and(|t| t >= since) {
+            let amount = event_amounts::amount(event).unwrap_or_default();
+            if mint {
+                mint_volume += amount;
+            } else {
+                burn_volume += amount;
+            }
+        }
+    }
+    let mut holders: Vec<&str> = balances
+        .iter()
+        .filter(|(_, balance)| balance.is_sign_positive() && !balance.is_zero())
+        .map(|((_, _, user), _)| user.as_str())
+        .collect();
+    holders.sort_unstable();
+    holders.dedup();
+
+    Ok(ProtocolStatsResponse {
+        total_tvl_usd,
+        indexes: board.entries.len(),
+        itps_deployed,
+        unique_holders: holders.len(),
+        mint_volume_24h_usd: mint_volume.to_f64().unwrap_or_default(),
+        burn_volume_24h_usd: burn_volume.to_f64().unwrap_or_default(),
+        tracked_coins,
+        as_of: now,
+    })
+}
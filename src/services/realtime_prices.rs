@@ -16,6 +16,8 @@ use tracing::{debug, error, info, warn};
 pub struct PriceData {
     pub price: f64,
     pub exchange: String,
+    /// Unix timestamp the price was polled at
+    pub fetched_at: i64,
 }
 
 /// Real-time price service that polls exchanges continuously
@@ -93,6 +95,7 @@ impl RealTimePriceService {
         );
 
         let mut new_prices: HashMap<String, PriceData> = HashMap::new();
+        let fetched_at = chrono::Utc::now().timestamp();
 
         // Process Bitget prices FIRST (primary exchange)
         match bitget_result {
@@ -102,6 +105,7 @@ impl RealTimePriceService {
                     new_prices.insert(symbol, PriceData {
                         price,
                         exchange: "bitget".to_string(),
+                        fetched_at,
                     });
                 }
                 debug!("Fetched {} prices from Bitget", bitget_count);
@@ -120,6 +124,7 @@ impl RealTimePriceService {
                         new_prices.insert(symbol, PriceData {
                             price,
                             exchange: "binance".to_string(),
+                            fetched_at,
                         });
                         binance_added += 1;
                     }
@@ -222,6 +227,12 @@ impl RealTimePriceService {
         cache.get(&symbol.to_uppercase()).map(|p| p.price)
     }
 
+    /// Get the current price for a symbol, with where and when it was polled
+    pub async fn get_price_data(&self, symbol: &str) -> Option<PriceData> {
+        let cache = self.prices.read().await;
+        cache.get(&symbol.to_uppercase()).cloned()
+    }
+
     /// Get all current prices
     pub async fn get_all_prices(&self) -> HashMap<String, f64> {
         let cache = self.prices.read().await;
@@ -0,0 +1,199 @@
+//! Two-step approval of on-chain rebalance deployments
+//!
+//! The rebalance deployer only submits a rebalance whose latest approval
+//! record is approved and unexpired, so a single leaked admin credential
+//! can't push weights on-chain. One admin credential asks for approval
+//! (POST /admin/rebalances/{id}/request-approval), a different one approves
+//! or rejects it. Credentials are the shared ADMIN_API_KEY ("admin") or an
+//! admin-tier API key ("api_key:<id>").
+//!
+//! A request must be decided within the approval TTL, and an approved
+//! rebalance deployed within the TTL of its approval; after that a new
+//! request is needed.
+
+use chrono::{Duration, NaiveDateTime};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::entities::{prelude::*, rebalance_approvals};
+
+/// Values of rebalance_approvals.status
+pub mod statuses {
+    pub const PENDING: &str = "pending";
+    pub const APPROVED: &str = "approved";
+    pub const REJECTED: &str = "rejected";
+    /// Reported for pending or approved records past expires_at; never stored
+    pub const EXPIRED: &str = "expired";
+}
+
+/// Hours a request or an approval stays valid by default
+const DEFAULT_TTL_HOURS: i64 = 24;
+
+#[derive(Debug)]
+pub enum ApprovalError {
+    RebalanceNotFound(i32),
+    AlreadyDeployed(i32),
+    /// The rebalance already has a live pending or approved record
+    AlreadyRequested(i32),
+    /// There is no live pending record to decide
+    NoPendingRequest(i32),
+    /// The requester tried to decide their own request
+    SameCredential,
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for ApprovalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalError::RebalanceNotFound(id) => write!(f, "Rebalance {} not found", id),
+            ApprovalError::AlreadyDeployed(id) => write!(f, "Rebalance {} is already deployed", id),
+            ApprovalError::AlreadyRequested(id) => write!(f, "Rebalance {} already has an open approval", id),
+            ApprovalError::NoPendingRequest(id) => write!(f, "Rebalance {} has no pending approval request", id),
+            ApprovalError::SameCredential => write!(f, "A request must be decided with a different API key"),
+            ApprovalError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApprovalError {}
+
+impl From<sea_orm::DbErr> for ApprovalError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        ApprovalError::Database(e)
+    }
+}
+
+/// How long a request or an approval stays valid (REBALANCE_APPROVAL_TTL_HOURS)
+pub fn ttl() -> Duration {
+    let hours = std::env::var("REBALANCE_APPROVAL_TTL_HOURS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|hours: &i64| *hours > 0)
+        .unwrap_or(DEFAULT_TTL_HOURS);
+    Duration::hours(hours)
+}
+
+/// `approval`'s status as of `now`, reporting lapsed records as expired
+pub fn effective_status(approval: &rebalance_approvals::Model, now: NaiveDateTime) -> &str {
+    let open = approval.status == statuses::PENDING || approval.status == statuses::APPROVED;
+    if open && approval.expires_at <= now {
+        statuses::EXPIRED
+    } else {
+        &approval.status
+    }
+}
+
+/// The most recent approval record of a rebalance
+pub async fn latest(
+    db: &DatabaseConnection,
+    rebalance_id: i32,
+) -> Result<Option<rebalance_approvals::Model>, sea_orm::DbErr> {
+    RebalanceApprovals::find()
+        .filter(rebalance_approvals::Column::RebalanceId.eq(rebalance_id))
+        .order_by_desc(rebalance_approvals::Column::Id)
+        .one(db)
+        .await
+}
+
+/// Whether the rebalance may be deployed at `now`
+pub async fn is_approved(db: &DatabaseConnection, rebalance_id: i32, now: NaiveDateTime) -> Result<bool, sea_orm::DbErr> {
+    Ok(latest(db, rebalance_id)
+        .await?
+        .is_some_and(|approval| effective_status(&approval, now) == statuses::APPROVED))
+}
+
+/// Open a pending approval request for a rebalance on behalf of `requested_by`
+pub async fn request(
+    db: &DatabaseConnection,
+    rebalance_id: i32,
+    requested_by: &str,
+    now: NaiveDateTime,
+) -> Result<rebalance_approvals::Model, ApprovalError> {
+    let rebalance = Rebalances::find_by_id(rebalance_id)
+        .one(db)
+        .await?
+        .ok_or(ApprovalError::RebalanceNotFound(rebalance_id))?;
+    if rebalance.deployed == Some(true) {
+        return Err(ApprovalError::AlreadyDeployed(rebalance_id));
+    }
+    if let Some(approval) = latest(db, rebalance_id).await?
+        && matches!(effective_status(&approval, now), statuses::PENDING | statuses::APPROVED)
+    {
+        return Err(ApprovalError::AlreadyRequested(rebalance_id));
+    }
+
+    Ok(rebalance_approvals::ActiveModel {
+        rebalance_id: Set(rebalance_id),
+        status: Set(statuses::PENDING.to_string()),
+        requested_by: Set(requested_by.to_string()),
+        expires_at: Set(now + ttl()),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Approve or reject the pending request of a rebalance on behalf of `decided_by`
+///
+/// An approval is valid for the TTL from now.
+pub async fn decide(
+    db: &DatabaseConnection,
+    rebalance_id: i32,
+    decided_by: &str,
+    approve: bool,
+    now: NaiveDateTime,
+) -> Result<rebalance_approvals::Model, ApprovalError> {
+    let rebalance = Rebalances::find_by_id(rebalance_id)
+        .one(db)
+        .await?
+        .ok_or(ApprovalError::RebalanceNotFound(rebalance_id))?;
+    if rebalance.deployed == Some(true) {
+        return Err(ApprovalError::AlreadyDeployed(rebalance_id));
+    }
+    let approval = latest(db, rebalance_id)
+        .await?
+        .filter(|approval| effective_status(approval, now) == statuses::PENDING)
+        .ok_or(ApprovalError::NoPendingRequest(rebalance_id))?;
+    if approval.requested_by == decided_by {
+        return Err(ApprovalError::SameCredential);
+    }
+
+    let mut active: rebalance_approvals::ActiveModel = approval.into();
+    if approve {
+        active.status = Set(statuses::APPROVED.to_string());
+        active.expires_at = Set(now + ttl());
+    } else {
+        active.status = Set(statuses::REJECTED.to_string());
+    }
+    active.decided_by = Set(Some(decided_by.to_string()));
+    active.decided_at = Set(Some(now));
+    Ok(active.update(db).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_status() {
+        let now = chrono::Utc::now().naive_utc();
+        let approval = |status: &str, expires_at: NaiveDateTime| rebalance_approvals::Model {
+            id: 1,
+            rebalance_id: 1,
+            status: status.to_string(),
+            requested_by: "admin".to_string(),
+            decided_by: None,
+            expires_at,
+            created_at: now,
+            decided_at: None,
+        };
+        let later = now + Duration::hours(1);
+        let earlier = now - Duration::hours(1);
+
+        assert_eq!(effective_status(&approval(statuses::PENDING, later), now), statuses::PENDING);
+        assert_eq!(effective_status(&approval(statuses::APPROVED, later), now), statuses::APPROVED);
+        assert_eq!(effective_status(&approval(statuses::APPROVED, earlier), now), statuses::EXPIRED);
+        assert_eq!(effective_status(&approval(statuses::PENDING, earlier), now), statuses::EXPIRED);
+        assert_eq!(effective_status(&approval(statuses::REJECTED, earlier), now), statuses::REJECTED);
+    }
+}
@@ -0,0 +1,246 @@
+//! Deployment of rebalances as on-chain weight updates
+//!
+//! Rebalances are computed off-chain and stored with `deployed = false`. For
+//! indexes that back an active ITP, the rebalance deployer job takes the
+//! index's latest rebalance, converts its weights to registry asset ids and
+//! basis points, submits them through ItpCreationService::submit_rebalance
+//! and records the transaction here. Older undeployed rebalances of the same
+//! index are superseded and never submitted.
+//!
+//! A rebalance is submitted once its tx hash is stored while `deployed` is
+//! still false; the hash is stored before waiting for the receipt, so a
+//! failed wait or crash leaves it to be reconciled by receipt on the next
+//! run instead of being sent twice. Only a reverted or dropped transaction
+//! clears the hash and lets the rebalance be sent again.
+
+use asset_registry::AssetRegistry;
+use chrono::Utc;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::entities::{itps, prelude::*, rebalances};
+use crate::services::itp_validation::registry_symbol;
+use crate::services::rebalancing::CoinRebalanceInfo;
+
+/// itps.state of active ITPs
+const ITP_STATE_ACTIVE: i16 = 1;
+
+/// Sum of on-chain weights (100%)
+pub const TOTAL_BPS: u128 = 10_000;
+
+#[derive(Debug)]
+pub enum DeploymentError {
+    /// A constituent has no asset in the registry
+    UnknownAsset(String),
+    InvalidWeights(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for DeploymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeploymentError::UnknownAsset(symbol) => write!(f, "No registry asset for '{}'", symbol),
+            DeploymentError::InvalidWeights(msg) => write!(f, "Invalid weights: {}", msg),
+            DeploymentError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeploymentError {}
+
+impl From<sea_orm::DbErr> for DeploymentError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        DeploymentError::Database(e)
+    }
+}
+
+/// A rebalance waiting to be deployed to its index's ITP
+#[derive(Debug, Clone)]
+pub struct PendingDeployment {
+    pub rebalance: rebalances::Model,
+    pub itp: itps::Model,
+}
+
+/// Weights in the form the contracts take
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnchainWeights {
+    pub asset_ids: Vec<u128>,
+    /// Basis points, summing to TOTAL_BPS
+    pub weights_bps: Vec<u128>,
+}
+
+/// Latest rebalance of every index backing an active ITP, if not yet deployed
+pub async fn pending(db: &DatabaseConnection) -> Result<Vec<PendingDeployment>, sea_orm::DbErr> {
+    let itps = Itps::find()
+        .filter(itps::Column::State.eq(ITP_STATE_ACTIVE))
+        .filter(itps::Column::IndexId.is_not_null())
+        .order_by_asc(itps::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut pending = Vec::new();
+    for itp in itps {
+        let Some(index_id) = itp.index_id.and_then(|id| i32::try_from(id).ok()) else {
+            continue;
+        };
+        let latest = Rebalances::find()
+            .filter(rebalances::Column::IndexId.eq(index_id))
+            .order_by_desc(rebalances::Column::Timestamp)
+            .order_by_desc(rebalances::Column::Id)
+            .one(db)
+            .await?;
+        if let Some(rebalance) = latest
+            && rebalance.deployed != Some(true)
+        {
+            pending.push(PendingDeployment { rebalance, itp });
+        }
+    }
+    Ok(pending)
+}
+
+/// Registry asset ids and basis-point weights of a rebalance's constituents
+///
+/// Weights are shares of their sum; rounding leftovers go to the largest
+/// remainders so the basis points add up to exactly TOTAL_BPS.
+pub fn onchain_weights(coins: &[CoinRebalanceInfo], registry: &AssetRegistry) -> Result<OnchainWeights, DeploymentError> {
+    if coins.is_empty() {
+        return Err(DeploymentError::InvalidWeights("rebalance has no constituents".to_string()));
+    }
+
+    let mut asset_ids = Vec::with_capacity(coins.len());
+    let mut weights = Vec::with_capacity(coins.len());
+    for coin in coins {
+        let symbol = coin.symbol.to_uppercase();
+        let asset = registry
+            .all()
+            .iter()
+            .find(|asset| registry_symbol(asset) == symbol)
+            .ok_or_else(|| DeploymentError::UnknownAsset(coin.symbol.clone()))?;
+        let weight: Decimal = coin
+            .weight
+            .parse()
+            .map_err(|_| DeploymentError::InvalidWeights(format!("'{}' for {}", coin.weight, coin.symbol)))?;
+        if weight.is_sign_negative() {
+            return Err(DeploymentError::InvalidWeights(format!("negative weight for {}", coin.symbol)));
+        }
+        asset_ids.push(asset.id);
+        weights.push(weight);
+    }
+
+    let total: Decimal = weights.iter().sum();
+    if total.is_zero() {
+        return Err(DeploymentError::InvalidWeights("weights sum to zero".to_string()));
+    }
+
+    let exact: Vec<Decimal> = weights.iter().map(|w| w * Decimal::from(TOTAL_BPS) / total).collect();
+    let mut weights_bps: Vec<u128> = exact.iter().map(|bps| bps.floor().to_u128().unwrap_or(0)).collect();
+    let mut by_remainder: Vec<usize> = (0..exact.len()).collect();
+    by_remainder.sort_by(|&a, &b| exact[b].fract().cmp(&exact[a].fract()).then(a.cmp(&b)));
+    let missing = TOTAL_BPS - weights_bps.iter().sum::<u128>();
+    for &i in by_remainder.iter().cycle().take(missing as usize) {
+        weights_bps[i] += 1;
+    }
+
+    Ok(OnchainWeights { asset_ids, weights_bps })
+}
+
+/// Record that `rebalance_id` was sent in `tx_hash`, not mined yet
+pub async fn mark_submitted(db: &DatabaseConnection, rebalance_id: i32, tx_hash: &str) -> Result<(), sea_orm::DbErr> {
+    rebalances::ActiveModel {
+        id: Set(rebalance_id),
+        deployed: Set(Some(false)),
+        tx_hash: Set(Some(tx_hash.to_string())),
+        ..Default::default()
+    }
+    .update(db)
+    .await?;
+    Ok(())
+}
+
+/// Forget the transaction of a submitted rebalance that reverted or was
+/// dropped, so the next run sends it again
+pub async fn clear_submission(db: &DatabaseConnection, rebalance_id: i32) -> Result<(), sea_orm::DbErr> {
+    rebalances::ActiveModel {
+        id: Set(rebalance_id),
+        tx_hash: Set(None),
+        ..Default::default()
+    }
+    .update(db)
+    .await?;
+    Ok(())
+}
+
+/// Record that `rebalance_id` was deployed in `tx_hash`, mined in `block_number`
+pub async fn mark_deployed(
+    db: &DatabaseConnection,
+    rebalance_id: i32,
+    tx_hash: &str,
+    block_number: u64,
+) -> Result<(), sea_orm::DbErr> {
+    rebalances::ActiveModel {
+        id: Set(rebalance_id),
+        deployed: Set(Some(true)),
+        deployed_at: Set(Some(Utc::now().naive_utc())),
+        tx_hash: Set(Some(tx_hash.to_string())),
+        deployed_block: Set(i64::try_from(block_number).ok()),
+        ..Default::default()
+    }
+    .update(db)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coin(symbol: &str, weight: &str) -> CoinRebalanceInfo {
+        CoinRebalanceInfo {
+            coin_id: symbol.to_lowercase(),
+            symbol: symbol.to_string(),
+            quantity: "1".to_string(),
+            weight: weight.to_string(),
+            price: Decimal::ONE,
+            exchange: "binance".to_string(),
+            trading_pair: "usdt".to_string(),
+        }
+    }
+
+    fn registry() -> AssetRegistry {
+        let path = std::env::temp_dir().join(format!("registry-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"[{"id": 101, "bitget": "BTCUSDT"}, {"id": 102, "bitget": "ETHUSDC"}, {"id": 103, "bitget": "SOLUSDT"}]"#,
+        )
+        .unwrap();
+        let registry = AssetRegistry::load(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_equal_weights_sum_to_total() {
+        let weights = onchain_weights(&[coin("BTC", "1"), coin("eth", "1"), coin("SOL", "1")], &registry()).unwrap();
+        assert_eq!(weights.asset_ids, [101, 102, 103]);
+        assert_eq!(weights.weights_bps, [3334, 3333, 3333]);
+    }
+
+    #[test]
+    fn test_relative_weights() {
+        let weights = onchain_weights(&[coin("BTC", "0.5"), coin("ETH", "0.3"), coin("SOL", "0.2")], &registry()).unwrap();
+        assert_eq!(weights.weights_bps, [5000, 3000, 2000]);
+    }
+
+    #[test]
+    fn test_invalid_constituents() {
+        assert!(matches!(
+            onchain_weights(&[coin("DOGE", "1")], &registry()),
+            Err(DeploymentError::UnknownAsset(_))
+        ));
+        assert!(matches!(
+            onchain_weights(&[coin("BTC", "0")], &registry()),
+            Err(DeploymentError::InvalidWeights(_))
+        ));
+        assert!(matches!(onchain_weights(&[], &registry()), Err(DeploymentError::InvalidWeights(_))));
+    }
+}
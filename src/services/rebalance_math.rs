@@ -0,0 +1,434 @@
+//! Pure rebalance math
+//!
+//! Quantity, portfolio value and fee computation for rebalances, on plain
+//! structs with no database or API access. `RebalancingService` gathers the
+//! inputs (constituents, weights, prices, previous positions, fee config) and
+//! persists the resulting composition.
+//!
+//! Conventions (unchanged from the original service code):
+//! - A position's value is `weight × quantity × price`
+//! - Each constituent gets an equal share of the portfolio value, so
+//!   `quantity = (portfolio_value / n) / (weight × price)`
+//! - Fees use a symmetric rate `trading_fee + spread / 2` on the traded value
+//! - A cash buffer takes its share of the value first at a price of 1; its
+//!   weight is the same share of the total weight (see `CashAllocation`)
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Errors from rebalance math
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebalanceMathError {
+    NoConstituents,
+    /// Price is zero or negative, so no quantity can be derived
+    InvalidPrice { coin_id: String },
+    InvalidWeight { coin_id: String },
+    /// Cash share outside [0, 1)
+    InvalidCashShare,
+}
+
+impl std::fmt::Display for RebalanceMathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RebalanceMathError::NoConstituents => write!(f, "No constituents to rebalance"),
+            RebalanceMathError::InvalidPrice { coin_id } => write!(f, "Invalid price for {}", coin_id),
+            RebalanceMathError::InvalidWeight { coin_id } => write!(f, "Invalid weight for {}", coin_id),
+            RebalanceMathError::InvalidCashShare => write!(f, "Cash share must be at least 0 and below 1"),
+        }
+    }
+}
+
+impl std::error::Error for RebalanceMathError {}
+
+/// Trading cost configuration of an index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeConfig {
+    pub trading_fee: Decimal,
+    pub spread: Decimal,
+}
+
+impl FeeConfig {
+    /// Symmetric fee rate applied to traded value
+    // TODO: Investigate asymmetric fees (different rates for buy vs sell)
+    pub fn rate(&self) -> Decimal {
+        self.trading_fee + (self.spread / Decimal::from(2))
+    }
+}
+
+/// A selected constituent with its weight and price on the rebalance date
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricedConstituent {
+    pub coin_id: String,
+    pub weight: Decimal,
+    pub price: Decimal,
+}
+
+/// Part of the portfolio held as a unit-price cash position
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashAllocation {
+    pub coin_id: String,
+    /// Fraction of the portfolio value, in [0, 1)
+    pub share: Decimal,
+}
+
+/// A held position (new or from a previous rebalance)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub coin_id: String,
+    pub weight: Decimal,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+impl Position {
+    pub fn value(&self) -> Decimal {
+        self.weight * self.quantity * self.price
+    }
+}
+
+/// Fee charged on one position of a rebalance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoinFee {
+    pub coin_id: String,
+    /// Value bought or sold, at the rebalance price
+    pub traded_value: Decimal,
+    pub fee: Decimal,
+}
+
+/// Result of a rebalance computation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    pub positions: Vec<Position>,
+    pub total_weight: Decimal,
+    /// Sum of `fee_breakdown`
+    pub fees: Decimal,
+    /// One entry per position, in the same order
+    pub fee_breakdown: Vec<CoinFee>,
+    pub portfolio_value_before_fees: Decimal,
+    pub portfolio_value_after_fees: Decimal,
+}
+
+/// Value of held positions at new prices
+///
+/// `prices` maps coin_id to the new price; positions missing from it are
+/// valued at their stored price.
+pub fn portfolio_value(positions: &[Position], prices: &HashMap<String, Decimal>) -> Decimal {
+    positions
+        .iter()
+        .map(|p| {
+            let price = prices.get(&p.coin_id).copied().unwrap_or(p.price);
+            p.weight * p.quantity * price
+        })
+        .sum()
+}
+
+/// Split `portfolio_value` equally across constituents and derive quantities
+pub fn compute_positions(
+    portfolio_value: Decimal,
+    constituents: &[PricedConstituent],
+) -> Result<Vec<Position>, RebalanceMathError> {
+    if constituents.is_empty() {
+        return Err(RebalanceMathError::NoConstituents);
+    }
+
+    let target_value_per_token = portfolio_value / Decimal::from(constituents.len());
+
+    constituents
+        .iter()
+        .map(|c| {
+            if c.weight <= Decimal::ZERO {
+                return Err(RebalanceMathError::InvalidWeight { coin_id: c.coin_id.clone() });
+            }
+            if c.price <= Decimal::ZERO {
+                return Err(RebalanceMathError::InvalidPrice { coin_id: c.coin_id.clone() });
+            }
+            Ok(Position {
+                coin_id: c.coin_id.clone(),
+                weight: c.weight,
+                quantity: target_value_per_token / (c.weight * c.price),
+                price: c.price,
+            })
+        })
+        .collect()
+}
+
+/// Positions with `cash.share` of the value held in cash, the rest split
+/// equally across constituents
+///
+/// The cash position comes last. Its weight is `share / (1 - share)` of the
+/// constituents' total weight, so it is `share` of the total weight as well
+/// as of the value.
+pub fn compute_positions_with_cash(
+    portfolio_value: Decimal,
+    constituents: &[PricedConstituent],
+    cash: &CashAllocation,
+) -> Result<Vec<Position>, RebalanceMathError> {
+    if cash.share.is_sign_negative() || cash.share >= Decimal::ONE {
+        return Err(RebalanceMathError::InvalidCashShare);
+    }
+
+    let cash_value = portfolio_value * cash.share;
+    let mut positions = compute_positions(portfolio_value - cash_value, constituents)?;
+    if cash.share.is_zero() {
+        return Ok(positions);
+    }
+
+    let constituents_weight: Decimal = positions.iter().map(|p| p.weight).sum();
+    let weight = constituents_weight * cash.share / (Decimal::ONE - cash.share);
+    positions.push(Position {
+        coin_id: cash.coin_id.clone(),
+        weight,
+        quantity: cash_value / weight,
+        price: Decimal::ONE,
+    });
+    Ok(positions)
+}
+
+/// Fees for an initial rebalance: every position is a BUY
+pub fn initial_fees(positions: &[Position], fees: &FeeConfig) -> Decimal {
+    initial_fee_breakdown(positions, fees).iter().map(|f| f.fee).sum()
+}
+
+/// Per-position fees of an initial rebalance
+pub fn initial_fee_breakdown(positions: &[Position], fees: &FeeConfig) -> Vec<CoinFee> {
+    let rate = fees.rate();
+    positions
+        .iter()
+        .map(|p| CoinFee {
+            coin_id: p.coin_id.clone(),
+            traded_value: p.value(),
+            fee: p.value() * rate,
+        })
+        .collect()
+}
+
+/// Fees for a periodic rebalance: charged on the changed quantity of each
+/// new position versus its previous quantity (0 if newly added)
+pub fn rebalance_fees(
+    previous_quantities: &HashMap<String, Decimal>,
+    positions: &[Position],
+    fees: &FeeConfig,
+) -> Decimal {
+    rebalance_fee_breakdown(previous_quantities, positions, fees).iter().map(|f| f.fee).sum()
+}
+
+/// Per-position fees of a periodic rebalance
+pub fn rebalance_fee_breakdown(
+    previous_quantities: &HashMap<String, Decimal>,
+    positions: &[Position],
+    fees: &FeeConfig,
+) -> Vec<CoinFee> {
+    let rate = fees.rate();
+    positions
+        .iter()
+        .map(|p| {
+            let old_quantity = previous_quantities.get(&p.coin_id).copied().unwrap_or(Decimal::ZERO);
+            let traded_value = p.weight * (p.quantity - old_quantity).abs() * p.price;
+            CoinFee {
+                coin_id: p.coin_id.clone(),
+                traded_value,
+                fee: traded_value * rate,
+            }
+        })
+        .collect()
+}
+
+/// Compute a full rebalance composition
+///
+/// `cash` keeps a share of the value in a cash position (see
+/// `compute_positions_with_cash`). `previous_quantities` is None for the
+/// initial rebalance (all BUYs).
+pub fn compose(
+    portfolio_value_before_fees: Decimal,
+    constituents: &[PricedConstituent],
+    cash: Option<&CashAllocation>,
+    previous_quantities: Option<&HashMap<String, Decimal>>,
+    fees: &FeeConfig,
+) -> Result<Composition, RebalanceMathError> {
+    let positions = match cash {
+        Some(cash) => compute_positions_with_cash(portfolio_value_before_fees, constituents, cash)?,
+        None => compute_positions(portfolio_value_before_fees, constituents)?,
+    };
+    let total_weight = positions.iter().map(|p| p.weight).sum();
+
+    let fee_breakdown = match previous_quantities {
+        None => initial_fee_breakdown(&positions, fees),
+        Some(previous) => rebalance_fee_breakdown(previous, &positions, fees),
+    };
+    let fees = fee_breakdown.iter().map(|f| f.fee).sum();
+
+    Ok(Composition {
+        positions,
+        total_weight,
+        fees,
+        fee_breakdown,
+        portfolio_value_before_fees,
+        portfolio_value_after_fees: portfolio_value_before_fees - fees,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rust_decimal_macros::dec;
+
+    fn constituent(coin_id: &str, weight: Decimal, price: Decimal) -> PricedConstituent {
+        PricedConstituent { coin_id: coin_id.to_string(), weight, price }
+    }
+
+    fn fee_config() -> FeeConfig {
+        FeeConfig { trading_fee: dec!(0.001), spread: dec!(0.002) }
+    }
+
+    #[test]
+    fn test_fee_rate() {
+        assert_eq!(fee_config().rate(), dec!(0.002));
+    }
+
+    #[test]
+    fn test_compute_positions_equal_split() {
+        let positions = compute_positions(
+            dec!(1000),
+            &[constituent("btc", dec!(1), dec!(50000)), constituent("eth", dec!(1), dec!(2500))],
+        )
+        .unwrap();
+
+        assert_eq!(positions[0].quantity, dec!(0.01));
+        assert_eq!(positions[1].quantity, dec!(0.2));
+        assert_eq!(positions[0].value(), dec!(500));
+    }
+
+    #[test]
+    fn test_compute_positions_rejects_bad_inputs() {
+        assert_eq!(compute_positions(dec!(1000), &[]), Err(RebalanceMathError::NoConstituents));
+        assert_eq!(
+            compute_positions(dec!(1000), &[constituent("btc", dec!(1), dec!(0))]),
+            Err(RebalanceMathError::InvalidPrice { coin_id: "btc".to_string() })
+        );
+        assert_eq!(
+            compute_positions(dec!(1000), &[constituent("btc", dec!(0), dec!(1))]),
+            Err(RebalanceMathError::InvalidWeight { coin_id: "btc".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_initial_fees_charge_full_value() {
+        let composition = compose(
+            dec!(1000),
+            &[constituent("btc", dec!(1), dec!(50000)), constituent("eth", dec!(1), dec!(2500))],
+            None,
+            None,
+            &fee_config(),
+        )
+        .unwrap();
+
+        assert_eq!(composition.fees, dec!(2));
+        assert_eq!(composition.portfolio_value_after_fees, dec!(998));
+        assert_eq!(composition.total_weight, dec!(2));
+        let fees: Vec<_> = composition.fee_breakdown.iter().map(|f| (f.coin_id.as_str(), f.fee)).collect();
+        assert_eq!(fees, vec![("btc", dec!(1)), ("eth", dec!(1))]);
+    }
+
+    #[test]
+    fn test_rebalance_fees_only_on_changes() {
+        // New quantity 0.02 vs 0.01 held: 0.01 × 50000 traded at 0.2%
+        let positions = compute_positions(dec!(1000), &[constituent("btc", dec!(1), dec!(50000))]).unwrap();
+        let mut previous = HashMap::new();
+        previous.insert("btc".to_string(), dec!(0.01));
+
+        assert_eq!(rebalance_fees(&previous, &positions, &fee_config()), dec!(1));
+        let breakdown = rebalance_fee_breakdown(&previous, &positions, &fee_config());
+        assert_eq!((breakdown[0].traded_value, breakdown[0].fee), (dec!(500), dec!(1)));
+
+        let unchanged = vec![Position { quantity: dec!(0.01), ..positions[0].clone() }];
+        assert_eq!(rebalance_fees(&previous, &unchanged, &fee_config()), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_portfolio_value_uses_new_prices() {
+        let held = vec![Position {
+            coin_id: "btc".to_string(),
+            weight: dec!(1),
+            quantity: dec!(0.01),
+            price: dec!(50000),
+        }];
+        let mut prices = HashMap::new();
+        prices.insert("btc".to_string(), dec!(60000));
+
+        assert_eq!(portfolio_value(&held, &prices), dec!(600));
+        assert_eq!(portfolio_value(&held, &HashMap::new()), dec!(500));
+    }
+
+    #[test]
+    fn test_cash_buffer_positions() {
+        let cash = CashAllocation { coin_id: "cash:USDC".to_string(), share: dec!(0.05) };
+        let constituents = [constituent("btc", dec!(1), dec!(50000)), constituent("eth", dec!(3), dec!(2500))];
+        let positions = compute_positions_with_cash(dec!(1000), &constituents, &cash).unwrap();
+
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0].value().round_dp(9), dec!(475));
+        assert_eq!(positions[1].value().round_dp(9), dec!(475));
+        let cash_position = &positions[2];
+        assert_eq!(cash_position.coin_id, "cash:USDC");
+        assert_eq!(cash_position.price, Decimal::ONE);
+        assert_eq!(cash_position.value().round_dp(9), dec!(50));
+        // 5% of the total weight too
+        let total_weight: Decimal = positions.iter().map(|p| p.weight).sum();
+        assert_eq!((cash_position.weight / total_weight).round_dp(9), dec!(0.05));
+
+        // Cash keeps its value whatever the coins do
+        let prices = HashMap::from([("btc".to_string(), dec!(0)), ("eth".to_string(), dec!(0))]);
+        assert_eq!(portfolio_value(&positions, &prices).round_dp(9), dec!(50));
+
+        let no_cash = CashAllocation { share: Decimal::ZERO, ..cash.clone() };
+        assert_eq!(compute_positions_with_cash(dec!(1000), &constituents, &no_cash).unwrap().len(), 2);
+        let all_cash = CashAllocation { share: Decimal::ONE, ..cash };
+        assert_eq!(
+            compute_positions_with_cash(dec!(1000), &constituents, &all_cash),
+            Err(RebalanceMathError::InvalidCashShare)
+        );
+    }
+
+    fn value_and_constituents() -> impl Strategy<Value = (Decimal, Vec<PricedConstituent>)> {
+        let constituents = prop::collection::vec((1u64..1_000_000, 1u64..1_000_000_000_000), 1..40).prop_map(|raw| {
+            raw.into_iter()
+                .enumerate()
+                .map(|(i, (weight, price))| {
+                    // Weights to 3 decimals, prices to 8
+                    constituent(&format!("c{}", i), Decimal::new(weight as i64, 3), Decimal::new(price as i64, 8))
+                })
+                .collect::<Vec<_>>()
+        });
+        (1u64..1_000_000_000_000, constituents).prop_map(|(value, constituents)| (Decimal::from(value), constituents))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_positions_revalue_to_portfolio_value((value, constituents) in value_and_constituents()) {
+            let composition = compose(value, &constituents, None, None, &fee_config()).unwrap();
+            let revalued: Decimal = composition.positions.iter().map(Position::value).sum();
+            prop_assert_eq!(composition.positions.len(), constituents.len());
+            prop_assert!((revalued - value).abs() <= value * dec!(0.000000001), "{} revalued to {}", value, revalued);
+        }
+
+        #[test]
+        fn prop_initial_fees_bounded_by_rate((value, constituents) in value_and_constituents()) {
+            let fees = fee_config();
+            let composition = compose(value, &constituents, None, None, &fees).unwrap();
+            prop_assert!(composition.fees <= value * fees.rate() * dec!(1.000000001), "fees {} on {}", composition.fees, value);
+            prop_assert_eq!(composition.portfolio_value_after_fees, value - composition.fees);
+        }
+
+        #[test]
+        fn prop_unchanged_quantities_cost_nothing((value, constituents) in value_and_constituents()) {
+            let initial = compose(value, &constituents, None, None, &fee_config()).unwrap();
+            let previous: HashMap<String, Decimal> =
+                initial.positions.iter().map(|p| (p.coin_id.clone(), p.quantity)).collect();
+            let again = compose(value, &constituents, None, Some(&previous), &fee_config()).unwrap();
+            prop_assert_eq!(again.fees, Decimal::ZERO);
+        }
+    }
+}
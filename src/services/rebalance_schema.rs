@@ -0,0 +1,190 @@
+//! Versioned schema of rebalances.coins
+//!
+//! rebalances.coins is an array with one entry per constituent, read back
+//! as `CoinRebalanceInfo`. Each entry carries the `schema_version` it was
+//! written with, so a change to `CoinRebalanceInfo` comes with an upgrade
+//! function instead of breaking the parsing of older rebalances:
+//! - 1: entries without `schema_version`. Computed rebalances wrote them
+//!   snake_case, manual rebalances camelCase (`coinId`, `tradingPair`).
+//! - 2: `schema_version` on every entry, keys as in `CoinRebalanceInfo`.
+//!
+//! Always read the column with `decode`, which brings entries up to
+//! `CURRENT_VERSION` first, and write it with `encode`. `upgrade_stored`
+//! (run by the migrate_rebalance_schema bin) rewrites stored rows in the
+//! current version; snapshot_log compares logged rebalances after upgrading
+//! them too, so the rewrite doesn't read as a changed record.
+//!
+//! To change the schema: bump `CURRENT_VERSION`, append the upgrade from
+//! the previous version to `UPGRADES` and document the version above.
+
+use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, EntityTrait, QueryOrder, Set};
+use serde::de::Error as _;
+use serde_json::{Map, Value};
+
+use crate::entities::{prelude::*, rebalances};
+use crate::services::rebalancing::CoinRebalanceInfo;
+
+pub const CURRENT_VERSION: u64 = 2;
+
+const VERSION_KEY: &str = "schema_version";
+
+/// `UPGRADES[n - 1]` brings an entry from version n to n + 1
+const UPGRADES: [fn(&mut Map<String, Value>); (CURRENT_VERSION - 1) as usize] = [upgrade_v1];
+
+/// Version 1 → 2: snake_case keys
+fn upgrade_v1(entry: &mut Map<String, Value>) {
+    for (from, to) in [("coinId", "coin_id"), ("tradingPair", "trading_pair")] {
+        if let Some(value) = entry.remove(from) {
+            entry.entry(to).or_insert(value);
+        }
+    }
+}
+
+/// Constituents of a rebalance, whatever version they were stored in
+pub fn decode(coins: Value) -> serde_json::Result<Vec<CoinRebalanceInfo>> {
+    serde_json::from_value(upgrade(coins)?)
+}
+
+/// rebalances.coins for `coins`, in the current version
+pub fn encode(coins: &[CoinRebalanceInfo]) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(coins)?;
+    if let Some(entries) = value.as_array_mut() {
+        for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+            entry.insert(VERSION_KEY.to_string(), CURRENT_VERSION.into());
+        }
+    }
+    Ok(value)
+}
+
+/// Bring every entry of a stored coins array to the current version,
+/// keeping any fields the upgrades don't touch
+pub fn upgrade(mut coins: Value) -> serde_json::Result<Value> {
+    let entries = coins
+        .as_array_mut()
+        .ok_or_else(|| serde_json::Error::custom("rebalance coins is not an array"))?;
+    for entry in entries {
+        let entry = entry
+            .as_object_mut()
+            .ok_or_else(|| serde_json::Error::custom("rebalance coin is not an object"))?;
+        let version = entry_version(entry)?;
+        for upgrade in &UPGRADES[(version - 1) as usize..] {
+            upgrade(entry);
+        }
+        entry.insert(VERSION_KEY.to_string(), CURRENT_VERSION.into());
+    }
+    Ok(coins)
+}
+
+/// Whether any entry is stored in an older version than the current one
+pub fn needs_upgrade(coins: &Value) -> bool {
+    coins.as_array().is_some_and(|entries| {
+        entries
+            .iter()
+            .any(|entry| entry.as_object().is_some_and(|e| matches!(entry_version(e), Ok(v) if v < CURRENT_VERSION)))
+    })
+}
+
+fn entry_version(entry: &Map<String, Value>) -> serde_json::Result<u64> {
+    match entry.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(value) => match value.as_u64() {
+            Some(version @ 1..=CURRENT_VERSION) => Ok(version),
+            _ => Err(serde_json::Error::custom(format!(
+                "unsupported rebalance schema_version {} (current: {})",
+                value, CURRENT_VERSION
+            ))),
+        },
+    }
+}
+
+/// Outcome of `upgrade_stored`
+#[derive(Debug, Default)]
+pub struct UpgradeSummary {
+    pub scanned: usize,
+    pub upgraded: usize,
+    /// Rebalances that can't be upgraded, with the reason; left as they are
+    pub failed: Vec<(i32, String)>,
+}
+
+/// Rewrite every rebalance stored in an older version in the current one
+/// (or only count them when `dry_run`)
+pub async fn upgrade_stored(db: &DatabaseConnection, dry_run: bool) -> Result<UpgradeSummary, DbErr> {
+    let mut summary = UpgradeSummary::default();
+    let all = Rebalances::find().order_by_asc(rebalances::Column::Id).all(db).await?;
+
+    for rebalance in all {
+        summary.scanned += 1;
+        if !needs_upgrade(&rebalance.coins) {
+            continue;
+        }
+
+        // Check the upgraded entries parse before storing them
+        let upgraded = upgrade(rebalance.coins.clone())
+            .and_then(|coins| serde_json::from_value::<Vec<CoinRebalanceInfo>>(coins.clone()).map(|_| coins));
+        let coins = match upgraded {
+            Ok(coins) => coins,
+            Err(e) => {
+                summary.failed.push((rebalance.id, e.to_string()));
+                continue;
+            }
+        };
+
+        if !dry_run {
+            let mut active: rebalances::ActiveModel = rebalance.into();
+            active.coins = Set(coins);
+            active.update(db).await?;
+        }
+        summary.upgraded += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_upgrades_old_entries() {
+        let stored = json!([
+            { "coin_id": "bitcoin", "symbol": "BTC", "quantity": "1", "weight": "1", "price": 60000.0,
+              "exchange": "binance", "trading_pair": "usdc" },
+            { "coinId": "ethereum", "symbol": "ETH", "quantity": "2", "weight": "1", "price": 3000.0,
+              "exchange": "bitget", "tradingPair": "usdt" },
+        ]);
+        assert!(needs_upgrade(&stored));
+
+        let coins = decode(stored).unwrap();
+        assert_eq!(coins[0].coin_id, "bitcoin");
+        assert_eq!((coins[1].coin_id.as_str(), coins[1].trading_pair.as_str()), ("ethereum", "usdt"));
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        let coins = vec![CoinRebalanceInfo {
+            coin_id: "solana".to_string(),
+            symbol: "SOL".to_string(),
+            quantity: "10".to_string(),
+            weight: "1".to_string(),
+            price: dec!(150),
+            exchange: "binance".to_string(),
+            trading_pair: "usdc".to_string(),
+        }];
+        let stored = encode(&coins).unwrap();
+        assert_eq!(stored[0]["schema_version"], json!(CURRENT_VERSION));
+        assert!(!needs_upgrade(&stored));
+        assert_eq!(upgrade(stored.clone()).unwrap(), stored);
+        assert_eq!(decode(stored).unwrap()[0].coin_id, "solana");
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let future = json!([{ "coin_id": "bitcoin", "schema_version": CURRENT_VERSION + 1 }]);
+        assert!(decode(future.clone()).unwrap_err().to_string().contains("unsupported"));
+        assert!(!needs_upgrade(&future));
+        assert!(decode(json!([{ "coin_id": "bitcoin", "schema_version": 0 }])).is_err());
+        assert!(decode(json!({ "coin_id": "bitcoin" })).is_err());
+        assert!(decode(json!([])).unwrap().is_empty());
+    }
+}
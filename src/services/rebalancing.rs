@@ -1,19 +1,31 @@
 use std::collections::HashMap;
 
 use chrono::{Duration, NaiveDate, Utc};
-use rust_decimal::Decimal;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect, Set};
 use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::Instrument;
 
 use crate::entities::{
     rebalances,
     prelude::*,
 };
+use crate::services::cash_buffer;
 use crate::services::coingecko::CoinGeckoService;
 
-use crate::services::constituent_selector::ConstituentSelectorFactory;
+use crate::services::constituent_selector::{ConstituentSelectorFactory, ConstituentToken};
+use crate::services::daily_prices;
 use crate::services::exchange_api::ExchangeApiService;
+use crate::services::index_constraints::{self, ConstraintViolation};
+use crate::services::portfolio_continuity;
+use crate::services::price_utils::{self, PriceMap};
+use crate::services::pricing_time;
+use crate::services::rebalance_math::{self, FeeConfig, Position, PricedConstituent};
+use crate::services::rebalance_schema;
+use crate::services::spread_calibration;
 use crate::services::weight_calculator::{WeightCalculator, WeightStrategy};
+use crate::services::yield_accrual::YieldAccrual;
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,11 +34,21 @@ pub struct CoinRebalanceInfo {
     pub symbol: String,
     pub quantity: String,
     pub weight: String,
-    pub price: f64,
+    /// Stored as a JSON number; read back exactly as written
+    #[serde(serialize_with = "serialize_as_number")]
+    pub price: Decimal,
     pub exchange: String,
     pub trading_pair: String,
 }
 
+/// Rebalances have always stored prices as JSON numbers
+fn serialize_as_number<S: serde::Serializer>(price: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    let number = price
+        .to_f64()
+        .ok_or_else(|| serde::ser::Error::custom(format!("Price {} out of range", price)))?;
+    serializer.serialize_f64(number)
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum RebalanceReason {
@@ -45,6 +67,25 @@ impl RebalanceReason {
     }
 }
 
+/// Snapshot of a running historical backfill
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackfillProgress {
+    /// Planned rebalance dates already processed
+    pub done: usize,
+    /// Planned rebalance dates in this run
+    pub total: usize,
+    /// Date being processed (None once finished)
+    pub current_date: Option<NaiveDate>,
+}
+
+/// Constituents and prices prepared up front for a backfill run, so the
+/// per-date loop doesn't hit CoinGecko/DB once per coin per date
+#[derive(Debug, Default)]
+struct BackfillPrefetch {
+    constituents: HashMap<NaiveDate, Vec<ConstituentToken>>,
+    prices: PriceMap,
+}
+
 pub struct RebalancingService {
     db: DatabaseConnection,
     coingecko: CoinGeckoService,
@@ -71,10 +112,29 @@ impl RebalancingService {
         &self.exchange_api
     }
 
-    /// Backfill all historical rebalances for an index from initial_date to current_date
+    /// Backfill all historical rebalances for an index from initial_date to
+    /// current_date, then its daily prices since inception, so performance
+    /// figures are available without waiting for the daily prices sync
     pub async fn backfill_historical_rebalances(
         &self,
         index_id: i32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.backfill_historical_rebalances_with_progress(index_id, None).await?;
+        daily_prices::backfill_daily_prices(&self.db, &self.coingecko, index_id)
+            .await
+            .map_err(|e| format!("Daily prices backfill failed: {}", e))?;
+
+        // Priced from the stored prices, so any value jump is a pricing bug
+        portfolio_continuity::validate_index(&self.db, index_id).await;
+        Ok(())
+    }
+
+    /// Same as `backfill_historical_rebalances`, publishing progress after
+    /// each planned rebalance date to `progress` (if given)
+    pub async fn backfill_historical_rebalances_with_progress(
+        &self,
+        index_id: i32,
+        progress: Option<&watch::Sender<BackfillProgress>>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Get index metadata
         let index = IndexMetadata::find_by_id(index_id)
@@ -102,9 +162,7 @@ impl RebalancingService {
         let start_date = match last_rebalance {
             Some(rb) => {
                 // Convert timestamp to date
-                let last_date = chrono::DateTime::from_timestamp(rb.timestamp, 0)
-                    .unwrap()
-                    .date_naive();
+                let last_date = pricing_time::rebalance_date(rb.timestamp).unwrap_or_default();
 
                 // Start from the NEXT period after last rebalance
                 let next_date = last_date + Duration::days(rebalance_period as i64);
@@ -138,6 +196,14 @@ impl RebalancingService {
             today,
         );
 
+        let total = rebalance_dates.len();
+        let publish = |done: usize, current_date: Option<NaiveDate>| {
+            if let Some(tx) = progress {
+                tx.send_replace(BackfillProgress { done, total, current_date });
+            }
+        };
+        publish(0, rebalance_dates.first().copied());
+
         if rebalance_dates.is_empty() {
             tracing::info!("No rebalances needed for index {} (already up to date)", index_id);
             return Ok(());
@@ -151,11 +217,35 @@ impl RebalancingService {
             today
         );
 
+        let prefetch = match self.prefetch_for_backfill(&index, &rebalance_dates).await {
+            Ok(prefetch) => Some(prefetch),
+            Err(e) => {
+                tracing::warn!(
+                    "Price prefetch failed for index {}, falling back to per-date fetching: {}",
+                    index_id,
+                    e
+                );
+                None
+            }
+        };
+
         for (i, date) in rebalance_dates.iter().enumerate() {
+            publish(i, Some(*date));
+
+            let span = tracing::info_span!(
+                "backfill_rebalance",
+                index_id = index_id,
+                date = %date,
+                step = i + 1,
+                total = total,
+                percent = format_args!("{:.1}", (i as f64 / total as f64) * 100.0),
+            );
+
             tracing::info!(
+                parent: &span,
                 "Backfilling rebalance {}/{} for index {} on {}",
                 i + 1,
-                rebalance_dates.len(),
+                total,
                 index_id,
                 date
             );
@@ -166,36 +256,102 @@ impl RebalancingService {
                 RebalanceReason::Periodic
             };
 
-            // Add delay to avoid rate limiting (500ms)
-            if i > 0 {
+            // Add delay to avoid rate limiting (500ms) when prices weren't prefetched
+            if i > 0 && prefetch.is_none() {
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
             }
 
             // Perform rebalance with retry
-            match self.perform_rebalance_with_retry(index_id, *date, reason).await {
-                Ok(_) => tracing::info!("Successfully created rebalance for {}", date),
+            match self
+                .perform_rebalance_with_retry(index_id, *date, reason, prefetch.as_ref())
+                .instrument(span.clone())
+                .await
+            {
+                Ok(_) => tracing::info!(parent: &span, "Successfully created rebalance for {}", date),
                 Err(e) => {
-                    tracing::error!("Failed to create rebalance for {}: {}", date, e);
+                    tracing::error!(parent: &span, "Failed to create rebalance for {}: {}", date, e);
                     // Continue with next date instead of failing entire backfill
                 }
             }
         }
 
+        publish(total, None);
+
         Ok(())
     }
 
+    /// Select constituents for every planned date and load all their prices in bulk
+    ///
+    /// Selection here runs in backfill mode (crypto_listings); dates computed
+    /// in live mode re-select and only reuse the prices.
+    async fn prefetch_for_backfill(
+        &self,
+        index: &crate::entities::index_metadata::Model,
+        dates: &[NaiveDate],
+    ) -> Result<BackfillPrefetch, Box<dyn std::error::Error + Send + Sync>> {
+        let selector = self.selector_factory.create_selector(&self.db, index).await?;
+
+        let mut constituents = HashMap::new();
+        for date in dates {
+            match selector.select_constituents(&self.db, None, *date).await {
+                Ok(tokens) => {
+                    constituents.insert(*date, tokens);
+                }
+                Err(e) => tracing::debug!("Prefetch: no constituents for {} on {}: {}", index.index_id, date, e),
+            }
+        }
+
+        // Each date needs its own constituents' prices, plus the previous
+        // date's constituents (to value the portfolio before rebalancing)
+        let mut needed = Vec::new();
+        for (i, date) in dates.iter().enumerate() {
+            let previous = i.checked_sub(1).and_then(|p| constituents.get(&dates[p]));
+            for token in constituents.get(date).into_iter().chain(previous).flatten() {
+                needed.push((token.coin_id.clone(), token.symbol.clone(), *date));
+            }
+        }
+
+        tracing::info!(
+            "Prefetching {} prices for {} rebalance dates of index {}",
+            needed.len(),
+            dates.len(),
+            index.index_id
+        );
+
+        let prices = price_utils::prefetch_coins_historical_prices(&self.db, &self.coingecko, &needed).await?;
+
+        Ok(BackfillPrefetch { constituents, prices })
+    }
+
+    /// Price of a coin on a date, from the prefetch if available
+    async fn price_for(
+        &self,
+        prefetch: Option<&BackfillPrefetch>,
+        coin_id: &str,
+        symbol: &str,
+        date: NaiveDate,
+    ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let prefetched = prefetch.and_then(|p| p.prices.get(&(coin_id.to_string(), date)));
+        if let Some(price) = prefetched.and_then(|price| price.to_f64()) {
+            return Ok(price);
+        }
+        // Use SELF-HEALING function that auto-fetches missing prices
+        price_utils::get_or_fetch_coins_historical_price(&self.db, &self.coingecko, coin_id, symbol, date).await
+    }
+
     /// Perform rebalance with exponential backoff retry
     async fn perform_rebalance_with_retry(
         &self,
         index_id: i32,
         date: NaiveDate,
         reason: RebalanceReason,
+        prefetch: Option<&BackfillPrefetch>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let max_retries = 5;
         let mut delay = tokio::time::Duration::from_secs(1);
 
         for attempt in 0..max_retries {
-            match self.perform_rebalance_for_date(index_id, date, reason.clone()).await {
+            match self.perform_rebalance(index_id, date, reason.clone(), prefetch).await {
                 Ok(_) => return Ok(()),
                 Err(e) => {
                     if attempt == max_retries - 1 {
@@ -224,9 +380,19 @@ impl RebalancingService {
         index_id: i32,
         date: NaiveDate,
         reason: RebalanceReason,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.perform_rebalance(index_id, date, reason, None).await
+    }
+
+    async fn perform_rebalance(
+        &self,
+        index_id: i32,
+        date: NaiveDate,
+        reason: RebalanceReason,
+        prefetch: Option<&BackfillPrefetch>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Check if rebalance already exists
-        let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+        let timestamp = pricing_time::rebalance_timestamp(date);
         let existing = Rebalances::find()
             .filter(rebalances::Column::IndexId.eq(index_id))
             .filter(rebalances::Column::Timestamp.eq(timestamp))
@@ -271,10 +437,18 @@ impl RebalancingService {
             None
         };
 
-        // Get constituents using the strategy
-        let constituents = selector
-            .select_constituents(&self.db, exchange_api_ref, date)
-            .await?;
+        // Get constituents using the strategy (reusing the backfill prefetch when possible)
+        let prefetched = prefetch
+            .filter(|_| !use_live_apis)
+            .and_then(|p| p.constituents.get(&date));
+        let mut constituents = match prefetched {
+            Some(tokens) => tokens.clone(),
+            None => {
+                selector
+                    .select_constituents(&self.db, exchange_api_ref, date)
+                    .await?
+            }
+        };
 
         if constituents.is_empty() {
             return Err(format!("No constituents found for index {}", index_id).into());
@@ -287,23 +461,52 @@ impl RebalancingService {
             date
         );
 
-        // Calculate total number for weight calculation
-        let total_category_tokens = constituents.len();
-
         // Get weight configuration from index metadata
         let weight_strategy_str = index.weight_strategy.as_str();
         let weight_strategy = WeightStrategy::from_str(weight_strategy_str)
             .ok_or(format!("Invalid weight strategy: {}", weight_strategy_str))?;
             
         let weight_threshold = index.weight_threshold;
-            
-        // Query market caps if using MarketCap strategy
-        let market_caps = if weight_strategy == WeightStrategy::MarketCap {
+
+        let constraints = index_constraints::for_index(&index);
+        let mut violations = Vec::new();
+        let over_max = constraints.max_constituents.filter(|max| constituents.len() > *max);
+
+        // Query market caps if using MarketCap strategy or trimming to maxConstituents
+        let market_caps = if weight_strategy == WeightStrategy::MarketCap || over_max.is_some() {
             tracing::debug!("Querying market caps for {} tokens on {}", constituents.len(), date);
             self.query_market_caps_for_date(&constituents, date).await?
         } else {
             HashMap::new()
         };
+
+        if let Some(max) = over_max {
+            let selected = constituents.len();
+            let (kept, removed) = index_constraints::trim_constituents(constituents, &market_caps, max);
+            tracing::info!(
+                "Trimmed index {} from {} to {} constituents on {}: {:?}",
+                index_id,
+                selected,
+                max,
+                date,
+                removed
+            );
+            constituents = kept;
+            violations.push(ConstraintViolation::MaxConstituents { limit: max, selected, removed });
+        }
+        if let Some(min) = constraints.min_constituents.filter(|min| constituents.len() < *min) {
+            tracing::warn!(
+                "Index {} has {} constituents on {}, below its minimum of {}",
+                index_id,
+                constituents.len(),
+                date,
+                min
+            );
+            violations.push(ConstraintViolation::MinConstituents { limit: min, selected: constituents.len() });
+        }
+
+        // Calculate total number for weight calculation
+        let total_category_tokens = constituents.len();
         
         // Calculate weights using WeightCalculator
         let calculator = WeightCalculator::new(weight_strategy.clone(), weight_threshold);
@@ -313,7 +516,16 @@ impl RebalancingService {
             .map(|t| t.coin_id.clone())
             .collect();
         
-        let weights = calculator.calculate_weights(&coin_ids, &market_caps, total_category_tokens)?;
+        let mut weights = calculator.calculate_weights(&coin_ids, &market_caps, total_category_tokens)?;
+
+        if !constraints.max_category_weight.is_empty() {
+            let members = index_constraints::category_members(&self.db, &coin_ids, &constraints.max_category_weight).await?;
+            violations.extend(index_constraints::cap_categories(
+                &mut weights,
+                &members,
+                &constraints.max_category_weight,
+            ));
+        }
         
         tracing::info!(
             "Using {:?} weight strategy for index {} (threshold: {:?})",
@@ -322,63 +534,86 @@ impl RebalancingService {
             weight_threshold
         );
 
-        // Get portfolio value BEFORE fees
-        let portfolio_value_before_fees = if matches!(reason, RebalanceReason::Initial) {
-            index.initial_price.ok_or("Index has no initial_price")?
-        } else {
-            self.calculate_current_portfolio_value(index_id, date).await?
+        let fee_config = FeeConfig {
+            trading_fee: index.exchange_trading_fees.ok_or("No trading fees configured")?,
+            spread: spread_calibration::spread_for(&self.db, &index, date).await?.ok_or("No spread configured")?,
         };
 
-        // Calculate quantities
-        let target_value_per_token = portfolio_value_before_fees / Decimal::from(constituents.len());
+        // Get portfolio value BEFORE fees; periodic rebalances also need the
+        // previous positions to detect BUY/SELL for fees
+        let (portfolio_value_before_fees, previous_quantities) = if matches!(reason, RebalanceReason::Initial) {
+            (index.initial_price.ok_or("Index has no initial_price")?, None)
+        } else {
+            let previous = self.previous_rebalance_positions(index_id, date).await?;
+            let value = self.calculate_current_portfolio_value(&previous, date, prefetch).await?;
+            let quantities: HashMap<String, Decimal> = previous
+                .into_iter()
+                .map(|(position, _)| (position.coin_id, position.quantity))
+                .collect();
+            (value, Some(quantities))
+        };
 
-        let mut coins_info = Vec::new();
-        let mut total_weight = Decimal::ZERO;
+        // Gather weights and prices for the pure math
+        let mut priced = Vec::with_capacity(constituents.len());
+        let mut raw_prices = HashMap::new();
 
-        for token_info in constituents {
+        for token_info in &constituents {
             // Get weight for this specific token
             let weight = weights
                 .get(&token_info.coin_id)
                 .ok_or(format!("No weight calculated for {}", token_info.coin_id))?;
 
-            total_weight += weight;
-            // Use SELF-HEALING function that auto-fetches missing prices
-            let price = crate::services::price_utils::get_or_fetch_coins_historical_price(
-                &self.db,
-                &self.coingecko,
-                &token_info.coin_id,
-                &token_info.symbol,
-                date
-            )
-            .await?;
+            let price = self
+                .price_for(prefetch, &token_info.coin_id, &token_info.symbol, date)
+                .await?;
+            let price = price_utils::decimal_from_f64(price).ok_or("Invalid price")?;
 
-            let price_decimal = Decimal::from_f64_retain(price)
-                .ok_or("Invalid price")?;
-
-            let quantity = target_value_per_token / (weight * price_decimal);
+            priced.push(PricedConstituent {
+                coin_id: token_info.coin_id.clone(),
+                weight: *weight,
+                price,
+            });
+            raw_prices.insert(token_info.coin_id.clone(), price);
+        }
 
-            coins_info.push(CoinRebalanceInfo {
+        let cash = cash_buffer::for_index(&index);
+        let composition = rebalance_math::compose(
+            portfolio_value_before_fees,
+            &priced,
+            cash.as_ref().map(|(_, allocation)| allocation),
+            previous_quantities.as_ref(),
+            &fee_config,
+        )?;
+
+        let mut coins_info: Vec<CoinRebalanceInfo> = constituents
+            .into_iter()
+            .zip(&composition.positions)
+            .map(|(token_info, position)| CoinRebalanceInfo {
+                price: raw_prices[&token_info.coin_id],
                 coin_id: token_info.coin_id,
                 symbol: token_info.symbol,
-                quantity: quantity.to_string(),
-                weight: weight.to_string(),
-                price,
+                quantity: position.quantity.to_string(),
+                weight: position.weight.to_string(),
                 exchange: token_info.exchange,
                 trading_pair: token_info.trading_pair,
+            })
+            .collect();
+
+        // The cash buffer position comes after the constituents
+        if let (Some((symbol, _)), Some(position)) = (cash, composition.positions.get(coins_info.len())) {
+            coins_info.push(CoinRebalanceInfo {
+                coin_id: position.coin_id.clone(),
+                symbol,
+                quantity: position.quantity.to_string(),
+                weight: position.weight.to_string(),
+                price: price_utils::decimal_from_f64(cash_buffer::PRICE).unwrap_or(Decimal::ONE),
+                exchange: String::new(),
+                trading_pair: String::new(),
             });
         }
 
-        // Calculate fees
-        let total_fees = if matches!(reason, RebalanceReason::Initial) {
-            // Initial: all positions are BUYs
-            self.calculate_initial_fees(&coins_info, &index).await?
-        } else {
-            // Periodic: compare with previous rebalance to detect BUY/SELL
-            self.calculate_rebalance_fees(index_id, date, &coins_info, &index).await?
-        };
-
-        // Apply fees to portfolio value
-        let portfolio_value_after_fees = portfolio_value_before_fees - total_fees;
+        let total_weight = composition.total_weight;
+        let portfolio_value_after_fees = composition.portfolio_value_after_fees;
 
         tracing::info!(
             "💰 Fees for index {} on {}: Portfolio ${} → ${} (fees: ${})",
@@ -386,11 +621,11 @@ impl RebalancingService {
             date,
             portfolio_value_before_fees,
             portfolio_value_after_fees,
-            total_fees
+            composition.fees
         );
 
         // Save to database with AFTER-FEES value
-        let coins_json = serde_json::to_value(&coins_info)?;
+        let coins_json = rebalance_schema::encode(&coins_info)?;
 
         let new_rebalance = rebalances::ActiveModel {
             index_id: Set(index_id),
@@ -400,6 +635,13 @@ impl RebalancingService {
             timestamp: Set(timestamp),
             rebalance_type: Set(reason.as_str().to_string()),
             deployed: Set(Some(false)),
+            constraint_violations: Set(if violations.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_value(&violations)?)
+            }),
+            total_fees: Set(Some(composition.fees)),
+            fee_breakdown: Set(Some(serde_json::to_value(&composition.fee_breakdown)?)),
             ..Default::default()
         };
 
@@ -417,129 +659,6 @@ impl RebalancingService {
         Ok(())
     }
 
-    /// Calculate fees for initial rebalance (all positions are BUYs)
-    async fn calculate_initial_fees(
-        &self,
-        coins_info: &[CoinRebalanceInfo],
-        index: &crate::entities::index_metadata::Model,
-    ) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
-        let trading_fee = index.exchange_trading_fees.ok_or("No trading fees configured")?;
-        let spread = index.exchange_avg_spread.ok_or("No spread configured")?;
-
-        // TODO: Investigate asymmetric fees (different rates for buy vs sell)
-        // Currently using symmetric formula: fee = quantity × price × (trading_fee + spread/2)
-        let fee_rate = trading_fee + (spread / Decimal::from(2));
-
-        let mut total_fees = Decimal::ZERO;
-
-        for coin in coins_info {
-            let quantity = coin.quantity.parse::<Decimal>()?;
-            let price = Decimal::from_f64_retain(coin.price).ok_or("Invalid price")?;
-            let weight = coin.weight.parse::<Decimal>()?;
-
-            // All positions are BUYs on initial rebalance
-            let position_value = weight * quantity * price;
-            let fee = position_value * fee_rate;
-
-            total_fees += fee;
-
-            tracing::debug!(
-                "  BUY {} qty={} price={} value={} fee={}",
-                coin.symbol,
-                quantity,
-                price,
-                position_value,
-                fee
-            );
-        }
-
-        Ok(total_fees)
-    }
-
-    /// Calculate fees for periodic rebalance (compare with previous to detect BUY/SELL/HOLD)
-    async fn calculate_rebalance_fees(
-        &self,
-        index_id: i32,
-        date: NaiveDate,
-        new_coins: &[CoinRebalanceInfo],
-        index: &crate::entities::index_metadata::Model,
-    ) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
-        // Get previous rebalance
-        let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
-        
-        let previous_rebalance = Rebalances::find()
-            .filter(rebalances::Column::IndexId.eq(index_id))
-            .filter(rebalances::Column::Timestamp.lt(timestamp))
-            .order_by(rebalances::Column::Timestamp, Order::Desc)
-            .limit(1)
-            .one(&self.db)
-            .await?
-            .ok_or("No previous rebalance found")?;
-
-        let old_coins: Vec<CoinRebalanceInfo> = serde_json::from_value(previous_rebalance.coins)?;
-
-        let trading_fee = index.exchange_trading_fees.ok_or("No trading fees configured")?;
-        let spread = index.exchange_avg_spread.ok_or("No spread configured")?;
-
-        // TODO: Investigate asymmetric fees (different rates for buy vs sell)
-        // Currently using symmetric formula: fee = |quantity_changed| × price × (trading_fee + spread/2)
-        let fee_rate = trading_fee + (spread / Decimal::from(2));
-
-        let mut total_fees = Decimal::ZERO;
-
-        // Build map of old positions
-        let mut old_positions: std::collections::HashMap<String, Decimal> = std::collections::HashMap::new();
-        for coin in old_coins {
-            let quantity = coin.quantity.parse::<Decimal>().unwrap_or(Decimal::ZERO);
-            old_positions.insert(coin.coin_id.clone(), quantity);
-        }
-
-        // Compare new vs old positions
-        for new_coin in new_coins {
-            let new_quantity = new_coin.quantity.parse::<Decimal>()?;
-            let old_quantity = old_positions.get(&new_coin.coin_id).copied().unwrap_or(Decimal::ZERO);
-            
-            let quantity_change = new_quantity - old_quantity;
-
-            if quantity_change == Decimal::ZERO {
-                // HOLD: no change, no fees
-                tracing::debug!("  HOLD {} (no change)", new_coin.symbol);
-                continue;
-            }
-
-            let price = Decimal::from_f64_retain(new_coin.price).ok_or("Invalid price")?;
-            let weight = new_coin.weight.parse::<Decimal>()?;
-
-            // Calculate fee on the changed amount (absolute value)
-            let change_value = weight * quantity_change.abs() * price;
-            let fee = change_value * fee_rate;
-
-            total_fees += fee;
-
-            if quantity_change > Decimal::ZERO {
-                tracing::debug!(
-                    "  BUY {} qty_change={} price={} value={} fee={}",
-                    new_coin.symbol,
-                    quantity_change,
-                    price,
-                    change_value,
-                    fee
-                );
-            } else {
-                tracing::debug!(
-                    "  SELL {} qty_change={} price={} value={} fee={}",
-                    new_coin.symbol,
-                    quantity_change.abs(),
-                    price,
-                    change_value,
-                    fee
-                );
-            }
-        }
-
-        Ok(total_fees)
-    }
-
     /// Calculate rebalance dates from initial_date to current_date
     fn calculate_rebalance_dates(
         &self,
@@ -558,14 +677,16 @@ impl RebalancingService {
         dates
     }
 
-    /// Calculate current portfolio value
-    async fn calculate_current_portfolio_value(
+    /// Positions of the last rebalance before `date`, with their symbols
+    ///
+    /// Quantities include the yield accrued until `date`, so it carries over
+    /// into the next rebalance.
+    async fn previous_rebalance_positions(
         &self,
         index_id: i32,
         date: NaiveDate,
-    ) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
-        // Get last rebalance before this date
-        let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+    ) -> Result<Vec<(Position, String)>, Box<dyn std::error::Error + Send + Sync>> {
+        let timestamp = pricing_time::rebalance_timestamp(date);
 
         let last_rebalance = Rebalances::find()
             .filter(rebalances::Column::IndexId.eq(index_id))
@@ -576,30 +697,46 @@ impl RebalancingService {
             .await?
             .ok_or("No previous rebalance found")?;
 
-        let coins: Vec<CoinRebalanceInfo> = serde_json::from_value(last_rebalance.coins)?;
-
-        let mut total_value = Decimal::ZERO;
-
-        for coin in coins {
-            // Use SELF-HEALING function that auto-fetches missing prices
-            let current_price = crate::services::price_utils::get_or_fetch_coins_historical_price(
-                &self.db,
-                &self.coingecko,
-                &coin.coin_id,
-                &coin.symbol,
-                date
-            )
-            .await?;
-
-            let quantity = coin.quantity.parse::<Decimal>()?;
-            let price_decimal = Decimal::from_f64_retain(current_price)
-                .ok_or("Invalid price")?;
-            let weight = coin.weight.parse::<Decimal>()?;
+        let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(last_rebalance.coins)?;
+        let rebalance_date = pricing_time::rebalance_date(last_rebalance.timestamp).ok_or("Invalid rebalance timestamp")?;
+        let coin_ids: Vec<String> = coins.iter().map(|coin| coin.coin_id.clone()).collect();
+        let accrual = YieldAccrual::for_index(&self.db, index_id, &coin_ids, rebalance_date).await?;
+
+        coins
+            .into_iter()
+            .map(|coin| {
+                let position = Position {
+                    quantity: accrual.quantity(&coin.coin_id, coin.quantity.parse::<Decimal>()?, date),
+                    weight: coin.weight.parse::<Decimal>()?,
+                    price: coin.price,
+                    coin_id: coin.coin_id,
+                };
+                Ok((position, coin.symbol))
+            })
+            .collect()
+    }
 
-            total_value += weight * quantity * price_decimal;
+    /// Calculate current portfolio value of previous positions at `date` prices
+    async fn calculate_current_portfolio_value(
+        &self,
+        previous: &[(Position, String)],
+        date: NaiveDate,
+        prefetch: Option<&BackfillPrefetch>,
+    ) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
+        let mut prices = HashMap::new();
+
+        for (position, symbol) in previous {
+            let current_price = self
+                .price_for(prefetch, &position.coin_id, symbol, date)
+                .await?;
+            prices.insert(
+                position.coin_id.clone(),
+                price_utils::decimal_from_f64(current_price).ok_or("Invalid price")?,
+            );
         }
 
-        Ok(total_value)
+        let positions: Vec<Position> = previous.iter().map(|(p, _)| p.clone()).collect();
+        Ok(rebalance_math::portfolio_value(&positions, &prices))
     }
 
     /// Query market caps for tokens on a specific date
@@ -0,0 +1,671 @@
+//! Scheduled reports
+//!
+//! Two templates are rendered once their period is over:
+//! - the weekly performance summary (Monday to Sunday): each index's close
+//!   before and at the end of the week, its return and its TVL;
+//! - the monthly rebalance report (calendar month): every rebalance of the
+//!   month with the coins it added and removed and whether it was deployed.
+//!
+//! A report is a table rendered as HTML, PDF and CSV. Each rendering is
+//! stored in the reports table (listed by GET /admin/reports) and, when
+//! email is configured (see services::email), sent to the newsletter
+//! subscribers: the HTML as the message, the PDF and CSV attached.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, Order,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+
+use crate::entities::{daily_prices, index_metadata, index_tvl, prelude::*, rebalances, reports, subscriptions};
+use crate::services::email::{Attachment, Email, EmailError, Mailer};
+use crate::services::pricing_time;
+
+/// Rows a PDF page holds (A4 landscape, 8pt Courier)
+const PDF_LINES_PER_PAGE: usize = 50;
+
+/// Cells longer than this are cut in the PDF so the columns stay aligned
+const PDF_MAX_CELL: usize = 44;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportTemplate {
+    WeeklyPerformance,
+    MonthlyRebalance,
+}
+
+impl ReportTemplate {
+    pub const ALL: [ReportTemplate; 2] = [ReportTemplate::WeeklyPerformance, ReportTemplate::MonthlyRebalance];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportTemplate::WeeklyPerformance => "weekly_performance",
+            ReportTemplate::MonthlyRebalance => "monthly_rebalance",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+
+    /// First and last day of the latest period that ended before `today`
+    pub fn last_period(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            ReportTemplate::WeeklyPerformance => {
+                let end = today - Duration::days(today.weekday().num_days_from_monday() as i64 + 1);
+                (end - Duration::days(6), end)
+            }
+            ReportTemplate::MonthlyRebalance => {
+                let end = today.with_day(1).unwrap() - Duration::days(1);
+                (end.with_day(1).unwrap(), end)
+            }
+        }
+    }
+
+    fn title(self, start: NaiveDate, end: NaiveDate) -> String {
+        match self {
+            ReportTemplate::WeeklyPerformance => format!("Weekly performance summary, {} to {}", start, end),
+            ReportTemplate::MonthlyRebalance => format!("Monthly rebalance report, {}", start.format("%B %Y")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+    Csv,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 3] = [ReportFormat::Html, ReportFormat::Pdf, ReportFormat::Csv];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Csv => "csv",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == s)
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ReportFormat::Html => "text/html; charset=utf-8",
+            ReportFormat::Pdf => "application/pdf",
+            ReportFormat::Csv => "text/csv",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReportError {
+    Database(DbErr),
+    Email(EmailError),
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::Database(e) => write!(f, "Database error: {}", e),
+            ReportError::Email(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+impl From<DbErr> for ReportError {
+    fn from(e: DbErr) -> Self {
+        ReportError::Database(e)
+    }
+}
+
+impl From<EmailError> for ReportError {
+    fn from(e: EmailError) -> Self {
+        ReportError::Email(e)
+    }
+}
+
+/// A report's content, before rendering
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Build the report of `template` for the days `start..=end`
+pub async fn build(
+    db: &DatabaseConnection,
+    template: ReportTemplate,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<ReportTable, DbErr> {
+    let rows = match template {
+        ReportTemplate::WeeklyPerformance => weekly_performance(db, start, end).await?,
+        ReportTemplate::MonthlyRebalance => monthly_rebalances(db, start, end).await?,
+    };
+    let columns = match template {
+        ReportTemplate::WeeklyPerformance => vec!["Index", "Symbol", "Open", "Close", "Return %", "TVL (USD)"],
+        ReportTemplate::MonthlyRebalance => vec![
+            "Date",
+            "Symbol",
+            "Type",
+            "Constituents",
+            "Added",
+            "Removed",
+            "Portfolio value",
+            "Deployment",
+        ],
+    };
+    Ok(ReportTable { title: template.title(start, end), columns, rows })
+}
+
+async fn weekly_performance(db: &DatabaseConnection, start: NaiveDate, end: NaiveDate) -> Result<Vec<Vec<String>>, DbErr> {
+    let indexes = IndexMetadata::find().order_by_asc(index_metadata::Column::IndexId).all(db).await?;
+
+    // The open is the last close before the week; a week back is enough to
+    // cover a missing day or two
+    let prices = DailyPrices::find()
+        .filter(daily_prices::Column::Date.between(start - Duration::days(7), end))
+        .order_by_asc(daily_prices::Column::Date)
+        .all(db)
+        .await?;
+    let mut by_index: HashMap<String, BTreeMap<NaiveDate, Decimal>> = HashMap::new();
+    for price in prices {
+        by_index.entry(price.index_id).or_default().insert(price.date, price.price);
+    }
+
+    let tvls = IndexTvl::find()
+        .filter(index_tvl::Column::Date.between(start, end))
+        .order_by_asc(index_tvl::Column::Date)
+        .all(db)
+        .await?;
+    let tvl_by_index: HashMap<i32, Decimal> = tvls.into_iter().map(|t| (t.index_id, t.tvl_usd)).collect();
+
+    let rows = indexes
+        .into_iter()
+        .map(|index| {
+            let prices = by_index.get(&index.index_id.to_string());
+            let open = prices.and_then(|p| p.range(..start).next_back()).map(|(_, price)| *price);
+            let close = prices.and_then(|p| p.range(..=end).next_back()).map(|(_, price)| *price);
+            let change = match (open, close) {
+                (Some(open), Some(close)) if !open.is_zero() => {
+                    Some(((close / open - Decimal::ONE) * Decimal::ONE_HUNDRED).round_dp(2))
+                }
+                _ => None,
+            };
+            vec![
+                index.name,
+                index.symbol,
+                cell(open.map(|p| p.round_dp(4))),
+                cell(close.map(|p| p.round_dp(4))),
+                cell(change),
+                cell(tvl_by_index.get(&index.index_id).map(|t| t.round_dp(2))),
+            ]
+        })
+        .collect();
+    Ok(rows)
+}
+
+async fn monthly_rebalances(db: &DatabaseConnection, start: NaiveDate, end: NaiveDate) -> Result<Vec<Vec<String>>, DbErr> {
+    let from = pricing_time::rebalance_timestamp(start);
+    let until = pricing_time::day_end_timestamp(end);
+
+    let symbols: HashMap<i32, String> = IndexMetadata::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|index| (index.index_id, index.symbol))
+        .collect();
+
+    // Earlier rebalances are read too, for what each one changed
+    let all = Rebalances::find()
+        .filter(rebalances::Column::Timestamp.lte(until))
+        .order_by(rebalances::Column::IndexId, Order::Asc)
+        .order_by(rebalances::Column::Timestamp, Order::Asc)
+        .order_by(rebalances::Column::Id, Order::Asc)
+        .all(db)
+        .await?;
+
+    let mut rows = Vec::new();
+    let mut previous: HashMap<i32, HashSet<String>> = HashMap::new();
+    for rebalance in all {
+        let coins = coin_ids(&rebalance.coins);
+        let before = previous.insert(rebalance.index_id, coins.clone());
+        if rebalance.timestamp < from {
+            continue;
+        }
+
+        let before = before.unwrap_or_default();
+        let mut added: Vec<&String> = coins.difference(&before).collect();
+        let mut removed: Vec<&String> = before.difference(&coins).collect();
+        added.sort();
+        removed.sort();
+        let deployment = match (rebalance.deployed, rebalance.tx_hash) {
+            (Some(true), Some(tx_hash)) => tx_hash,
+            (Some(true), None) => "deployed".to_string(),
+            _ => "pending".to_string(),
+        };
+
+        rows.push((
+            rebalance.timestamp,
+            vec![
+                pricing_time::rebalance_date(rebalance.timestamp).map(|d| d.to_string()).unwrap_or_default(),
+                symbols.get(&rebalance.index_id).cloned().unwrap_or_else(|| rebalance.index_id.to_string()),
+                rebalance.rebalance_type,
+                coins.len().to_string(),
+                join(&added),
+                join(&removed),
+                rebalance.portfolio_value.round_dp(2).to_string(),
+                deployment,
+            ],
+        ));
+    }
+    rows.sort_by_key(|(timestamp, _)| *timestamp);
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+fn coin_ids(coins: &serde_json::Value) -> HashSet<String> {
+    coins
+        .as_array()
+        .map(|coins| {
+            coins
+                .iter()
+                .filter_map(|c| c.get("coin_id").and_then(|id| id.as_str()).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn join(coins: &[&String]) -> String {
+    coins.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(" ")
+}
+
+fn cell(value: Option<Decimal>) -> String {
+    value.map(|v| v.normalize().to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// Render `table` in `format`
+pub fn render(table: &ReportTable, format: ReportFormat) -> Vec<u8> {
+    match format {
+        ReportFormat::Html => render_html(table).into_bytes(),
+        ReportFormat::Csv => render_csv(table).into_bytes(),
+        ReportFormat::Pdf => render_pdf(table),
+    }
+}
+
+fn render_html(table: &ReportTable) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title></head>\n\
+         <body style=\"font-family: sans-serif\">\n<h2>{0}</h2>\n\
+         <table cellpadding=\"4\" style=\"border-collapse: collapse\">\n<tr>",
+        escape_html(&table.title)
+    );
+    for column in &table.columns {
+        html.push_str(&format!("<th style=\"border-bottom: 1px solid #999; text-align: left\">{}</th>", escape_html(column)));
+    }
+    html.push_str("</tr>\n");
+    for row in &table.rows {
+        html.push_str("<tr>");
+        for value in row {
+            html.push_str(&format!("<td>{}</td>", escape_html(value)));
+        }
+        html.push_str("</tr>\n");
+    }
+    if table.rows.is_empty() {
+        html.push_str(&format!("<tr><td colspan=\"{}\">Nothing to report</td></tr>\n", table.columns.len()));
+    }
+    html.push_str("</table>\n</body></html>\n");
+    html
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn render_csv(table: &ReportTable) -> String {
+    let line = |values: Vec<&str>| values.into_iter().map(escape_csv).collect::<Vec<_>>().join(",") + "\n";
+    let mut csv = line(table.columns.clone());
+    for row in &table.rows {
+        csv.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+    csv
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The table as fixed-width text, one line per row
+fn text_lines(table: &ReportTable) -> Vec<String> {
+    let truncate = |s: &str| s.chars().take(PDF_MAX_CELL).collect::<String>();
+    let mut widths: Vec<usize> = table.columns.iter().map(|c| c.chars().count()).collect();
+    for row in &table.rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(truncate(value).chars().count());
+        }
+    }
+    let line = |values: Vec<String>| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(table.columns.iter().map(|c| c.to_string()).collect())];
+    lines.push("-".repeat(lines[0].len()));
+    for row in &table.rows {
+        lines.push(line(row.iter().map(|v| truncate(v)).collect()));
+    }
+    if table.rows.is_empty() {
+        lines.push("Nothing to report".to_string());
+    }
+    lines
+}
+
+/// A plain PDF of the table: the title, then the rows in Courier, as many
+/// A4 landscape pages as they need
+fn render_pdf(table: &ReportTable) -> Vec<u8> {
+    let lines = text_lines(table);
+    let pages: Vec<&[String]> = lines.chunks(PDF_LINES_PER_PAGE).collect();
+
+    // Objects: 1 catalog, 2 page tree, 3 font, then a page and its content
+    // stream for each page
+    let page_id = |i: usize| 4 + 2 * i;
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len()).map(|i| format!("{} 0 R", page_id(i))).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        let mut content = String::from("BT\n");
+        if i == 0 {
+            content.push_str(&format!("/F1 12 Tf\n40 555 Td\n({}) Tj\n/F1 8 Tf\n0 -24 Td\n", escape_pdf(&table.title)));
+        } else {
+            content.push_str("/F1 8 Tf\n40 555 Td\n");
+        }
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj\n0 -10 Td\n", escape_pdf(line)));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 842 595] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            page_id(i) + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref));
+    pdf.into_bytes()
+}
+
+/// Escape a string for a PDF literal; the standard fonts only cover ASCII
+fn escape_pdf(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\\' | '(' | ')' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// Filename of a stored report, e.g. weekly_performance_2026-10-05.pdf
+pub fn filename(report: &reports::Model) -> String {
+    format!("{}_{}.{}", report.template, report.period_start, report.format)
+}
+
+/// Render and store the reports of `template`'s last period that aren't
+/// stored yet, and return all of that period's reports
+pub async fn generate(
+    db: &DatabaseConnection,
+    template: ReportTemplate,
+    today: NaiveDate,
+) -> Result<Vec<reports::Model>, DbErr> {
+    let (start, end) = template.last_period(today);
+    let stored = period_reports(db, template, start).await?;
+    if stored.len() == ReportFormat::ALL.len() {
+        return Ok(stored);
+    }
+
+    let table = build(db, template, start, end).await?;
+    for format in ReportFormat::ALL {
+        if stored.iter().any(|r| r.format == format.as_str()) {
+            continue;
+        }
+        let report = reports::ActiveModel {
+            template: Set(template.as_str().to_string()),
+            format: Set(format.as_str().to_string()),
+            period_start: Set(start),
+            period_end: Set(end),
+            title: Set(table.title.clone()),
+            content_type: Set(format.content_type().to_string()),
+            content: Set(render(&table, format)),
+            recipients: Set(0),
+            delivered_at: Set(None),
+            ..Default::default()
+        };
+        Reports::insert(report)
+            .on_conflict(
+                OnConflict::columns([reports::Column::Template, reports::Column::PeriodStart, reports::Column::Format])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+    tracing::info!(template = template.as_str(), %start, %end, rows = table.rows.len(), "Report generated");
+
+    period_reports(db, template, start).await
+}
+
+async fn period_reports(
+    db: &DatabaseConnection,
+    template: ReportTemplate,
+    start: NaiveDate,
+) -> Result<Vec<reports::Model>, DbErr> {
+    Reports::find()
+        .filter(reports::Column::Template.eq(template.as_str()))
+        .filter(reports::Column::PeriodStart.eq(start))
+        .order_by_asc(reports::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Email a period's reports to every subscriber, unless they were sent
+/// already; returns the number of recipients reached
+///
+/// Failed deliveries are logged and not retried, so a bad address can't
+/// make everyone else receive the report again. Only when every recipient
+/// failed (e.g. the email API is down) is the report left undelivered for
+/// the next run.
+pub async fn deliver(
+    db: &DatabaseConnection,
+    mailer: &Mailer,
+    reports: &[reports::Model],
+    now: NaiveDateTime,
+) -> Result<usize, ReportError> {
+    let Some(html) = reports.iter().find(|r| r.format == ReportFormat::Html.as_str()) else {
+        return Ok(0);
+    };
+    if reports.iter().any(|r| r.delivered_at.is_some()) {
+        return Ok(0);
+    }
+
+    let recipients: Vec<String> = Subscriptions::find()
+        .select_only()
+        .column(subscriptions::Column::Email)
+        .order_by_asc(subscriptions::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut reached = recipients.len();
+    if !recipients.is_empty() {
+        let email = Email {
+            subject: html.title.clone(),
+            html: String::from_utf8_lossy(&html.content).into_owned(),
+            attachments: reports
+                .iter()
+                .filter(|r| r.format != ReportFormat::Html.as_str())
+                .map(|r| Attachment {
+                    filename: filename(r),
+                    content_type: r.content_type.clone(),
+                    content: r.content.clone(),
+                })
+                .collect(),
+        };
+        let mut failures = mailer.send(&recipients, &email).await;
+        if failures.len() == recipients.len() {
+            return Err(failures.swap_remove(0).into());
+        }
+        for failure in &failures {
+            tracing::warn!(template = %html.template, period_start = %html.period_start, "Report not delivered: {}", failure);
+        }
+        reached -= failures.len();
+    }
+
+    for report in reports {
+        let mut active: reports::ActiveModel = report.clone().into();
+        active.recipients = Set(reached as i32);
+        active.delivered_at = Set(Some(now));
+        active.update(db).await?;
+    }
+    tracing::info!(template = %html.template, period_start = %html.period_start, recipients = reached, "Report delivered");
+    Ok(reached)
+}
+
+/// Stored reports without their content, newest period first
+pub async fn list(
+    db: &DatabaseConnection,
+    template: Option<ReportTemplate>,
+    limit: u64,
+) -> Result<Vec<ReportSummary>, DbErr> {
+    let mut query = Reports::find()
+        .select_only()
+        .columns([
+            reports::Column::Id,
+            reports::Column::Template,
+            reports::Column::Format,
+            reports::Column::PeriodStart,
+            reports::Column::PeriodEnd,
+            reports::Column::Title,
+            reports::Column::ContentType,
+            reports::Column::Recipients,
+            reports::Column::DeliveredAt,
+            reports::Column::CreatedAt,
+        ])
+        .column_as(sea_orm::sea_query::Expr::cust("octet_length(content)"), "size");
+    if let Some(template) = template {
+        query = query.filter(reports::Column::Template.eq(template.as_str()));
+    }
+    query
+        .order_by(reports::Column::PeriodStart, Order::Desc)
+        .order_by(reports::Column::Id, Order::Asc)
+        .limit(limit)
+        .into_model::<ReportSummary>()
+        .all(db)
+        .await
+}
+
+/// A stored report's metadata
+#[derive(Debug, Clone, sea_orm::FromQueryResult)]
+pub struct ReportSummary {
+    pub id: i32,
+    pub template: String,
+    pub format: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub title: String,
+    pub content_type: String,
+    pub recipients: i32,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub size: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_last_period() {
+        // A Thursday
+        let today = date(2026, 10, 15);
+        assert_eq!(
+            ReportTemplate::WeeklyPerformance.last_period(today),
+            (date(2026, 10, 5), date(2026, 10, 11))
+        );
+        // On Monday the week that just ended is reported
+        assert_eq!(
+            ReportTemplate::WeeklyPerformance.last_period(date(2026, 10, 12)),
+            (date(2026, 10, 5), date(2026, 10, 11))
+        );
+        assert_eq!(
+            ReportTemplate::MonthlyRebalance.last_period(today),
+            (date(2026, 9, 1), date(2026, 9, 30))
+        );
+        assert_eq!(
+            ReportTemplate::MonthlyRebalance.last_period(date(2026, 1, 1)),
+            (date(2025, 12, 1), date(2025, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_and_paginates() {
+        let table = ReportTable {
+            title: "Weekly (test)".to_string(),
+            columns: vec!["Index", "Return %"],
+            rows: (0..120).map(|i| vec![format!("Index, <{}>", i), "1.5".to_string()]).collect(),
+        };
+
+        let csv = String::from_utf8(render(&table, ReportFormat::Csv)).unwrap();
+        assert_eq!(csv.lines().nth(1), Some("\"Index, <0>\",1.5"));
+
+        let html = String::from_utf8(render(&table, ReportFormat::Html)).unwrap();
+        assert!(html.contains("<td>Index, &lt;0&gt;</td>"));
+
+        let pdf = String::from_utf8(render(&table, ReportFormat::Pdf)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("(Weekly \\(test\\)) Tj"));
+        // Header, rule and 120 rows over 50-line pages
+        assert!(pdf.contains("/Count 3"));
+        let xref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 10\n"));
+    }
+}
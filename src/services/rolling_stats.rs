@@ -0,0 +1,299 @@
+//! Rolling return statistics of coins and indexes
+//!
+//! Once a day, the rolling stats job stores for every index and every coin
+//! held by one (index_constituents) the statistics of its daily log returns
+//! over the last 30, 90 and 365 days: total return, mean return,
+//! volatility and max drawdown. Coins are priced from coins_historical_prices,
+//! indexes from daily_prices; see services::correlation for how returns and
+//! gaps are handled.
+//!
+//! Computation is incremental: each run only fills the (subject, window,
+//! day) rows that are missing, going back at most `CATCH_UP_DAYS`, so a
+//! missed run is caught up without recomputing history.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+
+use crate::entities::{coins_historical_prices, daily_prices, index_constituents, prelude::*, rolling_stats};
+use crate::services::cash_buffer;
+use crate::services::correlation::{log_returns, std_dev};
+
+/// Window lengths, in days of returns
+pub const WINDOWS: [u32; 3] = [30, 90, 365];
+
+/// Days before the latest one filled in when missing
+pub const CATCH_UP_DAYS: i64 = 7;
+
+/// Days in a year of crypto trading, which never closes; returns and
+/// volatilities are annualized with it
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Decimals kept of each statistic
+const STAT_DP: u32 = 12;
+
+pub mod subjects {
+    pub const COIN: &str = "coin";
+    pub const INDEX: &str = "index";
+}
+
+/// Statistics of one window of prices
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowStats {
+    pub observations: usize,
+    pub total_return: Option<f64>,
+    pub mean_return: Option<f64>,
+    pub volatility: Option<f64>,
+    pub annualized_volatility: Option<f64>,
+    pub max_drawdown: Option<f64>,
+}
+
+/// Statistics of daily `prices` (one more than the window's returns)
+pub fn compute(prices: &[Option<f64>]) -> WindowStats {
+    let returns: Vec<f64> = log_returns(prices).into_iter().flatten().collect();
+    let available: Vec<f64> = prices.iter().flatten().copied().filter(|p| *p > 0.0).collect();
+
+    let total_return = match (available.first(), available.last()) {
+        (Some(first), Some(last)) if available.len() >= 2 => Some(last / first - 1.0),
+        _ => None,
+    };
+
+    let mut peak = f64::MIN;
+    let mut max_drawdown: Option<f64> = None;
+    for price in &available {
+        peak = peak.max(*price);
+        let drawdown = 1.0 - price / peak;
+        max_drawdown = Some(max_drawdown.map_or(drawdown, |max| max.max(drawdown)));
+    }
+
+    let volatility = std_dev(&returns);
+    WindowStats {
+        observations: returns.len(),
+        total_return,
+        mean_return: (!returns.is_empty()).then(|| returns.iter().sum::<f64>() / returns.len() as f64),
+        volatility,
+        annualized_volatility: volatility.map(|v| v * DAYS_PER_YEAR.sqrt()),
+        max_drawdown: max_drawdown.filter(|_| available.len() >= 2),
+    }
+}
+
+fn to_decimal(value: Option<f64>) -> Option<Decimal> {
+    value
+        .filter(|v| v.is_finite())
+        .and_then(Decimal::from_f64_retain)
+        .map(|d| d.round_dp(STAT_DP))
+}
+
+/// Prices of each subject by date, from `start` to `end`
+async fn load_prices(
+    db: &DatabaseConnection,
+    subject_type: &str,
+    ids: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<HashMap<String, HashMap<NaiveDate, f64>>, sea_orm::DbErr> {
+    let mut prices: HashMap<String, HashMap<NaiveDate, f64>> = HashMap::new();
+    if subject_type == subjects::COIN {
+        for row in CoinsHistoricalPrices::find()
+            .filter(coins_historical_prices::Column::CoinId.is_in(ids.iter().cloned()))
+            .filter(coins_historical_prices::Column::Date.between(start, end))
+            .all(db)
+            .await?
+        {
+            if let Some(price) = row.price.to_f64() {
+                prices.entry(row.coin_id).or_default().insert(row.date, price);
+            }
+        }
+    } else {
+        for row in DailyPrices::find()
+            .filter(daily_prices::Column::IndexId.is_in(ids.iter().cloned()))
+            .filter(daily_prices::Column::Date.between(start, end))
+            .all(db)
+            .await?
+        {
+            if let Some(price) = row.price.to_f64() {
+                prices.entry(row.index_id).or_default().insert(row.date, price);
+            }
+        }
+    }
+    Ok(prices)
+}
+
+/// Subjects whose stats are kept: every index, and every coin an index holds
+async fn subject_ids(db: &DatabaseConnection) -> Result<Vec<(&'static str, Vec<String>)>, sea_orm::DbErr> {
+    let mut coins: Vec<String> = IndexConstituents::find()
+        .select_only()
+        .column(index_constituents::Column::CoinId)
+        .distinct()
+        .into_tuple::<String>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|coin_id| !cash_buffer::is_cash(coin_id))
+        .collect();
+    coins.sort();
+
+    let mut indexes: Vec<String> = IndexMetadata::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|index| index.index_id.to_string())
+        .collect();
+    indexes.sort();
+
+    Ok(vec![(subjects::COIN, coins), (subjects::INDEX, indexes)])
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RollingStatsSummary {
+    pub subjects: usize,
+    pub stored: usize,
+    /// Rows already computed by an earlier run
+    pub skipped: usize,
+}
+
+/// Fill the missing rows of the `CATCH_UP_DAYS` days up to `latest`
+pub async fn run(
+    db: &DatabaseConnection,
+    latest: NaiveDate,
+    now: NaiveDateTime,
+) -> Result<RollingStatsSummary, sea_orm::DbErr> {
+    let first_day = latest - Duration::days(CATCH_UP_DAYS - 1);
+    let longest = *WINDOWS.iter().max().unwrap_or(&0) as i64;
+    let mut summary = RollingStatsSummary::default();
+
+    for (subject_type, ids) in subject_ids(db).await? {
+        if ids.is_empty() {
+            continue;
+        }
+        summary.subjects += ids.len();
+
+        let existing: HashSet<(String, i32, NaiveDate)> = RollingStats::find()
+            .filter(rolling_stats::Column::SubjectType.eq(subject_type))
+            .filter(rolling_stats::Column::AsOf.between(first_day, latest))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|row| (row.subject_id, row.window_days, row.as_of))
+            .collect();
+
+        let prices = load_prices(db, subject_type, &ids, first_day - Duration::days(longest), latest).await?;
+
+        let mut rows = Vec::new();
+        for id in &ids {
+            let Some(by_date) = prices.get(id) else {
+                continue;
+            };
+            for as_of in first_day.iter_days().take_while(|day| *day <= latest) {
+                // Nothing to report for a day the subject has no price
+                if !by_date.contains_key(&as_of) {
+                    continue;
+                }
+                for window in WINDOWS {
+                    if existing.contains(&(id.clone(), window as i32, as_of)) {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    let series: Vec<Option<f64>> = (0..=window as i64)
+                        .rev()
+                        .map(|back| by_date.get(&(as_of - Duration::days(back))).copied())
+                        .collect();
+                    let stats = compute(&series);
+                    rows.push(rolling_stats::ActiveModel {
+                        subject_type: Set(subject_type.to_string()),
+                        subject_id: Set(id.clone()),
+                        window_days: Set(window as i32),
+                        as_of: Set(as_of),
+                        observations: Set(stats.observations as i32),
+                        total_return: Set(to_decimal(stats.total_return)),
+                        mean_return: Set(to_decimal(stats.mean_return)),
+                        volatility: Set(to_decimal(stats.volatility)),
+                        annualized_volatility: Set(to_decimal(stats.annualized_volatility)),
+                        max_drawdown: Set(to_decimal(stats.max_drawdown)),
+                        computed_at: Set(now),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        summary.stored += rows.len();
+        // Keeps each insert well under Postgres' bind parameter limit
+        for chunk in rows.chunks(1000) {
+            RollingStats::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::columns([
+                        rolling_stats::Column::SubjectType,
+                        rolling_stats::Column::SubjectId,
+                        rolling_stats::Column::WindowDays,
+                        rolling_stats::Column::AsOf,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(db)
+                .await?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Stats of a subject on its latest computed day, or on `as_of`, by window
+pub async fn latest(
+    db: &DatabaseConnection,
+    subject_type: &str,
+    subject_id: &str,
+    as_of: Option<NaiveDate>,
+) -> Result<Vec<rolling_stats::Model>, sea_orm::DbErr> {
+    let mut query = RollingStats::find()
+        .filter(rolling_stats::Column::SubjectType.eq(subject_type))
+        .filter(rolling_stats::Column::SubjectId.eq(subject_id));
+    if let Some(as_of) = as_of {
+        query = query.filter(rolling_stats::Column::AsOf.lte(as_of));
+    }
+    let Some(newest) = query
+        .clone()
+        .order_by_desc(rolling_stats::Column::AsOf)
+        .one(db)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    query
+        .filter(rolling_stats::Column::AsOf.eq(newest.as_of))
+        .order_by_asc(rolling_stats::Column::WindowDays)
+        .all(db)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-9)
+    }
+
+    #[test]
+    fn test_compute() {
+        let stats = compute(&[Some(100.0), Some(120.0), Some(90.0), None, Some(110.0)]);
+        assert_eq!(stats.observations, 2);
+        assert!(close(stats.total_return, 0.1));
+        assert!(close(stats.max_drawdown, 0.25));
+        assert!(close(stats.mean_return, (1.2f64.ln() + 0.75f64.ln()) / 2.0));
+        assert!(close(stats.annualized_volatility, stats.volatility.unwrap() * 365f64.sqrt()));
+    }
+
+    #[test]
+    fn test_compute_without_enough_prices() {
+        let stats = compute(&[None, Some(100.0), None]);
+        assert_eq!(stats, WindowStats::default());
+    }
+}
@@ -0,0 +1,221 @@
+//! Entity vs. database schema drift detection
+//!
+//! Entities are generated once and then edited by hand alongside the
+//! migrations, so the two can disagree: a column added by a migration but
+//! never to the entity (ignored on read, missing on insert), or an entity
+//! for a table no migration creates (works on an old database, breaks on a
+//! fresh one). `check` compares the columns of every entity with the live
+//! schema. Used by the `check_schema` binary.
+//!
+//! New entities must be added to `entity_schemas` to be checked.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use sea_orm::{
+    DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, IdenStatic, Iterable,
+    Statement,
+};
+
+use crate::entities::{prelude::*, sync_status};
+
+/// Table and column names declared by an entity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntitySchema {
+    pub table: String,
+    pub columns: BTreeSet<String>,
+}
+
+fn schema_of<E: EntityTrait>() -> EntitySchema {
+    EntitySchema {
+        table: E::default().table_name().to_string(),
+        columns: E::Column::iter().map(|c| c.as_str().to_string()).collect(),
+    }
+}
+
+/// Every entity in `crate::entities`
+pub fn entity_schemas() -> Vec<EntitySchema> {
+    vec![
+        schema_of::<Announcements>(),
+        schema_of::<ApiKeyUsage>(),
+        schema_of::<ApiKeys>(),
+        schema_of::<AuthNonces>(),
+        schema_of::<BackgroundTasks>(),
+        schema_of::<BlockchainEvents>(),
+        schema_of::<CategoryChangeEvents>(),
+        schema_of::<CategoryMembership>(),
+        schema_of::<ChainTransactions>(),
+        schema_of::<CoingeckoCategories>(),
+        schema_of::<CoinLogos>(),
+        schema_of::<Coins>(),
+        schema_of::<CoinsHistoricalPrices>(),
+        schema_of::<CoinYieldRates>(),
+        schema_of::<CryptoListings>(),
+        schema_of::<DailyPrices>(),
+        schema_of::<FeatureFlags>(),
+        schema_of::<IndexConstituents>(),
+        schema_of::<IndexDeployments>(),
+        schema_of::<IndexMetadata>(),
+        schema_of::<IndexTranslations>(),
+        schema_of::<IndexTvl>(),
+        schema_of::<ItpDriftChecks>(),
+        schema_of::<ItpOrders>(),
+        schema_of::<ItpPriceHistory>(),
+        schema_of::<Itps>(),
+        schema_of::<JobFailures>(),
+        schema_of::<KeeperClaimableData>(),
+        schema_of::<Labels>(),
+        schema_of::<MarketCapRankings>(),
+        schema_of::<MethodologyDocuments>(),
+        schema_of::<Operations>(),
+        schema_of::<PriceReconciliationChecks>(),
+        schema_of::<RebalanceApprovals>(),
+        schema_of::<Rebalances>(),
+        schema_of::<RollingStats>(),
+        schema_of::<SnapshotLog>(),
+        schema_of::<Solvers>(),
+        schema_of::<Subscriptions>(),
+        schema_of::<sync_status::Entity>(),
+        schema_of::<WalletBalanceChecks>(),
+    ]
+}
+
+/// A mismatch between an entity and the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// The entity's table does not exist
+    MissingTable { table: String },
+    /// The entity declares a column the table doesn't have
+    MissingColumn { table: String, column: String },
+    /// The table has a column the entity doesn't declare
+    UnmappedColumn { table: String, column: String },
+}
+
+impl std::fmt::Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::MissingTable { table } => write!(f, "{}: table missing from database", table),
+            Drift::MissingColumn { table, column } => {
+                write!(f, "{}.{}: in entity, missing from database", table, column)
+            }
+            Drift::UnmappedColumn { table, column } => {
+                write!(f, "{}.{}: in database, missing from entity", table, column)
+            }
+        }
+    }
+}
+
+/// Compare entities with the database's tables (table name -> columns)
+pub fn diff(entities: &[EntitySchema], database: &BTreeMap<String, BTreeSet<String>>) -> Vec<Drift> {
+    let mut drift = Vec::new();
+
+    for entity in entities {
+        let Some(columns) = database.get(&entity.table) else {
+            drift.push(Drift::MissingTable { table: entity.table.clone() });
+            continue;
+        };
+
+        for column in entity.columns.difference(columns) {
+            drift.push(Drift::MissingColumn { table: entity.table.clone(), column: column.clone() });
+        }
+        for column in columns.difference(&entity.columns) {
+            drift.push(Drift::UnmappedColumn { table: entity.table.clone(), column: column.clone() });
+        }
+    }
+
+    drift
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ColumnRow {
+    table_name: String,
+    column_name: String,
+}
+
+/// Columns of every table in the connection's current schema
+///
+/// Partitions are skipped; their parent table is what entities map to.
+pub async fn database_columns(
+    db: &DatabaseConnection,
+) -> Result<BTreeMap<String, BTreeSet<String>>, Box<dyn std::error::Error + Send + Sync>> {
+    let rows = ColumnRow::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"
+        SELECT c.table_name::text AS table_name, c.column_name::text AS column_name
+        FROM information_schema.columns c
+        JOIN pg_class t ON t.relname = c.table_name
+        JOIN pg_namespace n ON n.oid = t.relnamespace AND n.nspname = c.table_schema
+        WHERE c.table_schema = current_schema()
+          AND NOT t.relispartition
+        "#,
+    ))
+    .all(db)
+    .await?;
+
+    let mut tables: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for row in rows {
+        tables.entry(row.table_name).or_default().insert(row.column_name);
+    }
+    Ok(tables)
+}
+
+/// Compare every entity with the live schema
+pub async fn check(db: &DatabaseConnection) -> Result<Vec<Drift>, Box<dyn std::error::Error + Send + Sync>> {
+    let database = database_columns(db).await?;
+    Ok(diff(&entity_schemas(), &database))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_entity_schema_reads_columns() {
+        let schema = schema_of::<IndexDeployments>();
+        assert_eq!(schema.table, "index_deployments");
+        assert_eq!(
+            schema.columns,
+            columns(&["id", "index_id", "network", "contract_address", "created_at"])
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_both_directions() {
+        let entities = vec![EntitySchema { table: "itps".to_string(), columns: columns(&["id", "name"]) }];
+        let mut database = BTreeMap::new();
+        database.insert("itps".to_string(), columns(&["id", "admin_address"]));
+
+        assert_eq!(
+            diff(&entities, &database),
+            vec![
+                Drift::MissingColumn { table: "itps".to_string(), column: "name".to_string() },
+                Drift::UnmappedColumn { table: "itps".to_string(), column: "admin_address".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_missing_table_and_match() {
+        let entities = vec![
+            EntitySchema { table: "token_metadata".to_string(), columns: columns(&["id"]) },
+            EntitySchema { table: "coins".to_string(), columns: columns(&["id"]) },
+        ];
+        let mut database = BTreeMap::new();
+        database.insert("coins".to_string(), columns(&["id"]));
+
+        assert_eq!(
+            diff(&entities, &database),
+            vec![Drift::MissingTable { table: "token_metadata".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_every_entity_has_a_distinct_table() {
+        let schemas = entity_schemas();
+        let tables: BTreeSet<_> = schemas.iter().map(|s| s.table.clone()).collect();
+        assert_eq!(tables.len(), schemas.len());
+    }
+}
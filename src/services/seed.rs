@@ -0,0 +1,418 @@
+//! Seed data for local and test databases
+//!
+//! A small, deterministic dataset: four coins with daily price history, one
+//! CoinGecko category, and one index with constituents, a deployment,
+//! rebalances, daily prices and mint events. Enough for the index, price and
+//! transaction endpoints to return realistic responses without a production
+//! dump. Used by the `seed` binary.
+//!
+//! Seeding is idempotent. Shared rows (coins, prices, the category) are
+//! inserted only if missing; the seed index and everything hanging off it
+//! are deleted and recreated. Nothing outside the seed index is removed.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, Set,
+};
+
+use crate::entities::{
+    blockchain_events, category_membership, coingecko_categories, coins, coins_historical_prices,
+    daily_prices, index_constituents, index_deployments, index_metadata, prelude::*, rebalances,
+};
+use crate::services::index_deployments::DEFAULT_NETWORK;
+use crate::services::pricing_time;
+use crate::services::rebalance_math::{self, FeeConfig, Position, PricedConstituent};
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+use crate::services::units;
+
+pub const SEED_INDEX_ID: i32 = 9000;
+pub const SEED_INDEX_SYMBOL: &str = "SEED";
+pub const SEED_INDEX_ADDRESS: &str = "0x5eed000000000000000000000000000000000001";
+pub const SEED_CATEGORY_ID: &str = "seed-layer-1";
+
+const REBALANCE_PERIOD_DAYS: i64 = 30;
+const INITIAL_PRICE: Decimal = dec!(1000);
+const USDC_DECIMALS: u32 = 6;
+const INDEX_DECIMALS: u32 = 18;
+
+/// A seeded coin: (coin_id, symbol, name, base price, circulating supply)
+struct SeedCoin {
+    coin_id: &'static str,
+    symbol: &'static str,
+    name: &'static str,
+    base_price: Decimal,
+    supply: Decimal,
+    /// Member of the seed category and constituent of the seed index
+    in_index: bool,
+}
+
+const COINS: &[SeedCoin] = &[
+    SeedCoin { coin_id: "bitcoin", symbol: "BTC", name: "Bitcoin", base_price: dec!(60000), supply: dec!(19700000), in_index: true },
+    SeedCoin { coin_id: "ethereum", symbol: "ETH", name: "Ethereum", base_price: dec!(3000), supply: dec!(120000000), in_index: true },
+    SeedCoin { coin_id: "solana", symbol: "SOL", name: "Solana", base_price: dec!(150), supply: dec!(460000000), in_index: true },
+    SeedCoin { coin_id: "chainlink", symbol: "LINK", name: "Chainlink", base_price: dec!(15), supply: dec!(600000000), in_index: false },
+];
+
+/// Rows written by `seed`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub coins: usize,
+    pub prices: usize,
+    pub rebalances: usize,
+    pub daily_prices: usize,
+    pub events: usize,
+}
+
+/// Deterministic price of a coin `day` days into the seeded history
+///
+/// A slow upward drift with a ±10% two-week swing, so charts and returns
+/// aren't flat.
+pub fn seed_price(base_price: Decimal, day: i64) -> Decimal {
+    let swing = Decimal::from((day % 14 - 7).abs() - 3) * dec!(2.5); // -7.5..=10 percent
+    let drift = Decimal::from(day) / dec!(10); // +0.1% per day
+    (base_price * (dec!(100) + swing + drift) / dec!(100)).round_dp(8)
+}
+
+/// Seed `days` days of history ending on `today`
+pub async fn seed(
+    db: &DatabaseConnection,
+    today: NaiveDate,
+    days: i64,
+) -> Result<SeedSummary, Box<dyn std::error::Error + Send + Sync>> {
+    if days < 1 {
+        return Err("Seed history needs at least one day".into());
+    }
+    let start = today - Duration::days(days - 1);
+    let now = Utc::now().naive_utc();
+    let mut summary = SeedSummary::default();
+
+    // Coins and their price history (kept if already present)
+    Coins::insert_many(COINS.iter().map(|c| coins::ActiveModel {
+        coin_id: Set(c.coin_id.to_string()),
+        symbol: Set(c.symbol.to_lowercase()),
+        name: Set(c.name.to_string()),
+        active: Set(true),
+        deactivated: Set(false),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+        ..Default::default()
+    }))
+    .on_conflict(OnConflict::column(coins::Column::CoinId).do_nothing().to_owned())
+    .exec_without_returning(db)
+    .await?;
+    summary.coins = COINS.len();
+
+    let mut prices: HashMap<(&str, NaiveDate), Decimal> = HashMap::new();
+    for coin in COINS {
+        let rows: Vec<_> = (0..days)
+            .map(|day| {
+                let date = start + Duration::days(day);
+                let price = seed_price(coin.base_price, day);
+                prices.insert((coin.coin_id, date), price);
+                coins_historical_prices::ActiveModel {
+                    coin_id: Set(coin.coin_id.to_string()),
+                    symbol: Set(coin.symbol.to_lowercase()),
+                    date: Set(date),
+                    price: Set(price),
+                    market_cap: Set(Some((price * coin.supply).round_dp(2))),
+                    volume: Set(Some((price * coin.supply / dec!(50)).round_dp(2))),
+                    created_at: Set(Some(now)),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        summary.prices += rows.len();
+
+        CoinsHistoricalPrices::insert_many(rows)
+            .on_conflict(
+                OnConflict::columns([coins_historical_prices::Column::CoinId, coins_historical_prices::Column::Date])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+
+    // Category and memberships
+    CoingeckoCategories::insert(coingecko_categories::ActiveModel {
+        category_id: Set(SEED_CATEGORY_ID.to_string()),
+        name: Set("Seed Layer 1 (L1)".to_string()),
+        updated_at: Set(Some(now)),
+        ..Default::default()
+    })
+    .on_conflict(OnConflict::column(coingecko_categories::Column::CategoryId).do_nothing().to_owned())
+    .exec_without_returning(db)
+    .await?;
+
+    CategoryMembership::delete_many()
+        .filter(category_membership::Column::CategoryId.eq(SEED_CATEGORY_ID))
+        .exec(db)
+        .await?;
+    let members: Vec<&SeedCoin> = COINS.iter().filter(|c| c.in_index).collect();
+    CategoryMembership::insert_many(members.iter().map(|c| category_membership::ActiveModel {
+        coin_id: Set(c.coin_id.to_string()),
+        category_id: Set(SEED_CATEGORY_ID.to_string()),
+        symbol: Set(Some(c.symbol.to_lowercase())),
+        added_date: Set(start.and_hms_opt(0, 0, 0).unwrap_or(now)),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+        ..Default::default()
+    }))
+    .exec_without_returning(db)
+    .await?;
+
+    // The seed index, recreated from scratch
+    clear_index(db).await?;
+
+    let fees = FeeConfig { trading_fee: dec!(0.001), spread: dec!(0.0005) };
+    index_metadata::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        name: Set("Seed Layer 1 Index".to_string()),
+        symbol: Set(SEED_INDEX_SYMBOL.to_string()),
+        address: Set(SEED_INDEX_ADDRESS.to_string()),
+        category: Set(Some("Layer 1".to_string())),
+        asset_class: Set(Some("Cryptocurrencies".to_string())),
+        initial_date: Set(Some(start)),
+        initial_price: Set(Some(INITIAL_PRICE)),
+        coingecko_category: Set(Some(SEED_CATEGORY_ID.to_string())),
+        exchanges_allowed: Set(Some(serde_json::json!(["binance"]))),
+        exchange_trading_fees: Set(Some(fees.trading_fee)),
+        exchange_avg_spread: Set(Some(fees.spread)),
+        rebalance_period: Set(Some(REBALANCE_PERIOD_DAYS as i32)),
+        weight_strategy: Set("equal".to_string()),
+        // History is seeded here; keep the rebalance job from backfilling it
+        skip_backfill: Set(true),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    index_deployments::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        network: Set(DEFAULT_NETWORK.to_string()),
+        contract_address: Set(SEED_INDEX_ADDRESS.to_string()),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    IndexConstituents::insert_many(members.iter().enumerate().map(|(position, c)| {
+        index_constituents::ActiveModel {
+            index_id: Set(SEED_INDEX_ID),
+            coin_id: Set(c.coin_id.to_string()),
+            token_symbol: Set(c.symbol.to_string()),
+            token_name: Set(c.name.to_string()),
+            exchange: Set("binance".to_string()),
+            trading_pair: Set("usdc".to_string()),
+            position: Set(position as i32 + 1),
+            added_at: Set(Some(now)),
+            ..Default::default()
+        }
+    }))
+    .exec_without_returning(db)
+    .await?;
+
+    // Rebalances every period, and the index price each day from the
+    // positions of the latest rebalance
+    let mut held: Vec<Position> = Vec::new();
+    let mut daily_rows = Vec::new();
+    for day in 0..days {
+        let date = start + Duration::days(day);
+        let prices_today: HashMap<String, Decimal> = members
+            .iter()
+            .filter_map(|c| prices.get(&(c.coin_id, date)).map(|p| (c.coin_id.to_string(), *p)))
+            .collect();
+
+        if day % REBALANCE_PERIOD_DAYS == 0 {
+            let constituents: Vec<PricedConstituent> = members
+                .iter()
+                .map(|c| PricedConstituent {
+                    coin_id: c.coin_id.to_string(),
+                    weight: Decimal::ONE,
+                    price: prices_today[c.coin_id],
+                })
+                .collect();
+            let (value, previous) = if held.is_empty() {
+                (INITIAL_PRICE, None)
+            } else {
+                let previous: HashMap<String, Decimal> =
+                    held.iter().map(|p| (p.coin_id.clone(), p.quantity)).collect();
+                (rebalance_math::portfolio_value(&held, &prices_today), Some(previous))
+            };
+            let composition = rebalance_math::compose(value, &constituents, None, previous.as_ref(), &fees)?;
+
+            insert_rebalance(db, date, &members, &composition, previous.is_none()).await?;
+            summary.rebalances += 1;
+
+            // Quantities are scaled so the positions hold the after-fees value
+            let scale = composition.portfolio_value_after_fees / composition.portfolio_value_before_fees;
+            held = composition
+                .positions
+                .into_iter()
+                .map(|p| Position { quantity: p.quantity * scale, ..p })
+                .collect();
+        }
+
+        let quantities: HashMap<&str, Decimal> =
+            held.iter().map(|p| (p.coin_id.as_str(), p.quantity.round_dp(12))).collect();
+        daily_rows.push(daily_prices::ActiveModel {
+            index_id: Set(SEED_INDEX_ID.to_string()),
+            date: Set(date),
+            price: Set(rebalance_math::portfolio_value(&held, &prices_today).round_dp(8)),
+            quantities: Set(Some(serde_json::to_value(&quantities)?)),
+            created_at: Set(Some(now)),
+            updated_at: Set(Some(now)),
+        });
+    }
+    summary.daily_prices = daily_rows.len();
+    DailyPrices::insert_many(daily_rows).exec_without_returning(db).await?;
+
+    summary.events = insert_events(db, start, days).await?;
+
+    Ok(summary)
+}
+
+/// Delete the seed index and its dependent rows
+async fn clear_index(db: &DatabaseConnection) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    BlockchainEvents::delete_many()
+        .filter(blockchain_events::Column::ContractAddress.eq(SEED_INDEX_ADDRESS))
+        .exec(db)
+        .await?;
+    DailyPrices::delete_many()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .exec(db)
+        .await?;
+    Rebalances::delete_many()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .exec(db)
+        .await?;
+    IndexConstituents::delete_many()
+        .filter(index_constituents::Column::IndexId.eq(SEED_INDEX_ID))
+        .exec(db)
+        .await?;
+    IndexDeployments::delete_many()
+        .filter(index_deployments::Column::IndexId.eq(SEED_INDEX_ID))
+        .exec(db)
+        .await?;
+    IndexMetadata::delete_by_id(SEED_INDEX_ID).exec(db).await?;
+    Ok(())
+}
+
+async fn insert_rebalance(
+    db: &DatabaseConnection,
+    date: NaiveDate,
+    members: &[&SeedCoin],
+    composition: &rebalance_math::Composition,
+    initial: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let symbols: HashMap<&str, &str> = members.iter().map(|c| (c.coin_id, c.symbol)).collect();
+    let coins: Vec<CoinRebalanceInfo> = composition
+        .positions
+        .iter()
+        .map(|p| CoinRebalanceInfo {
+            coin_id: p.coin_id.clone(),
+            symbol: symbols.get(p.coin_id.as_str()).copied().unwrap_or_default().to_string(),
+            quantity: p.quantity.to_string(),
+            weight: p.weight.to_string(),
+            price: p.price,
+            exchange: "binance".to_string(),
+            trading_pair: "usdc".to_string(),
+        })
+        .collect();
+
+    let timestamp = pricing_time::rebalance_timestamp(date);
+    rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(rebalance_schema::encode(&coins)?),
+        portfolio_value: Set(composition.portfolio_value_after_fees),
+        total_weight: Set(composition.total_weight),
+        timestamp: Set(timestamp),
+        rebalance_type: Set(if initial { "initial" } else { "periodic" }.to_string()),
+        deployed: Set(Some(false)),
+        created_at: Set(Some(Utc::now().naive_utc())),
+        total_fees: Set(Some(composition.fees)),
+        fee_breakdown: Set(Some(serde_json::to_value(&composition.fee_breakdown)?)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// A deposit and mint pair every week, with raw on-chain amounts
+async fn insert_events(
+    db: &DatabaseConnection,
+    start: NaiveDate,
+    days: i64,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut rows = Vec::new();
+    for (n, day) in (0..days).step_by(7).enumerate() {
+        let date = start + Duration::days(day);
+        let timestamp = Utc.from_utc_datetime(&date.and_hms_opt(12, 0, 0).unwrap_or_default());
+        let tx_hash = format!("0x{:064x}", 0x5eed_0000 + n);
+        let user = format!("0x{:040x}", 0xa11ce + n % 3);
+        let collateral = Decimal::from(100 * (n as i64 % 5 + 1));
+        let quantity = (collateral / INITIAL_PRICE).round_dp(6);
+
+        for (log_index, event_type) in ["deposit", "mint"].into_iter().enumerate() {
+            let raw_amount = units::to_base_units(collateral, USDC_DECIMALS)?;
+            let raw_quantity = units::to_base_units(quantity, INDEX_DECIMALS)?;
+            rows.push(blockchain_events::ActiveModel {
+                tx_hash: Set(tx_hash.clone()),
+                block_number: Set(20_000_000 + day as i32 * 43_200),
+                log_index: Set(log_index as i32),
+                event_type: Set(event_type.to_string()),
+                contract_address: Set(SEED_INDEX_ADDRESS.to_string()),
+                network: Set(DEFAULT_NETWORK.to_string()),
+                user_address: Set(Some(user.clone())),
+                amount: Set(Some(collateral)),
+                quantity: Set(Some(quantity)),
+                timestamp: Set(Some(timestamp.into())),
+                raw_amount: Set(Some(raw_amount.to_string())),
+                decimals: Set(Some(USDC_DECIMALS as i32)),
+                raw_quantity: Set(Some(raw_quantity.to_string())),
+                quantity_decimals: Set(Some(INDEX_DECIMALS as i32)),
+                ..Default::default()
+            });
+        }
+    }
+
+    let count = rows.len();
+    if count > 0 {
+        BlockchainEvents::insert_many(rows).exec_without_returning(db).await?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_price_is_deterministic_and_positive() {
+        for day in 0..365 {
+            let price = seed_price(dec!(150), day);
+            assert_eq!(price, seed_price(dec!(150), day));
+            assert!(price > Decimal::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_seed_price_swings_around_base() {
+        assert_eq!(seed_price(dec!(100), 0), dec!(110));
+        assert_eq!(seed_price(dec!(100), 7), dec!(93.2));
+    }
+
+    #[test]
+    fn test_index_members() {
+        let members: Vec<_> = COINS.iter().filter(|c| c.in_index).map(|c| c.coin_id).collect();
+        assert_eq!(members, vec!["bitcoin", "ethereum", "solana"]);
+    }
+}
@@ -0,0 +1,514 @@
+//! Transaction signers for the backend's Arbitrum wallet
+//!
+//! SIGNER_BACKEND picks where the signing key lives:
+//!
+//! - `local` (default): a raw key in ARBITRUM_PRIVATE_KEY (or DEPLOY_PRIVATE_KEY).
+//!   Meant for development and testnets.
+//! - `aws-kms` (built with the `aws-kms` feature): an ECC_SECG_P256K1 AWS KMS key,
+//!   AWS_KMS_KEY_ID, signed through the KMS API with credentials from AWS_REGION,
+//!   AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and optionally AWS_SESSION_TOKEN.
+//!   AWS_KMS_ENDPOINT overrides the regional endpoint (VPC endpoints).
+//! - `vault` (built with the `vault` feature): a secp256k1 transit key,
+//!   VAULT_TRANSIT_KEY on the VAULT_TRANSIT_MOUNT mount (default `transit`) of
+//!   VAULT_ADDR, authenticated with VAULT_TOKEN (and VAULT_NAMESPACE if set).
+//!   Stock transit has no secp256k1 key type, so this needs a transit engine
+//!   that provides one; any other key type is refused at startup.
+//!
+//! Remote backends only ever see transaction hashes. They return DER ECDSA
+//! signatures, which are normalized to low-s and given the recovery id that
+//! yields the key's address.
+
+// Signature recovery is only used by the feature-gated remote backends
+#![cfg_attr(not(any(feature = "aws-kms", feature = "vault")), allow(dead_code))]
+
+use alloy::{
+    network::EthereumWallet,
+    primitives::{PrimitiveSignature, B256},
+    signers::local::PrivateKeySigner,
+};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use k256::pkcs8::DecodePublicKey;
+use tokio::sync::OnceCell;
+use tracing::info;
+
+const ENV_BACKEND: &str = "SIGNER_BACKEND";
+
+#[derive(Debug)]
+pub enum SignerError {
+    /// A required environment variable is not set
+    MissingConfig(&'static str),
+    InvalidConfig(String),
+    /// SIGNER_BACKEND names a backend this binary wasn't built with
+    Unsupported(String),
+    /// The KMS / Vault request failed or returned something unusable
+    Remote(String),
+}
+
+impl std::fmt::Display for SignerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerError::MissingConfig(var) => write!(f, "{} not configured", var),
+            SignerError::InvalidConfig(msg) => write!(f, "Invalid signer configuration: {}", msg),
+            SignerError::Unsupported(backend) => write!(f, "Signer backend '{}' is not supported by this build", backend),
+            SignerError::Remote(msg) => write!(f, "Remote signer error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
+/// Wallets of remote backends, built once so the public key is only fetched at startup
+static REMOTE_WALLET: OnceCell<EthereumWallet> = OnceCell::const_new();
+
+/// The wallet the backend signs Arbitrum transactions with, per SIGNER_BACKEND
+pub async fn wallet_from_env() -> Result<EthereumWallet, SignerError> {
+    let backend = std::env::var(ENV_BACKEND).unwrap_or_else(|_| "local".to_string());
+    match backend.as_str() {
+        "local" => local_wallet(),
+        "aws-kms" | "vault" => REMOTE_WALLET.get_or_try_init(|| remote_wallet(&backend)).await.cloned(),
+        other => Err(SignerError::InvalidConfig(format!("unknown {} '{}'", ENV_BACKEND, other))),
+    }
+}
+
+fn local_wallet() -> Result<EthereumWallet, SignerError> {
+    let private_key = std::env::var("ARBITRUM_PRIVATE_KEY")
+        .or_else(|_| std::env::var("DEPLOY_PRIVATE_KEY"))
+        .map_err(|_| SignerError::MissingConfig("ARBITRUM_PRIVATE_KEY"))?;
+    let signer: PrivateKeySigner = private_key
+        .parse()
+        .map_err(|e| SignerError::InvalidConfig(format!("Invalid private key: {}", e)))?;
+    Ok(EthereumWallet::from(signer))
+}
+
+async fn remote_wallet(backend: &str) -> Result<EthereumWallet, SignerError> {
+    let wallet: Option<EthereumWallet> = match backend {
+        #[cfg(feature = "aws-kms")]
+        "aws-kms" => Some(EthereumWallet::from(remote::RemoteSigner::new(aws_kms::KmsSigner::from_env()?).await?)),
+        #[cfg(feature = "vault")]
+        "vault" => Some(EthereumWallet::from(remote::RemoteSigner::new(vault::VaultSigner::from_env()?).await?)),
+        _ => None,
+    };
+    let wallet = wallet.ok_or_else(|| SignerError::Unsupported(backend.to_string()))?;
+    info!(backend, address = %wallet.default_signer().address(), "Remote transaction signer ready");
+    Ok(wallet)
+}
+
+/// Public key of a DER SubjectPublicKeyInfo, refusing anything but secp256k1
+pub fn public_key_from_spki(der: &[u8]) -> Result<VerifyingKey, SignerError> {
+    VerifyingKey::from_public_key_der(der)
+        .map_err(|e| SignerError::InvalidConfig(format!("Signing key is not a secp256k1 public key: {}", e)))
+}
+
+/// Turn a DER signature of `hash` by `key` into an Ethereum signature
+///
+/// Ethereum only accepts low-s signatures, which KMS and Vault don't
+/// guarantee, and needs the recovery id they don't return.
+pub fn recoverable_signature(hash: &B256, der: &[u8], key: &VerifyingKey) -> Result<PrimitiveSignature, SignerError> {
+    let signature = Signature::from_der(der).map_err(|e| SignerError::Remote(format!("Invalid DER signature: {}", e)))?;
+    let signature = signature.normalize_s().unwrap_or(signature);
+    let recovery_id = RecoveryId::trial_recovery_from_prehash(key, hash.as_slice(), &signature)
+        .map_err(|_| SignerError::Remote("Signature does not match the signing key".to_string()))?;
+    Ok(PrimitiveSignature::from((signature, recovery_id)))
+}
+
+#[cfg(any(feature = "aws-kms", feature = "vault"))]
+mod remote {
+    use alloy::{
+        consensus::SignableTransaction,
+        network::TxSigner,
+        primitives::{Address, PrimitiveSignature, B256},
+    };
+    use async_trait::async_trait;
+    use k256::ecdsa::VerifyingKey;
+
+    use super::{recoverable_signature, SignerError};
+
+    /// A key held by an external service that signs digests
+    #[async_trait]
+    pub(super) trait DigestSigner: Send + Sync {
+        async fn public_key(&self) -> Result<VerifyingKey, SignerError>;
+
+        /// DER ECDSA signature of a 32-byte prehashed digest
+        async fn sign_digest(&self, digest: &B256) -> Result<Vec<u8>, SignerError>;
+    }
+
+    pub(super) struct RemoteSigner<S> {
+        backend: S,
+        key: VerifyingKey,
+        address: Address,
+    }
+
+    impl<S: DigestSigner> RemoteSigner<S> {
+        pub(super) async fn new(backend: S) -> Result<Self, SignerError> {
+            let key = backend.public_key().await?;
+            let address = Address::from_public_key(&key);
+            Ok(Self { backend, key, address })
+        }
+    }
+
+    #[async_trait]
+    impl<S: DigestSigner> TxSigner<PrimitiveSignature> for RemoteSigner<S> {
+        fn address(&self) -> Address {
+            self.address
+        }
+
+        async fn sign_transaction(
+            &self,
+            tx: &mut dyn SignableTransaction<PrimitiveSignature>,
+        ) -> alloy::signers::Result<PrimitiveSignature> {
+            let hash = tx.signature_hash();
+            let der = self.backend.sign_digest(&hash).await.map_err(alloy::signers::Error::other)?;
+            recoverable_signature(&hash, &der, &self.key).map_err(alloy::signers::Error::other)
+        }
+    }
+}
+
+#[cfg(feature = "aws-kms")]
+mod aws_kms {
+    use alloy::primitives::B256;
+    use async_trait::async_trait;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hmac::{Hmac, Mac};
+    use k256::ecdsa::VerifyingKey;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+
+    use super::{public_key_from_spki, remote::DigestSigner, required_env, SignerError};
+
+    /// Signs with an AWS KMS key through the JSON API (SigV4-signed requests)
+    pub(super) struct KmsSigner {
+        client: reqwest::Client,
+        endpoint: String,
+        host: String,
+        region: String,
+        key_id: String,
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    }
+
+    impl KmsSigner {
+        pub(super) fn from_env() -> Result<Self, SignerError> {
+            let region = std::env::var("AWS_REGION")
+                .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+                .map_err(|_| SignerError::MissingConfig("AWS_REGION"))?;
+            let endpoint = std::env::var("AWS_KMS_ENDPOINT")
+                .unwrap_or_else(|_| format!("https://kms.{}.amazonaws.com", region))
+                .trim_end_matches('/')
+                .to_string();
+            let host = endpoint
+                .split_once("://")
+                .map(|(_, host)| host.to_string())
+                .ok_or_else(|| SignerError::InvalidConfig(format!("Invalid AWS_KMS_ENDPOINT: {}", endpoint)))?;
+
+            Ok(Self {
+                client: reqwest::Client::new(),
+                endpoint,
+                host,
+                region,
+                key_id: required_env("AWS_KMS_KEY_ID")?,
+                access_key_id: required_env("AWS_ACCESS_KEY_ID")?,
+                secret_access_key: required_env("AWS_SECRET_ACCESS_KEY")?,
+                session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+            })
+        }
+
+        /// Call a KMS action (`TrentService.<action>`) with a SigV4-signed request
+        async fn call(&self, action: &str, body: Value) -> Result<Value, SignerError> {
+            let payload = body.to_string();
+            let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+            let target = format!("TrentService.{}", action);
+
+            let mut headers = vec![
+                ("content-type", "application/x-amz-json-1.1".to_string()),
+                ("host", self.host.clone()),
+                ("x-amz-date", amz_date.clone()),
+                ("x-amz-target", target),
+            ];
+            if let Some(token) = &self.session_token {
+                headers.push(("x-amz-security-token", token.clone()));
+            }
+            headers.sort_by_key(|(name, _)| *name);
+
+            let authorization = authorization(
+                &self.access_key_id,
+                &self.secret_access_key,
+                &self.region,
+                &amz_date,
+                &headers,
+                &payload,
+            );
+
+            let mut request = self.client.post(format!("{}/", self.endpoint)).body(payload);
+            for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+                request = request.header(*name, value);
+            }
+            let response = request
+                .header("authorization", authorization)
+                .send()
+                .await
+                .map_err(|e| SignerError::Remote(format!("KMS {} failed: {}", action, e)))?;
+
+            let status = response.status();
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| SignerError::Remote(format!("Invalid KMS {} response: {}", action, e)))?;
+            if !status.is_success() {
+                return Err(SignerError::Remote(format!("KMS {} returned {}: {}", action, status, body)));
+            }
+            Ok(body)
+        }
+
+        fn decode_field(body: &Value, field: &str) -> Result<Vec<u8>, SignerError> {
+            body[field]
+                .as_str()
+                .and_then(|value| STANDARD.decode(value).ok())
+                .ok_or_else(|| SignerError::Remote(format!("KMS response has no valid {}", field)))
+        }
+    }
+
+    #[async_trait]
+    impl DigestSigner for KmsSigner {
+        async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+            let body = self.call("GetPublicKey", json!({ "KeyId": self.key_id })).await?;
+            if body["KeySpec"] != "ECC_SECG_P256K1" {
+                return Err(SignerError::InvalidConfig(format!(
+                    "KMS key {} has key spec {}, expected ECC_SECG_P256K1",
+                    self.key_id, body["KeySpec"]
+                )));
+            }
+            public_key_from_spki(&Self::decode_field(&body, "PublicKey")?)
+        }
+
+        async fn sign_digest(&self, digest: &B256) -> Result<Vec<u8>, SignerError> {
+            let body = self
+                .call(
+                    "Sign",
+                    json!({
+                        "KeyId": self.key_id,
+                        "Message": STANDARD.encode(digest),
+                        "MessageType": "DIGEST",
+                        "SigningAlgorithm": "ECDSA_SHA_256",
+                    }),
+                )
+                .await?;
+            Self::decode_field(&body, "Signature")
+        }
+    }
+
+    fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+
+    /// SigV4 key for one day, region and service
+    fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+        let k_date = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes());
+        let k_region = hmac_sha256(&k_date, region.as_bytes());
+        let k_service = hmac_sha256(&k_region, service.as_bytes());
+        hmac_sha256(&k_service, b"aws4_request")
+    }
+
+    /// SigV4 Authorization header of a POST to `/` with sorted, lowercase `headers`
+    fn authorization(
+        access_key_id: &str,
+        secret_access_key: &str,
+        region: &str,
+        amz_date: &str,
+        headers: &[(&str, String)],
+        payload: &str,
+    ) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/kms/aws4_request", date, region);
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(payload.as_bytes()))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signature = hmac_sha256(&signing_key(secret_access_key, date, region, "kms"), string_to_sign.as_bytes());
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            access_key_id,
+            scope,
+            signed_headers,
+            hex::encode(signature)
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_sigv4_signing_key() {
+            // Example from the AWS SigV4 documentation
+            let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20150830", "us-east-1", "iam");
+            assert_eq!(hex::encode(key), "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9");
+        }
+    }
+}
+
+#[cfg(feature = "vault")]
+mod vault {
+    use alloy::primitives::B256;
+    use async_trait::async_trait;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use k256::ecdsa::VerifyingKey;
+    use serde_json::{json, Value};
+
+    use super::{public_key_from_spki, remote::DigestSigner, required_env, SignerError};
+
+    /// Signs with a Vault transit key
+    pub(super) struct VaultSigner {
+        client: reqwest::Client,
+        addr: String,
+        token: String,
+        namespace: Option<String>,
+        mount: String,
+        key: String,
+    }
+
+    impl VaultSigner {
+        pub(super) fn from_env() -> Result<Self, SignerError> {
+            Ok(Self {
+                client: reqwest::Client::new(),
+                addr: required_env("VAULT_ADDR")?.trim_end_matches('/').to_string(),
+                token: required_env("VAULT_TOKEN")?,
+                namespace: std::env::var("VAULT_NAMESPACE").ok(),
+                mount: std::env::var("VAULT_TRANSIT_MOUNT").unwrap_or_else(|_| "transit".to_string()),
+                key: required_env("VAULT_TRANSIT_KEY")?,
+            })
+        }
+
+        async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value, SignerError> {
+            let mut request = request.header("X-Vault-Token", &self.token);
+            if let Some(namespace) = &self.namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| SignerError::Remote(format!("Vault request failed: {}", e)))?;
+
+            let status = response.status();
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| SignerError::Remote(format!("Invalid Vault response: {}", e)))?;
+            if !status.is_success() {
+                return Err(SignerError::Remote(format!("Vault returned {}: {}", status, body["errors"])));
+            }
+            Ok(body)
+        }
+    }
+
+    #[async_trait]
+    impl DigestSigner for VaultSigner {
+        async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+            let url = format!("{}/v1/{}/keys/{}", self.addr, self.mount, self.key);
+            let body = self.send(self.client.get(url)).await?;
+            let data = &body["data"];
+            let version = data["latest_version"].as_u64().unwrap_or(1).to_string();
+            let pem = data["keys"][&version]["public_key"].as_str().ok_or_else(|| {
+                SignerError::InvalidConfig(format!("Vault key {} ({}) has no public key", self.key, data["type"]))
+            })?;
+            public_key_from_spki(&pem_to_der(pem)?)
+        }
+
+        async fn sign_digest(&self, digest: &B256) -> Result<Vec<u8>, SignerError> {
+            let url = format!("{}/v1/{}/sign/{}", self.addr, self.mount, self.key);
+            let request = self.client.post(url).json(&json!({
+                "input": STANDARD.encode(digest),
+                "prehashed": true,
+                "hash_algorithm": "sha2-256",
+                "marshaling_algorithm": "asn1",
+            }));
+            let body = self.send(request).await?;
+            body["data"]["signature"]
+                .as_str()
+                .and_then(|signature| signature.rsplit(':').next())
+                .and_then(|signature| STANDARD.decode(signature).ok())
+                .ok_or_else(|| SignerError::Remote("Vault response has no valid signature".to_string()))
+        }
+    }
+
+    /// DER body of a PEM public key
+    fn pem_to_der(pem: &str) -> Result<Vec<u8>, SignerError> {
+        let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+        STANDARD
+            .decode(body.trim())
+            .map_err(|e| SignerError::InvalidConfig(format!("Invalid public key PEM: {}", e)))
+    }
+}
+
+#[cfg(any(feature = "aws-kms", feature = "vault"))]
+fn required_env(var: &'static str) -> Result<String, SignerError> {
+    std::env::var(var).map_err(|_| SignerError::MissingConfig(var))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{keccak256, Address, U256};
+    use k256::ecdsa::SigningKey;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[0x42; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_recoverable_signature_recovers_the_signer() {
+        let key = signing_key();
+        let hash = keccak256(b"transaction");
+        let (signature, _) = key.sign_prehash_recoverable(hash.as_slice()).unwrap();
+
+        let recovered = recoverable_signature(&hash, signature.to_der().as_bytes(), key.verifying_key()).unwrap();
+        assert_eq!(recovered.recover_address_from_prehash(&hash).unwrap(), Address::from_public_key(key.verifying_key()));
+    }
+
+    #[test]
+    fn test_recoverable_signature_normalizes_high_s() {
+        let key = signing_key();
+        let hash = keccak256(b"transaction");
+        let (signature, _) = key.sign_prehash_recoverable(hash.as_slice()).unwrap();
+        // A remote signer may return the equally valid high-s twin
+        let high_s = Signature::from_scalars(signature.r(), -*signature.s()).unwrap();
+        assert!(high_s.normalize_s().is_some());
+
+        let recovered = recoverable_signature(&hash, high_s.to_der().as_bytes(), key.verifying_key()).unwrap();
+        assert_eq!(recovered.s(), U256::from_be_slice(&signature.s().to_bytes()));
+        assert_eq!(recovered.recover_address_from_prehash(&hash).unwrap(), Address::from_public_key(key.verifying_key()));
+    }
+
+    #[test]
+    fn test_recoverable_signature_rejects_other_keys() {
+        let hash = keccak256(b"transaction");
+        let (signature, _) = signing_key().sign_prehash_recoverable(hash.as_slice()).unwrap();
+        let other = SigningKey::from_slice(&[0x43; 32]).unwrap();
+
+        assert!(recoverable_signature(&hash, signature.to_der().as_bytes(), other.verifying_key()).is_err());
+        assert!(recoverable_signature(&hash, &[0x30, 0x00], other.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_public_key_from_spki() {
+        let key = signing_key();
+        // SubjectPublicKeyInfo header of an uncompressed secp256k1 point
+        let mut der = hex::decode("3056301006072a8648ce3d020106052b8104000a034200").unwrap();
+        der.extend_from_slice(key.verifying_key().to_encoded_point(false).as_bytes());
+        assert_eq!(public_key_from_spki(&der).unwrap(), *key.verifying_key());
+        assert!(public_key_from_spki(&[0x30, 0x03, 0x02, 0x01, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! On-chain anchoring of snapshot chain heads
+//!
+//! Once a day, the snapshot anchor job posts each index's latest
+//! snapshot_log hash (see services::snapshot_log) to the snapshot registry
+//! contract on Arbitrum, signed by the backend's wallet (see
+//! services::signers), and records the transaction on that entry. Anyone can
+//! then check a snapshot proof against a hash the backend can no longer
+//! change.
+//!
+//! The tx hash is stored as soon as the transaction is sent, before waiting
+//! for its receipt, so a failed wait or crash leaves the entry to be
+//! reconciled by receipt on the next run instead of being anchored twice.
+//! Only a reverted or dropped transaction clears the hash; a head cleared
+//! that way is sent again.
+//!
+//! Configuration (environment):
+//! - `SNAPSHOT_ANCHOR_ENABLED=true` - sends transactions, so off by default
+//! - `SNAPSHOT_REGISTRY_ADDRESS` - the registry contract
+//! - `ARB_RPC_URL` - the network it's on
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use alloy::{
+    network::EthereumWallet,
+    primitives::{Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
+    sol,
+    transports::http::reqwest::Url,
+};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use tracing::{error, info};
+
+use crate::entities::{prelude::*, snapshot_log};
+use crate::services::chain_spend::{self, ChainTransaction};
+
+pub const ENV_ENABLED: &str = "SNAPSHOT_ANCHOR_ENABLED";
+pub const ENV_REGISTRY_ADDRESS: &str = "SNAPSHOT_REGISTRY_ADDRESS";
+
+/// Interval between receipt checks while waiting for an anchor
+const POLL_INTERVAL_MS: u64 = 2000;
+
+/// How long `anchor` waits for the receipt before leaving the transaction
+/// to be reconciled
+const RECEIPT_TIMEOUT_MS: u64 = 60_000;
+
+sol! {
+    #[sol(rpc)]
+    interface ISnapshotRegistry {
+        function anchor(uint256 indexId, uint64 seq, bytes32 headHash) external;
+
+        event Anchored(uint256 indexed indexId, uint64 seq, bytes32 headHash);
+    }
+}
+
+#[derive(Debug)]
+pub enum AnchorError {
+    InvalidConfig(String),
+    ProviderError(String),
+    TransactionError(String),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for AnchorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnchorError::InvalidConfig(msg) => write!(f, "Invalid configuration: {}", msg),
+            AnchorError::ProviderError(msg) => write!(f, "Provider error: {}", msg),
+            AnchorError::TransactionError(msg) => write!(f, "Transaction error: {}", msg),
+            AnchorError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnchorError {}
+
+impl From<sea_orm::DbErr> for AnchorError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        AnchorError::Database(e)
+    }
+}
+
+/// A snapshot_log hash as the registry's bytes32
+pub fn head_bytes(hash: &str) -> Result<B256, AnchorError> {
+    B256::from_str(hash).map_err(|e| AnchorError::InvalidConfig(format!("Invalid snapshot hash {}: {}", hash, e)))
+}
+
+/// State of a sent anchor transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorTxStatus {
+    /// Known to the node but not mined yet
+    Pending,
+    /// Unknown to the node and not mined: it will never be, safe to resend
+    Dropped,
+    /// Mined but reverted, so nothing was anchored
+    Reverted,
+    /// Mined in this block
+    Confirmed(u64),
+}
+
+/// Chain heads not sent to the registry yet, one per index at most
+pub async fn unanchored_heads(db: &DatabaseConnection) -> Result<Vec<snapshot_log::Model>, sea_orm::DbErr> {
+    let mut heads = Vec::new();
+    for index in IndexMetadata::find().all(db).await? {
+        let head = SnapshotLog::find()
+            .filter(snapshot_log::Column::IndexId.eq(index.index_id))
+            .order_by_desc(snapshot_log::Column::Seq)
+            .one(db)
+            .await?;
+        if let Some(head) = head.filter(|head| head.anchor_tx_hash.is_none()) {
+            heads.push(head);
+        }
+    }
+    Ok(heads)
+}
+
+/// Entries whose anchor transaction was sent but not confirmed yet
+pub async fn submitted_anchors(db: &DatabaseConnection) -> Result<Vec<snapshot_log::Model>, sea_orm::DbErr> {
+    SnapshotLog::find()
+        .filter(snapshot_log::Column::AnchorTxHash.is_not_null())
+        .filter(snapshot_log::Column::AnchoredAt.is_null())
+        .order_by_asc(snapshot_log::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Record that `entry_id`'s hash was sent in `tx_hash`, not mined yet
+pub async fn mark_sent(
+    db: &DatabaseConnection,
+    entry_id: i32,
+    tx_hash: &str,
+) -> Result<snapshot_log::Model, sea_orm::DbErr> {
+    snapshot_log::ActiveModel {
+        id: Set(entry_id),
+        anchor_tx_hash: Set(Some(tx_hash.to_string())),
+        anchor_block: Set(None),
+        anchored_at: Set(None),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+}
+
+/// Forget the anchor transaction of an entry that reverted or was dropped
+pub async fn clear_anchor(db: &DatabaseConnection, entry_id: i32) -> Result<snapshot_log::Model, sea_orm::DbErr> {
+    snapshot_log::ActiveModel {
+        id: Set(entry_id),
+        anchor_tx_hash: Set(None),
+        anchor_block: Set(None),
+        anchored_at: Set(None),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+}
+
+pub async fn mark_anchored(
+    db: &DatabaseConnection,
+    entry_id: i32,
+    tx_hash: &str,
+    block_number: u64,
+    now: NaiveDateTime,
+) -> Result<snapshot_log::Model, sea_orm::DbErr> {
+    snapshot_log::ActiveModel {
+        id: Set(entry_id),
+        anchor_tx_hash: Set(Some(tx_hash.to_string())),
+        anchor_block: Set(Some(block_number as i64)),
+        anchored_at: Set(Some(now)),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+}
+
+/// Sends anchor transactions to the snapshot registry
+pub struct SnapshotAnchorService {
+    rpc_url: String,
+    wallet: EthereumWallet,
+    registry: Address,
+    chain_id: u64,
+    db: DatabaseConnection,
+}
+
+impl SnapshotAnchorService {
+    pub async fn new(
+        rpc_url: &str,
+        wallet: EthereumWallet,
+        registry_address: &str,
+        db: DatabaseConnection,
+    ) -> Result<Self, AnchorError> {
+        let registry = Address::from_str(registry_address)
+            .map_err(|e| AnchorError::InvalidConfig(format!("Invalid registry address: {}", e)))?;
+        let provider = ProviderBuilder::new()
+            .on_http(rpc_url.parse().map_err(|e| AnchorError::InvalidConfig(format!("Invalid RPC URL: {}", e)))?);
+        let chain_id = provider
+            .get_chain_id()
+            .await
+            .map_err(|e| AnchorError::ProviderError(format!("Connection failed: {}", e)))?;
+
+        info!(
+            chain_id,
+            registry = %registry,
+            signer = %wallet.default_signer().address(),
+            "Snapshot anchor service initialized"
+        );
+        Ok(Self {
+            rpc_url: rpc_url.to_string(),
+            wallet,
+            registry,
+            chain_id,
+            db,
+        })
+    }
+
+    fn rpc_url(&self) -> Result<Url, AnchorError> {
+        self.rpc_url
+            .parse()
+            .map_err(|e| AnchorError::InvalidConfig(format!("Invalid RPC URL: {}", e)))
+    }
+
+    /// Post `entry`'s hash to the registry, store the tx hash on it and wait
+    /// for the receipt
+    ///
+    /// If the wait fails or times out the transaction stays stored, to be
+    /// settled by `reconcile` later rather than sent again.
+    pub async fn anchor(&self, entry: &snapshot_log::Model, now: NaiveDateTime) -> Result<snapshot_log::Model, AnchorError> {
+        let head_hash = head_bytes(&entry.hash)?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(self.wallet.clone())
+            .on_http(self.rpc_url()?);
+
+        let pending_tx = ISnapshotRegistry::new(self.registry, &provider)
+            .anchor(U256::from(entry.index_id), entry.seq as u64, head_hash)
+            .send()
+            .await
+            .map_err(|e| AnchorError::TransactionError(format!("Send failed: {}", e)))?;
+        let tx_hash = format!("{:?}", pending_tx.tx_hash());
+        info!(index_id = entry.index_id, seq = entry.seq, tx_hash = %tx_hash, "Snapshot anchor sent");
+        let sent = mark_sent(&self.db, entry.id, &tx_hash).await?;
+
+        let start = std::time::Instant::now();
+        while start.elapsed() < Duration::from_millis(RECEIPT_TIMEOUT_MS) {
+            match self.reconcile(&sent, now).await? {
+                AnchorTxStatus::Pending => tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await,
+                AnchorTxStatus::Confirmed(block_number) => {
+                    return Ok(snapshot_log::Model {
+                        anchor_block: Some(block_number as i64),
+                        anchored_at: Some(now),
+                        ..sent
+                    });
+                }
+                AnchorTxStatus::Reverted => {
+                    return Err(AnchorError::TransactionError("Transaction reverted".to_string()));
+                }
+                AnchorTxStatus::Dropped => {
+                    return Err(AnchorError::TransactionError("Transaction dropped".to_string()));
+                }
+            }
+        }
+        Err(AnchorError::TransactionError(format!(
+            "Timeout waiting for anchor transaction {}, it will be reconciled next run",
+            tx_hash
+        )))
+    }
+
+    /// Check the receipt of `entry`'s anchor transaction and settle it:
+    /// confirmed ones are marked anchored, reverted or dropped ones cleared
+    /// so the head is sent again, pending ones left alone
+    ///
+    /// Mined transactions have their gas spend recorded.
+    pub async fn reconcile(
+        &self,
+        entry: &snapshot_log::Model,
+        now: NaiveDateTime,
+    ) -> Result<AnchorTxStatus, AnchorError> {
+        let Some(tx_hash) = entry.anchor_tx_hash.as_deref() else {
+            return Err(AnchorError::InvalidConfig(format!("Snapshot entry {} was not sent", entry.id)));
+        };
+        let hash = B256::from_str(tx_hash)
+            .map_err(|e| AnchorError::InvalidConfig(format!("Invalid tx hash '{}': {}", tx_hash, e)))?;
+        let provider = ProviderBuilder::new().on_http(self.rpc_url()?);
+
+        let receipt = provider
+            .get_transaction_receipt(hash)
+            .await
+            .map_err(|e| AnchorError::ProviderError(format!("Failed to get receipt: {}", e)))?;
+        let Some(receipt) = receipt else {
+            // Not mined: either still in the mempool or dropped from it
+            let known = provider
+                .get_transaction_by_hash(hash)
+                .await
+                .map_err(|e| AnchorError::ProviderError(format!("Failed to get transaction: {}", e)))?;
+            if known.is_some() {
+                return Ok(AnchorTxStatus::Pending);
+            }
+            clear_anchor(&self.db, entry.id).await?;
+            return Ok(AnchorTxStatus::Dropped);
+        };
+
+        // Already paid for, so a failure to record the spend is only logged
+        let tx = ChainTransaction::from_receipt(
+            chain_spend::kinds::SNAPSHOT_ANCHOR,
+            Some(format!("index:{}", entry.index_id)),
+            self.chain_id,
+            &receipt,
+        );
+        if let Err(e) = chain_spend::record(&self.db, tx, now).await {
+            error!(tx_hash = %tx_hash, error = %e, "Failed to record transaction spend");
+        }
+        if !receipt.status() {
+            clear_anchor(&self.db, entry.id).await?;
+            return Ok(AnchorTxStatus::Reverted);
+        }
+
+        let block_number = receipt.block_number.unwrap_or(0);
+        mark_anchored(&self.db, entry.id, tx_hash, block_number, now).await?;
+        Ok(AnchorTxStatus::Confirmed(block_number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_bytes() {
+        let hash = "ab".repeat(32);
+        assert_eq!(head_bytes(&hash).unwrap(), B256::repeat_byte(0xab));
+        assert!(head_bytes("abcd").is_err());
+    }
+}
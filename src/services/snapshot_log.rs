@@ -0,0 +1,321 @@
+//! Hash-chained snapshots of published index history
+//!
+//! With SNAPSHOT_LOG_ENABLED=true, the snapshot log job appends every
+//! rebalance and daily NAV record of each index to snapshot_log, once. Each
+//! entry keeps the record's normalized JSON (keys sorted, decimals as
+//! normalized strings, no whitespace) and chains it to the previous entry
+//! of the same index:
+//!
+//! ```text
+//! payload_hash = keccak256(normalized_json)
+//! hash         = keccak256(prev_hash ‖ payload_hash)   // prev_hash omitted for the first entry
+//! ```
+//!
+//! so anyone holding a chain head can check that an older record, or the log
+//! itself, hasn't been rewritten since. `proof` serves the entries of one
+//! day with the payload hashes that lead from them to the current head, and
+//! to the first head posted on-chain after them (see
+//! services::snapshot_anchor).
+
+use std::collections::HashSet;
+
+use alloy::primitives::keccak256;
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
+use serde_json::{json, Value};
+
+use crate::entities::{daily_prices, prelude::*, rebalances, snapshot_log, token_migrations};
+use crate::models::snapshot::{SnapshotAnchor, SnapshotEntry, SnapshotProofResponse};
+use crate::services::coin_merge;
+use crate::services::pricing_time;
+use crate::services::rebalance_schema;
+
+pub const ENV_ENABLED: &str = "SNAPSHOT_LOG_ENABLED";
+
+pub mod kinds {
+    pub const REBALANCE: &str = "rebalance";
+    pub const DAILY_PRICE: &str = "daily_price";
+}
+
+pub fn enabled_from_env() -> bool {
+    std::env::var(ENV_ENABLED).is_ok_and(|v| v == "true")
+}
+
+/// `value` as compact JSON with object keys sorted at every level
+pub fn normalized_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), normalized_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => format!("[{}]", items.iter().map(normalized_json).collect::<Vec<_>>().join(",")),
+        other => other.to_string(),
+    }
+}
+
+pub fn payload_hash(payload: &Value) -> String {
+    hex::encode(keccak256(normalized_json(payload).as_bytes()))
+}
+
+/// Hash of an entry from its predecessor's hash and its payload hash
+pub fn chain_hash(prev_hash: Option<&str>, payload_hash: &str) -> Result<String, hex::FromHexError> {
+    let mut bytes = match prev_hash {
+        Some(prev) => hex::decode(prev)?,
+        None => Vec::new(),
+    };
+    bytes.extend(hex::decode(payload_hash)?);
+    Ok(hex::encode(keccak256(&bytes)))
+}
+
+/// A record to log: (date, record key, kind, payload)
+type Record = (NaiveDate, String, &'static str, Value);
+
+fn rebalance_record(rebalance: &rebalances::Model) -> Option<Record> {
+    let date = pricing_time::rebalance_date(rebalance.timestamp)?;
+    let payload = json!({
+        "index_id": rebalance.index_id,
+        "timestamp": rebalance.timestamp,
+        "rebalance_type": rebalance.rebalance_type,
+        "portfolio_value": rebalance.portfolio_value.normalize().to_string(),
+        "total_weight": rebalance.total_weight.normalize().to_string(),
+        "coins": rebalance.coins,
+    });
+    Some((date, format!("{}:{}", kinds::REBALANCE, rebalance.timestamp), kinds::REBALANCE, payload))
+}
+
+fn daily_price_record(price: &daily_prices::Model) -> Record {
+    let payload = json!({
+        "index_id": price.index_id,
+        "date": price.date.to_string(),
+        "price": price.price.normalize().to_string(),
+        "quantities": price.quantities,
+    });
+    (price.date, format!("{}:{}", kinds::DAILY_PRICE, price.date), kinds::DAILY_PRICE, payload)
+}
+
+/// Normalized JSON of a record for comparing a logged payload with the
+/// current one
+///
+/// Rebalance coins are brought to the current schema version first, so
+/// rows rewritten by rebalance_schema::upgrade_stored still match what was
+/// logged before the upgrade, and coins merged since (`merges`, oldest
+/// first, as recorded in token_migrations) are renamed as coin_merge
+/// renamed them in rebalances.
+fn comparable_json(kind: &str, payload: &Value, merges: &[token_migrations::Model]) -> String {
+    let mut payload = payload.clone();
+    if kind == kinds::REBALANCE
+        && let Some(coins) = payload.get_mut("coins")
+        && let Ok(mut upgraded) = rebalance_schema::upgrade(coins.clone())
+    {
+        for merge in merges {
+            coin_merge::replace_coin_id(&mut upgraded, &merge.source_coin_id, &merge.target_coin_id);
+        }
+        *coins = upgraded;
+    }
+    normalized_json(&payload)
+}
+
+/// Current records of an index, oldest first
+async fn records(db: &DatabaseConnection, index_id: i32) -> Result<Vec<Record>, sea_orm::DbErr> {
+    let mut records: Vec<Record> = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(index_id))
+        .all(db)
+        .await?
+        .iter()
+        .filter_map(rebalance_record)
+        .collect();
+    records.extend(
+        DailyPrices::find()
+            .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+            .all(db)
+            .await?
+            .iter()
+            .map(daily_price_record),
+    );
+    records.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    Ok(records)
+}
+
+/// Append the records of an index not logged yet; returns how many
+pub async fn append_pending(
+    db: &DatabaseConnection,
+    index_id: i32,
+    now: NaiveDateTime,
+) -> Result<usize, sea_orm::DbErr> {
+    let logged: HashSet<String> = SnapshotLog::find()
+        .select_only()
+        .column(snapshot_log::Column::RecordKey)
+        .filter(snapshot_log::Column::IndexId.eq(index_id))
+        .into_tuple::<String>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    let pending: Vec<Record> = records(db, index_id)
+        .await?
+        .into_iter()
+        .filter(|(_, key, _, _)| !logged.contains(key))
+        .collect();
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    // The (index_id, seq) unique index rejects a concurrent writer's fork
+    let txn = db.begin().await?;
+    let head = SnapshotLog::find()
+        .filter(snapshot_log::Column::IndexId.eq(index_id))
+        .order_by_desc(snapshot_log::Column::Seq)
+        .one(&txn)
+        .await?;
+    let (mut seq, mut prev_hash) = head.map_or((0, None), |head| (head.seq, Some(head.hash)));
+
+    for (record_date, record_key, kind, payload) in &pending {
+        let payload_hash = payload_hash(payload);
+        let hash = chain_hash(prev_hash.as_deref(), &payload_hash)
+            .map_err(|e| sea_orm::DbErr::Custom(format!("Corrupt snapshot hash: {}", e)))?;
+        seq += 1;
+        snapshot_log::ActiveModel {
+            index_id: Set(index_id),
+            seq: Set(seq),
+            kind: Set(kind.to_string()),
+            record_key: Set(record_key.clone()),
+            record_date: Set(*record_date),
+            payload: Set(payload.clone()),
+            payload_hash: Set(payload_hash),
+            prev_hash: Set(prev_hash.clone()),
+            hash: Set(hash.clone()),
+            created_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        prev_hash = Some(hash);
+    }
+    txn.commit().await?;
+
+    Ok(pending.len())
+}
+
+/// Append pending records of every index; returns how many
+pub async fn append_all(db: &DatabaseConnection, now: NaiveDateTime) -> Result<usize, sea_orm::DbErr> {
+    let mut appended = 0;
+    for index in IndexMetadata::find().all(db).await? {
+        appended += append_pending(db, index.index_id, now).await?;
+    }
+    Ok(appended)
+}
+
+/// Entries of an index logged for `date`, with the payload hashes leading
+/// from the last of them to the head, and whether the stored chain and the
+/// current records still match; None when nothing was logged for `date`
+pub async fn proof(
+    db: &DatabaseConnection,
+    index_id: i32,
+    date: NaiveDate,
+) -> Result<Option<SnapshotProofResponse>, sea_orm::DbErr> {
+    let chain = SnapshotLog::find()
+        .filter(snapshot_log::Column::IndexId.eq(index_id))
+        .order_by_asc(snapshot_log::Column::Seq)
+        .all(db)
+        .await?;
+    let Some(last_on_date) = chain.iter().rposition(|entry| entry.record_date == date) else {
+        return Ok(None);
+    };
+
+    // Recompute the chain from its first entry
+    let mut chain_valid = true;
+    let mut prev_hash: Option<&str> = None;
+    for (position, entry) in chain.iter().enumerate() {
+        let recomputed = chain_hash(prev_hash, &payload_hash(&entry.payload)).ok();
+        if entry.seq != position as i32 + 1
+            || entry.prev_hash.as_deref() != prev_hash
+            || entry.payload_hash != payload_hash(&entry.payload)
+            || recomputed.as_deref() != Some(entry.hash.as_str())
+        {
+            chain_valid = false;
+            break;
+        }
+        prev_hash = Some(&entry.hash);
+    }
+
+    let merges = TokenMigrations::find()
+        .order_by_asc(token_migrations::Column::MergedAt)
+        .order_by_asc(token_migrations::Column::Id)
+        .all(db)
+        .await?;
+    let current: Vec<Record> = records(db, index_id)
+        .await?
+        .into_iter()
+        .filter(|(record_date, _, _, _)| *record_date == date)
+        .collect();
+    let entries = chain
+        .iter()
+        .filter(|entry| entry.record_date == date)
+        .map(|entry| SnapshotEntry {
+            seq: entry.seq,
+            kind: entry.kind.clone(),
+            record_key: entry.record_key.clone(),
+            payload: entry.payload.clone(),
+            payload_hash: entry.payload_hash.clone(),
+            prev_hash: entry.prev_hash.clone(),
+            hash: entry.hash.clone(),
+            matches_current: current.iter().any(|(_, key, kind, payload)| {
+                *key == entry.record_key
+                    && comparable_json(kind, payload, &merges) == comparable_json(kind, &entry.payload, &merges)
+            }),
+        })
+        .collect();
+    let head = chain.last().expect("chain has the entry found above");
+    // Anchors sent but not confirmed yet don't count
+    let anchor = chain[last_on_date..].iter().filter(|entry| entry.anchored_at.is_some()).find_map(|entry| {
+        Some(SnapshotAnchor {
+            seq: entry.seq,
+            hash: entry.hash.clone(),
+            tx_hash: entry.anchor_tx_hash.clone()?,
+            block_number: entry.anchor_block,
+            anchored_at: entry.anchored_at,
+        })
+    });
+
+    Ok(Some(SnapshotProofResponse {
+        index_id,
+        date,
+        entries,
+        path: chain[last_on_date + 1..].iter().map(|entry| entry.payload_hash.clone()).collect(),
+        head_seq: head.seq,
+        head_hash: head.hash.clone(),
+        chain_valid,
+        anchor,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_json_sorts_keys() {
+        let value = json!({ "b": [1, { "y": "2", "x": null }], "a": 1.5 });
+        assert_eq!(normalized_json(&value), r#"{"a":1.5,"b":[1,{"x":null,"y":"2"}]}"#);
+    }
+
+    #[test]
+    fn test_chain_hash() {
+        let first = payload_hash(&json!({ "price": "100" }));
+        let genesis = chain_hash(None, &first).unwrap();
+        assert_eq!(genesis, hex::encode(keccak256(hex::decode(&first).unwrap())));
+
+        let second = payload_hash(&json!({ "price": "101" }));
+        let next = chain_hash(Some(&genesis), &second).unwrap();
+        assert_ne!(next, chain_hash(None, &second).unwrap());
+        assert!(chain_hash(Some("not hex"), &second).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Solver registry
+//!
+//! Solvers (the execution agents that fill ITP orders) sign in with their
+//! wallet, register the assets and exchanges they support, and send
+//! heartbeats. A solver is online while its last heartbeat is within the
+//! timeout. Registering again from the same wallet updates the registration.
+//!
+//! Configuration (environment):
+//! - `SOLVER_HEARTBEAT_TIMEOUT_SECS` - heartbeat age after which a solver is offline (default 120)
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, Set,
+};
+
+use crate::entities::{prelude::*, solvers};
+use crate::models::solver::{RegisterSolverRequest, SolverResponse, SolversQuery};
+
+const ENV_HEARTBEAT_TIMEOUT_SECS: &str = "SOLVER_HEARTBEAT_TIMEOUT_SECS";
+
+const DEFAULT_HEARTBEAT_TIMEOUT_SECS: i64 = 120;
+
+const MAX_NAME_LEN: usize = 64;
+const MAX_VERSION_LEN: usize = 32;
+const MAX_ASSETS: usize = 500;
+const MAX_EXCHANGES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverConfig {
+    pub heartbeat_timeout_secs: i64,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self { heartbeat_timeout_secs: DEFAULT_HEARTBEAT_TIMEOUT_SECS }
+    }
+}
+
+impl SolverConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(std::env::var(ENV_HEARTBEAT_TIMEOUT_SECS).ok().as_deref())
+    }
+
+    /// Build from raw setting values; invalid values fall back to the defaults
+    fn from_values(heartbeat_timeout_secs: Option<&str>) -> Self {
+        match heartbeat_timeout_secs.map(|v| v.trim().parse::<i64>()) {
+            Some(Ok(v)) if v > 0 => Self { heartbeat_timeout_secs: v },
+            Some(_) => {
+                tracing::warn!("Invalid {}, using {}", ENV_HEARTBEAT_TIMEOUT_SECS, DEFAULT_HEARTBEAT_TIMEOUT_SECS);
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SolverError {
+    InvalidRegistration(String),
+    NotFound(i32),
+    /// The solver is registered to another wallet
+    NotOwner(i32),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolverError::InvalidRegistration(msg) => write!(f, "{}", msg),
+            SolverError::NotFound(id) => write!(f, "Solver {} not found", id),
+            SolverError::NotOwner(id) => write!(f, "Solver {} is registered to another wallet", id),
+            SolverError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+impl From<sea_orm::DbErr> for SolverError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        SolverError::Database(e)
+    }
+}
+
+/// Trimmed, deduplicated, non-empty values in first-seen order
+fn normalize_list(
+    values: &[String],
+    field: &str,
+    max: usize,
+    normalize: fn(&str) -> String,
+) -> Result<Vec<String>, SolverError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for value in values.iter().map(|v| normalize(v.trim())) {
+        let valid = !value.is_empty()
+            && value.len() <= 32
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(SolverError::InvalidRegistration(format!("Invalid {} entry '{}'", field, value)));
+        }
+        if !normalized.contains(&value) {
+            normalized.push(value);
+        }
+    }
+    if normalized.is_empty() {
+        return Err(SolverError::InvalidRegistration(format!("{} must not be empty", field)));
+    }
+    if normalized.len() > max {
+        return Err(SolverError::InvalidRegistration(format!("At most {} {} are allowed", max, field)));
+    }
+    Ok(normalized)
+}
+
+/// A registration request, checked and normalized
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    pub name: String,
+    /// Uppercase symbols
+    pub assets: Vec<String>,
+    /// Lowercase exchange names
+    pub exchanges: Vec<String>,
+    pub endpoint: Option<String>,
+    pub version: Option<String>,
+}
+
+pub fn validate_registration(request: &RegisterSolverRequest) -> Result<Registration, SolverError> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(SolverError::InvalidRegistration(format!(
+            "name must be 1 to {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+
+    let endpoint = request.endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty());
+    if let Some(endpoint) = endpoint.filter(|e| !e.starts_with("https://") && !e.starts_with("http://")) {
+        return Err(SolverError::InvalidRegistration(format!(
+            "endpoint '{}' must be an http(s) URL",
+            endpoint
+        )));
+    }
+
+    let version = request.version.as_deref().map(str::trim).filter(|v| !v.is_empty());
+    if version.is_some_and(|v| v.len() > MAX_VERSION_LEN) {
+        return Err(SolverError::InvalidRegistration(format!(
+            "version must be at most {} characters",
+            MAX_VERSION_LEN
+        )));
+    }
+
+    Ok(Registration {
+        name,
+        assets: normalize_list(&request.assets, "assets", MAX_ASSETS, str::to_uppercase)?,
+        exchanges: normalize_list(&request.exchanges, "exchanges", MAX_EXCHANGES, str::to_lowercase)?,
+        endpoint: endpoint.map(str::to_string),
+        version: version.map(str::to_string),
+    })
+}
+
+fn to_response(solver: solvers::Model, config: &SolverConfig, now: NaiveDateTime) -> SolverResponse {
+    let online = solver
+        .last_heartbeat_at
+        .is_some_and(|at| now - at <= Duration::seconds(config.heartbeat_timeout_secs));
+    SolverResponse {
+        id: solver.id,
+        address: solver.address,
+        name: solver.name,
+        assets: serde_json::from_value(solver.assets).unwrap_or_default(),
+        exchanges: serde_json::from_value(solver.exchanges).unwrap_or_default(),
+        endpoint: solver.endpoint,
+        version: solver.version,
+        registered_at: solver.registered_at,
+        last_heartbeat_at: solver.last_heartbeat_at,
+        online,
+    }
+}
+
+/// Register the solver of `wallet`, or update its registration
+///
+/// Registering counts as a heartbeat.
+pub async fn register(
+    db: &DatabaseConnection,
+    config: &SolverConfig,
+    wallet: &str,
+    request: &RegisterSolverRequest,
+) -> Result<SolverResponse, SolverError> {
+    let registration = validate_registration(request)?;
+    let address = wallet.to_lowercase();
+    let now = Utc::now().naive_utc();
+
+    let solver = solvers::ActiveModel {
+        address: Set(address.clone()),
+        name: Set(registration.name),
+        assets: Set(serde_json::json!(registration.assets)),
+        exchanges: Set(serde_json::json!(registration.exchanges)),
+        endpoint: Set(registration.endpoint),
+        version: Set(registration.version),
+        registered_at: Set(now),
+        updated_at: Set(now),
+        last_heartbeat_at: Set(Some(now)),
+        ..Default::default()
+    };
+
+    Solvers::insert(solver)
+        .on_conflict(
+            OnConflict::column(solvers::Column::Address)
+                .update_columns([
+                    solvers::Column::Name,
+                    solvers::Column::Assets,
+                    solvers::Column::Exchanges,
+                    solvers::Column::Endpoint,
+                    solvers::Column::Version,
+                    solvers::Column::UpdatedAt,
+                    solvers::Column::LastHeartbeatAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    let solver = Solvers::find()
+        .filter(solvers::Column::Address.eq(address))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            SolverError::Database(sea_orm::DbErr::RecordNotFound("solver missing after upsert".to_string()))
+        })?;
+    Ok(to_response(solver, config, now))
+}
+
+/// Record a heartbeat of solver `id`, which must belong to `wallet`
+pub async fn heartbeat(
+    db: &DatabaseConnection,
+    config: &SolverConfig,
+    id: i32,
+    wallet: &str,
+) -> Result<SolverResponse, SolverError> {
+    let solver = Solvers::find_by_id(id).one(db).await?.ok_or(SolverError::NotFound(id))?;
+    if !solver.address.eq_ignore_ascii_case(wallet) {
+        return Err(SolverError::NotOwner(id));
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut update = solver.into_active_model();
+    update.last_heartbeat_at = Set(Some(now));
+    let solver = update.update(db).await?;
+    Ok(to_response(solver, config, now))
+}
+
+/// Registered solvers matching `query`, online first, then by name
+pub async fn list(
+    db: &DatabaseConnection,
+    config: &SolverConfig,
+    query: &SolversQuery,
+) -> Result<Vec<SolverResponse>, sea_orm::DbErr> {
+    let now = Utc::now().naive_utc();
+    let asset = query.asset.as_deref().map(|a| a.trim().to_uppercase());
+    let exchange = query.exchange.as_deref().map(|e| e.trim().to_lowercase());
+
+    let mut solvers: Vec<SolverResponse> = Solvers::find()
+        .order_by_asc(solvers::Column::Name)
+        .order_by_asc(solvers::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|solver| to_response(solver, config, now))
+        .filter(|s| asset.as_ref().is_none_or(|a| s.assets.contains(a)))
+        .filter(|s| exchange.as_ref().is_none_or(|e| s.exchanges.contains(e)))
+        .filter(|s| query.online.is_none_or(|online| s.online == online))
+        .collect();
+    solvers.sort_by_key(|s| !s.online);
+    Ok(solvers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(assets: &[&str], exchanges: &[&str]) -> RegisterSolverRequest {
+        RegisterSolverRequest {
+            name: " Solver One ".to_string(),
+            assets: assets.iter().map(|a| a.to_string()).collect(),
+            exchanges: exchanges.iter().map(|e| e.to_string()).collect(),
+            endpoint: None,
+            version: Some(" 1.2.0 ".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_config_from_values() {
+        assert_eq!(SolverConfig::from_values(None), SolverConfig::default());
+        assert_eq!(SolverConfig::from_values(Some("30")).heartbeat_timeout_secs, 30);
+        assert_eq!(SolverConfig::from_values(Some("-1")), SolverConfig::default());
+    }
+
+    #[test]
+    fn test_registration_is_normalized() {
+        let registration = validate_registration(&request(&["btc", " ETH", "BTC"], &["Bitget", "bitget"])).unwrap();
+        assert_eq!(registration.name, "Solver One");
+        assert_eq!(registration.assets, vec!["BTC", "ETH"]);
+        assert_eq!(registration.exchanges, vec!["bitget"]);
+        assert_eq!(registration.version.as_deref(), Some("1.2.0"));
+    }
+
+    #[test]
+    fn test_invalid_registrations() {
+        assert!(validate_registration(&request(&[], &["bitget"])).is_err());
+        assert!(validate_registration(&request(&["BTC"], &[" "])).is_err());
+        assert!(validate_registration(&request(&["BTC/USDT"], &["bitget"])).is_err());
+
+        let mut bad_endpoint = request(&["BTC"], &["bitget"]);
+        bad_endpoint.endpoint = Some("ftp://solver".to_string());
+        assert!(validate_registration(&bad_endpoint).is_err());
+
+        let mut no_name = request(&["BTC"], &["bitget"]);
+        no_name.name = "  ".to_string();
+        assert!(validate_registration(&no_name).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Downsampled index price series for list-view charts
+//!
+//! Sparklines keep a chart's shape with a few dozen points using
+//! Largest-Triangle-Three-Buckets (Steinarsson, 2013): the first and last
+//! points are kept and each bucket in between contributes the point forming
+//! the largest triangle with the previously kept point and the next bucket's
+//! average, so peaks and troughs survive where plain striding would drop them.
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::entities::{daily_prices, prelude::*};
+use crate::services::pricing_time;
+
+/// Daily prices of an index over the `days` up to `end`, as (cutoff
+/// timestamp, price), downsampled to at most `points`
+pub async fn index_sparkline(
+    db: &DatabaseConnection,
+    index_id: i32,
+    end: NaiveDate,
+    days: i64,
+    points: usize,
+) -> Result<Vec<(i64, f64)>, sea_orm::DbErr> {
+    let prices = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(index_id.to_string()))
+        .filter(daily_prices::Column::Date.gt(end - Duration::days(days)))
+        .filter(daily_prices::Column::Date.lte(end))
+        .order_by_asc(daily_prices::Column::Date)
+        .all(db)
+        .await?;
+    let series: Vec<(i64, f64)> = prices
+        .into_iter()
+        .filter_map(|row| Some((pricing_time::cutoff_timestamp(row.date), row.price.to_f64()?)))
+        .collect();
+    Ok(lttb(&series, points))
+}
+
+/// Downsample `data` (sorted by x) to `threshold` points with LTTB
+///
+/// Returned as is when it has no more points than `threshold`, or when the
+/// threshold is under 3 (first, last and one bucket).
+pub fn lttb(data: &[(i64, f64)], threshold: usize) -> Vec<(i64, f64)> {
+    if threshold < 3 || data.len() <= threshold {
+        return data.to_vec();
+    }
+
+    let bucket = |i: usize| -> usize {
+        let every = (data.len() - 2) as f64 / (threshold - 2) as f64;
+        ((i as f64 * every) as usize + 1).min(data.len() - 1)
+    };
+
+    let mut sampled = Vec::with_capacity(threshold);
+    let mut kept = 0;
+    sampled.push(data[0]);
+    for i in 0..threshold - 2 {
+        // Average of the next bucket (the last point for the final one)
+        let (next_start, next_end) = (bucket(i + 1), bucket(i + 2).max(bucket(i + 1) + 1).min(data.len()));
+        let next = &data[next_start..next_end];
+        let avg_x = next.iter().map(|(x, _)| *x as f64).sum::<f64>() / next.len() as f64;
+        let avg_y = next.iter().map(|(_, y)| y).sum::<f64>() / next.len() as f64;
+
+        let (kept_x, kept_y) = (data[kept].0 as f64, data[kept].1);
+        let mut max_area = -1.0;
+        for (j, (x, y)) in data.iter().enumerate().take(bucket(i + 1)).skip(bucket(i)) {
+            let (x, y) = (*x as f64, *y);
+            let area = ((kept_x - avg_x) * (y - kept_y) - (kept_x - x) * (avg_y - kept_y)).abs();
+            if area > max_area {
+                max_area = area;
+                kept = j;
+            }
+        }
+        sampled.push(data[kept]);
+    }
+    sampled.push(data[data.len() - 1]);
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lttb() {
+        let data: Vec<(i64, f64)> = (0..100).map(|x| (x, if x == 42 { 50.0 } else { (x % 5) as f64 })).collect();
+
+        let sampled = lttb(&data, 10);
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled[0], data[0]);
+        assert_eq!(sampled[9], data[99]);
+        assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+        // The spike survives
+        assert!(sampled.contains(&(42, 50.0)));
+
+        assert_eq!(lttb(&data[..5], 10), data[..5].to_vec());
+        assert_eq!(lttb(&data, 2), data);
+        assert!(lttb(&[], 10).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Observed exchange spreads for the rebalance fee model
+//!
+//! Rebalance fees charge half of index_metadata.exchange_avg_spread on the
+//! traded value (see services::rebalance_math), a figure set once when the
+//! index is created. The spread sampling job records, once a day, the
+//! top-of-book spread of every pair held by an index's latest rebalance in
+//! spread_observations. An index with use_observed_spread set is then
+//! charged the trailing average of its pairs' observations over
+//! `TRAILING_DAYS` instead; until it has `MIN_OBSERVATIONS` of them (and for
+//! backfilled rebalances before sampling started) the static figure applies.
+
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+
+use crate::entities::{index_metadata, prelude::*, rebalances, spread_observations};
+use crate::services::exchange_api::{ExchangeApiService, OrderBookDepth};
+use crate::services::rebalance_schema;
+use crate::services::rebalancing::CoinRebalanceInfo;
+
+/// Days of observations averaged
+pub const TRAILING_DAYS: i64 = 30;
+
+/// Observations needed before they replace the static spread
+pub const MIN_OBSERVATIONS: usize = 5;
+
+/// Order book levels fetched; only the top one is used
+const BOOK_LEVELS: usize = 5;
+
+/// Decimal places of stored spreads
+const SPREAD_DP: u32 = 8;
+
+/// A pair held by an index, as its rebalance records it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HeldPair {
+    pub exchange: String,
+    pub symbol: String,
+    /// Quote asset, e.g. "usdc"
+    pub quote: String,
+    pub coin_id: String,
+}
+
+impl HeldPair {
+    /// Pair name as exchanges (and spread_observations) spell it, e.g. "BTCUSDC"
+    pub fn trading_pair(&self) -> String {
+        format!("{}{}", self.symbol.to_uppercase(), self.quote.to_uppercase())
+    }
+}
+
+/// Best bid, best ask and their spread relative to the mid price; None for
+/// an empty or crossed book
+pub fn top_of_book_spread(book: &OrderBookDepth) -> Option<(Decimal, Decimal, Decimal)> {
+    let (bid, _) = *book.bids.first()?;
+    let (ask, _) = *book.asks.first()?;
+    if !(bid > 0.0 && ask >= bid) {
+        return None;
+    }
+    let bid = Decimal::from_f64_retain(bid)?;
+    let ask = Decimal::from_f64_retain(ask)?;
+    let mid = (bid + ask) / Decimal::TWO;
+    Some((bid, ask, ((ask - bid) / mid).round_dp(SPREAD_DP)))
+}
+
+/// Pairs of `index_id`'s latest rebalance, excluding the cash buffer
+pub async fn held_pairs(db: &DatabaseConnection, index_id: i32) -> Result<Vec<HeldPair>, DbErr> {
+    let latest = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(index_id))
+        .order_by_desc(rebalances::Column::Timestamp)
+        .one(db)
+        .await?;
+    let Some(latest) = latest else {
+        return Ok(Vec::new());
+    };
+
+    let coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(latest.coins).unwrap_or_else(|e| {
+        tracing::warn!("Unreadable coins in rebalance {}: {}", latest.id, e);
+        Vec::new()
+    });
+    Ok(coins
+        .into_iter()
+        .filter(|coin| !coin.exchange.is_empty() && !coin.trading_pair.is_empty())
+        .map(|coin| HeldPair {
+            exchange: coin.exchange.to_lowercase(),
+            symbol: coin.symbol,
+            quote: coin.trading_pair,
+            coin_id: coin.coin_id,
+        })
+        .collect())
+}
+
+/// Sample the spread of every pair the indexes hold, once per pair and day;
+/// returns the number of pairs recorded. A pair whose book can't be fetched
+/// is skipped and retried on the next run.
+pub async fn sample_all(
+    db: &DatabaseConnection,
+    exchange_api: &ExchangeApiService,
+    now: NaiveDateTime,
+) -> Result<usize, DbErr> {
+    let index_ids: Vec<i32> = IndexMetadata::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|index| index.index_id)
+        .collect();
+    let mut pairs = BTreeSet::new();
+    for index_id in index_ids {
+        pairs.extend(held_pairs(db, index_id).await?);
+    }
+
+    let date = now.date();
+    let sampled: HashSet<(String, String)> = SpreadObservations::find()
+        .filter(spread_observations::Column::Date.eq(date))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|o| (o.exchange, o.trading_pair))
+        .collect();
+
+    let mut recorded = 0;
+    for pair in pairs {
+        let trading_pair = pair.trading_pair();
+        if sampled.contains(&(pair.exchange.clone(), trading_pair.clone())) {
+            continue;
+        }
+        let book = match exchange_api.get_order_book(&pair.exchange, &pair.symbol, &pair.quote, BOOK_LEVELS).await {
+            Ok(book) => book,
+            Err(e) => {
+                tracing::warn!("Order book of {} on {} unavailable: {}", trading_pair, pair.exchange, e);
+                continue;
+            }
+        };
+        let Some((best_bid, best_ask, spread)) = top_of_book_spread(&book) else {
+            tracing::warn!("No usable top of book for {} on {}", trading_pair, pair.exchange);
+            continue;
+        };
+
+        record(db, &pair, date, best_bid, best_ask, spread, now).await?;
+        recorded += 1;
+    }
+    Ok(recorded)
+}
+
+/// Store one observation, replacing that pair's observation of the day
+pub async fn record(
+    db: &DatabaseConnection,
+    pair: &HeldPair,
+    date: NaiveDate,
+    best_bid: Decimal,
+    best_ask: Decimal,
+    spread: Decimal,
+    now: NaiveDateTime,
+) -> Result<(), DbErr> {
+    let observation = spread_observations::ActiveModel {
+        exchange: Set(pair.exchange.clone()),
+        trading_pair: Set(pair.trading_pair()),
+        coin_id: Set(pair.coin_id.clone()),
+        date: Set(date),
+        best_bid: Set(best_bid),
+        best_ask: Set(best_ask),
+        spread: Set(spread),
+        observed_at: Set(now),
+        ..Default::default()
+    };
+    SpreadObservations::insert(observation)
+        .on_conflict(
+            OnConflict::columns([
+                spread_observations::Column::Exchange,
+                spread_observations::Column::TradingPair,
+                spread_observations::Column::Date,
+            ])
+            .update_columns([
+                spread_observations::Column::BestBid,
+                spread_observations::Column::BestAsk,
+                spread_observations::Column::Spread,
+                spread_observations::Column::ObservedAt,
+            ])
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    Ok(())
+}
+
+/// Mean spread observed for `pairs` over the `TRAILING_DAYS` before `date`;
+/// None with fewer than `MIN_OBSERVATIONS`
+pub async fn trailing_spread(
+    db: &DatabaseConnection,
+    pairs: &[HeldPair],
+    date: NaiveDate,
+) -> Result<Option<Decimal>, DbErr> {
+    if pairs.is_empty() {
+        return Ok(None);
+    }
+    let mut held = Condition::any();
+    for pair in pairs {
+        held = held.add(
+            Condition::all()
+                .add(spread_observations::Column::Exchange.eq(pair.exchange.clone()))
+                .add(spread_observations::Column::TradingPair.eq(pair.trading_pair())),
+        );
+    }
+
+    let spreads: Vec<Decimal> = SpreadObservations::find()
+        .filter(held)
+        .filter(spread_observations::Column::Date.gte(date - Duration::days(TRAILING_DAYS)))
+        .filter(spread_observations::Column::Date.lt(date))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|o| o.spread)
+        .collect();
+
+    if spreads.len() < MIN_OBSERVATIONS {
+        return Ok(None);
+    }
+    let mean = spreads.iter().sum::<Decimal>() / Decimal::from(spreads.len());
+    Ok(Some(mean.round_dp(SPREAD_DP)))
+}
+
+/// Spread the fee model charges `index` for a rebalance on `date`: the
+/// trailing observed average when the index opted in and enough
+/// observations exist, exchange_avg_spread otherwise
+pub async fn spread_for(
+    db: &DatabaseConnection,
+    index: &index_metadata::Model,
+    date: NaiveDate,
+) -> Result<Option<Decimal>, DbErr> {
+    if index.use_observed_spread {
+        let pairs = held_pairs(db, index.index_id).await?;
+        if let Some(observed) = trailing_spread(db, &pairs, date).await? {
+            tracing::info!("Index {} rebalance on {} uses observed spread {}", index.index_id, date, observed);
+            return Ok(Some(observed));
+        }
+    }
+    Ok(index.exchange_avg_spread)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> OrderBookDepth {
+        OrderBookDepth {
+            exchange: "binance".to_string(),
+            trading_pair: "BTCUSDC".to_string(),
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_top_of_book_spread() {
+        let (bid, ask, spread) = top_of_book_spread(&book(&[(99.0, 1.0), (98.0, 5.0)], &[(101.0, 1.0)])).unwrap();
+        assert_eq!((bid, ask), (dec!(99), dec!(101)));
+        assert_eq!(spread, dec!(0.02));
+
+        assert!(top_of_book_spread(&book(&[], &[(101.0, 1.0)])).is_none());
+        // Crossed book
+        assert!(top_of_book_spread(&book(&[(102.0, 1.0)], &[(101.0, 1.0)])).is_none());
+    }
+}
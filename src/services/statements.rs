@@ -0,0 +1,397 @@
+//! Annual statements of a wallet's index token activity
+//!
+//! Lists the mints and burns of one address in a calendar year (UTC) with,
+//! per event, the market value at the index's daily price on the event day,
+//! the fee (the gap between market value and the collateral that changed
+//! hands) and, for burns, the realized P&L against the average cost of the
+//! position. Cost basis is the market value at mint time and is carried over
+//! from earlier years, so the whole history of the address is replayed.
+//!
+//! Daily prices stand in for the exact event-time price, so on volatile days
+//! the fee column absorbs intraday moves and can even be negative.
+//!
+//! Events without a timestamp, or whose quantity or amount can't be read,
+//! are left out of the replay and listed under `excluded` instead.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::{Expr, Func},
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+};
+
+use crate::entities::{blockchain_events, daily_prices, prelude::*};
+use crate::models::statement::{AnnualStatement, ExcludedEvent, IndexStatement, StatementEvent, StatementTotals};
+use crate::services::{event_amounts, index_deployments};
+
+/// Event types that create index tokens
+const MINT_EVENTS: &[&str] = &["mint"];
+
+/// Event types that destroy index tokens
+const BURN_EVENTS: &[&str] = &["burn", "withdraw"];
+
+/// Decimal places of USD values in statements
+const USD_DP: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    Mint,
+    Burn,
+}
+
+/// A mint or burn of the address, with the index price of its day
+#[derive(Debug, Clone)]
+pub struct LedgerEvent {
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+    pub network: String,
+    pub index_id: i32,
+    pub symbol: String,
+    pub kind: LedgerKind,
+    pub quantity: Decimal,
+    pub amount: Decimal,
+    pub index_price: Option<Decimal>,
+}
+
+/// Build the statement of `address` for `year`
+pub async fn build(
+    db: &DatabaseConnection,
+    address: &str,
+    year: i32,
+    now: NaiveDateTime,
+) -> Result<AnnualStatement, Box<dyn std::error::Error + Send + Sync>> {
+    let address = address.to_lowercase();
+    let year_end = NaiveDate::from_ymd_opt(year, 12, 31).ok_or("Invalid year")?;
+
+    let indexes = IndexMetadata::find().all(db).await?;
+    let deployments = index_deployments::for_indexes(db, &indexes).await?;
+
+    let mut index_of: HashMap<(String, String), (i32, String)> = HashMap::new();
+    for index in &indexes {
+        for deployment in deployments.get(&index.index_id).into_iter().flatten() {
+            index_of.insert(
+                (deployment.network.clone(), deployment.contract_address.clone()),
+                (index.index_id, index.symbol.clone()),
+            );
+        }
+    }
+
+    let event_types: Vec<&str> = MINT_EVENTS.iter().chain(BURN_EVENTS).copied().collect();
+    let rows = BlockchainEvents::find()
+        .filter(Expr::expr(Func::lower(Expr::col(blockchain_events::Column::UserAddress))).eq(address.as_str()))
+        .filter(blockchain_events::Column::EventType.is_in(event_types))
+        .all(db)
+        .await?;
+
+    let index_ids: Vec<String> = rows
+        .iter()
+        .filter_map(|row| index_of.get(&(row.network.clone(), row.contract_address.to_lowercase())))
+        .map(|(index_id, _)| index_id.to_string())
+        .collect();
+    let prices = daily_prices_until(db, index_ids, year_end).await?;
+
+    let mut events = Vec::new();
+    let mut excluded = Vec::new();
+    for row in rows {
+        let Some((index_id, symbol)) = index_of.get(&(row.network.clone(), row.contract_address.to_lowercase()))
+        else {
+            continue;
+        };
+        let timestamp = row.timestamp.map(|t| t.with_timezone(&Utc));
+        let amounts = (event_amounts::quantity(&row), event_amounts::amount(&row));
+        let (Some(timestamp), (Some(quantity), Some(amount))) = (timestamp, amounts) else {
+            let reason = if timestamp.is_none() { "no timestamp" } else { "unreadable quantity or amount" };
+            tracing::warn!(tx_hash = %row.tx_hash, "Skipping event with {} in statement", reason);
+            if timestamp.is_none_or(|t| t.year() <= year) {
+                excluded.push(ExcludedEvent {
+                    timestamp,
+                    tx_hash: row.tx_hash,
+                    network: row.network,
+                    index_id: *index_id,
+                    symbol: symbol.clone(),
+                    event_type: row.event_type,
+                    reason: reason.to_string(),
+                });
+            }
+            continue;
+        };
+        let kind = if MINT_EVENTS.contains(&row.event_type.as_str()) { LedgerKind::Mint } else { LedgerKind::Burn };
+
+        events.push(LedgerEvent {
+            timestamp,
+            quantity,
+            amount,
+            index_price: price_on(&prices, *index_id, timestamp.date_naive()),
+            tx_hash: row.tx_hash,
+            network: row.network,
+            index_id: *index_id,
+            symbol: symbol.clone(),
+            kind,
+        });
+    }
+
+    excluded.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.tx_hash.cmp(&b.tx_hash)));
+    Ok(AnnualStatement { excluded, ..compute(&address, year, events, now) })
+}
+
+/// Daily prices of the indexes up to `until`, by index then date
+async fn daily_prices_until(
+    db: &DatabaseConnection,
+    index_ids: Vec<String>,
+    until: NaiveDate,
+) -> Result<HashMap<i32, BTreeMap<NaiveDate, Decimal>>, sea_orm::DbErr> {
+    if index_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.is_in(index_ids))
+        .filter(daily_prices::Column::Date.lte(until))
+        .all(db)
+        .await?;
+
+    let mut prices: HashMap<i32, BTreeMap<NaiveDate, Decimal>> = HashMap::new();
+    for row in rows {
+        if let Ok(index_id) = row.index_id.parse() {
+            prices.entry(index_id).or_default().insert(row.date, row.price);
+        }
+    }
+    Ok(prices)
+}
+
+/// Price on `date`, or the latest before it
+fn price_on(prices: &HashMap<i32, BTreeMap<NaiveDate, Decimal>>, index_id: i32, date: NaiveDate) -> Option<Decimal> {
+    prices.get(&index_id)?.range(..=date).next_back().map(|(_, price)| *price)
+}
+
+/// Position of the address in one index
+#[derive(Debug, Default)]
+struct Position {
+    quantity: Decimal,
+    cost: Decimal,
+}
+
+/// Replay the address's history and summarize `year`
+pub fn compute(address: &str, year: i32, mut events: Vec<LedgerEvent>, now: NaiveDateTime) -> AnnualStatement {
+    events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.tx_hash.cmp(&b.tx_hash)));
+
+    let mut positions: BTreeMap<i32, Position> = BTreeMap::new();
+    let mut summaries: BTreeMap<i32, IndexStatement> = BTreeMap::new();
+    let mut listed = Vec::new();
+
+    for event in events.into_iter().take_while(|e| e.timestamp.year() <= year) {
+        let position = positions.entry(event.index_id).or_default();
+        let market_value = event.index_price.map_or(event.amount, |price| event.quantity * price);
+
+        let (fee, cost_basis, realized_pnl) = match event.kind {
+            LedgerKind::Mint => {
+                position.quantity += event.quantity;
+                position.cost += market_value;
+                (event.amount - market_value, None, None)
+            }
+            LedgerKind::Burn => {
+                // Burning more than was minted (e.g. tokens received by
+                // transfer) has no known cost for the excess
+                let cost_basis = if position.quantity.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    position.cost * event.quantity.min(position.quantity) / position.quantity
+                };
+                position.quantity = (position.quantity - event.quantity).max(Decimal::ZERO);
+                position.cost = if position.quantity.is_zero() { Decimal::ZERO } else { position.cost - cost_basis };
+                (market_value - event.amount, Some(cost_basis), Some(market_value - cost_basis))
+            }
+        };
+
+        if event.timestamp.year() < year {
+            continue;
+        }
+
+        let summary = summaries.entry(event.index_id).or_insert_with(|| IndexStatement {
+            index_id: event.index_id,
+            symbol: event.symbol.clone(),
+            minted_quantity: Decimal::ZERO,
+            burned_quantity: Decimal::ZERO,
+            invested: Decimal::ZERO,
+            withdrawn: Decimal::ZERO,
+            fees: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            year_end_quantity: Decimal::ZERO,
+            year_end_cost_basis: Decimal::ZERO,
+        });
+        match event.kind {
+            LedgerKind::Mint => {
+                summary.minted_quantity += event.quantity;
+                summary.invested += event.amount;
+            }
+            LedgerKind::Burn => {
+                summary.burned_quantity += event.quantity;
+                summary.withdrawn += event.amount;
+            }
+        }
+        summary.fees += fee;
+        summary.realized_pnl += realized_pnl.unwrap_or(Decimal::ZERO);
+
+        listed.push(StatementEvent {
+            timestamp: event.timestamp,
+            tx_hash: event.tx_hash,
+            network: event.network,
+            index_id: event.index_id,
+            symbol: event.symbol,
+            event_type: match event.kind {
+                LedgerKind::Mint => "mint".to_string(),
+                LedgerKind::Burn => "burn".to_string(),
+            },
+            quantity: event.quantity,
+            index_price: event.index_price,
+            market_value: usd(market_value),
+            amount: usd(event.amount),
+            fee: usd(fee),
+            cost_basis: cost_basis.map(usd),
+            realized_pnl: realized_pnl.map(usd),
+        });
+    }
+
+    let mut totals = StatementTotals::default();
+    let indexes = summaries
+        .into_values()
+        .map(|mut summary| {
+            if let Some(position) = positions.get(&summary.index_id) {
+                summary.year_end_quantity = position.quantity;
+                summary.year_end_cost_basis = usd(position.cost);
+            }
+            summary.invested = usd(summary.invested);
+            summary.withdrawn = usd(summary.withdrawn);
+            summary.fees = usd(summary.fees);
+            summary.realized_pnl = usd(summary.realized_pnl);
+
+            totals.invested += summary.invested;
+            totals.withdrawn += summary.withdrawn;
+            totals.fees += summary.fees;
+            totals.realized_pnl += summary.realized_pnl;
+            summary
+        })
+        .collect();
+
+    AnnualStatement {
+        address: address.to_string(),
+        year,
+        generated_at: now,
+        totals,
+        indexes,
+        events: listed,
+        excluded: Vec::new(),
+    }
+}
+
+fn usd(value: Decimal) -> Decimal {
+    value.round_dp(USD_DP).normalize()
+}
+
+/// The statement's events as CSV, one row per event
+pub fn to_csv(statement: &AnnualStatement) -> String {
+    let mut csv = String::from(
+        "timestamp,tx_hash,network,index_id,symbol,type,quantity,index_price,market_value,amount,fee,cost_basis,realized_pnl\n",
+    );
+    let optional = |value: Option<Decimal>| value.map(|v| v.to_string()).unwrap_or_default();
+
+    for event in &statement.events {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            event.timestamp.to_rfc3339(),
+            event.tx_hash,
+            event.network,
+            event.index_id,
+            event.symbol,
+            event.event_type,
+            event.quantity,
+            optional(event.index_price),
+            event.market_value,
+            event.amount,
+            event.fee,
+            optional(event.cost_basis),
+            optional(event.realized_pnl),
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn event(date: (i32, u32, u32), kind: LedgerKind, quantity: Decimal, amount: Decimal, price: Decimal) -> LedgerEvent {
+        LedgerEvent {
+            timestamp: Utc.with_ymd_and_hms(date.0, date.1, date.2, 12, 0, 0).unwrap(),
+            tx_hash: format!("0x{:?}{}{}", kind, date.1, date.2),
+            network: "base".to_string(),
+            index_id: 7,
+            symbol: "L1TOP".to_string(),
+            kind,
+            quantity,
+            amount,
+            index_price: Some(price),
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, 15).unwrap().and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_realized_pnl_uses_average_cost_across_years() {
+        let events = vec![
+            // 2024: 2 units at 100 and 1 unit at 130 -> average cost 110
+            event((2024, 3, 1), LedgerKind::Mint, dec!(2), dec!(201), dec!(100)),
+            event((2024, 9, 1), LedgerKind::Mint, dec!(1), dec!(130.5), dec!(130)),
+            // 2025: burn 1.5 units at 150, receiving 224 after a 1 USD fee
+            event((2025, 2, 1), LedgerKind::Burn, dec!(1.5), dec!(224), dec!(150)),
+            // 2026 activity is out of range
+            event((2026, 1, 2), LedgerKind::Burn, dec!(1.5), dec!(300), dec!(200)),
+        ];
+
+        let statement = compute("0xabc", 2025, events, now());
+        assert_eq!(statement.events.len(), 1);
+        let burn = &statement.events[0];
+        assert_eq!(burn.market_value, dec!(225));
+        assert_eq!(burn.fee, dec!(1));
+        assert_eq!(burn.cost_basis, Some(dec!(165)));
+        assert_eq!(burn.realized_pnl, Some(dec!(60)));
+
+        let index = &statement.indexes[0];
+        assert_eq!(index.year_end_quantity, dec!(1.5));
+        assert_eq!(index.year_end_cost_basis, dec!(165));
+        assert_eq!(statement.totals.withdrawn, dec!(224));
+        assert_eq!(statement.totals.realized_pnl, dec!(60));
+        assert_eq!(statement.totals.invested, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_mint_fees_and_csv() {
+        let events = vec![event((2025, 5, 1), LedgerKind::Mint, dec!(0.5), dec!(50.25), dec!(100))];
+
+        let statement = compute("0xabc", 2025, events, now());
+        assert_eq!(statement.totals.invested, dec!(50.25));
+        assert_eq!(statement.totals.fees, dec!(0.25));
+
+        let csv = to_csv(&statement);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "2025-05-01T12:00:00+00:00,0xMint51,base,7,L1TOP,mint,0.5,100,50,50.25,0.25,,");
+    }
+
+    #[test]
+    fn test_price_on_falls_back_to_previous_day() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let prices = HashMap::from([(7, BTreeMap::from([(day(1), dec!(100)), (day(3), dec!(103))]))]);
+
+        assert_eq!(price_on(&prices, 7, day(2)), Some(dec!(100)));
+        assert_eq!(price_on(&prices, 7, day(3)), Some(dec!(103)));
+        assert_eq!(price_on(&prices, 7, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()), None);
+        assert_eq!(price_on(&prices, 8, day(3)), None);
+    }
+}
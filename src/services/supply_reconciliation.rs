@@ -0,0 +1,314 @@
+//! Index token supply reconciliation
+//!
+//! blockchain_events is filled by a push-based ingestion path, so a missed
+//! POST leaves a permanent gap. This compares the net supply implied by the
+//! ingested events (mints minus burns/withdrawals) with `totalSupply()` read
+//! from the index token contract, and flags indexes where they diverge by
+//! more than `DISCREPANCY_THRESHOLD_BPS`.
+
+use std::str::FromStr;
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::{ProviderBuilder, RootProvider},
+    sol,
+    transports::http::{Client, Http},
+};
+use chrono::Utc;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::entities::{blockchain_events, index_metadata, prelude::*};
+use crate::models::supply_reconciliation::SupplyReconciliationResponse;
+use crate::services::event_amounts;
+use crate::services::units::{self, UnitsError};
+
+/// RPC endpoint of the chain the index tokens and their events live on
+pub const ENV_BASE_RPC_URL: &str = "BASE_RPC_URL";
+
+/// Network value of the reconciled events in blockchain_events
+pub const NETWORK: &str = "base";
+
+/// Divergence above which an index is flagged (10 bps = 0.1%)
+pub const DISCREPANCY_THRESHOLD_BPS: u64 = 10;
+
+/// Event types that create index tokens
+const MINT_EVENTS: &[&str] = &["mint"];
+
+/// Event types that destroy index tokens
+const BURN_EVENTS: &[&str] = &["burn", "withdraw"];
+
+sol! {
+    #[sol(rpc)]
+    interface IIndexToken {
+        function totalSupply() external view returns (uint256);
+        function decimals() external view returns (uint8);
+    }
+}
+
+#[derive(Debug)]
+pub enum SupplyReconciliationError {
+    InvalidConfig(String),
+    IndexNotFound(i32),
+    ContractCallError(String),
+    Units(UnitsError),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for SupplyReconciliationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SupplyReconciliationError::InvalidConfig(msg) => write!(f, "Invalid config: {}", msg),
+            SupplyReconciliationError::IndexNotFound(id) => write!(f, "Index {} not found", id),
+            SupplyReconciliationError::ContractCallError(msg) => {
+                write!(f, "Contract call error: {}", msg)
+            }
+            SupplyReconciliationError::Units(e) => write!(f, "{}", e),
+            SupplyReconciliationError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SupplyReconciliationError {}
+
+impl From<UnitsError> for SupplyReconciliationError {
+    fn from(e: UnitsError) -> Self {
+        SupplyReconciliationError::Units(e)
+    }
+}
+
+impl From<sea_orm::DbErr> for SupplyReconciliationError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        SupplyReconciliationError::Database(e)
+    }
+}
+
+/// Minted and burned totals from events, in base units
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EventSupply {
+    pub minted: U256,
+    pub burned: U256,
+}
+
+/// Sum event quantities by direction; other event types are ignored
+pub fn sum_event_supply(
+    events: &[blockchain_events::Model],
+    decimals: u32,
+) -> Result<EventSupply, UnitsError> {
+    let mut supply = EventSupply::default();
+    for event in events {
+        let event_type = event.event_type.as_str();
+        if MINT_EVENTS.contains(&event_type) {
+            supply.minted += event_amounts::quantity_base_units(event, decimals)?;
+        } else if BURN_EVENTS.contains(&event_type) {
+            supply.burned += event_amounts::quantity_base_units(event, decimals)?;
+        }
+    }
+    Ok(supply)
+}
+
+/// Outcome of comparing event supply with on-chain supply
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    /// |onchain - events|
+    pub abs_difference: U256,
+    /// True when the event supply is below the on-chain supply
+    pub events_behind: bool,
+    /// |difference| in basis points of the on-chain supply; None when it is zero
+    pub difference_bps: Option<U256>,
+    pub discrepancy: bool,
+}
+
+/// Compare supplies; a non-zero difference against a zero on-chain supply
+/// is always a discrepancy
+pub fn compare(event_supply: U256, onchain_supply: U256, threshold_bps: u64) -> Comparison {
+    let events_behind = event_supply < onchain_supply;
+    let abs_difference = if events_behind {
+        onchain_supply - event_supply
+    } else {
+        event_supply - onchain_supply
+    };
+
+    let difference_bps = (!onchain_supply.is_zero())
+        .then(|| abs_difference.saturating_mul(U256::from(10_000u64)) / onchain_supply);
+
+    let discrepancy = match difference_bps {
+        Some(bps) => bps > U256::from(threshold_bps),
+        None => !abs_difference.is_zero(),
+    };
+
+    Comparison { abs_difference, events_behind, difference_bps, discrepancy }
+}
+
+/// Reads index token supplies on-chain and reconciles them against events
+pub struct SupplyReconciliationService {
+    db: DatabaseConnection,
+    provider: RootProvider<Http<Client>>,
+}
+
+impl SupplyReconciliationService {
+    pub fn new(db: DatabaseConnection, rpc_url: &str) -> Result<Self, SupplyReconciliationError> {
+        let provider = ProviderBuilder::new().on_http(rpc_url.parse().map_err(|e| {
+            SupplyReconciliationError::InvalidConfig(format!("Invalid RPC URL: {}", e))
+        })?);
+        Ok(Self { db, provider })
+    }
+
+    /// Build from `BASE_RPC_URL`
+    pub fn from_env(db: DatabaseConnection) -> Result<Self, SupplyReconciliationError> {
+        let rpc_url = std::env::var(ENV_BASE_RPC_URL).map_err(|_| {
+            SupplyReconciliationError::InvalidConfig(format!("{} not set", ENV_BASE_RPC_URL))
+        })?;
+        Self::new(db, &rpc_url)
+    }
+
+    /// Reconcile a single index
+    pub async fn reconcile_index(
+        &self,
+        index_id: i32,
+    ) -> Result<SupplyReconciliationResponse, SupplyReconciliationError> {
+        let index = IndexMetadata::find_by_id(index_id)
+            .one(&self.db)
+            .await?
+            .ok_or(SupplyReconciliationError::IndexNotFound(index_id))?;
+        self.reconcile(&index).await
+    }
+
+    /// Reconcile every index with a deployed address
+    ///
+    /// Failures on one index are logged and don't stop the others.
+    pub async fn reconcile_all(&self) -> Result<Vec<SupplyReconciliationResponse>, SupplyReconciliationError> {
+        let indexes = IndexMetadata::find().all(&self.db).await?;
+        let mut reports = Vec::new();
+
+        for index in indexes.iter().filter(|i| !i.address.is_empty()) {
+            match self.reconcile(index).await {
+                Ok(report) => reports.push(report),
+                Err(e) => tracing::warn!(index_id = index.index_id, error = %e, "Supply reconciliation failed"),
+            }
+        }
+
+        Ok(reports)
+    }
+
+    async fn reconcile(
+        &self,
+        index: &index_metadata::Model,
+    ) -> Result<SupplyReconciliationResponse, SupplyReconciliationError> {
+        let address = Address::from_str(&index.address).map_err(|e| {
+            SupplyReconciliationError::InvalidConfig(format!("Invalid address for index {}: {}", index.index_id, e))
+        })?;
+        let token = IIndexToken::new(address, &self.provider);
+
+        let decimals = token
+            .decimals()
+            .call()
+            .await
+            .map_err(|e| SupplyReconciliationError::ContractCallError(format!("decimals failed: {}", e)))?
+            ._0 as u32;
+        let onchain_supply = token
+            .totalSupply()
+            .call()
+            .await
+            .map_err(|e| SupplyReconciliationError::ContractCallError(format!("totalSupply failed: {}", e)))?
+            ._0;
+
+        let events = BlockchainEvents::find()
+            .filter(blockchain_events::Column::ContractAddress.eq(index.address.to_lowercase()))
+            .filter(blockchain_events::Column::Network.eq(NETWORK))
+            .all(&self.db)
+            .await?;
+
+        let supply = sum_event_supply(&events, decimals)?;
+        let event_supply = supply.minted.saturating_sub(supply.burned);
+        let comparison = compare(event_supply, onchain_supply, DISCREPANCY_THRESHOLD_BPS);
+
+        let sign = if comparison.events_behind || comparison.abs_difference.is_zero() { "" } else { "-" };
+
+        Ok(SupplyReconciliationResponse {
+            index_id: index.index_id,
+            address: index.address.clone(),
+            network: NETWORK.to_string(),
+            decimals,
+            minted: units::format_units(supply.minted, decimals),
+            burned: units::format_units(supply.burned, decimals),
+            event_supply: units::format_units(event_supply, decimals),
+            onchain_supply: units::format_units(onchain_supply, decimals),
+            difference: format!("{}{}", sign, units::format_units(comparison.abs_difference, decimals)),
+            difference_bps: comparison.difference_bps.map(|bps| bps.to_string()),
+            threshold_bps: DISCREPANCY_THRESHOLD_BPS,
+            discrepancy: comparison.discrepancy,
+            event_count: events.len(),
+            checked_at: Utc::now().naive_utc(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn event(event_type: &str, quantity: Decimal) -> blockchain_events::Model {
+        blockchain_events::Model {
+            id: 1,
+            tx_hash: "0xabc".to_string(),
+            block_number: 1,
+            log_index: 0,
+            event_type: event_type.to_string(),
+            contract_address: "0xindex".to_string(),
+            network: NETWORK.to_string(),
+            user_address: None,
+            amount: None,
+            quantity: Some(quantity),
+            timestamp: None,
+            raw_amount: None,
+            decimals: None,
+            raw_quantity: None,
+            quantity_decimals: None,
+        }
+    }
+
+    fn units18(value: Decimal) -> U256 {
+        units::to_base_units(value, 18).unwrap()
+    }
+
+    #[test]
+    fn test_sum_event_supply_by_direction() {
+        let events = vec![
+            event("mint", dec!(10)),
+            event("mint", dec!(2.5)),
+            event("withdraw", dec!(3)),
+            event("deposit", dec!(100)),
+        ];
+        let supply = sum_event_supply(&events, 18).unwrap();
+        assert_eq!(supply.minted, units18(dec!(12.5)));
+        assert_eq!(supply.burned, units18(dec!(3)));
+    }
+
+    #[test]
+    fn test_compare_within_threshold() {
+        // 0.05% apart
+        let result = compare(units18(dec!(9995)), units18(dec!(10000)), DISCREPANCY_THRESHOLD_BPS);
+        assert_eq!(result.difference_bps, Some(U256::from(5u8)));
+        assert!(result.events_behind);
+        assert!(!result.discrepancy);
+    }
+
+    #[test]
+    fn test_compare_flags_missing_events() {
+        let result = compare(units18(dec!(9000)), units18(dec!(10000)), DISCREPANCY_THRESHOLD_BPS);
+        assert_eq!(result.abs_difference, units18(dec!(1000)));
+        assert_eq!(result.difference_bps, Some(U256::from(1000u64)));
+        assert!(result.discrepancy);
+    }
+
+    #[test]
+    fn test_compare_zero_onchain_supply() {
+        assert!(!compare(U256::ZERO, U256::ZERO, DISCREPANCY_THRESHOLD_BPS).discrepancy);
+        let result = compare(units18(dec!(1)), U256::ZERO, DISCREPANCY_THRESHOLD_BPS);
+        assert_eq!(result.difference_bps, None);
+        assert!(!result.events_behind);
+        assert!(result.discrepancy);
+    }
+}
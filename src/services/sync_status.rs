@@ -9,6 +9,7 @@ use chrono::{Duration, Utc};
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 
 use crate::entities::sync_status::{self, Entity as SyncStatus};
+use crate::services::alerting::{self, Alert, AlertKind, Severity};
 
 /// Job names for tracking sync status
 pub mod jobs {
@@ -21,6 +22,28 @@ pub mod jobs {
     pub const INDEX_DAILY_PRICES: &str = "index_daily_prices_sync";
     pub const REBALANCE_SYNC: &str = "rebalance_sync";
     pub const BITGET_HISTORICAL_PRICES: &str = "bitget_historical_prices_sync";
+    pub const CATEGORY_MEMBERSHIP_CONSISTENCY: &str = "category_membership_consistency";
+    pub const ITP_PRICE_SNAPSHOT: &str = "itp_price_snapshot";
+    pub const ITP_PRICE_DOWNSAMPLER: &str = "itp_price_downsampler";
+    pub const ITP_CHAIN_DISCOVERY: &str = "itp_chain_discovery";
+    pub const KEEPER_CHART_SYNC: &str = "keeper_chart_sync";
+    pub const JOB_FAILURES_RETRY: &str = "job_failures_retry";
+    pub const SUPPLY_RECONCILIATION: &str = "supply_reconciliation";
+    pub const COINS_PRICE_RETENTION: &str = "coins_price_retention";
+    pub const COINS_PRICE_PARTITIONS: &str = "coins_price_partitions";
+    pub const PRICE_RECONCILIATION: &str = "price_reconciliation";
+    pub const ITP_DRIFT_MONITOR: &str = "itp_drift_monitor";
+    pub const ITP_ORDER_INDEXER: &str = "itp_order_indexer";
+    pub const TVL_SNAPSHOT: &str = "tvl_snapshot";
+    pub const REBALANCE_DEPLOYER: &str = "rebalance_deployer";
+    pub const WALLET_BALANCE_MONITOR: &str = "wallet_balance_monitor";
+    pub const YIELD_RATES_SYNC: &str = "yield_rates_sync";
+    pub const ROLLING_STATS: &str = "rolling_stats";
+    pub const SNAPSHOT_LOG: &str = "snapshot_log";
+    pub const SNAPSHOT_ANCHOR: &str = "snapshot_anchor";
+    pub const REPORT_GENERATION: &str = "report_generation";
+    pub const SPREAD_SAMPLING: &str = "spread_sampling";
+    pub const PAIR_VOLUME_SAMPLING: &str = "pair_volume_sampling";
 }
 
 /// Default minimum intervals between syncs (in seconds)
@@ -34,6 +57,19 @@ pub mod intervals {
     pub const INDEX_DAILY_PRICES: i32 = 3600;        // 1 hour
     pub const REBALANCE_SYNC: i32 = 3600;            // 1 hour
     pub const BITGET_HISTORICAL_PRICES: i32 = 86400; // 24 hours (daily update)
+    pub const CATEGORY_MEMBERSHIP_CONSISTENCY: i32 = 86400; // 24 hours
+    pub const SUPPLY_RECONCILIATION: i32 = 21600;    // 6 hours
+    pub const COINS_PRICE_RETENTION: i32 = 604800;   // 7 days
+    pub const PRICE_RECONCILIATION: i32 = 86400;     // 24 hours
+    pub const ITP_DRIFT_MONITOR: i32 = 21600;        // 6 hours
+    pub const TVL_SNAPSHOT: i32 = 21600;             // 6 hours
+    pub const YIELD_RATES_SYNC: i32 = 86400;         // 24 hours
+    pub const ROLLING_STATS: i32 = 86400;            // 24 hours
+    pub const SNAPSHOT_LOG: i32 = 3600;              // 1 hour
+    pub const SNAPSHOT_ANCHOR: i32 = 86400;          // 24 hours
+    pub const REPORT_GENERATION: i32 = 21600;        // 6 hours
+    pub const SPREAD_SAMPLING: i32 = 86400;          // 24 hours
+    pub const PAIR_VOLUME_SAMPLING: i32 = 86400;     // 24 hours
 }
 
 /// Check if a sync job should run based on last successful sync time
@@ -136,7 +172,7 @@ pub async fn record_success(
     Ok(())
 }
 
-/// Record a failed sync attempt
+/// Record a failed sync attempt, and raise it as a job failure alert
 pub async fn record_failure(
     db: &DatabaseConnection,
     job_name: &str,
@@ -144,6 +180,12 @@ pub async fn record_failure(
     default_interval_secs: i32,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let now = Utc::now().naive_utc();
+    alerting::notify(Alert::new(
+        AlertKind::JobFailure,
+        Severity::Warning,
+        format!("Job {} failed", job_name),
+        error,
+    ));
 
     let existing = SyncStatus::find()
         .filter(sync_status::Column::JobName.eq(job_name))
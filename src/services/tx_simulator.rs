@@ -0,0 +1,138 @@
+//! Dry runs of BridgeProxy transactions
+//!
+//! Before the backend sends an ITP creation or weight update, the exact
+//! calldata is run through eth_call and eth_estimateGas against the latest
+//! block, from the sending wallet. A call that would revert is reported with
+//! its decoded reason (Error(string), Panic(uint256) or a known custom error)
+//! instead of costing gas on a failed transaction.
+
+use alloy::{
+    primitives::{Address, Bytes},
+    providers::{Provider, RootProvider},
+    rpc::types::{BlockId, TransactionInput, TransactionRequest},
+    sol,
+    sol_types::{decode_revert_reason, SolError},
+    transports::{
+        http::{Client, Http},
+        TransportError,
+    },
+};
+
+// Custom errors the BridgeProxy and its Ownable base revert with
+sol! {
+    error InvalidTokenName();
+    error InvalidTokenSymbol();
+    error OwnableUnauthorizedAccount(address account);
+}
+
+/// A call that went through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simulation {
+    /// Block the call was simulated against
+    pub block_number: u64,
+    pub gas_estimate: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationError {
+    /// The call reverts; holds the decoded reason
+    Reverted(String),
+    /// The simulation itself failed (RPC unreachable, bad response)
+    Provider(String),
+}
+
+impl std::fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulationError::Reverted(reason) => write!(f, "Transaction would revert: {}", reason),
+            SimulationError::Provider(msg) => write!(f, "Simulation failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+/// eth_call and eth_estimateGas `calldata` sent by `from` to `to` at the latest block
+pub async fn simulate(
+    provider: &RootProvider<Http<Client>>,
+    from: Address,
+    to: Address,
+    calldata: Bytes,
+) -> Result<Simulation, SimulationError> {
+    let block_number = provider
+        .get_block_number()
+        .await
+        .map_err(|e| SimulationError::Provider(format!("Failed to get block number: {}", e)))?;
+    let block = BlockId::number(block_number);
+    let tx = TransactionRequest {
+        from: Some(from),
+        to: Some(to.into()),
+        input: TransactionInput::new(calldata),
+        ..Default::default()
+    };
+
+    provider.call(&tx).block(block).await.map_err(classify)?;
+    let gas_estimate = provider.estimate_gas(&tx).block(block).await.map_err(classify)?;
+
+    Ok(Simulation { block_number, gas_estimate })
+}
+
+/// Tell reverts apart from RPC failures
+fn classify(err: TransportError) -> SimulationError {
+    if let Some(payload) = err.as_error_resp() {
+        if let Some(data) = payload.as_revert_data() {
+            return SimulationError::Reverted(decode_revert(&data));
+        }
+        if payload.message.contains("revert") {
+            return SimulationError::Reverted(payload.message.to_string());
+        }
+    }
+    SimulationError::Provider(err.to_string())
+}
+
+/// Human-readable reason of a revert from its return data
+pub fn decode_revert(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "execution reverted without a reason".to_string();
+    }
+    if let Some(reason) = decode_revert_reason(data) {
+        return reason;
+    }
+    if let Ok(error) = OwnableUnauthorizedAccount::abi_decode(data, true) {
+        return format!("OwnableUnauthorizedAccount({})", error.account);
+    }
+    let selector = &data[..data.len().min(4)];
+    if selector == InvalidTokenName::SELECTOR {
+        return "InvalidTokenName()".to_string();
+    }
+    if selector == InvalidTokenSymbol::SELECTOR {
+        return "InvalidTokenSymbol()".to_string();
+    }
+    format!("custom error 0x{}", hex::encode(selector))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use alloy::sol_types::{Panic, Revert};
+
+    #[test]
+    fn test_decode_standard_reverts() {
+        assert_eq!(decode_revert(&Revert::from("paused").abi_encode()), "revert: paused");
+        let panic = Panic { code: U256::from(0x11) }.abi_encode();
+        assert!(decode_revert(&panic).contains("overflow"));
+        assert_eq!(decode_revert(&[]), "execution reverted without a reason");
+    }
+
+    #[test]
+    fn test_decode_custom_errors() {
+        assert_eq!(decode_revert(&InvalidTokenName {}.abi_encode()), "InvalidTokenName()");
+        let account = Address::repeat_byte(0xab);
+        assert_eq!(
+            decode_revert(&OwnableUnauthorizedAccount { account }.abi_encode()),
+            format!("OwnableUnauthorizedAccount({})", account)
+        );
+        assert_eq!(decode_revert(&[0xde, 0xad, 0xbe, 0xef, 0x00]), "custom error 0xdeadbeef");
+    }
+}
@@ -0,0 +1,213 @@
+//! Token unit conversion
+//!
+//! On-chain amounts are integers in base units (`value × 10^decimals`). USDC
+//! uses 6 decimals, vault shares and Castle prices 18, index quantities 30.
+//! `10^30` does not fit in a u64 and `value × 10^30` does not fit in a
+//! `Decimal`, so base units are held as `U256` and scaled digit-wise instead
+//! of through floating point or fixed-width multipliers.
+
+use std::str::FromStr;
+
+use alloy::primitives::U256;
+use rust_decimal::Decimal;
+
+/// Most decimals an amount can have: U256 holds at most 78 digits, so
+/// anything past 10^77 can't be a real token
+pub const MAX_DECIMALS: u32 = 77;
+
+/// Errors from unit conversion
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitsError {
+    /// Base units are unsigned; token amounts can't be negative
+    Negative(Decimal),
+    /// Result does not fit in a U256
+    Overflow { decimals: u32 },
+    /// Value has too many significant digits to be represented as a Decimal
+    OutOfRange(String),
+    /// Raw amount is not a base-10 unsigned integer
+    InvalidRaw(String),
+    /// More decimals than `MAX_DECIMALS`
+    TooManyDecimals(u32),
+}
+
+impl std::fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnitsError::Negative(value) => write!(f, "Negative token amount: {}", value),
+            UnitsError::Overflow { decimals } => {
+                write!(f, "Amount overflows U256 at {} decimals", decimals)
+            }
+            UnitsError::OutOfRange(value) => write!(f, "Amount out of Decimal range: {}", value),
+            UnitsError::InvalidRaw(value) => write!(f, "Invalid raw amount: '{}'", value),
+            UnitsError::TooManyDecimals(decimals) => {
+                write!(f, "Too many decimals: {} (max {})", decimals, MAX_DECIMALS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnitsError {}
+
+/// `10^exp` as a U256, None past 10^77
+fn pow10(exp: u32) -> Option<U256> {
+    U256::from(10u8).checked_pow(U256::from(exp))
+}
+
+/// Parse a raw on-chain amount (base-10 integer string, as stored in the DB)
+pub fn parse_raw(raw: &str) -> Result<U256, UnitsError> {
+    U256::from_str_radix(raw.trim(), 10).map_err(|_| UnitsError::InvalidRaw(raw.to_string()))
+}
+
+/// Re-express base units of a `from`-decimals token in `to` decimals
+///
+/// Scaling down truncates, as in `to_base_units`.
+pub fn rescale(raw: U256, from: u32, to: u32) -> Result<U256, UnitsError> {
+    if to >= from {
+        pow10(to - from)
+            .and_then(|multiplier| raw.checked_mul(multiplier))
+            .ok_or(UnitsError::Overflow { decimals: to })
+    } else {
+        Ok(pow10(from - to).map(|divisor| raw / divisor).unwrap_or(U256::ZERO))
+    }
+}
+
+/// Convert a human-readable amount to base units (like ethers `parseUnits`)
+///
+/// Digits beyond `decimals` are truncated, since they can't exist on-chain.
+pub fn to_base_units(value: Decimal, decimals: u32) -> Result<U256, UnitsError> {
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(UnitsError::Negative(value));
+    }
+
+    let mantissa = U256::from(value.mantissa().unsigned_abs());
+    let scale = value.scale();
+
+    if decimals >= scale {
+        pow10(decimals - scale)
+            .and_then(|multiplier| mantissa.checked_mul(multiplier))
+            .ok_or(UnitsError::Overflow { decimals })
+    } else {
+        // scale is at most 28, so this power always fits
+        Ok(mantissa / pow10(scale - decimals).unwrap_or(U256::from(1u8)))
+    }
+}
+
+/// Format base units as an exact decimal string (like ethers `formatUnits`)
+///
+/// Trailing fractional zeros are dropped: 1500000 at 6 decimals is "1.5".
+pub fn format_units(raw: U256, decimals: u32) -> String {
+    let digits = raw.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Convert base units back to a Decimal
+///
+/// Fractional digits beyond Decimal's 28-digit scale are rounded away, which
+/// only affects dust below 10^-28 at 30 decimals.
+pub fn from_base_units(raw: U256, decimals: u32) -> Result<Decimal, UnitsError> {
+    if decimals > MAX_DECIMALS {
+        return Err(UnitsError::TooManyDecimals(decimals));
+    }
+    let formatted = format_units(raw, decimals);
+    Decimal::from_str(&formatted).map_err(|_| UnitsError::OutOfRange(formatted))
+}
+
+/// Convert base units to f64 for display-only values
+pub fn to_f64(raw: U256, decimals: u32) -> f64 {
+    format_units(raw, decimals).parse::<f64>().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_18_decimal_round_trip() {
+        let raw = to_base_units(dec!(1.5), 18).unwrap();
+        assert_eq!(raw, U256::from(1_500_000_000_000_000_000u128));
+        assert_eq!(format_units(raw, 18), "1.5");
+        assert_eq!(from_base_units(raw, 18).unwrap(), dec!(1.5));
+    }
+
+    #[test]
+    fn test_30_decimal_round_trip() {
+        // 10^30 overflowed the old u64 multiplier
+        let raw = to_base_units(dec!(123.456), 30).unwrap();
+        assert_eq!(raw.to_string(), format!("123456{}", "0".repeat(27)));
+        assert_eq!(format_units(raw, 30), "123.456");
+        assert_eq!(from_base_units(raw, 30).unwrap(), dec!(123.456));
+    }
+
+    #[test]
+    fn test_30_decimal_sums_are_exact() {
+        let total = [dec!(0.1), dec!(0.2), dec!(1000000)]
+            .into_iter()
+            .map(|v| to_base_units(v, 30).unwrap())
+            .fold(U256::ZERO, |acc, v| acc + v);
+        assert_eq!(format_units(total, 30), "1000000.3");
+        assert_eq!(to_f64(total, 30), 1000000.3);
+    }
+
+    #[test]
+    fn test_30_decimal_dust() {
+        let one_wei = U256::from(1u8);
+        assert_eq!(format_units(one_wei, 30), format!("0.{}1", "0".repeat(29)));
+        assert_eq!(from_base_units(one_wei, 30).unwrap(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_extra_digits_are_truncated() {
+        assert_eq!(to_base_units(dec!(1.23456789), 6).unwrap(), U256::from(1_234_567u64));
+    }
+
+    #[test]
+    fn test_integers_and_zero() {
+        assert_eq!(format_units(U256::from(5_000_000u64), 6), "5");
+        assert_eq!(format_units(U256::ZERO, 18), "0");
+        assert_eq!(format_units(U256::from(42u8), 0), "42");
+        assert_eq!(to_base_units(Decimal::ZERO, 30).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_parse_raw() {
+        let raw = format!("123456{}", "0".repeat(27));
+        assert_eq!(parse_raw(&raw).unwrap(), to_base_units(dec!(123.456), 30).unwrap());
+        assert_eq!(parse_raw("0x10"), Err(UnitsError::InvalidRaw("0x10".to_string())));
+        assert_eq!(parse_raw("-1"), Err(UnitsError::InvalidRaw("-1".to_string())));
+    }
+
+    #[test]
+    fn test_rescale_between_decimals() {
+        let eighteen = to_base_units(dec!(2.5), 18).unwrap();
+        assert_eq!(rescale(eighteen, 18, 30).unwrap(), to_base_units(dec!(2.5), 30).unwrap());
+        assert_eq!(rescale(eighteen, 18, 6).unwrap(), U256::from(2_500_000u64));
+        assert_eq!(rescale(U256::from(1u8), 30, 6).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_rejects_negative_and_overflow() {
+        assert_eq!(to_base_units(dec!(-1), 18), Err(UnitsError::Negative(dec!(-1))));
+        assert_eq!(
+            to_base_units(Decimal::MAX, 60),
+            Err(UnitsError::Overflow { decimals: 60 })
+        );
+        assert_eq!(
+            from_base_units(U256::from(1u8), 4_000_000_000),
+            Err(UnitsError::TooManyDecimals(4_000_000_000))
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Balance of the backend's signer wallet on each network
+//!
+//! The signer wallet (see `services::signers`) pays the gas of ITP creation
+//! and weight updates; when it runs dry those fail with an opaque
+//! "insufficient funds" error. The wallet balance monitor records its ETH
+//! balance on every network with an RPC configured, one row per network,
+//! wallet and day in wallet_balance_checks, and flags and logs balances
+//! under the threshold. /readyz reports the latest check of each network.
+//!
+//! Configuration (environment):
+//! - `ARB_RPC_URL`, `BASE_RPC_URL`, `ORBIT_RPC_URL` - networks checked (those set)
+//! - `WALLET_BALANCE_MIN_ETH` - alert threshold (default 0.05)
+//! - `WALLET_BALANCE_MIN_ETH_<NETWORK>` - threshold of one network, e.g. `WALLET_BALANCE_MIN_ETH_ARBITRUM`
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::entities::{prelude::*, wallet_balance_checks};
+use crate::services::chain_spend;
+use crate::services::supply_reconciliation::ENV_BASE_RPC_URL;
+
+const ENV_THRESHOLD: &str = "WALLET_BALANCE_MIN_ETH";
+
+const DEFAULT_THRESHOLD_ETH: Decimal = dec!(0.05);
+
+/// Networks the wallet may send on, with the RPC setting that enables them
+const NETWORKS: [(&str, &str); 3] = [("arbitrum", "ARB_RPC_URL"), ("base", ENV_BASE_RPC_URL), ("orbit", "ORBIT_RPC_URL")];
+
+/// Checks older than this aren't reported by /readyz
+const LATEST_MAX_AGE_DAYS: i64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitoredNetwork {
+    pub name: &'static str,
+    pub rpc_url: String,
+    pub threshold_eth: Decimal,
+}
+
+/// Networks with an RPC configured and their thresholds
+pub fn networks_from_env() -> Vec<MonitoredNetwork> {
+    networks_from_values(|var| std::env::var(var).ok())
+}
+
+/// Build from raw setting values; invalid thresholds fall back to the default
+fn networks_from_values(setting: impl Fn(&str) -> Option<String>) -> Vec<MonitoredNetwork> {
+    let threshold = |var: &str, default: Decimal| match setting(var).map(|v| Decimal::from_str(v.trim())) {
+        Some(Ok(v)) if v >= Decimal::ZERO => v,
+        Some(_) => {
+            tracing::warn!("Invalid {}, using {}", var, default);
+            default
+        }
+        None => default,
+    };
+    let default_threshold = threshold(ENV_THRESHOLD, DEFAULT_THRESHOLD_ETH);
+
+    NETWORKS
+        .iter()
+        .filter_map(|(name, rpc_var)| {
+            let rpc_url = setting(rpc_var).filter(|url| !url.trim().is_empty())?;
+            let threshold_eth = threshold(&format!("{}_{}", ENV_THRESHOLD, name.to_uppercase()), default_threshold);
+            Some(MonitoredNetwork { name, rpc_url, threshold_eth })
+        })
+        .collect()
+}
+
+/// ETH balance of `address` at the latest block
+pub async fn fetch_balance(rpc_url: &str, address: Address) -> Result<Decimal, Box<dyn std::error::Error + Send + Sync>> {
+    let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
+    let balance = provider.get_balance(address).await?;
+    Ok(chain_spend::wei_to_eth(balance.saturating_to::<u128>()))
+}
+
+/// Store the day's balance check of a wallet on a network
+pub async fn record(
+    db: &DatabaseConnection,
+    network: &str,
+    address: Address,
+    balance_eth: Decimal,
+    threshold_eth: Decimal,
+    checked_at: NaiveDateTime,
+) -> Result<wallet_balance_checks::Model, sea_orm::DbErr> {
+    let check = wallet_balance_checks::ActiveModel {
+        network: Set(network.to_string()),
+        address: Set(address.to_string()),
+        date: Set(checked_at.date()),
+        balance_eth: Set(balance_eth),
+        threshold_eth: Set(threshold_eth),
+        low: Set(balance_eth < threshold_eth),
+        checked_at: Set(checked_at),
+        ..Default::default()
+    };
+
+    WalletBalanceChecks::insert(check)
+        .on_conflict(
+            OnConflict::columns([
+                wallet_balance_checks::Column::Network,
+                wallet_balance_checks::Column::Address,
+                wallet_balance_checks::Column::Date,
+            ])
+            .update_columns([
+                wallet_balance_checks::Column::BalanceEth,
+                wallet_balance_checks::Column::ThresholdEth,
+                wallet_balance_checks::Column::Low,
+                wallet_balance_checks::Column::CheckedAt,
+            ])
+            .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await
+}
+
+/// Latest check of each network and wallet, if from `today` or the day before
+pub async fn latest(db: &DatabaseConnection, today: NaiveDate) -> Result<Vec<wallet_balance_checks::Model>, sea_orm::DbErr> {
+    let checks = WalletBalanceChecks::find()
+        .filter(wallet_balance_checks::Column::Date.gte(today - chrono::Duration::days(LATEST_MAX_AGE_DAYS)))
+        .order_by_desc(wallet_balance_checks::Column::CheckedAt)
+        .all(db)
+        .await?;
+
+    let mut seen = HashSet::new();
+    let mut latest: Vec<_> = checks
+        .into_iter()
+        .filter(|check| seen.insert((check.network.clone(), check.address.clone())))
+        .collect();
+    latest.sort_by(|a, b| a.network.cmp(&b.network));
+    Ok(latest)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MonitorSummary {
+    pub checked: usize,
+    pub low: usize,
+    /// Networks whose RPC couldn't be queried
+    pub failed: usize,
+}
+
+/// Check and record the balance of `address` on every network
+pub async fn run(
+    db: &DatabaseConnection,
+    address: Address,
+    networks: &[MonitoredNetwork],
+    now: NaiveDateTime,
+) -> Result<MonitorSummary, sea_orm::DbErr> {
+    let mut summary = MonitorSummary::default();
+    for network in networks {
+        // An unreachable RPC shouldn't hide the other networks' balances
+        let balance_eth = match fetch_balance(&network.rpc_url, address).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                tracing::warn!(network = network.name, error = %e, "Failed to fetch wallet balance");
+                summary.failed += 1;
+                continue;
+            }
+        };
+
+        let check = record(db, network.name, address, balance_eth, network.threshold_eth, now).await?;
+        summary.checked += 1;
+        if check.low {
+            summary.low += 1;
+            tracing::warn!(
+                network = network.name,
+                address = %address,
+                balance_eth = %balance_eth,
+                threshold_eth = %network.threshold_eth,
+                "Signer wallet balance is low - transactions will fail once it runs out"
+            );
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn networks(settings: &[(&str, &str)]) -> Vec<MonitoredNetwork> {
+        let settings: HashMap<String, String> = settings.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        networks_from_values(|var| settings.get(var).cloned())
+    }
+
+    #[test]
+    fn test_networks_with_rpc_configured() {
+        assert!(networks(&[]).is_empty());
+
+        let configured = networks(&[("ARB_RPC_URL", "http://arb"), ("ORBIT_RPC_URL", "http://orbit")]);
+        let names: Vec<_> = configured.iter().map(|n| n.name).collect();
+        assert_eq!(names, vec!["arbitrum", "orbit"]);
+        assert!(configured.iter().all(|n| n.threshold_eth == DEFAULT_THRESHOLD_ETH));
+    }
+
+    #[test]
+    fn test_network_thresholds() {
+        let configured = networks(&[
+            ("ARB_RPC_URL", "http://arb"),
+            ("BASE_RPC_URL", "http://base"),
+            ("ORBIT_RPC_URL", "http://orbit"),
+            ("WALLET_BALANCE_MIN_ETH", "0.2"),
+            ("WALLET_BALANCE_MIN_ETH_BASE", "0.01"),
+            ("WALLET_BALANCE_MIN_ETH_ORBIT", "lots"),
+        ]);
+        let thresholds: Vec<_> = configured.iter().map(|n| (n.name, n.threshold_eth)).collect();
+        assert_eq!(thresholds, vec![("arbitrum", dec!(0.2)), ("base", dec!(0.01)), ("orbit", dec!(0.2))]);
+    }
+}
@@ -0,0 +1,375 @@
+//! Staking/dividend yield accrual
+//!
+//! Constituents like staked ETH or SOL earn a yield that a price-only NAV
+//! misses. coin_yield_rates holds one annual yield per coin, set manually
+//! (PUT /admin/yield-rates/{coin_id}) or synced daily from DefiLlama pool
+//! APYs; a manual rate always wins over a synced one.
+//!
+//! For indexes with index_metadata.accrue_yield, a constituent's quantity
+//! grows between rebalances as `quantity × (1 + apy / 100) ^ (days / 365)`,
+//! days counted from the rebalance. The daily NAV (daily_prices) uses the
+//! accrued quantities, and so does the portfolio value the next rebalance
+//! starts from, so the yield carries over.
+//!
+//! Configuration (environment):
+//! - `STAKING_APY_POOLS` - coins synced, as `coin_id=pool_id` pairs separated
+//!   by commas, e.g. `ethereum=747c1d2a-c668-4682-b9f9-296708a3dd90`
+//! - `DEFILLAMA_YIELDS_URL` - DefiLlama yields API base URL (default https://yields.llama.fi)
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::Deserialize;
+
+use crate::entities::{coin_yield_rates, prelude::*};
+
+const ENV_POOLS: &str = "STAKING_APY_POOLS";
+const ENV_DEFILLAMA_URL: &str = "DEFILLAMA_YIELDS_URL";
+
+const DEFAULT_DEFILLAMA_URL: &str = "https://yields.llama.fi";
+
+/// Rates above this are rejected as data errors
+const MAX_APY_PCT: Decimal = Decimal::ONE_HUNDRED;
+
+/// Values of coin_yield_rates.source
+pub mod sources {
+    pub const MANUAL: &str = "manual";
+    pub const DEFILLAMA: &str = "defillama";
+}
+
+#[derive(Debug)]
+pub enum YieldRateError {
+    InvalidRate(Decimal),
+    Database(sea_orm::DbErr),
+}
+
+impl std::fmt::Display for YieldRateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YieldRateError::InvalidRate(apy) => {
+                write!(f, "apyPct must be between 0 and {}, got {}", MAX_APY_PCT, apy)
+            }
+            YieldRateError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for YieldRateError {}
+
+impl From<sea_orm::DbErr> for YieldRateError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        YieldRateError::Database(e)
+    }
+}
+
+fn validate_apy(apy_pct: Decimal) -> Result<(), YieldRateError> {
+    if apy_pct.is_sign_negative() || apy_pct > MAX_APY_PCT {
+        return Err(YieldRateError::InvalidRate(apy_pct));
+    }
+    Ok(())
+}
+
+/// Growth of a quantity earning `apy_pct` a year over `days` days
+pub fn growth_factor(apy_pct: Decimal, days: i64) -> Decimal {
+    if days <= 0 || apy_pct <= Decimal::ZERO {
+        return Decimal::ONE;
+    }
+    let rate = (Decimal::ONE + apy_pct / Decimal::ONE_HUNDRED).to_f64().unwrap_or(1.0);
+    Decimal::from_f64_retain(rate.powf(days as f64 / 365.0))
+        .map(|factor| factor.round_dp(12))
+        .unwrap_or(Decimal::ONE)
+}
+
+/// Yield accrual of one rebalance period of an index
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct YieldAccrual {
+    /// coin_id to APY in percent; empty when the index doesn't accrue
+    rates: HashMap<String, Decimal>,
+    since: Option<NaiveDate>,
+}
+
+impl YieldAccrual {
+    /// Accrual from `since` (the rebalance date) of the coins of an index,
+    /// nothing if the index doesn't accrue yield
+    pub async fn for_index(
+        db: &DatabaseConnection,
+        index_id: i32,
+        coin_ids: &[String],
+        since: NaiveDate,
+    ) -> Result<Self, sea_orm::DbErr> {
+        let accrues = IndexMetadata::find_by_id(index_id)
+            .one(db)
+            .await?
+            .is_some_and(|index| index.accrue_yield);
+        if !accrues || coin_ids.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let rates = CoinYieldRates::find()
+            .filter(coin_yield_rates::Column::CoinId.is_in(coin_ids.iter().cloned()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|rate| (rate.coin_id, rate.apy_pct))
+            .collect();
+        Ok(Self { rates, since: Some(since) })
+    }
+
+    /// `quantity` of `coin_id` with the yield accrued until `on`
+    pub fn quantity(&self, coin_id: &str, quantity: Decimal, on: NaiveDate) -> Decimal {
+        match (self.rates.get(coin_id), self.since) {
+            (Some(apy_pct), Some(since)) => quantity * growth_factor(*apy_pct, (on - since).num_days()),
+            _ => quantity,
+        }
+    }
+}
+
+/// All rates, by coin
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<coin_yield_rates::Model>, sea_orm::DbErr> {
+    CoinYieldRates::find()
+        .order_by_asc(coin_yield_rates::Column::CoinId)
+        .all(db)
+        .await
+}
+
+async fn upsert(
+    db: &DatabaseConnection,
+    coin_id: &str,
+    apy_pct: Decimal,
+    source: &str,
+    source_ref: Option<String>,
+    now: NaiveDateTime,
+) -> Result<coin_yield_rates::Model, sea_orm::DbErr> {
+    let rate = coin_yield_rates::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        apy_pct: Set(apy_pct),
+        source: Set(source.to_string()),
+        source_ref: Set(source_ref),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+
+    CoinYieldRates::insert(rate)
+        .on_conflict(
+            OnConflict::column(coin_yield_rates::Column::CoinId)
+                .update_columns([
+                    coin_yield_rates::Column::ApyPct,
+                    coin_yield_rates::Column::Source,
+                    coin_yield_rates::Column::SourceRef,
+                    coin_yield_rates::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await
+}
+
+/// Set a coin's rate by hand; the sync leaves it alone from then on
+pub async fn set_manual(
+    db: &DatabaseConnection,
+    coin_id: &str,
+    apy_pct: Decimal,
+    now: NaiveDateTime,
+) -> Result<coin_yield_rates::Model, YieldRateError> {
+    validate_apy(apy_pct)?;
+    Ok(upsert(db, coin_id, apy_pct, sources::MANUAL, None, now).await?)
+}
+
+/// Remove a coin's rate; returns whether it had one
+pub async fn remove(db: &DatabaseConnection, coin_id: &str) -> Result<bool, sea_orm::DbErr> {
+    let result = CoinYieldRates::delete_many()
+        .filter(coin_yield_rates::Column::CoinId.eq(coin_id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YieldSyncConfig {
+    /// (coin_id, DefiLlama pool id)
+    pub pools: Vec<(String, String)>,
+    pub defillama_url: String,
+}
+
+impl YieldSyncConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(ENV_POOLS).ok().as_deref(),
+            std::env::var(ENV_DEFILLAMA_URL).ok().as_deref(),
+        )
+    }
+
+    /// Build from raw setting values; malformed pool entries are skipped
+    fn from_values(pools: Option<&str>, defillama_url: Option<&str>) -> Self {
+        let pools = pools
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| match entry.split_once('=') {
+                Some((coin_id, pool)) if !coin_id.trim().is_empty() && !pool.trim().is_empty() => {
+                    Some((coin_id.trim().to_string(), pool.trim().to_string()))
+                }
+                _ => {
+                    tracing::warn!("Invalid {} entry '{}', expected coin_id=pool_id", ENV_POOLS, entry);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            pools,
+            defillama_url: defillama_url
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .unwrap_or_else(|| DEFAULT_DEFILLAMA_URL.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolChart {
+    data: Vec<PoolChartPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoolChartPoint {
+    apy: Option<f64>,
+}
+
+/// Client for the DefiLlama yields API
+pub struct DefiLlamaYields {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl DefiLlamaYields {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Latest APY of a pool in percent, None if it has no data
+    pub async fn latest_apy(&self, pool: &str) -> Result<Option<Decimal>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.client.get(format!("{}/chart/{}", self.base_url, pool)).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("DefiLlama API error {}: {}", status, body).into());
+        }
+
+        let chart: PoolChart = response.json().await?;
+        Ok(chart
+            .data
+            .iter()
+            .rev()
+            .find_map(|point| point.apy)
+            .and_then(Decimal::from_f64_retain)
+            .map(|apy| apy.round_dp(6)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub updated: usize,
+    /// Coins with a manual rate
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Refresh the rate of every configured pool's coin, except manual ones
+pub async fn sync(
+    db: &DatabaseConnection,
+    source: &DefiLlamaYields,
+    config: &YieldSyncConfig,
+    now: NaiveDateTime,
+) -> Result<SyncSummary, sea_orm::DbErr> {
+    let manual: Vec<String> = CoinYieldRates::find()
+        .filter(coin_yield_rates::Column::Source.eq(sources::MANUAL))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|rate| rate.coin_id)
+        .collect();
+
+    let mut summary = SyncSummary::default();
+    for (coin_id, pool) in &config.pools {
+        if manual.contains(coin_id) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        let apy_pct = match source.latest_apy(pool).await {
+            Ok(Some(apy)) if validate_apy(apy).is_ok() => apy,
+            Ok(apy) => {
+                tracing::warn!(coin_id = %coin_id, pool = %pool, apy = ?apy, "No usable APY for pool");
+                summary.failed += 1;
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!(coin_id = %coin_id, pool = %pool, error = %e, "Failed to fetch pool APY");
+                summary.failed += 1;
+                continue;
+            }
+        };
+
+        upsert(db, coin_id, apy_pct, sources::DEFILLAMA, Some(pool.clone()), now).await?;
+        summary.updated += 1;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_growth_factor() {
+        assert_eq!(growth_factor(dec!(4), 0), Decimal::ONE);
+        assert_eq!(growth_factor(Decimal::ZERO, 30), Decimal::ONE);
+        assert_eq!(growth_factor(dec!(4), 365).round_dp(9), dec!(1.04));
+        // Compounds: half a year is the square root of a year
+        let half = growth_factor(dec!(4), 365).to_f64().unwrap().sqrt();
+        assert!((growth_factor(dec!(4), 182).to_f64().unwrap() - half).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_accrued_quantity() {
+        let since = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let accrual = YieldAccrual {
+            rates: HashMap::from([("ethereum".to_string(), dec!(3))]),
+            since: Some(since),
+        };
+        let a_year_later = NaiveDate::from_ymd_opt(2027, 1, 1).unwrap();
+
+        assert_eq!(accrual.quantity("ethereum", dec!(2), a_year_later).round_dp(9), dec!(2.06));
+        assert_eq!(accrual.quantity("bitcoin", dec!(2), a_year_later), dec!(2));
+        assert_eq!(YieldAccrual::default().quantity("ethereum", dec!(2), a_year_later), dec!(2));
+    }
+
+    #[test]
+    fn test_sync_config_from_values() {
+        let config = YieldSyncConfig::from_values(Some("ethereum=pool-a, solana = pool-b,broken,=x"), None);
+        assert_eq!(
+            config.pools,
+            vec![
+                ("ethereum".to_string(), "pool-a".to_string()),
+                ("solana".to_string(), "pool-b".to_string())
+            ]
+        );
+        assert_eq!(config.defillama_url, DEFAULT_DEFILLAMA_URL);
+
+        let config = YieldSyncConfig::from_values(None, Some("http://localhost:1234/"));
+        assert!(config.pools.is_empty());
+        assert_eq!(config.defillama_url, "http://localhost:1234");
+    }
+}
@@ -0,0 +1,100 @@
+//! Integration tests for alert delivery and the cash buffer depeg check
+
+mod common;
+
+use axum::Router;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use wiremock::{
+    matchers::{body_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins_historical_prices, index_metadata, prelude::*};
+use indexmaker_backend::services::alerting::{Alert, AlertConfig, AlertKind, Alerter, Severity};
+use indexmaker_backend::services::cash_buffer;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_alerts_are_posted_to_their_channel() {
+    let webhooks = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/ops"))
+        .and(body_json(serde_json::json!({ "text": "⚠️ *Job rebalance_sync failed*\ntimeout" })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&webhooks)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/trading"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(2)
+        .mount(&webhooks)
+        .await;
+
+    let alerter = Alerter::new(AlertConfig::from_values(
+        Some(&format!("ops={0}/ops,trading={0}/trading", webhooks.uri())),
+        None,
+    ));
+    let failure = Alert::new(AlertKind::JobFailure, Severity::Warning, "Job rebalance_sync failed", "timeout");
+    assert!(alerter.send(&failure).await.unwrap());
+    // A repeat within the hour isn't posted again
+    assert!(!alerter.send(&failure).await.unwrap());
+
+    // A failed post doesn't count, so it's retried
+    let depeg = Alert::new(AlertKind::Depeg, Severity::Critical, "USDC is off its peg", "");
+    assert!(alerter.send(&depeg).await.is_err());
+    assert!(alerter.send(&depeg).await.is_err());
+
+    // No webhook for the product channel
+    let deployed = Alert::new(AlertKind::RebalanceDeployed, Severity::Info, "Rebalance deployed", "");
+    assert!(!alerter.send(&deployed).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_depegged_buffer_stablecoins() {
+    let app = TestApp::spawn(Router::new()).await;
+    let date = app.seed_start;
+
+    assert!(cash_buffer::depegged(&app.db, date).await.unwrap().is_empty());
+
+    let index = IndexMetadata::find_by_id(SEED_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    let mut index: index_metadata::ActiveModel = index.into();
+    index.cash_buffer_pct = Set(Some(dec!(5)));
+    index.cash_buffer_symbol = Set(Some("usdc".to_string()));
+    index.update(&app.db).await.unwrap();
+
+    // The real USDC and a tiny token sharing its symbol
+    for (coin_id, price, market_cap) in [("usd-coin", dec!(0.97), dec!(60000000000)), ("fake-usdc", dec!(1), dec!(1000))] {
+        coins_historical_prices::ActiveModel {
+            coin_id: Set(coin_id.to_string()),
+            symbol: Set("USDC".to_string()),
+            date: Set(date),
+            price: Set(price),
+            market_cap: Set(Some(market_cap)),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(
+        cash_buffer::depegged(&app.db, date).await.unwrap(),
+        vec![("USDC".to_string(), dec!(0.97))]
+    );
+    // Within the threshold the next day
+    coins_historical_prices::ActiveModel {
+        coin_id: Set("usd-coin".to_string()),
+        symbol: Set("USDC".to_string()),
+        date: Set(date + chrono::Duration::days(1)),
+        price: Set(dec!(0.995)),
+        market_cap: Set(Some(dec!(60000000000))),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    assert!(cash_buffer::depegged(&app.db, date + chrono::Duration::days(1)).await.unwrap().is_empty());
+}
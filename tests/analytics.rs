@@ -0,0 +1,143 @@
+//! Integration tests for return correlations and rolling stats
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::handlers::analytics::{get_correlation, get_rolling_stats};
+use indexmaker_backend::services::rolling_stats::{self, CATCH_UP_DAYS, WINDOWS};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn post_json(app: &TestApp, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/analytics/correlation")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_correlation_of_seeded_coins() {
+    let app = TestApp::spawn(Router::new().route("/api/analytics/correlation", post(get_correlation))).await;
+    let end = app.seed_start + Duration::days(30);
+
+    let (status, body) = post_json(
+        &app,
+        json!({ "coinIds": ["bitcoin", "ethereum", "unknown-coin"], "window": 20, "endDate": end }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["window"], 20);
+    assert_eq!(body["startDate"], (end - Duration::days(19)).to_string());
+    assert_eq!(body["missing"], json!(["unknown-coin"]));
+
+    let coins = body["coins"].as_array().unwrap();
+    assert_eq!(coins.len(), 2);
+    for coin in coins {
+        assert_eq!(coin["observations"], 20);
+        let daily = coin["dailyVolatility"].as_f64().unwrap();
+        assert!(daily > 0.0);
+        assert!((coin["annualizedVolatility"].as_f64().unwrap() - daily * 365f64.sqrt()).abs() < 1e-9);
+    }
+
+    // The seed moves every coin by the same percentages
+    let matrix = body["correlation"].as_array().unwrap();
+    assert_eq!(matrix[0][0], 1.0);
+    assert!((matrix[0][1].as_f64().unwrap() - 1.0).abs() < 1e-6);
+    assert_eq!(matrix[0][1], matrix[1][0]);
+
+    // Returns before the seeded history are missing
+    let end = app.seed_start + Duration::days(4);
+    let (_, body) = post_json(&app, json!({ "coinIds": ["bitcoin"], "window": 10, "endDate": end })).await;
+    assert_eq!(body["coins"][0]["observations"], 4);
+}
+
+#[tokio::test]
+async fn test_correlation_validation() {
+    let app = TestApp::spawn(Router::new().route("/api/analytics/correlation", post(get_correlation))).await;
+
+    let (status, body) = post_json(&app, json!({ "coinIds": [" "] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("coinIds"));
+
+    let (status, _) = post_json(&app, json!({ "coinIds": ["bitcoin"], "window": 1 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = post_json(&app, json!({ "coinIds": ["bitcoin"], "endDate": "2999-01-01" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_rolling_stats_are_computed_incrementally() {
+    let app = TestApp::spawn(
+        Router::new()
+            .route("/api/analytics/correlation", post(get_correlation))
+            .route("/api/analytics/rolling-stats", get(get_rolling_stats)),
+    )
+    .await;
+    let now = Utc::now().naive_utc();
+    let yesterday = now.date() - Duration::days(1);
+
+    let (status, _) = app.get("/api/analytics/rolling-stats?subject=coin&id=bitcoin").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let summary = rolling_stats::run(&app.db, yesterday, now).await.unwrap();
+    assert!(summary.stored > 0);
+    assert_eq!(summary.skipped, 0);
+    // Every subject priced on every catch-up day gets every window
+    assert_eq!(summary.stored % (WINDOWS.len() * CATCH_UP_DAYS as usize), 0);
+
+    // Nothing left to do on a second run
+    let again = rolling_stats::run(&app.db, yesterday, now).await.unwrap();
+    assert_eq!(again.stored, 0);
+    assert_eq!(again.skipped, summary.stored);
+
+    let body = app.get_json("/api/analytics/rolling-stats?subject=coin&id=bitcoin").await;
+    assert_eq!(body["asOf"], yesterday.to_string());
+    let windows = body["windows"].as_array().unwrap();
+    assert_eq!(windows.len(), WINDOWS.len());
+    assert_eq!(windows[0]["windowDays"], 30);
+    assert_eq!(windows[0]["observations"], 30);
+    // The longer windows reach back before the seeded history
+    let seeded_returns = (yesterday - app.seed_start).num_days();
+    assert_eq!(windows[2]["observations"], seeded_returns);
+    assert!(windows[2]["maxDrawdown"].as_f64().unwrap() >= 0.0);
+
+    let body = app
+        .get_json(&format!("/api/analytics/rolling-stats?subject=index&id={}", SEED_INDEX_ID))
+        .await;
+    assert!(body["windows"][0]["volatility"].as_f64().unwrap() > 0.0);
+
+    // An earlier date gets the latest stats up to it
+    let date = yesterday - Duration::days(2);
+    let body = app
+        .get_json(&format!("/api/analytics/rolling-stats?subject=coin&id=bitcoin&date={}", date))
+        .await;
+    assert_eq!(body["asOf"], date.to_string());
+
+    // The correlation endpoint reuses stored volatilities
+    let (_, stored) = app.get("/api/analytics/rolling-stats?subject=coin&id=ethereum").await;
+    let stored: Value = serde_json::from_str(&stored).unwrap();
+    let (status, body) = post_json(&app, json!({ "coinIds": ["ethereum"], "window": 30, "endDate": yesterday })).await;
+    assert_eq!(status, StatusCode::OK);
+    let computed = body["coins"][0]["dailyVolatility"].as_f64().unwrap();
+    assert!((computed - stored["windows"][0]["volatility"].as_f64().unwrap()).abs() < 1e-9);
+
+    let (status, _) = app.get("/api/analytics/rolling-stats?subject=category&id=bitcoin").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
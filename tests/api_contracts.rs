@@ -0,0 +1,253 @@
+//! Snapshot tests for the JSON shape of public API responses
+//!
+//! The frontend and MCP server depend on these field names and types. A
+//! failing snapshot means the wire format changed: if that was intended,
+//! review the diff and accept it (`cargo insta review`, or rerun with
+//! `INSTA_UPDATE=always`), and update the consumers.
+
+use chrono::NaiveDate;
+use insta::assert_json_snapshot;
+use rust_decimal_macros::dec;
+
+use indexmaker_backend::models::index::{
+    CollateralToken, ConstituentPriceInfo, IndexConfigResponse, IndexLastPriceResponse, IndexListEntry,
+    IndexListResponse, Performance, PriceSource, Ratings,
+};
+use indexmaker_backend::models::itp::{CreateItpResponse, CreateItpSyncResponse, ItpErrorResponse, ItpStatusResponse, ItpValidationViolation};
+use indexmaker_backend::models::itp_listing::{ItpListEntry, ItpListResponse};
+use indexmaker_backend::models::market_cap::{TopCategoryCoin, TopCategoryResponse};
+use indexmaker_backend::models::methodology::MethodologyVersionRef;
+
+const ADDRESS: &str = "0x5eed000000000000000000000000000000000001";
+
+#[test]
+fn index_list_response() {
+    let full = IndexListEntry {
+        index_id: 21,
+        name: "Top 10 Layer 1".to_string(),
+        address: ADDRESS.to_string(),
+        ticker: "L1TOP".to_string(),
+        curator: "0x0000000000000000000000000000000000000002".to_string(),
+        total_supply: 1250.5,
+        total_supply_usd: 1313025.0,
+        ytd_return: 12.5,
+        collateral: vec![CollateralToken { name: "bitcoin".to_string(), logo: "https://logo/btc.png".to_string() }],
+        management_fee: 2,
+        asset_class: Some("Cryptocurrencies".to_string()),
+        inception_date: Some("2025-01-01".to_string()),
+        category: Some("layer-1".to_string()),
+        ratings: Some(Ratings {
+            overall_rating: "A+".to_string(),
+            expense_rating: "B".to_string(),
+            risk_rating: "C".to_string(),
+        }),
+        performance: Some(Performance {
+            ytd_return: 12.5,
+            one_year_return: 40.0,
+            three_year_return: 0.0,
+            five_year_return: 0.0,
+            ten_year_return: 0.0,
+        }),
+        index_price: Some(dec!(1050.25)),
+        description: Some("Les 10 plus grandes blockchains de couche 1".to_string()),
+        locale: Some("fr".to_string()),
+    };
+    // Optional fields are omitted, not null
+    let minimal = IndexListEntry { index_id: 22, ..IndexListEntry::default() };
+
+    assert_json_snapshot!(IndexListResponse { indexes: vec![full, minimal] });
+}
+
+#[test]
+fn index_last_price_response() {
+    let response = IndexLastPriceResponse {
+        index_id: 21,
+        timestamp: 1735689600,
+        last_price: dec!(1050.25),
+        last_bid: None,
+        last_ask: Some(dec!(1051)),
+        constituents: vec![ConstituentPriceInfo {
+            coin_id: "bitcoin".to_string(),
+            symbol: "BTC".to_string(),
+            quantity: "0.0105".to_string(),
+            weight: "1".to_string(),
+            price: dec!(100000),
+            value: dec!(1050),
+            fallback: None,
+            price_source: Some(PriceSource::DbCache),
+            price_timestamp: Some(1735689600),
+        }],
+        stale: false,
+    };
+
+    assert_json_snapshot!(response);
+}
+
+#[test]
+fn index_config_response() {
+    assert_json_snapshot!(IndexConfigResponse {
+        index_id: 21,
+        symbol: "L1TOP".to_string(),
+        name: "Top 10 Layer 1".to_string(),
+        address: ADDRESS.to_string(),
+        initial_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        initial_price: "1000".to_string(),
+        exchanges_allowed: vec!["binance".to_string(), "bitget".to_string()],
+        exchange_trading_fees: "0.001".to_string(),
+        exchange_avg_spread: "0.0005".to_string(),
+        rebalance_period: 30,
+        pricing_cutoff: "00:00 UTC".to_string(),
+        methodology: Some(MethodologyVersionRef {
+            version: 2,
+            effective_from: NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            url: "/indexes/21/methodology/2".to_string(),
+        }),
+        description: None,
+        locale: None,
+    });
+}
+
+#[test]
+fn top_category_response() {
+    let response = TopCategoryResponse {
+        category_id: "layer-1".to_string(),
+        category_name: "Layer 1 (L1)".to_string(),
+        date: "2025-01-12".to_string(),
+        top: 2,
+        coins: vec![
+            TopCategoryCoin {
+                rank: 1,
+                coin_id: "bitcoin".to_string(),
+                symbol: "BTC".to_string(),
+                name: "Bitcoin".to_string(),
+                market_cap: 1.9e12,
+                price: 95000.5,
+                volume_24h: 3.5e10,
+                logo: Some("https://logo/btc.png".to_string()),
+            },
+            TopCategoryCoin {
+                rank: 2,
+                coin_id: "ethereum".to_string(),
+                symbol: "ETH".to_string(),
+                name: "Ethereum".to_string(),
+                market_cap: 4.0e11,
+                price: 3300.0,
+                volume_24h: 2.0e10,
+                logo: None,
+            },
+        ],
+    };
+
+    assert_json_snapshot!(response);
+}
+
+#[test]
+fn itp_list_response() {
+    let full = ItpListEntry {
+        id: 1,
+        name: "Top 10 DeFi Index".to_string(),
+        symbol: "DEFI10".to_string(),
+        orbit_address: ADDRESS.to_string(),
+        arbitrum_address: Some("0x0000000000000000000000000000000000000003".to_string()),
+        index_id: Some(7),
+        current_price: Some(1.05),
+        price_24h_change: Some(-0.5),
+        initial_price: Some("1000000000000000000".to_string()),
+        total_supply: "2500000000000000000000".to_string(),
+        methodology: Some("Equal weight, monthly rebalance".to_string()),
+        description: Some("Top DeFi tokens".to_string()),
+        assets: Some(vec!["UNI".to_string(), "AAVE".to_string()]),
+        weights: Some(vec![0.5, 0.5]),
+        aum: Some(2625.0),
+        admin_address: Some("0x0000000000000000000000000000000000000004".to_string()),
+        created_at: 1735689600,
+    };
+    let minimal = ItpListEntry {
+        id: 2,
+        name: "Pending ITP".to_string(),
+        symbol: "PEND".to_string(),
+        orbit_address: ADDRESS.to_string(),
+        arbitrum_address: None,
+        index_id: None,
+        current_price: None,
+        price_24h_change: None,
+        initial_price: None,
+        total_supply: "0".to_string(),
+        methodology: None,
+        description: None,
+        assets: None,
+        weights: None,
+        aum: None,
+        admin_address: None,
+        created_at: 1735776000,
+    };
+
+    assert_json_snapshot!(ItpListResponse {
+        itps: vec![full, minimal],
+        total: 2,
+        limit: 20,
+        offset: 0,
+        total_aum: Some(2625.0),
+    });
+}
+
+#[test]
+fn itp_create_responses() {
+    let tx_hash = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+
+    assert_json_snapshot!("itp_create_async", CreateItpResponse {
+        tx_hash: tx_hash.to_string(),
+        nonce: 3,
+        confirmed_at_block: 123456,
+        estimated_completion_time: 60,
+        status: "pending".to_string(),
+    });
+    assert_json_snapshot!("itp_create_sync", CreateItpSyncResponse {
+        tx_hash: tx_hash.to_string(),
+        nonce: 3,
+        orbit_address: ADDRESS.to_string(),
+        arbitrum_address: "0x0000000000000000000000000000000000000003".to_string(),
+        status: "completed".to_string(),
+    });
+    assert_json_snapshot!("itp_error", ItpErrorResponse {
+        error: "Invalid weights".to_string(),
+        code: Some("INVALID_WEIGHTS".to_string()),
+        violations: Vec::new(),
+    });
+    assert_json_snapshot!("itp_error_violations", ItpErrorResponse {
+        error: "2 asset violation(s)".to_string(),
+        code: Some("INVALID_ASSETS".to_string()),
+        violations: vec![
+            ItpValidationViolation {
+                index: 0,
+                asset_id: 7,
+                symbol: Some("USDT".to_string()),
+                code: "EXCLUDED_ASSET".to_string(),
+                message: "USDT is on the exclusion list".to_string(),
+            },
+            ItpValidationViolation {
+                index: 1,
+                asset_id: 999,
+                symbol: None,
+                code: "UNKNOWN_ASSET".to_string(),
+                message: "Asset 999 is not in the asset registry".to_string(),
+            },
+        ],
+    });
+}
+
+#[test]
+fn itp_status_response() {
+    assert_json_snapshot!("itp_status_pending", ItpStatusResponse {
+        nonce: 3,
+        status: "pending".to_string(),
+        orbit_address: None,
+        arbitrum_address: None,
+    });
+    assert_json_snapshot!("itp_status_completed", ItpStatusResponse {
+        nonce: 3,
+        status: "completed".to_string(),
+        orbit_address: Some(ADDRESS.to_string()),
+        arbitrum_address: Some("0x0000000000000000000000000000000000000003".to_string()),
+    });
+}
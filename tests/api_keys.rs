@@ -0,0 +1,93 @@
+//! Integration tests for API key tiers, quotas and usage metering
+
+mod common;
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::Utc;
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::handlers::metering::meter;
+use indexmaker_backend::services::api_keys::{self, ApiKeyTier};
+
+fn router(app: &TestApp) -> Router {
+    Router::new()
+        .route("/indexes", get(|| async { "[]" }).post(|| async { "created" }))
+        .route("/admin/api-usage", get(|| async { "usage" }))
+        .layer(axum::middleware::from_fn_with_state(app.state.clone(), meter))
+        .with_state(app.state.clone())
+}
+
+async fn send(router: &Router, method: Method, uri: &str, key: Option<&str>) -> axum::response::Response {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        request = request.header("x-api-key", key);
+    }
+    router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+/// Usage is recorded after the response; wait for it to land
+async fn wait_for_requests(app: &TestApp, api_key_id: i32, expected: i64) {
+    let today = Utc::now().date_naive();
+    for _ in 0..50 {
+        if api_keys::requests_on(&app.db, api_key_id, today).await.unwrap() >= expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("usage of key {} never reached {}", api_key_id, expected);
+}
+
+#[tokio::test]
+async fn test_public_key_is_read_only_and_metered() {
+    let app = TestApp::spawn(Router::new()).await;
+    let router = router(&app);
+    let (api_key, key) = api_keys::create(&app.db, "partner-dashboard", ApiKeyTier::Public, Some(2)).await.unwrap();
+
+    // Anonymous requests aren't affected
+    assert_eq!(send(&router, Method::POST, "/indexes", None).await.status(), StatusCode::OK);
+    assert_eq!(send(&router, Method::GET, "/indexes", Some("imk_bogus")).await.status(), StatusCode::UNAUTHORIZED);
+
+    assert_eq!(send(&router, Method::GET, "/indexes", Some(&key)).await.status(), StatusCode::OK);
+    assert_eq!(send(&router, Method::POST, "/indexes", Some(&key)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(send(&router, Method::GET, "/admin/api-usage", Some(&key)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(send(&router, Method::GET, "/indexes", Some(&key)).await.status(), StatusCode::OK);
+    wait_for_requests(&app, api_key.id, 2).await;
+
+    // Over the daily quota of 2
+    let response = send(&router, Method::GET, "/indexes", Some(&key)).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+    let report = api_keys::usage_report(&app.db, 7, None, Utc::now().date_naive()).await.unwrap();
+    assert_eq!(report.keys.len(), 1);
+    assert_eq!(report.keys[0].requests_today, 2);
+    assert_eq!(report.keys[0].endpoints[0].endpoint, "/indexes");
+    assert_eq!(report.keys[0].endpoints[0].requests, 2);
+}
+
+#[tokio::test]
+async fn test_admin_tier_writes_and_revocation() {
+    let app = TestApp::spawn(Router::new()).await;
+    let router = router(&app);
+    let (api_key, key) = api_keys::create(&app.db, "internal-tools", ApiKeyTier::Admin, None).await.unwrap();
+    assert_eq!(api_key.daily_quota, None);
+
+    assert_eq!(send(&router, Method::POST, "/indexes", Some(&key)).await.status(), StatusCode::OK);
+
+    // A revoked key stops working once its cached lookup expires; a key
+    // never looked up before is rejected immediately
+    let (other, other_key) = api_keys::create(&app.db, "retired", ApiKeyTier::Partner, None).await.unwrap();
+    assert_eq!(other.daily_quota, Some(100_000));
+    let revoked = api_keys::revoke(&app.db, other.id).await.unwrap().unwrap();
+    assert!(!revoked.active && revoked.revoked_at.is_some());
+    assert_eq!(send(&router, Method::GET, "/indexes", Some(&other_key)).await.status(), StatusCode::UNAUTHORIZED);
+    assert!(api_keys::revoke(&app.db, 999_999).await.unwrap().is_none());
+}
@@ -0,0 +1,183 @@
+//! Integration tests for Sign-In-With-Ethereum sessions
+
+mod common;
+
+use alloy::signers::{local::PrivateKeySigner, SignerSync};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+use common::{TestApp, TEST_SIWE_DOMAIN};
+use indexmaker_backend::handlers::auth::{create_nonce, verify};
+use indexmaker_backend::handlers::deposit::get_deposit_transaction_data;
+use indexmaker_backend::services::api_keys::{self, ApiKeyTier};
+use indexmaker_backend::services::auth::{self, AuthError};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(
+        Router::new()
+            .route("/auth/nonce", post(create_nonce))
+            .route("/auth/verify", post(verify))
+            .route("/get-deposit-transaction-data/{index_id}/{address}", get(get_deposit_transaction_data)),
+    )
+    .await
+}
+
+fn siwe_message(address: &str, nonce: &str) -> String {
+    let now = Utc::now();
+    format!(
+        "{domain} wants you to sign in with your Ethereum account:\n{address}\n\nSign in to IndexMaker\n\n\
+         URI: https://{domain}\nVersion: 1\nChain ID: 8453\nNonce: {nonce}\nIssued At: {issued}\n\
+         Expiration Time: {expires}",
+        domain = TEST_SIWE_DOMAIN,
+        address = address,
+        nonce = nonce,
+        issued = now.to_rfc3339(),
+        expires = (now + Duration::minutes(10)).to_rfc3339(),
+    )
+}
+
+async fn get_deposits(app: &TestApp, address: &str, token: Option<&str>) -> StatusCode {
+    let mut request = Request::builder().uri(format!("/get-deposit-transaction-data/{}/{}", SEED_INDEX_ID, address));
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    app.router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+}
+
+/// Holders listed in the "0x0000" deposit breakdown
+async fn deposit_holders(app: &TestApp, api_key: Option<&str>) -> Vec<Option<String>> {
+    let mut request = Request::builder().uri(format!("/get-deposit-transaction-data/{}/0x0000", SEED_INDEX_ID));
+    if let Some(api_key) = api_key {
+        request = request.header("x-api-key", api_key);
+    }
+    let response = app.router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let rows: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    rows.iter().map(|row| row["user"].as_str().map(str::to_string)).collect()
+}
+
+#[tokio::test]
+async fn test_sign_in_scopes_deposits_to_own_wallet() {
+    let app = setup_test_app().await;
+    let signer = PrivateKeySigner::random();
+    let address = signer.address().to_checksum(None);
+
+    let nonce = auth::issue_nonce(&app.db, Utc::now().naive_utc()).await.unwrap();
+    let message = siwe_message(&address, &nonce.nonce);
+    let signature = signer.sign_message_sync(message.as_bytes()).unwrap().to_string();
+
+    let session = auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await.unwrap();
+    assert_eq!(session.address, address.to_lowercase());
+
+    // Nonces are single-use
+    let replay = auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await;
+    assert!(matches!(replay, Err(AuthError::InvalidNonce)));
+
+    assert_eq!(get_deposits(&app, &address, Some(&session.token)).await, StatusCode::OK);
+    assert_eq!(get_deposits(&app, &address, None).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(get_deposits(&app, &address, Some("not-a-token")).await, StatusCode::UNAUTHORIZED);
+    let other = PrivateKeySigner::random().address().to_checksum(None);
+    assert_eq!(get_deposits(&app, &other, Some(&session.token)).await, StatusCode::FORBIDDEN);
+    // The public view only has the index totals
+    assert_eq!(get_deposits(&app, "0x0000", None).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_holder_breakdown_requires_admin_credentials() {
+    let app = setup_test_app().await;
+
+    assert_eq!(deposit_holders(&app, None).await, vec![None]);
+    assert_eq!(deposit_holders(&app, Some("not-a-key")).await, vec![None]);
+
+    let (_, key) = api_keys::create(&app.db, "ops", ApiKeyTier::Admin, None).await.unwrap();
+    let mut holders = deposit_holders(&app, Some(key.as_str())).await;
+    holders.sort();
+    assert_eq!(
+        holders,
+        (0..3).map(|n| Some(format!("0x{:040x}", 0xa11ce + n))).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_verify_rejects_foreign_signatures_and_domains() {
+    let app = setup_test_app().await;
+    let signer = PrivateKeySigner::random();
+    let address = signer.address().to_checksum(None);
+    let nonce = auth::issue_nonce(&app.db, Utc::now().naive_utc()).await.unwrap();
+
+    // Signed by another key
+    let message = siwe_message(&address, &nonce.nonce);
+    let signature = PrivateKeySigner::random().sign_message_sync(message.as_bytes()).unwrap().to_string();
+    let result = auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await;
+    assert!(matches!(result, Err(AuthError::InvalidSignature)));
+
+    // Issued for another site
+    let message = message.replace(TEST_SIWE_DOMAIN, "phishing.example");
+    let signature = signer.sign_message_sync(message.as_bytes()).unwrap().to_string();
+    let result = auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await;
+    assert!(matches!(result, Err(AuthError::InvalidMessage(_))));
+
+    // The nonce survived both failures
+    let message = siwe_message(&address, &nonce.nonce);
+    let signature = signer.sign_message_sync(message.as_bytes()).unwrap().to_string();
+    assert!(auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await.is_ok());
+
+    // A nonce that was never issued
+    let message = siwe_message(&address, "deadbeefdeadbeef");
+    let signature = signer.sign_message_sync(message.as_bytes()).unwrap().to_string();
+    let result = auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await;
+    assert!(matches!(result, Err(AuthError::InvalidNonce)));
+}
+
+#[tokio::test]
+async fn test_nonce_endpoint() {
+    let app = setup_test_app().await;
+    let request = Request::builder().method("POST").uri("/auth/nonce").body(Body::empty()).unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let nonce: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(nonce["nonce"].as_str().unwrap().len(), 32);
+    assert!(nonce["expiresAt"].is_string());
+}
+
+#[tokio::test]
+async fn test_verify_rejects_other_chains_and_uris() {
+    let app = setup_test_app().await;
+    let signer = PrivateKeySigner::random();
+    let address = signer.address().to_checksum(None);
+    let nonce = auth::issue_nonce(&app.db, Utc::now().naive_utc()).await.unwrap();
+    let message = siwe_message(&address, &nonce.nonce);
+    let verify_signed = |message: String| {
+        let signature = signer.sign_message_sync(message.as_bytes()).unwrap().to_string();
+        let app = &app;
+        async move { auth::verify(&app.db, &app.state.auth, &message, &signature, Utc::now()).await }
+    };
+
+    // Signed for a chain the backend doesn't serve
+    let result = verify_signed(message.replace("Chain ID: 8453", "Chain ID: 1")).await;
+    assert!(matches!(result, Err(AuthError::InvalidMessage(msg)) if msg.contains("chain id")));
+
+    // Signed on another origin, including one only sharing a prefix
+    let other_site = format!("URI: https://{}.phishing.example", TEST_SIWE_DOMAIN);
+    let result = verify_signed(message.replace(&format!("URI: https://{}", TEST_SIWE_DOMAIN), &other_site)).await;
+    assert!(matches!(result, Err(AuthError::InvalidMessage(msg)) if msg.contains("URI")));
+    let plain_http = format!("URI: http://{}", TEST_SIWE_DOMAIN);
+    let result = verify_signed(message.replace(&format!("URI: https://{}", TEST_SIWE_DOMAIN), &plain_http)).await;
+    assert!(matches!(result, Err(AuthError::InvalidMessage(msg)) if msg.contains("URI")));
+
+    // A path on the origin is fine, and the nonce survived the failures
+    let with_path = format!("URI: https://{}/portfolio", TEST_SIWE_DOMAIN);
+    let result = verify_signed(message.replace(&format!("URI: https://{}", TEST_SIWE_DOMAIN), &with_path)).await;
+    assert!(result.is_ok(), "{:?}", result.err());
+}
@@ -0,0 +1,33 @@
+//! Integration tests for the background task queue
+
+mod common;
+
+use axum::Router;
+
+use common::TestApp;
+use indexmaker_backend::services::background_tasks::{self, statuses, task_types};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_deferred_task_does_not_block_the_queue() {
+    let app = TestApp::spawn(Router::new()).await;
+    let first = background_tasks::enqueue(&app.db, task_types::INDEX_BACKFILL, Some(SEED_INDEX_ID), None).await.unwrap();
+    let second = background_tasks::enqueue(&app.db, task_types::INDEX_BACKFILL, None, None).await.unwrap();
+
+    let claimed = background_tasks::claim_next(&app.db).await.unwrap().unwrap();
+    assert_eq!(claimed.id, first.id);
+    background_tasks::defer(&app.db, claimed).await.unwrap();
+
+    // The older, deferred task waits its turn instead of being handed back
+    let claimed = background_tasks::claim_next(&app.db).await.unwrap().unwrap();
+    assert_eq!(claimed.id, second.id);
+    assert!(background_tasks::claim_next(&app.db).await.unwrap().is_none());
+
+    let deferred = background_tasks::latest_for_index(&app.db, SEED_INDEX_ID, task_types::INDEX_BACKFILL)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(deferred.status, statuses::PENDING);
+    assert_eq!(deferred.attempts, 0);
+    assert!(deferred.run_after.is_some());
+}
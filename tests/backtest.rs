@@ -0,0 +1,128 @@
+//! Integration tests for backtest comparisons
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::post,
+    Router,
+};
+use chrono::Duration;
+use http_body_util::BodyExt;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::handlers::backtest::compare_backtests;
+use indexmaker_backend::services::seed::seed_price;
+
+async fn post_json(app: &TestApp, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/backtest/compare")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_compare_scenarios_over_seeded_coins() {
+    let app = TestApp::spawn(Router::new().route("/api/backtest/compare", post(compare_backtests))).await;
+    let end = app.seed_start + Duration::days(56);
+
+    let (status, body) = post_json(
+        &app,
+        json!({
+            "coinIds": ["bitcoin", "ethereum", "solana", "chainlink"],
+            "startDate": app.seed_start,
+            "endDate": end,
+            "scenarios": [
+                { "name": "monthly top 2", "topN": 2 },
+                { "topN": 3, "rebalancePeriodDays": 7, "rankingBuffer": 1, "weightStrategy": "equal" },
+                { "name": "inverse", "topN": 2, "leverageFactor": -1 },
+            ],
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["initialValue"], 1000.0);
+
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    let (monthly, weekly, inverse) = (&results[0], &results[1], &results[2]);
+    assert_eq!(monthly["name"], "monthly top 2");
+    assert_eq!(weekly["name"], "scenario 2");
+    assert_eq!(monthly["scenario"]["rebalancePeriodDays"], 30);
+    assert_eq!(monthly["scenario"]["weightStrategy"], "marketcap");
+
+    assert_eq!(monthly["rebalances"], 2);
+    assert_eq!(weekly["rebalances"], 9);
+    assert_eq!(monthly["avgConstituents"], 2.0);
+    assert_eq!(weekly["avgConstituents"], 3.0);
+    assert_eq!(weekly["constituentChanges"], 0);
+
+    // The seed moves every coin by the same percentages, so weights never
+    // drift and only the initial purchase pays fees (0.1% + 0.05% / 2)
+    let initial_fees = 1000.0 * 0.00125;
+    for result in results {
+        assert!((result["totalFees"].as_f64().unwrap() - initial_fees).abs() < 1e-6, "{}", result);
+        assert!(result["avgTurnover"].as_f64().unwrap() < 1e-9);
+    }
+    let growth = (seed_price(dec!(100), 56) / seed_price(dec!(100), 0)).to_f64().unwrap();
+    let expected = (1000.0 - initial_fees) * growth / 1000.0 - 1.0;
+    assert!((monthly["totalReturn"].as_f64().unwrap() - expected).abs() < 1e-9);
+    assert!(monthly["maxDrawdown"].as_f64().unwrap() > 0.0);
+    assert!(monthly["annualizedVolatility"].as_f64().unwrap() > 0.0);
+
+    // -1x of the same basket, reset daily
+    let mut nav = 1000.0 - initial_fees;
+    for day in 1..=56 {
+        let basket_return = (seed_price(dec!(100), day) / seed_price(dec!(100), day - 1)).to_f64().unwrap() - 1.0;
+        nav *= 1.0 - basket_return;
+    }
+    assert_eq!(inverse["scenario"]["leverageFactor"], "-1");
+    assert!((inverse["totalReturn"].as_f64().unwrap() - (nav / 1000.0 - 1.0)).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_compare_validation() {
+    let app = TestApp::spawn(Router::new().route("/api/backtest/compare", post(compare_backtests))).await;
+    let start = app.seed_start;
+    let request = |scenarios: Value, end| {
+        json!({ "startDate": start, "endDate": end, "scenarios": scenarios })
+    };
+    let end = start + Duration::days(30);
+
+    let (status, body) = post_json(&app, request(json!([]), end)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("scenarios"));
+
+    let (status, body) = post_json(&app, request(json!([{ "name": "big", "topN": 500 }]), end)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().starts_with("big: topN"));
+
+    for scenario in [
+        json!({ "topN": 5, "rebalancePeriodDays": 0 }),
+        json!({ "topN": 5, "weightStrategy": "inverse" }),
+        json!({ "topN": 5, "maxWeightPct": 120 }),
+        json!({ "topN": 5, "tradingFee": 1.5 }),
+        json!({ "topN": 5, "leverageFactor": 5 }),
+        json!({ "topN": 5, "leverageFactor": 0 }),
+    ] {
+        let (status, _) = post_json(&app, request(json!([scenario]), end)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", scenario);
+    }
+
+    let (status, _) = post_json(&app, request(json!([{ "topN": 5 }]), start)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let future = start + Duration::days(365);
+    let (status, _) = post_json(&app, request(json!([{ "topN": 5 }]), future)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,61 @@
+//! Integration tests for the gas spend accounting of sent transactions
+
+mod common;
+
+use alloy::primitives::Address;
+use axum::Router;
+use chrono::{Datelike, NaiveDate, Utc};
+use rust_decimal_macros::dec;
+
+use common::TestApp;
+use indexmaker_backend::services::chain_spend::{self, kinds, ChainTransaction};
+
+fn transaction(tx_hash: u64, kind: &'static str, succeeded: bool) -> ChainTransaction {
+    ChainTransaction {
+        kind,
+        reference: Some("TEST".to_string()),
+        tx_hash: format!("0x{:064x}", tx_hash),
+        chain_id: 42161,
+        from: Address::repeat_byte(1),
+        to: Some(Address::repeat_byte(2)),
+        block_number: 30_000_000,
+        succeeded,
+        gas_used: 400_000,
+        // 0.01 gwei
+        effective_gas_price: 10_000_000,
+    }
+}
+
+#[tokio::test]
+async fn test_monthly_spend_prices_transactions_in_usd() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    let eth_price = chain_spend::eth_price_usd(&app.db, now.date()).await.unwrap().unwrap();
+
+    let created = chain_spend::record(&app.db, transaction(1, kinds::CREATE_ITP, true), now).await.unwrap();
+    assert_eq!(created.cost_eth, dec!(0.000004));
+    assert_eq!(created.eth_price_usd, Some(eth_price));
+    assert_eq!(created.cost_usd, Some((dec!(0.000004) * eth_price).round_dp(6)));
+    chain_spend::record(&app.db, transaction(2, kinds::REBALANCE, false), now).await.unwrap();
+
+    // Sent long before any seeded ETH price
+    let old = NaiveDate::from_ymd_opt(2020, 1, 15).unwrap().and_hms_opt(9, 0, 0).unwrap();
+    let unpriced = chain_spend::record(&app.db, transaction(3, kinds::REBALANCE, true), old).await.unwrap();
+    assert_eq!(unpriced.cost_usd, None);
+
+    let month = now.date().with_day(1).unwrap();
+    let report = chain_spend::monthly_report(&app.db, month).await.unwrap();
+    assert_eq!(report.transaction_count, 2);
+    assert_eq!(report.failed_count, 1);
+    assert_eq!(report.unpriced_count, 0);
+    assert_eq!(report.gas_used, 800_000);
+    assert_eq!(report.cost_eth, dec!(0.000008));
+    assert_eq!(report.cost_usd, created.cost_usd.unwrap() * dec!(2));
+    assert_eq!(report.by_kind.len(), 2);
+    assert_eq!(report.transactions[0].tx_hash, created.tx_hash);
+
+    let old_report = chain_spend::monthly_report(&app.db, chain_spend::parse_month("2020-01").unwrap()).await.unwrap();
+    assert_eq!(old_report.transaction_count, 1);
+    assert_eq!(old_report.unpriced_count, 1);
+    assert_eq!(old_report.cost_usd, dec!(0));
+}
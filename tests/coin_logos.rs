@@ -0,0 +1,88 @@
+//! Integration tests for the coin logo proxy
+
+mod common;
+
+use std::io::Cursor;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use http_body_util::BodyExt;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use tower::ServiceExt;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins, prelude::*};
+use indexmaker_backend::handlers::asset::get_coin_logo;
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .unwrap();
+    data
+}
+
+async fn set_logo_address(app: &TestApp, coin_id: &str, logo_address: String) {
+    let coin = Coins::find().filter(coins::Column::CoinId.eq(coin_id)).one(&app.db).await.unwrap().unwrap();
+    let mut coin: coins::ActiveModel = coin.into();
+    coin.logo_address = Set(Some(logo_address));
+    coin.update(&app.db).await.unwrap();
+}
+
+async fn get_logo(app: &TestApp, uri: &str) -> Response {
+    app.router.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap()
+}
+
+async fn image_size(response: Response) -> (u32, u32) {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let image = image::load_from_memory(&body).unwrap();
+    (image.width(), image.height())
+}
+
+#[tokio::test]
+async fn test_logo_is_fetched_once_and_resized() {
+    let app = TestApp::spawn(Router::new().route("/assets/{coin_id}/logo", get(get_coin_logo))).await;
+    Mock::given(method("GET"))
+        .and(path("/coins/images/877/large/chainlink.png"))
+        .respond_with(ResponseTemplate::new(200).insert_header("content-type", "image/png").set_body_bytes(png(512, 512)))
+        .expect(1)
+        .mount(&app.coingecko)
+        .await;
+    set_logo_address(&app, "chainlink", format!("{}/coins/images/877/large/chainlink.png", app.coingecko.uri())).await;
+
+    let response = get_logo(&app, "/assets/chainlink/logo").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+    assert!(response.headers()[header::CACHE_CONTROL].to_str().unwrap().contains("max-age=604800"));
+    assert_eq!(image_size(response).await, (256, 256));
+
+    // Served from the database from now on
+    assert_eq!(image_size(get_logo(&app, "/assets/chainlink/logo?size=64").await).await, (64, 64));
+    assert!(CoinLogos::find_by_id("chainlink").one(&app.db).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_logo_errors() {
+    let app = TestApp::spawn(Router::new().route("/assets/{coin_id}/logo", get(get_coin_logo))).await;
+
+    assert_eq!(app.get("/assets/no-such-coin/logo").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/assets/chainlink/logo?size=4").await.0, StatusCode::BAD_REQUEST);
+
+    Mock::given(method("GET"))
+        .and(path("/missing.png"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&app.coingecko)
+        .await;
+    set_logo_address(&app, "chainlink", format!("{}/missing.png", app.coingecko.uri())).await;
+    assert_eq!(app.get("/assets/chainlink/logo").await.0, StatusCode::BAD_GATEWAY);
+}
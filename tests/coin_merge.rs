@@ -0,0 +1,453 @@
+//! Integration tests for merging duplicate coins
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, Set, Statement};
+use serde_json::json;
+
+use common::{TestApp, SEED_DAYS};
+use indexmaker_backend::entities::{
+    category_change_events, category_membership, coin_logos, coin_sync_cursors, coin_yield_rates, coins,
+    coins_historical_prices, crypto_listings, index_constituents, labels, prelude::*, price_reconciliation_checks,
+    rebalances, rolling_stats, spread_observations,
+};
+use indexmaker_backend::services::coin_merge::{self, MergeError};
+use indexmaker_backend::services::labels::entity_types;
+use indexmaker_backend::services::rolling_stats::subjects;
+use indexmaker_backend::services::snapshot_log::{self, kinds};
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+/// Rows still holding `coin_id`, by table, across every table with a
+/// coin_id column (and in rebalances' coins, in either key case)
+async fn references(app: &TestApp, coin_id: &str) -> Vec<(String, i64)> {
+    let backend = app.db.get_database_backend();
+    let tables: Vec<String> = app
+        .db
+        .query_all(Statement::from_string(
+            backend,
+            "SELECT DISTINCT table_name::text AS table_name FROM information_schema.columns
+             WHERE table_schema = 'public' AND column_name = 'coin_id'
+               AND table_name NOT IN (SELECT inhrelid::regclass::text FROM pg_inherits)",
+        ))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| row.try_get::<String>("", "table_name").unwrap())
+        .collect();
+    assert!(tables.len() >= 11, "only found {:?}", tables);
+
+    let mut found = Vec::new();
+    for table in tables {
+        let row = app
+            .db
+            .query_one(Statement::from_sql_and_values(
+                backend,
+                format!("SELECT COUNT(*) AS n FROM {table} WHERE coin_id = $1"),
+                [coin_id.into()],
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        found.push((table, row.try_get::<i64>("", "n").unwrap()));
+    }
+    let row = app
+        .db
+        .query_one(Statement::from_sql_and_values(
+            backend,
+            "SELECT COUNT(*) AS n FROM rebalances
+             WHERE coins @> jsonb_build_array(jsonb_build_object('coin_id', $1::text))
+                OR coins @> jsonb_build_array(jsonb_build_object('coinId', $1::text))",
+            [coin_id.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    found.push(("rebalances".to_string(), row.try_get::<i64>("", "n").unwrap()));
+    found.into_iter().filter(|(_, n)| *n > 0).collect()
+}
+
+/// solana under the id CoinGecko re-ided it to
+async fn insert_solana_2(app: &TestApp) {
+    Coins::insert(coins::ActiveModel {
+        coin_id: Set("solana-2".to_string()),
+        symbol: Set("sol".to_string()),
+        name: Set("Solana".to_string()),
+        active: Set(true),
+        deactivated: Set(false),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_merge_moves_references_to_target() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    let last_seed_day = app.seed_start + Duration::days(SEED_DAYS - 1);
+
+    // The re-ided coin, already fetched for the last two days
+    insert_solana_2(&app).await;
+    CoinsHistoricalPrices::insert_many((0..2).map(|day| coins_historical_prices::ActiveModel {
+        coin_id: Set("solana-2".to_string()),
+        symbol: Set("sol".to_string()),
+        date: Set(last_seed_day - Duration::days(day)),
+        price: Set(dec!(151)),
+        ..Default::default()
+    }))
+    .exec(&app.db)
+    .await
+    .unwrap();
+
+    // Listed on Binance under both ids, on Bitget only under the old one
+    let listing = |coin_id: &str, exchange: &str| crypto_listings::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        symbol: Set("SOL".to_string()),
+        token_name: Set("SOL".to_string()),
+        exchange: Set(exchange.to_string()),
+        trading_pair: Set("usdt".to_string()),
+        status: Set("active".to_string()),
+        ..Default::default()
+    };
+    CryptoListings::insert_many([
+        listing("solana", "binance"),
+        listing("solana", "bitget"),
+        listing("solana-2", "binance"),
+    ])
+    .exec(&app.db)
+    .await
+    .unwrap();
+
+    // Everything else keyed by coin, with the target's yield rate kept
+    let rate = |coin_id: &str, apy_pct| coin_yield_rates::ActiveModel {
+        coin_id: Set(coin_id.to_string()),
+        apy_pct: Set(apy_pct),
+        source: Set("manual".to_string()),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+    CoinYieldRates::insert_many([rate("solana", dec!(7)), rate("solana-2", dec!(6.5))])
+        .exec(&app.db)
+        .await
+        .unwrap();
+    CategoryMembership::insert(category_membership::ActiveModel {
+        coin_id: Set("solana".to_string()),
+        category_id: Set("layer-1".to_string()),
+        added_date: Set(now),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    CategoryChangeEvents::insert(category_change_events::ActiveModel {
+        category_id: Set("layer-1".to_string()),
+        coin_id: Set("solana".to_string()),
+        change_type: Set("added".to_string()),
+        effective_at: Set(now),
+        created_at: Set(now),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    CoinSyncCursors::insert(coin_sync_cursors::ActiveModel {
+        coin_id: Set("solana".to_string()),
+        synced_through: Set(Some(last_seed_day)),
+        last_attempt_at: Set(now),
+        last_success_at: Set(Some(now)),
+        consecutive_failures: Set(0),
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    CoinLogos::insert(coin_logos::ActiveModel {
+        coin_id: Set("solana".to_string()),
+        source_url: Set("https://example.com/sol.png".to_string()),
+        content_type: Set("image/png".to_string()),
+        data: Set(vec![0x89, 0x50]),
+        fetched_at: Set(now),
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    SpreadObservations::insert(spread_observations::ActiveModel {
+        exchange: Set("binance".to_string()),
+        trading_pair: Set("SOLUSDC".to_string()),
+        coin_id: Set("solana".to_string()),
+        date: Set(last_seed_day),
+        best_bid: Set(dec!(149.9)),
+        best_ask: Set(dec!(150.1)),
+        spread: Set(dec!(0.00133)),
+        observed_at: Set(now),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    PriceReconciliationChecks::insert(price_reconciliation_checks::ActiveModel {
+        coin_id: Set("solana".to_string()),
+        symbol: Set("SOL".to_string()),
+        date: Set(last_seed_day),
+        source: Set("binance".to_string()),
+        stored_price: Set(dec!(150)),
+        reference_price: Set(dec!(150)),
+        deviation_bps: Set(0),
+        flagged: Set(false),
+        checked_at: Set(now),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    // A manual rebalance stored before the schema was versioned
+    let manual = Rebalances::insert(rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{
+            "coinId": "solana", "symbol": "SOL", "weight": "1", "quantity": "2", "price": 150.0,
+            "exchange": "binance", "tradingPair": "usdc",
+        }])),
+        portfolio_value: Set(dec!(300)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(app.seed_start + Duration::days(SEED_DAYS))),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap()
+    .last_insert_id;
+
+    let summary = coin_merge::merge(&app.db, "solana", "solana-2", now).await.unwrap();
+    assert_eq!(summary.rows_moved["coins_historical_prices"], SEED_DAYS as u64 - 2);
+    assert_eq!(summary.rows_dropped["coins_historical_prices"], 2);
+    assert_eq!(summary.rows_moved["crypto_listings"], 1);
+    assert_eq!(summary.rows_dropped["crypto_listings"], 1);
+    assert_eq!(summary.rows_moved["index_constituents"], 1);
+    assert!(summary.rows_moved["rebalances"] > 0);
+    assert_eq!(summary.rows_dropped["coins"], 1);
+
+    assert_eq!(summary.rows_dropped["coin_yield_rates"], 1);
+    assert_eq!(summary.rows_moved["coin_logos"], 1);
+
+    // Nothing references the old id any more
+    assert_eq!(references(&app, "solana").await, vec![]);
+    let constituents = IndexConstituents::find()
+        .filter(index_constituents::Column::CoinId.eq("solana-2"))
+        .count(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(constituents, 1);
+    let manual = Rebalances::find_by_id(manual).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(manual.coins[0]["coin_id"], json!("solana-2"));
+    assert_eq!(manual.coins[0]["trading_pair"], json!("usdc"));
+    let rate = CoinYieldRates::find()
+        .filter(coin_yield_rates::Column::CoinId.eq("solana-2"))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rate.apy_pct, dec!(6.5));
+
+    // The target keeps its own prices where both had one
+    let overlapping = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq("solana-2"))
+        .filter(coins_historical_prices::Column::Date.eq(last_seed_day))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(overlapping.price, dec!(151));
+
+    let migrations = TokenMigrations::find().all(&app.db).await.unwrap();
+    assert_eq!(migrations.len(), 1);
+    assert_eq!((migrations[0].source_coin_id.as_str(), migrations[0].target_coin_id.as_str()), ("solana", "solana-2"));
+    assert_eq!(migrations[0].rows_moved["crypto_listings"], 1);
+}
+
+#[tokio::test]
+async fn test_merge_moves_labels_and_rolling_stats() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    let as_of = app.seed_start + Duration::days(SEED_DAYS - 1);
+    insert_solana_2(&app).await;
+
+    let label = |entity_type: &str, entity_id: &str, value: &str| labels::ActiveModel {
+        entity_type: Set(entity_type.to_string()),
+        entity_id: Set(entity_id.to_string()),
+        key: Set("theme".to_string()),
+        value: Set(value.to_string()),
+        created_at: Set(now),
+        ..Default::default()
+    };
+    Labels::insert_many([
+        label(entity_types::COIN, "solana", "l1"),
+        label(entity_types::COIN, "solana", "payments"),
+        label(entity_types::COIN, "solana-2", "l1"),
+        // Not a coin, so not merged
+        label(entity_types::INDEX, "solana", "l1"),
+    ])
+    .exec(&app.db)
+    .await
+    .unwrap();
+
+    let stats = |subject_id: &str, as_of, total_return| rolling_stats::ActiveModel {
+        subject_type: Set(subjects::COIN.to_string()),
+        subject_id: Set(subject_id.to_string()),
+        window_days: Set(30),
+        as_of: Set(as_of),
+        observations: Set(30),
+        total_return: Set(Some(total_return)),
+        computed_at: Set(now),
+        ..Default::default()
+    };
+    RollingStats::insert_many([
+        stats("solana", as_of - Duration::days(1), dec!(0.10)),
+        stats("solana", as_of, dec!(0.12)),
+        stats("solana-2", as_of, dec!(0.15)),
+    ])
+    .exec(&app.db)
+    .await
+    .unwrap();
+
+    let summary = coin_merge::merge(&app.db, "solana", "solana-2", now).await.unwrap();
+    assert_eq!(summary.rows_moved["labels"], 1);
+    assert_eq!(summary.rows_dropped["labels"], 1);
+    assert_eq!(summary.rows_moved["rolling_stats"], 1);
+    assert_eq!(summary.rows_dropped["rolling_stats"], 1);
+
+    let mut themes: Vec<(String, String, String)> = Labels::find()
+        .all(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|label| (label.entity_type, label.entity_id, label.value))
+        .collect();
+    themes.sort();
+    let theme = |entity_type: &str, entity_id: &str, value: &str| {
+        (entity_type.to_string(), entity_id.to_string(), value.to_string())
+    };
+    assert_eq!(
+        themes,
+        vec![
+            theme(entity_types::COIN, "solana-2", "l1"),
+            theme(entity_types::COIN, "solana-2", "payments"),
+            theme(entity_types::INDEX, "solana", "l1"),
+        ]
+    );
+
+    // The target keeps its own statistics where both had them
+    let mut returns: Vec<_> = RollingStats::find()
+        .filter(rolling_stats::Column::SubjectId.eq("solana-2"))
+        .all(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|stats| (stats.as_of, stats.total_return))
+        .collect();
+    returns.sort();
+    assert_eq!(
+        returns,
+        vec![(as_of - Duration::days(1), Some(dec!(0.10))), (as_of, Some(dec!(0.15)))]
+    );
+}
+
+#[tokio::test]
+async fn test_merge_rejects_invalid_coins() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+
+    assert!(matches!(
+        coin_merge::merge(&app.db, "bitcoin", "bitcoin", now).await,
+        Err(MergeError::SameCoin)
+    ));
+    assert!(matches!(
+        coin_merge::merge(&app.db, "bitcoin", "bitcoin-2", now).await,
+        Err(MergeError::CoinNotFound(coin_id)) if coin_id == "bitcoin-2"
+    ));
+
+    // Nothing was changed
+    let prices = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq("bitcoin"))
+        .count(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(prices, SEED_DAYS as u64);
+    assert_eq!(TokenMigrations::find().count(&app.db).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_merge_rejects_rebalance_holding_both_coins() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    insert_solana_2(&app).await;
+
+    let coins = json!([
+        { "coin_id": "solana", "symbol": "SOL", "weight": "0.5", "quantity": "1", "price": 150.0,
+          "exchange": "binance", "trading_pair": "usdc", "schema_version": 2 },
+        { "coin_id": "solana-2", "symbol": "SOL", "weight": "0.5", "quantity": "1", "price": 150.0,
+          "exchange": "binance", "trading_pair": "usdc", "schema_version": 2 },
+    ]);
+    let both = Rebalances::insert(rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(coins.clone()),
+        portfolio_value: Set(dec!(300)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(app.seed_start + Duration::days(SEED_DAYS))),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap()
+    .last_insert_id;
+
+    assert!(matches!(
+        coin_merge::merge(&app.db, "solana", "solana-2", now).await,
+        Err(MergeError::Conflict { rebalance_id }) if rebalance_id == both
+    ));
+
+    // The transaction was rolled back
+    let stored = Rebalances::find_by_id(both).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(stored.coins, coins);
+    assert!(!references(&app, "solana").await.is_empty());
+    assert_eq!(TokenMigrations::find().count(&app.db).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_snapshot_proof_matches_after_merge() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    let date = app.seed_start + Duration::days(SEED_DAYS);
+    insert_solana_2(&app).await;
+
+    // Logged in schema version 1, before the coin was re-ided
+    Rebalances::insert(rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{
+            "coinId": "solana", "symbol": "SOL", "weight": "1", "quantity": "2", "price": 150.0,
+            "exchange": "binance", "tradingPair": "usdc",
+        }])),
+        portfolio_value: Set(dec!(300)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(date)),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    })
+    .exec(&app.db)
+    .await
+    .unwrap();
+    snapshot_log::append_pending(&app.db, SEED_INDEX_ID, now).await.unwrap();
+
+    coin_merge::merge(&app.db, "solana", "solana-2", now).await.unwrap();
+
+    let proof = snapshot_log::proof(&app.db, SEED_INDEX_ID, date).await.unwrap().unwrap();
+    assert!(proof.chain_valid);
+    let logged = proof.entries.iter().find(|entry| entry.kind == kinds::REBALANCE).unwrap();
+    assert_eq!(logged.payload["coins"][0]["coinId"], json!("solana"));
+    assert!(logged.matches_current);
+}
@@ -0,0 +1,120 @@
+//! Integration tests for CoinGecko credit accounting and the daily budget
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins_historical_prices, prelude::*};
+use indexmaker_backend::handlers::health::metrics;
+use indexmaker_backend::handlers::index::get_index_last_price;
+use indexmaker_backend::handlers::market_cap::get_market_cap_history;
+use indexmaker_backend::services::coingecko::CoinGeckoService;
+use indexmaker_backend::services::coingecko_usage::calls;
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+fn routes() -> Router<indexmaker_backend::AppState> {
+    Router::new()
+        .route("/api/market-cap/history", get(get_market_cap_history))
+        .route("/indexes/{index_id}/last-price", get(get_index_last_price))
+        .route("/metrics", get(metrics))
+}
+
+#[tokio::test]
+async fn test_budget_stops_on_the_fly_fetches() {
+    let coingecko = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/coins/bitcoin/market_chart/range"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "prices": [], "market_caps": [], "total_volumes": [],
+        })))
+        .expect(1)
+        .mount(&coingecko)
+        .await;
+    let service = CoinGeckoService::with_budget("test_key".to_string(), coingecko.uri(), Some(1));
+    let app = TestApp::spawn_with_coingecko(routes(), service).await;
+
+    // Before the seeded history, so only CoinGecko has it
+    let start = app.seed_start - Duration::days(30);
+    let uri = format!("/api/market-cap/history?coin_id=bitcoin&start_date={}&end_date={}", start, start);
+    let (status, _) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let usage = app.state.coingecko.usage().snapshot();
+    assert_eq!(usage.credits_today, 1);
+    assert_eq!(usage.remaining_today, Some(0));
+    assert!(usage.budget_exhausted);
+    assert_eq!(usage.calls[0].call_type, calls::MARKET_CHART_RANGE);
+
+    // The budget is spent: no further request reaches CoinGecko
+    let (status, body) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.contains("budget"));
+
+    let (status, body) = app.get("/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("coingecko_credits_total{call_type=\"market_chart_range\"} 1"));
+    assert!(body.contains("coingecko_daily_credit_budget 1"));
+    assert!(body.contains("coingecko_budget_exhausted 1"));
+}
+
+#[tokio::test]
+async fn test_index_price_falls_back_to_stale_prices() {
+    let service = CoinGeckoService::with_budget("test_key".to_string(), "http://127.0.0.1:9".to_string(), Some(1));
+    let app = TestApp::spawn_with_coingecko(routes(), service).await;
+    let uri = format!("/indexes/{}/last-price", SEED_INDEX_ID);
+
+    let body = app.get_json(&uri).await;
+    assert!(body.get("stale").is_none());
+
+    // Today's prices are missing and the budget is spent
+    CoinsHistoricalPrices::delete_many()
+        .filter(coins_historical_prices::Column::Date.eq(Utc::now().date_naive()))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    app.state.coingecko.usage().record(calls::MARKET_CHART);
+
+    let body = app.get_json(&uri).await;
+    assert_eq!(body["stale"], true);
+    assert!(body["lastPrice"].is_string());
+}
+
+#[tokio::test]
+async fn test_price_on_date_fetches_one_day_and_retries_rate_limits() {
+    let coingecko = MockServer::start().await;
+    let date = Utc::now().date_naive() - Duration::days(400);
+    let cutoff = pricing_time::cutoff_timestamp(date);
+    Mock::given(method("GET"))
+        .and(path("/coins/bitcoin/market_chart/range"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+        .up_to_n_times(1)
+        .mount(&coingecko)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/coins/bitcoin/market_chart/range"))
+        .and(query_param("from", (cutoff - 12 * 3600).to_string()))
+        .and(query_param("to", (cutoff + 12 * 3600).to_string()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "prices": [[(cutoff - 86_400) * 1000, 1.0], [cutoff * 1000, 2.0]],
+            "market_caps": [], "total_volumes": [],
+        })))
+        .expect(1)
+        .mount(&coingecko)
+        .await;
+    let service = CoinGeckoService::with_budget("test_key".to_string(), coingecko.uri(), None);
+
+    assert_eq!(service.get_price_on_date("bitcoin", date).await.unwrap(), Some(2.0));
+    assert_eq!(service.usage().snapshot().credits_today, 2);
+
+    // Past days are cached
+    assert_eq!(service.get_price_on_date("bitcoin", date).await.unwrap(), Some(2.0));
+    assert_eq!(service.usage().snapshot().credits_today, 2);
+}
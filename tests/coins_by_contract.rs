@@ -0,0 +1,95 @@
+//! Integration tests for GET /coins/by-contract/{chain}/{address}
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use serde_json::json;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::handlers::asset::lookup_by_contract;
+
+const LINK_CONTRACT: &str = "0x514910771AF9Ca656af840dff83E8264EcF986CA";
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(Router::new().route("/coins/by-contract/{chain}/{address}", get(lookup_by_contract))).await
+}
+
+async fn mock_contract(app: &TestApp, platform: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/coins/{}/contract/{}", platform, LINK_CONTRACT.to_lowercase())))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "chainlink", "symbol": "link"})))
+        .expect(1)
+        .mount(&app.coingecko)
+        .await;
+}
+
+#[tokio::test]
+async fn test_lookup_with_market_data() {
+    let app = setup_test_app().await;
+    mock_contract(&app, "ethereum").await;
+    Mock::given(method("GET"))
+        .and(path("/coins/markets"))
+        .and(query_param("ids", "chainlink"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": "chainlink",
+            "symbol": "link",
+            "name": "Chainlink",
+            "image": "https://example.com/link.png",
+            "current_price": 15.5,
+            "market_cap": 9300000000.0,
+            "market_cap_rank": 14,
+            "fully_diluted_valuation": null,
+            "total_volume": 400000000.0,
+            "circulating_supply": 600000000.0,
+            "total_supply": 1000000000.0,
+            "max_supply": 1000000000.0,
+        }])))
+        .mount(&app.coingecko)
+        .await;
+
+    let coin = app.get_json(&format!("/coins/by-contract/eth/{}", LINK_CONTRACT)).await;
+    assert_eq!(coin["platform"], "ethereum");
+    assert_eq!(coin["contractAddress"], LINK_CONTRACT.to_lowercase());
+    assert_eq!(coin["coinId"], "chainlink");
+    assert_eq!(coin["name"], "Chainlink");
+    assert_eq!(coin["priceUsd"], 15.5);
+    assert_eq!(coin["marketCapRank"], 14);
+}
+
+#[tokio::test]
+async fn test_lookup_without_market_data_uses_stored_coin() {
+    let app = setup_test_app().await;
+    mock_contract(&app, "arbitrum-one").await;
+    Mock::given(method("GET"))
+        .and(path("/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&app.coingecko)
+        .await;
+
+    let coin = app.get_json(&format!("/coins/by-contract/arbitrum/{}", LINK_CONTRACT)).await;
+    assert_eq!(coin["platform"], "arbitrum-one");
+    assert_eq!(coin["symbol"], "link");
+    assert_eq!(coin["name"], "Chainlink");
+    assert!(coin["priceUsd"].is_null());
+}
+
+#[tokio::test]
+async fn test_lookup_errors() {
+    let app = setup_test_app().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/coins/ethereum/contract/0x{}", "0".repeat(40))))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({"error": "coin not found"})))
+        .mount(&app.coingecko)
+        .await;
+
+    let (status, _) = app.get(&format!("/coins/by-contract/ethereum/0x{}", "0".repeat(40))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.get("/coins/by-contract/ethereum/0x1234").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get(&format!("/coins/by-contract/Not%20A%20Chain/{}", LINK_CONTRACT)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
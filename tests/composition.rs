@@ -0,0 +1,66 @@
+//! Integration tests for composition generation against seeded market caps
+
+mod common;
+
+use axum::Router;
+use chrono::Utc;
+
+use asset_registry::AssetRegistry;
+use common::TestApp;
+use indexmaker_backend::config::CompositionLimits;
+use indexmaker_backend::models::composition::{CompositionLimitOverrides, GenerateCompositionRequest};
+use indexmaker_backend::services::composition::{self, CompositionError};
+
+fn registry() -> AssetRegistry {
+    let path = std::env::temp_dir().join(format!("indexmaker_test_assets_{}.json", uuid::Uuid::new_v4().simple()));
+    std::fs::write(
+        &path,
+        r#"[{"id": 1, "bitget": "BTCUSDT"}, {"id": 2, "bitget": "ETHUSDT"},
+            {"id": 3, "bitget": "SOLUSDT"}, {"id": 4, "bitget": "LINKUSDC"}]"#,
+    )
+    .unwrap();
+    AssetRegistry::load(&path).unwrap()
+}
+
+fn request(assets: &[&str], weighting: &str, limits: Option<CompositionLimitOverrides>) -> GenerateCompositionRequest {
+    GenerateCompositionRequest {
+        assets: assets.iter().map(|a| a.to_string()).collect(),
+        weighting: weighting.to_string(),
+        limits,
+    }
+}
+
+#[tokio::test]
+async fn test_generate_composition() {
+    let app = TestApp::spawn(Router::new()).await;
+    let registry = registry();
+    let today = Utc::now().date_naive();
+    let global = CompositionLimits::default();
+
+    let capped = Some(CompositionLimitOverrides { max_asset_weight_bps: Some(4000), ..Default::default() });
+    let generated = composition::generate(&app.db, &registry, &request(&["btc", "ETH", "SOL", "LINK"], "marketcap", capped), &global, today)
+        .await
+        .unwrap();
+    assert_eq!(generated.asset_ids, vec![1, 2, 3, 4]);
+    assert_eq!(generated.weights.iter().sum::<u128>(), 10_000);
+    assert_eq!(generated.weights[0], 4000);
+    assert!(generated.weights.iter().all(|w| *w <= 4000));
+    assert!(generated.weights[1] > generated.weights[2] && generated.weights[2] > generated.weights[3]);
+    assert_eq!(generated.assets[0].coin_id.as_deref(), Some("bitcoin"));
+    assert_eq!(generated.market_cap_date, Some(today));
+
+    let equal = composition::generate(&app.db, &registry, &request(&["BTC", "ETH"], "equal", None), &global, today)
+        .await
+        .unwrap();
+    assert_eq!(equal.weights, vec![5000, 5000]);
+    assert_eq!(equal.market_cap_date, None);
+
+    // Request limits can't loosen the server-wide ones
+    let global = CompositionLimits { max_assets: Some(2), ..global };
+    let loosened = Some(CompositionLimitOverrides { max_assets: Some(10), ..Default::default() });
+    let result = composition::generate(&app.db, &registry, &request(&["BTC", "ETH", "SOL"], "equal", loosened), &global, today).await;
+    assert!(matches!(result, Err(CompositionError::TooManyAssets { max: 2, requested: 3 })));
+
+    let result = composition::generate(&app.db, &registry, &request(&["BTC", "DOGE"], "equal", None), &global, today).await;
+    assert!(matches!(result, Err(CompositionError::UnknownAssets(unknown)) if unknown == vec!["DOGE".to_string()]));
+}
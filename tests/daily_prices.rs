@@ -0,0 +1,57 @@
+//! Integration tests for the daily prices backfill
+
+mod common;
+
+use axum::Router;
+use chrono::Duration;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+use common::TestApp;
+use indexmaker_backend::entities::{daily_prices, prelude::*, rebalances};
+use indexmaker_backend::services::daily_prices::backfill_daily_prices;
+use indexmaker_backend::services::inception;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_backfill_prices_from_inception() {
+    let app = TestApp::spawn(Router::new()).await;
+    let gap_end = app.seed_start + Duration::days(3);
+    let seed_index_prices = || DailyPrices::find().filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()));
+
+    DailyPrices::delete_many()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.lte(gap_end))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    assert!(!inception::check(&app.db, SEED_INDEX_ID).await.unwrap().valid);
+
+    backfill_daily_prices(&app.db, &app.state.coingecko, SEED_INDEX_ID).await.unwrap();
+
+    // The inception date is the first rebalance's portfolio value
+    let first_rebalance = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .order_by_asc(rebalances::Column::Timestamp)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let inception_price = seed_index_prices()
+        .filter(daily_prices::Column::Date.eq(app.seed_start))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(inception_price.price, first_rebalance.portfolio_value);
+    assert!(inception_price.quantities.is_some());
+    let report = inception::check(&app.db, SEED_INDEX_ID).await.unwrap();
+    assert!(report.valid, "{:?}", report.issues);
+
+    // And every day of the gap after it
+    let refilled = seed_index_prices()
+        .filter(daily_prices::Column::Date.lte(gap_end))
+        .all(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(refilled.len(), 4);
+}
@@ -0,0 +1,25 @@
+//! Integration tests for GET /exchanges/{exchange}/pairs
+//!
+//! Only the request checks are covered here; live pairs come from the
+//! exchanges' public APIs.
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+
+use common::TestApp;
+use indexmaker_backend::handlers::pairs::get_exchange_pairs;
+
+#[tokio::test]
+async fn test_pairs_request_errors() {
+    let app = TestApp::spawn(Router::new().route("/exchanges/{exchange}/pairs", get(get_exchange_pairs))).await;
+
+    let (status, body) = app.get("/exchanges/kraken/pairs?symbol=BTC").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("binance, bitget"), "{}", body);
+
+    for query in ["", "?symbol=", "?symbol=BTC-USD", &format!("?symbol={}", "A".repeat(21))] {
+        let (status, _) = app.get(&format!("/exchanges/binance/pairs{}", query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
@@ -0,0 +1,70 @@
+//! Integration tests for feature flags and read-only maintenance mode
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::handlers::maintenance::maintenance;
+use indexmaker_backend::handlers::pairs::get_exchange_pairs;
+use indexmaker_backend::services::feature_flags::{flags, FeatureFlagError, FeatureFlagService};
+
+fn router(app: &TestApp) -> Router {
+    Router::new()
+        .route("/indexes", get(|| async { "[]" }).post(|| async { "created" }))
+        .route("/admin/feature-flags/{key}", get(|| async { "flag" }).put(|| async { "updated" }))
+        .layer(axum::middleware::from_fn_with_state(app.state.clone(), maintenance))
+        .with_state(app.state.clone())
+}
+
+async fn send(router: &Router, method: Method, uri: &str) -> axum::response::Response {
+    let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+    router.clone().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_rejects_writes() {
+    let app = TestApp::spawn(Router::new()).await;
+    let router = router(&app);
+    assert_eq!(send(&router, Method::POST, "/indexes").await.status(), StatusCode::OK);
+
+    app.state.feature_flags.set(&app.db, flags::MAINTENANCE_MODE, true).await.unwrap();
+    let response = send(&router, Method::POST, "/indexes").await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "300");
+    assert_eq!(send(&router, Method::GET, "/indexes").await.status(), StatusCode::OK);
+    assert_eq!(send(&router, Method::PUT, "/admin/feature-flags/maintenance_mode").await.status(), StatusCode::OK);
+
+    app.state.feature_flags.set(&app.db, flags::MAINTENANCE_MODE, false).await.unwrap();
+    assert_eq!(send(&router, Method::POST, "/indexes").await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_overrides_persist_and_gate_handlers() {
+    let app = TestApp::spawn(Router::new().route("/exchanges/{exchange}/pairs", get(get_exchange_pairs))).await;
+
+    let state = app.state.feature_flags.set(&app.db, flags::LIVE_EXCHANGE_CHECKS, false).await.unwrap();
+    assert!(!state.enabled);
+    assert!(state.updated_at.is_some());
+    assert!(matches!(
+        app.state.feature_flags.set(&app.db, "no_such_flag", true).await,
+        Err(FeatureFlagError::UnknownFlag(_))
+    ));
+
+    let (status, body) = app.get("/exchanges/binance/pairs?symbol=BTC").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.contains("live_exchange_checks"));
+
+    // Another instance picks the override up on refresh
+    let other = FeatureFlagService::new(30);
+    assert!(other.is_enabled(flags::LIVE_EXCHANGE_CHECKS).await);
+    other.refresh(&app.db).await.unwrap();
+    assert!(!other.is_enabled(flags::LIVE_EXCHANGE_CHECKS).await);
+    assert!(other.is_enabled(flags::ITP_CREATION).await);
+}
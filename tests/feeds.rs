@@ -0,0 +1,117 @@
+//! Integration tests for the Atom feeds
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::NaiveDate;
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, EntityTrait, QueryOrder, Set};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::entities::{announcements, prelude::*, rebalances};
+use indexmaker_backend::handlers::feeds::{announcements_feed, index_events_feed};
+use indexmaker_backend::services::rebalancing::CoinRebalanceInfo;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+use indexmaker_backend::AppState;
+
+fn app_router() -> Router<AppState> {
+    Router::new()
+        .route("/feeds/announcements.xml", get(announcements_feed))
+        .route("/feeds/indexes/{index_id}/events.xml", get(index_events_feed))
+}
+
+async fn get_feed(app: &TestApp, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .router
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    if status == StatusCode::OK {
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/atom+xml; charset=utf-8");
+    }
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_announcements_feed() {
+    let app = TestApp::spawn(app_router()).await;
+    for (title, day) in [("Binance Will List Foo & Bar <FOO>", 2), ("Binance Will Delist Baz", 1)] {
+        announcements::ActiveModel {
+            title: Set(title.to_string()),
+            source: Set("binance".to_string()),
+            announce_date: Set(NaiveDate::from_ymd_opt(2025, 3, day).unwrap().and_hms_opt(8, 0, 0).unwrap()),
+            content: Set("Trading opens at 10:00 UTC".to_string()),
+            url: Set(Some(format!("https://binance.example/{}?a=1&b=2", day))),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let (status, xml) = get_feed(&app, "/feeds/announcements.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert!(xml.contains("<updated>2025-03-02T08:00:00+00:00</updated>"));
+    assert!(xml.contains("<title>[binance] Binance Will List Foo &amp; Bar &lt;FOO&gt;</title>"));
+    assert!(xml.contains("<link href=\"https://binance.example/2?a=1&amp;b=2\"/>"));
+    // Newest first
+    assert!(xml.find("Foo &amp; Bar").unwrap() < xml.find("Delist Baz").unwrap());
+}
+
+#[tokio::test]
+async fn test_index_events_feed_lists_composition_changes() {
+    let app = TestApp::spawn(app_router()).await;
+
+    // A rebalance after the seeded ones swapping one constituent for another
+    let latest = Rebalances::find()
+        .order_by_desc(rebalances::Column::Timestamp)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut coins: Vec<CoinRebalanceInfo> = serde_json::from_value(latest.coins.clone()).unwrap();
+    let dropped = coins.pop().unwrap();
+    coins.push(CoinRebalanceInfo {
+        coin_id: "newcoin".to_string(),
+        symbol: "NEW".to_string(),
+        ..dropped.clone()
+    });
+    rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(serde_json::to_value(&coins).unwrap()),
+        portfolio_value: Set(latest.portfolio_value),
+        total_weight: Set(latest.total_weight),
+        timestamp: Set(latest.timestamp + 86_400),
+        rebalance_type: Set("periodic".to_string()),
+        deployed: Set(Some(true)),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let (status, xml) = get_feed(&app, &format!("/feeds/indexes/{}/events.xml", SEED_INDEX_ID)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.contains("<title>Seed Layer 1 Index"));
+
+    let entries: Vec<&str> = xml.split("<entry>").skip(1).collect();
+    assert!(entries[0].contains("rebalanced: 1 added, 1 removed</title>"));
+    assert!(entries[0].contains("Added: NEW."));
+    assert!(entries[0].contains(&format!("Removed: {}.", dropped.symbol)));
+    assert!(entries[0].contains("Deployed on-chain."));
+    assert!(entries[1].contains("reweighted</title>"));
+    assert!(entries.last().unwrap().contains("launched with"));
+
+    let (status, _) = get_feed(&app, "/feeds/indexes/424242/events.xml").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,82 @@
+//! Integration tests for the GraphQL API (`graphql` feature)
+
+#![cfg(feature = "graphql")]
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::entities::itps;
+use indexmaker_backend::handlers::graphql::{graphiql, graphql};
+use indexmaker_backend::services::seed::{SEED_INDEX_ID, SEED_INDEX_SYMBOL};
+
+async fn query(app: &TestApp, query: &str) -> Value {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/graphql")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "query": query }).to_string()))
+        .unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_nested_index_query() {
+    let app = TestApp::spawn(Router::new().route("/graphql", get(graphiql).post(graphql))).await;
+    itps::ActiveModel {
+        orbit_address: Set("0x00000000000000000000000000000000000000aa".to_string()),
+        index_id: Set(Some(SEED_INDEX_ID as i64)),
+        name: Set("Seed ITP".to_string()),
+        symbol: Set("SEEDITP".to_string()),
+        state: Set(1),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let body = query(
+        &app,
+        &format!(
+            "{{ index(indexId: {}) {{ symbol constituents {{ coinId }} rebalances {{ id constituents {{ symbol }} }} \
+             prices(from: \"{}\") {{ date price }} lastPrice {{ price }} itps {{ symbol index {{ indexId }} }} }} }}",
+            SEED_INDEX_ID, app.seed_start
+        ),
+    )
+    .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+
+    let index = &body["data"]["index"];
+    assert_eq!(index["symbol"], SEED_INDEX_SYMBOL);
+    let mut coins: Vec<&str> =
+        index["constituents"].as_array().unwrap().iter().map(|c| c["coinId"].as_str().unwrap()).collect();
+    coins.sort();
+    assert_eq!(coins, vec!["bitcoin", "ethereum", "solana"]);
+    assert!(!index["rebalances"].as_array().unwrap().is_empty());
+    let prices = index["prices"].as_array().unwrap();
+    assert_eq!(prices[0]["date"], app.seed_start.to_string());
+    assert_eq!(index["lastPrice"]["price"], prices.last().unwrap()["price"]);
+    assert_eq!(index["itps"][0]["symbol"], "SEEDITP");
+    assert_eq!(index["itps"][0]["index"]["indexId"], SEED_INDEX_ID);
+
+    let body = query(&app, "{ index(indexId: -1) { symbol } itps(state: 2) { id } }").await;
+    assert_eq!(body["data"]["index"], Value::Null);
+    assert_eq!(body["data"]["itps"], json!([]));
+
+    let (status, page) = app.get("/graphql").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("graphiql"));
+}
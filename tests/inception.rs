@@ -0,0 +1,125 @@
+//! Integration tests for seeding and checking index inception
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{daily_prices, index_metadata, prelude::*};
+use indexmaker_backend::models::index::RebalanceCoin;
+use indexmaker_backend::services::inception::{self, InceptionError, InceptionSeed};
+use indexmaker_backend::services::rebalancing::CoinRebalanceInfo;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+const NEW_INDEX_ID: i32 = 9100;
+
+fn coin(coin_id: &str, weight: &str, quantity: &str, price: f64) -> RebalanceCoin {
+    RebalanceCoin {
+        coin_id: coin_id.to_string(),
+        symbol: coin_id.to_string(),
+        weight: weight.to_string(),
+        quantity: quantity.to_string(),
+        price,
+        exchange: "binance".to_string(),
+        trading_pair: "usdt".to_string(),
+    }
+}
+
+/// A copy of the seed index without any history or initial date
+async fn insert_new_index(app: &TestApp) {
+    let seed_index = IndexMetadata::find_by_id(SEED_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    let mut index: index_metadata::ActiveModel = seed_index.into();
+    index.index_id = Set(NEW_INDEX_ID);
+    index.symbol = Set("NEW".to_string());
+    index.address = Set("0x5eed000000000000000000000000000000000002".to_string());
+    index.initial_date = Set(None);
+    index.initial_price = Set(None);
+    IndexMetadata::insert(index).exec(&app.db).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_seeded_history_is_a_valid_inception() {
+    let app = TestApp::spawn(Router::new()).await;
+
+    let report = inception::check(&app.db, SEED_INDEX_ID).await.unwrap();
+    assert!(report.valid, "{:?}", report.issues);
+    assert_eq!(report.date, Some(app.seed_start));
+    assert!(report.rebalance_id.is_some());
+
+    // A different first daily price breaks it
+    daily_prices::ActiveModel {
+        index_id: Set(SEED_INDEX_ID.to_string()),
+        date: Set(app.seed_start),
+        price: Set(dec!(1)),
+        ..Default::default()
+    }
+    .update(&app.db)
+    .await
+    .unwrap();
+    let report = inception::check(&app.db, SEED_INDEX_ID).await.unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    assert!(report.issues[0].contains("first rebalance's portfolio value"));
+
+    let conflict = inception::seed(
+        &app.db,
+        SEED_INDEX_ID,
+        InceptionSeed {
+            date: app.seed_start,
+            price: dec!(1000),
+            coins: vec![coin("bitcoin", "1", "0.01", 100_000.0)],
+        },
+        Utc::now().naive_utc(),
+    )
+    .await;
+    assert!(matches!(conflict, Err(InceptionError::Conflict(_))));
+}
+
+#[tokio::test]
+async fn test_seed_inception() {
+    let app = TestApp::spawn(Router::new()).await;
+    insert_new_index(&app).await;
+    let date = Utc::now().date_naive() - Duration::days(10);
+
+    let report = inception::check(&app.db, NEW_INDEX_ID).await.unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.date, None);
+
+    let seed = |coins: Vec<RebalanceCoin>| InceptionSeed { date, price: dec!(1000), coins };
+    let now = Utc::now().naive_utc();
+    for (coins, expected) in [
+        (vec![coin("bitcoin", "0.5", "1", 500.0)], "Weights must sum to 1"),
+        (vec![coin("bitcoin", "0.5", "1", 500.0), coin("not-a-coin", "0.5", "1", 500.0)], "Unknown coin ids"),
+    ] {
+        match inception::seed(&app.db, NEW_INDEX_ID, seed(coins), now).await {
+            Err(InceptionError::Invalid(msg)) => assert!(msg.starts_with(expected), "{}", msg),
+            other => panic!("expected {}, got {:?}", expected, other.map(|r| r.issues)),
+        }
+    }
+
+    let coins = vec![coin("bitcoin", "0.6", "0.006", 100_000.0), coin("ethereum", "0.4", "0.1", 4_000.0)];
+    let report = inception::seed(&app.db, NEW_INDEX_ID, seed(coins.clone()), now).await.unwrap();
+    assert!(report.seeded && report.valid);
+
+    let rebalance = Rebalances::find_by_id(report.rebalance_id.unwrap()).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(rebalance.rebalance_type, inception::REBALANCE_TYPE);
+    assert_eq!(rebalance.portfolio_value, dec!(1000));
+    let stored: Vec<CoinRebalanceInfo> = serde_json::from_value(rebalance.coins).unwrap();
+    assert_eq!(stored[1].coin_id, "ethereum");
+
+    let index = IndexMetadata::find_by_id(NEW_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    assert_eq!((index.initial_date, index.initial_price), (Some(date), Some(dec!(1000))));
+    assert_eq!(inception::inception_date(&app.db, &index).await.unwrap(), Some(date));
+
+    let report = inception::check(&app.db, NEW_INDEX_ID).await.unwrap();
+    assert!(report.valid, "{:?}", report.issues);
+
+    // Seeding twice conflicts
+    assert!(matches!(
+        inception::seed(&app.db, NEW_INDEX_ID, seed(coins), now).await,
+        Err(InceptionError::Conflict(_))
+    ));
+}
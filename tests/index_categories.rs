@@ -0,0 +1,88 @@
+//! Integration tests for the index directory
+
+mod common;
+
+use axum::{routing::get, Router};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{daily_prices, index_metadata, index_tvl, prelude::*};
+use indexmaker_backend::handlers::index_categories::get_index_categories;
+use indexmaker_backend::services::index_categories::index_categories;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn insert_tvl(app: &TestApp, date: NaiveDate, tvl_usd: Decimal) {
+    index_tvl::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        date: Set(date),
+        net_supply: Set(tvl_usd),
+        nav: Set(Decimal::ONE),
+        tvl_usd: Set(tvl_usd),
+        recorded_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_categories_aggregate_count_tvl_and_ytd() {
+    let app = TestApp::spawn(Router::new().route("/index-categories", get(get_index_categories))).await;
+    let today = Utc::now().date_naive();
+    let jan1 = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap();
+
+    // The seed index doubled since January 1st
+    let latest = DailyPrices::find_by_id((SEED_INDEX_ID.to_string(), today)).one(&app.db).await.unwrap().unwrap();
+    DailyPrices::delete_by_id((SEED_INDEX_ID.to_string(), jan1)).exec(&app.db).await.unwrap();
+    daily_prices::ActiveModel {
+        index_id: Set(SEED_INDEX_ID.to_string()),
+        date: Set(jan1),
+        price: Set(latest.price / dec!(2)),
+        quantities: Set(None),
+        created_at: Set(None),
+        updated_at: Set(None),
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    // Only the latest TVL counts
+    insert_tvl(&app, today - Duration::days(1), dec!(100)).await;
+    insert_tvl(&app, today, dec!(250)).await;
+
+    // A second index in the same group, without prices or TVL
+    index_metadata::ActiveModel {
+        index_id: Set(SEED_INDEX_ID + 1),
+        name: Set("Another Layer 1 Index".to_string()),
+        symbol: Set("L1B".to_string()),
+        address: Set("0x5eed000000000000000000000000000000000002".to_string()),
+        category: Set(Some("Layer 1".to_string())),
+        asset_class: Set(Some("Cryptocurrencies".to_string())),
+        weight_strategy: Set("equal".to_string()),
+        skip_backfill: Set(true),
+        accrue_yield: Set(false),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let response = index_categories(&app.db, today).await.unwrap();
+    let layer1 = response
+        .categories
+        .iter()
+        .find(|c| c.category.as_deref() == Some("Layer 1") && c.asset_class.as_deref() == Some("Cryptocurrencies"))
+        .unwrap();
+    assert_eq!(layer1.index_count, 2);
+    assert_eq!(layer1.total_tvl_usd, dec!(250).to_f64().unwrap());
+    assert!((layer1.avg_ytd_return.unwrap() - 100.0).abs() < 1e-6);
+
+    let body = app.get_json("/index-categories").await;
+    let layer1 = body["categories"].as_array().unwrap().iter().find(|c| c["category"] == "Layer 1").unwrap();
+    assert_eq!(layer1["indexCount"], 2);
+    assert_eq!(layer1["totalTvlUsd"], 250.0);
+}
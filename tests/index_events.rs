@@ -0,0 +1,111 @@
+//! Integration tests for live index events (SSE)
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::Duration;
+use http_body_util::BodyExt;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, IntoActiveModel, Set};
+use serde_json::json;
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::entities::{itps, rebalances};
+use indexmaker_backend::handlers::events::index_events;
+use indexmaker_backend::models::index_event::IndexEvent;
+use indexmaker_backend::services::background_tasks::{self, task_types};
+use indexmaker_backend::services::index_events::IndexEventWatcher;
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+fn names(events: &[IndexEvent]) -> Vec<&'static str> {
+    events.iter().map(IndexEvent::name).collect()
+}
+
+#[tokio::test]
+async fn test_watcher_reports_changes() {
+    let app = TestApp::spawn(Router::new()).await;
+    let mut watcher = IndexEventWatcher::new(SEED_INDEX_ID);
+
+    // Existing rebalances are not replayed
+    assert!(watcher.poll(&app.db).await.unwrap().is_empty());
+
+    let task = background_tasks::enqueue(&app.db, task_types::INDEX_BACKFILL, Some(SEED_INDEX_ID), None).await.unwrap();
+    assert_eq!(names(&watcher.poll(&app.db).await.unwrap()), vec!["backfill"]);
+    assert!(watcher.poll(&app.db).await.unwrap().is_empty());
+
+    background_tasks::claim_next(&app.db).await.unwrap().unwrap();
+    background_tasks::record_progress(&app.db, task.id, 1, 4, Some(app.seed_start)).await.unwrap();
+    let events = watcher.poll(&app.db).await.unwrap();
+    let [IndexEvent::Backfill(status)] = events.as_slice() else { panic!("{:?}", events) };
+    assert_eq!(status.status, background_tasks::statuses::RUNNING);
+    assert_eq!(status.rebalances_done, Some(1));
+
+    let rebalance = rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([])),
+        portfolio_value: Set(dec!(1000)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(app.seed_start + Duration::days(60))),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(names(&watcher.poll(&app.db).await.unwrap()), vec!["rebalance-created"]);
+
+    let mut deployed = rebalance.into_active_model();
+    deployed.deployed = Set(Some(true));
+    deployed.tx_hash = Set(Some("0xabc".to_string()));
+    deployed.update(&app.db).await.unwrap();
+    let events = watcher.poll(&app.db).await.unwrap();
+    let [IndexEvent::RebalanceDeployed(event)] = events.as_slice() else { panic!("{:?}", events) };
+    assert_eq!(event.tx_hash.as_deref(), Some("0xabc"));
+
+    let itp = itps::ActiveModel {
+        orbit_address: Set("0x00000000000000000000000000000000000000aa".to_string()),
+        index_id: Set(Some(SEED_INDEX_ID as i64)),
+        name: Set("Seed ITP".to_string()),
+        symbol: Set("SEEDITP".to_string()),
+        state: Set(0),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(names(&watcher.poll(&app.db).await.unwrap()), vec!["itp"]);
+
+    let mut active = itp.into_active_model();
+    active.state = Set(1);
+    active.update(&app.db).await.unwrap();
+    let events = watcher.poll(&app.db).await.unwrap();
+    let [IndexEvent::Itp(status)] = events.as_slice() else { panic!("{:?}", events) };
+    assert_eq!(status.state, 1);
+}
+
+#[tokio::test]
+async fn test_index_events_stream() {
+    let app = TestApp::spawn(Router::new().route("/events/indexes/{index_id}", get(index_events))).await;
+    background_tasks::enqueue(&app.db, task_types::INDEX_BACKFILL, Some(SEED_INDEX_ID), None).await.unwrap();
+
+    let (status, _) = app.get("/events/indexes/-1").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let request = Request::builder().uri(format!("/events/indexes/{}", SEED_INDEX_ID)).body(Body::empty()).unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+    let mut body = response.into_body();
+    let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+    let frame = String::from_utf8(frame.to_vec()).unwrap();
+    assert!(frame.starts_with("event: backfill\n"), "{}", frame);
+    assert!(frame.contains("\"status\":\"pending\""), "{}", frame);
+}
@@ -0,0 +1,85 @@
+//! Integration tests for localized index names
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::handlers::index::{get_index_config, get_index_list, get_index_translations};
+use indexmaker_backend::services::index_translations::{self, TranslationError};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn get_localized(app: &TestApp, uri: &str, accept_language: &str) -> serde_json::Value {
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header(header::ACCEPT_LANGUAGE, accept_language)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_index_names_follow_accept_language() {
+    let app = TestApp::spawn(
+        Router::new()
+            .route("/indexes", get(get_index_list))
+            .route("/get-index-config/{index_id}", get(get_index_config))
+            .route("/indexes/{index_id}/translations", get(get_index_translations)),
+    )
+    .await;
+    let now = Utc::now().naive_utc();
+
+    index_translations::upsert(&app.db, SEED_INDEX_ID, "fr", "Indice Layer 1", Some("Les grandes chaînes"), now)
+        .await
+        .unwrap();
+    index_translations::upsert(&app.db, SEED_INDEX_ID, "pt_br", "Índice Camada 1", None, now)
+        .await
+        .unwrap();
+    // Replacing keeps one row per locale
+    index_translations::upsert(&app.db, SEED_INDEX_ID, "FR", "Indice de couche 1", Some("Les grandes chaînes"), now)
+        .await
+        .unwrap();
+    assert!(matches!(
+        index_translations::upsert(&app.db, 424242, "fr", "Rien", None, now).await,
+        Err(TranslationError::IndexNotFound(424242))
+    ));
+
+    let translations = app.get_json(&format!("/indexes/{}/translations", SEED_INDEX_ID)).await;
+    let locales: Vec<_> = translations.as_array().unwrap().iter().map(|t| t["locale"].as_str().unwrap()).collect();
+    assert_eq!(locales, vec!["fr", "pt-BR"]);
+
+    let list = get_localized(&app, "/indexes", "fr-CH, en;q=0.8").await;
+    assert_eq!(list["indexes"][0]["name"], "Indice de couche 1");
+    assert_eq!(list["indexes"][0]["description"], "Les grandes chaînes");
+    assert_eq!(list["indexes"][0]["locale"], "fr");
+
+    let config = get_localized(&app, &format!("/get-index-config/{}", SEED_INDEX_ID), "pt-BR").await;
+    assert_eq!(config["name"], "Índice Camada 1");
+    assert_eq!(config["locale"], "pt-BR");
+    assert!(config.get("description").is_none());
+
+    // No matching translation: the index's own name
+    let config = get_localized(&app, &format!("/get-index-config/{}", SEED_INDEX_ID), "ja").await;
+    assert_eq!(config["name"], "Seed Layer 1 Index");
+    assert!(config.get("locale").is_none());
+
+    assert!(index_translations::remove(&app.db, SEED_INDEX_ID, "pt-br").await.unwrap());
+    assert!(!index_translations::remove(&app.db, SEED_INDEX_ID, "pt-BR").await.unwrap());
+}
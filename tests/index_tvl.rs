@@ -0,0 +1,87 @@
+//! Integration tests for the daily index TVL snapshots
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+
+use common::{TestApp, SEED_DAYS};
+use indexmaker_backend::entities::{blockchain_events, prelude::*};
+use indexmaker_backend::handlers::tvl::get_tvl_history;
+use indexmaker_backend::services::index_deployments::DEFAULT_NETWORK;
+use indexmaker_backend::services::index_tvl;
+use indexmaker_backend::services::seed::{SEED_INDEX_ADDRESS, SEED_INDEX_ID};
+
+async fn minted(app: &TestApp) -> Decimal {
+    BlockchainEvents::find()
+        .filter(blockchain_events::Column::EventType.eq("mint"))
+        .all(&app.db)
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|e| e.quantity)
+        .sum()
+}
+
+#[tokio::test]
+async fn test_snapshots_backfill_and_refresh_latest_day() {
+    let app = TestApp::spawn(Router::new()).await;
+    let today = Utc::now().date_naive();
+
+    assert_eq!(index_tvl::record_snapshots(&app.db).await.unwrap(), SEED_DAYS as usize);
+    let history = index_tvl::history(&app.db, SEED_INDEX_ID, None, None).await.unwrap();
+    assert_eq!(history.len(), SEED_DAYS as usize);
+    assert!(history.windows(2).all(|w| w[0].date < w[1].date && w[0].net_supply <= w[1].net_supply));
+
+    let latest = history.last().unwrap();
+    let price = DailyPrices::find_by_id((SEED_INDEX_ID.to_string(), today)).one(&app.db).await.unwrap().unwrap().price;
+    assert_eq!(latest.date, today);
+    assert_eq!(latest.net_supply, minted(&app).await);
+    assert_eq!(latest.nav, price);
+    assert_eq!(latest.tvl_usd, latest.net_supply * price);
+
+    // A redemption today only changes the latest snapshot
+    let burn = latest.net_supply / dec!(2);
+    blockchain_events::ActiveModel {
+        tx_hash: Set(format!("0x{:064x}", 0xb0b)),
+        block_number: Set(30_000_000),
+        log_index: Set(0),
+        event_type: Set("burn".to_string()),
+        contract_address: Set(SEED_INDEX_ADDRESS.to_string()),
+        network: Set(DEFAULT_NETWORK.to_string()),
+        quantity: Set(Some(burn)),
+        timestamp: Set(Some(Utc::now().into())),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    assert_eq!(index_tvl::record_snapshots(&app.db).await.unwrap(), 1);
+    let history = index_tvl::history(&app.db, SEED_INDEX_ID, Some(today - Duration::days(1)), None).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].net_supply, history[0].net_supply - burn);
+    assert_eq!(IndexTvl::find().count(&app.db).await.unwrap(), SEED_DAYS as u64);
+}
+
+#[tokio::test]
+async fn test_tvl_history_endpoint() {
+    let app = TestApp::spawn(Router::new().route("/indexes/{index_id}/tvl-history", get(get_tvl_history))).await;
+    index_tvl::record_snapshots(&app.db).await.unwrap();
+    let today = Utc::now().date_naive();
+
+    let body = app
+        .get_json(&format!("/indexes/{}/tvl-history?start_date={}", SEED_INDEX_ID, today - Duration::days(6)))
+        .await;
+    assert_eq!(body["indexId"], SEED_INDEX_ID);
+    let points = body["points"].as_array().unwrap();
+    assert_eq!(points.len(), 7);
+    assert_eq!(points[6]["date"], today.to_string());
+    assert!(points[6]["tvlUsd"].is_string());
+
+    assert_eq!(app.get("/indexes/424242/tvl-history").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(app.get(&format!("/indexes/{}/tvl-history?end_date=yesterday", SEED_INDEX_ID)).await.0, StatusCode::BAD_REQUEST);
+}
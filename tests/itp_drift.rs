@@ -0,0 +1,79 @@
+//! Integration tests for ITP composition drift against seeded prices
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, Set};
+use serde_json::json;
+
+use common::TestApp;
+use indexmaker_backend::entities::{itps, prelude::*};
+use indexmaker_backend::handlers;
+use indexmaker_backend::services::itp_drift::{self, DriftConfig};
+use indexmaker_backend::services::price_utils::get_coins_historical_price_for_date;
+
+async fn price(app: &TestApp, coin_id: &str, date: NaiveDate) -> f64 {
+    get_coins_historical_price_for_date(&app.db, coin_id, date).await.unwrap().unwrap()
+}
+
+async fn insert_itp(app: &TestApp, orbit_address: &str, created: NaiveDate, assets: Option<serde_json::Value>) {
+    itps::ActiveModel {
+        orbit_address: Set(orbit_address.to_string()),
+        name: Set("Drift Test".to_string()),
+        symbol: Set("DRIFT".to_string()),
+        state: Set(1),
+        assets: Set(assets),
+        weights: Set(Some(json!([0.5, 0.5]))),
+        created_at: Set(Some(created.and_hms_opt(12, 0, 0).unwrap().and_utc().into())),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_drift_recorded_and_served() {
+    let app = TestApp::spawn(Router::new().route("/api/itp/{address}/drift", get(handlers::itp_drift::get_itp_drift))).await;
+    let today = Utc::now().date_naive();
+    let created = app.seed_start + Duration::days(10);
+
+    let address = "0xAbCdEf0000000000000000000000000000000001";
+    insert_itp(&app, address, created, Some(json!(["BTC", "eth"]))).await;
+    insert_itp(&app, "0x0000000000000000000000000000000000000002", created, None).await;
+
+    let (status, _) = app.get(&format!("/api/itp/{}/drift", address)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let btc = price(&app, "bitcoin", today).await / price(&app, "bitcoin", created).await;
+    let eth = price(&app, "ethereum", today).await / price(&app, "ethereum", created).await;
+    let expected_btc_bps = ((btc / (btc + eth) - 0.5) * 10_000.0).round() as i32;
+
+    let config = DriftConfig { threshold_bps: 1 };
+    let summary = itp_drift::run(&app.db, &config, today).await.unwrap();
+    assert_eq!(summary.itps, 2);
+    assert_eq!(summary.checked, 1);
+    assert_eq!(summary.incomplete, 1);
+    assert_eq!(summary.max_drift_bps, expected_btc_bps.abs());
+
+    // Re-running the same day updates the check instead of duplicating it
+    itp_drift::run(&app.db, &config, today).await.unwrap();
+    assert_eq!(ItpDriftChecks::find().count(&app.db).await.unwrap(), 1);
+
+    let drift = app.get_json(&format!("/api/itp/{}/drift", address.to_lowercase())).await;
+    assert_eq!(drift["orbit_address"], address);
+    assert_eq!(drift["date"], today.to_string());
+    assert_eq!(drift["base_date"], created.to_string());
+    assert_eq!(drift["rebalance_due"], expected_btc_bps.abs() >= 1);
+    assert_eq!(drift["constituents"][0]["symbol"], "BTC");
+    assert_eq!(drift["constituents"][0]["drift_bps"], expected_btc_bps);
+    assert_eq!(drift["constituents"][1]["drift_bps"], -expected_btc_bps);
+    let base: Decimal = drift["constituents"][0]["base_price"].as_str().unwrap().parse().unwrap();
+    assert!((base.to_f64().unwrap() - price(&app, "bitcoin", created).await).abs() < 1e-6);
+
+    let (status, body) = app.get("/api/itp/0x00000000000000000000000000000000000000ff/drift").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("ITP_NOT_FOUND"));
+}
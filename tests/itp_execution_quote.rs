@@ -0,0 +1,49 @@
+//! Integration tests for ITP execution quotes
+//!
+//! Only the request checks are covered here; quoting itself needs live
+//! exchange order books and is unit-tested in services::itp_execution_quote.
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use sea_orm::{ActiveModelTrait, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::itps;
+use indexmaker_backend::handlers::itp_execution_quote::get_execution_quote;
+
+const ORBIT_ADDRESS: &str = "0x0000000000000000000000000000000000000e01";
+
+#[tokio::test]
+async fn test_quote_request_errors() {
+    let routes = Router::new().route("/api/itp/{address}/execution-quote", get(get_execution_quote));
+    let app = TestApp::spawn(routes).await;
+    // No target weights stored
+    itps::ActiveModel {
+        orbit_address: Set(ORBIT_ADDRESS.to_string()),
+        name: Set("Quote Test".to_string()),
+        symbol: Set("QUOTE".to_string()),
+        state: Set(1),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    let uri = format!("/api/itp/{}/execution-quote", ORBIT_ADDRESS);
+
+    for query in ["", "?notional=0", "?notional=-5", "?notional=1e12"] {
+        let (status, body) = app.get(&format!("{}{}", uri, query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert!(body.contains("INVALID_NOTIONAL"), "{}", body);
+    }
+
+    let (status, body) = app
+        .get("/api/itp/0x00000000000000000000000000000000000000ff/execution-quote?notional=1000")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("ITP_NOT_FOUND"));
+
+    let (status, body) = app.get(&format!("{}?notional=1000", uri)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.contains("WEIGHTS_UNAVAILABLE"));
+}
@@ -0,0 +1,166 @@
+//! Integration tests for ITP order tracking
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::{FixedOffset, Utc};
+use http_body_util::BodyExt;
+use jsonwebtoken::{EncodingKey, Header};
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{TestApp, TEST_JWT_SECRET};
+use indexmaker_backend::entities::{blockchain_events, itps};
+use indexmaker_backend::handlers::itp_orders::{create_order, get_order, list_orders};
+use indexmaker_backend::services::api_keys::{self, ApiKeyTier};
+use indexmaker_backend::services::auth::Claims;
+use indexmaker_backend::services::itp_orders;
+
+const WALLET: &str = "0x00000000000000000000000000000000000A11CE";
+const ORBIT_ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+const ARBITRUM_ADDRESS: &str = "0x00000000000000000000000000000000000000Ab";
+
+fn session_token(address: &str) -> String {
+    let now = Utc::now().timestamp();
+    let claims = Claims { sub: address.to_string(), iat: now, exp: now + 600, iss: "indexmaker-backend".to_string() };
+    jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+async fn send(app: &TestApp, method: Method, uri: &str, body: Option<Value>, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri).header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty);
+    let response = app.router.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn insert_event(app: &TestApp, event_type: &str, tx_hash: &str, block_number: i32, log_index: i32) {
+    blockchain_events::ActiveModel {
+        tx_hash: Set(tx_hash.to_string()),
+        block_number: Set(block_number),
+        log_index: Set(log_index),
+        event_type: Set(event_type.to_string()),
+        contract_address: Set(ARBITRUM_ADDRESS.to_lowercase()),
+        network: Set("arbitrum".to_string()),
+        user_address: Set(Some(WALLET.to_string())),
+        timestamp: Set(Some(Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap()))),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_order_lifecycle() {
+    let routes = Router::new()
+        .route("/api/itp/{address}/orders", get(list_orders).post(create_order))
+        .route("/api/itp/orders/{order_id}", get(get_order));
+    let app = TestApp::spawn(routes).await;
+    itps::ActiveModel {
+        orbit_address: Set(ORBIT_ADDRESS.to_string()),
+        arbitrum_address: Set(Some(ARBITRUM_ADDRESS.to_string())),
+        name: Set("Orders Test".to_string()),
+        symbol: Set("ORDR".to_string()),
+        state: Set(1),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    let token = session_token(WALLET);
+    let orders_uri = format!("/api/itp/{}/orders", ARBITRUM_ADDRESS.to_lowercase());
+
+    let (status, _) = send(&app, Method::POST, &orders_uri, Some(json!({"side": "mint", "amount": "100"})), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) =
+        send(&app, Method::POST, &orders_uri, Some(json!({"side": "swap", "amount": "100"})), Some(&token)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_SIDE");
+    let (status, body) = send(
+        &app,
+        Method::POST,
+        "/api/itp/0x00000000000000000000000000000000000000ff/orders",
+        Some(json!({"side": "mint", "amount": "100"})),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "ITP_NOT_FOUND");
+
+    let (status, mint) =
+        send(&app, Method::POST, &orders_uri, Some(json!({"side": "mint", "amount": "100"})), Some(&token)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(mint["status"], "pending");
+    assert_eq!(mint["itp_address"], ORBIT_ADDRESS);
+    assert_eq!(mint["wallet_address"], WALLET.to_lowercase());
+
+    let redeem_tx = format!("0x{}", "bb".repeat(32));
+    let (status, redeem) = send(
+        &app,
+        Method::POST,
+        &orders_uri,
+        Some(json!({"side": "redeem", "amount": "5", "tx_hash": redeem_tx.to_uppercase().replace("0X", "0x")})),
+        Some(&token),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(redeem["submit_tx_hash"], redeem_tx);
+
+    // Nothing on chain yet
+    let summary = itp_orders::index_orders(&app.db).await.unwrap();
+    assert_eq!((summary.open, summary.filled, summary.settled), (2, 0, 0));
+
+    // The mint is filled and settled in one transaction; the redeem's burn
+    // lands in its own transaction and a burn from elsewhere is ignored
+    let mint_tx = format!("0x{}", "aa".repeat(32));
+    insert_event(&app, "mint", &mint_tx, 100, 0).await;
+    insert_event(&app, "deposit", &mint_tx, 100, 1).await;
+    insert_event(&app, "burn", &format!("0x{}", "cc".repeat(32)), 101, 0).await;
+    insert_event(&app, "burn", &redeem_tx, 102, 0).await;
+
+    let summary = itp_orders::index_orders(&app.db).await.unwrap();
+    assert_eq!((summary.open, summary.filled, summary.settled), (2, 2, 1));
+
+    let order_uri = format!("/api/itp/orders/{}", mint["order_id"].as_str().unwrap());
+    let (status, _) = send(&app, Method::GET, &order_uri, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let other_wallet = session_token("0x00000000000000000000000000000000000B0B00");
+    let (status, body) = send(&app, Method::GET, &order_uri, None, Some(&other_wallet)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "ORDER_NOT_FOUND");
+    let (_, admin_key) = api_keys::create(&app.db, "support", ApiKeyTier::Admin, None).await.unwrap();
+    let request = Request::builder().uri(&order_uri).header("x-api-key", admin_key).body(Body::empty()).unwrap();
+    assert_eq!(app.router.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+    let (status, mint) = send(&app, Method::GET, &order_uri, None, Some(&token)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mint["status"], "settled");
+    assert_eq!(mint["fill_tx_hash"], mint_tx);
+    assert_eq!(mint["settle_tx_hash"], mint_tx);
+
+    let (status, orders) = send(&app, Method::GET, &orders_uri, None, Some(&token)).await;
+    assert_eq!(status, StatusCode::OK);
+    let orders = orders.as_array().unwrap();
+    assert_eq!(orders.len(), 2);
+    assert_eq!(orders[0]["order_id"], redeem["order_id"]);
+    assert_eq!(orders[0]["status"], "filled");
+    assert_eq!(orders[0]["fill_tx_hash"], redeem_tx);
+
+    // The redeem settles once its withdraw arrives
+    insert_event(&app, "withdraw", &redeem_tx, 102, 1).await;
+    let summary = itp_orders::index_orders(&app.db).await.unwrap();
+    assert_eq!((summary.open, summary.filled, summary.settled), (1, 0, 1));
+
+    let (status, _) = send(&app, Method::GET, "/api/itp/orders/unknown", None, Some(&token)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,35 @@
+//! Integration tests for the rebalance history endpoint
+
+mod common;
+
+use axum::{routing::get, Router};
+use rust_decimal::Decimal;
+
+use common::TestApp;
+use indexmaker_backend::handlers::itp_rebalances::get_rebalance_history;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_history_includes_fee_breakdown() {
+    let app = TestApp::spawn(Router::new().route("/api/itp/{index_id}/rebalances", get(get_rebalance_history))).await;
+
+    let body = app.get_json(&format!("/api/itp/{}/rebalances", SEED_INDEX_ID)).await;
+    let rebalances = body["rebalances"].as_array().unwrap();
+    assert!(!rebalances.is_empty());
+
+    let decimal = |value: &serde_json::Value| value.as_str().unwrap().parse::<Decimal>().unwrap();
+    for rebalance in rebalances {
+        let total = decimal(&rebalance["total_fees"]);
+        let breakdown = rebalance["fee_breakdown"].as_array().unwrap();
+        assert_eq!(breakdown.len(), rebalance["coins"].as_array().unwrap().len());
+        assert_eq!(breakdown.iter().map(|coin| decimal(&coin["fee"])).sum::<Decimal>(), total);
+    }
+
+    // The initial rebalance buys everything
+    let initial = rebalances.last().unwrap();
+    assert_eq!(initial["rebalance_type"], "initial");
+    assert!(decimal(&initial["total_fees"]) > Decimal::ZERO);
+    for coin in initial["fee_breakdown"].as_array().unwrap() {
+        assert!(decimal(&coin["traded_value"]) > Decimal::ZERO);
+    }
+}
@@ -0,0 +1,35 @@
+//! Integration tests for the job failure queue
+
+mod common;
+
+use axum::Router;
+
+use common::TestApp;
+use indexmaker_backend::services::job_failures::{
+    entity_types, mark_retry_failed, record_failure, requeue, statuses, MAX_ATTEMPTS,
+};
+
+#[tokio::test]
+async fn test_requeue_resets_attempts() {
+    let app = TestApp::spawn(Router::new()).await;
+
+    let mut record = record_failure(&app.db, "test_job", entity_types::COIN, "bitcoin", None, "upstream down")
+        .await
+        .unwrap();
+    while record.status != statuses::EXHAUSTED {
+        record = mark_retry_failed(&app.db, record, "upstream down").await.unwrap();
+    }
+    assert_eq!(record.attempt_count, MAX_ATTEMPTS);
+
+    let requeued = requeue(&app.db, record.id).await.unwrap().unwrap();
+    assert_eq!(requeued.status, statuses::PENDING);
+    assert_eq!(requeued.attempt_count, 0);
+    assert!(requeued.next_retry_at.is_some());
+
+    // A failed retry after a requeue is rescheduled, not exhausted again
+    let retried = mark_retry_failed(&app.db, requeued, "still down").await.unwrap();
+    assert_eq!(retried.status, statuses::PENDING);
+    assert_eq!(retried.attempt_count, 1);
+
+    assert!(requeue(&app.db, record.id + 1000).await.unwrap().is_none());
+}
@@ -0,0 +1,59 @@
+//! Integration tests for job run history and regression alerts
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, NaiveDate};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use common::TestApp;
+use indexmaker_backend::entities::{job_runs, prelude::*};
+use indexmaker_backend::services::job_runs::{record, track, Regression, MIN_HISTORY};
+
+#[tokio::test]
+async fn test_track_records_runs() {
+    let app = TestApp::spawn(Router::new()).await;
+
+    let rows = track(&app.db, "test_job", async { Ok::<_, String>(42) }).await;
+    assert_eq!(rows, Ok(42));
+    let failed = track(&app.db, "test_job", async { Err::<usize, _>("upstream down".to_string()) }).await;
+    assert_eq!(failed, Err("upstream down".to_string()));
+
+    let runs = JobRuns::find()
+        .filter(job_runs::Column::JobName.eq("test_job"))
+        .all(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 2);
+    let ok = runs.iter().find(|r| r.succeeded).unwrap();
+    assert_eq!(ok.rows_processed, Some(42));
+    assert!(ok.finished_at >= ok.started_at);
+    let err = runs.iter().find(|r| !r.succeeded).unwrap();
+    assert_eq!(err.rows_processed, None);
+    assert_eq!(err.error.as_deref(), Some("upstream down"));
+}
+
+#[tokio::test]
+async fn test_record_flags_regressions() {
+    let app = TestApp::spawn(Router::new()).await;
+    let start = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(2, 0, 0).unwrap();
+    let day = |n: i64| start + Duration::days(n);
+
+    for n in 0..MIN_HISTORY as i64 {
+        let regressions = record(&app.db, "nightly", day(n), 60_000, Some(500), None).await.unwrap();
+        assert!(regressions.is_empty());
+    }
+
+    // A failed run isn't compared, nor part of later baselines
+    let failed = record(&app.db, "nightly", day(5), 900_000, None, Some("timeout".to_string())).await.unwrap();
+    assert!(failed.is_empty());
+
+    let slow = record(&app.db, "nightly", day(6), 150_000, Some(500), None).await.unwrap();
+    assert_eq!(slow, vec![Regression::Slowdown { duration_ms: 150_000, median_ms: 60_000 }]);
+
+    let empty = record(&app.db, "nightly", day(7), 1_000, Some(0), None).await.unwrap();
+    assert_eq!(empty, vec![Regression::NoRows { median_rows: 500 }]);
+
+    // Other jobs have their own history
+    assert!(record(&app.db, "other", day(8), 150_000, Some(0), None).await.unwrap().is_empty());
+}
@@ -0,0 +1,67 @@
+//! Integration tests for index and coin labels
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+
+use common::TestApp;
+use indexmaker_backend::handlers::index::get_index_list;
+use indexmaker_backend::services::labels::{self, entity_types, LabelError};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(Router::new().route("/indexes", get(get_index_list))).await
+}
+
+#[tokio::test]
+async fn test_add_list_and_remove() {
+    let app = setup_test_app().await;
+    let index_id = SEED_INDEX_ID.to_string();
+
+    let label = labels::add(&app.db, "Index", &index_id, "Theme", " AI ").await.unwrap();
+    assert_eq!((label.key.as_str(), label.value.as_str()), ("theme", "ai"));
+    // Adding it again returns the existing label
+    let again = labels::add(&app.db, "index", &index_id, "theme", "ai").await.unwrap();
+    assert_eq!(again.id, label.id);
+    labels::add(&app.db, "coin", "chainlink", "theme", "oracle").await.unwrap();
+
+    assert_eq!(labels::list(&app.db, Some(entity_types::INDEX), None).await.unwrap().len(), 1);
+    assert_eq!(labels::list(&app.db, None, None).await.unwrap().len(), 2);
+
+    assert!(matches!(
+        labels::add(&app.db, "index", "123456", "theme", "ai").await,
+        Err(LabelError::EntityNotFound { .. })
+    ));
+    assert!(matches!(
+        labels::add(&app.db, "coin", "not-a-coin", "theme", "ai").await,
+        Err(LabelError::EntityNotFound { .. })
+    ));
+    assert!(matches!(labels::add(&app.db, "itp", "1", "theme", "ai").await, Err(LabelError::Invalid(_))));
+
+    assert_eq!(labels::remove(&app.db, label.id).await.unwrap().map(|l| l.id), Some(label.id));
+    assert!(labels::remove(&app.db, label.id).await.unwrap().is_none());
+    assert_eq!(labels::list(&app.db, Some(entity_types::INDEX), None).await.unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_index_list_label_filter() {
+    let app = setup_test_app().await;
+    let index_id = SEED_INDEX_ID.to_string();
+    labels::add(&app.db, "index", &index_id, "theme", "ai").await.unwrap();
+    labels::add(&app.db, "index", &index_id, "tier", "1").await.unwrap();
+
+    let all = app.get_json("/indexes").await;
+    assert!(!all["indexes"].as_array().unwrap().is_empty());
+
+    for (filter, expected) in [("theme:ai", 1), ("THEME:AI,tier", 1), ("theme:defi", 0), ("theme:ai,region", 0)] {
+        let body = app.get_json(&format!("/indexes?label={}", filter)).await;
+        let indexes = body["indexes"].as_array().unwrap();
+        assert_eq!(indexes.len(), expected, "{}", filter);
+        if expected > 0 {
+            assert_eq!(indexes[0]["indexId"], SEED_INDEX_ID);
+        }
+    }
+
+    let (status, _) = app.get("/indexes?label=the%20me:ai").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,78 @@
+//! Integration tests for the index leaderboard
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use common::TestApp;
+use indexmaker_backend::entities::{blockchain_events, daily_prices, prelude::*};
+use indexmaker_backend::handlers::leaderboard::get_leaderboard;
+use indexmaker_backend::services::leaderboard::{compute, LeaderboardMetric, LeaderboardWindow};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn price(app: &TestApp, date: chrono::NaiveDate) -> Decimal {
+    DailyPrices::find_by_id((SEED_INDEX_ID.to_string(), date)).one(&app.db).await.unwrap().unwrap().price
+}
+
+#[tokio::test]
+async fn test_leaderboard_metrics() {
+    let app = TestApp::spawn(Router::new()).await;
+    let today = Utc::now().date_naive();
+
+    let board = compute(&app.db, LeaderboardWindow::Days7, LeaderboardMetric::Return, today).await.unwrap();
+    let entry = board.entries.iter().find(|e| e.index_id == SEED_INDEX_ID).unwrap();
+    let (start, latest) = (price(&app, today - Duration::days(7)).await, price(&app, today).await);
+    let expected = ((latest - start) / start * Decimal::ONE_HUNDRED).to_f64().unwrap();
+    assert!((entry.return_pct.unwrap() - expected).abs() < 1e-9);
+    assert_eq!(entry.value, entry.return_pct.unwrap());
+    assert_eq!(board.entries[0].rank, 1);
+
+    let mints = BlockchainEvents::find()
+        .filter(blockchain_events::Column::EventType.eq("mint"))
+        .all(&app.db)
+        .await
+        .unwrap();
+    let supply: Decimal = mints.iter().filter_map(|e| e.quantity).sum();
+    let volume_30d: Decimal = mints
+        .iter()
+        .filter(|e| e.timestamp.unwrap().date_naive() >= today - Duration::days(30))
+        .filter_map(|e| e.amount)
+        .sum();
+
+    let board = compute(&app.db, LeaderboardWindow::Days30, LeaderboardMetric::Volume, today).await.unwrap();
+    let entry = board.entries.iter().find(|e| e.index_id == SEED_INDEX_ID).unwrap();
+    assert_eq!(entry.value, volume_30d.to_f64().unwrap());
+    assert!((entry.tvl_usd - (supply * latest).to_f64().unwrap()).abs() < 1e-6);
+
+    // Seeded history doesn't reach back to January 1st unless the year just started
+    let board = compute(&app.db, LeaderboardWindow::YearToDate, LeaderboardMetric::Return, today).await.unwrap();
+    let has_start_price = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.lte(board.start_date))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .is_some();
+    assert_eq!(board.entries.iter().any(|e| e.index_id == SEED_INDEX_ID), has_start_price);
+}
+
+#[tokio::test]
+async fn test_leaderboard_endpoint() {
+    let app = TestApp::spawn(Router::new().route("/indexes/leaderboard", get(get_leaderboard))).await;
+
+    let board = app.get_json("/indexes/leaderboard?window=7d&metric=tvl").await;
+    assert_eq!(board["window"], "7d");
+    assert_eq!(board["metric"], "tvl");
+    assert_eq!(board["entries"][0]["indexId"], SEED_INDEX_ID);
+    assert_eq!(board["entries"][0]["rank"], 1);
+
+    let board = app.get_json("/indexes/leaderboard").await;
+    assert_eq!(board["window"], "30d");
+    assert_eq!(board["metric"], "return");
+
+    assert_eq!(app.get("/indexes/leaderboard?window=1y").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(app.get("/indexes/leaderboard?metric=price").await.0, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,92 @@
+//! Integration tests for leveraged index NAVs
+
+mod common;
+
+use axum::{routing::get, Router};
+use chrono::Duration;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{daily_prices, index_metadata, prelude::*};
+use indexmaker_backend::handlers::index::get_index_price_at_date;
+use indexmaker_backend::services::leverage;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn daily_price(app: &TestApp, date: chrono::NaiveDate) -> Option<Decimal> {
+    DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .map(|row| row.price)
+}
+
+#[tokio::test]
+async fn test_previous_nav_needs_the_day_before() {
+    let app = TestApp::spawn(Router::new()).await;
+    let day = |n: i64| app.seed_start + Duration::days(n);
+
+    let nav = leverage::previous_nav(&app.db, SEED_INDEX_ID, day(10)).await.unwrap();
+    assert_eq!(nav, daily_price(&app, day(9)).await);
+
+    // A gap can't be carried over
+    DailyPrices::delete_many()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(day(20)))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(leverage::previous_nav(&app.db, SEED_INDEX_ID, day(21)).await.unwrap(), None);
+    assert!(leverage::nav_on(&app.db, SEED_INDEX_ID, day(21), dec!(2), dec!(100), dec!(110)).await.is_err());
+
+    // Without any daily price, the NAV starts from the first rebalance's value
+    DailyPrices::delete_many()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    let first = Rebalances::find().all(&app.db).await.unwrap().into_iter().min_by_key(|r| r.timestamp).unwrap();
+    assert_eq!(
+        leverage::previous_nav(&app.db, SEED_INDEX_ID, day(1)).await.unwrap(),
+        Some(first.portfolio_value)
+    );
+    assert_eq!(
+        leverage::nav_on(&app.db, SEED_INDEX_ID, day(1), dec!(-1), dec!(100), dec!(110)).await.unwrap(),
+        first.portfolio_value * dec!(0.9)
+    );
+    assert_eq!(leverage::previous_nav(&app.db, SEED_INDEX_ID, day(5)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_leveraged_price_at_date_uses_the_nav() {
+    let app = TestApp::spawn(
+        Router::new().route("/indexes/{index_id}/price-at-date", get(get_index_price_at_date)),
+    )
+    .await;
+    let date = app.seed_start + Duration::days(12);
+
+    let index = IndexMetadata::find_by_id(SEED_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    let mut index: index_metadata::ActiveModel = index.into();
+    index.leverage_factor = Set(Some(dec!(-1)));
+    index.update(&app.db).await.unwrap();
+
+    // Stand-in NAV, distinct from the basket value
+    let row = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut row: daily_prices::ActiveModel = row.into();
+    row.price = Set(dec!(777.5));
+    row.update(&app.db).await.unwrap();
+
+    let body = app
+        .get_json(&format!("/indexes/{}/price-at-date?date={}", SEED_INDEX_ID, date))
+        .await;
+    assert_eq!(body["price"].as_str().unwrap().parse::<Decimal>().unwrap(), dec!(777.5));
+}
@@ -0,0 +1,114 @@
+//! Integration tests for the filter and sort parameters of list endpoints
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::NaiveDate;
+use sea_orm::{ActiveModelTrait, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::announcements;
+use indexmaker_backend::handlers::asset::fetch_all_assets;
+use indexmaker_backend::handlers::feeds::announcements_feed;
+use indexmaker_backend::handlers::index::get_index_list;
+use indexmaker_backend::handlers::transaction::get_index_transactions;
+use indexmaker_backend::services::seed::{SEED_INDEX_ID, SEED_INDEX_SYMBOL};
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(
+        Router::new()
+            .route("/indexes", get(get_index_list))
+            .route("/indexes/{index_id}/transactions", get(get_index_transactions))
+            .route("/fetch-all-assets", get(fetch_all_assets))
+            .route("/feeds/announcements.xml", get(announcements_feed)),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_index_list_filter_and_sort() {
+    let app = setup_test_app().await;
+
+    for (query, expected) in [
+        (format!("filter=symbol:{}", SEED_INDEX_SYMBOL.to_lowercase()), 1),
+        (format!("filter=id:{}&sort=-name", SEED_INDEX_ID), 1),
+        ("filter=symbol:NOPE".to_string(), 0),
+    ] {
+        let body = app.get_json(&format!("/indexes?{}", query)).await;
+        assert_eq!(body["indexes"].as_array().unwrap().len(), expected, "{}", query);
+    }
+
+    for query in ["filter=address:0x1", "filter=id:abc", "sort=-address", "filter=symbol"] {
+        let (status, body) = app.get(&format!("/indexes?{}", query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert!(!body.contains("Database error"), "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_transactions_filter_and_sort() {
+    let app = setup_test_app().await;
+    let uri = format!("/indexes/{}/transactions", SEED_INDEX_ID);
+
+    let all = app.get_json(&uri).await;
+    let all = all.as_array().unwrap();
+    assert!(!all.is_empty());
+
+    let mints = app.get_json(&format!("{}?filter=type:MINT", uri)).await;
+    let mints = mints.as_array().unwrap();
+    assert_eq!(mints.len() * 2, all.len());
+    assert!(mints.iter().all(|tx| tx["transactionType"] == "Mint"));
+
+    // Oldest first when asked, newest first by default
+    let oldest_first = app.get_json(&format!("{}?filter=type:mint&sort=block", uri)).await;
+    let oldest_first = oldest_first.as_array().unwrap();
+    assert_eq!(oldest_first.first().unwrap()["hash"], mints.last().unwrap()["hash"]);
+    assert!(oldest_first.first().unwrap()["dateTime"].as_str() < oldest_first.last().unwrap()["dateTime"].as_str());
+
+    let wallet = all[0]["wallet"].as_str().unwrap().to_uppercase().replace("0X", "0x");
+    let by_wallet = app.get_json(&format!("{}?filter=wallet:{}", uri, wallet)).await;
+    let by_wallet = by_wallet.as_array().unwrap();
+    assert!(!by_wallet.is_empty() && by_wallet.len() < all.len());
+
+    for query in ["filter=amount:100", "filter=block:latest", "sort=amount", "filter=timestamp:2025-01-01"] {
+        let (status, _) = app.get(&format!("{}?{}", uri, query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn test_assets_reject_unknown_fields() {
+    let app = setup_test_app().await;
+    for query in ["filter=market_cap:1", "sort=-volume", "sort=a,b,c,d"] {
+        let (status, body) = app.get(&format!("/fetch-all-assets?{}", query)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert!(body.contains("expected") || body.contains("At most"), "{}", body);
+    }
+}
+
+#[tokio::test]
+async fn test_announcements_feed_filter() {
+    let app = setup_test_app().await;
+    for (title, source, day) in [("Binance Will List Foo", "binance", 1), ("OKX Will List Bar", "okx", 2)] {
+        announcements::ActiveModel {
+            title: Set(title.to_string()),
+            source: Set(source.to_string()),
+            announce_date: Set(NaiveDate::from_ymd_opt(2025, 3, day).unwrap().and_hms_opt(8, 0, 0).unwrap()),
+            content: Set("Trading opens at 10:00 UTC".to_string()),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let (status, xml) = app.get("/feeds/announcements.xml?filter=source:Binance").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(xml.contains("Foo") && !xml.contains("Bar"));
+
+    let (_, xml) = app.get("/feeds/announcements.xml?sort=date").await;
+    assert!(xml.find("Foo").unwrap() < xml.find("Bar").unwrap());
+
+    let (status, _) = app.get("/feeds/announcements.xml?filter=content:list").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,159 @@
+//! Integration tests for exchange listings and their source announcements
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{ActiveModelTrait, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{announcements, crypto_listings};
+use indexmaker_backend::handlers::listing::{get_crypto_listings, get_listings};
+use indexmaker_backend::services::crypto_listings::{find_announcement_id, for_exchange_symbol};
+
+fn at(hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+}
+
+async fn insert_announcement(app: &TestApp, title: &str, source: &str, announce_date: NaiveDateTime) -> i32 {
+    announcements::ActiveModel {
+        title: Set(title.to_string()),
+        source: Set(source.to_string()),
+        announce_date: Set(announce_date),
+        content: Set(String::new()),
+        url: Set(Some(format!("https://{}.example/{}", source, title.len()))),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap()
+    .id
+}
+
+#[tokio::test]
+async fn test_find_announcement_id() {
+    let app = TestApp::spawn(Router::new()).await;
+    let listing = insert_announcement(&app, "Binance Will List Foo (FOO)", "binance", at(8)).await;
+    insert_announcement(&app, "Bitget Will List Foo (FOO)", "bitget", at(8)).await;
+    insert_announcement(&app, "Binance Will Delist Bar", "binance", at(12)).await;
+    insert_announcement(&app, "Binance Will Delist Baz", "binance", at(12)).await;
+
+    let find = |source: &'static str, date, title| find_announcement_id(&app.db, source, date, title);
+    assert_eq!(find("binance", at(8), None).await.unwrap(), Some(listing));
+    assert_eq!(find("binance", at(8), Some("Binance Will List Foo (FOO)")).await.unwrap(), Some(listing));
+    assert_eq!(find("binance", at(8), Some("Another title")).await.unwrap(), None);
+    assert_eq!(find("binance", at(9), None).await.unwrap(), None);
+    // Two announcements at the same time can't be told apart by date alone
+    assert_eq!(find("binance", at(12), None).await.unwrap(), None);
+    assert!(find("binance", at(12), Some("Binance Will Delist Bar")).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_listings_include_source_announcement() {
+    let app = TestApp::spawn(Router::new().route("/api/listings", get(get_listings))).await;
+    let announcement_id = insert_announcement(&app, "Binance Will List Foo (FOO)", "binance", at(8)).await;
+
+    for (coin_id, exchange, source_announcement_id) in
+        [("foo", "binance", Some(announcement_id)), ("bar", "bitget", None)]
+    {
+        crypto_listings::ActiveModel {
+            coin_id: Set(coin_id.to_string()),
+            symbol: Set(coin_id.to_uppercase()),
+            token_name: Set(coin_id.to_uppercase()),
+            exchange: Set(exchange.to_string()),
+            trading_pair: Set("usdt".to_string()),
+            listing_announcement_date: Set(Some(at(8))),
+            listing_date: Set(Some(at(10))),
+            status: Set("active".to_string()),
+            source_announcement_id: Set(source_announcement_id),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let listings = app.get_json("/api/listings?exchange=Binance").await;
+    let listings = listings.as_array().unwrap();
+    assert_eq!(listings.len(), 1);
+    assert_eq!(listings[0]["coinId"], "foo");
+    assert_eq!(listings[0]["sourceAnnouncementId"], announcement_id);
+    assert_eq!(listings[0]["sourceAnnouncement"]["title"], "Binance Will List Foo (FOO)");
+    assert_eq!(listings[0]["sourceAnnouncement"]["announceDate"], "2025-03-01T08:00:00");
+
+    let listings = app.get_json("/api/listings?coin_id=bar").await;
+    assert!(listings[0]["sourceAnnouncementId"].is_null());
+    assert!(listings[0]["sourceAnnouncement"].is_null());
+
+    assert_eq!(app.get_json("/api/listings?limit=1").await.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_for_exchange_symbol() {
+    let app = TestApp::spawn(Router::new()).await;
+    for (symbol, exchange, trading_pair) in [("FOO", "binance", "usdt"), ("foo", "binance", "usdc"), ("FOO", "bitget", "usdt")] {
+        crypto_listings::ActiveModel {
+            coin_id: Set("foo".to_string()),
+            symbol: Set(symbol.to_string()),
+            token_name: Set(symbol.to_string()),
+            exchange: Set(exchange.to_string()),
+            trading_pair: Set(trading_pair.to_string()),
+            status: Set("active".to_string()),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let listings = for_exchange_symbol(&app.db, "Binance", "foo").await.unwrap();
+    let pairs: Vec<_> = listings.iter().map(|l| l.trading_pair.as_str()).collect();
+    assert_eq!(pairs, ["usdc", "usdt"]);
+    assert!(for_exchange_symbol(&app.db, "binance", "bar").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_crypto_listings_pages_and_csv() {
+    let app = TestApp::spawn(Router::new().route("/crypto-listings", get(get_crypto_listings))).await;
+    for (coin_id, symbol, exchange, status) in [
+        ("foo", "FOO", "binance", "active"),
+        ("foo", "foo", "bitget", "delisted"),
+        ("bar", "BAR", "binance", "active"),
+        ("baz", "BAZ", "binance", "active"),
+    ] {
+        crypto_listings::ActiveModel {
+            coin_id: Set(coin_id.to_string()),
+            symbol: Set(symbol.to_string()),
+            token_name: Set(format!("{}, Inc.", symbol)),
+            exchange: Set(exchange.to_string()),
+            trading_pair: Set("usdt".to_string()),
+            listing_date: Set(Some(at(10))),
+            status: Set(status.to_string()),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let page = app.get_json("/crypto-listings?exchange=binance&status=active&page_size=2").await;
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["totalPages"], 2);
+    let coins: Vec<_> = page["listings"].as_array().unwrap().iter().map(|l| l["coinId"].as_str().unwrap()).collect();
+    assert_eq!(coins, ["baz", "bar"]);
+    let page = app.get_json("/crypto-listings?exchange=binance&status=active&page_size=2&page=2").await;
+    assert_eq!(page["listings"][0]["coinId"], "foo");
+
+    let page = app.get_json("/crypto-listings?symbol=Foo").await;
+    assert_eq!(page["total"], 2);
+
+    let (status, csv) = app.get("/crypto-listings?symbol=foo&format=csv").await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("id,coin_id,symbol,token_name,exchange"));
+    assert!(lines[1].contains(",FOO,\"FOO, Inc.\",binance,usdt,active,,2025-03-01T10:00:00,"));
+
+    assert_eq!(app.get("/crypto-listings?page=0").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(app.get("/crypto-listings?format=xml").await.0, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,432 @@
+//! Integration tests for /api/market-cap/*
+
+mod common;
+
+use std::collections::HashSet;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use wiremock::{
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::coins_historical_prices;
+use indexmaker_backend::handlers::market_cap::{
+    get_market_cap_history, get_market_cap_history_batch, get_top_category, get_top_market_cap,
+};
+use indexmaker_backend::services::market_cap::top_by_market_cap;
+use indexmaker_backend::services::seed::SEED_CATEGORY_ID;
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(
+        Router::new()
+            .route("/api/market-cap/history", get(get_market_cap_history))
+            .route("/api/market-cap/top-category", get(get_top_category))
+            .route("/api/market-cap/top", get(get_top_market_cap))
+            .route("/api/market-cap/history/batch", post(get_market_cap_history_batch)),
+    )
+    .await
+}
+
+async fn post_json(app: &TestApp, uri: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_market_cap_history_invalid_date_format() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/history?coin_id=bitcoin&start_date=2024/01/01")
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Invalid start_date format"));
+}
+
+#[tokio::test]
+async fn test_market_cap_history_invalid_coin_id() {
+    let app = setup_test_app().await;
+    let (status, body) = app.get("/api/market-cap/history?coin_id=").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("coin_id cannot be empty"));
+}
+
+#[tokio::test]
+async fn test_market_cap_history_coin_not_found() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/history?coin_id=nonexistent-coin-xyz-123")
+        .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("not found in system"));
+}
+
+#[tokio::test]
+async fn test_market_cap_history_future_end_date() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/history?coin_id=bitcoin&end_date=2030-01-01")
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("cannot be in the future"));
+}
+
+#[tokio::test]
+async fn test_market_cap_history_start_after_end() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/history?coin_id=bitcoin&start_date=2024-12-31&end_date=2024-01-01")
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("start_date must be before"));
+}
+
+#[tokio::test]
+async fn test_market_cap_history_valid_request_with_bitcoin() {
+    let app = setup_test_app().await;
+    let start = app.seed_start;
+    let end = start + Duration::days(4);
+
+    let json = app
+        .get_json(&format!(
+            "/api/market-cap/history?coin_id=bitcoin&start_date={}&end_date={}",
+            start, end
+        ))
+        .await;
+
+    assert_eq!(json["coin_id"], "bitcoin");
+    assert_eq!(json["symbol"], "btc");
+    let data = json["data"].as_array().unwrap();
+    assert_eq!(data.len(), 5);
+    assert!(data[0]["market_cap"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_market_cap_history_default_date_range() {
+    let app = setup_test_app().await;
+    let json = app.get_json("/api/market-cap/history?coin_id=bitcoin").await;
+
+    // The default year covers all seeded history
+    assert_eq!(json["data"].as_array().unwrap().len(), common::SEED_DAYS as usize);
+}
+
+#[tokio::test]
+async fn test_market_cap_history_falls_back_to_coingecko() {
+    let app = setup_test_app().await;
+    let start = app.seed_start - Duration::days(30);
+    let day_ms = start.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+
+    Mock::given(method("GET"))
+        .and(path("/coins/bitcoin/market_chart/range"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "prices": [[day_ms, 42000.0]],
+            "market_caps": [[day_ms, 820000000000.0]],
+            "total_volumes": [[day_ms, 15000000000.0]],
+        })))
+        .expect(1)
+        .mount(&app.coingecko)
+        .await;
+
+    let json = app
+        .get_json(&format!(
+            "/api/market-cap/history?coin_id=bitcoin&start_date={}&end_date={}",
+            start,
+            start + Duration::days(1)
+        ))
+        .await;
+
+    let data = json["data"].as_array().unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["price"], 42000.0);
+}
+
+#[tokio::test]
+async fn test_top_category_invalid_category_id() {
+    let app = setup_test_app().await;
+    let (status, body) = app.get("/api/market-cap/top-category?category_id=").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("category_id cannot be empty"));
+}
+
+#[tokio::test]
+async fn test_top_category_top_out_of_range_below() {
+    let app = setup_test_app().await;
+    let (status, body) = app.get("/api/market-cap/top-category?category_id=defi&top=0").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("between 1 and 250"));
+}
+
+#[tokio::test]
+async fn test_top_category_top_out_of_range_above() {
+    let app = setup_test_app().await;
+    let (status, body) = app.get("/api/market-cap/top-category?category_id=defi&top=251").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("between 1 and 250"));
+}
+
+#[tokio::test]
+async fn test_top_category_future_date() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/top-category?category_id=defi&date=2030-01-01")
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("cannot be in the future"));
+}
+
+#[tokio::test]
+async fn test_top_category_invalid_date_format() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/top-category?category_id=defi&date=2024/01/01")
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Invalid date format"));
+}
+
+#[tokio::test]
+async fn test_top_category_not_found() {
+    let app = setup_test_app().await;
+    let (status, body) = app
+        .get("/api/market-cap/top-category?category_id=nonexistent-category-xyz-123")
+        .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("not found in system"));
+}
+
+#[tokio::test]
+async fn test_top_category_default_top_value() {
+    let app = setup_test_app().await;
+    let json = app
+        .get_json(&format!("/api/market-cap/top-category?category_id={}", SEED_CATEGORY_ID))
+        .await;
+
+    assert_eq!(json["category_id"], SEED_CATEGORY_ID);
+    assert_eq!(json["top"], 10);
+    assert_eq!(json["date"], Utc::now().date_naive().to_string());
+    assert_eq!(json["coins"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_top_category_custom_top_and_date() {
+    let app = setup_test_app().await;
+    let date = app.seed_start + Duration::days(10);
+    let json = app
+        .get_json(&format!(
+            "/api/market-cap/top-category?category_id={}&top=2&date={}",
+            SEED_CATEGORY_ID, date
+        ))
+        .await;
+
+    assert_eq!(json["top"], 2);
+    assert_eq!(json["date"], date.to_string());
+    assert_eq!(json["coins"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_top_category_valid_response_structure() {
+    let app = setup_test_app().await;
+    let json = app
+        .get_json(&format!("/api/market-cap/top-category?category_id={}&top=5", SEED_CATEGORY_ID))
+        .await;
+
+    assert!(json.get("category_id").is_some());
+    assert!(json.get("category_name").is_some());
+    assert!(json.get("date").is_some());
+    assert!(json.get("top").is_some());
+
+    let coins = json["coins"].as_array().unwrap();
+    let first_coin = &coins[0];
+    assert!(first_coin.get("symbol").is_some());
+    assert!(first_coin.get("name").is_some());
+    assert!(first_coin.get("price").is_some());
+    assert!(first_coin.get("volume_24h").is_some());
+
+    // Ranked by market cap: bitcoin leads the seeded category
+    assert_eq!(first_coin["rank"], 1);
+    assert_eq!(first_coin["coin_id"], "bitcoin");
+    let caps: Vec<f64> = coins.iter().map(|c| c["market_cap"].as_f64().unwrap()).collect();
+    assert!(caps.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[tokio::test]
+async fn test_top_market_cap_validation() {
+    let app = setup_test_app().await;
+    let (status, body) = app.get("/api/market-cap/top?n=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("between 1 and 250"));
+
+    let (status, body) = app.get("/api/market-cap/top?date=2030-01-01").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("cannot be in the future"));
+}
+
+#[tokio::test]
+async fn test_top_market_cap_ranks_all_coins_on_a_date() {
+    let app = setup_test_app().await;
+    let date = app.seed_start + Duration::days(5);
+
+    let json = app.get_json(&format!("/api/market-cap/top?date={}&n=2", date)).await;
+    assert_eq!(json["date"], date.to_string());
+    assert_eq!(json["n"], 2);
+    assert_eq!((&json["prices_from"], &json["prices_to"]), (&json!(date.to_string()), &json!(date.to_string())));
+    assert_eq!(json["partial"], false);
+    let coins = json["coins"].as_array().unwrap();
+    assert_eq!(coins.len(), 2);
+    assert_eq!(coins[0]["rank"], 1);
+    assert!(coins[0]["market_cap"].as_f64() >= coins[1]["market_cap"].as_f64());
+
+    // A coin outside any category, larger than all the others
+    coins_historical_prices::ActiveModel {
+        coin_id: Set("megacoin".to_string()),
+        symbol: Set("MEGA".to_string()),
+        date: Set(date),
+        price: Set(dec!(1)),
+        market_cap: Set(Some(dec!(100000000000000))),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    // Cached per (date, n)
+    let cached = app.get_json(&format!("/api/market-cap/top?date={}&n=2", date)).await;
+    assert_eq!(cached["coins"], json["coins"]);
+
+    let json = app.get_json(&format!("/api/market-cap/top?date={}&n=3", date)).await;
+    let coins = json["coins"].as_array().unwrap();
+    assert_eq!(coins[0]["coin_id"], "megacoin");
+    // Not in coins, so named after its symbol
+    assert_eq!(coins[0]["name"], "MEGA");
+    assert_eq!(coins.len(), 3);
+
+    // Dates without prices rank nothing
+    let json = app.get_json("/api/market-cap/top?date=2001-01-01").await;
+    assert!(json["coins"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_market_cap_history_batch_aligns_series() {
+    let app = setup_test_app().await;
+    let start = app.seed_start - Duration::days(1);
+    let end = app.seed_start + Duration::days(2);
+
+    let (status, json) = post_json(
+        &app,
+        "/api/market-cap/history/batch",
+        json!({
+            "coin_ids": ["ethereum", "bitcoin", "nonexistent-coin", "ethereum"],
+            "start_date": start.to_string(),
+            "end_date": end.to_string(),
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["dates"].as_array().unwrap().len(), 4);
+    assert_eq!(json["dates"][0], start.to_string());
+    assert_eq!(json["missing"], json!(["nonexistent-coin"]));
+
+    let coins = json["coins"].as_array().unwrap();
+    assert_eq!(coins.len(), 2);
+    assert_eq!(coins[0]["coin_id"], "ethereum");
+    assert_eq!(coins[1]["coin_id"], "bitcoin");
+    for coin in coins {
+        let market_caps = coin["market_caps"].as_array().unwrap();
+        assert_eq!(market_caps.len(), 4);
+        // Before the seeded history
+        assert!(market_caps[0].is_null());
+        assert!(market_caps[1..].iter().all(|mc| mc.as_f64().unwrap() > 0.0));
+        assert!(coin["prices"][3].as_f64().unwrap() > 0.0);
+    }
+}
+
+#[tokio::test]
+async fn test_market_cap_history_batch_validation() {
+    let app = setup_test_app().await;
+    let uri = "/api/market-cap/history/batch";
+
+    let (status, json) = post_json(&app, uri, json!({ "coin_ids": [] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("cannot be empty"));
+
+    let too_many: Vec<String> = (0..101).map(|i| format!("coin-{}", i)).collect();
+    let (status, _) = post_json(&app, uri, json!({ "coin_ids": too_many })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = post_json(
+        &app,
+        uri,
+        json!({ "coin_ids": ["bitcoin"], "start_date": "2015-01-01", "end_date": "2024-01-01" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("cannot exceed"));
+
+    let (status, _) = post_json(
+        &app,
+        uri,
+        json!({ "coin_ids": ["bitcoin"], "start_date": "2024-02-01", "end_date": "2024-01-01" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_top_market_cap_over_a_weekly_window() {
+    let app = setup_test_app().await;
+    let from = app.seed_start + Duration::days(7);
+    let to = from + Duration::days(6);
+
+    // Downsampled history: only the last day of the week is left
+    coins_historical_prices::ActiveModel {
+        coin_id: Set("weeklycoin".to_string()),
+        symbol: Set("WEEK".to_string()),
+        date: Set(to),
+        price: Set(dec!(1)),
+        market_cap: Set(Some(dec!(100000000000000))),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let day = top_by_market_cap(&app.db, from, from, 3).await.unwrap();
+    assert!(day.iter().all(|coin| coin.coin_id != "weeklycoin"));
+
+    let week = top_by_market_cap(&app.db, from, to, 3).await.unwrap();
+    assert_eq!(week[0].coin_id, "weeklycoin");
+    // Daily coins are ranked once, on their latest price of the window
+    let coin_ids: HashSet<&str> = week.iter().map(|coin| coin.coin_id.as_str()).collect();
+    assert_eq!(coin_ids.len(), week.len());
+}
@@ -0,0 +1,76 @@
+//! Integration tests for index methodology versions
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, Utc};
+
+use common::TestApp;
+use indexmaker_backend::handlers::index::get_index_config;
+use indexmaker_backend::handlers::methodology::{get_methodology_version, list_methodology_versions};
+use indexmaker_backend::services::methodology_documents::{self, MethodologyError};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn setup_test_app() -> TestApp {
+    TestApp::spawn(
+        Router::new()
+            .route("/get-index-config/{index_id}", get(get_index_config))
+            .route("/indexes/{index_id}/methodology", get(list_methodology_versions))
+            .route("/indexes/{index_id}/methodology/{version}", get(get_methodology_version)),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_methodology_versions_and_current_reference() {
+    let app = setup_test_app().await;
+    let today = Utc::now().date_naive();
+
+    // Without any version the config omits the reference
+    let config = app.get_json(&format!("/get-index-config/{}", SEED_INDEX_ID)).await;
+    assert!(config.get("methodology").is_none());
+
+    let v1 = methodology_documents::create(&app.db, SEED_INDEX_ID, "markdown", "# Equal weight", today - Duration::days(30))
+        .await
+        .unwrap();
+    let v2 = methodology_documents::create(&app.db, SEED_INDEX_ID, "text", "Market cap weight", today + Duration::days(7))
+        .await
+        .unwrap();
+    assert_eq!((v1.version, v2.version), (1, 2));
+
+    // A version can't take effect before the latest one
+    let err = methodology_documents::create(&app.db, SEED_INDEX_ID, "markdown", "Backdated", today)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, MethodologyError::EffectiveBeforeLatest { latest_version: 2, .. }));
+
+    // v2 is scheduled, so v1 is still in effect
+    let config = app.get_json(&format!("/get-index-config/{}", SEED_INDEX_ID)).await;
+    assert_eq!(config["methodology"]["version"], 1);
+    assert_eq!(config["methodology"]["url"], format!("/indexes/{}/methodology/1", SEED_INDEX_ID));
+
+    let list = app.get_json(&format!("/indexes/{}/methodology", SEED_INDEX_ID)).await;
+    assert_eq!(list["currentVersion"], 1);
+    assert_eq!(list["versions"][0]["version"], 2);
+    assert_eq!(list["versions"][1]["version"], 1);
+
+    let document = app.get_json(&format!("/indexes/{}/methodology/1", SEED_INDEX_ID)).await;
+    assert_eq!(document["format"], "markdown");
+    assert_eq!(document["content"], "# Equal weight");
+
+    let (status, _) = app.get(&format!("/indexes/{}/methodology/3", SEED_INDEX_ID)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.get("/indexes/999999/methodology").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_methodology_rejects_invalid_documents() {
+    let app = setup_test_app().await;
+    let today = Utc::now().date_naive();
+
+    let err = methodology_documents::create(&app.db, SEED_INDEX_ID, "pdf", "content", today).await.unwrap_err();
+    assert!(matches!(err, MethodologyError::InvalidFormat(_)));
+    let err = methodology_documents::create(&app.db, SEED_INDEX_ID, "text", "  ", today).await.unwrap_err();
+    assert!(matches!(err, MethodologyError::EmptyContent));
+}
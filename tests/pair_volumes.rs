@@ -0,0 +1,60 @@
+//! Integration tests for exchange-native pair volume snapshots
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::EntityTrait;
+
+use common::TestApp;
+use indexmaker_backend::entities::prelude::*;
+use indexmaker_backend::services::exchange_api::PairVolume;
+use indexmaker_backend::services::pair_volumes::{meets_floor, record, volume_on, MAX_SNAPSHOT_AGE_DAYS};
+
+fn volume(exchange: &str, base: &str, quote: &str, quote_volume: Decimal) -> PairVolume {
+    PairVolume {
+        exchange: exchange.to_string(),
+        trading_pair: format!("{}{}", base, quote),
+        base_asset: base.to_string(),
+        quote_asset: quote.to_string(),
+        base_volume: dec!(1),
+        quote_volume,
+    }
+}
+
+#[tokio::test]
+async fn test_pair_volume_snapshots() {
+    let app = TestApp::spawn(Router::new()).await;
+    let day = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+    let at = |date: NaiveDate| date.and_hms_opt(0, 30, 0).unwrap();
+
+    let snapshot = [
+        volume("binance", "BTC", "USDC", dec!(250000000)),
+        volume("binance", "FOO", "USDC", dec!(40000)),
+        volume("bitget", "FOO", "USDT", dec!(9000000)),
+    ];
+    assert_eq!(record(&app.db, &snapshot, at(day)).await.unwrap(), 3);
+    // Re-sampling the same day replaces it
+    record(&app.db, &[volume("binance", "FOO", "USDC", dec!(50000))], at(day)).await.unwrap();
+    assert_eq!(PairVolumes::find().all(&app.db).await.unwrap().len(), 3);
+
+    // Symbols and quotes as constituents spell them
+    assert_eq!(volume_on(&app.db, "Binance", "foo", "usdc", day).await.unwrap(), Some(dec!(50000)));
+    assert_eq!(volume_on(&app.db, "bitget", "FOO", "usdt", day).await.unwrap(), Some(dec!(9000000)));
+    assert_eq!(volume_on(&app.db, "bitget", "FOO", "usdc", day).await.unwrap(), None);
+    // Not sampled yet, then stale
+    assert_eq!(volume_on(&app.db, "binance", "BTC", "USDC", day - Duration::days(1)).await.unwrap(), None);
+    let stale = day + Duration::days(MAX_SNAPSHOT_AGE_DAYS);
+    assert_eq!(volume_on(&app.db, "binance", "BTC", "USDC", stale - Duration::days(1)).await.unwrap(), Some(dec!(250000000)));
+    assert_eq!(volume_on(&app.db, "binance", "BTC", "USDC", stale).await.unwrap(), None);
+
+    // A later snapshot takes over
+    record(&app.db, &[volume("binance", "FOO", "USDC", dec!(2000000))], at(day + Duration::days(2))).await.unwrap();
+    let floor = dec!(1000000);
+    assert!(!meets_floor(&app.db, "binance", "FOO", "usdc", day + Duration::days(1), floor).await.unwrap());
+    assert!(meets_floor(&app.db, "binance", "FOO", "usdc", day + Duration::days(3), floor).await.unwrap());
+    // Unsampled pairs aren't screened out
+    assert!(meets_floor(&app.db, "binance", "BAR", "usdc", day, floor).await.unwrap());
+}
@@ -0,0 +1,99 @@
+//! Integration tests for the portfolio value continuity check
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins_historical_prices, prelude::*, rebalances};
+use indexmaker_backend::services::portfolio_continuity::{self, DEFAULT_TOLERANCE_BPS};
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn stored_price(app: &TestApp, coin_id: &str, date: NaiveDate) -> Decimal {
+    CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
+        .filter(coins_historical_prices::Column::Date.eq(date))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .price
+}
+
+async fn insert_rebalance(app: &TestApp, date: NaiveDate, coins: serde_json::Value, value: Decimal, fees: Option<Decimal>) -> i32 {
+    rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(coins),
+        portfolio_value: Set(value),
+        total_weight: Set(dec!(2)),
+        timestamp: Set(pricing_time::rebalance_timestamp(date)),
+        rebalance_type: Set("periodic".to_string()),
+        total_fees: Set(fees),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap()
+    .id
+}
+
+fn entry(coin_id: &str, quantity: &str) -> serde_json::Value {
+    json!({ "coin_id": coin_id, "symbol": "X", "quantity": quantity, "weight": "1", "price": 1.0,
+            "exchange": "binance", "trading_pair": "usdc" })
+}
+
+#[tokio::test]
+async fn test_check_flags_value_jumps() {
+    let app = TestApp::spawn(Router::new()).await;
+    Rebalances::delete_many()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .exec(&app.db)
+        .await
+        .unwrap();
+
+    let day = |n| app.seed_start + Duration::days(n);
+    let basket = json!([entry("bitcoin", "0.01"), entry("cash:USDC", "100")]);
+    insert_rebalance(&app, day(0), basket.clone(), dec!(700), Some(dec!(1))).await;
+
+    // Priced at the stored prices, fees taken out of the value
+    let marked = dec!(0.01) * stored_price(&app, "bitcoin", day(7)).await + dec!(100);
+    let continuous = insert_rebalance(&app, day(7), basket.clone(), marked - dec!(2), Some(dec!(2))).await;
+    // 1% off, and holding a coin without prices
+    let marked = dec!(0.01) * stored_price(&app, "bitcoin", day(14)).await + dec!(100);
+    let jump = insert_rebalance(
+        &app,
+        day(14),
+        json!([entry("bitcoin", "0.01"), entry("unknown-coin", "5")]),
+        marked * dec!(1.01),
+        None,
+    )
+    .await;
+    let unpriced = insert_rebalance(&app, day(21), basket, dec!(700), Some(dec!(1))).await;
+
+    let report = portfolio_continuity::check_index(&app.db, SEED_INDEX_ID, DEFAULT_TOLERANCE_BPS).await.unwrap();
+    assert_eq!((report.checked, report.flagged), (3, 2));
+
+    let check = |id| report.checks.iter().find(|c| c.rebalance_id == id).unwrap();
+    assert!(!check(continuous).flagged);
+    assert_eq!(check(continuous).deviation_bps, Some(0));
+    assert_eq!(check(continuous).marked_value, Some(check(continuous).value_before_fees));
+
+    assert!(check(jump).flagged);
+    assert!(!check(jump).fees_recorded);
+    assert_eq!(check(jump).deviation_bps, Some(100));
+    assert_eq!(check(jump).previous_rebalance_id, continuous);
+
+    assert!(check(unpriced).flagged);
+    assert_eq!(check(unpriced).marked_value, None);
+    assert_eq!(check(unpriced).missing_prices, vec!["unknown-coin".to_string()]);
+
+    // A looser tolerance accepts the jump, never the missing price
+    let report = portfolio_continuity::check_index(&app.db, SEED_INDEX_ID, 200).await.unwrap();
+    assert_eq!(report.flagged, 1);
+}
@@ -0,0 +1,104 @@
+//! Integration tests for fallback pricing of constituents without a price
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::{Duration, NaiveDate};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins_historical_prices, daily_prices, prelude::*, rebalances};
+use indexmaker_backend::handlers::index::get_index_price_at_date;
+use indexmaker_backend::services::daily_prices::backfill_daily_prices;
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::rebalance_schema;
+use indexmaker_backend::services::rebalancing::CoinRebalanceInfo;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn delete_prices(app: &TestApp, coin_id: &str, from: NaiveDate, to: NaiveDate) {
+    CoinsHistoricalPrices::delete_many()
+        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
+        .filter(coins_historical_prices::Column::Date.between(from, to))
+        .exec(&app.db)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_index_price_carries_forward_missing_prices() {
+    let app = TestApp::spawn(Router::new().route("/indexes/{index_id}/price-at-date", get(get_index_price_at_date))).await;
+    let date = app.seed_start + Duration::days(40);
+    let uri = format!("/indexes/{}/price-at-date?date={}", SEED_INDEX_ID, date);
+
+    let body = app.get_json(&uri).await;
+    assert!(body.get("stale").is_none());
+    assert!(body["constituents"].as_array().unwrap().iter().all(|c| c.get("fallback").is_none()));
+    assert!(body["constituents"].as_array().unwrap().iter().all(|c| {
+        c["priceSource"] == "db-cache" && c["priceTimestamp"] == pricing_time::cutoff_timestamp(date)
+    }));
+
+    // Not stored, and CoinGecko doesn't have it either
+    delete_prices(&app, "solana", date, date).await;
+    let body = app.get_json(&uri).await;
+    assert_eq!(body["stale"], true);
+    let solana = body["constituents"].as_array().unwrap().iter().find(|c| c["coinId"] == "solana").unwrap();
+    assert_eq!(
+        solana["fallback"],
+        serde_json::json!({ "rule": "carriedForward", "from": (date - Duration::days(1)).to_string() })
+    );
+    assert_eq!(solana["priceSource"], "carried-forward");
+    assert_eq!(solana["priceTimestamp"], pricing_time::cutoff_timestamp(date - Duration::days(1)));
+
+    // Nothing recent to carry forward, and a third of the basket is too much to exclude
+    delete_prices(&app, "solana", date - Duration::days(7), date).await;
+    let (status, body) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body.contains("may be excluded"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_index_price_rejects_unparseable_rebalances() {
+    let app = TestApp::spawn(Router::new().route("/indexes/{index_id}/price-at-date", get(get_index_price_at_date))).await;
+    let date = app.seed_start + Duration::days(40);
+    let rebalance = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .filter(rebalances::Column::Timestamp.lte(pricing_time::day_end_timestamp(date)))
+        .order_by_desc(rebalances::Column::Timestamp)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut coins: Vec<CoinRebalanceInfo> = rebalance_schema::decode(rebalance.coins.clone()).unwrap();
+    coins[0].quantity = "not-a-number".to_string();
+    let mut rebalance = rebalance.into_active_model();
+    rebalance.coins = Set(rebalance_schema::encode(&coins).unwrap());
+    rebalance.update(&app.db).await.unwrap();
+
+    // A quantity that doesn't parse fails the price rather than counting as zero
+    let (status, body) = app.get(&format!("/indexes/{}/price-at-date?date={}", SEED_INDEX_ID, date)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body.contains("Invalid quantity 'not-a-number'"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_daily_price_backfill_carries_forward_missing_prices() {
+    let app = TestApp::spawn(Router::new()).await;
+    let date = app.seed_start + Duration::days(40);
+    DailyPrices::delete_many()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    delete_prices(&app, "solana", date, date).await;
+
+    backfill_daily_prices(&app.db, &app.state.coingecko, SEED_INDEX_ID).await.unwrap();
+
+    let row = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .one(&app.db)
+        .await
+        .unwrap();
+    assert!(row.is_some());
+}
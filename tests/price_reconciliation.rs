@@ -0,0 +1,76 @@
+//! Integration tests for price reconciliation against a mocked Binance API
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serde_json::json;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::prelude::*;
+use indexmaker_backend::services::price_reconciliation::{self, BinanceKlines, ReconciliationConfig};
+use indexmaker_backend::services::price_utils::get_coins_historical_price_for_date;
+
+async fn stored_price(app: &TestApp, coin_id: &str, date: NaiveDate) -> Decimal {
+    let price = get_coins_historical_price_for_date(&app.db, coin_id, date).await.unwrap().unwrap();
+    Decimal::from_f64_retain(price).unwrap().round_dp(8)
+}
+
+async fn mock_kline(binance: &MockServer, symbol: &str, date: NaiveDate, open: Decimal) {
+    let open_time = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+    Mock::given(method("GET"))
+        .and(path("/api/v3/klines"))
+        .and(query_param("symbol", symbol))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            [open_time, open.to_string(), "0", "0", "0", "0", open_time + 86_399_999]
+        ])))
+        .mount(binance)
+        .await;
+}
+
+#[tokio::test]
+async fn test_reconciliation_flags_deviations_and_skips_unlisted() {
+    let app = TestApp::spawn(Router::new()).await;
+    let today = Utc::now().date_naive();
+    let date = today - Duration::days(1);
+
+    let binance = MockServer::start().await;
+    mock_kline(&binance, "BTCUSDT", date, stored_price(&app, "bitcoin", date).await).await;
+    mock_kline(&binance, "ETHUSDT", date, stored_price(&app, "ethereum", date).await * dec!(1.1)).await;
+    // SOL and LINK fall through to Binance's "Invalid symbol" response
+    Mock::given(method("GET"))
+        .and(path("/api/v3/klines"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({"code": -1121, "msg": "Invalid symbol."})))
+        .mount(&binance)
+        .await;
+
+    let config = ReconciliationConfig { sample_size: 10, threshold_bps: 200, binance_api_url: binance.uri() };
+    let source = BinanceKlines::new(&config.binance_api_url);
+
+    let summary = price_reconciliation::run(&app.db, &source, &config, date).await.unwrap();
+    assert_eq!(summary.sampled, 4);
+    assert_eq!(summary.checked, 2);
+    assert_eq!(summary.unavailable, 2);
+    assert_eq!(summary.errors, 0);
+    assert_eq!(summary.flagged, 1);
+    // 10% above the reference is 1/1.1 = 909 bps below it
+    assert_eq!(summary.max_deviation_bps, 909);
+
+    // Re-running the same day updates the checks instead of duplicating them
+    price_reconciliation::run(&app.db, &source, &config, date).await.unwrap();
+    assert_eq!(PriceReconciliationChecks::find().count(&app.db).await.unwrap(), 2);
+
+    let report = price_reconciliation::report(&app.db, 30, today).await.unwrap();
+    assert_eq!(report.checks, 2);
+    assert_eq!(report.flagged, 1);
+    assert_eq!(report.median_deviation_bps, Some(0));
+    assert_eq!(report.flagged_checks[0].coin_id, "ethereum");
+    assert_eq!(report.flagged_checks[0].source, "binance");
+}
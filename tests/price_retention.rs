@@ -0,0 +1,72 @@
+//! Integration tests for the coins historical price retention policy
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use serde_json::json;
+
+use common::{TestApp, SEED_DAYS};
+use indexmaker_backend::entities::{coins_historical_prices, prelude::*, rebalances};
+use indexmaker_backend::services::price_retention::{self, RetentionConfig, RetentionMode};
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn price_rows(app: &TestApp, coin_id: &str) -> u64 {
+    CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq(coin_id))
+        .count(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_coins_of_v1_manual_rebalances_are_kept() {
+    let app = TestApp::spawn(Router::new()).await;
+    let old = Utc::now().date_naive() - Duration::days(5 * 365);
+
+    // Two weeks of daily history past the cutoff for two coins
+    for coin_id in ["dogecoin", "delisted-coin"] {
+        CoinsHistoricalPrices::insert_many((0..14).map(|day| coins_historical_prices::ActiveModel {
+            coin_id: Set(coin_id.to_string()),
+            symbol: Set(coin_id.to_string()),
+            date: Set(old + Duration::days(day)),
+            price: Set(dec!(0.1)),
+            ..Default::default()
+        }))
+        .exec(&app.db)
+        .await
+        .unwrap();
+    }
+    // dogecoin is only held by a manual rebalance stored before the schema
+    // was versioned
+    rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{
+            "coinId": "dogecoin",
+            "symbol": "DOGE",
+            "weight": "1",
+            "quantity": "1000",
+            "price": 0.1,
+            "exchange": "binance",
+            "tradingPair": "usdc",
+        }])),
+        portfolio_value: Set(dec!(100)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(app.seed_start + Duration::days(SEED_DAYS))),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let config = RetentionConfig { max_age_years: 3, mode: RetentionMode::Drop, dry_run: false };
+    let report = price_retention::run(&app.db, &config).await.unwrap();
+    assert_eq!((report.affected_coins, report.rows_removed), (1, 14));
+
+    assert_eq!(price_rows(&app, "dogecoin").await, 14);
+    assert_eq!(price_rows(&app, "delisted-coin").await, 0);
+}
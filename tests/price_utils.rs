@@ -0,0 +1,131 @@
+//! Integration tests for self-healing price lookups, against FakeCoinGecko
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::{routing::get, Router};
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+use common::TestApp;
+use indexmaker_backend::entities::{coins_historical_prices, prelude::*};
+use indexmaker_backend::handlers::index::get_index_price_at_date;
+use indexmaker_backend::services::coingecko::CoinGeckoService;
+use indexmaker_backend::services::coingecko_fake::FakeCoinGecko;
+use indexmaker_backend::services::price_utils::{
+    get_coins_historical_price_for_date, get_or_fetch_coins_historical_price,
+};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn setup(fake: FakeCoinGecko) -> (TestApp, Arc<FakeCoinGecko>, CoinGeckoService) {
+    let fake = Arc::new(fake);
+    let coingecko = CoinGeckoService::from_api(fake.clone());
+    let app = TestApp::spawn_with_coingecko(Router::new(), coingecko.clone()).await;
+    (app, fake, coingecko)
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+#[tokio::test]
+async fn test_get_or_fetch_backfills_missing_coin() {
+    let today = today();
+    let (app, fake, coingecko) = setup(FakeCoinGecko::new().with_prices(
+        "aave",
+        &[(today - Duration::days(2), 300.0), (today - Duration::days(1), 310.0), (today, 320.0)],
+    ))
+    .await;
+
+    let price = get_or_fetch_coins_historical_price(&app.db, &coingecko, "aave", "aave", today - Duration::days(1))
+        .await
+        .unwrap();
+
+    assert_eq!(price, 310.0);
+    assert_eq!(fake.requests(), vec!["fetch_price_on_date aave"]);
+
+    // Only the requested day is fetched and stored
+    let stored = CoinsHistoricalPrices::find()
+        .filter(coins_historical_prices::Column::CoinId.eq("aave"))
+        .count(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+    let price = get_or_fetch_coins_historical_price(&app.db, &coingecko, "aave", "aave", today - Duration::days(1))
+        .await
+        .unwrap();
+    assert_eq!(price, 310.0);
+    assert_eq!(fake.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_get_or_fetch_prefers_stored_price() {
+    let (app, fake, coingecko) = setup(FakeCoinGecko::new().with_prices("bitcoin", &[(today(), 1.0)])).await;
+
+    let stored = get_coins_historical_price_for_date(&app.db, "bitcoin", app.seed_start)
+        .await
+        .unwrap()
+        .expect("seeded price");
+    let price = get_or_fetch_coins_historical_price(&app.db, &coingecko, "bitcoin", "btc", app.seed_start)
+        .await
+        .unwrap();
+
+    assert_eq!(price, stored);
+    assert!(fake.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_or_fetch_unknown_coin_fails() {
+    let (app, _fake, coingecko) = setup(FakeCoinGecko::new()).await;
+
+    let result = get_or_fetch_coins_historical_price(&app.db, &coingecko, "delisted", "dl", today()).await;
+
+    assert!(result.unwrap_err().to_string().contains("not found"));
+}
+
+#[tokio::test]
+async fn test_index_price_fetches_each_missing_constituent_once() {
+    let date = today() - Duration::days(1);
+    let fake = Arc::new(
+        FakeCoinGecko::new()
+            .with_prices("bitcoin", &[(date, 61000.0)])
+            .with_prices("ethereum", &[(date, 3100.0)])
+            .with_prices("solana", &[(date, 160.0)]),
+    );
+    let coingecko = CoinGeckoService::from_api(fake.clone());
+    let app = TestApp::spawn_with_coingecko(
+        Router::new().route("/indexes/{index_id}/price-at-date", get(get_index_price_at_date)),
+        coingecko.clone(),
+    )
+    .await;
+    CoinsHistoricalPrices::delete_many()
+        .filter(coins_historical_prices::Column::Date.eq(date))
+        .exec(&app.db)
+        .await
+        .unwrap();
+
+    let uri = format!("/indexes/{}/price-at-date?date={}", SEED_INDEX_ID, date);
+    let body = app.get_json(&uri).await;
+    assert!(body["price"].is_string(), "{}", body);
+
+    let mut requests = fake.requests();
+    requests.sort();
+    assert_eq!(
+        requests,
+        vec!["fetch_price_on_date bitcoin", "fetch_price_on_date ethereum", "fetch_price_on_date solana"]
+    );
+
+    // Now stored
+    app.get_json(&uri).await;
+    assert_eq!(fake.requests().len(), 3);
+
+    // Lookups of the same coin and day share one request, even without data
+    let other_day = date - Duration::days(1);
+    let (a, b) = tokio::join!(
+        coingecko.get_price_on_date("bitcoin", other_day),
+        coingecko.get_price_on_date("bitcoin", other_day)
+    );
+    assert_eq!((a.unwrap(), b.unwrap()), (None, None));
+    assert_eq!(fake.requests().len(), 4);
+}
@@ -0,0 +1,120 @@
+//! Integration tests for the two-step rebalance approval
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use tower::ServiceExt;
+
+use common::TestApp;
+use indexmaker_backend::entities::{prelude::*, rebalance_approvals, rebalances};
+use indexmaker_backend::handlers::admin;
+use indexmaker_backend::handlers::metering::meter;
+use indexmaker_backend::services::api_keys::{self, ApiKeyTier};
+use indexmaker_backend::services::rebalance_approvals::{is_approved, request, statuses};
+use indexmaker_backend::services::rebalance_deployment::mark_deployed;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+fn router(app: &TestApp) -> Router {
+    Router::new()
+        .route("/admin/rebalances/{id}/approval", get(admin::get_rebalance_approval))
+        .route("/admin/rebalances/{id}/request-approval", post(admin::request_rebalance_approval))
+        .route("/admin/rebalances/{id}/approve", post(admin::approve_rebalance))
+        .route("/admin/rebalances/{id}/reject", post(admin::reject_rebalance))
+        .layer(axum::middleware::from_fn_with_state(app.state.clone(), meter))
+        .with_state(app.state.clone())
+}
+
+async fn send(router: &Router, method: Method, uri: &str, key: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder().method(method).uri(uri).header("x-api-key", key);
+    let response = router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+async fn seeded_rebalances(app: &TestApp) -> Vec<rebalances::Model> {
+    Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .order_by_asc(rebalances::Column::Id)
+        .all(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_approval_needs_a_second_credential() {
+    let app = TestApp::spawn(Router::new()).await;
+    let router = router(&app);
+    let (first, first_key) = api_keys::create(&app.db, "ops-1", ApiKeyTier::Admin, None).await.unwrap();
+    let (second, second_key) = api_keys::create(&app.db, "ops-2", ApiKeyTier::Admin, None).await.unwrap();
+    let (_, public_key) = api_keys::create(&app.db, "dashboard", ApiKeyTier::Public, None).await.unwrap();
+    let rebalance = seeded_rebalances(&app).await.pop().unwrap();
+    let uri = |action: &str| format!("/admin/rebalances/{}/{}", rebalance.id, action);
+
+    assert_eq!(send(&router, Method::GET, &uri("approval"), &public_key).await.0, StatusCode::FORBIDDEN);
+    assert_eq!(send(&router, Method::GET, &uri("approval"), &first_key).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(send(&router, Method::POST, &uri("approve"), &second_key).await.0, StatusCode::CONFLICT);
+    assert_eq!(
+        send(&router, Method::POST, "/admin/rebalances/999999/request-approval", &first_key).await.0,
+        StatusCode::NOT_FOUND
+    );
+
+    let (status, body) = send(&router, Method::POST, &uri("request-approval"), &first_key).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["status"], "pending");
+    assert_eq!(body["requestedBy"], format!("api_key:{}", first.id));
+    assert_eq!(send(&router, Method::POST, &uri("request-approval"), &second_key).await.0, StatusCode::CONFLICT);
+
+    // The requester can't approve their own request
+    assert_eq!(send(&router, Method::POST, &uri("approve"), &first_key).await.0, StatusCode::FORBIDDEN);
+    assert!(!is_approved(&app.db, rebalance.id, Utc::now().naive_utc()).await.unwrap());
+
+    let (status, body) = send(&router, Method::POST, &uri("approve"), &second_key).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "approved");
+    assert_eq!(body["decidedBy"], format!("api_key:{}", second.id));
+    assert!(is_approved(&app.db, rebalance.id, Utc::now().naive_utc()).await.unwrap());
+    assert_eq!(send(&router, Method::GET, &uri("approval"), &first_key).await.1["status"], "approved");
+    assert_eq!(send(&router, Method::POST, &uri("reject"), &first_key).await.0, StatusCode::CONFLICT);
+
+    // Approvals lapse at their expiry
+    assert!(!is_approved(&app.db, rebalance.id, Utc::now().naive_utc() + Duration::days(2)).await.unwrap());
+
+    mark_deployed(&app.db, rebalance.id, "0xabc", 1).await.unwrap();
+    assert_eq!(send(&router, Method::POST, &uri("request-approval"), &first_key).await.0, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_rejected_and_expired_requests() {
+    let app = TestApp::spawn(Router::new()).await;
+    let router = router(&app);
+    let (_, first_key) = api_keys::create(&app.db, "ops-1", ApiKeyTier::Admin, None).await.unwrap();
+    let (_, second_key) = api_keys::create(&app.db, "ops-2", ApiKeyTier::Admin, None).await.unwrap();
+    let rebalance = seeded_rebalances(&app).await.pop().unwrap();
+    let uri = |action: &str| format!("/admin/rebalances/{}/{}", rebalance.id, action);
+
+    assert_eq!(send(&router, Method::POST, &uri("request-approval"), &first_key).await.0, StatusCode::CREATED);
+    let (status, body) = send(&router, Method::POST, &uri("reject"), &second_key).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "rejected");
+    assert!(!is_approved(&app.db, rebalance.id, Utc::now().naive_utc()).await.unwrap());
+
+    // A lapsed request can't be approved, and a new one can be opened
+    let now = Utc::now().naive_utc();
+    let approval = request(&app.db, rebalance.id, "admin", now).await.unwrap();
+    assert_eq!(approval.status, statuses::PENDING);
+    let mut lapsed: rebalance_approvals::ActiveModel = approval.into();
+    lapsed.expires_at = Set(now - Duration::minutes(1));
+    lapsed.update(&app.db).await.unwrap();
+
+    assert_eq!(send(&router, Method::GET, &uri("approval"), &first_key).await.1["status"], "expired");
+    assert_eq!(send(&router, Method::POST, &uri("approve"), &second_key).await.0, StatusCode::CONFLICT);
+    assert_eq!(send(&router, Method::POST, &uri("request-approval"), &first_key).await.0, StatusCode::CREATED);
+}
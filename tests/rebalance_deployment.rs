@@ -0,0 +1,103 @@
+//! Integration tests for picking and recording rebalance deployments
+
+mod common;
+
+use axum::Router;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{itps, prelude::*, rebalances};
+use indexmaker_backend::services::rebalance_deployment::{clear_submission, mark_deployed, mark_submitted, pending};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+async fn insert_itp(app: &TestApp, orbit_address: &str, index_id: Option<i64>, state: i16) {
+    itps::ActiveModel {
+        orbit_address: Set(orbit_address.to_string()),
+        index_id: Set(index_id),
+        name: Set("Deploy Test".to_string()),
+        symbol: Set("DPLY".to_string()),
+        state: Set(state),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_pending_and_mark_deployed() {
+    let app = TestApp::spawn(Router::new()).await;
+
+    // Seeded rebalances aren't pending until an active ITP tracks the index
+    assert!(pending(&app.db).await.unwrap().is_empty());
+    insert_itp(&app, "0x00000000000000000000000000000000000d0001", Some(SEED_INDEX_ID as i64), 3).await;
+    insert_itp(&app, "0x00000000000000000000000000000000000d0002", None, 1).await;
+    assert!(pending(&app.db).await.unwrap().is_empty());
+
+    insert_itp(&app, "0x00000000000000000000000000000000000d0003", Some(SEED_INDEX_ID as i64), 1).await;
+    let latest = Rebalances::find()
+        .filter(rebalances::Column::IndexId.eq(SEED_INDEX_ID))
+        .order_by_desc(rebalances::Column::Timestamp)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let found = pending(&app.db).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].rebalance.id, latest.id);
+    assert_eq!(found[0].itp.orbit_address, "0x00000000000000000000000000000000000d0003");
+
+    mark_deployed(&app.db, latest.id, "0xabc", 12_345).await.unwrap();
+    let deployed = Rebalances::find_by_id(latest.id).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(deployed.deployed, Some(true));
+    assert_eq!(deployed.tx_hash.as_deref(), Some("0xabc"));
+    assert_eq!(deployed.deployed_block, Some(12_345));
+    assert!(deployed.deployed_at.is_some());
+    assert!(pending(&app.db).await.unwrap().is_empty());
+
+    // A newer rebalance is pending again
+    let newer = rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(latest.coins.clone()),
+        portfolio_value: Set(latest.portfolio_value),
+        total_weight: Set(latest.total_weight),
+        timestamp: Set(latest.timestamp + 86_400),
+        rebalance_type: Set("periodic".to_string()),
+        deployed: Set(Some(false)),
+        created_at: Set(Some(Utc::now().naive_utc())),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    let found = pending(&app.db).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].rebalance.id, newer.id);
+}
+
+#[tokio::test]
+async fn test_submitted_rebalance_stays_pending_with_its_tx_hash() {
+    let app = TestApp::spawn(Router::new()).await;
+    insert_itp(&app, "0x00000000000000000000000000000000000d0004", Some(SEED_INDEX_ID as i64), 1).await;
+    let found = pending(&app.db).await.unwrap();
+    assert_eq!(found.len(), 1);
+    let rebalance_id = found[0].rebalance.id;
+    assert_eq!(found[0].rebalance.tx_hash, None);
+
+    // Sent but not mined: still pending, reconciled by its hash next run
+    mark_submitted(&app.db, rebalance_id, "0xdef").await.unwrap();
+    let found = pending(&app.db).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].rebalance.deployed, Some(false));
+    assert_eq!(found[0].rebalance.tx_hash.as_deref(), Some("0xdef"));
+
+    // Reverted or dropped: the hash is forgotten so it's sent again
+    clear_submission(&app.db, rebalance_id).await.unwrap();
+    let found = pending(&app.db).await.unwrap();
+    assert_eq!(found[0].rebalance.tx_hash, None);
+
+    mark_submitted(&app.db, rebalance_id, "0x123").await.unwrap();
+    mark_deployed(&app.db, rebalance_id, "0x123", 77).await.unwrap();
+    assert!(pending(&app.db).await.unwrap().is_empty());
+}
@@ -0,0 +1,112 @@
+//! Integration tests for upgrading stored rebalance coins
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::json;
+
+use common::{TestApp, SEED_DAYS};
+use indexmaker_backend::entities::{prelude::*, rebalances};
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::rebalance_schema::{self, CURRENT_VERSION};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+use indexmaker_backend::services::snapshot_log::{self, kinds};
+
+#[tokio::test]
+async fn test_upgrade_stored_rewrites_old_rebalances() {
+    let app = TestApp::spawn(Router::new()).await;
+
+    // As manual rebalances used to store them
+    let legacy = rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{
+            "coinId": "bitcoin",
+            "symbol": "BTC",
+            "weight": "1",
+            "quantity": "0.02",
+            "price": 60000.0,
+            "exchange": "binance",
+            "tradingPair": "usdc",
+        }])),
+        portfolio_value: Set(dec!(1200)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(app.seed_start + Duration::days(SEED_DAYS))),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    // Unparseable even after upgrading
+    let broken = rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{ "coin_id": "bitcoin" }])),
+        portfolio_value: Set(dec!(1200)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(legacy.timestamp + 86400),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    let seeded = Rebalances::find().all(&app.db).await.unwrap().len() - 2;
+
+    // Seeded rebalances are written in the current version already
+    let summary = rebalance_schema::upgrade_stored(&app.db, true).await.unwrap();
+    assert_eq!((summary.scanned, summary.upgraded), (seeded + 2, 1));
+    assert_eq!(summary.failed.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![broken.id]);
+    let unchanged = Rebalances::find_by_id(legacy.id).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(unchanged.coins, legacy.coins);
+
+    let summary = rebalance_schema::upgrade_stored(&app.db, false).await.unwrap();
+    assert_eq!(summary.upgraded, 1);
+    let upgraded = Rebalances::find_by_id(legacy.id).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(upgraded.coins[0]["coin_id"], json!("bitcoin"));
+    assert_eq!(upgraded.coins[0]["trading_pair"], json!("usdc"));
+    assert_eq!(upgraded.coins[0]["schema_version"], json!(CURRENT_VERSION));
+
+    // Nothing left to do
+    let summary = rebalance_schema::upgrade_stored(&app.db, false).await.unwrap();
+    assert_eq!((summary.upgraded, summary.failed.len()), (0, 1));
+}
+
+#[tokio::test]
+async fn test_upgrade_keeps_snapshot_proofs_matching() {
+    let app = TestApp::spawn(Router::new()).await;
+    let date = app.seed_start + Duration::days(SEED_DAYS);
+    rebalances::ActiveModel {
+        index_id: Set(SEED_INDEX_ID),
+        coins: Set(json!([{
+            "coinId": "bitcoin",
+            "symbol": "BTC",
+            "weight": "1",
+            "quantity": "0.02",
+            "price": 60000.0,
+            "exchange": "binance",
+            "tradingPair": "usdc",
+        }])),
+        portfolio_value: Set(dec!(1200)),
+        total_weight: Set(dec!(1)),
+        timestamp: Set(pricing_time::rebalance_timestamp(date)),
+        rebalance_type: Set("manual".to_string()),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+    snapshot_log::append_pending(&app.db, SEED_INDEX_ID, Utc::now().naive_utc()).await.unwrap();
+
+    let summary = rebalance_schema::upgrade_stored(&app.db, false).await.unwrap();
+    assert_eq!(summary.upgraded, 1);
+
+    // The logged v1 payload still matches the upgraded row
+    let proof = snapshot_log::proof(&app.db, SEED_INDEX_ID, date).await.unwrap().unwrap();
+    assert!(proof.chain_valid);
+    let logged = proof.entries.iter().find(|entry| entry.kind == kinds::REBALANCE).unwrap();
+    assert_eq!(logged.payload["coins"][0]["coinId"], json!("bitcoin"));
+    assert!(logged.matches_current);
+}
@@ -0,0 +1,174 @@
+//! Integration tests for scheduled reports
+
+mod common;
+
+use axum::Router;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, Set};
+use wiremock::{
+    matchers::{body_partial_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::subscriptions;
+use indexmaker_backend::services::email::{EmailConfig, Mailer};
+use indexmaker_backend::services::reports::{self, ReportTemplate};
+use indexmaker_backend::services::seed::SEED_INDEX_SYMBOL;
+
+#[tokio::test]
+async fn test_reports_are_stored_once_per_period() {
+    let app = TestApp::spawn(Router::new()).await;
+    let today = Utc::now().date_naive();
+
+    let weekly = reports::generate(&app.db, ReportTemplate::WeeklyPerformance, today).await.unwrap();
+    let mut formats: Vec<&str> = weekly.iter().map(|r| r.format.as_str()).collect();
+    formats.sort();
+    assert_eq!(formats, vec!["csv", "html", "pdf"]);
+    assert_eq!(
+        (weekly[0].period_start, weekly[0].period_end),
+        ReportTemplate::WeeklyPerformance.last_period(today)
+    );
+
+    let csv = weekly.iter().find(|r| r.format == "csv").unwrap();
+    let csv = String::from_utf8(csv.content.clone()).unwrap();
+    assert!(csv.starts_with("Index,Symbol,Open,Close,Return %,TVL (USD)\n"));
+    let seed_row = csv.lines().find(|l| l.contains(&format!(",{},", SEED_INDEX_SYMBOL))).unwrap();
+    // The seed history covers last week, so the return is known
+    assert_ne!(seed_row.split(',').nth(4), Some("-"));
+    let pdf = weekly.iter().find(|r| r.format == "pdf").unwrap();
+    assert!(pdf.content.starts_with(b"%PDF-"));
+
+    // Regenerating the same period doesn't duplicate
+    let again = reports::generate(&app.db, ReportTemplate::WeeklyPerformance, today).await.unwrap();
+    assert_eq!(again.iter().map(|r| r.id).collect::<Vec<_>>(), weekly.iter().map(|r| r.id).collect::<Vec<_>>());
+
+    reports::generate(&app.db, ReportTemplate::MonthlyRebalance, today).await.unwrap();
+    let listed = reports::list(&app.db, None, 100).await.unwrap();
+    assert_eq!(listed.len(), 6);
+    assert!(listed.iter().all(|r| r.size > 0));
+    let monthly = reports::list(&app.db, Some(ReportTemplate::MonthlyRebalance), 100).await.unwrap();
+    assert_eq!(monthly.len(), 3);
+}
+
+#[tokio::test]
+async fn test_reports_are_emailed_to_subscribers_once() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    for email in ["alice@example.com", "bob@example.com"] {
+        subscriptions::ActiveModel {
+            email: Set(email.to_string()),
+            twitter: Set(None),
+            created_at: Set(Some(now)),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let email_api = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&email_api)
+        .await;
+    let mailer = Mailer::new(EmailConfig {
+        api_url: format!("{}/emails", email_api.uri()),
+        api_key: Some("test-key".to_string()),
+        from: "reports@example.com".to_string(),
+    });
+
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert_eq!(reports::deliver(&app.db, &mailer, &stored, now).await.unwrap(), 2);
+
+    // Already delivered
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert_eq!(reports::deliver(&app.db, &mailer, &stored, now).await.unwrap(), 0);
+    assert!(stored.iter().all(|r| r.recipients == 2 && r.delivered_at.is_some()));
+
+    let requests = email_api.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["to"], serde_json::json!(["alice@example.com"]));
+    assert!(body["html"].as_str().unwrap().contains("Monthly rebalance report"));
+    assert_eq!(body["attachments"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_rejected_recipient_does_not_block_delivery() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    for email in ["alice@example.com", "bad@example", "bob@example.com"] {
+        subscriptions::ActiveModel {
+            email: Set(email.to_string()),
+            twitter: Set(None),
+            created_at: Set(Some(now)),
+            ..Default::default()
+        }
+        .insert(&app.db)
+        .await
+        .unwrap();
+    }
+
+    let email_api = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .and(body_partial_json(serde_json::json!({"to": ["bad@example"]})))
+        .respond_with(ResponseTemplate::new(422))
+        .with_priority(1)
+        .expect(1)
+        .mount(&email_api)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/emails"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&email_api)
+        .await;
+    let mailer = Mailer::new(EmailConfig {
+        api_url: format!("{}/emails", email_api.uri()),
+        api_key: None,
+        from: "reports@example.com".to_string(),
+    });
+
+    // Bob is still emailed after the bad address, and the report counts as
+    // delivered so the next run doesn't email Alice and Bob again
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert_eq!(reports::deliver(&app.db, &mailer, &stored, now).await.unwrap(), 2);
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert_eq!(reports::deliver(&app.db, &mailer, &stored, now).await.unwrap(), 0);
+    assert!(stored.iter().all(|r| r.recipients == 2 && r.delivered_at.is_some()));
+}
+
+#[tokio::test]
+async fn test_report_is_retried_when_no_recipient_was_reached() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    subscriptions::ActiveModel {
+        email: Set("alice@example.com".to_string()),
+        twitter: Set(None),
+        created_at: Set(Some(now)),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let email_api = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&email_api)
+        .await;
+    let mailer = Mailer::new(EmailConfig {
+        api_url: format!("{}/emails", email_api.uri()),
+        api_key: None,
+        from: "reports@example.com".to_string(),
+    });
+
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert!(reports::deliver(&app.db, &mailer, &stored, now).await.is_err());
+    let stored = reports::generate(&app.db, ReportTemplate::MonthlyRebalance, now.date()).await.unwrap();
+    assert!(stored.iter().all(|r| r.delivered_at.is_none()));
+}
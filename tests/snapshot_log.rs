@@ -0,0 +1,209 @@
+//! Integration tests for the hash-chained snapshot log
+
+mod common;
+
+use alloy::network::EthereumWallet;
+use alloy::signers::local::PrivateKeySigner;
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::Utc;
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use common::TestApp;
+use indexmaker_backend::entities::{daily_prices, prelude::*, snapshot_log as log_entity};
+use indexmaker_backend::handlers::snapshots::get_snapshot_proof;
+use indexmaker_backend::services::snapshot_anchor::{self, AnchorError, AnchorTxStatus, SnapshotAnchorService};
+use indexmaker_backend::services::snapshot_log::{self, chain_hash};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_snapshot_chain_and_proof() {
+    let app = TestApp::spawn(
+        Router::new().route("/indexes/{index_id}/snapshot-proof/{date}", get(get_snapshot_proof)),
+    )
+    .await;
+    let now = Utc::now().naive_utc();
+    let date = app.seed_start + chrono::Duration::days(10);
+    let uri = format!("/indexes/{}/snapshot-proof/{}", SEED_INDEX_ID, date);
+
+    let (status, _) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let appended = snapshot_log::append_pending(&app.db, SEED_INDEX_ID, now).await.unwrap();
+    assert!(appended >= common::SEED_DAYS as usize);
+    // Records are logged once
+    assert_eq!(snapshot_log::append_pending(&app.db, SEED_INDEX_ID, now).await.unwrap(), 0);
+
+    let proof = app.get_json(&uri).await;
+    assert_eq!(proof["chainValid"], true);
+    let entries = proof["entries"].as_array().unwrap();
+    let price = entries.iter().find(|e| e["kind"] == "daily_price").unwrap();
+    assert_eq!(price["matchesCurrent"], true);
+
+    // Folding the path from the date's last entry reaches the head
+    let mut hash = entries.last().unwrap()["hash"].as_str().unwrap().to_string();
+    for payload_hash in proof["path"].as_array().unwrap() {
+        hash = chain_hash(Some(&hash), payload_hash.as_str().unwrap()).unwrap();
+    }
+    assert_eq!(proof["headHash"], hash);
+
+    // A retro-edited NAV no longer matches its snapshot
+    let record = DailyPrices::find()
+        .filter(daily_prices::Column::IndexId.eq(SEED_INDEX_ID.to_string()))
+        .filter(daily_prices::Column::Date.eq(date))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut record: daily_prices::ActiveModel = record.into();
+    record.price = Set(dec!(123.45));
+    record.update(&app.db).await.unwrap();
+
+    let proof = app.get_json(&uri).await;
+    let price = proof["entries"].as_array().unwrap().iter().find(|e| e["kind"] == "daily_price").unwrap().clone();
+    assert_eq!(price["matchesCurrent"], false);
+    assert_eq!(proof["chainValid"], true);
+
+    // Rewriting a logged entry breaks the chain
+    let first = SnapshotLog::find()
+        .filter(log_entity::Column::IndexId.eq(SEED_INDEX_ID))
+        .order_by_asc(log_entity::Column::Seq)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut first: log_entity::ActiveModel = first.into();
+    first.payload = Set(serde_json::json!({ "edited": true }));
+    first.update(&app.db).await.unwrap();
+    assert_eq!(app.get_json(&uri).await["chainValid"], false);
+
+    let (status, _) = app.get(&format!("/indexes/{}/snapshot-proof/yesterday", SEED_INDEX_ID)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_anchored_heads_are_referenced_by_proofs() {
+    let app = TestApp::spawn(
+        Router::new().route("/indexes/{index_id}/snapshot-proof/{date}", get(get_snapshot_proof)),
+    )
+    .await;
+    let now = Utc::now().naive_utc();
+    snapshot_log::append_pending(&app.db, SEED_INDEX_ID, now).await.unwrap();
+
+    let heads = snapshot_anchor::unanchored_heads(&app.db).await.unwrap();
+    let head = heads.iter().find(|h| h.index_id == SEED_INDEX_ID).unwrap().clone();
+    let tx_hash = format!("0x{}", "ab".repeat(32));
+    snapshot_anchor::mark_anchored(&app.db, head.id, &tx_hash, 1234, now).await.unwrap();
+    assert!(snapshot_anchor::unanchored_heads(&app.db)
+        .await
+        .unwrap()
+        .iter()
+        .all(|h| h.index_id != SEED_INDEX_ID));
+
+    let proof = app
+        .get_json(&format!("/indexes/{}/snapshot-proof/{}", SEED_INDEX_ID, app.seed_start))
+        .await;
+    assert_eq!(proof["anchor"]["seq"], head.seq);
+    assert_eq!(proof["anchor"]["hash"], head.hash);
+    assert_eq!(proof["anchor"]["txHash"], tx_hash);
+    assert_eq!(proof["anchor"]["blockNumber"], 1234);
+}
+
+/// Answer JSON-RPC `rpc_method` with `result`, or with `error` if given
+async fn mock_rpc(server: &MockServer, rpc_method: &str, result: serde_json::Value, error: Option<serde_json::Value>) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(move |request: &Request| {
+            let id = request.body_json::<serde_json::Value>().unwrap()["id"].clone();
+            let body = match &error {
+                Some(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                None => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            };
+            ResponseTemplate::new(200).set_body_json(body)
+        })
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_anchor_keeps_sent_tx_hash_when_receipt_fails() {
+    let app = TestApp::spawn(
+        Router::new().route("/indexes/{index_id}/snapshot-proof/{date}", get(get_snapshot_proof)),
+    )
+    .await;
+    let now = Utc::now().naive_utc();
+    snapshot_log::append_pending(&app.db, SEED_INDEX_ID, now).await.unwrap();
+    let head = snapshot_anchor::unanchored_heads(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|h| h.index_id == SEED_INDEX_ID)
+        .unwrap();
+
+    // The node takes the transaction but can't serve its receipt
+    let server = MockServer::start().await;
+    let tx_hash = format!("0x{}", "cd".repeat(32));
+    mock_rpc(&server, "eth_chainId", json!("0xa4b1"), None).await;
+    mock_rpc(&server, "eth_getTransactionCount", json!("0x0"), None).await;
+    mock_rpc(&server, "eth_estimateGas", json!("0x10000"), None).await;
+    mock_rpc(&server, "eth_gasPrice", json!("0x3b9aca00"), None).await;
+    mock_rpc(&server, "eth_maxPriorityFeePerGas", json!("0x1"), None).await;
+    mock_rpc(
+        &server,
+        "eth_feeHistory",
+        json!({ "oldestBlock": "0x1", "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"], "gasUsedRatio": [0.5], "reward": [["0x1"]] }),
+        None,
+    )
+    .await;
+    mock_rpc(&server, "eth_sendRawTransaction", json!(tx_hash), None).await;
+    mock_rpc(&server, "eth_getTransactionReceipt", json!(null), Some(json!({ "code": -32000, "message": "header not found" })))
+        .await;
+
+    let wallet = EthereumWallet::from(PrivateKeySigner::random());
+    let registry = format!("0x{}", "11".repeat(20));
+    let service = SnapshotAnchorService::new(&server.uri(), wallet, &registry, app.db.clone()).await.unwrap();
+    let result = service.anchor(&head, now).await;
+    assert!(matches!(result, Err(AnchorError::ProviderError(_))), "{:?}", result);
+
+    // Stored as sent: not sent again, and not referenced by proofs yet
+    let sent = SnapshotLog::find_by_id(head.id).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(sent.anchor_tx_hash.as_deref(), Some(tx_hash.as_str()));
+    assert_eq!(sent.anchored_at, None);
+    assert!(snapshot_anchor::unanchored_heads(&app.db).await.unwrap().iter().all(|h| h.id != head.id));
+    let submitted = snapshot_anchor::submitted_anchors(&app.db).await.unwrap();
+    assert_eq!(submitted.iter().map(|e| e.id).collect::<Vec<_>>(), vec![head.id]);
+    let proof = app.get_json(&format!("/indexes/{}/snapshot-proof/{}", SEED_INDEX_ID, app.seed_start)).await;
+    assert_eq!(proof["anchor"], json!(null));
+
+    // The next run finds the receipt
+    server.reset().await;
+    mock_rpc(&server, "eth_getTransactionReceipt", receipt(&tx_hash, 4321), None).await;
+    assert_eq!(service.reconcile(&sent, now).await.unwrap(), AnchorTxStatus::Confirmed(4321));
+    assert!(snapshot_anchor::submitted_anchors(&app.db).await.unwrap().is_empty());
+    let proof = app.get_json(&format!("/indexes/{}/snapshot-proof/{}", SEED_INDEX_ID, app.seed_start)).await;
+    assert_eq!(proof["anchor"]["txHash"], tx_hash);
+    assert_eq!(proof["anchor"]["blockNumber"], 4321);
+}
+
+/// A successful receipt of `tx_hash`, mined in `block_number`
+fn receipt(tx_hash: &str, block_number: u64) -> serde_json::Value {
+    json!({
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "blockHash": format!("0x{}", "ef".repeat(32)),
+        "blockNumber": format!("{:#x}", block_number),
+        "from": format!("0x{}", "22".repeat(20)),
+        "to": format!("0x{}", "11".repeat(20)),
+        "cumulativeGasUsed": "0x10000",
+        "gasUsed": "0x10000",
+        "effectiveGasPrice": "0x3b9aca00",
+        "contractAddress": null,
+        "logs": [],
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "type": "0x2",
+        "status": "0x1",
+    })
+}
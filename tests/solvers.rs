@@ -0,0 +1,116 @@
+//! Integration tests for the solver registry
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use jsonwebtoken::{EncodingKey, Header};
+use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{TestApp, TEST_JWT_SECRET};
+use indexmaker_backend::entities::prelude::*;
+use indexmaker_backend::handlers::solvers::{list_solvers, register_solver, solver_heartbeat};
+use indexmaker_backend::services::auth::Claims;
+
+const SOLVER_A: &str = "0x00000000000000000000000000000000000000AA";
+const SOLVER_B: &str = "0x00000000000000000000000000000000000000bb";
+
+fn session_token(address: &str) -> String {
+    let now = Utc::now().timestamp();
+    let claims = Claims { sub: address.to_string(), iat: now, exp: now + 600, iss: "indexmaker-backend".to_string() };
+    jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+async fn post_json(app: &TestApp, uri: &str, body: Value, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(Method::POST).uri(uri).header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = app.router.clone().oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_register_heartbeat_and_list() {
+    let routes = Router::new()
+        .route("/api/solvers", get(list_solvers))
+        .route("/api/solvers/register", post(register_solver))
+        .route("/api/solvers/{id}/heartbeat", post(solver_heartbeat));
+    let app = TestApp::spawn(routes).await;
+    let (token_a, token_b) = (session_token(SOLVER_A), session_token(SOLVER_B));
+
+    let registration = json!({"name": "Alpha", "assets": ["btc", "ETH"], "exchanges": ["Bitget"], "version": "1.0.0"});
+    let (status, _) = post_json(&app, "/api/solvers/register", registration.clone(), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = post_json(&app, "/api/solvers/register", json!({"name": "Alpha", "assets": [], "exchanges": ["bitget"]}), Some(&token_a)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, alpha) = post_json(&app, "/api/solvers/register", registration, Some(&token_a)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(alpha["address"], SOLVER_A.to_lowercase());
+    assert_eq!(alpha["assets"], json!(["BTC", "ETH"]));
+    assert_eq!(alpha["exchanges"], json!(["bitget"]));
+    assert_eq!(alpha["online"], true);
+    let alpha_id = alpha["id"].as_i64().unwrap() as i32;
+
+    // Registering again from the same wallet updates the solver
+    let (_, updated) = post_json(
+        &app,
+        "/api/solvers/register",
+        json!({"name": "Alpha", "assets": ["BTC", "SOL"], "exchanges": ["bitget", "binance"]}),
+        Some(&token_a),
+    )
+    .await;
+    assert_eq!(updated["id"], alpha["id"]);
+    assert_eq!(updated["assets"], json!(["BTC", "SOL"]));
+
+    let (_, beta) = post_json(
+        &app,
+        "/api/solvers/register",
+        json!({"name": "Beta", "assets": ["ETH"], "exchanges": ["binance"], "endpoint": "https://beta.example"}),
+        Some(&token_b),
+    )
+    .await;
+
+    let heartbeat = format!("/api/solvers/{}/heartbeat", alpha_id);
+    let (status, _) = post_json(&app, &heartbeat, Value::Null, Some(&token_b)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = post_json(&app, "/api/solvers/999999/heartbeat", Value::Null, Some(&token_a)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Beta's last heartbeat is long gone; Alpha's is refreshed
+    let mut stale = Solvers::find_by_id(beta["id"].as_i64().unwrap() as i32)
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_active_model();
+    stale.last_heartbeat_at = Set(Some(Utc::now().naive_utc() - Duration::hours(1)));
+    stale.update(&app.db).await.unwrap();
+    let (status, alpha) = post_json(&app, &heartbeat, Value::Null, Some(&token_a)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(alpha["online"], true);
+
+    let all = app.get_json("/api/solvers").await;
+    let names: Vec<&str> = all.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Alpha", "Beta"]);
+    assert_eq!(all[1]["online"], false);
+    assert_eq!(all[1]["endpoint"], "https://beta.example");
+
+    let binance_eth = app.get_json("/api/solvers?asset=eth&exchange=binance").await;
+    assert_eq!(binance_eth.as_array().unwrap().len(), 1);
+    assert_eq!(binance_eth[0]["name"], "Beta");
+    let online = app.get_json("/api/solvers?online=true").await;
+    assert_eq!(online.as_array().unwrap().len(), 1);
+    assert_eq!(online[0]["name"], "Alpha");
+}
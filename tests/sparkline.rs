@@ -0,0 +1,32 @@
+//! Integration tests for index sparklines
+
+mod common;
+
+use axum::{http::StatusCode, routing::get, Router};
+use chrono::Duration;
+
+use common::{TestApp, SEED_DAYS};
+use indexmaker_backend::handlers::index::get_index_sparkline;
+use indexmaker_backend::services::pricing_time;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+
+#[tokio::test]
+async fn test_sparkline_downsamples_daily_prices() {
+    let app = TestApp::spawn(Router::new().route("/indexes/{index_id}/sparkline", get(get_index_sparkline))).await;
+    let today = app.seed_start + Duration::days(SEED_DAYS - 1);
+
+    let body = app.get_json(&format!("/indexes/{}/sparkline?days=30&points=10", SEED_INDEX_ID)).await;
+    let points = body["points"].as_array().unwrap();
+    assert_eq!(points.len(), 10);
+    assert_eq!(points[0][0], pricing_time::cutoff_timestamp(today - Duration::days(29)));
+    assert_eq!(points[9][0], pricing_time::cutoff_timestamp(today));
+
+    // Fewer days than points: every day
+    let body = app.get_json(&format!("/indexes/{}/sparkline?days=7", SEED_INDEX_ID)).await;
+    assert_eq!(body["points"].as_array().unwrap().len(), 7);
+
+    let (status, _) = app.get(&format!("/indexes/{}/sparkline?points=2", SEED_INDEX_ID)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app.get("/indexes/-1/sparkline").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
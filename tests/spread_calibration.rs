@@ -0,0 +1,55 @@
+//! Integration tests for observed-spread fees
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::prelude::*;
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+use indexmaker_backend::services::spread_calibration::{held_pairs, record, spread_for, MIN_OBSERVATIONS, TRAILING_DAYS};
+
+#[tokio::test]
+async fn test_observed_spread_replaces_static_figure() {
+    let app = TestApp::spawn(Router::new()).await;
+    let today = Utc::now().date_naive();
+    let now = Utc::now().naive_utc();
+
+    let pairs = held_pairs(&app.db, SEED_INDEX_ID).await.unwrap();
+    let mut names: Vec<String> = pairs.iter().map(|p| p.trading_pair()).collect();
+    names.sort();
+    assert_eq!(names, ["BTCUSDC", "ETHUSDC", "SOLUSDC"]);
+    assert!(pairs.iter().all(|p| p.exchange == "binance"));
+
+    let index = IndexMetadata::find_by_id(SEED_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    assert_eq!(spread_for(&app.db, &index, today).await.unwrap(), Some(dec!(0.0005)));
+
+    let mut opted_in = index.into_active_model();
+    opted_in.use_observed_spread = Set(true);
+    let index = opted_in.update(&app.db).await.unwrap();
+
+    // Too few observations yet
+    record(&app.db, &pairs[0], today - Duration::days(1), dec!(99), dec!(101), dec!(0.002), now).await.unwrap();
+    assert_eq!(spread_for(&app.db, &index, today).await.unwrap(), Some(dec!(0.0005)));
+
+    // BTC at 0.002 for two days, ETH and SOL at 0.001
+    record(&app.db, &pairs[0], today - Duration::days(2), dec!(99), dec!(101), dec!(0.002), now).await.unwrap();
+    for pair in &pairs[1..] {
+        record(&app.db, pair, today - Duration::days(1), dec!(99), dec!(101), dec!(0.001), now).await.unwrap();
+        record(&app.db, pair, today - Duration::days(2), dec!(99), dec!(101), dec!(0.001), now).await.unwrap();
+    }
+    assert!(2 * pairs.len() >= MIN_OBSERVATIONS);
+    let expected = (dec!(0.002) * dec!(2) + dec!(0.001) * dec!(4)) / dec!(6);
+    assert_eq!(spread_for(&app.db, &index, today).await.unwrap(), Some(expected.round_dp(8)));
+
+    // Re-recording a day replaces it
+    record(&app.db, &pairs[0], today - Duration::days(1), dec!(99), dec!(101), dec!(0.001), now).await.unwrap();
+    assert_eq!(SpreadObservations::find().all(&app.db).await.unwrap().len(), 6);
+
+    // Observations outside the trailing window don't count
+    let later = today + Duration::days(TRAILING_DAYS + 2);
+    assert_eq!(spread_for(&app.db, &index, later).await.unwrap(), Some(dec!(0.0005)));
+}
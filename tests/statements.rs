@@ -0,0 +1,122 @@
+//! Integration tests for GET /me/statements
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::{Datelike, Utc};
+use http_body_util::BodyExt;
+use jsonwebtoken::{EncodingKey, Header};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use tower::ServiceExt;
+
+use common::{TestApp, TEST_JWT_SECRET};
+use indexmaker_backend::entities::{blockchain_events, prelude::*};
+use indexmaker_backend::handlers::statements::get_my_statement;
+use indexmaker_backend::services::auth::Claims;
+
+const WALLET: &str = "0x00000000000000000000000000000000000a11ce";
+
+fn session_token(address: &str) -> String {
+    let now = Utc::now().timestamp();
+    let claims = Claims { sub: address.to_string(), iat: now, exp: now + 600, iss: "indexmaker-backend".to_string() };
+    jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(TEST_JWT_SECRET.as_bytes())).unwrap()
+}
+
+async fn get_statement(app: &TestApp, query: &str, token: Option<&str>) -> (StatusCode, Option<String>, String) {
+    let mut request = Request::builder().uri(format!("/me/statements{}", query));
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let response = app.router.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, content_type, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_statement_of_signed_in_wallet() {
+    let app = TestApp::spawn(Router::new().route("/me/statements", get(get_my_statement))).await;
+    let token = session_token(WALLET);
+
+    let mints = BlockchainEvents::find()
+        .filter(blockchain_events::Column::UserAddress.eq(WALLET))
+        .filter(blockchain_events::Column::EventType.eq("mint"))
+        .all(&app.db)
+        .await
+        .unwrap();
+    let year = mints[0].timestamp.unwrap().year();
+    let in_year = mints.iter().filter(|m| m.timestamp.unwrap().year() == year).count();
+
+    let (status, _, body) = get_statement(&app, &format!("?year={}", year), Some(&token)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let statement: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(statement["address"], WALLET);
+    assert_eq!(statement["events"].as_array().unwrap().len(), in_year);
+    assert_eq!(statement["events"][0]["eventType"], "mint");
+    assert!(statement["events"][0]["indexPrice"].is_string());
+    assert_eq!(statement["totals"]["realizedPnl"], "0");
+    assert_eq!(statement["excluded"], serde_json::json!([]));
+
+    let (status, content_type, csv) = get_statement(&app, &format!("?year={}&format=csv", year), Some(&token)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/csv"));
+    assert_eq!(csv.lines().count(), in_year + 1);
+
+    assert_eq!(get_statement(&app, "", None).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(get_statement(&app, "?year=1999", Some(&token)).await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(get_statement(&app, "?format=pdf", Some(&token)).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_statement_lists_unreadable_events_as_excluded() {
+    let app = TestApp::spawn(Router::new().route("/me/statements", get(get_my_statement))).await;
+    let token = session_token(WALLET);
+
+    let mint = BlockchainEvents::find()
+        .filter(blockchain_events::Column::UserAddress.eq(WALLET))
+        .filter(blockchain_events::Column::EventType.eq("mint"))
+        .one(&app.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let year = mint.timestamp.unwrap().year();
+    let (_, _, body) = get_statement(&app, &format!("?year={}", year), Some(&token)).await;
+    let before: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+    // A burn with neither a stored nor a raw quantity
+    blockchain_events::ActiveModel {
+        tx_hash: Set("0xunreadable".to_string()),
+        block_number: Set(mint.block_number + 1),
+        log_index: Set(0),
+        event_type: Set("burn".to_string()),
+        contract_address: Set(mint.contract_address.clone()),
+        network: Set(mint.network.clone()),
+        user_address: Set(Some(WALLET.to_string())),
+        amount: Set(mint.amount),
+        quantity: Set(None),
+        timestamp: Set(mint.timestamp),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let (status, _, body) = get_statement(&app, &format!("?year={}", year), Some(&token)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let statement: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let excluded = statement["excluded"].as_array().unwrap();
+    assert_eq!(excluded.len(), 1);
+    assert_eq!(excluded[0]["txHash"], "0xunreadable");
+    assert_eq!(excluded[0]["eventType"], "burn");
+    assert_eq!(excluded[0]["reason"], "unreadable quantity or amount");
+
+    // Left out of the events and positions, not counted as a zero burn
+    assert_eq!(statement["events"], before["events"]);
+    assert_eq!(statement["indexes"], before["indexes"]);
+}
@@ -0,0 +1,66 @@
+//! Integration tests for the protocol statistics
+
+mod common;
+
+use axum::{routing::get, Router};
+use chrono::{Duration, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+
+use common::TestApp;
+use indexmaker_backend::entities::{blockchain_events, coins, prelude::*};
+use indexmaker_backend::handlers::stats::get_stats;
+use indexmaker_backend::services::index_deployments::DEFAULT_NETWORK;
+use indexmaker_backend::services::protocol_stats::compute;
+use indexmaker_backend::services::seed::SEED_INDEX_ADDRESS;
+
+async fn insert_event(app: &TestApp, n: i64, event_type: &str, user: &str, amount: Decimal, quantity: Decimal) {
+    blockchain_events::ActiveModel {
+        tx_hash: Set(format!("0x{:064x}", 0x57a7_0000 + n)),
+        block_number: Set(30_000_000),
+        log_index: Set(0),
+        event_type: Set(event_type.to_string()),
+        contract_address: Set(SEED_INDEX_ADDRESS.to_string()),
+        network: Set(DEFAULT_NETWORK.to_string()),
+        user_address: Set(Some(user.to_string())),
+        amount: Set(Some(amount)),
+        quantity: Set(Some(quantity)),
+        timestamp: Set(Some((Utc::now() - Duration::hours(1)).into())),
+        ..Default::default()
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_protocol_stats() {
+    let app = TestApp::spawn(Router::new().route("/stats", get(get_stats))).await;
+
+    let before = compute(&app.db).await.unwrap();
+    assert!(before.total_tvl_usd > 0.0);
+    assert_eq!(before.indexes as u64, IndexMetadata::find().count(&app.db).await.unwrap());
+    let tracked = Coins::find()
+        .filter(coins::Column::Active.eq(true))
+        .filter(coins::Column::Deactivated.eq(false))
+        .count(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(before.tracked_coins, tracked);
+
+    // A new holder mints and redeems part; another mints and redeems everything
+    insert_event(&app, 1, "mint", "0x00000000000000000000000000000000000b0b01", Decimal::from(500), Decimal::from(5)).await;
+    insert_event(&app, 2, "burn", "0x00000000000000000000000000000000000b0b01", Decimal::from(200), Decimal::from(2)).await;
+    insert_event(&app, 3, "mint", "0x00000000000000000000000000000000000b0b02", Decimal::from(100), Decimal::from(1)).await;
+    insert_event(&app, 4, "withdraw", "0x00000000000000000000000000000000000B0B02", Decimal::from(100), Decimal::from(1)).await;
+
+    let after = compute(&app.db).await.unwrap();
+    assert_eq!(after.unique_holders, before.unique_holders + 1);
+    assert_eq!((after.mint_volume_24h_usd - before.mint_volume_24h_usd).to_i64(), Some(600));
+    assert_eq!((after.burn_volume_24h_usd - before.burn_volume_24h_usd).to_i64(), Some(300));
+
+    let body = app.get_json("/stats").await;
+    assert_eq!(body["trackedCoins"], tracked);
+    assert!(body["totalTvlUsd"].as_f64().unwrap() > 0.0);
+    assert!(body["asOf"].is_string());
+}
@@ -0,0 +1,71 @@
+//! Integration tests for transaction dry runs against a mocked JSON-RPC node
+
+use alloy::primitives::{Address, Bytes};
+use alloy::providers::{ProviderBuilder, RootProvider};
+use alloy::sol_types::{Revert, SolError};
+use alloy::transports::http::{Client, Http};
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use indexmaker_backend::services::tx_simulator::{simulate, Simulation, SimulationError};
+
+/// Answer JSON-RPC `rpc_method` with `result`, or with `error` if given
+async fn mock_rpc(server: &MockServer, rpc_method: &str, result: serde_json::Value, error: Option<serde_json::Value>) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(move |request: &Request| {
+            let id = request.body_json::<serde_json::Value>().unwrap()["id"].clone();
+            let body = match &error {
+                Some(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                None => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            };
+            ResponseTemplate::new(200).set_body_json(body)
+        })
+        .mount(server)
+        .await;
+}
+
+fn provider(server: &MockServer) -> RootProvider<Http<Client>> {
+    ProviderBuilder::new().on_http(server.uri().parse().unwrap())
+}
+
+#[tokio::test]
+async fn test_simulation_succeeds() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_blockNumber", json!("0x1c9c380"), None).await;
+    mock_rpc(&server, "eth_call", json!("0x"), None).await;
+    mock_rpc(&server, "eth_estimateGas", json!("0x5208"), None).await;
+
+    let simulation = simulate(&provider(&server), Address::repeat_byte(1), Address::repeat_byte(2), Bytes::from(vec![1, 2, 3, 4]))
+        .await
+        .unwrap();
+    assert_eq!(simulation, Simulation { block_number: 30_000_000, gas_estimate: 21_000 });
+}
+
+#[tokio::test]
+async fn test_simulation_decodes_reverts() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_blockNumber", json!("0x10"), None).await;
+    let data = format!("0x{}", hex::encode(Revert::from("weights must sum to 10000").abi_encode()));
+    mock_rpc(
+        &server,
+        "eth_call",
+        json!(null),
+        Some(json!({ "code": 3, "message": "execution reverted: weights must sum to 10000", "data": data })),
+    )
+    .await;
+
+    let result = simulate(&provider(&server), Address::repeat_byte(1), Address::repeat_byte(2), Bytes::new()).await;
+    assert_eq!(result, Err(SimulationError::Reverted("revert: weights must sum to 10000".to_string())));
+}
+
+#[tokio::test]
+async fn test_simulation_reports_provider_errors() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_blockNumber", json!("0x10"), None).await;
+    mock_rpc(&server, "eth_call", json!(null), Some(json!({ "code": -32000, "message": "header not found" }))).await;
+
+    let result = simulate(&provider(&server), Address::repeat_byte(1), Address::repeat_byte(2), Bytes::new()).await;
+    assert!(matches!(result, Err(SimulationError::Provider(_))));
+}
@@ -0,0 +1,45 @@
+//! Integration tests for the signer wallet balance checks and /readyz
+
+mod common;
+
+use alloy::primitives::Address;
+use axum::{routing::get, Router};
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+
+use common::TestApp;
+use indexmaker_backend::handlers::health::readyz;
+use indexmaker_backend::services::wallet_balances;
+
+#[tokio::test]
+async fn test_readyz_reports_latest_wallet_balances() {
+    let app = TestApp::spawn(Router::new().route("/readyz", get(readyz))).await;
+    let wallet = Address::repeat_byte(7);
+    let now = Utc::now().naive_utc();
+
+    let body = app.get_json("/readyz").await;
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["lowWalletBalance"], false);
+    assert_eq!(body["wallets"].as_array().unwrap().len(), 0);
+
+    // Re-checks on the same day update the day's row
+    wallet_balances::record(&app.db, "arbitrum", wallet, dec!(0.5), dec!(0.05), now - Duration::minutes(10)).await.unwrap();
+    let low = wallet_balances::record(&app.db, "arbitrum", wallet, dec!(0.01), dec!(0.05), now).await.unwrap();
+    assert!(low.low);
+    let base = wallet_balances::record(&app.db, "base", wallet, dec!(1), dec!(0.05), now).await.unwrap();
+    assert!(!base.low);
+    // Too old to be reported
+    wallet_balances::record(&app.db, "orbit", wallet, dec!(0), dec!(0.05), now - Duration::days(5)).await.unwrap();
+
+    let latest = wallet_balances::latest(&app.db, now.date()).await.unwrap();
+    let networks: Vec<_> = latest.iter().map(|c| (c.network.as_str(), c.balance_eth)).collect();
+    assert_eq!(networks, vec![("arbitrum", dec!(0.01)), ("base", dec!(1))]);
+
+    let body = app.get_json("/readyz").await;
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["database"], true);
+    assert_eq!(body["lowWalletBalance"], true);
+    assert_eq!(body["wallets"][0]["network"], "arbitrum");
+    assert_eq!(body["wallets"][0]["low"], true);
+    assert_eq!(body["wallets"][0]["address"], wallet.to_string());
+}
@@ -0,0 +1,98 @@
+//! Integration tests for coin yield rates and their accrual
+
+mod common;
+
+use axum::Router;
+use chrono::{Duration, Utc};
+use rust_decimal_macros::dec;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::TestApp;
+use indexmaker_backend::entities::{index_metadata, prelude::*};
+use indexmaker_backend::services::seed::SEED_INDEX_ID;
+use indexmaker_backend::services::yield_accrual::{self, sources, DefiLlamaYields, YieldAccrual, YieldSyncConfig};
+
+#[tokio::test]
+async fn test_sync_keeps_manual_rates() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+
+    let defillama = MockServer::start().await;
+    for (pool, apy) in [("pool-eth", 3.1), ("pool-sol", 7.25)] {
+        Mock::given(method("GET"))
+            .and(path(format!("/chart/{}", pool)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "success",
+                "data": [
+                    { "timestamp": "2026-01-01T23:01:38.617Z", "apy": 2.0 },
+                    { "timestamp": "2026-01-02T23:01:38.617Z", "apy": apy },
+                ]
+            })))
+            .mount(&defillama)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/chart/pool-gone"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&defillama)
+        .await;
+
+    yield_accrual::set_manual(&app.db, "solana", dec!(6), now).await.unwrap();
+    assert!(yield_accrual::set_manual(&app.db, "solana", dec!(-1), now).await.is_err());
+
+    let config = YieldSyncConfig {
+        pools: vec![
+            ("ethereum".to_string(), "pool-eth".to_string()),
+            ("solana".to_string(), "pool-sol".to_string()),
+            ("cardano".to_string(), "pool-gone".to_string()),
+        ],
+        defillama_url: defillama.uri(),
+    };
+    let summary = yield_accrual::sync(&app.db, &DefiLlamaYields::new(&defillama.uri()), &config, now)
+        .await
+        .unwrap();
+    assert_eq!((summary.updated, summary.skipped, summary.failed), (1, 1, 1));
+
+    let rates: Vec<_> = yield_accrual::list(&app.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|rate| (rate.coin_id, rate.apy_pct, rate.source))
+        .collect();
+    assert_eq!(
+        rates,
+        vec![
+            ("ethereum".to_string(), dec!(3.1), sources::DEFILLAMA.to_string()),
+            ("solana".to_string(), dec!(6), sources::MANUAL.to_string()),
+        ]
+    );
+
+    assert!(yield_accrual::remove(&app.db, "solana").await.unwrap());
+    assert!(!yield_accrual::remove(&app.db, "solana").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_accrual_only_for_opted_in_indexes() {
+    let app = TestApp::spawn(Router::new()).await;
+    let now = Utc::now().naive_utc();
+    let since = now.date() - Duration::days(365);
+    let coin_ids = vec!["ethereum".to_string(), "bitcoin".to_string()];
+
+    yield_accrual::set_manual(&app.db, "ethereum", dec!(4), now).await.unwrap();
+
+    let accrual = YieldAccrual::for_index(&app.db, SEED_INDEX_ID, &coin_ids, since).await.unwrap();
+    assert_eq!(accrual.quantity("ethereum", dec!(10), now.date()), dec!(10));
+
+    let index = IndexMetadata::find_by_id(SEED_INDEX_ID).one(&app.db).await.unwrap().unwrap();
+    let mut index: index_metadata::ActiveModel = index.into();
+    index.accrue_yield = Set(true);
+    index.update(&app.db).await.unwrap();
+
+    let accrual = YieldAccrual::for_index(&app.db, SEED_INDEX_ID, &coin_ids, since).await.unwrap();
+    assert_eq!(accrual.quantity("ethereum", dec!(10), now.date()).round_dp(9), dec!(10.4));
+    assert_eq!(accrual.quantity("bitcoin", dec!(10), now.date()), dec!(10));
+}